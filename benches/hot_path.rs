@@ -0,0 +1,122 @@
+//! Benchmarks for the simulation hot path.
+//!
+//! Covers AMM swaps, the cascading liquidation scan at several vault counts,
+//! a single `Scenario::step` on a warmed-up scenario, and a full 1000-block
+//! stress run. Run with `cargo bench`.
+
+use std::hint::black_box;
+use std::time::Instant;
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use zai_sim::amm::Amm;
+use zai_sim::cdp::{CdpConfig, VaultRegistry};
+use zai_sim::liquidation::{LiquidationConfig, LiquidationEngine};
+use zai_sim::scenario::{Scenario, ScenarioConfig};
+use zai_sim::scenarios::{run_stress, ScenarioId};
+
+/// Blocks run before `Scenario::step` is measured, so agents and the TWAP
+/// are past their start-up transient
+const WARMUP_BLOCKS: usize = 200;
+
+fn amm() -> Amm {
+    let mut amm = Amm::new(100_000.0, 5_000_000.0, 0.003);
+    for block in 1..=50 {
+        amm.record_price(block);
+    }
+    amm
+}
+
+fn bench_amm_swaps(c: &mut Criterion) {
+    let mut group = c.benchmark_group("amm");
+    group.bench_function("swap_zec_for_zai", |b| {
+        b.iter_batched(
+            amm,
+            |mut amm| amm.swap_zec_for_zai(black_box(10.0), 51),
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("swap_zai_for_zec", |b| {
+        b.iter_batched(
+            amm,
+            |mut amm| amm.swap_zai_for_zec(black_box(500.0), 51),
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+/// `vaults` vaults opened at CR 2.0 and knocked down to CR 1.4, below the
+/// default 1.5 minimum.
+fn underwater_vaults(vaults: usize) -> (Amm, VaultRegistry, LiquidationEngine) {
+    let amm = amm();
+    let mut registry = VaultRegistry::new(CdpConfig {
+        stability_fee_rate: 0.0,
+        ..CdpConfig::default()
+    });
+    for i in 0..vaults {
+        let id = registry
+            .open_vault(&format!("vault{}", i), 40.0, 1000.0, 50, &amm)
+            .unwrap();
+        registry.vaults.get_mut(&id).unwrap().collateral_zec = 28.0;
+        registry.reindex(id);
+    }
+    let engine = LiquidationEngine::new(LiquidationConfig::default());
+    (amm, registry, engine)
+}
+
+fn bench_cascading_liquidation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("cascading_spot_liquidate");
+    for vaults in [10, 100, 1000] {
+        group.bench_with_input(BenchmarkId::from_parameter(vaults), &vaults, |b, &n| {
+            b.iter_batched(
+                || underwater_vaults(n),
+                |(mut amm, mut registry, mut engine)| {
+                    engine.cascading_spot_liquidate(&mut registry, &mut amm, 51)
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn warmed_up(config: &ScenarioConfig) -> Scenario {
+    run_stress(ScenarioId::SteadyState, config, WARMUP_BLOCKS, 42)
+}
+
+fn bench_scenario_step(c: &mut Criterion) {
+    let config = ScenarioConfig::default();
+    c.bench_function("scenario_step", |b| {
+        // A fresh scenario per sample, so the metrics history stays short
+        b.iter_custom(|iters| {
+            let mut scenario = warmed_up(&config);
+            let price = scenario.metrics.last().unwrap().external_price;
+            let start = Instant::now();
+            for i in 0..iters {
+                scenario.step(WARMUP_BLOCKS as u64 + i + 1, black_box(price));
+            }
+            start.elapsed()
+        })
+    });
+}
+
+fn bench_full_run(c: &mut Criterion) {
+    let config = ScenarioConfig::default();
+    let mut group = c.benchmark_group("full_run");
+    group.sample_size(10);
+    for id in [ScenarioId::SteadyState, ScenarioId::BlackThursday] {
+        group.bench_function(BenchmarkId::new("1000_blocks", id.name()), |b| {
+            b.iter(|| run_stress(id, &config, 1000, 42))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_amm_swaps,
+    bench_cascading_liquidation,
+    bench_scenario_step,
+    bench_full_run
+);
+criterion_main!(benches);
@@ -0,0 +1,17 @@
+//! Records the commit being built, for the `manifest.json` of each run.
+
+use std::process::Command;
+
+fn main() {
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs");
+    let hash = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .and_then(|o| String::from_utf8(o.stdout).ok());
+    if let Some(hash) = hash {
+        println!("cargo:rustc-env=ZAI_SIM_GIT_HASH={}", hash.trim());
+    }
+}
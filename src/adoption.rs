@@ -0,0 +1,105 @@
+//! Demand adoption curve and user growth.
+//!
+//! A scenario's demand agents are normally a fixed roster. With an adoption
+//! model the roster follows a Bass diffusion curve instead: users join at
+//! `(innovation_rate + imitation_rate * users / market_size) * (market_size
+//! - users)` per block, the logistic curve when `innovation_rate` is zero.
+//! Joining is scaled down as the smoothed peg deviation approaches
+//! `peg_tolerance_pct` and stops beyond it, where users start to leave at
+//! `churn_per_pct` of the population per block per percentage point over
+//! the tolerance, on top of `base_churn_rate`.
+//!
+//! The population is fractional; the scenario spawns or removes demand
+//! agents to match its rounded value. Newest users leave first, selling
+//! their ZAI on the way out, so a poor peg feeds back into more selling and
+//! a good one into more buyers over long horizons.
+
+use serde::{Deserialize, Serialize};
+
+use crate::agents::DemandAgentConfig;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdoptionConfig {
+    /// Users the market could eventually hold
+    pub market_size: f64,
+    /// Per-block chance a non-user adopts on their own (Bass `p`)
+    pub innovation_rate: f64,
+    /// Per-block adoption per non-user at full penetration (Bass `q`)
+    pub imitation_rate: f64,
+    /// Fraction of users leaving per block regardless of the peg
+    pub base_churn_rate: f64,
+    /// Smoothed peg deviation (%) beyond which users stop joining and
+    /// start leaving
+    pub peg_tolerance_pct: f64,
+    /// Extra fraction of users leaving per block per % over the tolerance
+    pub churn_per_pct: f64,
+    /// Span of the peg deviation's moving average (blocks)
+    pub peg_memory_blocks: u64,
+    /// Configuration given to each user who joins
+    pub new_agent: DemandAgentConfig,
+}
+
+impl Default for AdoptionConfig {
+    fn default() -> Self {
+        AdoptionConfig {
+            market_size: 100.0,
+            innovation_rate: 0.00001,
+            imitation_rate: 0.0005,
+            base_churn_rate: 0.00001,
+            peg_tolerance_pct: 2.0,
+            churn_per_pct: 0.0002,
+            peg_memory_blocks: 144, // ~3 hours
+            new_agent: DemandAgentConfig::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Adoption {
+    pub config: AdoptionConfig,
+    /// Fractional user population; `None` until seeded from the roster
+    pub users: Option<f64>,
+    /// Moving average of the absolute peg deviation (%)
+    pub peg_deviation_pct: f64,
+    pub total_joined: u64,
+    pub total_churned: u64,
+    /// ZAI sold on the AMM by users leaving
+    pub exit_zai_sold: f64,
+}
+
+impl Adoption {
+    pub fn new(config: AdoptionConfig) -> Self {
+        Adoption {
+            config,
+            users: None,
+            peg_deviation_pct: 0.0,
+            total_joined: 0,
+            total_churned: 0,
+            exit_zai_sold: 0.0,
+        }
+    }
+
+    /// Advance one block given the current peg deviation (%) and the
+    /// demand roster size, and return the roster size to aim for.
+    pub fn step(&mut self, deviation_pct: f64, roster: usize) -> usize {
+        let c = &self.config;
+        let alpha = 2.0 / (c.peg_memory_blocks as f64 + 1.0);
+        let deviation = if deviation_pct.is_finite() {
+            deviation_pct.abs()
+        } else {
+            c.peg_tolerance_pct + 100.0
+        };
+        self.peg_deviation_pct += alpha * (deviation - self.peg_deviation_pct);
+
+        let users = self.users.get_or_insert(roster as f64);
+        let market = c.market_size.max(0.0);
+        let quality = (1.0 - self.peg_deviation_pct / c.peg_tolerance_pct.max(1e-9)).max(0.0);
+        let joining = (c.innovation_rate + c.imitation_rate * *users / market.max(1e-9))
+            * (market - *users).max(0.0)
+            * quality;
+        let over = (self.peg_deviation_pct - c.peg_tolerance_pct).max(0.0);
+        let leaving = *users * (c.base_churn_rate + c.churn_per_pct * over).min(1.0);
+        *users = (*users + joining - leaving).max(0.0);
+        users.round() as usize
+    }
+}
@@ -0,0 +1,322 @@
+//! Opt-in per-agent time series.
+//!
+//! `BlockMetrics` only aggregates arber totals. When
+//! `ScenarioConfig::record_agent_metrics` is set, every agent's balances,
+//! mark-to-market value, PnL and action count are recorded each block, keyed
+//! by a stable agent id (`arber_0`, `il_lp_2`, ...), so individual agents can
+//! be compared over a run.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::agents::AgentAction;
+use crate::error::ZaiSimError;
+use crate::lending::LendingAsset;
+use crate::scenario::Scenario;
+
+/// One agent's state at the end of a block.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AgentSample {
+    pub block: u64,
+    pub agent_id: String,
+    pub kind: String,
+    pub zec_balance: f64,
+    pub zai_balance: f64,
+    /// AMM LP shares held (LP agents only)
+    pub lp_shares: f64,
+    /// Holdings marked at AMM spot, net of vault and lending-market debt (ZAI)
+    pub value_zai: f64,
+    /// `value_zai` minus the value at the start of the run
+    pub pnl_zai: f64,
+    /// Non-trivial actions taken this block
+    pub actions: u32,
+    /// Kind of the last action this block ("none" if idle)
+    pub last_action: String,
+}
+
+/// Balances and value of one agent, before PnL and actions are attached.
+struct AgentState {
+    id: String,
+    kind: &'static str,
+    zec: f64,
+    zai: f64,
+    shares: f64,
+    value: f64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct AgentMetricsCollector {
+    pub samples: Vec<AgentSample>,
+    baseline: HashMap<String, f64>,
+    block_actions: HashMap<String, (u32, String)>,
+}
+
+/// Short label for an action, used in the `last_action` column.
+pub fn action_label(action: &AgentAction) -> &'static str {
+    match action {
+        AgentAction::None => "none",
+        AgentAction::BuyZec { .. } => "buy_zec",
+        AgentAction::SellZec { .. } => "sell_zec",
+        AgentAction::BuyZai { .. } => "buy_zai",
+        AgentAction::PanicSellZai { .. } => "panic_sell_zai",
+        AgentAction::MinerSell { .. } => "miner_sell",
+        AgentAction::CdpAction { .. } => "cdp_action",
+        AgentAction::LpAdd { .. } => "lp_add",
+        AgentAction::LpRemove { .. } => "lp_remove",
+        AgentAction::AttackSwap { .. } => "attack_swap",
+        AgentAction::Queued { .. } => "queued",
+        AgentAction::Redeem { .. } => "redeem",
+        AgentAction::Borrow { .. } => "borrow",
+        AgentAction::Repay { .. } => "repay",
+        AgentAction::SavingsDeposit { .. } => "savings_deposit",
+        AgentAction::SavingsWithdraw { .. } => "savings_withdraw",
+    }
+}
+
+impl AgentMetricsCollector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count an action taken by `agent_id` in the current block.
+    pub fn note(&mut self, agent_id: &str, action: &AgentAction) {
+        if matches!(action, AgentAction::None) {
+            return;
+        }
+        let entry = self
+            .block_actions
+            .entry(agent_id.to_string())
+            .or_insert((0, String::new()));
+        entry.0 += 1;
+        entry.1 = action_label(action).to_string();
+    }
+
+    /// Fix every agent's starting value; PnL is measured against it.
+    pub fn set_baseline(&mut self, scenario: &Scenario) {
+        for state in agent_states(scenario) {
+            self.baseline.insert(state.id, state.value);
+        }
+    }
+
+    /// Record one sample per agent for `block` and reset the action tally.
+    pub fn record(&mut self, scenario: &Scenario, block: u64) {
+        for state in agent_states(scenario) {
+            let baseline = *self.baseline.entry(state.id.clone()).or_insert(state.value);
+            let (actions, last_action) = self
+                .block_actions
+                .remove(&state.id)
+                .unwrap_or((0, "none".to_string()));
+            self.samples.push(AgentSample {
+                block,
+                agent_id: state.id,
+                kind: state.kind.to_string(),
+                zec_balance: state.zec,
+                zai_balance: state.zai,
+                lp_shares: state.shares,
+                value_zai: state.value,
+                pnl_zai: state.value - baseline,
+                actions,
+                last_action,
+            });
+        }
+        self.block_actions.clear();
+    }
+
+    /// All samples for one agent, in block order.
+    pub fn series(&self, agent_id: &str) -> Vec<&AgentSample> {
+        self.samples
+            .iter()
+            .filter(|s| s.agent_id == agent_id)
+            .collect()
+    }
+}
+
+fn agent_states(scenario: &Scenario) -> Vec<AgentState> {
+    let amm = &scenario.amm;
+    let spot = amm.spot_price();
+    let share_value = if amm.total_lp_shares > 0.0 {
+        (amm.reserve_zec * spot + amm.reserve_zai) / amm.total_lp_shares
+    } else {
+        0.0
+    };
+    let lending_debt = |id: &str| match &scenario.lending_market {
+        Some(market) => {
+            market.debt_of(id, LendingAsset::Zec) * spot + market.debt_of(id, LendingAsset::Zai)
+        }
+        None => 0.0,
+    };
+    let wallet = |id: String, kind: &'static str, zec: f64, zai: f64, debt: f64| AgentState {
+        id,
+        kind,
+        zec,
+        zai,
+        shares: 0.0,
+        value: zec * spot + zai - debt,
+    };
+
+    let mut states = Vec::new();
+    for (i, a) in scenario.arbers.iter().enumerate() {
+        let id = format!("arber_{}", i);
+        let debt = lending_debt(&id);
+        states.push(wallet(id, "arber", a.zec_balance, a.zai_balance, debt));
+    }
+    for (i, d) in scenario.demand_agents.iter().enumerate() {
+        states.push(wallet(
+            format!("demand_{}", i),
+            "demand",
+            d.zec_balance,
+            d.zai_balance,
+            0.0,
+        ));
+    }
+    for (i, m) in scenario.miners.iter().enumerate() {
+        states.push(wallet(
+            format!("miner_{}", i),
+            "miner",
+            m.zec_balance,
+            m.zai_balance,
+            0.0,
+        ));
+    }
+    for (i, h) in scenario.cdp_holders.iter().enumerate() {
+        let equity = h
+            .vault_id
+            .and_then(|id| scenario.registry.vaults.get(&id))
+            .map_or(0.0, |v| v.collateral_zec * spot - v.debt_zai);
+        states.push(AgentState {
+            id: format!("cdp_holder_{}", i),
+            kind: "cdp_holder",
+            zec: h.reserve_zec,
+            zai: h.zai_balance,
+            shares: 0.0,
+            value: h.reserve_zec * spot + h.zai_balance + equity,
+        });
+    }
+    for (i, lp) in scenario.lp_agents.iter().enumerate() {
+        states.push(AgentState {
+            id: format!("lp_{}", i),
+            kind: "lp",
+            zec: lp.zec_balance,
+            zai: lp.zai_balance,
+            shares: lp.shares,
+            value: lp.zec_balance * spot + lp.zai_balance + lp.shares * share_value,
+        });
+    }
+    for (i, lp) in scenario.il_aware_lps.iter().enumerate() {
+        states.push(AgentState {
+            id: format!("il_lp_{}", i),
+            kind: "il_aware_lp",
+            zec: lp.withdrawn_zec,
+            zai: lp.withdrawn_zai,
+            shares: lp.shares,
+            value: lp.withdrawn_zec * spot + lp.withdrawn_zai + lp.shares * share_value,
+        });
+    }
+    for (i, a) in scenario.attackers.iter().enumerate() {
+        let id = format!("attacker_{}", i);
+        let debt = lending_debt(&id);
+        states.push(wallet(id, "attacker", a.zec_balance, a.zai_balance, debt));
+    }
+    for (i, r) in scenario.redeemers.iter().enumerate() {
+        states.push(wallet(
+            format!("redeemer_{}", i),
+            "redeemer",
+            r.zec_balance,
+            r.zai_balance,
+            0.0,
+        ));
+    }
+    for (i, t) in scenario.basis_traders.iter().enumerate() {
+        states.push(wallet(
+            format!("basis_{}", i),
+            "basis_trader",
+            t.zec_balance,
+            t.zai_balance,
+            0.0,
+        ));
+    }
+    for (i, s) in scenario.savers.iter().enumerate() {
+        // Locked ZAI counts toward the wallet at its redeemable value
+        let saved = scenario
+            .savings
+            .as_ref()
+            .map_or(0.0, |m| m.balance_of(s.savings_shares));
+        states.push(wallet(
+            format!("saver_{}", i),
+            "saver",
+            s.zec_balance,
+            s.zai_balance + saved,
+            0.0,
+        ));
+    }
+    for (i, t) in scenario.noise_traders.iter().enumerate() {
+        states.push(wallet(
+            format!("noise_{}", i),
+            "noise_trader",
+            t.zec_balance,
+            t.zai_balance,
+            0.0,
+        ));
+    }
+    for (i, p) in scenario.vault_portfolios.iter().enumerate() {
+        let (collateral, debt) = scenario.registry.owner_totals(&p.owner);
+        states.push(AgentState {
+            id: format!("portfolio_{}", i),
+            kind: "vault_portfolio",
+            zec: p.reserve_zec,
+            zai: 0.0,
+            shares: 0.0,
+            value: (p.reserve_zec + collateral) * spot - debt,
+        });
+    }
+    for (i, a) in scenario.basis_arbs.iter().enumerate() {
+        // Perp margin and open PnL count toward the value, marked at spot
+        states.push(AgentState {
+            id: format!("basis_arb_{}", i),
+            kind: "basis_arb",
+            zec: a.zec_balance,
+            zai: a.zai_balance,
+            shares: 0.0,
+            value: a.value_zai(spot),
+        });
+    }
+    states
+}
+
+/// Save per-agent samples to CSV (one row per agent per block).
+pub fn save_agent_metrics_csv(samples: &[AgentSample], path: &Path) -> Result<(), ZaiSimError> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut wtr = csv::Writer::from_path(path)?;
+    wtr.write_record([
+        "block",
+        "agent_id",
+        "kind",
+        "zec_balance",
+        "zai_balance",
+        "lp_shares",
+        "value_zai",
+        "pnl_zai",
+        "actions",
+        "last_action",
+    ])?;
+    for s in samples {
+        wtr.write_record([
+            s.block.to_string(),
+            s.agent_id.clone(),
+            s.kind.clone(),
+            format!("{:.6}", s.zec_balance),
+            format!("{:.6}", s.zai_balance),
+            format!("{:.6}", s.lp_shares),
+            format!("{:.6}", s.value_zai),
+            format!("{:.6}", s.pnl_zai),
+            s.actions.to_string(),
+            s.last_action.clone(),
+        ])?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
@@ -1,7 +1,18 @@
 use std::collections::VecDeque;
 
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use rand_distr::StandardNormal;
+use serde::{Deserialize, Serialize};
+
 use crate::amm::Amm;
 use crate::cdp::VaultRegistry;
+use crate::emission::EmissionConfig;
+use crate::error::ZaiSimError;
+use crate::external_market::{ExternalMarket, ExternalMarketConfig};
+use crate::lending::{LendingAsset, LendingMarket};
+use crate::liquidation::LiquidationEngine;
+use crate::savings::SavingsModule;
 
 // ═══════════════════════════════════════════════════════════════════════
 // Agent action — returned from each agent's `act()` to describe what happened
@@ -30,20 +41,43 @@ pub enum AgentAction {
     AttackSwap { direction: String, amount: f64 },
     /// Queued action (latency pending)
     Queued { description: String },
+    /// Redeemed ZAI against vaults at face value
+    Redeem { zai_redeemed: f64, zec_received: f64 },
+    /// Borrowed inventory from the lending market
+    Borrow { asset: LendingAsset, amount: f64 },
+    /// Repaid a lending-market loan
+    Repay { asset: LendingAsset, amount: f64 },
+    /// Locked ZAI in the savings module
+    SavingsDeposit {
+        zai: f64,
+        shares: f64,
+    },
+    /// Withdrew ZAI from the savings module
+    SavingsWithdraw {
+        zai: f64,
+        shares: f64,
+    },
+}
+
+impl AgentAction {
+    /// Whether the action is an on-chain transaction that pays gas.
+    pub fn is_transaction(&self) -> bool {
+        !matches!(self, AgentAction::None | AgentAction::Queued { .. })
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════════
 // 1. Arbitrageur
 // ═══════════════════════════════════════════════════════════════════════
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct PendingTrade {
     execute_at_block: u64,
     is_buy_zec: bool,
     amount: f64,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ArbitrageurConfig {
     pub initial_zai_balance: f64,
     pub initial_zec_balance: f64,
@@ -62,6 +96,13 @@ pub struct ArbitrageurConfig {
     pub activity_rate: f64,
     /// Fraction of balance to trade per opportunity. Default 0.1 (10%).
     pub max_trade_pct: f64,
+    /// Refill inventory from the lending market when it drops below 25% of
+    /// the initial balance, and repay once it is back above the initial level.
+    pub borrow_from_lending: bool,
+    /// Hedge each AMM trade on a finite-depth external market and arb against
+    /// its impacted price. `None` keeps the frictionless model: inventory is
+    /// held and converted off-chain at the oracle price with no slippage.
+    pub external_market: Option<ExternalMarketConfig>,
 }
 
 impl Default for ArbitrageurConfig {
@@ -76,34 +117,87 @@ impl Default for ArbitrageurConfig {
             min_arb_profit: 0.0,
             activity_rate: 1.0,
             max_trade_pct: 0.1,
+            borrow_from_lending: false,
+            external_market: None,
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Arbitrageur {
     pub config: ArbitrageurConfig,
     pub zai_balance: f64,
     pub zec_balance: f64,
     pub total_profit_zai: f64,
     pending_trades: VecDeque<PendingTrade>,
+    /// This arber's view of the external venue, including its own impact
+    #[serde(default)]
+    pub external_market: Option<ExternalMarket>,
+    /// Gas per trade (ZAI), added to `min_arb_profit`; set by the scenario
+    /// when a gas model is configured
+    #[serde(default)]
+    pub gas_fee_zai: f64,
+    /// External capital arrives through the scenario's bridge rather than
+    /// in `act`; set by the scenario when a bridge is configured
+    #[serde(default)]
+    pub bridged: bool,
+    /// Multiplier on capital replenishment from historical volume; set by
+    /// the scenario each block when a volume profile is loaded
+    #[serde(default)]
+    pub volume_scale: Option<f64>,
 }
 
 impl Arbitrageur {
     pub fn new(config: ArbitrageurConfig) -> Self {
         let zai = config.initial_zai_balance;
         let zec = config.initial_zec_balance;
+        let external_market = config.external_market.clone().map(ExternalMarket::new);
         Arbitrageur {
             config,
             zai_balance: zai,
             zec_balance: zec,
             total_profit_zai: 0.0,
             pending_trades: VecDeque::new(),
+            external_market,
+            gas_fee_zai: 0.0,
+            bridged: false,
+            volume_scale: None,
+        }
+    }
+
+    /// External capital arriving per block, scaled by market volume.
+    pub fn replenish_rate(&self) -> f64 {
+        self.config.capital_replenish_rate * self.volume_scale.unwrap_or(1.0)
+    }
+
+    /// External leg of a ZEC sale on the AMM: buy the ZEC back off-chain.
+    /// Skipped when the venue can't fill it from the arber's ZAI.
+    fn hedge_zec_sold(&mut self, zec: f64, external_price: f64, block: u64) {
+        if let Some(market) = &mut self.external_market {
+            let cost = market.quote_buy_zec(zec, external_price);
+            if cost.is_finite() && cost <= self.zai_balance {
+                self.zai_balance -= market.buy_zec(zec, external_price, block);
+                self.zec_balance += zec;
+            }
+        }
+    }
+
+    /// External leg of a ZEC purchase on the AMM: sell the ZEC off-chain.
+    fn hedge_zec_bought(&mut self, zec: f64, external_price: f64, block: u64) {
+        if let Some(market) = &mut self.external_market {
+            let zec = zec.min(self.zec_balance);
+            self.zai_balance += market.sell_zec(zec, external_price, block);
+            self.zec_balance -= zec;
         }
     }
 
     /// Execute any pending trades that have reached their execution block.
-    fn execute_pending(&mut self, amm: &mut Amm, block: u64) -> Vec<AgentAction> {
+    fn execute_pending(
+        &mut self,
+        amm: &mut Amm,
+        external_price: f64,
+        block: u64,
+    ) -> Vec<AgentAction> {
         let mut actions = Vec::new();
 
         while let Some(front) = self.pending_trades.front() {
@@ -119,6 +213,7 @@ impl Arbitrageur {
                     if let Ok(zec_out) = amm.swap_zai_for_zec(spend, block) {
                         self.zai_balance -= spend;
                         self.zec_balance += zec_out;
+                        self.hedge_zec_bought(zec_out, external_price, block);
                         actions.push(AgentAction::BuyZec {
                             zai_spent: spend,
                             zec_received: zec_out,
@@ -133,6 +228,7 @@ impl Arbitrageur {
                         self.zec_balance -= spend;
                         self.zai_balance += zai_out;
                         self.total_profit_zai += zai_out - spend * amm.spot_price();
+                        self.hedge_zec_sold(spend, external_price, block);
                         actions.push(AgentAction::SellZec {
                             zec_spent: spend,
                             zai_received: zai_out,
@@ -145,35 +241,89 @@ impl Arbitrageur {
         actions
     }
 
-    /// Observe prices and decide whether to arb. `external_price` is the
-    /// off-chain ZEC/ZAI price (e.g., from Binance).
-    pub fn act(
+    /// Borrow or repay lending-market inventory to keep balances near their
+    /// initial levels. No-op unless `borrow_from_lending` is set.
+    pub fn manage_inventory(
         &mut self,
-        amm: &mut Amm,
-        external_price: f64,
+        borrower: &str,
+        market: &mut LendingMarket,
         block: u64,
     ) -> Vec<AgentAction> {
-        // Replenish capital from external sources
-        self.zai_balance += self.config.capital_replenish_rate;
+        let mut actions = Vec::new();
+        if !self.config.borrow_from_lending {
+            return actions;
+        }
+
+        let targets = [
+            (LendingAsset::Zec, self.config.initial_zec_balance),
+            (LendingAsset::Zai, self.config.initial_zai_balance),
+        ];
+        for (asset, target) in targets {
+            let balance = match asset {
+                LendingAsset::Zec => &mut self.zec_balance,
+                LendingAsset::Zai => &mut self.zai_balance,
+            };
+            let debt = market.debt_of(borrower, asset);
+
+            if *balance < target * 0.25 {
+                if let Ok(amount) = market.borrow(borrower, asset, target - *balance, block) {
+                    *balance += amount;
+                    actions.push(AgentAction::Borrow { asset, amount });
+                }
+            } else if *balance > target && debt > 0.0 {
+                if let Ok(amount) = market.repay(borrower, asset, *balance - target, block) {
+                    *balance -= amount;
+                    actions.push(AgentAction::Repay { asset, amount });
+                }
+            }
+        }
+        actions
+    }
+
+    /// Replenish capital from external sources, instantly.
+    fn replenish(&mut self, external_price: f64, block: u64) {
+        let rate = self.replenish_rate();
+        self.zai_balance += rate;
 
         // External market access: when arber is low on ZEC but has ZAI,
         // model buying ZEC on Binance/Coinbase (converting ZAI → ZEC at external price).
-        // Limited to capital_replenish_rate per block to bound throughput.
-        if self.config.capital_replenish_rate > 0.0
-            && self.zec_balance < 10.0
-            && self.zai_balance > 0.0
-            && external_price > 0.0
-        {
-            let convert = self.config.capital_replenish_rate.min(self.zai_balance);
+        // Limited to the replenish rate per block to bound throughput.
+        if rate > 0.0 && self.zec_balance < 10.0 && self.zai_balance > 0.0 && external_price > 0.0 {
+            let convert = rate.min(self.zai_balance);
             self.zai_balance -= convert;
-            self.zec_balance += convert / external_price;
+            self.zec_balance += self.buy_zec_externally(convert, external_price, block);
+        }
+    }
+
+    /// ZEC bought off-chain with `zai` at the external venue.
+    pub fn buy_zec_externally(&mut self, zai: f64, external_price: f64, block: u64) -> f64 {
+        match &mut self.external_market {
+            Some(market) => market.spend_zai(zai, external_price, block),
+            None => zai / external_price,
+        }
+    }
+
+    /// Observe prices and decide whether to arb. `external_price` is the
+    /// off-chain ZEC/ZAI price (e.g., from Binance).
+    pub fn act(&mut self, amm: &mut Amm, external_price: f64, block: u64) -> Vec<AgentAction> {
+        if let Some(market) = &mut self.external_market {
+            market.recover(block);
+        }
+
+        if !self.bridged {
+            self.replenish(external_price, block);
         }
 
         // Execute any matured pending trades
-        let mut actions = self.execute_pending(amm, block);
+        let mut actions = self.execute_pending(amm, external_price, block);
 
+        // With a finite-depth venue, arb against its (impacted) price
+        let venue_price = self
+            .external_market
+            .as_ref()
+            .map_or(external_price, |m| m.price(external_price));
         let amm_price = amm.spot_price();
-        let deviation_pct = ((amm_price - external_price) / external_price) * 100.0;
+        let deviation_pct = ((amm_price - venue_price) / venue_price) * 100.0;
 
         if deviation_pct > self.config.arb_threshold_pct {
             // AMM price too high → sell ZEC on AMM (get ZAI) → buy ZEC cheaper externally
@@ -182,8 +332,12 @@ impl Arbitrageur {
             if trade_size > 0.01 {
                 // Profitability check: expected profit must exceed tx fee floor
                 let expected_zai = amm.quote_zec_for_zai(trade_size);
-                let expected_profit = expected_zai - trade_size * external_price;
-                if expected_profit < self.config.min_arb_profit {
+                let rebuy_cost = match &self.external_market {
+                    Some(market) => market.quote_buy_zec(trade_size, external_price),
+                    None => trade_size * external_price,
+                };
+                let expected_profit = expected_zai - rebuy_cost;
+                if expected_profit < self.config.min_arb_profit + self.gas_fee_zai {
                     return actions;
                 }
 
@@ -194,6 +348,7 @@ impl Arbitrageur {
                     if let Ok(zai_out) = amm.swap_zec_for_zai(spend, block) {
                         self.zec_balance -= spend;
                         self.zai_balance += zai_out;
+                        self.hedge_zec_sold(spend, external_price, block);
                         actions.push(AgentAction::SellZec {
                             zec_spent: spend,
                             zai_received: zai_out,
@@ -217,8 +372,12 @@ impl Arbitrageur {
             if trade_value > 0.01 {
                 // Profitability check: expected profit must exceed tx fee floor
                 let expected_zec = amm.quote_zai_for_zec(trade_value);
-                let expected_profit = expected_zec * external_price - trade_value;
-                if expected_profit < self.config.min_arb_profit {
+                let proceeds = match &self.external_market {
+                    Some(market) => market.quote_sell_zec(expected_zec, external_price),
+                    None => expected_zec * external_price,
+                };
+                let expected_profit = proceeds - trade_value;
+                if expected_profit < self.config.min_arb_profit + self.gas_fee_zai {
                     return actions;
                 }
 
@@ -228,6 +387,7 @@ impl Arbitrageur {
                     if let Ok(zec_out) = amm.swap_zai_for_zec(spend, block) {
                         self.zai_balance -= spend;
                         self.zec_balance += zec_out;
+                        self.hedge_zec_bought(zec_out, external_price, block);
                         actions.push(AgentAction::BuyZec {
                             zai_spent: spend,
                             zec_received: zec_out,
@@ -254,7 +414,7 @@ impl Arbitrageur {
 // 2. Demand Agent
 // ═══════════════════════════════════════════════════════════════════════
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DemandAgentConfig {
     /// Fraction of ZEC balance to spend per 1% discount to par
     pub demand_elasticity: f64,
@@ -267,6 +427,10 @@ pub struct DemandAgentConfig {
     /// Fraction of ZAI balance to panic sell
     pub demand_panic_sell_fraction: f64,
     pub initial_zec_balance: f64,
+    /// Fraction of ZAI balance sold per block per 1%/yr of holding cost
+    /// while a funding charge applies; buying stops meanwhile
+    #[serde(default)]
+    pub carry_sensitivity: f64,
 }
 
 impl Default for DemandAgentConfig {
@@ -278,17 +442,22 @@ impl Default for DemandAgentConfig {
             demand_exit_window_blocks: 48,
             demand_panic_sell_fraction: 0.5,
             initial_zec_balance: 5000.0,
+            carry_sensitivity: 0.001,
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct DemandAgent {
     pub config: DemandAgentConfig,
     pub zec_balance: f64,
     pub zai_balance: f64,
     deviation_blocks: u64,
     pub panicked: bool,
+    /// Multiplier on normal buying from historical volume; set by the
+    /// scenario each block when a volume profile is loaded
+    #[serde(default)]
+    pub volume_scale: Option<f64>,
 }
 
 impl DemandAgent {
@@ -300,13 +469,20 @@ impl DemandAgent {
             zai_balance: 0.0,
             deviation_blocks: 0,
             panicked: false,
+            volume_scale: None,
         }
     }
 
-    pub fn act(
+    pub fn act(&mut self, amm: &mut Amm, redemption_price: f64, block: u64) -> AgentAction {
+        self.act_with_carry(amm, redemption_price, 0.0, block)
+    }
+
+    /// `act` with an annual `holding_cost` charged on ZAI balances.
+    pub fn act_with_carry(
         &mut self,
         amm: &mut Amm,
         redemption_price: f64,
+        holding_cost: f64,
         block: u64,
     ) -> AgentAction {
         let market_price = amm.spot_price();
@@ -331,7 +507,7 @@ impl DemandAgent {
         {
             let sell_amount = self.zai_balance * self.config.demand_panic_sell_fraction;
             if sell_amount > 0.01 {
-                if let Ok(zec_out) = amm.swap_zai_for_zec(sell_amount, block) {
+                if let Ok(zec_out) = amm.buy_zec(sell_amount, block) {
                     self.zai_balance -= sell_amount;
                     self.zec_balance += zec_out;
                     self.panicked = true;
@@ -343,6 +519,23 @@ impl DemandAgent {
             }
         }
 
+        // Holding ZAI costs carry: sell down instead of buying
+        if holding_cost > 0.0 {
+            let fraction = (self.config.carry_sensitivity * holding_cost * 100.0).min(1.0);
+            let sell_amount = self.zai_balance * fraction;
+            if sell_amount > 0.01 {
+                if let Ok(zec_out) = amm.buy_zec(sell_amount, block) {
+                    self.zai_balance -= sell_amount;
+                    self.zec_balance += zec_out;
+                    return AgentAction::BuyZec {
+                        zai_spent: sell_amount,
+                        zec_received: zec_out,
+                    };
+                }
+            }
+            return AgentAction::None;
+        }
+
         // Normal buying: base rate + elasticity bonus when ZAI is cheap
         let mut buy_amount_zec = self.config.demand_base_rate;
 
@@ -352,10 +545,11 @@ impl DemandAgent {
                 * (deviation_pct / 100.0);
         }
 
+        buy_amount_zec *= self.volume_scale.unwrap_or(1.0);
         buy_amount_zec = buy_amount_zec.min(self.zec_balance);
 
         if buy_amount_zec > 0.01 {
-            if let Ok(zai_out) = amm.swap_zec_for_zai(buy_amount_zec, block) {
+            if let Ok(zai_out) = amm.sell_zec(buy_amount_zec, block) {
                 self.zec_balance -= buy_amount_zec;
                 self.zai_balance += zai_out;
                 return AgentAction::BuyZai {
@@ -373,7 +567,7 @@ impl DemandAgent {
 // 3. Miner Agent
 // ═══════════════════════════════════════════════════════════════════════
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MinerAgentConfig {
     /// ZEC received per block (block reward)
     pub block_reward: f64,
@@ -385,6 +579,8 @@ pub struct MinerAgentConfig {
     pub sell_immediately: bool,
     /// Blocks between batch sells (only used when sell_immediately=false)
     pub batch_interval: u64,
+    /// Halving-aware issuance; when set it replaces the static `block_reward`
+    pub emission: Option<EmissionConfig>,
 }
 
 impl Default for MinerAgentConfig {
@@ -395,17 +591,32 @@ impl Default for MinerAgentConfig {
             miner_amm_fraction: 0.3,
             sell_immediately: true,
             batch_interval: 48,
+            emission: None,
+        }
+    }
+}
+
+impl MinerAgentConfig {
+    /// ZEC received at simulation `block`.
+    pub fn reward_at(&self, block: u64) -> f64 {
+        match &self.emission {
+            Some(emission) => emission.miner_reward(block),
+            None => self.block_reward,
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct MinerAgent {
     pub config: MinerAgentConfig,
     pub zec_balance: f64,
     pub zai_balance: f64,
     accumulated_sell: f64,
     last_batch_block: u64,
+    /// Multiplier on AMM sales from historical volume; set by the scenario
+    /// each block when a volume profile is loaded
+    #[serde(default)]
+    pub volume_scale: Option<f64>,
 }
 
 impl MinerAgent {
@@ -416,19 +627,22 @@ impl MinerAgent {
             zai_balance: 0.0,
             accumulated_sell: 0.0,
             last_batch_block: 0,
+            volume_scale: None,
         }
     }
 
     pub fn act(&mut self, amm: &mut Amm, block: u64) -> AgentAction {
         // Receive block reward
-        self.zec_balance += self.config.block_reward;
+        let reward = self.config.reward_at(block);
+        self.zec_balance += reward;
 
-        let sell_total = self.config.block_reward * self.config.miner_sell_fraction;
-        let amm_sell = sell_total * self.config.miner_amm_fraction;
+        let sell_total = reward * self.config.miner_sell_fraction;
+        let scale = self.volume_scale.unwrap_or(1.0);
+        let amm_sell = (sell_total * self.config.miner_amm_fraction * scale).min(self.zec_balance);
 
         if self.config.sell_immediately {
             if amm_sell > 0.001 {
-                if let Ok(zai_out) = amm.swap_zec_for_zai(amm_sell, block) {
+                if let Ok(zai_out) = amm.sell_zec(amm_sell, block) {
                     self.zec_balance -= amm_sell;
                     self.zai_balance += zai_out;
                     return AgentAction::MinerSell {
@@ -445,7 +659,7 @@ impl MinerAgent {
                 let batch = self.accumulated_sell.min(self.zec_balance);
                 self.accumulated_sell = 0.0;
                 self.last_batch_block = block;
-                if let Ok(zai_out) = amm.swap_zec_for_zai(batch, block) {
+                if let Ok(zai_out) = amm.sell_zec(batch, block) {
                     self.zec_balance -= batch;
                     self.zai_balance += zai_out;
                     return AgentAction::MinerSell {
@@ -464,7 +678,7 @@ impl MinerAgent {
 // 4. CDP Holder
 // ═══════════════════════════════════════════════════════════════════════
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CdpHolderConfig {
     /// Collateral ratio the holder targets (e.g., 2.0 = 200%)
     pub target_ratio: f64,
@@ -490,11 +704,113 @@ impl Default for CdpHolderConfig {
     }
 }
 
-#[derive(Debug)]
+/// Transaction economics of collateral top-ups. The default reproduces a
+/// frictionless holder: no fee and any top-up above dust is executed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TopUpPolicy {
+    /// Fee paid from reserves per top-up transaction (ZEC)
+    pub tx_fee_zec: f64,
+    /// Smallest top-up worth sending; smaller needs are deferred, and when a
+    /// top-up is sent it is rounded up to at least this size
+    pub min_topup_zec: f64,
+    /// Defer while the fee exceeds this fraction of the amount needed
+    pub max_fee_fraction: f64,
+    /// Below this collateral ratio the holder tops up regardless of cost
+    pub emergency_ratio: f64,
+}
+
+impl Default for TopUpPolicy {
+    fn default() -> Self {
+        TopUpPolicy {
+            tx_fee_zec: 0.0,
+            min_topup_zec: 0.01,
+            max_fee_fraction: 1.0,
+            emergency_ratio: 0.0,
+        }
+    }
+}
+
+/// How a holder manages its debt through the AMM. The default reproduces
+/// a holder that only ever tops up collateral.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DebtPolicy {
+    /// Below the action threshold, sell reserve ZEC on the AMM for ZAI and
+    /// repay debt back to the target ratio instead of adding the ZEC as
+    /// collateral
+    pub buy_to_repay: bool,
+    /// Above this ratio, borrow back down to the target ratio and sell the
+    /// ZAI on the AMM for reserve ZEC (`None` never re-borrows)
+    pub reborrow_ratio: Option<f64>,
+}
+
+/// When a holder that can no longer defend its vault closes it itself at
+/// the reduced self-liquidation penalty rather than wait for keepers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SelfLiquidationPolicy {
+    /// Weight on the expected liquidation penalty against the certain
+    /// self-liquidation penalty: 1.0 is risk neutral, higher exits earlier
+    pub risk_aversion: f64,
+    /// Adverse price move (fraction) that cuts the chance of reaching the
+    /// liquidation ratio by a factor of e; larger is more pessimistic
+    pub expected_drop: f64,
+}
+
+impl Default for SelfLiquidationPolicy {
+    fn default() -> Self {
+        SelfLiquidationPolicy {
+            risk_aversion: 1.0,
+            expected_drop: 0.05,
+        }
+    }
+}
+
+impl SelfLiquidationPolicy {
+    /// Chance of liquidation at `ratio`: 1 at or below `min_ratio`, decaying
+    /// exponentially in the price drop that would take the vault there.
+    pub fn liquidation_probability(&self, ratio: f64, min_ratio: f64) -> f64 {
+        let drop = (1.0 - min_ratio / ratio).max(0.0);
+        (-drop / self.expected_drop.max(f64::EPSILON)).exp()
+    }
+
+    /// Whether waiting costs more than exiting: risk-weighted expected
+    /// liquidation penalty against the self-liquidation penalty, both as
+    /// fractions of debt.
+    pub fn prefers_exit(
+        &self,
+        ratio: f64,
+        min_ratio: f64,
+        liquidation_penalty: f64,
+        self_liquidation_penalty: f64,
+    ) -> bool {
+        self.risk_aversion * self.liquidation_probability(ratio, min_ratio) * liquidation_penalty
+            > self_liquidation_penalty
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct CdpHolder {
     pub config: CdpHolderConfig,
     pub vault_id: Option<u64>,
     pub reserve_zec: f64,
+    /// ZAI on hand, spent on repaying debt when the ratio is low
+    #[serde(default)]
+    pub zai_balance: f64,
+    pub topup_policy: TopUpPolicy,
+    #[serde(default)]
+    pub debt_policy: DebtPolicy,
+    /// Self-liquidate an undefended vault when it pays (`None` never does)
+    #[serde(default)]
+    pub self_liquidation: Option<SelfLiquidationPolicy>,
+    pub topups: u32,
+    /// Blocks where a top-up was needed but not worth its fee
+    pub deferred_topups: u32,
+    #[serde(default)]
+    pub repayments: u32,
+    #[serde(default)]
+    pub reborrows: u32,
+    #[serde(default)]
+    pub self_liquidations: u32,
+    pub fees_paid_zec: f64,
 }
 
 impl CdpHolder {
@@ -504,16 +820,51 @@ impl CdpHolder {
             config,
             vault_id: None,
             reserve_zec: reserve,
+            zai_balance: 0.0,
+            topup_policy: TopUpPolicy::default(),
+            debt_policy: DebtPolicy::default(),
+            self_liquidation: None,
+            topups: 0,
+            deferred_topups: 0,
+            repayments: 0,
+            reborrows: 0,
+            self_liquidations: 0,
+            fees_paid_zec: 0.0,
         }
     }
 
+    /// Use gas-aware top-up sizing instead of frictionless top-ups.
+    pub fn with_topup_policy(mut self, policy: TopUpPolicy) -> Self {
+        self.topup_policy = policy;
+        self
+    }
+
+    /// Repay and re-borrow through the AMM (see `act_with_amm`).
+    pub fn with_debt_policy(mut self, policy: DebtPolicy) -> Self {
+        self.debt_policy = policy;
+        self
+    }
+
+    /// Weigh self-liquidation against waiting for keepers (see
+    /// `self_liquidate_if_rational`).
+    pub fn with_self_liquidation(mut self, policy: SelfLiquidationPolicy) -> Self {
+        self.self_liquidation = Some(policy);
+        self
+    }
+
+    /// On-chain transactions sent so far: top-ups, repayments, re-borrows
+    /// and self-liquidations.
+    pub fn transactions(&self) -> u32 {
+        self.topups + self.repayments + self.reborrows + self.self_liquidations
+    }
+
     /// Open the initial vault. Call once at simulation start.
     pub fn open_vault(
         &mut self,
         registry: &mut VaultRegistry,
         amm: &Amm,
         block: u64,
-    ) -> Result<u64, String> {
+    ) -> Result<u64, ZaiSimError> {
         let id = registry.open_vault(
             "cdp_holder",
             self.config.initial_collateral,
@@ -530,7 +881,7 @@ impl CdpHolder {
         &mut self,
         registry: &mut VaultRegistry,
         amm: &Amm,
-        _block: u64,
+        block: u64,
     ) -> AgentAction {
         let vault_id = match self.vault_id {
             Some(id) => id,
@@ -538,8 +889,8 @@ impl CdpHolder {
         };
 
         // Check if vault still exists
-        let price = amm.get_twap(registry.config.twap_window);
-        let vault = match registry.get_vault(vault_id) {
+        let price = registry.get_price(amm);
+        let vault = match registry.vault_at(vault_id, block) {
             Some(v) => v,
             None => {
                 self.vault_id = None;
@@ -551,16 +902,37 @@ impl CdpHolder {
 
         if ratio < self.config.action_threshold_ratio && ratio > 0.0 {
             // Try to add collateral first
-            if self.reserve_zec > 0.0 {
+            let policy = &self.topup_policy;
+            let spendable = self.reserve_zec - policy.tx_fee_zec;
+            if spendable > 0.0 {
                 // How much ZEC needed to reach target ratio?
                 // target = (collateral + add) * price / debt
                 // add = (target * debt / price) - collateral
-                let needed = (self.config.target_ratio * vault.debt_zai / price)
-                    - vault.collateral_zec;
-                let add_amount = needed.max(0.0).min(self.reserve_zec);
+                let needed = ((self.config.target_ratio * vault.debt_zai / price)
+                    - vault.collateral_zec)
+                    .max(0.0);
+
+                // Batch small needs until the top-up is worth its fee,
+                // unless the vault is close enough to liquidation to panic
+                let uneconomic = needed < policy.min_topup_zec
+                    || policy.tx_fee_zec > needed * policy.max_fee_fraction;
+                if uneconomic && ratio >= policy.emergency_ratio {
+                    if needed > 0.0 {
+                        self.deferred_topups += 1;
+                        return AgentAction::CdpAction {
+                            vault_id,
+                            description: format!("deferred {:.4} ZEC top-up", needed),
+                        };
+                    }
+                    return AgentAction::None;
+                }
 
+                let add_amount = needed.max(policy.min_topup_zec).min(spendable);
                 if add_amount > 0.01 {
-                    self.reserve_zec -= add_amount;
+                    let fee = policy.tx_fee_zec;
+                    self.reserve_zec -= add_amount + fee;
+                    self.fees_paid_zec += fee;
+                    self.topups += 1;
                     if registry.deposit_collateral(vault_id, add_amount).is_ok() {
                         return AgentAction::CdpAction {
                             vault_id,
@@ -580,13 +952,187 @@ impl CdpHolder {
 
         AgentAction::None
     }
+
+    /// Manage the vault with its ZAI side as well: when the ratio is low,
+    /// repay debt from the ZAI balance (buying ZAI on the AMM with reserve
+    /// ZEC under `buy_to_repay`); when it is above `reborrow_ratio`,
+    /// borrow back to the target ratio and sell the ZAI for ZEC, if
+    /// `can_borrow` allows minting that much. Otherwise tops up collateral
+    /// as `act` does.
+    pub fn act_with_amm(
+        &mut self,
+        registry: &mut VaultRegistry,
+        amm: &mut Amm,
+        can_borrow: impl Fn(f64) -> bool,
+        block: u64,
+    ) -> AgentAction {
+        let Some(vault_id) = self.vault_id else {
+            return AgentAction::None;
+        };
+        let price = registry.get_price(amm);
+        let Some(vault) = registry.vault_at(vault_id, block) else {
+            self.vault_id = None;
+            return AgentAction::None;
+        };
+        let ratio = vault.collateral_ratio(price);
+        // Debt above (positive) or below the target ratio's
+        let excess_debt = vault.debt_zai - vault.collateral_zec * price / self.config.target_ratio;
+
+        if ratio < self.config.action_threshold_ratio && ratio > 0.0 {
+            let mut bought = 0.0;
+            if self.debt_policy.buy_to_repay {
+                bought = self.buy_zai(amm, excess_debt - self.zai_balance, block);
+            }
+            let repaid = self.repay(registry, vault_id, excess_debt, block);
+            if repaid > 0.0 {
+                return AgentAction::CdpAction {
+                    vault_id,
+                    description: if bought > 0.0 {
+                        format!("bought and repaid {:.2} ZAI", repaid)
+                    } else {
+                        format!("repaid {:.2} ZAI", repaid)
+                    },
+                };
+            }
+        } else if let Some(reborrow_ratio) = self.debt_policy.reborrow_ratio {
+            let amount = -excess_debt;
+            if ratio > reborrow_ratio
+                && amount > 0.01
+                && can_borrow(amount)
+                && registry.borrow_zai(vault_id, amount, block, amm).is_ok()
+            {
+                self.pay_fee();
+                self.reborrows += 1;
+                let zec = match amm.swap_zai_for_zec(amount, block) {
+                    Ok(zec) => {
+                        self.reserve_zec += zec;
+                        zec
+                    }
+                    Err(_) => {
+                        self.zai_balance += amount;
+                        0.0
+                    }
+                };
+                return AgentAction::CdpAction {
+                    vault_id,
+                    description: format!("borrowed {:.2} ZAI for {:.4} ZEC", amount, zec),
+                };
+            }
+        }
+
+        self.act(registry, amm, block)
+    }
+
+    /// Sell enough reserve ZEC on the AMM for about `zai` ZAI, leaving the
+    /// top-up fee. Returns the ZAI bought.
+    fn buy_zai(&mut self, amm: &mut Amm, zai: f64, block: u64) -> f64 {
+        let spendable = self.reserve_zec - self.topup_policy.tx_fee_zec;
+        let zec_in = (zai / amm.spot_price()).min(spendable);
+        if zai <= 0.01 || zec_in <= 0.0 {
+            return 0.0;
+        }
+        match amm.swap_zec_for_zai(zec_in, block) {
+            Ok(zai_out) => {
+                self.reserve_zec -= zec_in;
+                self.zai_balance += zai_out;
+                zai_out
+            }
+            Err(_) => 0.0,
+        }
+    }
+
+    /// Repay up to `amount` of debt from the ZAI balance, respecting the
+    /// debt floor: all of the debt if the balance covers it, otherwise
+    /// never below the floor. Returns the ZAI repaid.
+    fn repay(
+        &mut self,
+        registry: &mut VaultRegistry,
+        vault_id: u64,
+        amount: f64,
+        block: u64,
+    ) -> f64 {
+        if self.zai_balance <= 0.01 || registry.accrue_fees(vault_id, block).is_err() {
+            return 0.0;
+        }
+        let Some(debt) = registry.get_vault(vault_id).map(|v| v.debt_zai) else {
+            return 0.0;
+        };
+        let wanted = amount.min(self.zai_balance).min(debt);
+        let repay = if registry.config.is_dust(debt - wanted) && self.zai_balance >= debt {
+            debt
+        } else {
+            registry.config.max_repayment(debt, wanted)
+        };
+        if repay <= 0.01 || registry.repay_zai(vault_id, repay, block).is_err() {
+            return 0.0;
+        }
+        self.zai_balance -= repay;
+        self.pay_fee();
+        self.repayments += 1;
+        repay
+    }
+
+    /// Close the vault through `engine.self_liquidate` if it is below the
+    /// action threshold, the holder has nothing left to defend it with, and
+    /// its policy prefers the reduced penalty now to the chance of the full
+    /// one later. Any surplus over debt and penalty comes back as ZAI.
+    pub fn self_liquidate_if_rational(
+        &mut self,
+        engine: &mut LiquidationEngine,
+        registry: &mut VaultRegistry,
+        amm: &mut Amm,
+        block: u64,
+    ) -> AgentAction {
+        let (Some(policy), Some(vault_id)) = (&self.self_liquidation, self.vault_id) else {
+            return AgentAction::None;
+        };
+        let Some(vault) = registry.vault_at(vault_id, block) else {
+            return AgentAction::None;
+        };
+        let ratio = vault.collateral_ratio(registry.get_price(amm));
+        let defended =
+            self.reserve_zec - self.topup_policy.tx_fee_zec > 0.01 || self.zai_balance > 0.01;
+        if ratio >= self.config.action_threshold_ratio || defended {
+            return AgentAction::None;
+        }
+        let penalty = registry.config.liquidation_penalty;
+        let self_penalty = penalty * engine.config.self_liquidation_penalty_pct;
+        if !policy.prefers_exit(ratio, registry.config.min_ratio, penalty, self_penalty) {
+            return AgentAction::None;
+        }
+
+        match engine.self_liquidate(vault_id, registry, amm, block) {
+            Ok(result) => {
+                self.vault_id = None;
+                self.zai_balance += result.surplus_to_owner;
+                self.reserve_zec += result.collateral_returned;
+                self.pay_fee();
+                self.self_liquidations += 1;
+                AgentAction::CdpAction {
+                    vault_id,
+                    description: format!(
+                        "self-liquidated at ratio {:.2}, {:.2} ZAI penalty",
+                        ratio, result.penalty_amount
+                    ),
+                }
+            }
+            Err(_) => AgentAction::None,
+        }
+    }
+
+    /// Pay one transaction fee from reserves, as far as they go.
+    fn pay_fee(&mut self) {
+        let fee = self.topup_policy.tx_fee_zec.min(self.reserve_zec.max(0.0));
+        self.reserve_zec -= fee;
+        self.fees_paid_zec += fee;
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════════
 // 5. LP Agent
 // ═══════════════════════════════════════════════════════════════════════
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LpAgentConfig {
     pub initial_zec: f64,
     pub initial_zai: f64,
@@ -607,7 +1153,7 @@ impl Default for LpAgentConfig {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct LpAgent {
     pub config: LpAgentConfig,
     pub shares: f64,
@@ -685,7 +1231,7 @@ impl LpAgent {
 // 6. IL-Aware LP Agent
 // ═══════════════════════════════════════════════════════════════════════
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IlAwareLpConfig {
     pub initial_zec: f64,
     pub initial_zai: f64,
@@ -708,7 +1254,7 @@ impl Default for IlAwareLpConfig {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct IlAwareLpAgent {
     pub config: IlAwareLpConfig,
     pub shares: f64,
@@ -813,21 +1359,63 @@ impl IlAwareLpAgent {
 // 7. Attacker
 // ═══════════════════════════════════════════════════════════════════════
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum AttackPhase {
     Idle,
     Manipulating { revert_at_block: u64 },
     Done,
 }
 
-#[derive(Debug, Clone)]
+/// What an attacker does from `attack_at_block` on. Every strategy trades
+/// `attack_capital_zec` into ZAI and ends holding ZEC again.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AttackStrategy {
+    /// Dump all capital in one block, hold for `hold_blocks`, buy back
+    #[default]
+    DumpAndRevert,
+    /// Sell `zec_per_block` for `blocks` blocks, then buy back evenly over
+    /// `unwind_blocks`
+    Drip {
+        zec_per_block: f64,
+        blocks: u64,
+        unwind_blocks: u64,
+    },
+    /// Dump `swing_zec`, buy it back `half_period` blocks later and repeat
+    /// `cycles` times, so the TWAP window keeps averaging a distorted spot
+    TwapOscillation {
+        swing_zec: f64,
+        half_period: u64,
+        cycles: u32,
+    },
+    /// Dump all capital to push vaults under, then buy ZEC back once the
+    /// liquidation cascade has taken spot a further `buy_drop_pct` below
+    /// the post-dump price, or after `max_hold_blocks`
+    LiquidationHunt {
+        buy_drop_pct: f64,
+        max_hold_blocks: u64,
+    },
+    /// Buy ZAI with up to `zec_per_block` a block whenever it trades less
+    /// than `target_premium_pct` above the pre-attack spot, squeezing
+    /// vault owners who need ZAI to repay, then sell it all back after
+    /// `hold_blocks`
+    PegSqueeze {
+        target_premium_pct: f64,
+        zec_per_block: f64,
+        hold_blocks: u64,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AttackerConfig {
     /// ZEC capital available for the attack
     pub attack_capital_zec: f64,
-    /// How many blocks to hold the manipulated position
+    /// How many blocks to hold the manipulated position (dump and revert)
     pub hold_blocks: u64,
     /// Block at which to begin the attack
     pub attack_at_block: u64,
+    #[serde(default)]
+    pub strategy: AttackStrategy,
 }
 
 impl Default for AttackerConfig {
@@ -836,17 +1424,26 @@ impl Default for AttackerConfig {
             attack_capital_zec: 5000.0,
             hold_blocks: 3,
             attack_at_block: 100,
+            strategy: AttackStrategy::DumpAndRevert,
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Attacker {
     pub config: AttackerConfig,
     pub phase: AttackPhase,
     pub zec_balance: f64,
     pub zai_balance: f64,
+    /// Attack capital is borrowed from the lending market instead of owned
+    pub borrows_capital: bool,
     zai_received_from_attack: f64,
+    /// AMM spot the strategy measures its moves against
+    #[serde(default)]
+    reference_spot: f64,
+    /// Dumps and buy-backs completed (TWAP oscillation)
+    #[serde(default)]
+    swings_done: u32,
 }
 
 impl Attacker {
@@ -857,48 +1454,1186 @@ impl Attacker {
             phase: AttackPhase::Idle,
             zec_balance: zec,
             zai_balance: 0.0,
+            borrows_capital: false,
             zai_received_from_attack: 0.0,
+            reference_spot: 0.0,
+            swings_done: 0,
         }
     }
 
-    pub fn act(&mut self, amm: &mut Amm, block: u64) -> AgentAction {
-        match &self.phase {
-            AttackPhase::Idle => {
-                if block >= self.config.attack_at_block {
-                    // Phase 1: dump ZEC on AMM to crash price
-                    let spend = self.zec_balance;
-                    if let Ok(zai_out) = amm.swap_zec_for_zai(spend, block) {
-                        self.zec_balance = 0.0;
-                        self.zai_balance += zai_out;
-                        self.zai_received_from_attack = zai_out;
-                        self.phase = AttackPhase::Manipulating {
-                            revert_at_block: block + self.config.hold_blocks,
-                        };
-                        return AgentAction::AttackSwap {
-                            direction: "sell_zec".to_string(),
-                            amount: spend,
+    /// An attacker with no starting capital who must borrow
+    /// `attack_capital_zec` from the lending market at the attack block.
+    pub fn new_borrowed(config: AttackerConfig) -> Self {
+        let mut attacker = Self::new(config);
+        attacker.zec_balance = 0.0;
+        attacker.borrows_capital = true;
+        attacker
+    }
+
+    /// Borrow attack capital when the attack starts and repay it once the
+    /// position is unwound. Call before and after `act` each block.
+    pub fn settle_funding(
+        &mut self,
+        borrower: &str,
+        market: &mut LendingMarket,
+        block: u64,
+    ) -> AgentAction {
+        if !self.borrows_capital {
+            return AgentAction::None;
+        }
+        match self.phase {
+            AttackPhase::Idle
+                if block >= self.config.attack_at_block && self.zec_balance == 0.0 =>
+            {
+                let want = self.config.attack_capital_zec;
+                if let Ok(amount) = market.borrow(borrower, LendingAsset::Zec, want, block) {
+                    self.zec_balance += amount;
+                    return AgentAction::Borrow {
+                        asset: LendingAsset::Zec,
+                        amount,
+                    };
+                }
+            }
+            AttackPhase::Done if self.zec_balance > 0.0 => {
+                let debt = market.debt_of(borrower, LendingAsset::Zec);
+                if debt > 0.0 {
+                    let pay = self.zec_balance.min(debt);
+                    if let Ok(amount) = market.repay(borrower, LendingAsset::Zec, pay, block) {
+                        self.zec_balance -= amount;
+                        return AgentAction::Repay {
+                            asset: LendingAsset::Zec,
+                            amount,
                         };
                     }
                 }
-                AgentAction::None
+            }
+            _ => {}
+        }
+        AgentAction::None
+    }
+
+    pub fn act(&mut self, amm: &mut Amm, block: u64) -> AgentAction {
+        if self.phase == AttackPhase::Done {
+            return AgentAction::None;
+        }
+        let action = match self.config.strategy.clone() {
+            AttackStrategy::DumpAndRevert => self.dump_and_revert(amm, block),
+            AttackStrategy::Drip {
+                zec_per_block,
+                blocks,
+                unwind_blocks,
+            } => self.drip(amm, block, zec_per_block, blocks, unwind_blocks),
+            AttackStrategy::TwapOscillation {
+                swing_zec,
+                half_period,
+                cycles,
+            } => self.oscillate(amm, block, swing_zec, half_period, cycles),
+            AttackStrategy::LiquidationHunt {
+                buy_drop_pct,
+                max_hold_blocks,
+            } => self.hunt(amm, block, buy_drop_pct, max_hold_blocks),
+            AttackStrategy::PegSqueeze {
+                target_premium_pct,
+                zec_per_block,
+                hold_blocks,
+            } => self.squeeze(amm, block, target_premium_pct, zec_per_block, hold_blocks),
+        };
+        action.unwrap_or(AgentAction::None)
+    }
+
+    /// Enter the manipulating phase at the attack block, with `revert_at_block`
+    /// `active_blocks` later. Returns the phase's revert block while active.
+    fn start(&mut self, amm: &Amm, block: u64, active_blocks: u64) -> Option<u64> {
+        if self.phase == AttackPhase::Idle {
+            if block < self.config.attack_at_block {
+                return None;
+            }
+            self.reference_spot = amm.spot_price();
+            self.phase = AttackPhase::Manipulating {
+                revert_at_block: block + active_blocks,
+            };
+        }
+        match self.phase {
+            AttackPhase::Manipulating { revert_at_block } => Some(revert_at_block),
+            _ => None,
+        }
+    }
+
+    fn dump_and_revert(&mut self, amm: &mut Amm, block: u64) -> Option<AgentAction> {
+        match self.phase {
+            AttackPhase::Idle if block >= self.config.attack_at_block => {
+                // Phase 1: dump ZEC on AMM to crash price
+                let action = self.dump(amm, self.zec_balance, block)?;
+                self.phase = AttackPhase::Manipulating {
+                    revert_at_block: block + self.config.hold_blocks,
+                };
+                Some(action)
+            }
+            AttackPhase::Manipulating { revert_at_block } if block >= revert_at_block => {
+                // Phase 2: buy back ZEC with the ZAI received
+                let action = self.buy_back(amm, self.zai_received_from_attack, block)?;
+                self.phase = AttackPhase::Done;
+                Some(action)
+            }
+            _ => None,
+        }
+    }
+
+    fn drip(
+        &mut self,
+        amm: &mut Amm,
+        block: u64,
+        zec_per_block: f64,
+        blocks: u64,
+        unwind_blocks: u64,
+    ) -> Option<AgentAction> {
+        let revert_at_block = self.start(amm, block, blocks)?;
+        if block < revert_at_block {
+            return self.dump(amm, zec_per_block, block);
+        }
+        // Unwind in equal parts of what is left
+        let end = revert_at_block + unwind_blocks.max(1);
+        let remaining = end.saturating_sub(block).max(1);
+        let action = self.buy_back(amm, self.zai_balance / remaining as f64, block);
+        if block + 1 >= end || self.zai_balance <= 0.0 {
+            self.phase = AttackPhase::Done;
+        }
+        action
+    }
+
+    fn oscillate(
+        &mut self,
+        amm: &mut Amm,
+        block: u64,
+        swing_zec: f64,
+        half_period: u64,
+        cycles: u32,
+    ) -> Option<AgentAction> {
+        let flip_at_block = self.start(amm, block, 0)?;
+        if block < flip_at_block {
+            return None;
+        }
+        let action = if self.swings_done.is_multiple_of(2) {
+            self.dump(amm, swing_zec, block)
+        } else {
+            let action = self.buy_back(amm, self.zai_received_from_attack, block);
+            self.zai_received_from_attack = 0.0;
+            action
+        };
+        self.swings_done += 1;
+        self.phase = if self.swings_done >= 2 * cycles {
+            AttackPhase::Done
+        } else {
+            AttackPhase::Manipulating {
+                revert_at_block: block + half_period,
+            }
+        };
+        action
+    }
+
+    fn hunt(
+        &mut self,
+        amm: &mut Amm,
+        block: u64,
+        buy_drop_pct: f64,
+        max_hold_blocks: u64,
+    ) -> Option<AgentAction> {
+        match self.phase {
+            AttackPhase::Idle if block >= self.config.attack_at_block => {
+                let action = self.dump(amm, self.zec_balance, block)?;
+                // The cascade is measured from where the dump left spot
+                self.reference_spot = amm.spot_price();
+                self.phase = AttackPhase::Manipulating {
+                    revert_at_block: block + max_hold_blocks,
+                };
+                Some(action)
             }
             AttackPhase::Manipulating { revert_at_block } => {
-                if block >= *revert_at_block {
-                    // Phase 2: buy back ZEC with the ZAI received
-                    let spend = self.zai_received_from_attack.min(self.zai_balance);
-                    if let Ok(zec_out) = amm.swap_zai_for_zec(spend, block) {
-                        self.zai_balance -= spend;
-                        self.zec_balance += zec_out;
-                        self.phase = AttackPhase::Done;
-                        return AgentAction::AttackSwap {
-                            direction: "buy_zec".to_string(),
-                            amount: spend,
-                        };
-                    }
+                let target = self.reference_spot * (1.0 - buy_drop_pct / 100.0);
+                if block < revert_at_block && amm.spot_price() > target {
+                    return None;
                 }
-                AgentAction::None
+                let action = self.buy_back(amm, self.zai_balance, block)?;
+                self.phase = AttackPhase::Done;
+                Some(action)
+            }
+            _ => None,
+        }
+    }
+
+    fn squeeze(
+        &mut self,
+        amm: &mut Amm,
+        block: u64,
+        target_premium_pct: f64,
+        zec_per_block: f64,
+        hold_blocks: u64,
+    ) -> Option<AgentAction> {
+        let revert_at_block = self.start(amm, block, hold_blocks)?;
+        if block < revert_at_block {
+            // ZAI's premium is how many fewer ZAI one ZEC now buys
+            let target = self.reference_spot / (1.0 + target_premium_pct / 100.0);
+            if amm.spot_price() <= target {
+                return None;
             }
-            AttackPhase::Done => AgentAction::None,
+            return self.dump(amm, zec_per_block, block);
+        }
+        let action = self.buy_back(amm, self.zai_balance, block)?;
+        self.phase = AttackPhase::Done;
+        Some(action)
+    }
+
+    /// Sell up to `zec` on the AMM for ZAI.
+    fn dump(&mut self, amm: &mut Amm, zec: f64, block: u64) -> Option<AgentAction> {
+        let spend = zec.min(self.zec_balance);
+        if spend <= 0.0 {
+            return None;
+        }
+        let zai_out = amm.swap_zec_for_zai(spend, block).ok()?;
+        self.zec_balance -= spend;
+        self.zai_balance += zai_out;
+        self.zai_received_from_attack += zai_out;
+        Some(AgentAction::AttackSwap {
+            direction: "sell_zec".to_string(),
+            amount: spend,
+        })
+    }
+
+    /// Spend up to `zai` on the AMM buying ZEC back.
+    fn buy_back(&mut self, amm: &mut Amm, zai: f64, block: u64) -> Option<AgentAction> {
+        let spend = zai.min(self.zai_balance);
+        if spend <= 0.0 {
+            return None;
+        }
+        let zec_out = amm.swap_zai_for_zec(spend, block).ok()?;
+        self.zai_balance -= spend;
+        self.zec_balance += zec_out;
+        Some(AgentAction::AttackSwap {
+            direction: "buy_zec".to_string(),
+            amount: spend,
+        })
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+// 8. Redeemer
+// ═══════════════════════════════════════════════════════════════════════
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedeemerConfig {
+    pub initial_zec_balance: f64,
+    /// Minimum ZAI discount to par (%) before redeeming
+    pub redeem_threshold_pct: f64,
+    /// Fraction of ZEC balance spent buying ZAI per opportunity
+    pub max_trade_pct: f64,
+}
+
+impl Default for RedeemerConfig {
+    fn default() -> Self {
+        RedeemerConfig {
+            initial_zec_balance: 2000.0,
+            redeem_threshold_pct: 0.5,
+            max_trade_pct: 0.1,
+        }
+    }
+}
+
+/// Buys ZAI on the AMM when it trades below par and redeems it against the
+/// lowest-CR vaults for ZEC at face value.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RedeemerAgent {
+    pub config: RedeemerConfig,
+    pub zec_balance: f64,
+    pub zai_balance: f64,
+    pub total_redeemed_zai: f64,
+    pub total_profit_zec: f64,
+}
+
+impl RedeemerAgent {
+    pub fn new(config: RedeemerConfig) -> Self {
+        let zec = config.initial_zec_balance;
+        RedeemerAgent {
+            config,
+            zec_balance: zec,
+            zai_balance: 0.0,
+            total_redeemed_zai: 0.0,
+            total_profit_zec: 0.0,
+        }
+    }
+
+    pub fn act(
+        &mut self,
+        amm: &mut Amm,
+        registry: &mut VaultRegistry,
+        engine: &mut LiquidationEngine,
+        redemption_price: f64,
+        block: u64,
+    ) -> AgentAction {
+        // ZAI below par: one ZEC buys more ZAI on the AMM than redemption_price
+        let discount_pct =
+            ((amm.spot_price() - redemption_price) / redemption_price) * 100.0;
+        if discount_pct <= self.config.redeem_threshold_pct {
+            return AgentAction::None;
+        }
+
+        let zec_in = self.zec_balance * self.config.max_trade_pct;
+        if zec_in < 0.01 {
+            return AgentAction::None;
+        }
+
+        // Profitability check: ZEC back from redemption must exceed ZEC spent
+        let fee = engine.config.redemption_fee_pct;
+        let expected_zec = amm.quote_zec_for_zai(zec_in) / redemption_price * (1.0 - fee);
+        if expected_zec <= zec_in {
+            return AgentAction::None;
+        }
+
+        let zai_out = match amm.swap_zec_for_zai(zec_in, block) {
+            Ok(z) => z,
+            Err(_) => return AgentAction::None,
+        };
+        self.zec_balance -= zec_in;
+        self.zai_balance += zai_out;
+
+        match engine.redeem(
+            "redeemer",
+            self.zai_balance,
+            redemption_price,
+            registry,
+            amm,
+            block,
+        ) {
+            Ok(result) => {
+                self.zai_balance -= result.zai_redeemed;
+                self.zec_balance += result.zec_received;
+                self.total_redeemed_zai += result.zai_redeemed;
+                self.total_profit_zec += result.zec_received
+                    - zec_in * (result.zai_redeemed / zai_out).min(1.0);
+                AgentAction::Redeem {
+                    zai_redeemed: result.zai_redeemed,
+                    zec_received: result.zec_received,
+                }
+            }
+            Err(_) => AgentAction::None,
+        }
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+// 9. Basis Trader
+// ═══════════════════════════════════════════════════════════════════════
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BasisTraderConfig {
+    pub initial_zec_balance: f64,
+    pub initial_zai_balance: f64,
+    /// Basis (% gap between AMM spot and redemption price) needed to open a position
+    pub entry_threshold_pct: f64,
+    /// Basis below which the position is unwound back to the starting inventory
+    pub exit_threshold_pct: f64,
+    /// Fraction of the relevant balance traded per block
+    pub max_trade_pct: f64,
+    /// Only enter when the redemption rate already pushes toward convergence
+    pub require_rate_confirmation: bool,
+}
+
+impl Default for BasisTraderConfig {
+    fn default() -> Self {
+        BasisTraderConfig {
+            initial_zec_balance: 2000.0,
+            initial_zai_balance: 100_000.0,
+            entry_threshold_pct: 1.0,
+            exit_threshold_pct: 0.25,
+            max_trade_pct: 0.05,
+            require_rate_confirmation: true,
+        }
+    }
+}
+
+/// Trades the redemption-price vs market-price basis rather than the
+/// external ZEC price: buys ZAI when it trades below redemption (AMM spot
+/// above `redemption_price`) and sells it when above, expecting the
+/// controller's redemption rate to pull the market back. Positions are
+/// unwound once the basis closes.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BasisTrader {
+    pub config: BasisTraderConfig,
+    pub zec_balance: f64,
+    pub zai_balance: f64,
+    pub trade_count: u64,
+}
+
+impl BasisTrader {
+    pub fn new(config: BasisTraderConfig) -> Self {
+        let zec = config.initial_zec_balance;
+        let zai = config.initial_zai_balance;
+        BasisTrader {
+            config,
+            zec_balance: zec,
+            zai_balance: zai,
+            trade_count: 0,
+        }
+    }
+
+    /// Net ZAI held beyond the starting inventory (negative = short ZAI).
+    pub fn zai_position(&self) -> f64 {
+        self.zai_balance - self.config.initial_zai_balance
+    }
+
+    /// Portfolio value in ZEC, marking ZAI at the redemption price.
+    pub fn value_zec(&self, redemption_price: f64) -> f64 {
+        self.zec_balance + self.zai_balance / redemption_price
+    }
+
+    /// Profit in ZEC relative to the starting inventory, both marked at
+    /// `redemption_price`.
+    pub fn pnl_zec(&self, redemption_price: f64) -> f64 {
+        let initial = self.config.initial_zec_balance
+            + self.config.initial_zai_balance / redemption_price;
+        self.value_zec(redemption_price) - initial
+    }
+
+    pub fn act(
+        &mut self,
+        amm: &mut Amm,
+        redemption_price: f64,
+        redemption_rate: f64,
+        block: u64,
+    ) -> AgentAction {
+        // Positive basis: one ZEC buys more ZAI on the AMM than at redemption,
+        // i.e. ZAI is cheap
+        let basis_pct = ((amm.spot_price() - redemption_price) / redemption_price) * 100.0;
+        let position = self.zai_position();
+        let confirmed = |expected_sign: f64| {
+            !self.config.require_rate_confirmation || redemption_rate * expected_sign >= 0.0
+        };
+
+        // Positive basis pushes the rate negative, raising ZAI's redemption value
+        if basis_pct > self.config.entry_threshold_pct && confirmed(-1.0) {
+            return self.buy_zai(amm, self.zec_balance * self.config.max_trade_pct, block);
+        }
+        if basis_pct < -self.config.entry_threshold_pct && confirmed(1.0) {
+            return self.sell_zai(amm, self.zai_balance * self.config.max_trade_pct, block);
+        }
+
+        // Basis closed: unwind back toward the starting inventory
+        if basis_pct.abs() < self.config.exit_threshold_pct {
+            let step = self.config.initial_zai_balance * self.config.max_trade_pct;
+            if position > 0.01 {
+                return self.sell_zai(amm, position.min(step), block);
+            }
+            if position < -0.01 {
+                let zec_in = amm.quote_zai_for_zec(-position).min(self.zec_balance);
+                let max_zec = self.zec_balance * self.config.max_trade_pct;
+                return self.buy_zai(amm, zec_in.min(max_zec), block);
+            }
+        }
+
+        AgentAction::None
+    }
+
+    fn buy_zai(&mut self, amm: &mut Amm, zec_in: f64, block: u64) -> AgentAction {
+        if zec_in < 0.01 {
+            return AgentAction::None;
+        }
+        match amm.swap_zec_for_zai(zec_in, block) {
+            Ok(zai_out) => {
+                self.zec_balance -= zec_in;
+                self.zai_balance += zai_out;
+                self.trade_count += 1;
+                AgentAction::SellZec {
+                    zec_spent: zec_in,
+                    zai_received: zai_out,
+                }
+            }
+            Err(_) => AgentAction::None,
+        }
+    }
+
+    fn sell_zai(&mut self, amm: &mut Amm, zai_in: f64, block: u64) -> AgentAction {
+        if zai_in < 0.01 {
+            return AgentAction::None;
+        }
+        match amm.swap_zai_for_zec(zai_in, block) {
+            Ok(zec_out) => {
+                self.zai_balance -= zai_in;
+                self.zec_balance += zec_out;
+                self.trade_count += 1;
+                AgentAction::BuyZec {
+                    zai_spent: zai_in,
+                    zec_received: zec_out,
+                }
+            }
+            Err(_) => AgentAction::None,
+        }
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+// 10. Saver
+// ═══════════════════════════════════════════════════════════════════════
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaverAgentConfig {
+    pub initial_zec_balance: f64,
+    pub initial_zai_balance: f64,
+    /// Annual savings rate needed to keep ZAI locked
+    pub min_rate: f64,
+    /// ZAI premium over redemption (%) at which selling on the AMM beats
+    /// the savings rate
+    pub exit_premium_pct: f64,
+    /// Fraction of the relevant balance moved per block
+    pub max_trade_pct: f64,
+}
+
+impl Default for SaverAgentConfig {
+    fn default() -> Self {
+        SaverAgentConfig {
+            initial_zec_balance: 1000.0,
+            initial_zai_balance: 50_000.0,
+            min_rate: 0.02,
+            exit_premium_pct: 1.0,
+            max_trade_pct: 0.1,
+        }
+    }
+}
+
+/// Holds ZAI in the savings module while the rate is worth it, buying more
+/// with ZEC when ZAI trades at or below redemption. When the rate drops
+/// below `min_rate` or ZAI trades at a premium it withdraws, and sells the
+/// premium on the AMM.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SaverAgent {
+    pub config: SaverAgentConfig,
+    pub zec_balance: f64,
+    /// ZAI outside the savings module
+    pub zai_balance: f64,
+    /// Savings module shares held
+    pub savings_shares: f64,
+    pub trade_count: u64,
+}
+
+impl SaverAgent {
+    pub fn new(config: SaverAgentConfig) -> Self {
+        let zec = config.initial_zec_balance;
+        let zai = config.initial_zai_balance;
+        SaverAgent {
+            config,
+            zec_balance: zec,
+            zai_balance: zai,
+            savings_shares: 0.0,
+            trade_count: 0,
+        }
+    }
+
+    /// ZAI in the wallet and in savings.
+    pub fn total_zai(&self, savings: &SavingsModule) -> f64 {
+        self.zai_balance + savings.balance_of(self.savings_shares)
+    }
+
+    pub fn act(
+        &mut self,
+        amm: &mut Amm,
+        savings: &mut SavingsModule,
+        redemption_price: f64,
+        block: u64,
+    ) -> AgentAction {
+        // Positive premium: one ZEC buys fewer ZAI on the AMM than at redemption
+        let premium_pct = ((redemption_price - amm.spot_price()) / redemption_price) * 100.0;
+        let saving =
+            savings.rate >= self.config.min_rate && premium_pct < self.config.exit_premium_pct;
+
+        if saving {
+            if self.zai_balance > 0.01 {
+                return self.deposit(savings, self.zai_balance);
+            }
+            if premium_pct <= 0.0 && self.zec_balance > 0.01 {
+                return self.buy_zai(amm, self.zec_balance * self.config.max_trade_pct, block);
+            }
+            return AgentAction::None;
+        }
+
+        if self.savings_shares > 0.0 {
+            let step = self.config.initial_zai_balance * self.config.max_trade_pct / savings.chi;
+            return self.withdraw(savings, step.min(self.savings_shares));
+        }
+        if premium_pct > self.config.exit_premium_pct {
+            return self.sell_zai(amm, self.zai_balance * self.config.max_trade_pct, block);
+        }
+        AgentAction::None
+    }
+
+    fn deposit(&mut self, savings: &mut SavingsModule, zai: f64) -> AgentAction {
+        match savings.deposit(zai) {
+            Ok(shares) => {
+                self.zai_balance -= zai;
+                self.savings_shares += shares;
+                AgentAction::SavingsDeposit { zai, shares }
+            }
+            Err(_) => AgentAction::None,
+        }
+    }
+
+    fn withdraw(&mut self, savings: &mut SavingsModule, shares: f64) -> AgentAction {
+        match savings.withdraw(shares) {
+            Ok(zai) => {
+                self.savings_shares -= shares;
+                self.zai_balance += zai;
+                AgentAction::SavingsWithdraw { zai, shares }
+            }
+            Err(_) => AgentAction::None,
+        }
+    }
+
+    fn buy_zai(&mut self, amm: &mut Amm, zec_in: f64, block: u64) -> AgentAction {
+        if zec_in < 0.01 {
+            return AgentAction::None;
+        }
+        match amm.sell_zec(zec_in, block) {
+            Ok(zai_out) => {
+                self.zec_balance -= zec_in;
+                self.zai_balance += zai_out;
+                self.trade_count += 1;
+                AgentAction::BuyZai {
+                    zec_spent: zec_in,
+                    zai_received: zai_out,
+                }
+            }
+            Err(_) => AgentAction::None,
+        }
+    }
+
+    fn sell_zai(&mut self, amm: &mut Amm, zai_in: f64, block: u64) -> AgentAction {
+        if zai_in < 0.01 {
+            return AgentAction::None;
+        }
+        match amm.buy_zec(zai_in, block) {
+            Ok(zec_out) => {
+                self.zai_balance -= zai_in;
+                self.zec_balance += zec_out;
+                self.trade_count += 1;
+                AgentAction::BuyZec {
+                    zai_spent: zai_in,
+                    zec_received: zec_out,
+                }
+            }
+            Err(_) => AgentAction::None,
+        }
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+// 11. Noise Trader
+// ═══════════════════════════════════════════════════════════════════════
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoiseTraderConfig {
+    pub initial_zec_balance: f64,
+    pub initial_zai_balance: f64,
+    /// Chance of trading in a given block
+    pub trade_probability: f64,
+    /// ZAI value of a trade at unit signal strength
+    pub trade_size_zai: f64,
+    /// Weight on a standard normal draw
+    pub noise_weight: f64,
+    /// Weight on the AMM spot return over `lookback_blocks`, in percent.
+    /// Positive follows the trend, negative fades it.
+    pub momentum_weight: f64,
+    /// Weight on the population's net direction in the previous block (-1 to 1)
+    pub herding_weight: f64,
+    pub lookback_blocks: usize,
+    /// Cap on the signal, in multiples of `trade_size_zai`
+    pub max_signal: f64,
+}
+
+impl Default for NoiseTraderConfig {
+    fn default() -> Self {
+        NoiseTraderConfig {
+            initial_zec_balance: 1000.0,
+            initial_zai_balance: 50_000.0,
+            trade_probability: 0.2,
+            trade_size_zai: 500.0,
+            noise_weight: 1.0,
+            momentum_weight: 0.0,
+            herding_weight: 0.0,
+            lookback_blocks: 20,
+            max_signal: 3.0,
+        }
+    }
+}
+
+/// A population of identically configured noise traders spawned with the
+/// scenario, so stress runs and Monte Carlo sweeps get a fresh one per seed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoiseTraderPopulation {
+    pub count: usize,
+    pub trader: NoiseTraderConfig,
+}
+
+impl Default for NoiseTraderPopulation {
+    fn default() -> Self {
+        NoiseTraderPopulation {
+            count: 10,
+            trader: NoiseTraderConfig::default(),
+        }
+    }
+}
+
+impl NoiseTraderPopulation {
+    /// Create the traders, each on its own stream of `seed`.
+    pub fn spawn(&self, seed: u64) -> Vec<NoiseTrader> {
+        (0..self.count)
+            .map(|i| NoiseTrader::new(self.trader.clone(), seed.wrapping_add(i as u64)))
+            .collect()
+    }
+}
+
+/// Trades on noise, trend and the crowd rather than value. Each block it
+/// trades with `trade_probability`; the signal mixes a normal draw, the
+/// recent AMM return and the population's last move. A positive signal
+/// buys ZEC with ZAI, a negative one sells ZEC. Seeded per agent, so runs
+/// reproduce and Monte Carlo paths differ only by seed.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NoiseTrader {
+    pub config: NoiseTraderConfig,
+    pub zec_balance: f64,
+    pub zai_balance: f64,
+    pub trade_count: u64,
+    /// Direction of this block's trade: 1 bought ZEC, -1 sold ZEC, 0 idle
+    pub last_direction: f64,
+    price_history: VecDeque<f64>,
+    rng: ChaCha12Rng,
+}
+
+impl NoiseTrader {
+    pub fn new(config: NoiseTraderConfig, seed: u64) -> Self {
+        let zec = config.initial_zec_balance;
+        let zai = config.initial_zai_balance;
+        NoiseTrader {
+            config,
+            zec_balance: zec,
+            zai_balance: zai,
+            trade_count: 0,
+            last_direction: 0.0,
+            price_history: VecDeque::new(),
+            rng: ChaCha12Rng::seed_from_u64(seed),
+        }
+    }
+
+    /// AMM spot return over the lookback window in percent, 0 until the
+    /// window has filled.
+    pub fn trend_pct(&self) -> f64 {
+        match (self.price_history.front(), self.price_history.back()) {
+            (Some(first), Some(last)) if self.price_history.len() > self.config.lookback_blocks => {
+                (last / first - 1.0) * 100.0
+            }
+            _ => 0.0,
+        }
+    }
+
+    /// Trade for this block. `herd` is the population's net direction in
+    /// the previous block.
+    pub fn act(&mut self, amm: &mut Amm, herd: f64, block: u64) -> AgentAction {
+        let spot = amm.spot_price();
+        self.price_history.push_back(spot);
+        while self.price_history.len() > self.config.lookback_blocks + 1 {
+            self.price_history.pop_front();
+        }
+        self.last_direction = 0.0;
+
+        if self.rng.gen::<f64>() >= self.config.trade_probability {
+            return AgentAction::None;
+        }
+        let noise: f64 = self.rng.sample(StandardNormal);
+        let max = self.config.max_signal;
+        let signal = (self.config.noise_weight * noise
+            + self.config.momentum_weight * self.trend_pct()
+            + self.config.herding_weight * herd)
+            .clamp(-max, max);
+
+        if signal > 0.0 {
+            let zai_in = (self.config.trade_size_zai * signal).min(self.zai_balance);
+            self.sell_zai(amm, zai_in, block)
+        } else {
+            let zec_in = (self.config.trade_size_zai * -signal / spot).min(self.zec_balance);
+            self.buy_zai(amm, zec_in, block)
+        }
+    }
+
+    fn buy_zai(&mut self, amm: &mut Amm, zec_in: f64, block: u64) -> AgentAction {
+        if zec_in < 0.01 {
+            return AgentAction::None;
+        }
+        match amm.sell_zec(zec_in, block) {
+            Ok(zai_out) => {
+                self.zec_balance -= zec_in;
+                self.zai_balance += zai_out;
+                self.trade_count += 1;
+                self.last_direction = -1.0;
+                AgentAction::BuyZai {
+                    zec_spent: zec_in,
+                    zai_received: zai_out,
+                }
+            }
+            Err(_) => AgentAction::None,
+        }
+    }
+
+    fn sell_zai(&mut self, amm: &mut Amm, zai_in: f64, block: u64) -> AgentAction {
+        if zai_in < 0.01 {
+            return AgentAction::None;
+        }
+        match amm.buy_zec(zai_in, block) {
+            Ok(zec_out) => {
+                self.zai_balance -= zai_in;
+                self.zec_balance += zec_out;
+                self.trade_count += 1;
+                self.last_direction = 1.0;
+                AgentAction::BuyZec {
+                    zai_spent: zai_in,
+                    zec_received: zec_out,
+                }
+            }
+            Err(_) => AgentAction::None,
+        }
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+// 12. Basis Arbitrageur
+// ═══════════════════════════════════════════════════════════════════════
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BasisArbConfig {
+    pub initial_zec_balance: f64,
+    pub initial_zai_balance: f64,
+    /// Perp funding rate per interval at which a hedged position is opened
+    pub entry_funding_rate: f64,
+    /// Funding rate magnitude below which positions are unwound
+    pub exit_funding_rate: f64,
+    /// Largest perp position either way (ZEC)
+    pub max_position_zec: f64,
+    /// ZEC traded per block while opening or unwinding
+    pub max_trade_zec: f64,
+}
+
+impl Default for BasisArbConfig {
+    fn default() -> Self {
+        BasisArbConfig {
+            initial_zec_balance: 1000.0,
+            initial_zai_balance: 50_000.0,
+            entry_funding_rate: 0.0003,
+            exit_funding_rate: 0.00005,
+            max_position_zec: 2000.0,
+            max_trade_zec: 25.0,
+        }
+    }
+}
+
+/// Cash-and-carry against the ZEC perp: while funding is rich it buys ZEC
+/// on the AMM with ZAI and shorts the same amount of perp, collecting
+/// funding from longs; while funding is negative it sells ZEC on the AMM
+/// and goes long. Positions are unwound once funding fades. Perp margin,
+/// funding and realized PnL settle off-chain in `margin_zai`, at the
+/// external (index) price.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BasisArbAgent {
+    pub config: BasisArbConfig,
+    pub zec_balance: f64,
+    pub zai_balance: f64,
+    /// Perp position in ZEC (negative = short)
+    pub perp_zec: f64,
+    /// Average entry price of the open perp position
+    pub perp_entry_price: f64,
+    /// Off-chain margin account: funding and realized perp PnL (ZAI)
+    pub margin_zai: f64,
+    /// Funding received so far (ZAI; negative = paid)
+    pub funding_zai: f64,
+    pub trade_count: u64,
+}
+
+impl BasisArbAgent {
+    pub fn new(config: BasisArbConfig) -> Self {
+        let zec = config.initial_zec_balance;
+        let zai = config.initial_zai_balance;
+        BasisArbAgent {
+            config,
+            zec_balance: zec,
+            zai_balance: zai,
+            perp_zec: 0.0,
+            perp_entry_price: 0.0,
+            margin_zai: 0.0,
+            funding_zai: 0.0,
+            trade_count: 0,
+        }
+    }
+
+    /// Open perp PnL at `index_price` (ZAI).
+    pub fn unrealized_pnl_zai(&self, index_price: f64) -> f64 {
+        self.perp_zec * (index_price - self.perp_entry_price)
+    }
+
+    /// Wallet, margin and open perp PnL at `index_price` (ZAI).
+    pub fn value_zai(&self, index_price: f64) -> f64 {
+        self.zec_balance * index_price
+            + self.zai_balance
+            + self.margin_zai
+            + self.unrealized_pnl_zai(index_price)
+    }
+
+    /// Settle one funding payment at `rate` on the position's notional.
+    /// Returns the ZAI received (negative = paid).
+    pub fn receive_funding(&mut self, rate: f64, index_price: f64) -> f64 {
+        let amount = -self.perp_zec * index_price * rate;
+        self.margin_zai += amount;
+        self.funding_zai += amount;
+        amount
+    }
+
+    pub fn act(
+        &mut self,
+        amm: &mut Amm,
+        funding_rate: f64,
+        index_price: f64,
+        block: u64,
+    ) -> AgentAction {
+        let max_position = self.config.max_position_zec;
+        let step = self.config.max_trade_zec;
+
+        // Longs pay shorts: hold spot, short the perp
+        if funding_rate > self.config.entry_funding_rate && self.perp_zec > -max_position {
+            let zec = step.min(max_position + self.perp_zec);
+            return self.buy_spot(amm, zec * amm.spot_price(), index_price, block);
+        }
+        // Shorts pay longs: sell spot, long the perp
+        if funding_rate < -self.config.entry_funding_rate && self.perp_zec < max_position {
+            let zec = step.min(max_position - self.perp_zec);
+            return self.sell_spot(amm, zec, index_price, block);
+        }
+
+        // Funding faded: unwind both legs
+        if funding_rate.abs() < self.config.exit_funding_rate {
+            if self.perp_zec < -0.01 {
+                return self.sell_spot(amm, step.min(-self.perp_zec), index_price, block);
+            }
+            if self.perp_zec > 0.01 {
+                let zec = step.min(self.perp_zec);
+                return self.buy_spot(amm, zec * amm.spot_price(), index_price, block);
+            }
+        }
+
+        AgentAction::None
+    }
+
+    /// Buy ZEC on the AMM with `zai_in` and sell as much perp.
+    fn buy_spot(
+        &mut self,
+        amm: &mut Amm,
+        zai_in: f64,
+        index_price: f64,
+        block: u64,
+    ) -> AgentAction {
+        let zai_in = zai_in.min(self.zai_balance);
+        if zai_in < 0.01 {
+            return AgentAction::None;
+        }
+        match amm.swap_zai_for_zec(zai_in, block) {
+            Ok(zec_out) => {
+                self.zai_balance -= zai_in;
+                self.zec_balance += zec_out;
+                self.trade_perp(-zec_out, index_price);
+                AgentAction::BuyZec {
+                    zai_spent: zai_in,
+                    zec_received: zec_out,
+                }
+            }
+            Err(_) => AgentAction::None,
+        }
+    }
+
+    /// Sell `zec_in` ZEC on the AMM and buy as much perp.
+    fn sell_spot(
+        &mut self,
+        amm: &mut Amm,
+        zec_in: f64,
+        index_price: f64,
+        block: u64,
+    ) -> AgentAction {
+        let zec_in = zec_in.min(self.zec_balance);
+        if zec_in < 0.01 {
+            return AgentAction::None;
+        }
+        match amm.swap_zec_for_zai(zec_in, block) {
+            Ok(zai_out) => {
+                self.zec_balance -= zec_in;
+                self.zai_balance += zai_out;
+                self.trade_perp(zec_in, index_price);
+                AgentAction::SellZec {
+                    zec_spent: zec_in,
+                    zai_received: zai_out,
+                }
+            }
+            Err(_) => AgentAction::None,
+        }
+    }
+
+    /// Change the perp position by `zec` at `price`, realizing PnL on any
+    /// size closed into the margin account.
+    fn trade_perp(&mut self, zec: f64, price: f64) {
+        let old = self.perp_zec;
+        let new = old + zec;
+        if old == 0.0 || old.signum() == zec.signum() {
+            self.perp_entry_price =
+                (old.abs() * self.perp_entry_price + zec.abs() * price) / new.abs();
+        } else {
+            let closed = zec.abs().min(old.abs());
+            self.margin_zai += closed * (price - self.perp_entry_price) * old.signum();
+            if zec.abs() > old.abs() {
+                self.perp_entry_price = price;
+            }
+        }
+        self.perp_zec = new;
+        self.trade_count += 1;
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+// 13. Vault Portfolio
+// ═══════════════════════════════════════════════════════════════════════
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VaultPortfolioConfig {
+    /// Collateral ratio each vault opens at, one vault per entry; a single
+    /// entry is a concentrated position
+    pub vault_ratios: Vec<f64>,
+    /// Debt drawn in each vault
+    pub debt_per_vault: f64,
+    /// ZEC available to add as collateral
+    pub reserve_zec: f64,
+    /// Vaults below this ratio get topped up, riskiest first
+    pub action_threshold_ratio: f64,
+    /// Ratio a top-up brings a vault back to
+    pub target_ratio: f64,
+    /// Once reserves run out, fold a vault still below the threshold into
+    /// the owner's healthiest one
+    pub consolidate: bool,
+}
+
+impl Default for VaultPortfolioConfig {
+    fn default() -> Self {
+        VaultPortfolioConfig {
+            vault_ratios: vec![1.8, 2.5, 3.5],
+            debt_per_vault: 1000.0,
+            reserve_zec: 100.0,
+            action_threshold_ratio: 1.7,
+            target_ratio: 2.2,
+            consolidate: true,
+        }
+    }
+}
+
+/// An owner running several vaults at different collateral ratios from one
+/// reserve. Spread across ratios, a price drop reaches the owner's vaults
+/// one at a time instead of all at once; the owner defends the riskiest
+/// first and, out of reserves, pools collateral by merging vaults.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VaultPortfolio {
+    pub config: VaultPortfolioConfig,
+    /// Owner name the vaults are registered under
+    pub owner: String,
+    pub opened: bool,
+    pub reserve_zec: f64,
+    pub topups: u32,
+    pub merges: u32,
+}
+
+impl VaultPortfolio {
+    pub fn new(config: VaultPortfolioConfig, owner: &str) -> Self {
+        let reserve = config.reserve_zec;
+        VaultPortfolio {
+            config,
+            owner: owner.to_string(),
+            opened: false,
+            reserve_zec: reserve,
+            topups: 0,
+            merges: 0,
+        }
+    }
+
+    /// Open one vault per configured ratio, each drawing `debt_per_vault`.
+    /// Call once at simulation start.
+    pub fn open_vaults(
+        &mut self,
+        registry: &mut VaultRegistry,
+        amm: &Amm,
+        block: u64,
+    ) -> Result<Vec<u64>, ZaiSimError> {
+        let price = registry.get_price(amm);
+        let debt = self.config.debt_per_vault;
+        let mut ids = Vec::with_capacity(self.config.vault_ratios.len());
+        for ratio in &self.config.vault_ratios {
+            ids.push(registry.open_vault(&self.owner, ratio * debt / price, debt, block, amm)?);
+        }
+        self.opened = true;
+        Ok(ids)
+    }
+
+    pub fn act(&mut self, registry: &mut VaultRegistry, amm: &Amm, block: u64) -> AgentAction {
+        let price = registry.get_price(amm);
+        let at_risk: Vec<u64> = registry
+            .vaults_of_by_ratio(&self.owner, price)
+            .into_iter()
+            .take_while(|id| {
+                registry.vault_at(*id, block).is_some_and(|v| {
+                    v.collateral_ratio(price) < self.config.action_threshold_ratio
+                })
+            })
+            .collect();
+        let Some(&riskiest) = at_risk.first() else {
+            return AgentAction::None;
+        };
+
+        // Top up the riskiest vaults first while reserves last
+        let mut added = 0.0;
+        for &id in &at_risk {
+            let Some(vault) = registry.vault_at(id, block) else {
+                continue;
+            };
+            let needed = (self.config.target_ratio * vault.debt_zai / price - vault.collateral_zec)
+                .max(0.0)
+                .min(self.reserve_zec);
+            if needed <= 0.01 || registry.deposit_collateral(id, needed).is_err() {
+                break;
+            }
+            self.reserve_zec -= needed;
+            self.topups += 1;
+            added += needed;
+        }
+        if added > 0.0 {
+            return AgentAction::CdpAction {
+                vault_id: riskiest,
+                description: format!("added {:.2} ZEC across the riskiest vaults", added),
+            };
+        }
+
+        // Out of reserves: pool the riskiest vault's collateral with the
+        // healthiest one
+        if self.config.consolidate {
+            let ranked = registry.vaults_of_by_ratio(&self.owner, price);
+            if let Some(&healthiest) = ranked.last().filter(|id| **id != riskiest) {
+                if registry.merge_vaults(riskiest, healthiest, block).is_ok() {
+                    self.merges += 1;
+                    return AgentAction::CdpAction {
+                        vault_id: healthiest,
+                        description: format!("merged vault {} into {}", riskiest, healthiest),
+                    };
+                }
+            }
+        }
+
+        AgentAction::CdpAction {
+            vault_id: riskiest,
+            description: format!("{} vaults at risk, no reserves to add", at_risk.len()),
         }
     }
 }
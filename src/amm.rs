@@ -1,13 +1,48 @@
 use std::collections::HashMap;
 
-#[derive(Debug, Clone)]
+use serde::{Deserialize, Serialize};
+
+use crate::error::ZaiSimError;
+use crate::fixed;
+use crate::order_book::OrderBook;
+use crate::pool::Pool;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PriceObservation {
     pub block: u64,
     pub cumulative_price: f64,
     pub spot_price: f64,
+    /// Cumulative block-weighted ln(spot), for the geometric TWAP
+    #[serde(default)]
+    pub cumulative_log_price: f64,
+    /// Cumulative swap volume on each side, for the volume-weighted price
+    #[serde(default)]
+    pub cumulative_volume_zec: f64,
+    #[serde(default)]
+    pub cumulative_volume_zai: f64,
+    /// Cumulative ZEC swapped into the pool minus ZEC swapped out
+    #[serde(default)]
+    pub cumulative_net_zec_in: f64,
 }
 
-#[derive(Debug)]
+/// How a TWAP averages the AMM's spot prices over its window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TwapKind {
+    /// Block-weighted arithmetic mean (`get_twap`)
+    #[default]
+    Arithmetic,
+    /// Block-weighted median; ignores spikes lasting under half the window
+    Median,
+    /// Block-weighted geometric mean, as in Uniswap v3; pushing it down
+    /// costs as much as pushing it up
+    Geometric,
+    /// Swap volume-weighted average execution price; arithmetic when the
+    /// window saw no swaps
+    VolumeWeighted,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Amm {
     pub reserve_zec: f64,
     pub reserve_zai: f64,
@@ -17,11 +52,93 @@ pub struct Amm {
     pub lp_shares: HashMap<String, f64>,
 
     cumulative_price: f64,
+    #[serde(default)]
+    cumulative_log_price: f64,
+    #[serde(default)]
+    cumulative_volume_zec: f64,
+    #[serde(default)]
+    cumulative_volume_zai: f64,
+    #[serde(default)]
+    cumulative_net_zec_in: f64,
     price_observations: Vec<PriceObservation>,
     last_update_block: u64,
 
     /// Total swap fees collected, denominated in ZAI-equivalent.
     pub cumulative_fees_zai: f64,
+
+    /// Graded-halt swap cap: max swap input as a fraction of the input-side reserve.
+    pub max_swap_fraction: Option<f64>,
+    /// Graded-halt LP withdrawal budget (shares) remaining for the current block.
+    pub lp_withdrawal_budget: Option<f64>,
+
+    /// Side pools that routed trades may use instead of or alongside this
+    /// one (see `routing`)
+    #[serde(default)]
+    pub pools: Vec<Pool>,
+    /// Limit order book quoting ZEC/ZAI beside the pool
+    #[serde(default)]
+    pub order_book: Option<OrderBook>,
+}
+
+/// Volatility-responsive swap fee. While the AMM's realized volatility or
+/// net flow over the trailing window exceeds its threshold the fee jumps to
+/// `max_fee`; afterwards it decays back toward the base fee.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DynamicFeeConfig {
+    /// Trailing blocks for realized volatility and net flow
+    pub window_blocks: u64,
+    /// Per-block realized volatility (std dev of log returns) that raises the fee
+    pub volatility_threshold: f64,
+    /// Net ZEC flow into or out of the pool, as a fraction of its ZEC
+    /// reserve, that raises the fee
+    pub net_flow_threshold: f64,
+    /// Fee while a threshold is exceeded
+    pub max_fee: f64,
+    /// Blocks for the premium over the base fee to halve once calm
+    pub decay_half_life_blocks: f64,
+}
+
+impl Default for DynamicFeeConfig {
+    fn default() -> Self {
+        DynamicFeeConfig {
+            window_blocks: 12, // ~15 minutes
+            volatility_threshold: 0.02,
+            net_flow_threshold: 0.05,
+            max_fee: 0.01,
+            decay_half_life_blocks: 12.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DynamicFee {
+    pub config: DynamicFeeConfig,
+    /// Current fee above the base fee
+    pub premium: f64,
+}
+
+impl DynamicFee {
+    pub fn new(config: DynamicFeeConfig) -> Self {
+        DynamicFee {
+            config,
+            premium: 0.0,
+        }
+    }
+
+    /// This block's fee given `amm`'s recent history and the `base_fee`.
+    pub fn update(&mut self, amm: &Amm, base_fee: f64) -> f64 {
+        let window = self.config.window_blocks;
+        let stressed = amm.realized_volatility(window) > self.config.volatility_threshold
+            || amm.net_flow_fraction(window).abs() > self.config.net_flow_threshold;
+        if stressed {
+            self.premium = (self.config.max_fee - base_fee).max(self.premium);
+        } else if self.config.decay_half_life_blocks > 0.0 {
+            self.premium *= 0.5f64.powf(1.0 / self.config.decay_half_life_blocks);
+        } else {
+            self.premium = 0.0;
+        }
+        base_fee + self.premium
+    }
 }
 
 impl Amm {
@@ -37,6 +154,10 @@ impl Amm {
             block: 0,
             cumulative_price: 0.0,
             spot_price: spot,
+            cumulative_log_price: 0.0,
+            cumulative_volume_zec: 0.0,
+            cumulative_volume_zai: 0.0,
+            cumulative_net_zec_in: 0.0,
         };
 
         Amm {
@@ -47,9 +168,17 @@ impl Amm {
             total_lp_shares: initial_shares,
             lp_shares,
             cumulative_price: 0.0,
+            cumulative_log_price: 0.0,
+            cumulative_volume_zec: 0.0,
+            cumulative_volume_zai: 0.0,
+            cumulative_net_zec_in: 0.0,
             price_observations: vec![obs],
             last_update_block: 0,
             cumulative_fees_zai: 0.0,
+            max_swap_fraction: None,
+            lp_withdrawal_budget: None,
+            pools: Vec::new(),
+            order_book: None,
         }
     }
 
@@ -64,15 +193,36 @@ impl Amm {
         let blocks_elapsed = block - self.last_update_block;
         let spot = self.spot_price();
         self.cumulative_price += spot * blocks_elapsed as f64;
+        self.cumulative_log_price += fixed::ln(spot) * blocks_elapsed as f64;
 
         self.price_observations.push(PriceObservation {
             block,
             cumulative_price: self.cumulative_price,
             spot_price: spot,
+            cumulative_log_price: self.cumulative_log_price,
+            cumulative_volume_zec: self.cumulative_volume_zec,
+            cumulative_volume_zai: self.cumulative_volume_zai,
+            cumulative_net_zec_in: self.cumulative_net_zec_in,
         });
         self.last_update_block = block;
     }
 
+    /// Move the price observations out, leaving the pool without history.
+    pub(crate) fn take_observations(&mut self) -> Vec<PriceObservation> {
+        std::mem::take(&mut self.price_observations)
+    }
+
+    /// Put back observations from `take_observations`, dropping those from
+    /// block `from` on.
+    pub(crate) fn restore_observations(
+        &mut self,
+        mut observations: Vec<PriceObservation>,
+        from: u64,
+    ) {
+        observations.truncate(observations.partition_point(|o| o.block < from));
+        self.price_observations = observations;
+    }
+
     pub fn get_twap(&self, window_blocks: u64) -> f64 {
         if self.price_observations.is_empty() {
             return self.spot_price();
@@ -99,9 +249,130 @@ impl Amm {
         cumulative_diff / block_diff as f64
     }
 
-    pub fn swap_zec_for_zai(&mut self, zec_in: f64, block: u64) -> Result<f64, String> {
+    /// TWAP over `window_blocks` using the `kind` estimator.
+    pub fn twap(&self, window_blocks: u64, kind: TwapKind) -> f64 {
+        match kind {
+            TwapKind::Arithmetic => self.get_twap(window_blocks),
+            TwapKind::Median => self.median_twap(window_blocks),
+            TwapKind::Geometric => self.geometric_twap(window_blocks),
+            TwapKind::VolumeWeighted => self.volume_weighted_price(window_blocks),
+        }
+    }
+
+    /// Index of the observation at or just before `window_blocks` before
+    /// the latest one.
+    fn window_start(&self, window_blocks: u64) -> usize {
+        let current = self.price_observations.last().unwrap();
+        let target_block = current.block.saturating_sub(window_blocks);
+        self.price_observations
+            .iter()
+            .rposition(|obs| obs.block <= target_block)
+            .unwrap_or(0)
+    }
+
+    pub fn median_twap(&self, window_blocks: u64) -> f64 {
+        if self.price_observations.len() < 2 {
+            return self.spot_price();
+        }
+        let start = self.window_start(window_blocks);
+        // Each observation's spot price held since the previous observation
+        let mut weighted: Vec<(f64, u64)> = self.price_observations[start..]
+            .windows(2)
+            .map(|pair| (pair[1].spot_price, pair[1].block - pair[0].block))
+            .collect();
+        let total: u64 = weighted.iter().map(|&(_, w)| w).sum();
+        if total == 0 {
+            return self.price_observations.last().unwrap().spot_price;
+        }
+        weighted.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let mut seen = 0;
+        for (price, weight) in weighted {
+            seen += weight;
+            if 2 * seen >= total {
+                return price;
+            }
+        }
+        unreachable!("weights sum to total")
+    }
+
+    pub fn geometric_twap(&self, window_blocks: u64) -> f64 {
+        if self.price_observations.is_empty() {
+            return self.spot_price();
+        }
+        let current = self.price_observations.last().unwrap();
+        let start_obs = &self.price_observations[self.window_start(window_blocks)];
+        let block_diff = current.block - start_obs.block;
+        if block_diff == 0 {
+            return current.spot_price;
+        }
+        fixed::exp(
+            (current.cumulative_log_price - start_obs.cumulative_log_price) / block_diff as f64,
+        )
+    }
+
+    /// Average execution price (ZAI per ZEC) of swaps since the window
+    /// started, including any made after the latest observation.
+    pub fn volume_weighted_price(&self, window_blocks: u64) -> f64 {
+        if self.price_observations.is_empty() {
+            return self.spot_price();
+        }
+        let start_obs = &self.price_observations[self.window_start(window_blocks)];
+        let zec = self.cumulative_volume_zec - start_obs.cumulative_volume_zec;
+        let zai = self.cumulative_volume_zai - start_obs.cumulative_volume_zai;
+        if zec <= 0.0 {
+            return self.get_twap(window_blocks);
+        }
+        zai / zec
+    }
+
+    /// Standard deviation of the spot price's per-block log returns over
+    /// `window_blocks`.
+    pub fn realized_volatility(&self, window_blocks: u64) -> f64 {
+        if self.price_observations.is_empty() {
+            return 0.0;
+        }
+        let start = self.window_start(window_blocks);
+        // Returns over gaps of several blocks are scaled to one block
+        let returns: Vec<f64> = self.price_observations[start..]
+            .windows(2)
+            .map(|pair| {
+                let blocks = (pair[1].block - pair[0].block) as f64;
+                (pair[1].spot_price / pair[0].spot_price).ln() / blocks.sqrt()
+            })
+            .collect();
+        if returns.len() < 2 {
+            return 0.0;
+        }
+        let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+        let variance =
+            returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (returns.len() - 1) as f64;
+        variance.sqrt()
+    }
+
+    /// Net ZEC swapped into (positive) or out of the pool since the window
+    /// started, as a fraction of the ZEC reserve.
+    pub fn net_flow_fraction(&self, window_blocks: u64) -> f64 {
+        if self.price_observations.is_empty() || self.reserve_zec <= 0.0 {
+            return 0.0;
+        }
+        let start_obs = &self.price_observations[self.window_start(window_blocks)];
+        (self.cumulative_net_zec_in - start_obs.cumulative_net_zec_in) / self.reserve_zec
+    }
+
+    pub fn swap_zec_for_zai(&mut self, zec_in: f64, block: u64) -> Result<f64, ZaiSimError> {
         if zec_in <= 0.0 {
-            return Err("Input must be positive".to_string());
+            return Err(ZaiSimError::InvalidInput(
+                "Input must be positive".to_string(),
+            ));
+        }
+        if let Some(cap) = self.max_swap_fraction {
+            if zec_in > self.reserve_zec * cap {
+                return Err(ZaiSimError::VelocityLimit(format!(
+                    "Swap of {:.4} ZEC exceeds cap of {:.4}",
+                    zec_in,
+                    self.reserve_zec * cap
+                )));
+            }
         }
 
         // Record price before swap
@@ -113,25 +384,42 @@ impl Amm {
 
         let effective_input = zec_in * (1.0 - self.swap_fee);
         let new_reserve_zec = self.reserve_zec + effective_input;
-        let new_reserve_zai = self.k / new_reserve_zec;
+        let new_reserve_zai = fixed::div(self.k, new_reserve_zec);
         let zai_out = self.reserve_zai - new_reserve_zai;
 
         if zai_out <= 0.0 {
-            return Err("Insufficient output".to_string());
+            return Err(ZaiSimError::InsufficientLiquidity(
+                "swap output is not positive".to_string(),
+            ));
         }
 
+        self.cumulative_volume_zec += zec_in;
+        self.cumulative_volume_zai += zai_out;
+        self.cumulative_net_zec_in += zec_in;
+
         // Update reserves: full input goes in (fee stays in pool)
         self.reserve_zec += zec_in;
         self.reserve_zai -= zai_out;
         // k increases because the fee portion stays in the pool
-        self.k = self.reserve_zec * self.reserve_zai;
+        self.k = fixed::mul(self.reserve_zec, self.reserve_zai);
 
         Ok(zai_out)
     }
 
-    pub fn swap_zai_for_zec(&mut self, zai_in: f64, block: u64) -> Result<f64, String> {
+    pub fn swap_zai_for_zec(&mut self, zai_in: f64, block: u64) -> Result<f64, ZaiSimError> {
         if zai_in <= 0.0 {
-            return Err("Input must be positive".to_string());
+            return Err(ZaiSimError::InvalidInput(
+                "Input must be positive".to_string(),
+            ));
+        }
+        if let Some(cap) = self.max_swap_fraction {
+            if zai_in > self.reserve_zai * cap {
+                return Err(ZaiSimError::VelocityLimit(format!(
+                    "Swap of {:.4} ZAI exceeds cap of {:.4}",
+                    zai_in,
+                    self.reserve_zai * cap
+                )));
+            }
         }
 
         // Record price before swap
@@ -142,23 +430,31 @@ impl Amm {
 
         let effective_input = zai_in * (1.0 - self.swap_fee);
         let new_reserve_zai = self.reserve_zai + effective_input;
-        let new_reserve_zec = self.k / new_reserve_zai;
+        let new_reserve_zec = fixed::div(self.k, new_reserve_zai);
         let zec_out = self.reserve_zec - new_reserve_zec;
 
         if zec_out <= 0.0 {
-            return Err("Insufficient output".to_string());
+            return Err(ZaiSimError::InsufficientLiquidity(
+                "swap output is not positive".to_string(),
+            ));
         }
 
+        self.cumulative_volume_zec += zec_out;
+        self.cumulative_volume_zai += zai_in;
+        self.cumulative_net_zec_in -= zec_out;
+
         self.reserve_zai += zai_in;
         self.reserve_zec -= zec_out;
-        self.k = self.reserve_zec * self.reserve_zai;
+        self.k = fixed::mul(self.reserve_zec, self.reserve_zai);
 
         Ok(zec_out)
     }
 
-    pub fn add_liquidity(&mut self, zec: f64, zai: f64, owner: &str) -> Result<f64, String> {
+    pub fn add_liquidity(&mut self, zec: f64, zai: f64, owner: &str) -> Result<f64, ZaiSimError> {
         if zec <= 0.0 || zai <= 0.0 {
-            return Err("Amounts must be positive".to_string());
+            return Err(ZaiSimError::InvalidInput(
+                "Amounts must be positive".to_string(),
+            ));
         }
 
         let shares = if self.total_lp_shares == 0.0 {
@@ -172,7 +468,7 @@ impl Amm {
 
         self.reserve_zec += zec;
         self.reserve_zai += zai;
-        self.k = self.reserve_zec * self.reserve_zai;
+        self.k = fixed::mul(self.reserve_zec, self.reserve_zai);
         self.total_lp_shares += shares;
 
         let entry = self.lp_shares.entry(owner.to_string()).or_insert(0.0);
@@ -181,16 +477,32 @@ impl Amm {
         Ok(shares)
     }
 
-    pub fn remove_liquidity(&mut self, shares: f64, owner: &str) -> Result<(f64, f64), String> {
+    pub fn remove_liquidity(
+        &mut self,
+        shares: f64,
+        owner: &str,
+    ) -> Result<(f64, f64), ZaiSimError> {
         let owner_shares = self.lp_shares.get(owner).copied().unwrap_or(0.0);
         if shares > owner_shares {
-            return Err(format!(
-                "Insufficient shares: have {}, requested {}",
-                owner_shares, shares
-            ));
+            return Err(ZaiSimError::InsufficientBalance {
+                what: "shares".to_string(),
+                have: owner_shares,
+                requested: shares,
+            });
         }
         if shares <= 0.0 {
-            return Err("Shares must be positive".to_string());
+            return Err(ZaiSimError::InvalidInput(
+                "Shares must be positive".to_string(),
+            ));
+        }
+        if let Some(budget) = self.lp_withdrawal_budget {
+            if shares > budget {
+                return Err(ZaiSimError::VelocityLimit(format!(
+                    "Withdrawal of {} shares exceeds per-block budget {}",
+                    shares, budget
+                )));
+            }
+            self.lp_withdrawal_budget = Some(budget - shares);
         }
 
         let fraction = shares / self.total_lp_shares;
@@ -199,7 +511,7 @@ impl Amm {
 
         self.reserve_zec -= zec_out;
         self.reserve_zai -= zai_out;
-        self.k = self.reserve_zec * self.reserve_zai;
+        self.k = fixed::mul(self.reserve_zec, self.reserve_zai);
         self.total_lp_shares -= shares;
 
         let entry = self.lp_shares.get_mut(owner).unwrap();
@@ -222,7 +534,7 @@ impl Amm {
     pub fn quote_zec_for_zai(&self, zec_in: f64) -> f64 {
         let effective_input = zec_in * (1.0 - self.swap_fee);
         let new_reserve_zec = self.reserve_zec + effective_input;
-        let new_reserve_zai = self.k / new_reserve_zec;
+        let new_reserve_zai = fixed::div(self.k, new_reserve_zec);
         (self.reserve_zai - new_reserve_zai).max(0.0)
     }
 
@@ -230,7 +542,7 @@ impl Amm {
     pub fn quote_zai_for_zec(&self, zai_in: f64) -> f64 {
         let effective_input = zai_in * (1.0 - self.swap_fee);
         let new_reserve_zai = self.reserve_zai + effective_input;
-        let new_reserve_zec = self.k / new_reserve_zai;
+        let new_reserve_zec = fixed::div(self.k, new_reserve_zai);
         (self.reserve_zec - new_reserve_zec).max(0.0)
     }
 }
@@ -0,0 +1,291 @@
+//! Search for the worst-case attack against a config.
+//!
+//! The attack tests replay hand-picked whale schedules, which are unlikely
+//! to be the worst an attacker can do. `AttackSearch` runs a grid of
+//! built-in attackers — strategy, capital, start block and duration — over
+//! one config and price path, hill-climbs from the best of them, and ranks
+//! the outcomes either by attacker profit or by how cheaply the attacker
+//! buys bad debt (the griefing ratio).
+
+use rayon::prelude::*;
+
+use crate::agents::{AttackStrategy, Attacker, AttackerConfig};
+use crate::scenario::{Scenario, ScenarioConfig};
+use crate::scenarios::{run_stress_with, ScenarioId};
+
+/// Attack shapes the search tries. Each maps capital and duration onto the
+/// parameters of an `AttackStrategy`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttackKind {
+    DumpAndRevert,
+    Drip,
+    TwapOscillation,
+    LiquidationHunt,
+    PegSqueeze,
+}
+
+impl AttackKind {
+    pub fn all() -> [AttackKind; 5] {
+        [
+            Self::DumpAndRevert,
+            Self::Drip,
+            Self::TwapOscillation,
+            Self::LiquidationHunt,
+            Self::PegSqueeze,
+        ]
+    }
+}
+
+/// What the search optimizes for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttackObjective {
+    /// Largest attacker profit
+    MaxProfit,
+    /// Lowest attacker loss per ZAI of bad debt caused
+    CheapestGriefing,
+}
+
+/// One attack to evaluate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttackCandidate {
+    pub kind: AttackKind,
+    pub capital_zec: f64,
+    pub start_block: u64,
+    /// Blocks the position is built or held, depending on the kind
+    pub duration: u64,
+}
+
+impl AttackCandidate {
+    /// The built-in attacker for this candidate.
+    pub fn attacker_config(&self) -> AttackerConfig {
+        let duration = self.duration.max(1);
+        let strategy = match self.kind {
+            AttackKind::DumpAndRevert => AttackStrategy::DumpAndRevert,
+            AttackKind::Drip => AttackStrategy::Drip {
+                zec_per_block: self.capital_zec / duration as f64,
+                blocks: duration,
+                unwind_blocks: (duration / 10).max(1),
+            },
+            AttackKind::TwapOscillation => AttackStrategy::TwapOscillation {
+                swing_zec: self.capital_zec,
+                half_period: duration,
+                cycles: 3,
+            },
+            AttackKind::LiquidationHunt => AttackStrategy::LiquidationHunt {
+                buy_drop_pct: 10.0,
+                max_hold_blocks: duration,
+            },
+            AttackKind::PegSqueeze => AttackStrategy::PegSqueeze {
+                target_premium_pct: 5.0,
+                zec_per_block: self.capital_zec / 10.0,
+                hold_blocks: duration,
+            },
+        };
+        AttackerConfig {
+            attack_capital_zec: self.capital_zec,
+            hold_blocks: duration,
+            attack_at_block: self.start_block,
+            strategy,
+        }
+    }
+}
+
+/// Grid of candidates: every combination of the listed values.
+#[derive(Debug, Clone)]
+pub struct AttackSearchSpace {
+    pub kinds: Vec<AttackKind>,
+    pub capital_zec: Vec<f64>,
+    pub start_blocks: Vec<u64>,
+    pub durations: Vec<u64>,
+    /// Hill-climbing rounds from the best grid point (0 = grid only)
+    pub refine_rounds: usize,
+}
+
+impl AttackSearchSpace {
+    /// A coarse grid for a run of `blocks` blocks.
+    pub fn default_for(blocks: usize) -> Self {
+        let blocks = blocks as u64;
+        AttackSearchSpace {
+            kinds: AttackKind::all().to_vec(),
+            capital_zec: vec![1_000.0, 5_000.0, 20_000.0, 50_000.0],
+            start_blocks: vec![blocks / 4, blocks / 2],
+            durations: vec![3, 20, 100],
+            refine_rounds: 3,
+        }
+    }
+
+    pub fn candidates(&self) -> Vec<AttackCandidate> {
+        let mut out = Vec::new();
+        for &kind in &self.kinds {
+            for &capital_zec in &self.capital_zec {
+                for &start_block in &self.start_blocks {
+                    for &duration in &self.durations {
+                        out.push(AttackCandidate {
+                            kind,
+                            capital_zec,
+                            start_block,
+                            duration,
+                        });
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+/// Result of running one candidate.
+#[derive(Debug, Clone)]
+pub struct AttackOutcome {
+    pub candidate: AttackCandidate,
+    /// Attacker gain in ZAI: ZEC change marked at the final AMM spot plus
+    /// any ZAI still held
+    pub attacker_pnl_zai: f64,
+    /// Bad debt at the end of the run
+    pub bad_debt: f64,
+    pub liquidations: u32,
+    /// Attacker loss per ZAI of bad debt: 0 when the attack pays for
+    /// itself, infinite when it causes no bad debt
+    pub griefing_ratio: f64,
+}
+
+impl AttackOutcome {
+    /// How bad the outcome is for the protocol under `objective`; higher
+    /// is worse.
+    pub fn severity(&self, objective: AttackObjective) -> f64 {
+        match objective {
+            AttackObjective::MaxProfit => self.attacker_pnl_zai,
+            AttackObjective::CheapestGriefing if self.bad_debt > 0.0 => -self.griefing_ratio,
+            AttackObjective::CheapestGriefing => f64::NEG_INFINITY,
+        }
+    }
+}
+
+/// Runs candidate attacks against one config and price path.
+pub struct AttackSearch {
+    pub config: ScenarioConfig,
+    /// Supplies the price path and the non-attacking agents
+    pub scenario: ScenarioId,
+    pub blocks: usize,
+    pub seed: u64,
+    /// Extra setup before the attacker joins, e.g. opening target vaults
+    pub setup: fn(&mut Scenario),
+}
+
+impl AttackSearch {
+    pub fn new(config: ScenarioConfig, scenario: ScenarioId, blocks: usize, seed: u64) -> Self {
+        AttackSearch {
+            config,
+            scenario,
+            blocks,
+            seed,
+            setup: |_| {},
+        }
+    }
+
+    /// Run one candidate to the end of the path.
+    pub fn evaluate(&self, candidate: &AttackCandidate) -> AttackOutcome {
+        let scenario = run_stress_with(self.scenario, &self.config, self.blocks, self.seed, |s| {
+            (self.setup)(s);
+            s.attackers.push(Attacker::new(candidate.attacker_config()));
+        });
+
+        let spot = scenario.amm.spot_price();
+        let attacker_pnl_zai = scenario.attackers.last().map_or(0.0, |a| {
+            (a.zec_balance - candidate.capital_zec) * spot + a.zai_balance
+        });
+        let bad_debt = scenario.metrics.bad_debt.last().copied().unwrap_or(0.0);
+        let liquidations = scenario.metrics.liquidation_count.iter().sum();
+        let griefing_ratio = if bad_debt > 0.0 {
+            (-attacker_pnl_zai).max(0.0) / bad_debt
+        } else {
+            f64::INFINITY
+        };
+
+        AttackOutcome {
+            candidate: candidate.clone(),
+            attacker_pnl_zai,
+            bad_debt,
+            liquidations,
+            griefing_ratio,
+        }
+    }
+
+    /// Evaluate the grid, then hill-climb from the most severe outcome by
+    /// scaling capital and duration and shifting the start. Returns every
+    /// outcome evaluated, most severe first.
+    pub fn search(
+        &self,
+        space: &AttackSearchSpace,
+        objective: AttackObjective,
+    ) -> Vec<AttackOutcome> {
+        let mut outcomes: Vec<AttackOutcome> = space
+            .candidates()
+            .par_iter()
+            .map(|c| self.evaluate(c))
+            .collect();
+        Self::rank(&mut outcomes, objective);
+
+        for _ in 0..space.refine_rounds {
+            let Some(best) = outcomes.first().cloned() else {
+                break;
+            };
+            let tried: Vec<AttackCandidate> =
+                outcomes.iter().map(|o| o.candidate.clone()).collect();
+            let mut neighbours: Vec<AttackCandidate> = Vec::new();
+            for c in self.neighbours(&best.candidate) {
+                if !tried.contains(&c) && !neighbours.contains(&c) {
+                    neighbours.push(c);
+                }
+            }
+            if neighbours.is_empty() {
+                break;
+            }
+            outcomes.extend(
+                neighbours
+                    .par_iter()
+                    .map(|c| self.evaluate(c))
+                    .collect::<Vec<_>>(),
+            );
+            Self::rank(&mut outcomes, objective);
+            if outcomes[0].candidate == best.candidate {
+                break;
+            }
+        }
+        outcomes
+    }
+
+    /// Candidates one step from `c` along each axis, within the run.
+    fn neighbours(&self, c: &AttackCandidate) -> Vec<AttackCandidate> {
+        let step = (self.blocks as u64 / 20).max(1);
+        let last = (self.blocks as u64).saturating_sub(1).max(1);
+        let mut out = Vec::new();
+        for capital_zec in [c.capital_zec * 1.5, c.capital_zec / 1.5] {
+            out.push(AttackCandidate {
+                capital_zec,
+                ..c.clone()
+            });
+        }
+        for duration in [c.duration * 2, (c.duration / 2).max(1)] {
+            out.push(AttackCandidate {
+                duration,
+                ..c.clone()
+            });
+        }
+        for start_block in [
+            c.start_block.saturating_sub(step).max(1),
+            (c.start_block + step).min(last),
+        ] {
+            out.push(AttackCandidate {
+                start_block,
+                ..c.clone()
+            });
+        }
+        out.retain(|n| n != c);
+        out
+    }
+
+    fn rank(outcomes: &mut [AttackOutcome], objective: AttackObjective) {
+        outcomes.sort_by(|a, b| b.severity(objective).total_cmp(&a.severity(objective)));
+    }
+}
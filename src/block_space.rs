@@ -0,0 +1,139 @@
+//! Block-time jitter and per-block transaction capacity.
+//!
+//! Without a block space model blocks are uniform and unbounded: every
+//! agent acts every block and every eligible vault can be liquidated. With
+//! one, each block's interval is drawn around the target (Zcash aims for
+//! 75 seconds) and the block holds a limited number of transactions. Agents
+//! act once per block whatever its length, so a long block is modelled as
+//! the same demand against proportionally less room, and a short one as
+//! more room.
+//!
+//! When the block fills, agents still waiting are turned away and queue
+//! for the next block, where they are served before anyone else.
+//! Liquidations draw on the same room; vaults left over are found again by
+//! the next block's scan.
+
+use std::collections::BTreeSet;
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use rand_distr::StandardNormal;
+use serde::{Deserialize, Serialize};
+
+use crate::agents::AgentAction;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlockSpaceConfig {
+    /// Target block interval (seconds)
+    pub block_time_secs: f64,
+    /// Standard deviation of the interval as a fraction of the target;
+    /// 0 keeps blocks uniform
+    pub block_time_jitter: f64,
+    /// Transactions a block of target length holds; `None` for no limit
+    pub max_txs_per_block: Option<u32>,
+}
+
+impl Default for BlockSpaceConfig {
+    fn default() -> Self {
+        BlockSpaceConfig {
+            block_time_secs: 75.0,
+            block_time_jitter: 0.0,
+            max_txs_per_block: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockSpace {
+    pub config: BlockSpaceConfig,
+    /// Interval of the current block (seconds)
+    pub block_time_secs: f64,
+    /// Time since the first block (seconds)
+    pub elapsed_secs: f64,
+    /// Transactions the current block can still take
+    pub capacity_left: u32,
+    /// Agents crowded out of the last block, served first in this one
+    pub queued: BTreeSet<String>,
+    /// Agents crowded out of the current block so far
+    pub deferred: BTreeSet<String>,
+    /// Agent turns lost to a full block over the run
+    pub total_deferred: u64,
+    rng: ChaCha12Rng,
+}
+
+impl BlockSpace {
+    pub fn new(config: BlockSpaceConfig, seed: u64) -> Self {
+        BlockSpace {
+            block_time_secs: config.block_time_secs,
+            elapsed_secs: 0.0,
+            capacity_left: config.max_txs_per_block.unwrap_or(u32::MAX),
+            config,
+            queued: BTreeSet::new(),
+            deferred: BTreeSet::new(),
+            total_deferred: 0,
+            rng: ChaCha12Rng::seed_from_u64(seed),
+        }
+    }
+
+    /// Draw a block interval: log-normal around the target with the
+    /// configured relative spread.
+    fn sample_block_time(&mut self) -> f64 {
+        let target = self.config.block_time_secs;
+        let jitter = self.config.block_time_jitter;
+        if jitter <= 0.0 {
+            return target;
+        }
+        let sigma = (1.0 + jitter * jitter).ln().sqrt();
+        let z: f64 = self.rng.sample(StandardNormal);
+        target * (sigma * z - sigma * sigma / 2.0).exp()
+    }
+
+    /// Open a block: draw its interval, size its capacity and move the
+    /// last block's overflow to the front of the queue. Returns the
+    /// interval.
+    pub fn begin_block(&mut self) -> f64 {
+        self.block_time_secs = self.sample_block_time();
+        self.elapsed_secs += self.block_time_secs;
+        self.capacity_left = match self.config.max_txs_per_block {
+            Some(max) => {
+                let scale = self.config.block_time_secs / self.block_time_secs;
+                (max as f64 * scale).round() as u32
+            }
+            None => u32::MAX,
+        };
+        self.queued = std::mem::take(&mut self.deferred);
+        self.block_time_secs
+    }
+
+    /// Whether `agent` may act now. Queued agents are always let in while
+    /// room remains; others only get room beyond what the queue still
+    /// needs. An agent turned away queues for the next block.
+    pub fn admit(&mut self, agent: &str) -> bool {
+        let admitted = if self.queued.remove(agent) {
+            self.capacity_left > 0
+        } else {
+            self.capacity_left as usize > self.queued.len()
+        };
+        if !admitted {
+            self.deferred.insert(agent.to_string());
+            self.total_deferred += 1;
+        }
+        admitted
+    }
+
+    /// Take the room used by `actions`.
+    pub fn fill(&mut self, actions: &[AgentAction]) {
+        let txs = actions.iter().filter(|a| a.is_transaction()).count();
+        self.fill_count(txs as u32);
+    }
+
+    /// Take the room used by `txs` transactions.
+    pub fn fill_count(&mut self, txs: u32) {
+        self.capacity_left = self.capacity_left.saturating_sub(txs);
+    }
+
+    /// Room left for liquidations, or `None` without a capacity limit.
+    pub fn liquidation_room(&self) -> Option<u32> {
+        self.config.max_txs_per_block.map(|_| self.capacity_left)
+    }
+}
@@ -0,0 +1,199 @@
+//! Cross-chain bridge for external capital.
+//!
+//! Without a bridge, arbers' external capital arrives the block it is
+//! needed: `capital_replenish_rate` ZAI lands in the wallet every block and
+//! low ZEC inventory is topped up from ZAI at the external price on the
+//! spot. With one, that capital crosses a bridge. Each transfer waits for
+//! room under the per-block capacity of its direction, then takes a drawn
+//! latency (log-normal around the configured mean) to arrive. A ZEC top-up
+//! becomes a round trip: ZAI is withdrawn, sold off-chain when it lands and
+//! the ZEC deposited back.
+//!
+//! During a depeg this bounds how fast outside money can close the gap, so
+//! recovery times are not flattered by instant capital.
+
+use std::collections::VecDeque;
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use rand_distr::StandardNormal;
+use serde::{Deserialize, Serialize};
+
+use crate::pool::Asset;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BridgeDirection {
+    /// Into the simulated chain
+    Deposit,
+    /// Out to the external venue
+    Withdraw,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BridgeConfig {
+    /// Mean blocks for a deposit to arrive once it starts
+    pub deposit_latency_blocks: f64,
+    /// Mean blocks for a withdrawal to arrive once it starts
+    pub withdraw_latency_blocks: f64,
+    /// Standard deviation of latencies as a fraction of the mean
+    pub latency_jitter: f64,
+    /// Value (ZAI, ZEC at the external price) deposits may start per
+    /// block; `None` for no limit
+    pub deposit_capacity_zai: Option<f64>,
+    /// Value withdrawals may start per block; `None` for no limit
+    pub withdraw_capacity_zai: Option<f64>,
+}
+
+impl Default for BridgeConfig {
+    fn default() -> Self {
+        BridgeConfig {
+            deposit_latency_blocks: 12.0, // ~15 minutes
+            withdraw_latency_blocks: 24.0,
+            latency_jitter: 0.5,
+            deposit_capacity_zai: Some(50_000.0),
+            withdraw_capacity_zai: Some(50_000.0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BridgeTransfer {
+    /// Agent id the funds belong to (`arber_0`, …)
+    pub agent: String,
+    pub asset: Asset,
+    pub amount: f64,
+    pub direction: BridgeDirection,
+    /// Block the transfer lands; 0 while it waits for capacity
+    pub arrives_at_block: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bridge {
+    pub config: BridgeConfig,
+    /// Transfers waiting for capacity, in request order
+    pub queued: VecDeque<BridgeTransfer>,
+    /// Transfers under way
+    pub in_transit: Vec<BridgeTransfer>,
+    /// Value delivered in each direction (ZAI)
+    pub total_deposited_zai: f64,
+    pub total_withdrawn_zai: f64,
+    rng: ChaCha12Rng,
+}
+
+impl Bridge {
+    pub fn new(config: BridgeConfig, seed: u64) -> Self {
+        Bridge {
+            config,
+            queued: VecDeque::new(),
+            in_transit: Vec::new(),
+            total_deposited_zai: 0.0,
+            total_withdrawn_zai: 0.0,
+            rng: ChaCha12Rng::seed_from_u64(seed),
+        }
+    }
+
+    /// Ask to move `amount` of `asset` for `agent`. It starts once the
+    /// bridge has room.
+    pub fn request(&mut self, agent: &str, asset: Asset, amount: f64, direction: BridgeDirection) {
+        if amount > 0.0 {
+            self.queued.push_back(BridgeTransfer {
+                agent: agent.to_string(),
+                asset,
+                amount,
+                direction,
+                arrives_at_block: 0,
+            });
+        }
+    }
+
+    fn value(asset: Asset, amount: f64, zec_price: f64) -> f64 {
+        match asset {
+            Asset::Zec => amount * zec_price,
+            Asset::Zai | Asset::Usd => amount,
+        }
+    }
+
+    /// Draw a latency: log-normal around the direction's mean.
+    fn sample_latency(&mut self, direction: BridgeDirection) -> u64 {
+        let mean = match direction {
+            BridgeDirection::Deposit => self.config.deposit_latency_blocks,
+            BridgeDirection::Withdraw => self.config.withdraw_latency_blocks,
+        }
+        .max(0.0);
+        let jitter = self.config.latency_jitter;
+        if jitter <= 0.0 {
+            return mean.round() as u64;
+        }
+        let sigma = (1.0 + jitter * jitter).ln().sqrt();
+        let z: f64 = self.rng.sample(StandardNormal);
+        (mean * (sigma * z - sigma * sigma / 2.0).exp()).round() as u64
+    }
+
+    /// Start queued transfers as far as this block's capacity allows,
+    /// splitting the one that doesn't fit, and return those that land at
+    /// `block`.
+    pub fn advance(&mut self, block: u64, zec_price: f64) -> Vec<BridgeTransfer> {
+        let mut deposit_room = self.config.deposit_capacity_zai.unwrap_or(f64::INFINITY);
+        let mut withdraw_room = self.config.withdraw_capacity_zai.unwrap_or(f64::INFINITY);
+        let mut waiting = VecDeque::new();
+        while let Some(mut transfer) = self.queued.pop_front() {
+            let room = match transfer.direction {
+                BridgeDirection::Deposit => &mut deposit_room,
+                BridgeDirection::Withdraw => &mut withdraw_room,
+            };
+            let value = Self::value(transfer.asset, transfer.amount, zec_price);
+            if *room <= 0.0 || !value.is_finite() || value <= 0.0 {
+                waiting.push_back(transfer);
+                continue;
+            }
+            if value > *room {
+                let started = transfer.amount * (*room / value);
+                waiting.push_back(BridgeTransfer {
+                    amount: transfer.amount - started,
+                    ..transfer.clone()
+                });
+                transfer.amount = started;
+                *room = 0.0;
+            } else {
+                *room -= value;
+            }
+            transfer.arrives_at_block = block + self.sample_latency(transfer.direction);
+            self.in_transit.push(transfer);
+        }
+        self.queued = waiting;
+
+        let (arrived, in_transit): (Vec<_>, Vec<_>) = std::mem::take(&mut self.in_transit)
+            .into_iter()
+            .partition(|t| t.arrives_at_block <= block);
+        self.in_transit = in_transit;
+        for transfer in &arrived {
+            let value = Self::value(transfer.asset, transfer.amount, zec_price);
+            match transfer.direction {
+                BridgeDirection::Deposit => self.total_deposited_zai += value,
+                BridgeDirection::Withdraw => self.total_withdrawn_zai += value,
+            }
+        }
+        arrived
+    }
+
+    /// Amount of `asset` still to arrive for `agent` in `direction`,
+    /// queued or under way.
+    pub fn pending(&self, agent: &str, asset: Asset, direction: BridgeDirection) -> f64 {
+        self.queued
+            .iter()
+            .chain(&self.in_transit)
+            .filter(|t| t.agent == agent && t.asset == asset && t.direction == direction)
+            .map(|t| t.amount)
+            .sum()
+    }
+
+    /// Value of everything queued or under way (ZAI).
+    pub fn outstanding_zai(&self, zec_price: f64) -> f64 {
+        self.queued
+            .iter()
+            .chain(&self.in_transit)
+            .map(|t| Self::value(t.asset, t.amount, zec_price))
+            .sum()
+    }
+}
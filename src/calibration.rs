@@ -0,0 +1,338 @@
+//! Calibration of agent behavior constants against historical data.
+//!
+//! Arber thresholds, demand elasticity and the miners' AMM fraction are
+//! otherwise guesses. Given hourly ZEC candles and an observed DEX volume,
+//! this back-solves a plausible range for each so runs can be grounded in
+//! market behavior rather than defaults.
+//!
+//! `calibrate_depth` does the same for the AMM and external market sizes,
+//! fitting their price impact to order-book depth curves.
+
+use crate::agents::{ArbitrageurConfig, DemandAgentConfig, MinerAgentConfig};
+use crate::depth::DepthCurve;
+use crate::error::ZaiSimError;
+use crate::external_market::{ExternalMarketConfig, PriceFeedbackConfig};
+use crate::historical::HourlyCandle;
+use crate::scenario::ScenarioConfig;
+
+/// Assumptions the back-solve needs beyond the price data.
+#[derive(Debug, Clone)]
+pub struct CalibrationInputs {
+    /// Observed DEX (AMM) volume in ZEC per day
+    pub dex_volume_zec_per_day: f64,
+    /// AMM swap fee (0.003 = 0.3%)
+    pub swap_fee: f64,
+    /// Blocks per hour (48 at 75-second blocks)
+    pub blocks_per_hour: f64,
+    /// ZEC balance of a demand agent
+    pub demand_balance_zec: f64,
+    /// Miner reward and sell fraction the AMM fraction is solved against
+    pub miner: MinerAgentConfig,
+}
+
+impl Default for CalibrationInputs {
+    fn default() -> Self {
+        CalibrationInputs {
+            dex_volume_zec_per_day: 5000.0,
+            swap_fee: 0.003,
+            blocks_per_hour: 48.0,
+            demand_balance_zec: DemandAgentConfig::default().initial_zec_balance,
+            miner: MinerAgentConfig::default(),
+        }
+    }
+}
+
+/// A calibrated range for one agent parameter.
+#[derive(Debug, Clone)]
+pub struct ParamEstimate {
+    pub name: &'static str,
+    pub low: f64,
+    pub estimate: f64,
+    pub high: f64,
+    /// Current default, for comparison
+    pub default: f64,
+    /// How the range was derived
+    pub basis: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct CalibrationReport {
+    pub source: String,
+    pub hours: usize,
+    pub mean_hourly_volume_zec: f64,
+    /// DEX share of total (DEX + CEX) volume
+    pub dex_share: f64,
+    pub median_abs_return_pct: f64,
+    pub median_range_pct: f64,
+    pub params: Vec<ParamEstimate>,
+}
+
+fn median(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    let mut v = values.to_vec();
+    v.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let mid = v.len() / 2;
+    if v.len().is_multiple_of(2) {
+        (v[mid - 1] + v[mid]) / 2.0
+    } else {
+        v[mid]
+    }
+}
+
+/// Least-squares fit of `y = a + b x`. Returns `(b, standard error of b)`.
+fn ols_slope(x: &[f64], y: &[f64]) -> (f64, f64) {
+    let n = x.len() as f64;
+    if x.len() < 3 {
+        return (0.0, 0.0);
+    }
+    let mx = x.iter().sum::<f64>() / n;
+    let my = y.iter().sum::<f64>() / n;
+    let sxx: f64 = x.iter().map(|v| (v - mx).powi(2)).sum();
+    if sxx <= 0.0 {
+        return (0.0, 0.0);
+    }
+    let sxy: f64 = x.iter().zip(y).map(|(a, b)| (a - mx) * (b - my)).sum();
+    let slope = sxy / sxx;
+    let intercept = my - slope * mx;
+    let sse: f64 = x
+        .iter()
+        .zip(y)
+        .map(|(a, b)| (b - intercept - slope * a).powi(2))
+        .sum();
+    (slope, (sse / (n - 2.0) / sxx).sqrt())
+}
+
+impl CalibrationReport {
+    pub fn get(&self, name: &str) -> Option<&ParamEstimate> {
+        self.params.iter().find(|p| p.name == name)
+    }
+
+    /// Default agent configs with the calibrated point estimates applied.
+    pub fn calibrated_configs(&self) -> (ArbitrageurConfig, DemandAgentConfig, MinerAgentConfig) {
+        let mut arber = ArbitrageurConfig::default();
+        let mut demand = DemandAgentConfig::default();
+        let mut miner = MinerAgentConfig::default();
+        if let Some(p) = self.get("arb_threshold_pct") {
+            arber.arb_threshold_pct = p.estimate;
+        }
+        if let Some(p) = self.get("demand_elasticity") {
+            demand.demand_elasticity = p.estimate;
+        }
+        if let Some(p) = self.get("miner_amm_fraction") {
+            miner.miner_amm_fraction = p.estimate;
+        }
+        (arber, demand, miner)
+    }
+}
+
+/// Back-solve agent parameter ranges from hourly candles.
+pub fn calibrate(
+    source: &str,
+    candles: &[HourlyCandle],
+    inputs: &CalibrationInputs,
+) -> Result<CalibrationReport, ZaiSimError> {
+    if candles.len() < 3 {
+        return Err(ZaiSimError::InvalidInput(format!(
+            "Need at least 3 hourly candles to calibrate, got {}",
+            candles.len()
+        )));
+    }
+    if inputs.dex_volume_zec_per_day < 0.0 {
+        return Err(ZaiSimError::InvalidInput(
+            "DEX volume must be non-negative".to_string(),
+        ));
+    }
+
+    let abs_returns: Vec<f64> = candles
+        .windows(2)
+        .map(|w| (w[1].close / w[0].close).ln().abs() * 100.0)
+        .collect();
+    let ranges: Vec<f64> = candles
+        .iter()
+        .filter(|c| c.close > 0.0)
+        .map(|c| (c.high - c.low) / c.close * 100.0)
+        .collect();
+    let mean_volume = candles.iter().map(|c| c.volume_from).sum::<f64>() / candles.len() as f64;
+    let cex_per_day = mean_volume * 24.0;
+    let dex_share = if cex_per_day + inputs.dex_volume_zec_per_day > 0.0 {
+        inputs.dex_volume_zec_per_day / (cex_per_day + inputs.dex_volume_zec_per_day)
+    } else {
+        0.0
+    };
+    let median_abs_return = median(&abs_returns);
+    let median_range = median(&ranges);
+
+    // Arbers must clear a round trip through the AMM fee, and a threshold
+    // above the typical hourly range would leave most moves unarbitraged.
+    let arb_low = 2.0 * inputs.swap_fee * 100.0;
+    let arb_high = median_range.max(arb_low);
+    let arb = ParamEstimate {
+        name: "arb_threshold_pct",
+        low: arb_low,
+        estimate: median_abs_return.clamp(arb_low, arb_high),
+        high: arb_high,
+        default: ArbitrageurConfig::default().arb_threshold_pct,
+        basis: format!(
+            "round-trip fee {:.2}% to median hourly range {:.2}%; estimate at median hourly move",
+            arb_low, median_range
+        ),
+    };
+
+    // Extra volume per 1% move, scaled to the DEX and to one demand agent:
+    // the agent adds balance * elasticity / 100 ZEC per block per 1% discount.
+    let relative_volume: Vec<f64> = candles[1..]
+        .iter()
+        .map(|c| {
+            if mean_volume > 0.0 {
+                c.volume_from / mean_volume
+            } else {
+                0.0
+            }
+        })
+        .collect();
+    let (slope, se) = ols_slope(&abs_returns, &relative_volume);
+    let to_elasticity = |b: f64| {
+        let extra_per_block = b.max(0.0) * mean_volume * dex_share / inputs.blocks_per_hour;
+        100.0 * extra_per_block / inputs.demand_balance_zec
+    };
+    let demand = ParamEstimate {
+        name: "demand_elasticity",
+        low: to_elasticity(slope - 2.0 * se),
+        estimate: to_elasticity(slope),
+        high: to_elasticity(slope + 2.0 * se),
+        default: DemandAgentConfig::default().demand_elasticity,
+        basis: format!(
+            "volume rises {:.1}% (±{:.1}%) of its mean per 1% hourly move; DEX share {:.1}%",
+            slope * 100.0,
+            2.0 * se * 100.0,
+            dex_share * 100.0
+        ),
+    };
+
+    // Miners selling across venues in proportion to volume route the DEX
+    // share through the AMM; they cannot route more than the DEX trades.
+    let blocks_per_day = inputs.blocks_per_hour * 24.0;
+    let miner_sells_per_day =
+        inputs.miner.block_reward * inputs.miner.miner_sell_fraction * blocks_per_day;
+    let miner_high = if miner_sells_per_day > 0.0 {
+        (inputs.dex_volume_zec_per_day / miner_sells_per_day).min(1.0)
+    } else {
+        1.0
+    };
+    let miner_low = dex_share.min(miner_high);
+    let miner = ParamEstimate {
+        name: "miner_amm_fraction",
+        low: miner_low,
+        estimate: miner_low,
+        high: miner_high,
+        default: inputs.miner.miner_amm_fraction,
+        basis: format!(
+            "volume-proportional routing ({:.1}%) up to all DEX volume ({:.0} of {:.0} ZEC/day sold)",
+            dex_share * 100.0,
+            inputs.dex_volume_zec_per_day,
+            miner_sells_per_day
+        ),
+    };
+
+    Ok(CalibrationReport {
+        source: source.to_string(),
+        hours: candles.len(),
+        mean_hourly_volume_zec: mean_volume,
+        dex_share,
+        median_abs_return_pct: median_abs_return,
+        median_range_pct: median_range,
+        params: vec![arb, demand, miner],
+    })
+}
+
+/// Impact parameters fitted to order-book depth.
+#[derive(Debug, Clone)]
+pub struct DepthCalibration {
+    pub snapshots: usize,
+    /// Median mid price across the snapshots
+    pub mid: f64,
+    /// External market ZAI notional that moves the price by 1%
+    pub depth_zai: f64,
+    /// Constant-product ZAI reserve with the book's impact
+    pub amm_zai_reserve: f64,
+    /// ZEC reserve at the median mid
+    pub amm_zec_reserve: f64,
+}
+
+impl DepthCalibration {
+    /// `base` with the fitted depth, keeping its recovery half-life.
+    pub fn external_market(&self, base: &ExternalMarketConfig) -> ExternalMarketConfig {
+        ExternalMarketConfig {
+            depth_zai: self.depth_zai,
+            ..base.clone()
+        }
+    }
+
+    /// Size the scenario's AMM to the fitted reserve (keeping its initial
+    /// price) and its external market, enabling price feedback if off.
+    pub fn apply(&self, config: &mut ScenarioConfig) {
+        config.amm_initial_depth = Some(self.amm_zai_reserve);
+        let feedback = config
+            .price_feedback
+            .get_or_insert_with(PriceFeedbackConfig::default);
+        feedback.market = self.external_market(&feedback.market);
+    }
+}
+
+/// Least-squares slope of `y = b x` through the origin.
+fn slope_through_origin(points: &[(f64, f64)]) -> f64 {
+    let sxx: f64 = points.iter().map(|(x, _)| x * x).sum();
+    let sxy: f64 = points.iter().map(|(x, y)| x * y).sum();
+    if sxx > 0.0 {
+        sxy / sxx
+    } else {
+        0.0
+    }
+}
+
+/// Fit impact parameters to depth curves, pooling both sides of every
+/// snapshot.
+///
+/// The external market's price is linear in notional, so the depth within
+/// `bps` of the mid is `depth_zai * bps / 100`. A constant-product pool with
+/// ZAI reserve `Y` absorbs `Y (sqrt(1 + m) - 1)` of buying before its price
+/// rises by `m = bps / 10000`, and `Y (1 - sqrt(1 - m))` of selling before it
+/// falls by `m`. Both are fitted through the origin.
+pub fn calibrate_depth(curves: &[DepthCurve]) -> Result<DepthCalibration, ZaiSimError> {
+    let curves: Vec<&DepthCurve> = curves.iter().filter(|c| !c.points.is_empty()).collect();
+    if curves.is_empty() {
+        return Err(ZaiSimError::InvalidInput(
+            "Need at least one depth snapshot to calibrate".to_string(),
+        ));
+    }
+
+    let mut linear = Vec::new();
+    let mut amm = Vec::new();
+    for curve in &curves {
+        for p in &curve.points {
+            let m = p.bps / 10_000.0;
+            linear.push((p.bps / 100.0, p.bid_notional));
+            linear.push((p.bps / 100.0, p.ask_notional));
+            amm.push(((1.0 + m).sqrt() - 1.0, p.ask_notional));
+            if m < 1.0 {
+                amm.push((1.0 - (1.0 - m).sqrt(), p.bid_notional));
+            }
+        }
+    }
+
+    let mid = median(&curves.iter().map(|c| c.mid).collect::<Vec<_>>());
+    let amm_zai_reserve = slope_through_origin(&amm);
+    Ok(DepthCalibration {
+        snapshots: curves.len(),
+        mid,
+        depth_zai: slope_through_origin(&linear),
+        amm_zai_reserve,
+        amm_zec_reserve: if mid > 0.0 {
+            amm_zai_reserve / mid
+        } else {
+            0.0
+        },
+    })
+}
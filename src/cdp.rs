@@ -1,11 +1,28 @@
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 
-use crate::amm::Amm;
+use serde::{Deserialize, Serialize};
+
+use crate::amm::{Amm, TwapKind};
+use crate::error::ZaiSimError;
+use crate::fixed;
 
 /// 75-second blocks → blocks per year
-const BLOCKS_PER_YEAR: f64 = 365.25 * 24.0 * 3600.0 / 75.0; // ~420,768
+pub(crate) const BLOCKS_PER_YEAR: f64 = 365.25 * 24.0 * 3600.0 / 75.0; // ~420,768
+
+/// Per-block rate for an annual rate (APR) compounded every block:
+/// `apr / blocks_per_year`. A year of blocks grows debt by
+/// `(1 + rate)^blocks_per_year`, about `e^apr`.
+pub fn apr_to_per_block(apr: f64) -> f64 {
+    apr / BLOCKS_PER_YEAR
+}
+
+/// Annual rate (APR) for a per-block rate; the inverse of
+/// `apr_to_per_block`.
+pub fn per_block_to_apr(rate: f64) -> f64 {
+    rate * BLOCKS_PER_YEAR
+}
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CdpConfig {
     /// Minimum collateral ratio (e.g., 1.5 = 150%)
     pub min_ratio: f64,
@@ -17,6 +34,9 @@ pub struct CdpConfig {
     pub stability_fee_rate: f64,
     /// TWAP window in blocks for collateral valuation
     pub twap_window: u64,
+    /// How the TWAP averages prices over `twap_window`
+    #[serde(default)]
+    pub twap_kind: TwapKind,
 }
 
 impl Default for CdpConfig {
@@ -27,18 +47,80 @@ impl Default for CdpConfig {
             debt_floor: 100.0,
             stability_fee_rate: 0.02,
             twap_window: 48, // ~1 hour at 75s blocks
+            twap_kind: TwapKind::Arithmetic,
+        }
+    }
+}
+
+impl CdpConfig {
+    /// Whether `debt` is dust: owed, but under the debt floor.
+    pub fn is_dust(&self, debt: f64) -> bool {
+        debt > 0.0 && debt < self.debt_floor
+    }
+
+    /// Reject a vault left with dust debt. Zero debt is allowed.
+    pub fn check_debt_floor(&self, debt: f64) -> Result<(), ZaiSimError> {
+        if self.is_dust(debt) {
+            return Err(ZaiSimError::BelowDebtFloor {
+                debt,
+                floor: self.debt_floor,
+            });
+        }
+        Ok(())
+    }
+
+    /// The most of `amount` that can be repaid on `debt` without leaving
+    /// dust: all of it, or down to the floor when the rest would be dust.
+    pub fn max_repayment(&self, debt: f64, amount: f64) -> f64 {
+        let amount = amount.clamp(0.0, debt.max(0.0));
+        if self.is_dust(debt - amount) {
+            (debt - self.debt_floor).max(0.0)
+        } else {
+            amount
         }
     }
+
+    /// Debt a liquidation planned to repay `amount` of `debt` covers under
+    /// `policy`: the whole debt when wiping would-be dust, otherwise the
+    /// planned amount, any dust left to `VaultRegistry::clear_dust`.
+    pub fn liquidation_repayment(&self, debt: f64, amount: f64, policy: DustPolicy) -> f64 {
+        let amount = amount.clamp(0.0, debt.max(0.0));
+        match policy {
+            DustPolicy::Wipe if debt - amount < self.debt_floor => debt,
+            _ => amount,
+        }
+    }
+}
+
+/// How a partial liquidation that would leave debt under the floor
+/// settles it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DustPolicy {
+    /// Liquidate the whole debt instead
+    #[default]
+    Wipe,
+    /// Repay as planned and clear the leftover out of the penalty, writing
+    /// off what the penalty doesn't cover as bad debt
+    RollIntoPenalty,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Vault {
     pub id: u64,
     pub owner: String,
     pub collateral_zec: f64,
+    /// Debt as of the vault's last accrual, at `rate_snapshot`
     pub debt_zai: f64,
     pub last_fee_block: u64,
     pub created_block: u64,
+    /// The registry's rate accumulator when `debt_zai` was last accrued
+    #[serde(default = "unit_rate")]
+    pub rate_snapshot: f64,
+}
+
+fn unit_rate() -> f64 {
+    1.0
 }
 
 impl Vault {
@@ -51,12 +133,65 @@ impl Vault {
     }
 }
 
-#[derive(Debug)]
+/// Vaults with debt ordered by parity price: the ZEC price at which their
+/// collateral only just covers their debt (debt / collateral). A vault is
+/// below a minimum ratio `r` at price `p` exactly when its parity price is
+/// above `p / r`, so liquidation scans at any price are range queries.
+/// Debt is indexed normalized by the rate accumulator (`debt_zai /
+/// rate_snapshot`), which fee accrual leaves unchanged: every vault's
+/// parity price grows by the same factor, so the order holds.
+///
+/// Keys are the bit patterns of the parity prices, which order like the
+/// prices themselves for non-negative values; the rare negative price (from
+/// dust-level negative collateral or debt) sorts above every other.
+#[derive(Debug, Default)]
+struct ParityIndex {
+    by_price: BTreeSet<(u64, u64)>,
+    /// Indexed key of every vault, `None` for vaults without debt
+    keys: HashMap<u64, Option<u64>>,
+}
+
+impl ParityIndex {
+    fn key(vault: &Vault) -> Option<u64> {
+        (vault.debt_zai != 0.0)
+            .then(|| (vault.debt_zai / vault.rate_snapshot / vault.collateral_zec).to_bits())
+    }
+
+    fn update(&mut self, id: u64, vault: Option<&Vault>) {
+        if let Some(Some(old)) = self.keys.remove(&id) {
+            self.by_price.remove(&(old, id));
+        }
+        if let Some(vault) = vault {
+            let key = Self::key(vault);
+            if let Some(key) = key {
+                self.by_price.insert((key, id));
+            }
+            self.keys.insert(id, key);
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct VaultRegistry {
+    /// Vaults by ID. Changes made here directly, rather than through the
+    /// registry's methods, must be followed by `reindex`.
     pub vaults: HashMap<u64, Vault>,
     pub config: CdpConfig,
     next_id: u64,
+    /// Debt across all vaults, with fees due up to `accumulator_block`
     pub total_debt: f64,
+    /// Cumulative stability fees accrued across all vaults (ZAI)
+    pub total_fees_accrued: f64,
+    /// Growth of one ZAI of debt through stability fees, compounded per
+    /// block up to `accumulator_block`
+    #[serde(default = "unit_rate")]
+    pub rate_accumulator: f64,
+    #[serde(default)]
+    pub accumulator_block: u64,
+    /// Not saved; after loading a checkpoint, scans take a full pass until
+    /// the next `reindex` rebuilds it
+    #[serde(skip)]
+    index: ParityIndex,
 }
 
 impl VaultRegistry {
@@ -66,36 +201,167 @@ impl VaultRegistry {
             config,
             next_id: 1,
             total_debt: 0.0,
+            total_fees_accrued: 0.0,
+            rate_accumulator: 1.0,
+            accumulator_block: 0,
+            index: ParityIndex::default(),
         }
     }
 
-    /// Get ZEC price from AMM TWAP.
-    fn get_price(&self, amm: &Amm) -> f64 {
-        amm.get_twap(self.config.twap_window)
+    /// Refresh the liquidation index for `vault_id` after changing or
+    /// removing it through `vaults`.
+    pub fn reindex(&mut self, vault_id: u64) {
+        if self.index.keys.len() != self.vaults.len() {
+            // Vaults were added or removed behind the index's back
+            self.index = ParityIndex::default();
+            for (id, vault) in &self.vaults {
+                self.index.update(*id, Some(vault));
+            }
+        }
+        self.index.update(vault_id, self.vaults.get(&vault_id));
     }
 
-    /// Accrue stability fee on a vault. Compounds per-block.
-    /// debt_new = debt_old * (1 + annual_rate / blocks_per_year) ^ blocks_elapsed
-    pub fn accrue_fees(&mut self, vault_id: u64, block: u64) -> Result<(), String> {
-        let vault = self
-            .vaults
-            .get_mut(&vault_id)
-            .ok_or_else(|| format!("Vault {} not found", vault_id))?;
+    /// IDs of vaults whose parity price (debt / collateral, with fees due)
+    /// may lie within `lo..=hi`, unordered. The range is padded for
+    /// rounding, so callers apply their exact collateral ratio test to the
+    /// candidates. Falls back to every vault with debt while the index is
+    /// out of date.
+    pub fn parity_candidates(&self, lo: f64, hi: f64) -> Vec<u64> {
+        if self.index.keys.len() != self.vaults.len() {
+            return self
+                .vaults
+                .values()
+                .filter(|v| v.debt_zai != 0.0)
+                .map(|v| v.id)
+                .collect();
+        }
+        let lo = (lo / self.rate_accumulator * (1.0 - 1e-9))
+            .max(0.0)
+            .to_bits();
+        if hi == f64::INFINITY {
+            return self
+                .index
+                .by_price
+                .range((lo, 0)..)
+                .map(|(_, id)| *id)
+                .collect();
+        }
+        let hi = (hi / self.rate_accumulator * (1.0 + 1e-9)).to_bits();
+        if hi < lo {
+            return Vec::new();
+        }
+        self.index
+            .by_price
+            .range((lo, 0)..=(hi, u64::MAX))
+            .map(|(_, id)| *id)
+            .collect()
+    }
 
-        if block <= vault.last_fee_block {
-            return Ok(());
+    /// IDs of vaults with debt below `min_ratio` at `price`, in ID order.
+    pub fn liquidatable_at_price(&self, price: f64) -> Vec<u64> {
+        let min_ratio = self.config.min_ratio;
+        let mut ids: Vec<u64> = self
+            .parity_candidates(price / min_ratio, f64::INFINITY)
+            .into_iter()
+            .filter(|id| {
+                self.vaults.get(id).is_some_and(|vault| {
+                    vault.debt_zai > 0.0 && self.ratio_due(vault, price) < min_ratio
+                })
+            })
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    /// ZEC price for collateral valuation: the AMM TWAP, averaged per
+    /// `twap_kind`.
+    pub fn get_price(&self, amm: &Amm) -> f64 {
+        amm.twap(self.config.twap_window, self.config.twap_kind)
+    }
+
+    /// Advance the rate accumulator to `block` at the current stability
+    /// fee, compounding per block, and return it. Earlier blocks leave it
+    /// where it is, so a rate change applies from the block it is made at
+    /// as long as the accumulator was advanced before it. The fees accrued
+    /// meanwhile are added to `total_debt` and `total_fees_accrued`; vaults
+    /// take their share into `debt_zai` when next accrued.
+    pub fn drip(&mut self, block: u64) -> f64 {
+        if block > self.accumulator_block {
+            let accumulator = self.accumulator_at(block);
+            let fees = self.total_debt * (accumulator / self.rate_accumulator - 1.0);
+            self.total_debt += fees;
+            self.total_fees_accrued += fees;
+            self.rate_accumulator = accumulator;
+            self.accumulator_block = block;
         }
+        self.rate_accumulator
+    }
 
-        let blocks_elapsed = block - vault.last_fee_block;
-        let rate_per_block = self.config.stability_fee_rate / BLOCKS_PER_YEAR;
-        let multiplier = (1.0 + rate_per_block).powi(blocks_elapsed as i32);
+    /// The rate accumulator as of `block`, without advancing it: blocks
+    /// past `accumulator_block` compound at the current stability fee.
+    pub fn accumulator_at(&self, block: u64) -> f64 {
+        if block <= self.accumulator_block {
+            return self.rate_accumulator;
+        }
+        let rate_per_block = apr_to_per_block(self.config.stability_fee_rate);
+        let blocks_elapsed = block - self.accumulator_block;
+        self.rate_accumulator * fixed::powi(1.0 + rate_per_block, blocks_elapsed as i32)
+    }
 
-        let old_debt = vault.debt_zai;
-        vault.debt_zai *= multiplier;
-        vault.last_fee_block = block;
+    /// A vault's debt with fees due up to `accumulator_block`.
+    pub fn debt_due(&self, vault: &Vault) -> f64 {
+        vault.debt_zai * (self.rate_accumulator / vault.rate_snapshot)
+    }
 
-        self.total_debt += vault.debt_zai - old_debt;
+    /// A vault's collateral ratio at `price` with fees due up to
+    /// `accumulator_block`.
+    pub fn ratio_due(&self, vault: &Vault, price: f64) -> f64 {
+        if vault.debt_zai == 0.0 {
+            return f64::INFINITY;
+        }
+        (vault.collateral_zec * price) / self.debt_due(vault)
+    }
+
+    /// A vault's debt with fees due at `block`, without accruing them.
+    pub fn debt_at(&self, vault_id: u64, block: u64) -> Option<f64> {
+        let vault = self.vaults.get(&vault_id)?;
+        Some(vault.debt_zai * (self.accumulator_at(block) / vault.rate_snapshot))
+    }
 
+    /// A copy of a vault with fees due at `block` added to its debt.
+    pub fn vault_at(&self, vault_id: u64, block: u64) -> Option<Vault> {
+        let mut vault = self.vaults.get(&vault_id)?.clone();
+        vault.debt_zai = self.debt_at(vault_id, block)?;
+        vault.rate_snapshot = self.accumulator_at(block);
+        vault.last_fee_block = vault.last_fee_block.max(block);
+        Some(vault)
+    }
+
+    /// Accrue stability fees on a vault: advance the rate accumulator to
+    /// `block` and grow the debt by its change since the vault's last
+    /// accrual. Compounds per block,
+    /// debt_new = debt_old * (1 + annual_rate / blocks_per_year) ^ blocks_elapsed.
+    /// The vault is brought up to the accumulator, which never runs
+    /// backwards, and accruing it again there is a no-op, so the result
+    /// doesn't depend on how often or in what order vaults are accrued.
+    pub fn accrue_fees(&mut self, vault_id: u64, block: u64) -> Result<(), ZaiSimError> {
+        if !self.vaults.contains_key(&vault_id) {
+            return Err(ZaiSimError::VaultNotFound(vault_id));
+        }
+        let accumulator = self.drip(block);
+        let accumulator_block = self.accumulator_block;
+        let vault = self.vaults.get_mut(&vault_id).unwrap();
+        vault.last_fee_block = vault.last_fee_block.max(accumulator_block);
+        if vault.rate_snapshot == accumulator {
+            return Ok(());
+        }
+
+        // The fees are already in `total_debt` from the drip
+        vault.debt_zai *= accumulator / vault.rate_snapshot;
+        vault.rate_snapshot = accumulator;
+
+        // Normalized debt only moves by rounding, but keep the key exact
+        self.reindex(vault_id);
         Ok(())
     }
 
@@ -107,31 +373,30 @@ impl VaultRegistry {
         debt_zai: f64,
         block: u64,
         amm: &Amm,
-    ) -> Result<u64, String> {
+    ) -> Result<u64, ZaiSimError> {
         if collateral_zec <= 0.0 {
-            return Err("Collateral must be positive".to_string());
+            return Err(ZaiSimError::InvalidInput(
+                "Collateral must be positive".to_string(),
+            ));
         }
         if debt_zai < 0.0 {
-            return Err("Debt cannot be negative".to_string());
+            return Err(ZaiSimError::InvalidInput(
+                "Debt cannot be negative".to_string(),
+            ));
         }
 
         // Check debt floor (zero debt is allowed — collateral-only vault)
-        if debt_zai > 0.0 && debt_zai < self.config.debt_floor {
-            return Err(format!(
-                "Debt {} below floor {}",
-                debt_zai, self.config.debt_floor
-            ));
-        }
+        self.config.check_debt_floor(debt_zai)?;
 
         // Check collateral ratio
         if debt_zai > 0.0 {
             let price = self.get_price(amm);
             let ratio = (collateral_zec * price) / debt_zai;
             if ratio < self.config.min_ratio {
-                return Err(format!(
-                    "Collateral ratio {:.4} below minimum {:.4}",
-                    ratio, self.config.min_ratio
-                ));
+                return Err(ZaiSimError::BelowMinRatio {
+                    ratio,
+                    min: self.config.min_ratio,
+                });
             }
         }
 
@@ -145,49 +410,47 @@ impl VaultRegistry {
             debt_zai,
             last_fee_block: block,
             created_block: block,
+            rate_snapshot: self.drip(block),
         };
 
         self.vaults.insert(id, vault);
         self.total_debt += debt_zai;
+        self.reindex(id);
 
         Ok(id)
     }
 
     /// Close a vault — repay all debt, return all collateral.
     /// Returns (collateral_returned, total_debt_owed) including accrued fees.
-    pub fn close_vault(
-        &mut self,
-        vault_id: u64,
-        block: u64,
-    ) -> Result<(f64, f64), String> {
+    pub fn close_vault(&mut self, vault_id: u64, block: u64) -> Result<(f64, f64), ZaiSimError> {
         self.accrue_fees(vault_id, block)?;
 
         let vault = self
             .vaults
             .remove(&vault_id)
-            .ok_or_else(|| format!("Vault {} not found", vault_id))?;
+            .ok_or(ZaiSimError::VaultNotFound(vault_id))?;
 
         self.total_debt -= vault.debt_zai;
+        self.reindex(vault_id);
 
         Ok((vault.collateral_zec, vault.debt_zai))
     }
 
     /// Deposit additional collateral into a vault.
-    pub fn deposit_collateral(
-        &mut self,
-        vault_id: u64,
-        amount: f64,
-    ) -> Result<(), String> {
+    pub fn deposit_collateral(&mut self, vault_id: u64, amount: f64) -> Result<(), ZaiSimError> {
         if amount <= 0.0 {
-            return Err("Amount must be positive".to_string());
+            return Err(ZaiSimError::InvalidInput(
+                "Amount must be positive".to_string(),
+            ));
         }
 
         let vault = self
             .vaults
             .get_mut(&vault_id)
-            .ok_or_else(|| format!("Vault {} not found", vault_id))?;
+            .ok_or(ZaiSimError::VaultNotFound(vault_id))?;
 
         vault.collateral_zec += amount;
+        self.reindex(vault_id);
         Ok(())
     }
 
@@ -198,9 +461,11 @@ impl VaultRegistry {
         amount: f64,
         block: u64,
         amm: &Amm,
-    ) -> Result<(), String> {
+    ) -> Result<(), ZaiSimError> {
         if amount <= 0.0 {
-            return Err("Amount must be positive".to_string());
+            return Err(ZaiSimError::InvalidInput(
+                "Amount must be positive".to_string(),
+            ));
         }
 
         self.accrue_fees(vault_id, block)?;
@@ -210,13 +475,14 @@ impl VaultRegistry {
         let vault = self
             .vaults
             .get_mut(&vault_id)
-            .ok_or_else(|| format!("Vault {} not found", vault_id))?;
+            .ok_or(ZaiSimError::VaultNotFound(vault_id))?;
 
         if amount > vault.collateral_zec {
-            return Err(format!(
-                "Insufficient collateral: have {}, requested {}",
-                vault.collateral_zec, amount
-            ));
+            return Err(ZaiSimError::InsufficientBalance {
+                what: "collateral".to_string(),
+                have: vault.collateral_zec,
+                requested: amount,
+            });
         }
 
         let new_collateral = vault.collateral_zec - amount;
@@ -225,14 +491,15 @@ impl VaultRegistry {
         if vault.debt_zai > 0.0 {
             let new_ratio = (new_collateral * price) / vault.debt_zai;
             if new_ratio < self.config.min_ratio {
-                return Err(format!(
-                    "Withdrawal would drop ratio to {:.4}, below minimum {:.4}",
-                    new_ratio, self.config.min_ratio
-                ));
+                return Err(ZaiSimError::BelowMinRatio {
+                    ratio: new_ratio,
+                    min: self.config.min_ratio,
+                });
             }
         }
 
         vault.collateral_zec = new_collateral;
+        self.reindex(vault_id);
         Ok(())
     }
 
@@ -243,9 +510,11 @@ impl VaultRegistry {
         amount: f64,
         block: u64,
         amm: &Amm,
-    ) -> Result<(), String> {
+    ) -> Result<(), ZaiSimError> {
         if amount <= 0.0 {
-            return Err("Amount must be positive".to_string());
+            return Err(ZaiSimError::InvalidInput(
+                "Amount must be positive".to_string(),
+            ));
         }
 
         self.accrue_fees(vault_id, block)?;
@@ -255,42 +524,35 @@ impl VaultRegistry {
         let vault = self
             .vaults
             .get_mut(&vault_id)
-            .ok_or_else(|| format!("Vault {} not found", vault_id))?;
+            .ok_or(ZaiSimError::VaultNotFound(vault_id))?;
 
         let new_debt = vault.debt_zai + amount;
 
         // Check debt floor
-        if new_debt < self.config.debt_floor {
-            return Err(format!(
-                "Total debt {} would be below floor {}",
-                new_debt, self.config.debt_floor
-            ));
-        }
+        self.config.check_debt_floor(new_debt)?;
 
         // Check collateral ratio
         let new_ratio = (vault.collateral_zec * price) / new_debt;
         if new_ratio < self.config.min_ratio {
-            return Err(format!(
-                "Borrow would drop ratio to {:.4}, below minimum {:.4}",
-                new_ratio, self.config.min_ratio
-            ));
+            return Err(ZaiSimError::BelowMinRatio {
+                ratio: new_ratio,
+                min: self.config.min_ratio,
+            });
         }
 
         self.total_debt += amount;
         vault.debt_zai = new_debt;
+        self.reindex(vault_id);
         Ok(())
     }
 
     /// Repay ZAI debt. Full repayment (to zero) is always allowed.
     /// Partial repayment must not leave debt below the floor.
-    pub fn repay_zai(
-        &mut self,
-        vault_id: u64,
-        amount: f64,
-        block: u64,
-    ) -> Result<(), String> {
+    pub fn repay_zai(&mut self, vault_id: u64, amount: f64, block: u64) -> Result<(), ZaiSimError> {
         if amount <= 0.0 {
-            return Err("Amount must be positive".to_string());
+            return Err(ZaiSimError::InvalidInput(
+                "Amount must be positive".to_string(),
+            ));
         }
 
         self.accrue_fees(vault_id, block)?;
@@ -298,33 +560,64 @@ impl VaultRegistry {
         let vault = self
             .vaults
             .get_mut(&vault_id)
-            .ok_or_else(|| format!("Vault {} not found", vault_id))?;
+            .ok_or(ZaiSimError::VaultNotFound(vault_id))?;
 
         if amount > vault.debt_zai {
-            return Err(format!(
+            return Err(ZaiSimError::InvalidInput(format!(
                 "Repayment {} exceeds debt {}",
                 amount, vault.debt_zai
-            ));
+            )));
         }
 
         let new_debt = vault.debt_zai - amount;
 
         // Partial repayment must respect debt floor (full repay to 0 is fine)
-        if new_debt > 0.0 && new_debt < self.config.debt_floor {
-            return Err(format!(
-                "Partial repayment would leave debt {} below floor {}. Repay fully or leave above floor.",
-                new_debt, self.config.debt_floor
-            ));
-        }
+        self.config.check_debt_floor(new_debt)?;
 
         self.total_debt -= amount;
         vault.debt_zai = new_debt;
+        self.reindex(vault_id);
         Ok(())
     }
 
+    /// Settle dust left on a vault by a partial liquidation: up to
+    /// `penalty` ZAI of the penalty repays it, the rest is written off, and
+    /// the vault closes. Returns the penalty used, the debt written off and
+    /// the collateral left in the closed vault, which is the owner's; all
+    /// zero for a vault without dust.
+    pub fn clear_dust(
+        &mut self,
+        vault_id: u64,
+        penalty: f64,
+    ) -> Result<(f64, f64, f64), ZaiSimError> {
+        let vault = self
+            .vaults
+            .get(&vault_id)
+            .ok_or(ZaiSimError::VaultNotFound(vault_id))?;
+        let (debt, collateral) = (vault.debt_zai, vault.collateral_zec);
+        if !self.config.is_dust(debt) {
+            return Ok((0.0, 0.0, 0.0));
+        }
+        let used = penalty.clamp(0.0, debt);
+        self.vaults.remove(&vault_id);
+        self.total_debt -= debt;
+        self.reindex(vault_id);
+        Ok((used, debt - used, collateral))
+    }
+
+    /// Vaults whose debt, with fees due, is dust.
+    pub fn dust_vault_count(&self) -> usize {
+        self.vaults
+            .values()
+            .filter(|v| self.config.is_dust(self.debt_due(v)))
+            .count()
+    }
+
     /// Accrue stability fees on all vaults and return the total fee delta in ZAI.
     pub fn accrue_all_fees(&mut self, block: u64) -> f64 {
-        let vault_ids: Vec<u64> = self.vaults.keys().copied().collect();
+        // Sorted so the fee sum does not depend on hash-map order
+        let mut vault_ids: Vec<u64> = self.vaults.keys().copied().collect();
+        vault_ids.sort();
         let mut total_fees = 0.0;
         for id in vault_ids {
             let old_debt = self.vaults[&id].debt_zai;
@@ -334,7 +627,8 @@ impl VaultRegistry {
         total_fees
     }
 
-    /// Check if a vault is liquidatable (ratio below min_ratio).
+    /// Check if a vault is liquidatable (ratio below min_ratio, with fees
+    /// due).
     pub fn is_liquidatable(&self, vault_id: u64, amm: &Amm) -> bool {
         let vault = match self.vaults.get(&vault_id) {
             Some(v) => v,
@@ -346,17 +640,106 @@ impl VaultRegistry {
         }
 
         let price = self.get_price(amm);
-        vault.collateral_ratio(price) < self.config.min_ratio
+        self.ratio_due(vault, price) < self.config.min_ratio
     }
 
     /// Calculate the liquidation penalty amount for a vault.
     pub fn liquidation_penalty_amount(&self, vault_id: u64) -> Option<f64> {
         let vault = self.vaults.get(&vault_id)?;
-        Some(vault.debt_zai * self.config.liquidation_penalty)
+        Some(self.debt_due(vault) * self.config.liquidation_penalty)
     }
 
-    /// Get a vault by ID (immutable).
+    /// IDs of vaults with debt, ordered by collateral ratio at `price` (lowest first).
+    pub fn vaults_by_collateral_ratio(&self, price: f64) -> Vec<u64> {
+        let mut entries: Vec<(u64, f64)> = self
+            .vaults
+            .values()
+            .filter(|v| v.debt_zai > 0.0)
+            .map(|v| (v.id, self.ratio_due(v, price)))
+            .collect();
+        entries.sort_by(|a, b| {
+            a.1.partial_cmp(&b.1)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then(a.0.cmp(&b.0))
+        });
+        entries.into_iter().map(|(id, _)| id).collect()
+    }
+
+    /// Get a vault by ID (immutable), with debt as of its last accrual;
+    /// `vault_at` adds the fees due since.
     pub fn get_vault(&self, vault_id: u64) -> Option<&Vault> {
         self.vaults.get(&vault_id)
     }
+
+    /// IDs of `owner`'s vaults, oldest first.
+    pub fn vaults_of(&self, owner: &str) -> Vec<u64> {
+        let mut ids: Vec<u64> = self
+            .vaults
+            .values()
+            .filter(|v| v.owner == owner)
+            .map(|v| v.id)
+            .collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    /// IDs of `owner`'s vaults with debt, ordered by collateral ratio at
+    /// `price` (riskiest first).
+    pub fn vaults_of_by_ratio(&self, owner: &str, price: f64) -> Vec<u64> {
+        let mut entries: Vec<(u64, f64)> = self
+            .vaults
+            .values()
+            .filter(|v| v.owner == owner && v.debt_zai > 0.0)
+            .map(|v| (v.id, self.ratio_due(v, price)))
+            .collect();
+        entries.sort_by(|a, b| {
+            a.1.partial_cmp(&b.1)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then(a.0.cmp(&b.0))
+        });
+        entries.into_iter().map(|(id, _)| id).collect()
+    }
+
+    /// Total collateral and debt (with fees due) across `owner`'s vaults.
+    pub fn owner_totals(&self, owner: &str) -> (f64, f64) {
+        self.vaults
+            .values()
+            .filter(|v| v.owner == owner)
+            .fold((0.0, 0.0), |(c, d), v| {
+                (c + v.collateral_zec, d + self.debt_due(v))
+            })
+    }
+
+    /// Fold vault `from` into `into`, which must have the same owner: its
+    /// collateral and debt (with fees accrued) move over and `from` closes.
+    pub fn merge_vaults(&mut self, from: u64, into: u64, block: u64) -> Result<(), ZaiSimError> {
+        if from == into {
+            return Err(ZaiSimError::InvalidInput(
+                "Cannot merge a vault into itself".to_string(),
+            ));
+        }
+        self.accrue_fees(from, block)?;
+        self.accrue_fees(into, block)?;
+        let same_owner = self.vaults[&from].owner == self.vaults[&into].owner;
+        if !same_owner {
+            return Err(ZaiSimError::InvalidInput(format!(
+                "Vaults {} and {} have different owners",
+                from, into
+            )));
+        }
+
+        let merged = self
+            .vaults
+            .remove(&from)
+            .ok_or(ZaiSimError::VaultNotFound(from))?;
+        let vault = self
+            .vaults
+            .get_mut(&into)
+            .ok_or(ZaiSimError::VaultNotFound(into))?;
+        vault.collateral_zec += merged.collateral_zec;
+        vault.debt_zai += merged.debt_zai;
+        self.reindex(from);
+        self.reindex(into);
+        Ok(())
+    }
 }
@@ -0,0 +1,179 @@
+//! Debt ceiling auto-growth policy.
+//!
+//! The circuit breaker's debt ceiling only steps down on a price deviation
+//! and creeps back to its initial value. A ceiling policy takes over the
+//! ceiling's path for a bootstrap phase: while the system is well
+//! collateralized and free of recent bad debt the ceiling grows toward a
+//! multiple of the AMM's ZAI depth, and when collateralization thins or bad
+//! debt appears it shrinks toward `min_ceiling`.
+//!
+//! Growing starts once the collateral ratio is above `grow_above_ratio` and
+//! shrinking once it is below `shrink_below_ratio` (or bad debt in the
+//! window exceeds `max_recent_bad_debt`); in between the policy keeps its
+//! current course. A new course must be signalled for `hysteresis_blocks`
+//! in a row before it is taken, so the ceiling doesn't flap on noise.
+//!
+//! With a policy the ceiling also binds: CDP holders whose vault would take
+//! total debt over it wait in line and open once there is room.
+
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::circuit_breaker::DebtCeiling;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CeilingPolicyConfig {
+    /// Ceiling to grow toward, as a multiple of the AMM's ZAI reserve
+    pub depth_multiple: f64,
+    /// System collateral ratio above which the ceiling grows
+    pub grow_above_ratio: f64,
+    /// System collateral ratio below which the ceiling shrinks
+    pub shrink_below_ratio: f64,
+    /// Bad debt (ZAI) within the window that makes the ceiling shrink
+    pub max_recent_bad_debt: f64,
+    /// Blocks of bad debt history considered
+    pub bad_debt_window_blocks: u64,
+    /// Fractional ceiling growth per block
+    pub growth_rate: f64,
+    /// Fractional ceiling reduction per block
+    pub shrink_rate: f64,
+    /// Consecutive blocks a new course must be signalled before it is taken
+    pub hysteresis_blocks: u64,
+    /// Hard cap on the ceiling
+    pub max_ceiling: f64,
+}
+
+impl Default for CeilingPolicyConfig {
+    fn default() -> Self {
+        CeilingPolicyConfig {
+            depth_multiple: 10.0,
+            grow_above_ratio: 2.5,
+            shrink_below_ratio: 1.75,
+            max_recent_bad_debt: 100.0,
+            bad_debt_window_blocks: 1000,
+            growth_rate: 0.002,
+            shrink_rate: 0.005,
+            hysteresis_blocks: 48,
+            max_ceiling: 10_000_000.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CeilingMode {
+    /// No course taken yet
+    #[default]
+    Hold,
+    Grow,
+    Shrink,
+}
+
+/// System state the policy reads each block.
+#[derive(Debug, Clone, Copy)]
+pub struct CeilingInputs {
+    /// ZAI reserve of the AMM
+    pub amm_depth_zai: f64,
+    pub total_debt: f64,
+    /// Value of all vault collateral at the registry's price (ZAI)
+    pub collateral_value: f64,
+    /// Cumulative bad debt
+    pub total_bad_debt: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CeilingPolicy {
+    pub config: CeilingPolicyConfig,
+    pub mode: CeilingMode,
+    pending: CeilingMode,
+    pending_blocks: u64,
+    bad_debt_history: VecDeque<f64>,
+    /// Number of times the course changed
+    pub mode_changes: u64,
+    /// CDP holders (by index) waiting for room under the ceiling, in order
+    pub waiting: VecDeque<usize>,
+    /// Vaults opened from the waiting line
+    pub vaults_admitted: u64,
+}
+
+impl CeilingPolicy {
+    pub fn new(config: CeilingPolicyConfig) -> Self {
+        CeilingPolicy {
+            config,
+            mode: CeilingMode::Hold,
+            pending: CeilingMode::Hold,
+            pending_blocks: 0,
+            bad_debt_history: VecDeque::new(),
+            mode_changes: 0,
+            waiting: VecDeque::new(),
+            vaults_admitted: 0,
+        }
+    }
+
+    /// Bad debt taken on within the window.
+    pub fn recent_bad_debt(&self) -> f64 {
+        match (self.bad_debt_history.front(), self.bad_debt_history.back()) {
+            (Some(first), Some(last)) => last - first,
+            _ => 0.0,
+        }
+    }
+
+    /// The course this block's inputs call for; `None` inside the band.
+    fn signal(&self, inputs: &CeilingInputs) -> Option<CeilingMode> {
+        let ratio = if inputs.total_debt > 0.0 {
+            inputs.collateral_value / inputs.total_debt
+        } else {
+            f64::INFINITY
+        };
+        if self.recent_bad_debt() > self.config.max_recent_bad_debt
+            || ratio < self.config.shrink_below_ratio
+        {
+            Some(CeilingMode::Shrink)
+        } else if ratio > self.config.grow_above_ratio {
+            Some(CeilingMode::Grow)
+        } else {
+            None
+        }
+    }
+
+    /// Update the course from this block's inputs and move `ceiling` one
+    /// block along it.
+    pub fn adjust(&mut self, ceiling: &mut DebtCeiling, inputs: &CeilingInputs) {
+        self.bad_debt_history.push_back(inputs.total_bad_debt);
+        while self.bad_debt_history.len() as u64 > self.config.bad_debt_window_blocks + 1 {
+            self.bad_debt_history.pop_front();
+        }
+
+        match self.signal(inputs) {
+            Some(signal) if signal != self.mode => {
+                if signal == self.pending {
+                    self.pending_blocks += 1;
+                } else {
+                    self.pending = signal;
+                    self.pending_blocks = 1;
+                }
+                if self.pending_blocks >= self.config.hysteresis_blocks {
+                    self.mode = signal;
+                    self.pending_blocks = 0;
+                    self.mode_changes += 1;
+                }
+            }
+            _ => self.pending_blocks = 0,
+        }
+
+        let c = &self.config;
+        let floor = ceiling.config.min_ceiling;
+        let target = (inputs.amm_depth_zai * c.depth_multiple)
+            .min(c.max_ceiling)
+            .max(floor);
+        let current = ceiling.current_ceiling;
+        ceiling.current_ceiling = match self.mode {
+            CeilingMode::Hold => current,
+            // Growing tracks the depth target, down as well as up
+            CeilingMode::Grow if current < target => (current * (1.0 + c.growth_rate)).min(target),
+            CeilingMode::Grow => (current * (1.0 - c.shrink_rate)).max(target),
+            CeilingMode::Shrink => (current * (1.0 - c.shrink_rate)).max(floor),
+        };
+    }
+}
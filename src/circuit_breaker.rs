@@ -1,8 +1,13 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
 use crate::amm::Amm;
 use crate::cdp::VaultRegistry;
+use crate::oracle::Oracle;
 
 /// Circuit breaker actions the simulation loop should take.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum BreakerAction {
     /// No action needed.
     None,
@@ -12,13 +17,53 @@ pub enum BreakerAction {
     ReduceDebtCeiling { new_ceiling: f64, reason: String },
     /// Halt all non-liquidation activity.
     EmergencyHalt { reason: String },
+    /// Graded halt: agents keep acting under swap caps, frozen minting and
+    /// rate-limited LP withdrawals.
+    PartialHalt { reason: String },
+    /// Hold the redemption price at the edge of its allowed drift.
+    CapRedemptionDrift { capped_price: f64, reason: String },
+    /// Pause minting for N blocks after too much net new debt in a window.
+    RateLimitMinting { blocks: u64, reason: String },
+    /// Vaults partially unwound this block while system collateralization
+    /// was thin.
+    AutoDeleverage { vault_ids: Vec<u64>, reason: String },
+}
+
+/// How the engine responds when the cascade breaker fires.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub enum HaltMode {
+    /// Halt all non-liquidation agent activity.
+    #[default]
+    Full,
+    /// Keep agents active under graded restrictions.
+    Graded(GradedHaltConfig),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GradedHaltConfig {
+    /// Maximum single swap input as a fraction of the AMM reserve on the input side.
+    pub max_swap_pct_of_reserve: f64,
+    /// Freeze new minting while the partial halt is active.
+    pub freeze_minting: bool,
+    /// Maximum fraction of total LP shares that may be withdrawn per block.
+    pub max_lp_withdrawal_pct_per_block: f64,
+}
+
+impl Default for GradedHaltConfig {
+    fn default() -> Self {
+        GradedHaltConfig {
+            max_swap_pct_of_reserve: 0.01,
+            freeze_minting: true,
+            max_lp_withdrawal_pct_per_block: 0.01,
+        }
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════════
 // TWAP Movement Circuit Breaker
 // ═══════════════════════════════════════════════════════════════════════
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TwapBreakerConfig {
     /// Maximum allowed TWAP change (fraction) per window before triggering.
     /// E.g., 0.15 = 15% movement triggers breaker.
@@ -42,7 +87,7 @@ impl Default for TwapBreakerConfig {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct TwapBreaker {
     pub config: TwapBreakerConfig,
     pub triggered: bool,
@@ -62,11 +107,7 @@ impl TwapBreaker {
 
     /// Check TWAP divergence and return action if breaker should trigger.
     pub fn check(&mut self, amm: &Amm, block: u64) -> BreakerAction {
-        // If currently triggered, check if we can resume
-        if self.triggered {
-            if block >= self.resume_at_block {
-                self.triggered = false;
-            }
+        if self.cooling_down(block) {
             return BreakerAction::None;
         }
 
@@ -80,25 +121,75 @@ impl TwapBreaker {
         let change = ((twap_short - twap_long) / twap_long).abs();
 
         if change > self.config.max_twap_change_pct {
-            self.triggered = true;
-            self.resume_at_block = block + self.config.pause_blocks;
-            self.trigger_count += 1;
-
-            BreakerAction::PauseMinting {
-                blocks: self.config.pause_blocks,
-                reason: format!(
+            self.trigger(
+                block,
+                format!(
                     "TWAP divergence {:.2}% exceeds {:.2}% threshold (short={:.2}, long={:.2})",
                     change * 100.0,
                     self.config.max_twap_change_pct * 100.0,
                     twap_short,
                     twap_long,
                 ),
-            }
+            )
         } else {
             BreakerAction::None
         }
     }
 
+    /// Like `check`, but measures the short TWAP against `oracle`'s price
+    /// instead of the long TWAP. Minting also pauses while the oracle has
+    /// no usable price, since new debt cannot be valued.
+    pub fn check_oracle(&mut self, amm: &Amm, oracle: &dyn Oracle, block: u64) -> BreakerAction {
+        if self.cooling_down(block) {
+            return BreakerAction::None;
+        }
+
+        let Some(oracle_price) = oracle.price() else {
+            return self.trigger(block, "Oracle has no usable price".to_string());
+        };
+        if oracle_price == 0.0 {
+            return BreakerAction::None;
+        }
+
+        let twap_short = amm.get_twap(self.config.short_window);
+        let change = ((twap_short - oracle_price) / oracle_price).abs();
+
+        if change > self.config.max_twap_change_pct {
+            self.trigger(
+                block,
+                format!(
+                    "TWAP deviates {:.2}% from oracle, exceeding {:.2}% threshold (short={:.2}, oracle={:.2})",
+                    change * 100.0,
+                    self.config.max_twap_change_pct * 100.0,
+                    twap_short,
+                    oracle_price,
+                ),
+            )
+        } else {
+            BreakerAction::None
+        }
+    }
+
+    /// While triggered, checks are skipped until the pause has run out.
+    fn cooling_down(&mut self, block: u64) -> bool {
+        if self.triggered && block >= self.resume_at_block {
+            self.triggered = false;
+            return true;
+        }
+        self.triggered
+    }
+
+    fn trigger(&mut self, block: u64, reason: String) -> BreakerAction {
+        self.triggered = true;
+        self.resume_at_block = block + self.config.pause_blocks;
+        self.trigger_count += 1;
+
+        BreakerAction::PauseMinting {
+            blocks: self.config.pause_blocks,
+            reason,
+        }
+    }
+
     pub fn is_active(&self, block: u64) -> bool {
         self.triggered && block < self.resume_at_block
     }
@@ -108,7 +199,7 @@ impl TwapBreaker {
 // Cascade Circuit Breaker
 // ═══════════════════════════════════════════════════════════════════════
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CascadeBreakerConfig {
     /// Maximum liquidations within the window before triggering.
     pub max_liquidations_in_window: u32,
@@ -128,7 +219,7 @@ impl Default for CascadeBreakerConfig {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct CascadeBreaker {
     pub config: CascadeBreakerConfig,
     pub triggered: bool,
@@ -201,7 +292,7 @@ impl CascadeBreaker {
 // Dynamic Debt Ceiling
 // ═══════════════════════════════════════════════════════════════════════
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DebtCeilingConfig {
     /// Initial maximum total debt allowed.
     pub initial_ceiling: f64,
@@ -227,7 +318,7 @@ impl Default for DebtCeilingConfig {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct DebtCeiling {
     pub config: DebtCeilingConfig,
     pub current_ceiling: f64,
@@ -287,17 +378,327 @@ impl DebtCeiling {
     }
 }
 
+// ═══════════════════════════════════════════════════════════════════════
+// Redemption Price Drift Limiter
+// ═══════════════════════════════════════════════════════════════════════
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedemptionDriftConfig {
+    /// Maximum redemption price move (fraction) within one window.
+    pub max_drift_pct: f64,
+    /// Window in blocks the drift is measured over.
+    pub window_blocks: u64,
+}
+
+impl Default for RedemptionDriftConfig {
+    fn default() -> Self {
+        RedemptionDriftConfig {
+            max_drift_pct: 0.02,
+            window_blocks: 1152, // ~1 day
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RedemptionDriftLimiter {
+    pub config: RedemptionDriftConfig,
+    /// Blocks the redemption price was held at the band edge.
+    pub capped_blocks: u64,
+    pub trigger_count: u64,
+    capping: bool,
+    /// (block, redemption price after limiting) over the window.
+    price_log: VecDeque<(u64, f64)>,
+}
+
+impl RedemptionDriftLimiter {
+    pub fn new(config: RedemptionDriftConfig) -> Self {
+        RedemptionDriftLimiter {
+            config,
+            capped_blocks: 0,
+            trigger_count: 0,
+            capping: false,
+            price_log: VecDeque::new(),
+        }
+    }
+
+    /// Keep `redemption_price` within `max_drift_pct` of the price at the
+    /// start of the window. Returns the price to use; an action is emitted
+    /// when capping starts, not for every capped block.
+    pub fn limit(&mut self, redemption_price: f64, block: u64) -> (f64, BreakerAction) {
+        while self
+            .price_log
+            .front()
+            .is_some_and(|(b, _)| b + self.config.window_blocks < block)
+        {
+            self.price_log.pop_front();
+        }
+        let anchor = self.price_log.front().map_or(redemption_price, |(_, p)| *p);
+        let drift = self.config.max_drift_pct.max(0.0);
+        let capped = redemption_price.clamp(anchor * (1.0 - drift), anchor * (1.0 + drift));
+        self.price_log.push_back((block, capped));
+
+        let was_capping = self.capping;
+        self.capping = capped != redemption_price;
+        if !self.capping {
+            return (capped, BreakerAction::None);
+        }
+        self.capped_blocks += 1;
+        if was_capping {
+            return (capped, BreakerAction::None);
+        }
+        self.trigger_count += 1;
+        (
+            capped,
+            BreakerAction::CapRedemptionDrift {
+                capped_price: capped,
+                reason: format!(
+                    "Redemption price {:.4} drifts {:.2}% from {:.4} within {} blocks, over {:.2}% limit",
+                    redemption_price,
+                    ((redemption_price - anchor) / anchor).abs() * 100.0,
+                    anchor,
+                    self.config.window_blocks,
+                    drift * 100.0,
+                ),
+            },
+        )
+    }
+
+    pub fn is_capping(&self) -> bool {
+        self.capping
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+// Minting Rate Limiter
+// ═══════════════════════════════════════════════════════════════════════
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MintRateLimiterConfig {
+    /// Maximum net new debt (ZAI) within one window.
+    pub max_net_mint: f64,
+    /// Window in blocks the net new debt is measured over.
+    pub window_blocks: u64,
+    /// Blocks to pause minting when the limit is exceeded.
+    pub pause_blocks: u64,
+}
+
+impl Default for MintRateLimiterConfig {
+    fn default() -> Self {
+        MintRateLimiterConfig {
+            max_net_mint: 50_000.0,
+            window_blocks: 1152, // ~1 day
+            pause_blocks: 96,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MintRateLimiter {
+    pub config: MintRateLimiterConfig,
+    pub triggered: bool,
+    pub resume_at_block: u64,
+    pub trigger_count: u64,
+    /// (block, total debt) over the window.
+    debt_log: VecDeque<(u64, f64)>,
+}
+
+impl MintRateLimiter {
+    pub fn new(config: MintRateLimiterConfig) -> Self {
+        MintRateLimiter {
+            config,
+            triggered: false,
+            resume_at_block: 0,
+            trigger_count: 0,
+            debt_log: VecDeque::new(),
+        }
+    }
+
+    /// Net new debt since the start of the window.
+    pub fn net_minted(&self, total_debt: f64) -> f64 {
+        self.debt_log.front().map_or(0.0, |(_, d)| total_debt - d)
+    }
+
+    /// Check if minting `new_debt` stays within this window's limit.
+    pub fn allows(&self, total_debt: f64, new_debt: f64) -> bool {
+        self.net_minted(total_debt) + new_debt <= self.config.max_net_mint
+    }
+
+    /// Record this block's total debt and pause minting if the window's
+    /// net new debt is over the limit.
+    pub fn check(&mut self, total_debt: f64, block: u64) -> BreakerAction {
+        while self
+            .debt_log
+            .front()
+            .is_some_and(|(b, _)| b + self.config.window_blocks < block)
+        {
+            self.debt_log.pop_front();
+        }
+        self.debt_log.push_back((block, total_debt));
+
+        if self.triggered {
+            if block >= self.resume_at_block {
+                self.triggered = false;
+            }
+            return BreakerAction::None;
+        }
+
+        let minted = self.net_minted(total_debt);
+        if minted > self.config.max_net_mint {
+            self.triggered = true;
+            self.resume_at_block = block + self.config.pause_blocks;
+            self.trigger_count += 1;
+            BreakerAction::RateLimitMinting {
+                blocks: self.config.pause_blocks,
+                reason: format!(
+                    "Net new debt {:.0} in {} blocks exceeds limit of {:.0}",
+                    minted, self.config.window_blocks, self.config.max_net_mint,
+                ),
+            }
+        } else {
+            BreakerAction::None
+        }
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+// Auto-Deleveraging Breaker
+// ═══════════════════════════════════════════════════════════════════════
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutoDeleverageConfig {
+    /// System collateral ratio below which the breaker engages.
+    pub engage_below_cr: f64,
+    /// System collateral ratio above which it disengages again.
+    pub release_above_cr: f64,
+    /// Vaults below this collateral ratio, but not yet liquidatable, are
+    /// deleveraged while engaged.
+    pub vault_cr_threshold: f64,
+    /// Most vaults deleveraged per block, riskiest first.
+    pub max_vaults_per_block: usize,
+}
+
+impl Default for AutoDeleverageConfig {
+    fn default() -> Self {
+        AutoDeleverageConfig {
+            engage_below_cr: 2.0,
+            release_above_cr: 2.25,
+            vault_cr_threshold: 1.8,
+            max_vaults_per_block: 5,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AutoDeleverageBreaker {
+    pub config: AutoDeleverageConfig,
+    pub engaged: bool,
+    pub trigger_count: u64,
+    /// Liquidation passes made while engaged.
+    pub engaged_blocks: u64,
+    /// Partial unwinds performed.
+    pub vaults_deleveraged: u64,
+    /// System collateral ratio at the last engaged selection.
+    engaged_cr: f64,
+    /// Vaults deleveraged since the last engine check.
+    pending: Vec<u64>,
+}
+
+impl AutoDeleverageBreaker {
+    pub fn new(config: AutoDeleverageConfig) -> Self {
+        AutoDeleverageBreaker {
+            config,
+            engaged: false,
+            trigger_count: 0,
+            engaged_blocks: 0,
+            vaults_deleveraged: 0,
+            engaged_cr: 0.0,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Update the engaged state from the system collateral ratio and pick
+    /// the vaults to deleverage, lowest collateral ratio first. Vaults
+    /// already liquidatable are left to liquidation.
+    pub fn select(&mut self, registry: &VaultRegistry, amm: &Amm) -> Vec<u64> {
+        let price = registry.get_price(amm);
+        let collateral: f64 = registry.vaults.values().map(|v| v.collateral_zec).sum();
+        let system_cr = if registry.total_debt > 0.0 {
+            collateral * price / registry.total_debt
+        } else {
+            f64::INFINITY
+        };
+
+        if !self.engaged && system_cr < self.config.engage_below_cr {
+            self.engaged = true;
+            self.trigger_count += 1;
+        } else if self.engaged && system_cr > self.config.release_above_cr {
+            self.engaged = false;
+        }
+        if !self.engaged {
+            return Vec::new();
+        }
+        self.engaged_blocks += 1;
+        self.engaged_cr = system_cr;
+
+        let min_ratio = registry.config.min_ratio;
+        let mut candidates: Vec<(f64, u64)> = registry
+            .vaults
+            .iter()
+            .filter(|(_, v)| v.debt_zai > 0.0)
+            .map(|(id, v)| (registry.ratio_due(v, price), *id))
+            .filter(|(cr, _)| *cr >= min_ratio && *cr < self.config.vault_cr_threshold)
+            .collect();
+        candidates.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+        candidates
+            .into_iter()
+            .take(self.config.max_vaults_per_block)
+            .map(|(_, id)| id)
+            .collect()
+    }
+
+    /// Note vaults that were deleveraged, to be reported by the next check.
+    pub fn record(&mut self, vault_ids: &[u64]) {
+        self.vaults_deleveraged += vault_ids.len() as u64;
+        self.pending.extend_from_slice(vault_ids);
+    }
+
+    /// Report the vaults deleveraged since the last check.
+    pub fn check(&mut self) -> BreakerAction {
+        if self.pending.is_empty() {
+            return BreakerAction::None;
+        }
+        let vault_ids = std::mem::take(&mut self.pending);
+        BreakerAction::AutoDeleverage {
+            reason: format!(
+                "System CR {:.3} below {:.2}: deleveraged {} vault(s)",
+                self.engaged_cr,
+                self.config.engage_below_cr,
+                vault_ids.len(),
+            ),
+            vault_ids,
+        }
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════════
 // Combined Circuit Breaker Engine
 // ═══════════════════════════════════════════════════════════════════════
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct CircuitBreakerEngine {
     pub twap_breaker: TwapBreaker,
     pub cascade_breaker: CascadeBreaker,
     pub debt_ceiling: DebtCeiling,
+    #[serde(default)]
+    pub redemption_limiter: Option<RedemptionDriftLimiter>,
+    #[serde(default)]
+    pub mint_limiter: Option<MintRateLimiter>,
+    #[serde(default)]
+    pub auto_deleverage: Option<AutoDeleverageBreaker>,
     pub minting_paused_until: u64,
     pub halted_until: u64,
+    pub halt_mode: HaltMode,
+    pub partial_halt_until: u64,
 }
 
 impl CircuitBreakerEngine {
@@ -310,8 +711,13 @@ impl CircuitBreakerEngine {
             twap_breaker: TwapBreaker::new(twap_config),
             cascade_breaker: CascadeBreaker::new(cascade_config),
             debt_ceiling: DebtCeiling::new(ceiling_config),
+            redemption_limiter: None,
+            mint_limiter: None,
+            auto_deleverage: None,
             minting_paused_until: 0,
             halted_until: 0,
+            halt_mode: HaltMode::Full,
+            partial_halt_until: 0,
         }
     }
 
@@ -322,11 +728,27 @@ impl CircuitBreakerEngine {
         registry: &VaultRegistry,
         redemption_price: f64,
         block: u64,
+    ) -> Vec<BreakerAction> {
+        self.check_all_with_oracle(amm, registry, redemption_price, block, None)
+    }
+
+    /// `check_all`, with the TWAP breaker measuring against `oracle` when
+    /// one is given.
+    pub fn check_all_with_oracle(
+        &mut self,
+        amm: &Amm,
+        registry: &VaultRegistry,
+        redemption_price: f64,
+        block: u64,
+        oracle: Option<&dyn Oracle>,
     ) -> Vec<BreakerAction> {
         let mut actions = Vec::new();
 
         // TWAP breaker
-        let twap_action = self.twap_breaker.check(amm, block);
+        let twap_action = match oracle {
+            Some(oracle) => self.twap_breaker.check_oracle(amm, oracle, block),
+            None => self.twap_breaker.check(amm, block),
+        };
         if let BreakerAction::PauseMinting { blocks, .. } = &twap_action {
             self.minting_paused_until = self.minting_paused_until.max(block + blocks);
         }
@@ -335,9 +757,23 @@ impl CircuitBreakerEngine {
         }
 
         // Cascade breaker
-        let cascade_action = self.cascade_breaker.check(block);
-        if let BreakerAction::EmergencyHalt { .. } = &cascade_action {
-            self.halted_until = self.halted_until.max(block + self.cascade_breaker.config.pause_blocks);
+        let mut cascade_action = self.cascade_breaker.check(block);
+        if let BreakerAction::EmergencyHalt { reason } = &cascade_action {
+            let until = block + self.cascade_breaker.config.pause_blocks;
+            match &self.halt_mode {
+                HaltMode::Full => {
+                    self.halted_until = self.halted_until.max(until);
+                }
+                HaltMode::Graded(graded) => {
+                    self.partial_halt_until = self.partial_halt_until.max(until);
+                    if graded.freeze_minting {
+                        self.minting_paused_until = self.minting_paused_until.max(until);
+                    }
+                    cascade_action = BreakerAction::PartialHalt {
+                        reason: reason.clone(),
+                    };
+                }
+            }
         }
         if cascade_action != BreakerAction::None {
             actions.push(cascade_action);
@@ -349,6 +785,25 @@ impl CircuitBreakerEngine {
             actions.push(ceiling_action);
         }
 
+        // Minting rate limiter
+        if let Some(limiter) = &mut self.mint_limiter {
+            let mint_action = limiter.check(registry.total_debt, block);
+            if let BreakerAction::RateLimitMinting { blocks, .. } = &mint_action {
+                self.minting_paused_until = self.minting_paused_until.max(block + blocks);
+            }
+            if mint_action != BreakerAction::None {
+                actions.push(mint_action);
+            }
+        }
+
+        // Auto-deleveraging done since the last check
+        if let Some(breaker) = &mut self.auto_deleverage {
+            let deleverage_action = breaker.check();
+            if deleverage_action != BreakerAction::None {
+                actions.push(deleverage_action);
+            }
+        }
+
         actions
     }
 
@@ -360,7 +815,47 @@ impl CircuitBreakerEngine {
         block < self.halted_until
     }
 
+    pub fn is_partially_halted(&self, block: u64) -> bool {
+        block < self.partial_halt_until
+    }
+
+    /// Restrictions to apply this block, if a graded halt is active.
+    pub fn graded_restrictions(&self, block: u64) -> Option<&GradedHaltConfig> {
+        match &self.halt_mode {
+            HaltMode::Graded(graded) if self.is_partially_halted(block) => Some(graded),
+            _ => None,
+        }
+    }
+
     pub fn record_liquidations(&mut self, block: u64, count: u32) {
         self.cascade_breaker.record_liquidations(block, count);
     }
+
+    /// Hold the controller's `redemption_price` within the drift limiter's
+    /// band, if one is configured. Returns the price to use.
+    pub fn limit_redemption_drift(
+        &mut self,
+        redemption_price: f64,
+        block: u64,
+    ) -> (f64, BreakerAction) {
+        match &mut self.redemption_limiter {
+            Some(limiter) => limiter.limit(redemption_price, block),
+            None => (redemption_price, BreakerAction::None),
+        }
+    }
+
+    /// Check if minting `new_debt` stays within the rate limiter's window.
+    pub fn mint_allowed(&self, total_debt: f64, new_debt: f64) -> bool {
+        self.mint_limiter
+            .as_ref()
+            .is_none_or(|l| l.allows(total_debt, new_debt))
+    }
+
+    /// Vaults the auto-deleveraging breaker wants unwound now, if one is
+    /// configured and engaged.
+    pub fn deleverage_targets(&mut self, registry: &VaultRegistry, amm: &Amm) -> Vec<u64> {
+        self.auto_deleverage
+            .as_mut()
+            .map_or_else(Vec::new, |b| b.select(registry, amm))
+    }
 }
@@ -1,3 +1,7 @@
+use serde::{Deserialize, Serialize};
+
+use crate::fixed;
+
 /// Stability controller: adjusts redemption_price via redemption_rate
 /// based on the deviation between market_price and redemption_price.
 ///
@@ -5,7 +9,7 @@
 /// - PI: proportional + integral with anti-windup clamping
 /// - Tick: Rico-style integral-only, log-scale, with sensitivity parameter
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ControllerMode {
     /// Classic PI controller with proportional and integral gains.
     PI {
@@ -21,7 +25,7 @@ pub enum ControllerMode {
     },
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ControllerConfig {
     pub mode: ControllerMode,
     /// Minimum redemption rate per block (negative = price falling)
@@ -65,7 +69,7 @@ impl ControllerConfig {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Controller {
     pub config: ControllerConfig,
     /// Target price of ZAI in USD
@@ -96,7 +100,7 @@ impl Controller {
             return;
         }
         let blocks_elapsed = block - self.last_block;
-        self.redemption_price *= (1.0 + self.redemption_rate).powi(blocks_elapsed as i32);
+        self.redemption_price *= fixed::powi(1.0 + self.redemption_rate, blocks_elapsed as i32);
         self.last_block = block;
     }
 
@@ -144,7 +148,7 @@ impl Controller {
     /// error_log = ln(market / target)
     /// Integral-only with negative feedback on log scale.
     fn update_tick(&mut self, market_price: f64, sensitivity: f64) -> f64 {
-        let error_log = (market_price / self.redemption_price).ln();
+        let error_log = fixed::ln(market_price / self.redemption_price);
 
         // Integral accumulates with negative feedback
         self.integral += -sensitivity * error_log;
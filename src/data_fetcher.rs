@@ -1,8 +1,16 @@
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
 use serde::Deserialize;
 use std::path::Path;
+use std::str::FromStr;
 use std::thread;
 use std::time::Duration;
 
+use crate::depth::DepthSnapshot;
+use crate::error::ZaiSimError;
+use crate::perp::{FundingSample, OpenInterestSample};
+use crate::price_matrix::PriceMatrix;
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct Kline {
     pub timestamp_ms: u64,
@@ -13,79 +21,822 @@ pub struct Kline {
     pub volume: f64,
 }
 
+/// Order in which a kline's wick extremes are visited within its block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WickOrder {
+    /// Open → high → low → close
+    HighFirst,
+    /// High or low first with equal probability, drawn per candle
+    Random,
+}
+
+impl FromStr for WickOrder {
+    type Err = ZaiSimError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ohlc" | "high-first" => Ok(Self::HighFirst),
+            "random" => Ok(Self::Random),
+            _ => Err(ZaiSimError::Parse(format!(
+                "Unknown wick order: {} (use ohlc or random)",
+                s
+            ))),
+        }
+    }
+}
+
+/// Intrablock price path per kline: open, both wick extremes in `order`,
+/// then close. Consecutive duplicates (e.g. open at the high) are dropped.
+pub fn ohlc_paths(klines: &[Kline], order: WickOrder, seed: u64) -> Vec<Vec<f64>> {
+    let mut rng = ChaCha12Rng::seed_from_u64(seed);
+    klines
+        .iter()
+        .map(|k| {
+            let high_first = match order {
+                WickOrder::HighFirst => true,
+                WickOrder::Random => rng.gen_bool(0.5),
+            };
+            let mut path = if high_first {
+                vec![k.open, k.high, k.low, k.close]
+            } else {
+                vec![k.open, k.low, k.high, k.close]
+            };
+            path.dedup();
+            path
+        })
+        .collect()
+}
+
+/// A page of candles from one request, and where the next page starts.
+#[derive(Debug, Clone)]
+pub struct Page {
+    pub klines: Vec<Kline>,
+    /// Start of the next request in ms; `None` when the venue has no more data
+    pub next_start_ms: Option<u64>,
+}
+
+/// An exchange's public candle API.
+///
+/// Adapters handle the venue's symbol format, interval vocabulary, page size
+/// and response layout; `fetch_range_from` drives pagination and pacing.
+pub trait PriceSource {
+    /// Venue name for progress output
+    fn name(&self) -> &'static str;
+
+    /// The venue's ZEC/USD trading pair symbol
+    fn default_pair(&self) -> &'static str;
+
+    /// Pause between requests to stay under the venue's public rate limit
+    fn request_delay(&self) -> Duration;
+
+    /// Fetch one page of candles starting at `start_ms`.
+    fn fetch_page(
+        &self,
+        pair: &str,
+        interval: &str,
+        start_ms: u64,
+        end_ms: u64,
+    ) -> Result<Page, ZaiSimError>;
+
+    /// Parse a response body into klines in ascending time order.
+    fn parse_page(&self, body: &serde_json::Value) -> Result<Vec<Kline>, ZaiSimError>;
+}
+
+/// Supported candle venues, selectable with `--source`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Exchange {
+    Binance,
+    Coinbase,
+    Kraken,
+}
+
+impl Exchange {
+    pub fn source(self) -> Box<dyn PriceSource> {
+        match self {
+            Exchange::Binance => Box::new(Binance),
+            Exchange::Coinbase => Box::new(Coinbase),
+            Exchange::Kraken => Box::new(Kraken),
+        }
+    }
+}
+
+impl FromStr for Exchange {
+    type Err = ZaiSimError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "binance" => Ok(Self::Binance),
+            "coinbase" => Ok(Self::Coinbase),
+            "kraken" => Ok(Self::Kraken),
+            _ => Err(ZaiSimError::Parse(format!(
+                "Unknown price source: {} (use binance, coinbase or kraken)",
+                s
+            ))),
+        }
+    }
+}
+
+/// Candle interval in seconds, from Binance-style notation (1m, 5m, 1h, 1d, 1w).
+pub fn interval_secs(interval: &str) -> Result<u64, ZaiSimError> {
+    let invalid =
+        || ZaiSimError::Parse(format!("Invalid interval: {} (e.g. 1m, 1h, 1d)", interval));
+    let split = interval
+        .char_indices()
+        .last()
+        .map(|(i, _)| i)
+        .ok_or_else(invalid)?;
+    let (count, unit) = interval.split_at(split);
+    let count: u64 = count.parse().map_err(|_| invalid())?;
+    let unit_secs = match unit {
+        "m" => 60,
+        "h" => 3600,
+        "d" => 86_400,
+        "w" => 604_800,
+        _ => return Err(invalid()),
+    };
+    Ok(count * unit_secs)
+}
+
+/// GET a URL, failing on non-2xx statuses. Coinbase rejects requests
+/// without a User-Agent.
+fn get(url: &str) -> Result<reqwest::blocking::Response, ZaiSimError> {
+    let client = reqwest::blocking::Client::new();
+    Ok(client
+        .get(url)
+        .header("User-Agent", "zai-sim")
+        .send()?
+        .error_for_status()?)
+}
+
+fn get_json(url: &str) -> Result<serde_json::Value, ZaiSimError> {
+    Ok(get(url)?.json()?)
+}
+
+/// Exponential backoff with jitter for transient request failures.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Retries per page before the fetch gives up
+    pub max_retries: u32,
+    /// Delay before the first retry; doubles on each further attempt
+    pub base_delay: Duration,
+    /// Upper bound on a single delay
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_retries: 5,
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Delay before retry `attempt` (0-based). `jitter` in [0, 1) spreads the
+    /// delay over the upper half of the exponential step so parallel
+    /// fetchers don't retry in lockstep.
+    pub fn delay(&self, attempt: u32, jitter: f64) -> Duration {
+        let step = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_delay);
+        step.mul_f64(0.5 + 0.5 * jitter.clamp(0.0, 1.0))
+    }
+
+    /// Whether an error is worth retrying: rate limiting (429), server errors
+    /// (5xx), timeouts and dropped connections. Binance's 418 (IP ban) and
+    /// other client errors are not.
+    pub fn is_transient(err: &ZaiSimError) -> bool {
+        match err {
+            ZaiSimError::Http(e) => match e.status() {
+                Some(status) => status.as_u16() == 429 || status.is_server_error(),
+                None => e.is_timeout() || e.is_connect() || e.is_request(),
+            },
+            _ => false,
+        }
+    }
+}
+
+fn field_f64(v: &serde_json::Value) -> f64 {
+    match v {
+        serde_json::Value::String(s) => s.parse().unwrap_or(0.0),
+        _ => v.as_f64().unwrap_or(0.0),
+    }
+}
+
+fn rows(body: &serde_json::Value) -> Result<&Vec<serde_json::Value>, ZaiSimError> {
+    body.as_array()
+        .ok_or_else(|| ZaiSimError::Exchange(format!("expected candle array, got {}", body)))
+}
+
+/// Binance spot klines: up to 1000 candles per request, string-encoded prices.
+pub struct Binance;
+
+impl Binance {
+    /// Request weight per minute we allow ourselves, out of Binance's 6000
+    pub const WEIGHT_BUDGET: u32 = 4800;
+
+    fn klines_url(symbol: &str, interval: &str, start_ms: u64, end_ms: u64) -> String {
+        format!(
+            "https://api.binance.com/api/v3/klines?symbol={}&interval={}&startTime={}&endTime={}&limit=1000",
+            symbol, interval, start_ms, end_ms
+        )
+    }
+
+    fn depth_url(symbol: &str, limit: u32) -> String {
+        format!(
+            "https://api.binance.com/api/v3/depth?symbol={}&limit={}",
+            symbol, limit
+        )
+    }
+
+    /// Pause until Binance's weight window resets once the used weight
+    /// reported for the current minute reaches `WEIGHT_BUDGET`.
+    pub fn weight_pause(used_weight: u32, now_ms: u64) -> Option<Duration> {
+        if used_weight < Self::WEIGHT_BUDGET {
+            return None;
+        }
+        Some(Duration::from_millis(60_000 - now_ms % 60_000))
+    }
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64)
+}
+
+impl PriceSource for Binance {
+    fn name(&self) -> &'static str {
+        "Binance"
+    }
+
+    fn default_pair(&self) -> &'static str {
+        "ZECUSDT"
+    }
+
+    fn request_delay(&self) -> Duration {
+        // Binance allows 1200 requests/min, be conservative
+        Duration::from_millis(250)
+    }
+
+    fn fetch_page(
+        &self,
+        pair: &str,
+        interval: &str,
+        start_ms: u64,
+        end_ms: u64,
+    ) -> Result<Page, ZaiSimError> {
+        let resp = get(&Self::klines_url(pair, interval, start_ms, end_ms))?;
+        let used_weight = resp
+            .headers()
+            .get("x-mbx-used-weight-1m")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<u32>().ok());
+        let klines = self.parse_page(&resp.json()?)?;
+
+        if let Some(pause) = used_weight.and_then(|w| Self::weight_pause(w, now_ms())) {
+            thread::sleep(pause);
+        }
+
+        // Move cursor past the last received candle
+        let next_start_ms = klines.last().map(|k| k.timestamp_ms + 1);
+        Ok(Page {
+            klines,
+            next_start_ms,
+        })
+    }
+
+    fn parse_page(&self, body: &serde_json::Value) -> Result<Vec<Kline>, ZaiSimError> {
+        Ok(rows(body)?
+            .iter()
+            .map(|row| Kline {
+                timestamp_ms: row[0].as_u64().unwrap_or(0),
+                open: field_f64(&row[1]),
+                high: field_f64(&row[2]),
+                low: field_f64(&row[3]),
+                close: field_f64(&row[4]),
+                volume: field_f64(&row[5]),
+            })
+            .collect())
+    }
+}
+
+/// Coinbase Exchange candles: at most 300 per request, newest first, rows of
+/// `[time_s, low, high, open, close, volume]`, and only six granularities.
+/// Pages are fixed time windows, so an empty window (a trading gap) does not
+/// end the range.
+pub struct Coinbase;
+
+impl Coinbase {
+    const MAX_CANDLES: u64 = 300;
+    const GRANULARITIES: [u64; 6] = [60, 300, 900, 3600, 21_600, 86_400];
+}
+
+impl PriceSource for Coinbase {
+    fn name(&self) -> &'static str {
+        "Coinbase"
+    }
+
+    fn default_pair(&self) -> &'static str {
+        "ZEC-USD"
+    }
+
+    fn request_delay(&self) -> Duration {
+        // Public endpoints allow 10 requests/s
+        Duration::from_millis(150)
+    }
+
+    fn fetch_page(
+        &self,
+        pair: &str,
+        interval: &str,
+        start_ms: u64,
+        end_ms: u64,
+    ) -> Result<Page, ZaiSimError> {
+        let granularity = interval_secs(interval)?;
+        if !Self::GRANULARITIES.contains(&granularity) {
+            return Err(ZaiSimError::Config(format!(
+                "Coinbase does not support interval {} (use 1m, 5m, 15m, 1h, 6h or 1d)",
+                interval
+            )));
+        }
+        let step_ms = granularity * 1000;
+        // Both window ends are inclusive
+        let window_end_ms = (start_ms + (Self::MAX_CANDLES - 1) * step_ms).min(end_ms);
+        let iso = |ms: u64| {
+            chrono::DateTime::from_timestamp_millis(ms as i64)
+                .map(|t| t.to_rfc3339_opts(chrono::SecondsFormat::Secs, true))
+                .unwrap_or_default()
+        };
+        let url = format!(
+            "https://api.exchange.coinbase.com/products/{}/candles?granularity={}&start={}&end={}",
+            pair,
+            granularity,
+            iso(start_ms),
+            iso(window_end_ms)
+        );
+
+        let klines = self.parse_page(&get_json(&url)?)?;
+        Ok(Page {
+            klines,
+            next_start_ms: Some(window_end_ms + step_ms),
+        })
+    }
+
+    fn parse_page(&self, body: &serde_json::Value) -> Result<Vec<Kline>, ZaiSimError> {
+        let mut klines: Vec<Kline> = rows(body)?
+            .iter()
+            .map(|row| Kline {
+                timestamp_ms: row[0].as_u64().unwrap_or(0) * 1000,
+                low: field_f64(&row[1]),
+                high: field_f64(&row[2]),
+                open: field_f64(&row[3]),
+                close: field_f64(&row[4]),
+                volume: field_f64(&row[5]),
+            })
+            .collect();
+        klines.sort_by_key(|k| k.timestamp_ms);
+        Ok(klines)
+    }
+}
+
+/// Kraken OHLC: minute-denominated intervals, a `since` cursor in seconds,
+/// errors reported in-band, and only the most recent 720 candles available
+/// regardless of `since` — older ranges come back truncated at the front.
+pub struct Kraken;
+
+impl Kraken {
+    const INTERVAL_MINUTES: [u64; 9] = [1, 5, 15, 30, 60, 240, 1440, 10_080, 21_600];
+}
+
+impl PriceSource for Kraken {
+    fn name(&self) -> &'static str {
+        "Kraken"
+    }
+
+    fn default_pair(&self) -> &'static str {
+        "ZECUSD"
+    }
+
+    fn request_delay(&self) -> Duration {
+        // Public endpoints decay the call counter at ~1 request/s
+        Duration::from_millis(1000)
+    }
+
+    fn fetch_page(
+        &self,
+        pair: &str,
+        interval: &str,
+        start_ms: u64,
+        _end_ms: u64,
+    ) -> Result<Page, ZaiSimError> {
+        let secs = interval_secs(interval)?;
+        if !Self::INTERVAL_MINUTES.contains(&(secs / 60)) || secs % 60 != 0 {
+            return Err(ZaiSimError::Config(format!(
+                "Kraken does not support interval {} (use 1m, 5m, 15m, 30m, 1h, 4h, 1d, 1w or 15d)",
+                interval
+            )));
+        }
+        let url = format!(
+            "https://api.kraken.com/0/public/OHLC?pair={}&interval={}&since={}",
+            pair,
+            secs / 60,
+            start_ms / 1000
+        );
+
+        let klines = self.parse_page(&get_json(&url)?)?;
+        let next_start_ms = klines.last().map(|k| k.timestamp_ms + secs * 1000);
+        Ok(Page {
+            klines,
+            next_start_ms,
+        })
+    }
+
+    fn parse_page(&self, body: &serde_json::Value) -> Result<Vec<Kline>, ZaiSimError> {
+        if let Some(errors) = body["error"].as_array() {
+            if !errors.is_empty() {
+                let msgs: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+                return Err(ZaiSimError::Exchange(msgs.join(", ")));
+            }
+        }
+        // The result is keyed by Kraken's internal pair name (e.g. XZECZUSD)
+        // alongside the `last` cursor
+        let candles = body["result"]
+            .as_object()
+            .and_then(|result| result.iter().find(|(key, _)| *key != "last"))
+            .map(|(_, candles)| candles)
+            .ok_or_else(|| ZaiSimError::Exchange(format!("no OHLC result in {}", body)))?;
+
+        // Rows are [time_s, open, high, low, close, vwap, volume, count]
+        Ok(rows(candles)?
+            .iter()
+            .map(|row| Kline {
+                timestamp_ms: row[0].as_u64().unwrap_or(0) * 1000,
+                open: field_f64(&row[1]),
+                high: field_f64(&row[2]),
+                low: field_f64(&row[3]),
+                close: field_f64(&row[4]),
+                volume: field_f64(&row[6]),
+            })
+            .collect())
+    }
+}
+
 /// Fetch a single batch of klines from Binance (max 1000 candles).
 pub fn fetch_klines(
     symbol: &str,
     interval: &str,
     start_ms: u64,
     end_ms: u64,
-) -> Result<Vec<Kline>, Box<dyn std::error::Error>> {
-    let url = format!(
-        "https://api.binance.com/api/v3/klines?symbol={}&interval={}&startTime={}&endTime={}&limit=1000",
-        symbol, interval, start_ms, end_ms
-    );
+) -> Result<Vec<Kline>, ZaiSimError> {
+    Binance.parse_page(&get_json(&Binance::klines_url(
+        symbol, interval, start_ms, end_ms,
+    ))?)
+}
 
-    let client = reqwest::blocking::Client::new();
-    let resp = client.get(&url).send()?;
-    let raw: Vec<Vec<serde_json::Value>> = resp.json()?;
-
-    let klines = raw
-        .into_iter()
-        .map(|row| Kline {
-            timestamp_ms: row[0].as_u64().unwrap_or(0),
-            open: row[1].as_str().unwrap_or("0").parse().unwrap_or(0.0),
-            high: row[2].as_str().unwrap_or("0").parse().unwrap_or(0.0),
-            low: row[3].as_str().unwrap_or("0").parse().unwrap_or(0.0),
-            close: row[4].as_str().unwrap_or("0").parse().unwrap_or(0.0),
-            volume: row[5].as_str().unwrap_or("0").parse().unwrap_or(0.0),
+/// Parse a Binance `/api/v3/depth` body: `bids` and `asks` arrays of
+/// string-encoded `[price, quantity]` levels, best first.
+pub fn parse_depth(
+    body: &serde_json::Value,
+    timestamp_ms: u64,
+) -> Result<DepthSnapshot, ZaiSimError> {
+    let side = |name: &str| -> Result<Vec<(f64, f64)>, ZaiSimError> {
+        let levels = body[name].as_array().ok_or_else(|| {
+            ZaiSimError::Exchange(format!("expected {} array in depth, got {}", name, body))
+        })?;
+        Ok(levels
+            .iter()
+            .map(|level| (field_f64(&level[0]), field_f64(&level[1])))
+            .collect())
+    };
+    Ok(DepthSnapshot {
+        timestamp_ms,
+        bids: side("bids")?,
+        asks: side("asks")?,
+    })
+}
+
+/// Fetch the current Binance order book for `symbol`, `limit` levels per
+/// side (up to 5000; requests above 100 levels weigh more).
+pub fn fetch_depth(symbol: &str, limit: u32) -> Result<DepthSnapshot, ZaiSimError> {
+    parse_depth(&get_json(&Binance::depth_url(symbol, limit))?, now_ms())
+}
+
+/// GET JSON, retrying transient failures per `retry`.
+fn get_json_retrying(url: &str, retry: &RetryPolicy) -> Result<serde_json::Value, ZaiSimError> {
+    let mut rng = rand::thread_rng();
+    let mut attempt = 0;
+    loop {
+        match get_json(url) {
+            Err(e) if attempt < retry.max_retries && RetryPolicy::is_transient(&e) => {
+                thread::sleep(retry.delay(attempt, rng.gen()));
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Parse a Binance futures `/fapi/v1/fundingRate` body: objects with
+/// `fundingTime`, and string-encoded `fundingRate` and `markPrice`.
+pub fn parse_funding(body: &serde_json::Value) -> Result<Vec<FundingSample>, ZaiSimError> {
+    let rows = body
+        .as_array()
+        .ok_or_else(|| ZaiSimError::Exchange(format!("expected funding array, got {}", body)))?;
+    Ok(rows
+        .iter()
+        .map(|row| FundingSample {
+            timestamp_ms: row["fundingTime"].as_u64().unwrap_or(0),
+            rate: field_f64(&row["fundingRate"]),
+            mark_price: field_f64(&row["markPrice"]),
         })
-        .collect();
+        .collect())
+}
 
-    Ok(klines)
+/// Parse a Binance futures `/futures/data/openInterestHist` body: objects
+/// with `timestamp`, and string-encoded `sumOpenInterest` (contracts, in
+/// ZEC) and `sumOpenInterestValue` (USDT).
+pub fn parse_open_interest(
+    body: &serde_json::Value,
+) -> Result<Vec<OpenInterestSample>, ZaiSimError> {
+    let rows = body.as_array().ok_or_else(|| {
+        ZaiSimError::Exchange(format!("expected open interest array, got {}", body))
+    })?;
+    Ok(rows
+        .iter()
+        .map(|row| OpenInterestSample {
+            timestamp_ms: row["timestamp"].as_u64().unwrap_or(0),
+            open_interest_zec: field_f64(&row["sumOpenInterest"]),
+            notional: field_f64(&row["sumOpenInterestValue"]),
+        })
+        .collect())
 }
 
-/// Fetch klines across a full date range, paginating in batches of 1000.
-pub fn fetch_range(
+/// Fetch a Binance perp's funding history from `start_ms` to `end_ms`,
+/// 1000 payments per request.
+pub fn fetch_funding(
     symbol: &str,
-    interval: &str,
     start_ms: u64,
     end_ms: u64,
-) -> Result<Vec<Kline>, Box<dyn std::error::Error>> {
-    let mut all_klines = Vec::new();
+    retry: &RetryPolicy,
+) -> Result<Vec<FundingSample>, ZaiSimError> {
+    let mut samples: Vec<FundingSample> = Vec::new();
     let mut cursor = start_ms;
-
-    loop {
-        if cursor >= end_ms {
+    while cursor < end_ms {
+        let url = format!(
+            "https://fapi.binance.com/fapi/v1/fundingRate?symbol={}&startTime={}&endTime={}&limit=1000",
+            symbol, cursor, end_ms
+        );
+        let page = parse_funding(&get_json_retrying(&url, retry)?)?;
+        let Some(last) = page.last().map(|s| s.timestamp_ms) else {
+            break;
+        };
+        samples.extend(page.into_iter().filter(|s| s.timestamp_ms >= cursor));
+        if last < cursor {
             break;
         }
+        cursor = last + 1;
+        thread::sleep(Binance.request_delay());
+    }
+    Ok(samples)
+}
 
-        let batch = fetch_klines(symbol, interval, cursor, end_ms)?;
-        if batch.is_empty() {
+/// Fetch a Binance perp's open interest every `period` (e.g. 1h) from
+/// `start_ms` to `end_ms`, 500 points per request. Binance keeps only the
+/// last 30 days.
+pub fn fetch_open_interest(
+    symbol: &str,
+    period: &str,
+    start_ms: u64,
+    end_ms: u64,
+    retry: &RetryPolicy,
+) -> Result<Vec<OpenInterestSample>, ZaiSimError> {
+    let mut samples: Vec<OpenInterestSample> = Vec::new();
+    let mut cursor = start_ms;
+    while cursor < end_ms {
+        let url = format!(
+            "https://fapi.binance.com/futures/data/openInterestHist?symbol={}&period={}&startTime={}&endTime={}&limit=500",
+            symbol, period, cursor, end_ms
+        );
+        let page = parse_open_interest(&get_json_retrying(&url, retry)?)?;
+        let Some(last) = page.last().map(|s| s.timestamp_ms) else {
+            break;
+        };
+        samples.extend(page.into_iter().filter(|s| s.timestamp_ms >= cursor));
+        if last < cursor {
             break;
         }
-
-        let last_ts = batch.last().unwrap().timestamp_ms;
-        all_klines.extend(batch);
-
-        // Move cursor past the last received candle
-        cursor = last_ts + 1;
-
-        // Rate limiting: Binance allows 1200 requests/min, be conservative
-        thread::sleep(Duration::from_millis(250));
+        cursor = last + 1;
+        thread::sleep(Binance.request_delay());
     }
+    Ok(samples)
+}
+
+/// Fetch Binance klines across a full date range, paginating in batches of 1000.
+pub fn fetch_range(
+    symbol: &str,
+    interval: &str,
+    start_ms: u64,
+    end_ms: u64,
+) -> Result<Vec<Kline>, ZaiSimError> {
+    fetch_range_from(
+        &Binance,
+        symbol,
+        interval,
+        start_ms,
+        end_ms,
+        &RetryPolicy::default(),
+    )
+}
 
+/// Fetch klines from any venue across a full date range, following the
+/// source's pagination and pacing requests by its rate limit.
+pub fn fetch_range_from(
+    source: &dyn PriceSource,
+    pair: &str,
+    interval: &str,
+    start_ms: u64,
+    end_ms: u64,
+    retry: &RetryPolicy,
+) -> Result<Vec<Kline>, ZaiSimError> {
+    let mut all_klines = Vec::new();
+    fetch_pages(
+        source,
+        pair,
+        interval,
+        start_ms,
+        end_ms,
+        retry,
+        None,
+        |klines| {
+            all_klines.extend_from_slice(klines);
+            Ok(())
+        },
+    )?;
     Ok(all_klines)
 }
 
-/// Save klines to a CSV file.
-pub fn save_csv(klines: &[Kline], path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+/// Outcome of `fetch_to_csv`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FetchSummary {
+    /// Candles already in the file from an earlier run
+    pub existing: usize,
+    /// Candles fetched and appended by this run
+    pub fetched: usize,
+}
+
+/// Fetch a date range straight into a CSV file, appending and flushing each
+/// page as it arrives. If the file already holds candles (an interrupted
+/// earlier run), fetching resumes after the last saved one.
+pub fn fetch_to_csv(
+    source: &dyn PriceSource,
+    pair: &str,
+    interval: &str,
+    start_ms: u64,
+    end_ms: u64,
+    retry: &RetryPolicy,
+    path: &Path,
+) -> Result<FetchSummary, ZaiSimError> {
+    let existing = if path.exists() {
+        load_csv(path)?
+    } else {
+        Vec::new()
+    };
+    let last_saved = existing.last().map(|k| k.timestamp_ms);
+    let cursor = last_saved.map_or(start_ms, |ts| start_ms.max(ts + 1));
+
     if let Some(parent) = path.parent() {
         std::fs::create_dir_all(parent)?;
     }
+    let needs_header = std::fs::metadata(path)
+        .map(|m| m.len() == 0)
+        .unwrap_or(true);
+    let file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    let mut wtr = csv::WriterBuilder::new()
+        .has_headers(false)
+        .from_writer(file);
+    if needs_header {
+        write_header(&mut wtr)?;
+        wtr.flush()?;
+    }
 
-    let mut wtr = csv::Writer::from_path(path)?;
+    let mut fetched = 0;
+    fetch_pages(
+        source,
+        pair,
+        interval,
+        cursor,
+        end_ms,
+        retry,
+        last_saved,
+        |klines| {
+            write_records(&mut wtr, klines)?;
+            wtr.flush()?;
+            fetched += klines.len();
+            Ok(())
+        },
+    )?;
+
+    Ok(FetchSummary {
+        existing: existing.len(),
+        fetched,
+    })
+}
+
+/// Fetch each `(pair, path)` into its CSV with `fetch_to_csv` (so each
+/// resumes on its own), then align the pairs' closes into one matrix.
+pub fn fetch_matrix(
+    source: &dyn PriceSource,
+    pairs: &[(&str, &Path)],
+    interval: &str,
+    start_ms: u64,
+    end_ms: u64,
+    retry: &RetryPolicy,
+) -> Result<(PriceMatrix, Vec<FetchSummary>), ZaiSimError> {
+    let mut summaries = Vec::new();
+    let mut series = Vec::new();
+    for &(pair, path) in pairs {
+        summaries.push(fetch_to_csv(
+            source, pair, interval, start_ms, end_ms, retry, path,
+        )?);
+        let closes = load_csv(path)?
+            .iter()
+            .map(|k| (k.timestamp_ms, k.close))
+            .collect();
+        series.push((pair.to_string(), closes));
+    }
+    Ok((PriceMatrix::align(series)?, summaries))
+}
+
+/// Drive a source's pagination from `start_ms` to `end_ms`, handing each
+/// page's new in-range candles to `on_page`. Transient failures are retried
+/// per `retry`; requests are paced by the source's delay.
+#[allow(clippy::too_many_arguments)]
+fn fetch_pages(
+    source: &dyn PriceSource,
+    pair: &str,
+    interval: &str,
+    start_ms: u64,
+    end_ms: u64,
+    retry: &RetryPolicy,
+    mut last_ts: Option<u64>,
+    mut on_page: impl FnMut(&[Kline]) -> Result<(), ZaiSimError>,
+) -> Result<(), ZaiSimError> {
+    let mut rng = rand::thread_rng();
+    let mut cursor = start_ms;
+
+    while cursor < end_ms {
+        let mut attempt = 0;
+        let page = loop {
+            match source.fetch_page(pair, interval, cursor, end_ms) {
+                Ok(page) => break page,
+                Err(e) if attempt < retry.max_retries && RetryPolicy::is_transient(&e) => {
+                    thread::sleep(retry.delay(attempt, rng.gen()));
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        };
+
+        // Venues may return candles outside the requested window (Kraken's
+        // `since` is approximate); keep only new in-range candles
+        let from = last_ts.map_or(cursor, |ts| cursor.max(ts + 1));
+        let klines: Vec<Kline> = page
+            .klines
+            .into_iter()
+            .filter(|k| k.timestamp_ms >= from && k.timestamp_ms <= end_ms)
+            .collect();
+        if let Some(k) = klines.last() {
+            last_ts = Some(k.timestamp_ms);
+            on_page(&klines)?;
+        }
+
+        match page.next_start_ms {
+            Some(next) if next > cursor => cursor = next,
+            _ => break,
+        }
+
+        thread::sleep(source.request_delay());
+    }
+
+    Ok(())
+}
+
+fn write_header<W: std::io::Write>(wtr: &mut csv::Writer<W>) -> Result<(), ZaiSimError> {
     wtr.write_record(["timestamp_ms", "open", "high", "low", "close", "volume"])?;
+    Ok(())
+}
 
+fn write_records<W: std::io::Write>(
+    wtr: &mut csv::Writer<W>,
+    klines: &[Kline],
+) -> Result<(), ZaiSimError> {
     for k in klines {
         wtr.write_record(&[
             k.timestamp_ms.to_string(),
@@ -96,13 +847,24 @@ pub fn save_csv(klines: &[Kline], path: &Path) -> Result<(), Box<dyn std::error:
             k.volume.to_string(),
         ])?;
     }
+    Ok(())
+}
+
+/// Save klines to a CSV file.
+pub fn save_csv(klines: &[Kline], path: &Path) -> Result<(), ZaiSimError> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
 
+    let mut wtr = csv::Writer::from_path(path)?;
+    write_header(&mut wtr)?;
+    write_records(&mut wtr, klines)?;
     wtr.flush()?;
     Ok(())
 }
 
 /// Load klines from a CSV file.
-pub fn load_csv(path: &Path) -> Result<Vec<Kline>, Box<dyn std::error::Error>> {
+pub fn load_csv(path: &Path) -> Result<Vec<Kline>, ZaiSimError> {
     let mut rdr = csv::Reader::from_path(path)?;
     let mut klines = Vec::new();
 
@@ -0,0 +1,135 @@
+//! Order-book depth snapshots and the depth curves derived from them.
+//!
+//! A snapshot is one order book; its curve is the quote notional resting
+//! within each distance of the mid, per side. `fetch-depth` appends curves
+//! to a CSV over time, and `calibration::calibrate_depth` fits the
+//! external market's and AMM's impact parameters to them.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::ZaiSimError;
+
+/// Distances from the mid, in basis points, a curve measures depth at.
+pub const DEFAULT_LEVELS_BPS: [f64; 8] = [10.0, 25.0, 50.0, 100.0, 200.0, 300.0, 500.0, 1000.0];
+
+/// One order book: `(price, quantity)` levels, bids best (highest) first
+/// and asks best (lowest) first.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DepthSnapshot {
+    pub timestamp_ms: u64,
+    pub bids: Vec<(f64, f64)>,
+    pub asks: Vec<(f64, f64)>,
+}
+
+impl DepthSnapshot {
+    /// Midpoint of the best bid and ask.
+    pub fn mid(&self) -> Option<f64> {
+        Some((self.bids.first()?.0 + self.asks.first()?.0) / 2.0)
+    }
+
+    /// Quote notional within each of `levels_bps` of the mid on each side.
+    /// `None` for a one-sided or empty book.
+    pub fn curve(&self, levels_bps: &[f64]) -> Option<DepthCurve> {
+        let mid = self.mid()?;
+        let within = |levels: &[(f64, f64)], bps: f64| -> f64 {
+            levels
+                .iter()
+                .filter(|(price, _)| (price - mid).abs() / mid * 10_000.0 <= bps)
+                .map(|(price, qty)| price * qty)
+                .sum()
+        };
+        Some(DepthCurve {
+            timestamp_ms: self.timestamp_ms,
+            mid,
+            points: levels_bps
+                .iter()
+                .map(|&bps| DepthPoint {
+                    bps,
+                    bid_notional: within(&self.bids, bps),
+                    ask_notional: within(&self.asks, bps),
+                })
+                .collect(),
+        })
+    }
+}
+
+/// Resting quote notional within `bps` of the mid.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DepthPoint {
+    pub bps: f64,
+    /// Bids within `bps` below the mid: what selling down to there absorbs
+    pub bid_notional: f64,
+    /// Asks within `bps` above the mid: what buying up to there costs
+    pub ask_notional: f64,
+}
+
+/// A snapshot's depth at increasing distances from the mid.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DepthCurve {
+    pub timestamp_ms: u64,
+    pub mid: f64,
+    pub points: Vec<DepthPoint>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CurveRow {
+    timestamp_ms: u64,
+    mid: f64,
+    bps: f64,
+    bid_notional: f64,
+    ask_notional: f64,
+}
+
+/// Append `curves` to a CSV (one row per curve point), writing the header
+/// if the file is new.
+pub fn append_curves(curves: &[DepthCurve], path: &Path) -> Result<(), ZaiSimError> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let needs_header = std::fs::metadata(path).map_or(true, |m| m.len() == 0);
+    let file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    let mut wtr = csv::WriterBuilder::new()
+        .has_headers(needs_header)
+        .from_writer(file);
+    for curve in curves {
+        for point in &curve.points {
+            wtr.serialize(CurveRow {
+                timestamp_ms: curve.timestamp_ms,
+                mid: curve.mid,
+                bps: point.bps,
+                bid_notional: point.bid_notional,
+                ask_notional: point.ask_notional,
+            })?;
+        }
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+/// Read curves written by `append_curves`, grouping rows by timestamp.
+pub fn load_curves(path: &Path) -> Result<Vec<DepthCurve>, ZaiSimError> {
+    let mut rdr = csv::Reader::from_path(path)?;
+    let mut curves: Vec<DepthCurve> = Vec::new();
+    for row in rdr.deserialize() {
+        let row: CurveRow = row?;
+        let point = DepthPoint {
+            bps: row.bps,
+            bid_notional: row.bid_notional,
+            ask_notional: row.ask_notional,
+        };
+        match curves.last_mut() {
+            Some(curve) if curve.timestamp_ms == row.timestamp_ms => curve.points.push(point),
+            _ => curves.push(DepthCurve {
+                timestamp_ms: row.timestamp_ms,
+                mid: row.mid,
+                points: vec![point],
+            }),
+        }
+    }
+    Ok(curves)
+}
@@ -0,0 +1,166 @@
+//! Run-to-run determinism verification.
+//!
+//! Runs the same config and seed repeatedly, optionally inside rayon pools
+//! of different sizes, and requires every run's per-block metrics to be
+//! bit-identical to the first. Any divergence is reported as the earliest
+//! block and the fields that differ there.
+
+use rayon::prelude::*;
+use serde_json::Value;
+
+use crate::error::ZaiSimError;
+use crate::scenario::{BlockMetrics, MetricsStore, ScenarioConfig};
+use crate::scenarios::{self, ScenarioId};
+
+/// A metrics field that differs between the reference run and another run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricDivergence {
+    pub block: u64,
+    pub field: String,
+    pub expected: String,
+    pub actual: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct DeterminismReport {
+    pub scenario: ScenarioId,
+    pub seed: u64,
+    pub blocks: usize,
+    /// Runs compared against the reference (excluding the reference itself)
+    pub runs_compared: usize,
+    pub thread_counts: Vec<usize>,
+    /// Thread count of the first diverging run and its differing fields at
+    /// the earliest diverging block (empty when deterministic)
+    pub divergence: Option<(usize, Vec<MetricDivergence>)>,
+}
+
+impl DeterminismReport {
+    pub fn is_deterministic(&self) -> bool {
+        self.divergence.is_none()
+    }
+}
+
+fn same_bits(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(x), Value::Number(y)) => match (x.as_u64(), y.as_u64()) {
+            (Some(p), Some(q)) => p == q,
+            _ => match (x.as_f64(), y.as_f64()) {
+                (Some(p), Some(q)) => p.to_bits() == q.to_bits(),
+                _ => x == y,
+            },
+        },
+        _ => a == b,
+    }
+}
+
+fn render(v: &Value) -> String {
+    match v {
+        Value::Number(n) => match n.as_f64() {
+            Some(f) if n.as_u64().is_none() => format!("{:?}", f),
+            _ => n.to_string(),
+        },
+        other => other.to_string(),
+    }
+}
+
+/// Every field that differs between two blocks' metrics. Floats must match
+/// bit-for-bit; non-finite values serialize as `null` and compare equal.
+pub fn diff_block_metrics(expected: &BlockMetrics, actual: &BlockMetrics) -> Vec<MetricDivergence> {
+    let to_map = |m: &BlockMetrics| match serde_json::to_value(m) {
+        Ok(Value::Object(map)) => map,
+        _ => serde_json::Map::new(),
+    };
+    let (e, a) = (to_map(expected), to_map(actual));
+    e.iter()
+        .filter_map(|(field, ev)| {
+            let av = a.get(field).unwrap_or(&Value::Null);
+            (!same_bits(ev, av)).then(|| MetricDivergence {
+                block: expected.block,
+                field: field.clone(),
+                expected: render(ev),
+                actual: render(av),
+            })
+        })
+        .collect()
+}
+
+/// Differing fields at the earliest block where two runs diverge. A run
+/// that ends early is reported as field `block_missing`.
+pub fn first_metrics_divergence(
+    expected: &MetricsStore,
+    actual: &MetricsStore,
+) -> Option<Vec<MetricDivergence>> {
+    for (i, e) in expected.iter().enumerate() {
+        let Some(a) = actual.get(i) else {
+            return Some(vec![MetricDivergence {
+                block: e.block,
+                field: "block_missing".to_string(),
+                expected: "present".to_string(),
+                actual: "missing".to_string(),
+            }]);
+        };
+        let diffs = diff_block_metrics(&e, &a);
+        if !diffs.is_empty() {
+            return Some(diffs);
+        }
+    }
+    actual.get(expected.len()).map(|a| {
+        vec![MetricDivergence {
+            block: a.block,
+            field: "block_missing".to_string(),
+            expected: "missing".to_string(),
+            actual: "present".to_string(),
+        }]
+    })
+}
+
+/// Run `id` with `config` and `seed` once as a reference, then once per
+/// thread in a rayon pool of each size in `thread_counts` (or once more on
+/// the current thread when empty), comparing every run to the reference.
+pub fn verify_determinism(
+    id: ScenarioId,
+    config: &ScenarioConfig,
+    blocks: usize,
+    seed: u64,
+    thread_counts: &[usize],
+) -> Result<DeterminismReport, ZaiSimError> {
+    let run = || scenarios::run_stress(id, config, blocks, seed).metrics;
+    let reference = run();
+
+    let mut report = DeterminismReport {
+        scenario: id,
+        seed,
+        blocks,
+        runs_compared: 0,
+        thread_counts: thread_counts.to_vec(),
+        divergence: None,
+    };
+
+    if thread_counts.is_empty() {
+        report.runs_compared = 1;
+        report.divergence = first_metrics_divergence(&reference, &run()).map(|d| (1, d));
+        return Ok(report);
+    }
+
+    for &threads in thread_counts {
+        if threads == 0 {
+            return Err(ZaiSimError::InvalidInput(
+                "Thread counts must be at least 1".to_string(),
+            ));
+        }
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()?;
+        let runs: Vec<MetricsStore> =
+            pool.install(|| (0..threads).into_par_iter().map(|_| run()).collect());
+        report.runs_compared += runs.len();
+        if let Some(d) = runs
+            .iter()
+            .find_map(|metrics| first_metrics_divergence(&reference, metrics))
+        {
+            report.divergence = Some((threads, d));
+            break;
+        }
+    }
+    Ok(report)
+}
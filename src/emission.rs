@@ -0,0 +1,157 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Zcash block subsidy schedule.
+///
+/// Post-Blossom (75s blocks) the subsidy halves every `halving_interval`
+/// blocks after `first_halving_height`; a `dev_fund_fraction` share of each
+/// subsidy goes to the development fund / lockbox instead of the miner.
+/// Transaction fees are ignored.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmissionSchedule {
+    /// Subsidy per block before the first halving (post-Blossom)
+    pub initial_subsidy: f64,
+    pub first_halving_height: u64,
+    pub halving_interval: u64,
+    /// Share of the subsidy withheld from miners (ZIP 1014 / ZIP 1015)
+    pub dev_fund_fraction: f64,
+    /// Height at which the dev fund stops; `None` = it continues indefinitely
+    pub dev_fund_end_height: Option<u64>,
+}
+
+impl Default for EmissionSchedule {
+    fn default() -> Self {
+        EmissionSchedule {
+            initial_subsidy: 6.25,
+            first_halving_height: 1_046_400,
+            halving_interval: 1_680_000,
+            dev_fund_fraction: 0.20,
+            dev_fund_end_height: None,
+        }
+    }
+}
+
+impl EmissionSchedule {
+    /// Number of halvings that have taken effect at `height`.
+    pub fn halvings(&self, height: u64) -> u32 {
+        if height < self.first_halving_height {
+            0
+        } else {
+            let after = (height - self.first_halving_height) / self.halving_interval.max(1);
+            (after + 1).min(u32::MAX as u64) as u32
+        }
+    }
+
+    /// Height of the first halving strictly after `height`.
+    pub fn next_halving_height(&self, height: u64) -> u64 {
+        self.first_halving_height + self.halvings(height) as u64 * self.halving_interval
+    }
+
+    /// Total block subsidy at `height`.
+    pub fn subsidy(&self, height: u64) -> f64 {
+        self.initial_subsidy * 0.5_f64.powi(self.halvings(height) as i32)
+    }
+
+    /// Dev fund share of the subsidy at `height`.
+    pub fn dev_fund(&self, height: u64) -> f64 {
+        match self.dev_fund_end_height {
+            Some(end) if height >= end => 0.0,
+            _ => self.subsidy(height) * self.dev_fund_fraction,
+        }
+    }
+
+    /// ZEC paid to the miner at `height`.
+    pub fn miner_reward(&self, height: u64) -> f64 {
+        self.subsidy(height) - self.dev_fund(height)
+    }
+}
+
+/// Maps simulation blocks to mainnet heights and dates.
+///
+/// Simulation block 1 is mainnet height `start_height`, mined at
+/// `start_unix_secs`; later blocks follow at `block_time_secs`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChainCalendar {
+    pub start_height: u64,
+    pub start_unix_secs: i64,
+    pub block_time_secs: u64,
+}
+
+/// Second halving (height 2,726,400), approximately 2024-11-23 21:00 UTC.
+const REFERENCE_HEIGHT: u64 = 2_726_400;
+const REFERENCE_UNIX_SECS: i64 = 1_732_395_600;
+const BLOCK_TIME_SECS: u64 = 75;
+
+impl Default for ChainCalendar {
+    fn default() -> Self {
+        ChainCalendar {
+            start_height: REFERENCE_HEIGHT,
+            start_unix_secs: REFERENCE_UNIX_SECS,
+            block_time_secs: BLOCK_TIME_SECS,
+        }
+    }
+}
+
+impl ChainCalendar {
+    /// Calendar whose block 1 falls at midnight UTC on `date`, with the
+    /// height estimated from the 2024 halving at 75s blocks.
+    pub fn starting_at(date: NaiveDate) -> Self {
+        let secs = date
+            .and_hms_opt(0, 0, 0)
+            .expect("midnight is a valid time")
+            .and_utc()
+            .timestamp();
+        let blocks = (secs - REFERENCE_UNIX_SECS).div_euclid(BLOCK_TIME_SECS as i64);
+        ChainCalendar {
+            start_height: (REFERENCE_HEIGHT as i64 + blocks).max(0) as u64,
+            start_unix_secs: secs,
+            block_time_secs: BLOCK_TIME_SECS,
+        }
+    }
+
+    /// Mainnet height of simulation `block`.
+    pub fn height(&self, block: u64) -> u64 {
+        self.start_height + block.saturating_sub(1)
+    }
+
+    /// Unix timestamp of simulation `block`.
+    pub fn timestamp(&self, block: u64) -> i64 {
+        self.start_unix_secs + (block.saturating_sub(1) * self.block_time_secs) as i64
+    }
+
+    pub fn datetime(&self, block: u64) -> DateTime<Utc> {
+        DateTime::from_timestamp(self.timestamp(block), 0).unwrap_or_default()
+    }
+
+    pub fn date(&self, block: u64) -> NaiveDate {
+        self.datetime(block).date_naive()
+    }
+
+    /// Simulation block at mainnet `height`, if it is not before the start.
+    pub fn block_at_height(&self, height: u64) -> Option<u64> {
+        height
+            .checked_sub(self.start_height)
+            .map(|offset| offset + 1)
+    }
+}
+
+/// Halving-aware issuance for a miner agent: the reward for each
+/// simulation block comes from `schedule` at the height `calendar` maps it to.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EmissionConfig {
+    pub schedule: EmissionSchedule,
+    pub calendar: ChainCalendar,
+}
+
+impl EmissionConfig {
+    pub fn miner_reward(&self, block: u64) -> f64 {
+        self.schedule.miner_reward(self.calendar.height(block))
+    }
+
+    /// Simulation block of the first halving after `block`.
+    pub fn next_halving_block(&self, block: u64) -> Option<u64> {
+        let height = self.calendar.height(block);
+        self.calendar
+            .block_at_height(self.schedule.next_halving_height(height))
+    }
+}
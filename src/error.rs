@@ -0,0 +1,115 @@
+use thiserror::Error;
+
+/// Errors returned by the simulator's public APIs.
+///
+/// Protocol operations (AMM, CDP, liquidation, lending) reject requests with
+/// the specific variants below so callers can react programmatically; file,
+/// network and database failures wrap their underlying error.
+#[derive(Debug, Error)]
+pub enum ZaiSimError {
+    /// An amount or argument is zero, negative or otherwise out of range
+    #[error("{0}")]
+    InvalidInput(String),
+
+    /// A pool, market or vault set cannot fill the request
+    #[error("Insufficient liquidity: {0}")]
+    InsufficientLiquidity(String),
+
+    /// The caller holds less than the requested amount
+    #[error("Insufficient {what}: have {have}, requested {requested}")]
+    InsufficientBalance {
+        what: String,
+        have: f64,
+        requested: f64,
+    },
+
+    /// A per-swap, per-block or per-window rate limit was hit
+    #[error("Velocity limit reached: {0}")]
+    VelocityLimit(String),
+
+    #[error("Vault {0} not found")]
+    VaultNotFound(u64),
+
+    /// The action would leave a vault below the minimum collateral ratio
+    #[error("Collateral ratio {ratio:.4} below minimum {min:.4}")]
+    BelowMinRatio { ratio: f64, min: f64 },
+
+    /// The action would leave vault debt between zero and the debt floor
+    #[error("Debt {debt} below floor {floor}")]
+    BelowDebtFloor { debt: f64, floor: f64 },
+
+    /// The vault is above its liquidation threshold
+    #[error("Vault {0} is not liquidatable")]
+    NotLiquidatable(u64),
+
+    /// The vault is unsafe but still inside its owner-priority grace window
+    #[error("Vault {0} is in its grace period")]
+    InGracePeriod(u64),
+
+    /// The vault has no debt to liquidate
+    #[error("Vault {0} has no debt")]
+    NoDebt(u64),
+
+    /// A parameter set that does not exist or cannot be applied
+    #[error("Invalid configuration: {0}")]
+    Config(String),
+
+    /// A metric name that is not a `SummaryMetrics` field
+    #[error("Unknown metric: {0}")]
+    UnknownMetric(String),
+
+    /// Malformed text: CLI values, CSV fields, ranges
+    #[error("Parse error: {0}")]
+    Parse(String),
+
+    #[cfg(feature = "sqlite")]
+    #[error("SQLite: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+
+    /// An exchange API reported an error or returned an unexpected payload
+    #[error("Exchange API error: {0}")]
+    Exchange(String),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error(transparent)]
+    Csv(#[from] csv::Error),
+
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+
+    #[cfg(feature = "net")]
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+
+    #[cfg(feature = "parquet")]
+    #[error(transparent)]
+    Parquet(#[from] parquet::errors::ParquetError),
+
+    #[error(transparent)]
+    ThreadPool(#[from] rayon::ThreadPoolBuildError),
+
+    #[cfg(feature = "net")]
+    #[error(transparent)]
+    WebSocket(Box<tungstenite::Error>),
+}
+
+#[cfg(feature = "net")]
+impl From<tungstenite::Error> for ZaiSimError {
+    fn from(e: tungstenite::Error) -> Self {
+        ZaiSimError::WebSocket(Box::new(e))
+    }
+}
+
+impl From<std::num::ParseFloatError> for ZaiSimError {
+    fn from(e: std::num::ParseFloatError) -> Self {
+        ZaiSimError::Parse(e.to_string())
+    }
+}
+
+impl From<std::num::ParseIntError> for ZaiSimError {
+    fn from(e: std::num::ParseIntError) -> Self {
+        ZaiSimError::Parse(e.to_string())
+    }
+}
@@ -0,0 +1,153 @@
+//! Per-scenario acceptance expectations.
+//!
+//! Each stress scenario carries a machine-readable set of expectations,
+//! calibrated at the reference config (default `ScenarioConfig`, 1000 blocks,
+//! seed 42). Evaluating them after `stress --id 0` turns the 13 scenarios into
+//! a self-checking acceptance suite for protocol parameter proposals.
+
+use crate::output::SummaryMetrics;
+use crate::report::{PassFailResult, Verdict};
+use crate::scenarios::ScenarioId;
+use serde::Serialize;
+
+/// A single expected property of a scenario run.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum Expectation {
+    /// Overall verdict must be this good or better (Pass > SoftFail > HardFail).
+    VerdictAtLeast(Verdict),
+    MaxLiquidations(u32),
+    MaxBadDebt(f64),
+    /// Mean absolute peg deviation as a fraction (0.05 = 5%).
+    MaxMeanPegDeviation(f64),
+    MaxHaltBlocks(u64),
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExpectationResult {
+    pub expectation: Expectation,
+    pub passed: bool,
+    pub actual: String,
+}
+
+impl Expectation {
+    pub fn describe(&self) -> String {
+        match self {
+            Self::VerdictAtLeast(v) => format!("Verdict at least {}", v.label()),
+            Self::MaxLiquidations(n) => format!("Liquidations <= {}", n),
+            Self::MaxBadDebt(x) => format!("Bad debt <= {:.2}", x),
+            Self::MaxMeanPegDeviation(x) => {
+                format!("Mean peg deviation <= {:.2}%", x * 100.0)
+            }
+            Self::MaxHaltBlocks(n) => format!("Halt blocks <= {}", n),
+        }
+    }
+
+    pub fn check(&self, verdict: &PassFailResult, summary: &SummaryMetrics) -> ExpectationResult {
+        let (passed, actual) = match self {
+            Self::VerdictAtLeast(v) => (
+                verdict.overall.rank() <= v.rank(),
+                verdict.overall.label().to_string(),
+            ),
+            Self::MaxLiquidations(n) => (
+                summary.total_liquidations <= *n,
+                summary.total_liquidations.to_string(),
+            ),
+            Self::MaxBadDebt(x) => (
+                summary.total_bad_debt <= *x,
+                format!("{:.2}", summary.total_bad_debt),
+            ),
+            Self::MaxMeanPegDeviation(x) => (
+                summary.mean_peg_deviation <= *x,
+                format!("{:.2}%", summary.mean_peg_deviation * 100.0),
+            ),
+            Self::MaxHaltBlocks(n) => (
+                summary.halt_blocks <= *n,
+                summary.halt_blocks.to_string(),
+            ),
+        };
+        ExpectationResult {
+            expectation: self.clone(),
+            passed,
+            actual,
+        }
+    }
+}
+
+/// Expectations for a scenario at the reference config.
+pub fn expectations_for(id: ScenarioId) -> Vec<Expectation> {
+    use Expectation::*;
+    use ScenarioId::*;
+
+    let mut expectations = match id {
+        SteadyState => vec![
+            VerdictAtLeast(Verdict::Pass),
+            MaxLiquidations(0),
+            MaxMeanPegDeviation(0.02),
+        ],
+        BlackThursday => vec![
+            VerdictAtLeast(Verdict::SoftFail),
+            MaxMeanPegDeviation(0.30),
+        ],
+        FlashCrash => vec![
+            VerdictAtLeast(Verdict::Pass),
+            MaxMeanPegDeviation(0.05),
+        ],
+        SustainedBear => vec![
+            VerdictAtLeast(Verdict::SoftFail),
+            MaxMeanPegDeviation(0.30),
+        ],
+        TwapManipulation => vec![
+            VerdictAtLeast(Verdict::Pass),
+            MaxMeanPegDeviation(0.03),
+        ],
+        LiquidityCrisis => vec![
+            VerdictAtLeast(Verdict::SoftFail),
+            MaxMeanPegDeviation(0.25),
+        ],
+        BankRun => vec![
+            VerdictAtLeast(Verdict::SoftFail),
+            MaxMeanPegDeviation(0.30),
+        ],
+        BullMarket => vec![
+            VerdictAtLeast(Verdict::SoftFail),
+            MaxMeanPegDeviation(0.35),
+        ],
+        OracleComparison => vec![
+            VerdictAtLeast(Verdict::Pass),
+            MaxMeanPegDeviation(0.25),
+        ],
+        CombinedStress => vec![
+            VerdictAtLeast(Verdict::SoftFail),
+            MaxMeanPegDeviation(0.25),
+        ],
+        DemandShock => vec![
+            VerdictAtLeast(Verdict::SoftFail),
+            MaxMeanPegDeviation(0.80),
+        ],
+        MinerCapitulation => vec![
+            VerdictAtLeast(Verdict::SoftFail),
+            MaxMeanPegDeviation(0.35),
+        ],
+        SequencerDowntime => vec![
+            VerdictAtLeast(Verdict::SoftFail),
+            MaxMeanPegDeviation(0.20),
+        ],
+    };
+
+    // No scenario may generate bad debt or halt the system at the reference config
+    expectations.push(MaxBadDebt(0.0));
+    expectations.push(MaxHaltBlocks(0));
+    expectations
+}
+
+/// Evaluate all expectations for a scenario run.
+pub fn evaluate(
+    id: ScenarioId,
+    verdict: &PassFailResult,
+    summary: &SummaryMetrics,
+) -> Vec<ExpectationResult> {
+    expectations_for(id)
+        .iter()
+        .map(|e| e.check(verdict, summary))
+        .collect()
+}
@@ -0,0 +1,154 @@
+use serde::{Deserialize, Serialize};
+
+/// Finite-depth off-chain ZEC market (e.g. a CEX order book).
+///
+/// Trades move the venue's price away from the oracle price linearly in
+/// notional: `depth_zai` of buying lifts it by 1%. The accumulated impact
+/// decays back toward the oracle with `recovery_half_life_blocks` as other
+/// participants trade against it, or persists when that is `None`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExternalMarketConfig {
+    /// ZAI notional that moves the price by 1%
+    pub depth_zai: f64,
+    /// Blocks for price impact to halve; `None` = impact never recovers
+    pub recovery_half_life_blocks: Option<f64>,
+}
+
+impl Default for ExternalMarketConfig {
+    fn default() -> Self {
+        ExternalMarketConfig {
+            depth_zai: 50_000.0,
+            recovery_half_life_blocks: Some(10.0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExternalMarket {
+    pub config: ExternalMarketConfig,
+    /// Current price impact as a fraction of the oracle price
+    /// (positive = ZEC bid up by our buying)
+    pub impact: f64,
+    last_block: u64,
+}
+
+/// Impact can push the price down at most 99%.
+const MIN_IMPACT: f64 = -0.99;
+
+impl ExternalMarket {
+    pub fn new(config: ExternalMarketConfig) -> Self {
+        ExternalMarket {
+            config,
+            impact: 0.0,
+            last_block: 0,
+        }
+    }
+
+    /// Decay accumulated impact up to `block`.
+    pub fn recover(&mut self, block: u64) {
+        if block <= self.last_block {
+            return;
+        }
+        if let Some(half_life) = self.config.recovery_half_life_blocks {
+            let elapsed = (block - self.last_block) as f64;
+            self.impact *= 0.5_f64.powf(elapsed / half_life.max(f64::EPSILON));
+        }
+        self.last_block = block;
+    }
+
+    /// Current venue price given the oracle price.
+    pub fn price(&self, oracle_price: f64) -> f64 {
+        oracle_price * (1.0 + self.impact)
+    }
+
+    fn impact_per_zai(&self) -> f64 {
+        0.01 / self.config.depth_zai.max(f64::EPSILON)
+    }
+
+    /// ZAI cost of buying `zec` ZEC, walking the price up as it fills.
+    pub fn quote_buy_zec(&self, zec: f64, oracle_price: f64) -> f64 {
+        self.buy_zec_fill(zec, oracle_price).0
+    }
+
+    /// ZAI received for selling `zec` ZEC, walking the price down as it fills.
+    pub fn quote_sell_zec(&self, zec: f64, oracle_price: f64) -> f64 {
+        self.sell_zec_fill(zec, oracle_price).0
+    }
+
+    /// Buy `zec` ZEC; returns the ZAI cost and leaves the price impact.
+    pub fn buy_zec(&mut self, zec: f64, oracle_price: f64, block: u64) -> f64 {
+        self.recover(block);
+        let (cost, impact) = self.buy_zec_fill(zec, oracle_price);
+        self.impact = impact;
+        cost
+    }
+
+    /// Sell `zec` ZEC; returns the ZAI received and leaves the price impact.
+    pub fn sell_zec(&mut self, zec: f64, oracle_price: f64, block: u64) -> f64 {
+        self.recover(block);
+        let (proceeds, impact) = self.sell_zec_fill(zec, oracle_price);
+        self.impact = impact;
+        proceeds
+    }
+
+    /// Spend `zai` ZAI on ZEC; returns the ZEC received.
+    pub fn spend_zai(&mut self, zai: f64, oracle_price: f64, block: u64) -> f64 {
+        self.recover(block);
+        // Price is linear in notional, so the fill is at the mid of the walk
+        let k = self.impact_per_zai();
+        let start = self.price(oracle_price);
+        let end_impact = self.impact + zai * k;
+        let avg_price = (start + oracle_price * (1.0 + end_impact)) / 2.0;
+        self.impact = end_impact;
+        zai / avg_price
+    }
+
+    /// Buying `zec` at linearly rising price p(q) = p0 + p_o·k·q over ZAI
+    /// notional q: cost c solves c = zec · (p0 + p_o·k·c / 2).
+    fn buy_zec_fill(&self, zec: f64, oracle_price: f64) -> (f64, f64) {
+        let k = self.impact_per_zai();
+        let p0 = self.price(oracle_price);
+        let denom = 1.0 - zec * oracle_price * k / 2.0;
+        // Beyond the book's capacity no finite notional fills the order
+        let cost = if denom > 0.0 {
+            zec * p0 / denom
+        } else {
+            f64::INFINITY
+        };
+        (cost, self.impact + cost * k)
+    }
+
+    fn sell_zec_fill(&self, zec: f64, oracle_price: f64) -> (f64, f64) {
+        let k = self.impact_per_zai();
+        let p0 = self.price(oracle_price);
+        // Proceeds r solve r = zec · (p0 − p_o·k·r / 2)
+        let proceeds = zec * p0 / (1.0 + zec * oracle_price * k / 2.0);
+        let impact = (self.impact - proceeds * k).max(MIN_IMPACT);
+        (proceeds, impact)
+    }
+}
+
+/// Endogenous feedback from simulated ZEC flows to the external price.
+///
+/// ZEC the simulation pushes onto the external market — miners' off-AMM
+/// sales, attackers cashing out bought-back ZEC and the share of liquidated
+/// collateral that keepers hedge off-chain — is sold into a finite-depth
+/// market whose impact is applied on top of the scripted price series.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceFeedbackConfig {
+    pub market: ExternalMarketConfig,
+    /// Fraction of seized collateral whose sale is hedged externally
+    pub liquidation_external_share: f64,
+}
+
+impl Default for PriceFeedbackConfig {
+    fn default() -> Self {
+        PriceFeedbackConfig {
+            market: ExternalMarketConfig {
+                depth_zai: 250_000.0,
+                recovery_half_life_blocks: Some(48.0),
+            },
+            liquidation_external_share: 0.5,
+        }
+    }
+}
@@ -0,0 +1,165 @@
+//! Scheduled infrastructure faults.
+//!
+//! A `FaultSchedule` lists outages over block ranges and can be attached
+//! to any scenario, so downtime combines freely with crashes, bank runs
+//! and attacks:
+//!
+//! - **Halt**: the chain stops including transactions (sequencer or
+//!   consensus downtime). No agent acts, no liquidation runs, the AMM takes
+//!   no swaps and the oracle gets no new reports. External prices keep
+//!   moving, so the system resumes against wherever the market went.
+//! - **Oracle outage**: the oracle reports nothing. Liquidations that need
+//!   an external price wait; TWAP- and AMM-priced ones carry on.
+//! - **AMM pause**: the pool rejects swaps and LP withdrawals, and
+//!   liquidations (which sell collateral through it) wait for it to reopen.
+//!
+//! Faults are part of `ScenarioConfig` (`faults: [{ type: halt, from_block:
+//! 400, to_block: 600 }]` in a scenario file) and parse from
+//! `KIND:FROM-TO` on the command line.
+
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::ZaiSimError;
+use crate::oracle::OracleFailure;
+
+/// One fault over blocks `from_block..=to_block`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Fault {
+    Halt { from_block: u64, to_block: u64 },
+    OracleOutage { from_block: u64, to_block: u64 },
+    AmmPause { from_block: u64, to_block: u64 },
+}
+
+impl Fault {
+    pub fn blocks(&self) -> (u64, u64) {
+        match self {
+            Self::Halt {
+                from_block,
+                to_block,
+            }
+            | Self::OracleOutage {
+                from_block,
+                to_block,
+            }
+            | Self::AmmPause {
+                from_block,
+                to_block,
+            } => (*from_block, *to_block),
+        }
+    }
+
+    pub fn covers(&self, block: u64) -> bool {
+        let (from, to) = self.blocks();
+        (from..=to).contains(&block)
+    }
+}
+
+impl FromStr for Fault {
+    type Err = ZaiSimError;
+
+    /// Parse `KIND:FROM-TO`, e.g. `halt:400-600`, `oracle:100-150` or
+    /// `amm:700-720`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            ZaiSimError::Parse(format!(
+                "Invalid fault: {} (use halt|oracle|amm:FROM-TO)",
+                s
+            ))
+        };
+        let (kind, range) = s.split_once(':').ok_or_else(invalid)?;
+        let (from, to) = range.split_once('-').ok_or_else(invalid)?;
+        let from_block: u64 = from.trim().parse()?;
+        let to_block: u64 = to.trim().parse()?;
+        if to_block < from_block {
+            return Err(invalid());
+        }
+        match kind.trim() {
+            "halt" => Ok(Fault::Halt {
+                from_block,
+                to_block,
+            }),
+            "oracle" | "oracle_outage" => Ok(Fault::OracleOutage {
+                from_block,
+                to_block,
+            }),
+            "amm" | "amm_pause" => Ok(Fault::AmmPause {
+                from_block,
+                to_block,
+            }),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct FaultSchedule {
+    pub faults: Vec<Fault>,
+}
+
+impl FaultSchedule {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, fault: Fault) -> Self {
+        self.faults.push(fault);
+        self
+    }
+
+    /// The network freeze of `ScenarioId::SequencerDowntime` over a run of
+    /// `blocks`: a halt through its middle fifth.
+    pub fn sequencer_downtime(blocks: u64) -> Self {
+        Self::new().with(Fault::Halt {
+            from_block: blocks * 2 / 5 + 1,
+            to_block: blocks * 3 / 5,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.faults.is_empty()
+    }
+
+    /// The chain is down: nothing is included at `block`.
+    pub fn is_halted(&self, block: u64) -> bool {
+        self.faults
+            .iter()
+            .any(|f| matches!(f, Fault::Halt { .. }) && f.covers(block))
+    }
+
+    /// No external price is available at `block`.
+    pub fn is_oracle_down(&self, block: u64) -> bool {
+        self.faults
+            .iter()
+            .any(|f| matches!(f, Fault::OracleOutage { .. }) && f.covers(block))
+    }
+
+    /// The AMM takes no swaps at `block`, paused or halted with the chain.
+    pub fn is_amm_closed(&self, block: u64) -> bool {
+        self.faults
+            .iter()
+            .any(|f| !matches!(f, Fault::OracleOutage { .. }) && f.covers(block))
+    }
+
+    /// The failures a `PriceOracle` sees: outages, and stale reports
+    /// while the chain is halted.
+    pub fn oracle_failures(&self) -> impl Iterator<Item = OracleFailure> + '_ {
+        self.faults.iter().filter_map(|f| {
+            let (from_block, to_block) = f.blocks();
+            match f {
+                Fault::OracleOutage { .. } => Some(OracleFailure::Outage {
+                    from_block,
+                    to_block,
+                }),
+                Fault::Halt { .. } => Some(OracleFailure::Stale {
+                    from_block,
+                    to_block,
+                }),
+                Fault::AmmPause { .. } => None,
+            }
+        })
+    }
+}
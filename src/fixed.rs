@@ -0,0 +1,316 @@
+//! Deterministic fixed-point arithmetic for cross-platform reproducibility.
+//!
+//! `f64` addition, multiplication, division and square roots are correctly
+//! rounded everywhere, but `ln`, `exp` and `powi` come from the platform's
+//! math library and can differ in the last bit between Linux, macOS and
+//! compilers, which compounds over a long run. `Fixed` is a signed 128-bit
+//! decimal with 18 fractional digits whose operations are pure integer
+//! arithmetic, so they give the same bits on every platform.
+//!
+//! The AMM, CDP and controller math call the functions at the bottom of
+//! this module. With the `fixed-point` feature they are computed in `Fixed`;
+//! without it they are the plain `f64` operations, bit-for-bit the default
+//! behavior. Other modules (agent sampling, oracles, reports) keep `f64`.
+
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+
+/// Signed fixed-point number: the value times 10^18, as an `i128`, so
+/// values up to about 1.7e20 with 18 decimal places. Arithmetic saturates
+/// at the range limits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Fixed(i128);
+
+const SCALE: u128 = 1_000_000_000_000_000_000;
+
+/// ln 2 × 10^18
+const LN_2: Fixed = Fixed(693_147_180_559_945_309);
+
+impl Fixed {
+    pub const ZERO: Fixed = Fixed(0);
+    pub const ONE: Fixed = Fixed(SCALE as i128);
+    pub const MAX: Fixed = Fixed(i128::MAX);
+    pub const MIN: Fixed = Fixed(i128::MIN);
+
+    /// The value times 10^18.
+    pub const fn from_raw(raw: i128) -> Self {
+        Fixed(raw)
+    }
+
+    pub const fn raw(self) -> i128 {
+        self.0
+    }
+
+    /// Nearest representable value; NaN maps to zero and out-of-range
+    /// values saturate.
+    pub fn from_f64(x: f64) -> Self {
+        // One correctly rounded multiply and an exact round, so the same
+        // on every IEEE 754 platform
+        Fixed((x * SCALE as f64).round() as i128)
+    }
+
+    pub fn to_f64(self) -> f64 {
+        self.0 as f64 / SCALE as f64
+    }
+
+    pub fn from_int(n: i64) -> Self {
+        Fixed((n as i128).saturating_mul(SCALE as i128))
+    }
+
+    pub fn is_negative(self) -> bool {
+        self.0 < 0
+    }
+
+    pub fn abs(self) -> Self {
+        Fixed(self.0.saturating_abs())
+    }
+
+    /// Quotient, truncated toward zero; `None` when dividing by zero.
+    pub fn checked_div(self, rhs: Fixed) -> Option<Fixed> {
+        if rhs.0 == 0 {
+            return None;
+        }
+        let magnitude = mul_div(self.0.unsigned_abs(), SCALE, rhs.0.unsigned_abs());
+        Some(signed(magnitude, (self.0 < 0) != (rhs.0 < 0)))
+    }
+
+    /// `self` raised to an integer power by repeated squaring.
+    pub fn powi(self, exp: i64) -> Fixed {
+        let mut base = self;
+        let mut result = Fixed::ONE;
+        let mut n = exp.unsigned_abs();
+        while n > 0 {
+            if n & 1 == 1 {
+                result = result * base;
+            }
+            n >>= 1;
+            if n > 0 {
+                base = base * base;
+            }
+        }
+        if exp < 0 {
+            Fixed::ONE.checked_div(result).unwrap_or(Fixed::MAX)
+        } else {
+            result
+        }
+    }
+
+    /// Natural logarithm; `None` for values that are not positive.
+    pub fn ln(self) -> Option<Fixed> {
+        if self.0 <= 0 {
+            return None;
+        }
+        // self = m · 2^e with m in [1, 2)
+        let mut m = self.0;
+        let mut e: i64 = 0;
+        while m >= 2 * SCALE as i128 {
+            m >>= 1;
+            e += 1;
+        }
+        while m < SCALE as i128 {
+            m <<= 1;
+            e -= 1;
+        }
+        // ln m = 2 atanh z, z = (m - 1) / (m + 1) ≤ 1/3
+        let m = Fixed(m);
+        let z = (m - Fixed::ONE).checked_div(m + Fixed::ONE)?;
+        let z2 = z * z;
+        let mut term = z;
+        let mut sum = z;
+        let mut k: i128 = 1;
+        loop {
+            term = term * z2;
+            k += 2;
+            let t = Fixed(term.0 / k);
+            if t.0 == 0 {
+                break;
+            }
+            sum = sum + t;
+        }
+        Some(Fixed::from_int(e) * LN_2 + sum + sum)
+    }
+
+    /// e raised to `self`; saturates above about e^46.
+    pub fn exp(self) -> Fixed {
+        // self = n ln 2 + r with |r| ≤ ln 2 / 2
+        let n = self.0.saturating_add(self.0.signum() * LN_2.0 / 2) / LN_2.0;
+        let r = self - Fixed(n.saturating_mul(LN_2.0));
+        let mut term = Fixed::ONE;
+        let mut sum = Fixed::ONE;
+        let mut k: i128 = 1;
+        loop {
+            term = Fixed((term * r).0 / k);
+            if term.0 == 0 {
+                break;
+            }
+            sum = sum + term;
+            k += 1;
+        }
+        if n >= 0 {
+            if n >= 127 || sum.0.leading_zeros() <= n as u32 {
+                return Fixed::MAX;
+            }
+            Fixed(sum.0 << n)
+        } else if n <= -127 {
+            Fixed::ZERO
+        } else {
+            Fixed(sum.0 >> -n)
+        }
+    }
+}
+
+impl Add for Fixed {
+    type Output = Fixed;
+
+    fn add(self, rhs: Fixed) -> Fixed {
+        Fixed(self.0.saturating_add(rhs.0))
+    }
+}
+
+/// Product, truncated toward zero.
+impl Mul for Fixed {
+    type Output = Fixed;
+
+    fn mul(self, rhs: Fixed) -> Fixed {
+        let magnitude = mul_div(self.0.unsigned_abs(), rhs.0.unsigned_abs(), SCALE);
+        signed(magnitude, (self.0 < 0) != (rhs.0 < 0))
+    }
+}
+
+impl Sub for Fixed {
+    type Output = Fixed;
+
+    fn sub(self, rhs: Fixed) -> Fixed {
+        Fixed(self.0.saturating_sub(rhs.0))
+    }
+}
+
+impl Neg for Fixed {
+    type Output = Fixed;
+
+    fn neg(self) -> Fixed {
+        Fixed(self.0.saturating_neg())
+    }
+}
+
+impl fmt::Display for Fixed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let magnitude = self.0.unsigned_abs();
+        write!(f, "{}{}.{:018}", sign, magnitude / SCALE, magnitude % SCALE)
+    }
+}
+
+fn signed(magnitude: u128, negative: bool) -> Fixed {
+    let value = i128::try_from(magnitude).unwrap_or(i128::MAX);
+    Fixed(if negative { -value } else { value })
+}
+
+/// `a * b / c`, truncated, through a 256-bit intermediate; saturates when
+/// the quotient does not fit.
+fn mul_div(a: u128, b: u128, c: u128) -> u128 {
+    let (hi, lo) = mul_wide(a, b);
+    if hi >= c {
+        return u128::MAX;
+    }
+    // Shift-subtract long division of hi:lo by c
+    let mut rem = hi;
+    let mut quotient = 0u128;
+    for i in (0..128).rev() {
+        let carry = rem >> 127;
+        rem = (rem << 1) | ((lo >> i) & 1);
+        quotient <<= 1;
+        if carry == 1 || rem >= c {
+            rem = rem.wrapping_sub(c);
+            quotient |= 1;
+        }
+    }
+    quotient
+}
+
+/// Full 256-bit product of `a` and `b` as (high, low) halves.
+fn mul_wide(a: u128, b: u128) -> (u128, u128) {
+    let mask = u64::MAX as u128;
+    let (a1, a0) = (a >> 64, a & mask);
+    let (b1, b0) = (b >> 64, b & mask);
+    let p00 = a0 * b0;
+    let p01 = a0 * b1;
+    let p10 = a1 * b0;
+    let p11 = a1 * b1;
+    let mid = (p00 >> 64) + (p01 & mask) + (p10 & mask);
+    let lo = (p00 & mask) | (mid << 64);
+    let hi = p11 + (p01 >> 64) + (p10 >> 64) + (mid >> 64);
+    (hi, lo)
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+// Simulation math
+// ═══════════════════════════════════════════════════════════════════════
+
+/// `a * b`
+#[cfg(feature = "fixed-point")]
+pub fn mul(a: f64, b: f64) -> f64 {
+    (Fixed::from_f64(a) * Fixed::from_f64(b)).to_f64()
+}
+
+/// `a * b`
+#[cfg(not(feature = "fixed-point"))]
+pub fn mul(a: f64, b: f64) -> f64 {
+    a * b
+}
+
+/// `a / b`; division by zero gives infinity or NaN as with `f64`.
+#[cfg(feature = "fixed-point")]
+pub fn div(a: f64, b: f64) -> f64 {
+    match Fixed::from_f64(a).checked_div(Fixed::from_f64(b)) {
+        Some(q) => q.to_f64(),
+        None => a / b,
+    }
+}
+
+/// `a / b`; division by zero gives infinity or NaN as with `f64`.
+#[cfg(not(feature = "fixed-point"))]
+pub fn div(a: f64, b: f64) -> f64 {
+    a / b
+}
+
+/// `base` raised to an integer power.
+#[cfg(feature = "fixed-point")]
+pub fn powi(base: f64, exp: i32) -> f64 {
+    Fixed::from_f64(base).powi(exp as i64).to_f64()
+}
+
+/// `base` raised to an integer power.
+#[cfg(not(feature = "fixed-point"))]
+pub fn powi(base: f64, exp: i32) -> f64 {
+    base.powi(exp)
+}
+
+/// Natural logarithm; NaN for negative values and -∞ for zero, as with
+/// `f64`.
+#[cfg(feature = "fixed-point")]
+pub fn ln(x: f64) -> f64 {
+    match Fixed::from_f64(x).ln() {
+        Some(l) => l.to_f64(),
+        None => x.ln(),
+    }
+}
+
+/// Natural logarithm; NaN for negative values and -∞ for zero, as with
+/// `f64`.
+#[cfg(not(feature = "fixed-point"))]
+pub fn ln(x: f64) -> f64 {
+    x.ln()
+}
+
+/// e raised to `x`.
+#[cfg(feature = "fixed-point")]
+pub fn exp(x: f64) -> f64 {
+    Fixed::from_f64(x).exp().to_f64()
+}
+
+/// e raised to `x`.
+#[cfg(not(feature = "fixed-point"))]
+pub fn exp(x: f64) -> f64 {
+    x.exp()
+}
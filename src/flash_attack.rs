@@ -0,0 +1,207 @@
+//! Flash-loan style atomic attacks.
+//!
+//! On a programmable chain an attacker can borrow without collateral, act
+//! and repay inside one transaction, which reverts unless it ends in
+//! profit. A flash attack models that between two blocks: borrow ZEC, sell
+//! it on the AMM, run the configured liquidation pass against the
+//! manipulated pool, buy the ZEC back and repay the loan plus fee. No
+//! capital carries over, and the sequence only lands when it pays.
+//!
+//! Liquidations priced off the TWAP (or an external oracle) cannot see a
+//! price that exists only inside the transaction, so an atomic attack
+//! there is pure cost. Spot-priced liquidation can be sandwiched.
+
+use crate::error::ZaiSimError;
+use crate::scenario::{Scenario, ScenarioConfig};
+use crate::scenarios::{add_agents, apply_price_noise, generate_prices, ScenarioId};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlashAttackConfig {
+    /// ZEC borrowed and sold into the AMM
+    pub borrow_zec: f64,
+    /// Loan fee as a fraction of the amount borrowed
+    pub loan_fee_pct: f64,
+}
+
+impl Default for FlashAttackConfig {
+    fn default() -> Self {
+        FlashAttackConfig {
+            borrow_zec: 5000.0,
+            loan_fee_pct: 0.0009,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlashAttackResult {
+    /// Block the attack lands at, ahead of that block's own step
+    pub block: u64,
+    pub borrowed_zec: f64,
+    pub zai_from_sale: f64,
+    /// Liquidations the manipulated price triggered
+    pub liquidations: u32,
+    pub collateral_liquidated: f64,
+    pub bad_debt_created: f64,
+    /// ZEC recovered by spending the sale proceeds and any keeper rewards
+    pub zec_bought_back: f64,
+    pub loan_fee_zec: f64,
+    /// ZEC left after repaying the loan and fee; negative means the
+    /// transaction would revert
+    pub profit_zec: f64,
+    /// Whether the attack was applied to the scenario
+    pub executed: bool,
+}
+
+/// The atomic sequence at `block`. Fails, like a reverted transaction, if
+/// either swap is rejected.
+fn apply(
+    scenario: &mut Scenario,
+    attack: &FlashAttackConfig,
+    block: u64,
+) -> Result<FlashAttackResult, ZaiSimError> {
+    let external_price = scenario
+        .metrics
+        .external_price
+        .last()
+        .copied()
+        .unwrap_or(scenario.config.initial_amm_price());
+    let history_before = scenario.liquidation_engine.history.len();
+    let bad_debt_before = scenario.liquidation_engine.total_bad_debt;
+
+    let zai_from_sale = scenario.amm.swap_zec_for_zai(attack.borrow_zec, block)?;
+    let (liquidations, _) = scenario.run_liquidations(block, external_price);
+    let triggered = &scenario.liquidation_engine.history[history_before..];
+    let collateral_liquidated = triggered.iter().map(|r| r.collateral_seized).sum();
+    let keeper_rewards: f64 = triggered.iter().map(|r| r.keeper_reward).sum();
+    let zec_bought_back = scenario
+        .amm
+        .swap_zai_for_zec(zai_from_sale + keeper_rewards, block)?;
+
+    let loan_fee_zec = attack.borrow_zec * attack.loan_fee_pct;
+    Ok(FlashAttackResult {
+        block,
+        borrowed_zec: attack.borrow_zec,
+        zai_from_sale,
+        liquidations,
+        collateral_liquidated,
+        bad_debt_created: scenario.liquidation_engine.total_bad_debt - bad_debt_before,
+        zec_bought_back,
+        loan_fee_zec,
+        profit_zec: zec_bought_back - attack.borrow_zec - loan_fee_zec,
+        executed: false,
+    })
+}
+
+/// Run `attack` on a copy of `scenario` at its next block and report the
+/// outcome without changing the scenario.
+pub fn simulate(
+    scenario: &Scenario,
+    attack: &FlashAttackConfig,
+) -> Result<FlashAttackResult, ZaiSimError> {
+    let mut copy: Scenario = serde_json::from_value(serde_json::to_value(scenario)?)?;
+    apply(&mut copy, attack, scenario.last_block() + 1)
+}
+
+/// Apply `attack` to `scenario` if it repays with a profit; otherwise it
+/// reverts and the scenario is left untouched.
+pub fn execute(
+    scenario: &mut Scenario,
+    attack: &FlashAttackConfig,
+) -> Result<FlashAttackResult, ZaiSimError> {
+    let trial = simulate(scenario, attack)?;
+    if trial.profit_zec <= 0.0 {
+        return Ok(trial);
+    }
+    let block = scenario.last_block() + 1;
+    let mut result = apply(scenario, attack, block)?;
+    result.executed = true;
+    Ok(result)
+}
+
+/// Whether a config is atomically exploitable along a stress path.
+#[derive(Debug, Clone, Default)]
+pub struct FlashScanReport {
+    /// Attacks simulated (rejected swaps excluded)
+    pub attempts: usize,
+    /// Attacks that repaid with a profit
+    pub exploitable: Vec<FlashAttackResult>,
+    /// Most profitable attempt, profitable or not
+    pub best: Option<FlashAttackResult>,
+}
+
+impl FlashScanReport {
+    pub fn is_exploitable(&self) -> bool {
+        !self.exploitable.is_empty()
+    }
+}
+
+/// Tries flash attacks of several sizes at regular points along a stress
+/// scenario run.
+pub struct FlashScan {
+    pub config: ScenarioConfig,
+    pub scenario: ScenarioId,
+    pub blocks: usize,
+    pub seed: u64,
+    /// Extra setup before the run, e.g. opening target vaults
+    pub setup: fn(&mut Scenario),
+    /// ZEC amounts to try borrowing
+    pub borrow_sizes: Vec<f64>,
+    pub loan_fee_pct: f64,
+    /// Blocks between attack attempts
+    pub every_blocks: usize,
+}
+
+impl FlashScan {
+    pub fn new(config: ScenarioConfig, scenario: ScenarioId, blocks: usize, seed: u64) -> Self {
+        FlashScan {
+            config,
+            scenario,
+            blocks,
+            seed,
+            setup: |_| {},
+            borrow_sizes: vec![1_000.0, 5_000.0, 20_000.0],
+            loan_fee_pct: FlashAttackConfig::default().loan_fee_pct,
+            every_blocks: 50,
+        }
+    }
+
+    /// Run the scenario, simulating every attack size after each
+    /// `every_blocks` blocks. Attacks never land, so the run itself is
+    /// unaffected.
+    pub fn run(&self) -> Result<FlashScanReport, ZaiSimError> {
+        let mut prices = generate_prices(self.scenario, self.blocks, self.seed);
+        if self.config.stochastic {
+            apply_price_noise(&mut prices, self.config.noise_sigma, self.seed);
+        }
+        let mut scenario = Scenario::new_with_seed(&self.config, self.seed);
+        add_agents(self.scenario, &mut scenario);
+        (self.setup)(&mut scenario);
+
+        let mut report = FlashScanReport::default();
+        let every = self.every_blocks.max(1);
+        for end in (every..=prices.len()).step_by(every) {
+            scenario.run(&prices[..end]);
+            for &borrow_zec in &self.borrow_sizes {
+                let attack = FlashAttackConfig {
+                    borrow_zec,
+                    loan_fee_pct: self.loan_fee_pct,
+                };
+                let Ok(result) = simulate(&scenario, &attack) else {
+                    continue;
+                };
+                report.attempts += 1;
+                if result.profit_zec > 0.0 {
+                    report.exploitable.push(result.clone());
+                }
+                if report
+                    .best
+                    .as_ref()
+                    .is_none_or(|b| result.profit_zec > b.profit_zec)
+                {
+                    report.best = Some(result);
+                }
+            }
+        }
+        Ok(report)
+    }
+}
@@ -0,0 +1,91 @@
+//! Funding-rate style negative rates on ZAI balances.
+//!
+//! The controller answers ZAI trading above par (AMM spot below the
+//! redemption price) with a positive redemption rate, which only moves the
+//! redemption price. With a funding rate configured, the same rate is also
+//! charged directly on ZAI held: each block a fraction of every wallet
+//! balance is taken as demurrage and paid to the treasury surplus. Holders
+//! then face a carry cost and sell ZAI, pushing it back toward par.
+//!
+//! The rate is a ZAI rate, so a charge is a negative `rate`; ZAI trading
+//! below par is left to the redemption price alone.
+
+use serde::{Deserialize, Serialize};
+
+use crate::cdp::per_block_to_apr;
+use crate::savings::SavingsModule;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FundingRateConfig {
+    /// Charge per block per unit of positive redemption rate
+    pub rate_multiplier: f64,
+    /// Cap on the fraction of a balance charged per block
+    pub max_charge_per_block: f64,
+    /// Charge ZAI locked in the savings module as well as wallets
+    pub charge_savings: bool,
+}
+
+impl Default for FundingRateConfig {
+    fn default() -> Self {
+        FundingRateConfig {
+            rate_multiplier: 1.0,
+            max_charge_per_block: 1e-4,
+            charge_savings: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FundingRate {
+    pub config: FundingRateConfig,
+    /// Per-block rate on ZAI balances (≤ 0; negative while charging)
+    pub rate: f64,
+    /// Demurrage collected into the treasury (ZAI)
+    pub total_charged_zai: f64,
+}
+
+impl FundingRate {
+    pub fn new(config: FundingRateConfig) -> Self {
+        FundingRate {
+            config,
+            rate: 0.0,
+            total_charged_zai: 0.0,
+        }
+    }
+
+    /// Set the rate from the controller's per-block redemption rate.
+    pub fn update(&mut self, redemption_rate: f64) -> f64 {
+        let charge = (redemption_rate * self.config.rate_multiplier)
+            .clamp(0.0, self.config.max_charge_per_block);
+        self.rate = -charge;
+        self.rate
+    }
+
+    /// Annual cost of holding ZAI at the current rate (≥ 0).
+    pub fn holding_cost(&self) -> f64 {
+        per_block_to_apr(-self.rate)
+    }
+
+    /// Take one block's charge from `balance`. Returns the ZAI taken.
+    pub fn charge(&mut self, balance: &mut f64) -> f64 {
+        if *balance <= 0.0 || self.rate >= 0.0 {
+            return 0.0;
+        }
+        let amount = *balance * -self.rate;
+        *balance -= amount;
+        self.total_charged_zai += amount;
+        amount
+    }
+
+    /// Take one block's charge from savings deposits by shrinking the
+    /// savings index. Returns the ZAI taken.
+    pub fn charge_savings(&mut self, savings: &mut SavingsModule) -> f64 {
+        if !self.config.charge_savings || self.rate >= 0.0 {
+            return 0.0;
+        }
+        let amount = savings.total_deposits_zai() * -self.rate;
+        savings.chi *= 1.0 + self.rate;
+        self.total_charged_zai += amount;
+        amount
+    }
+}
@@ -0,0 +1,314 @@
+//! Property-based fuzzing of whole runs (`fuzz` feature).
+//!
+//! `proptest` strategies generate scenario configs, price paths and agent
+//! schedules, and `check_run` asserts the properties every run should
+//! have: no accounting invariant violations, cumulative counters that never
+//! decrease, and no NaN in any metric. The generators are public, so
+//! downstream crates can fuzz their own agents by adding them in
+//! `FuzzCase::run`'s setup:
+//!
+//! ```ignore
+//! proptest! {
+//!     #[test]
+//!     fn my_agent_keeps_accounting(case in fuzz_case(300)) {
+//!         let scenario = case.run(|s| s.after_step(my_agent));
+//!         check_run(&scenario)?;
+//!     }
+//! }
+//! ```
+
+use proptest::prelude::*;
+use proptest::test_runner::TestCaseError;
+
+use crate::agents::{
+    Arbitrageur, ArbitrageurConfig, CdpHolder, CdpHolderConfig, DemandAgent, DemandAgentConfig,
+    MinerAgent, MinerAgentConfig,
+};
+use crate::controller::ControllerConfig;
+use crate::invariants::InvariantConfig;
+use crate::scenario::{BlockMetrics, MetricsStore, Scenario, ScenarioConfig};
+
+/// An agent added to a run at some block.
+#[derive(Debug, Clone)]
+pub enum ScheduledAgent {
+    Arber(ArbitrageurConfig),
+    Demand(DemandAgentConfig),
+    Miner(MinerAgentConfig),
+    /// Opens its vault on joining, if the vault is allowed
+    CdpHolder(CdpHolderConfig),
+}
+
+impl ScheduledAgent {
+    fn join(self, scenario: &mut Scenario, block: u64) {
+        match self {
+            ScheduledAgent::Arber(c) => scenario.arbers.push(Arbitrageur::new(c)),
+            ScheduledAgent::Demand(c) => scenario.demand_agents.push(DemandAgent::new(c)),
+            ScheduledAgent::Miner(c) => scenario.miners.push(MinerAgent::new(c)),
+            ScheduledAgent::CdpHolder(c) => {
+                let mut holder = CdpHolder::new(c);
+                // Before the run, the scenario opens it with the others
+                if block > 0 {
+                    let _ = holder.open_vault(&mut scenario.registry, &scenario.amm, block);
+                }
+                scenario.cdp_holders.push(holder);
+            }
+        }
+    }
+}
+
+/// Agents joining a run, by block; block 0 joins before the first block.
+#[derive(Debug, Clone, Default)]
+pub struct AgentSchedule {
+    pub joins: Vec<(u64, ScheduledAgent)>,
+}
+
+impl AgentSchedule {
+    /// Add the block-0 agents now and the rest as their blocks start.
+    pub fn install(&self, scenario: &mut Scenario) {
+        let mut later = Vec::new();
+        for (block, agent) in &self.joins {
+            if *block == 0 {
+                agent.clone().join(scenario, 0);
+            } else {
+                later.push((*block, agent.clone()));
+            }
+        }
+        if later.is_empty() {
+            return;
+        }
+        scenario.before_step(move |scenario, block| {
+            for (_, agent) in later.iter().filter(|(b, _)| *b == block) {
+                agent.clone().join(scenario, block);
+            }
+        });
+    }
+}
+
+/// One generated run.
+#[derive(Debug, Clone)]
+pub struct FuzzCase {
+    pub config: ScenarioConfig,
+    /// External ZEC price per block
+    pub prices: Vec<f64>,
+    pub schedule: AgentSchedule,
+    pub seed: u64,
+}
+
+impl FuzzCase {
+    /// Build the scenario, add the scheduled agents, let `setup` add its
+    /// own (or hooks), and run it.
+    pub fn run(&self, setup: impl FnOnce(&mut Scenario)) -> Scenario {
+        let mut scenario = Scenario::new_with_seed(&self.config, self.seed);
+        self.schedule.install(&mut scenario);
+        setup(&mut scenario);
+        scenario.run(&self.prices);
+        scenario
+    }
+}
+
+/// Scenario configs across AMM depth, fees, collateral and liquidation
+/// parameters and controller mode, with the invariant checker recording
+/// rather than panicking.
+pub fn scenario_config() -> impl Strategy<Value = ScenarioConfig> {
+    (
+        (20.0..120.0, 500_000.0..20_000_000.0, 0.0..0.01),
+        (1.2..2.5, 0.0..0.2, 0.0..0.1),
+        (any::<bool>(), any::<bool>(), any::<bool>()),
+    )
+        .prop_map(
+            |((price, depth, fee), (min_ratio, penalty, stability_fee), flags)| {
+                let (amm_liquidation, graduated, tick) = flags;
+                let mut config = ScenarioConfig::default().with_amm(price, depth);
+                config.initial_redemption_price = price;
+                config.amm_swap_fee = fee;
+                config.cdp_config.min_ratio = min_ratio;
+                config.cdp_config.liquidation_penalty = penalty;
+                config.cdp_config.stability_fee_rate = stability_fee;
+                config.use_amm_liquidation = amm_liquidation;
+                config.use_graduated_liquidation = graduated;
+                if tick {
+                    config.controller_config = ControllerConfig::default_tick();
+                }
+                config.invariants = Some(InvariantConfig {
+                    panic_on_violation: false,
+                    ..InvariantConfig::default()
+                });
+                config
+            },
+        )
+}
+
+/// `blocks` external prices starting at `start`: a random walk of up to
+/// ±5% per block with occasional jumps of -50% to +30%.
+pub fn price_path(start: f64, blocks: usize) -> impl Strategy<Value = Vec<f64>> {
+    let step = (-0.05..0.05, prop::bool::weighted(0.01), -0.7..0.26);
+    prop::collection::vec(step, blocks).prop_map(move |steps| {
+        let mut price = start;
+        steps
+            .into_iter()
+            .map(|(r, jump, jump_r)| {
+                let r: f64 = if jump { jump_r } else { r };
+                price *= r.exp();
+                price
+            })
+            .collect()
+    })
+}
+
+/// An arber, demand agent or vault holder (at a collateral ratio of 1.5 to
+/// 4 at `price`) joining at a block up to `blocks`, or a miner joining at
+/// the start.
+pub fn scheduled_agent(price: f64, blocks: usize) -> impl Strategy<Value = (u64, ScheduledAgent)> {
+    let join = 0..blocks as u64;
+    prop_oneof![
+        (join.clone(), 1_000.0..200_000.0, 10.0..5_000.0, 0.1..2.0).prop_map(
+            |(block, zai, zec, threshold)| {
+                let config = ArbitrageurConfig {
+                    initial_zai_balance: zai,
+                    initial_zec_balance: zec,
+                    arb_threshold_pct: threshold,
+                    ..ArbitrageurConfig::default()
+                };
+                (block, ScheduledAgent::Arber(config))
+            }
+        ),
+        (join.clone(), 10.0..10_000.0, 0.0..0.2).prop_map(|(block, zec, elasticity)| {
+            let config = DemandAgentConfig {
+                initial_zec_balance: zec,
+                demand_elasticity: elasticity,
+                ..DemandAgentConfig::default()
+            };
+            (block, ScheduledAgent::Demand(config))
+        }),
+        (0.1..5.0, 0.0..1.0).prop_map(|(reward, sell)| {
+            let config = MinerAgentConfig {
+                block_reward: reward,
+                miner_sell_fraction: sell,
+                ..MinerAgentConfig::default()
+            };
+            (0, ScheduledAgent::Miner(config))
+        }),
+        (join, 10.0..500.0, 1.5..4.0, 0.0..500.0).prop_map(
+            move |(block, collateral, ratio, reserve)| {
+                let config = CdpHolderConfig {
+                    target_ratio: ratio,
+                    action_threshold_ratio: (ratio * 0.8).max(1.2),
+                    reserve_zec: reserve,
+                    initial_collateral: collateral,
+                    initial_debt: collateral * price / ratio,
+                };
+                (block, ScheduledAgent::CdpHolder(config))
+            }
+        ),
+    ]
+}
+
+/// One to twelve scheduled agents, always including an arber at the start
+/// so the AMM tracks the external price.
+pub fn agent_schedule(price: f64, blocks: usize) -> impl Strategy<Value = AgentSchedule> {
+    prop::collection::vec(scheduled_agent(price, blocks), 0..12).prop_map(|mut joins| {
+        joins.insert(0, (0, ScheduledAgent::Arber(ArbitrageurConfig::default())));
+        AgentSchedule { joins }
+    })
+}
+
+/// A config, a price path from its AMM price, an agent schedule and a
+/// seed, for a `blocks`-block run.
+pub fn fuzz_case(blocks: usize) -> impl Strategy<Value = FuzzCase> {
+    (scenario_config(), any::<u64>()).prop_flat_map(move |(config, seed)| {
+        let price = config.initial_amm_price();
+        (price_path(price, blocks), agent_schedule(price, blocks)).prop_map(
+            move |(prices, schedule)| FuzzCase {
+                config: config.clone(),
+                prices,
+                schedule,
+                seed,
+            },
+        )
+    })
+}
+
+/// Every property below.
+pub fn check_run(scenario: &Scenario) -> Result<(), TestCaseError> {
+    check_invariants(scenario)?;
+    check_counters(&scenario.metrics)?;
+    check_no_nan(&scenario.metrics)
+}
+
+/// No accounting invariant violations, when the checker is configured.
+pub fn check_invariants(scenario: &Scenario) -> Result<(), TestCaseError> {
+    if let Some(v) = scenario
+        .invariants
+        .as_ref()
+        .and_then(|c| c.violations.first())
+    {
+        return Err(TestCaseError::fail(v.to_string()));
+    }
+    Ok(())
+}
+
+/// Cumulative counters recorded in `BlockMetrics`.
+const COUNTERS: [(&str, fn(&BlockMetrics) -> f64); 12] = [
+    ("bad_debt", |m| m.bad_debt),
+    ("cumulative_fees_zai", |m| m.cumulative_fees_zai),
+    ("cumulative_redeemed_zai", |m| m.cumulative_redeemed_zai),
+    ("penalty_to_keepers", |m| m.penalty_to_keepers),
+    ("penalty_to_lps", |m| m.penalty_to_lps),
+    ("penalty_to_insurance", |m| m.penalty_to_insurance),
+    ("penalty_to_treasury", |m| m.penalty_to_treasury),
+    ("penalty_burned", |m| m.penalty_burned),
+    ("savings_interest_paid_zai", |m| m.savings_interest_paid_zai),
+    ("funding_charged_zai", |m| m.funding_charged_zai),
+    ("gas_paid_zec", |m| m.gas_paid_zec),
+    ("reorgs", |m| m.reorgs as f64),
+];
+
+/// Block numbers increase and cumulative counters never decrease, beyond
+/// floating-point rounding.
+pub fn check_counters(metrics: &MetricsStore) -> Result<(), TestCaseError> {
+    let mut rows = metrics.iter();
+    let Some(mut prev) = rows.next() else {
+        return Ok(());
+    };
+    for next in rows {
+        prop_assert!(
+            next.block > prev.block,
+            "block {} follows block {}",
+            next.block,
+            prev.block
+        );
+        for (name, counter) in COUNTERS {
+            let (a, b) = (counter(&prev), counter(&next));
+            prop_assert!(
+                b >= a - 1e-9 * a.abs().max(1.0),
+                "{} fell from {} to {} at block {}",
+                name,
+                a,
+                b,
+                next.block
+            );
+        }
+        prev = next;
+    }
+    Ok(())
+}
+
+/// No metric is NaN.
+pub fn check_no_nan(metrics: &MetricsStore) -> Result<(), TestCaseError> {
+    for m in metrics {
+        let text = format!("{:?}", m);
+        if let Some(at) = text.find("NaN") {
+            // The innermost field holding it
+            let before = &text[..at];
+            let field = before
+                .rfind(": ")
+                .and_then(|colon| before[..colon].rsplit([' ', '{', '(']).next())
+                .unwrap_or("?");
+            return Err(TestCaseError::fail(format!(
+                "{} is NaN at block {}",
+                field, m.block
+            )));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,148 @@
+//! Per-action transaction fees.
+//!
+//! Without a fee model every agent trade, vault operation and liquidation
+//! is free, so arbers act on any deviation past their threshold and
+//! keepers take lots of any size. With gas configured each action costs a
+//! fee in ZEC, paid from the acting agent's ZEC balance, or from its ZAI
+//! at spot when it holds too little ZEC. Keepers pay for liquidations out
+//! of their rewards.
+//!
+//! The fee is flat, or with congestion pricing it moves each block in the
+//! EIP-1559 manner: up when the last block held more actions than the
+//! target, down when it held fewer, never below the base fee. Fees go to
+//! block producers, so they leave the simulated system.
+
+use serde::{Deserialize, Serialize};
+
+use crate::agents::AgentAction;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CongestionConfig {
+    /// Actions per block at which the fee holds steady
+    pub target_actions_per_block: u32,
+    /// Largest fractional fee change per block
+    pub max_change_per_block: f64,
+    /// Cap on the fee as a multiple of the base fee
+    pub max_multiplier: f64,
+}
+
+impl Default for CongestionConfig {
+    fn default() -> Self {
+        CongestionConfig {
+            target_actions_per_block: 20,
+            max_change_per_block: 0.125,
+            max_multiplier: 100.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GasConfig {
+    /// Fee per action (ZEC)
+    pub base_fee_zec: f64,
+    /// Congestion-dependent fee; `None` charges the base fee flat
+    pub congestion: Option<CongestionConfig>,
+}
+
+impl Default for GasConfig {
+    fn default() -> Self {
+        GasConfig {
+            base_fee_zec: 0.02,
+            congestion: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GasMarket {
+    pub config: GasConfig,
+    /// Current fee per action (ZEC)
+    pub fee_zec: f64,
+    /// Actions charged so far this block
+    pub block_actions: u32,
+    pub total_actions: u64,
+    /// Fees paid by agents (ZEC)
+    pub total_fees_zec: f64,
+    /// Fees paid by keepers for liquidations (ZEC)
+    pub keeper_fees_zec: f64,
+}
+
+impl GasMarket {
+    pub fn new(config: GasConfig) -> Self {
+        GasMarket {
+            fee_zec: config.base_fee_zec,
+            config,
+            block_actions: 0,
+            total_actions: 0,
+            total_fees_zec: 0.0,
+            keeper_fees_zec: 0.0,
+        }
+    }
+
+    /// Current fee per action in ZAI at `zec_price`.
+    pub fn fee_zai(&self, zec_price: f64) -> f64 {
+        self.fee_zec * zec_price
+    }
+
+    /// Count one action paid for outside `pay`, e.g. a fee an agent
+    /// deducts itself.
+    pub fn record(&mut self, fee_zec: f64) {
+        self.block_actions += 1;
+        self.total_actions += 1;
+        self.total_fees_zec += fee_zec;
+    }
+
+    /// Take one action's fee from `zec`, covering any shortfall from `zai`
+    /// at `zec_price`. Returns the fee paid in ZEC, which is less than the
+    /// fee only when the agent cannot afford it.
+    pub fn pay(&mut self, zec: &mut f64, zai: &mut f64, zec_price: f64) -> f64 {
+        let from_zec = self.fee_zec.min(zec.max(0.0));
+        *zec -= from_zec;
+        let mut paid = from_zec;
+        if paid < self.fee_zec && zec_price > 0.0 {
+            let from_zai = ((self.fee_zec - paid) * zec_price).min(zai.max(0.0));
+            *zai -= from_zai;
+            paid += from_zai / zec_price;
+        }
+        self.record(paid);
+        paid
+    }
+
+    /// Charge an agent for each transaction in `actions`. Returns the ZEC
+    /// paid.
+    pub fn charge(
+        &mut self,
+        actions: &[AgentAction],
+        zec: &mut f64,
+        zai: &mut f64,
+        zec_price: f64,
+    ) -> f64 {
+        let count = actions.iter().filter(|a| a.is_transaction()).count();
+        (0..count).map(|_| self.pay(zec, zai, zec_price)).sum()
+    }
+
+    /// Keepers pay for `count` liquidations. Returns the ZEC paid.
+    pub fn charge_liquidations(&mut self, count: u32) -> f64 {
+        let paid = self.fee_zec * count as f64;
+        self.block_actions += count;
+        self.total_actions += count as u64;
+        self.keeper_fees_zec += paid;
+        paid
+    }
+
+    /// Open a block: reprice from the last block's action count and reset
+    /// the count. Returns the fee for the new block.
+    pub fn begin_block(&mut self) -> f64 {
+        if let Some(congestion) = &self.config.congestion {
+            let target = congestion.target_actions_per_block.max(1) as f64;
+            let pressure = (self.block_actions as f64 - target) / target;
+            let change = pressure.clamp(-1.0, 1.0) * congestion.max_change_per_block;
+            let base = self.config.base_fee_zec;
+            self.fee_zec = (self.fee_zec * (1.0 + change))
+                .min(base * congestion.max_multiplier)
+                .max(base);
+        }
+        self.block_actions = 0;
+        self.fee_zec
+    }
+}
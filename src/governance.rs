@@ -0,0 +1,249 @@
+//! Scheduled governance parameter changes.
+//!
+//! A `ParameterSchedule` sets `ScenarioConfig` values at given blocks, so a
+//! run can model governance reacting to a crash some blocks after it starts
+//! (e.g. raising `min_ratio` at block 400). Changes apply at the start of
+//! their block, before any agent acts. A `GovernanceAgent` fills the
+//! schedule endogenously: it watches block metrics and, once a rule's
+//! threshold is breached, enacts that rule's changes after a voting delay.
+
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::ZaiSimError;
+use crate::scenario::{BlockMetrics, ScenarioConfig};
+use crate::sweep::SweepEngine;
+
+/// Set `param` to `value` at the start of `block`. `param` is a sweep alias
+/// or dotted `ScenarioConfig` path, as accepted by `SweepEngine::set_param`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParameterChange {
+    pub block: u64,
+    pub param: String,
+    pub value: f64,
+}
+
+/// Parses `BLOCK:PARAM=VALUE`, e.g. `400:min_ratio=2.5`.
+impl FromStr for ParameterChange {
+    type Err = ZaiSimError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            ZaiSimError::Parse(format!(
+                "Invalid parameter change: {} (use BLOCK:PARAM=VALUE)",
+                s
+            ))
+        };
+        let (block, assignment) = s.split_once(':').ok_or_else(invalid)?;
+        let (param, value) = assignment.split_once('=').ok_or_else(invalid)?;
+        if param.trim().is_empty() {
+            return Err(invalid());
+        }
+        Ok(ParameterChange {
+            block: block.trim().parse()?,
+            param: param.trim().to_string(),
+            value: value.trim().parse()?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ParameterSchedule {
+    pub changes: Vec<ParameterChange>,
+}
+
+impl ParameterSchedule {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a change of `param` to `value` at `block`.
+    pub fn at(mut self, block: u64, param: &str, value: f64) -> Self {
+        self.changes.push(ParameterChange {
+            block,
+            param: param.to_string(),
+            value,
+        });
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Changes scheduled for `block`, in the order they were added.
+    pub fn due(&self, block: u64) -> impl Iterator<Item = &ParameterChange> {
+        self.changes.iter().filter(move |c| c.block == block)
+    }
+
+    /// `config` with the changes due at `block` applied; see `apply_changes`.
+    pub fn apply(
+        &self,
+        config: &ScenarioConfig,
+        block: u64,
+    ) -> (ScenarioConfig, Vec<(ParameterChange, ZaiSimError)>) {
+        apply_changes(config, self.due(block))
+    }
+
+    /// Check that every change can be applied to `config`.
+    pub fn validate(&self, config: &ScenarioConfig) -> Result<(), ZaiSimError> {
+        let mut config = config.clone();
+        for change in &self.changes {
+            SweepEngine::set_param(&mut config, &change.param, change.value).map_err(|e| {
+                ZaiSimError::Config(format!("Change at block {}: {}", change.block, e))
+            })?;
+        }
+        Ok(())
+    }
+}
+
+/// `config` with `changes` applied in order. Changes that cannot be applied
+/// are skipped and returned alongside.
+pub fn apply_changes<'a>(
+    config: &ScenarioConfig,
+    changes: impl IntoIterator<Item = &'a ParameterChange>,
+) -> (ScenarioConfig, Vec<(ParameterChange, ZaiSimError)>) {
+    let mut config = config.clone();
+    let mut failed = Vec::new();
+    for change in changes {
+        if let Err(e) = SweepEngine::set_param(&mut config, &change.param, change.value) {
+            failed.push((change.clone(), e));
+        }
+    }
+    (config, failed)
+}
+
+/// Quantity a `GovernanceRule` watches, read from each block's metrics.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum GovernanceMetric {
+    /// |AMM spot − redemption price| / redemption price
+    PegDeviation,
+    /// Cumulative bad debt (ZAI)
+    BadDebt,
+    /// Liquidations this block
+    Liquidations,
+    /// Total debt as a fraction of the debt ceiling
+    DebtUtilization,
+}
+
+impl GovernanceMetric {
+    pub fn value(&self, m: &BlockMetrics) -> f64 {
+        match self {
+            Self::PegDeviation => {
+                ((m.amm_spot_price - m.redemption_price) / m.redemption_price).abs()
+            }
+            Self::BadDebt => m.bad_debt,
+            Self::Liquidations => m.liquidation_count as f64,
+            Self::DebtUtilization => {
+                if m.debt_ceiling > 0.0 {
+                    m.total_debt / m.debt_ceiling
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+}
+
+/// A parameter assignment enacted by governance.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParameterSet {
+    pub param: String,
+    pub value: f64,
+}
+
+/// When `metric` exceeds `threshold` for `breach_blocks` consecutive
+/// blocks, propose `changes`. Each rule passes at most once per run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GovernanceRule {
+    pub metric: GovernanceMetric,
+    pub threshold: f64,
+    pub breach_blocks: u64,
+    pub changes: Vec<ParameterSet>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GovernanceAgentConfig {
+    pub rules: Vec<GovernanceRule>,
+    /// Blocks from a proposal to its changes taking effect (vote + delay)
+    pub voting_delay_blocks: u64,
+}
+
+impl Default for GovernanceAgentConfig {
+    fn default() -> Self {
+        GovernanceAgentConfig {
+            rules: Vec::new(),
+            voting_delay_blocks: 1152, // ~1 day at 75s blocks
+        }
+    }
+}
+
+/// A proposal raised by a `GovernanceAgent`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Proposal {
+    /// Index into `GovernanceAgentConfig::rules`
+    pub rule: usize,
+    /// Block at which the breach condition was met
+    pub proposed_at: u64,
+    /// Block at whose start the changes apply
+    pub enacted_at: u64,
+}
+
+/// Monitors block metrics and enacts parameter changes after a voting
+/// delay. The scenario applies its queued changes at the start of their
+/// block, alongside the config's `ParameterSchedule`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GovernanceAgent {
+    pub config: GovernanceAgentConfig,
+    pub proposals: Vec<Proposal>,
+    /// Changes from passed proposals, enacted or still pending
+    pub queued: Vec<ParameterChange>,
+    breach_streaks: Vec<u64>,
+}
+
+impl GovernanceAgent {
+    pub fn new(config: GovernanceAgentConfig) -> Self {
+        let rules = config.rules.len();
+        GovernanceAgent {
+            config,
+            proposals: Vec::new(),
+            queued: Vec::new(),
+            breach_streaks: vec![0; rules],
+        }
+    }
+
+    /// Observe one block's metrics, queueing the changes of any rule that
+    /// passes for `voting_delay_blocks` later (at least the next block).
+    pub fn observe(&mut self, m: &BlockMetrics) {
+        let enacted_at = m.block + self.config.voting_delay_blocks.max(1);
+        for (i, rule) in self.config.rules.iter().enumerate() {
+            if self.proposals.iter().any(|p| p.rule == i) {
+                continue;
+            }
+            if rule.metric.value(m) > rule.threshold {
+                self.breach_streaks[i] += 1;
+            } else {
+                self.breach_streaks[i] = 0;
+            }
+            if self.breach_streaks[i] >= rule.breach_blocks.max(1) {
+                self.proposals.push(Proposal {
+                    rule: i,
+                    proposed_at: m.block,
+                    enacted_at,
+                });
+                self.queued
+                    .extend(rule.changes.iter().map(|set| ParameterChange {
+                        block: enacted_at,
+                        param: set.param.clone(),
+                        value: set.value,
+                    }));
+            }
+        }
+    }
+
+    /// Queued changes taking effect at the start of `block`.
+    pub fn due(&self, block: u64) -> impl Iterator<Item = &ParameterChange> {
+        self.queued.iter().filter(move |c| c.block == block)
+    }
+}
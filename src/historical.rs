@@ -5,8 +5,12 @@
 //! (48 blocks per hour at 75-second block time).
 
 use crate::controller::ControllerConfig;
+use crate::error::ZaiSimError;
 use crate::scenario::ScenarioConfig;
+use chrono::{NaiveDate, NaiveDateTime};
+use std::ops::RangeInclusive;
 use std::path::Path;
+use std::str::FromStr;
 
 /// Load hourly close prices from a CryptoCompare CSV file.
 ///
@@ -33,6 +37,295 @@ pub fn load_hourly_prices(csv_path: &str) -> Vec<f64> {
     prices
 }
 
+/// Load the `close` column of a CSV with a header row: CryptoCompare
+/// hourly files as well as `fetch` output.
+pub fn load_close_prices(path: &Path) -> Result<Vec<f64>, ZaiSimError> {
+    let mut reader = csv::Reader::from_path(path)?;
+    let close = reader
+        .headers()?
+        .iter()
+        .position(|h| h.trim() == "close")
+        .ok_or_else(|| ZaiSimError::Parse(format!("{} has no close column", path.display())))?;
+    let mut prices = Vec::new();
+    for result in reader.records() {
+        let record = result?;
+        let price: f64 = record
+            .get(close)
+            .ok_or_else(|| ZaiSimError::Parse(format!("Missing close in {}", path.display())))?
+            .trim()
+            .parse()?;
+        prices.push(price);
+    }
+    if prices.is_empty() {
+        return Err(ZaiSimError::Parse(format!(
+            "CSV {} contained no data rows",
+            path.display()
+        )));
+    }
+    Ok(prices)
+}
+
+/// Load `(timestamp_ms, close)` rows from a CSV with a header row: a
+/// `timestamp_ms` column as in `fetch` output, or a `timestamp` column in
+/// seconds as in CryptoCompare files.
+pub fn load_timed_prices(path: &Path) -> Result<Vec<(i64, f64)>, ZaiSimError> {
+    load_timed_column(path, &["close"])
+}
+
+/// Load `(timestamp_ms, volume)` rows, timestamped as in
+/// `load_timed_prices`: the `volume` column of `fetch` output, or
+/// CryptoCompare's `volume_from` (ZEC traded).
+pub fn load_timed_volumes(path: &Path) -> Result<Vec<(i64, f64)>, ZaiSimError> {
+    load_timed_column(path, &["volume", "volume_from"])
+}
+
+/// Timestamped values of the first of `names` the CSV has.
+fn load_timed_column(path: &Path, names: &[&str]) -> Result<Vec<(i64, f64)>, ZaiSimError> {
+    let mut reader = csv::Reader::from_path(path)?;
+    let headers = reader.headers()?.clone();
+    let column = |name: &str| headers.iter().position(|h| h.trim() == name);
+    let (time, scale) = match (column("timestamp_ms"), column("timestamp")) {
+        (Some(i), _) => (i, 1),
+        (None, Some(i)) => (i, 1000),
+        (None, None) => {
+            return Err(ZaiSimError::Parse(format!(
+                "{} has no timestamp_ms or timestamp column",
+                path.display()
+            )))
+        }
+    };
+    let value = names.iter().find_map(|name| column(name)).ok_or_else(|| {
+        ZaiSimError::Parse(format!(
+            "{} has no {} column",
+            path.display(),
+            names.join(" or ")
+        ))
+    })?;
+    let mut rows = Vec::new();
+    for result in reader.records() {
+        let record = result?;
+        let field = |i: usize| {
+            record
+                .get(i)
+                .map(str::trim)
+                .ok_or_else(|| ZaiSimError::Parse(format!("Short row in {}", path.display())))
+        };
+        rows.push((field(time)?.parse::<i64>()? * scale, field(value)?.parse()?));
+    }
+    if rows.is_empty() {
+        return Err(ZaiSimError::Parse(format!(
+            "CSV {} contained no data rows",
+            path.display()
+        )));
+    }
+    Ok(rows)
+}
+
+/// Median spacing of `rows`' timestamps in seconds, if there are two rows.
+pub fn row_interval_secs(rows: &[(i64, f64)]) -> Option<f64> {
+    let mut gaps: Vec<i64> = rows.windows(2).map(|w| w[1].0 - w[0].0).collect();
+    if gaps.is_empty() {
+        return None;
+    }
+    gaps.sort_unstable();
+    Some(gaps[gaps.len() / 2] as f64 / 1000.0)
+}
+
+/// Prices every `interval_secs` from the first row to the last,
+/// interpolated linearly between rows, e.g. 1m klines onto 75 s blocks.
+pub fn resample(rows: &[(i64, f64)], interval_secs: f64) -> Vec<(i64, f64)> {
+    let (Some(&(start, _)), Some(&(end, _))) = (rows.first(), rows.last()) else {
+        return Vec::new();
+    };
+    let step = interval_secs * 1000.0;
+    let mut out = Vec::new();
+    let mut i = 0;
+    let mut k = 0;
+    loop {
+        let t = start + (k as f64 * step).round() as i64;
+        if t > end {
+            break;
+        }
+        while i + 1 < rows.len() && rows[i + 1].0 <= t {
+            i += 1;
+        }
+        let (t0, p0) = rows[i];
+        let price = match rows.get(i + 1) {
+            Some(&(t1, p1)) if t1 > t0 => p0 + (p1 - p0) * (t - t0) as f64 / (t1 - t0) as f64,
+            _ => p0,
+        };
+        out.push((t, price));
+        k += 1;
+    }
+    out
+}
+
+/// Parse an interval like `75s`, `1m`, `4h` or `1d` (bare numbers are
+/// seconds) into seconds.
+pub fn parse_interval(s: &str) -> Result<f64, ZaiSimError> {
+    let s = s.trim();
+    let (number, unit) = match s.find(|c: char| c.is_ascii_alphabetic()) {
+        Some(i) => s.split_at(i),
+        None => (s, "s"),
+    };
+    let scale = match unit {
+        "s" => 1.0,
+        "m" => 60.0,
+        "h" => 3600.0,
+        "d" => 86_400.0,
+        _ => {
+            return Err(ZaiSimError::Parse(format!(
+                "Invalid interval: {} (use e.g. 75s, 1m, 1h)",
+                s
+            )))
+        }
+    };
+    let secs = number.parse::<f64>()? * scale;
+    if secs <= 0.0 || !secs.is_finite() {
+        return Err(ZaiSimError::Parse(format!("Invalid interval: {}", s)));
+    }
+    Ok(secs)
+}
+
+/// One end of a price window: a block (row) index, or a UTC date or time
+/// (`2021-05-19` or `2021-05-19T12:00`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PriceBound {
+    Block(usize),
+    Time(NaiveDateTime),
+}
+
+impl FromStr for PriceBound {
+    type Err = ZaiSimError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Ok(block) = s.parse() {
+            return Ok(PriceBound::Block(block));
+        }
+        [
+            "%Y-%m-%dT%H:%M:%S",
+            "%Y-%m-%dT%H:%M",
+            "%Y-%m-%d %H:%M:%S",
+            "%Y-%m-%d %H:%M",
+        ]
+        .iter()
+        .find_map(|f| NaiveDateTime::parse_from_str(s, f).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(s, "%Y-%m-%d")
+                .ok()
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+        })
+        .map(PriceBound::Time)
+        .ok_or_else(|| {
+            ZaiSimError::Parse(format!(
+                "Invalid bound: {} (a block number or YYYY-MM-DD[THH:MM])",
+                s
+            ))
+        })
+    }
+}
+
+/// Which part of a timed price series to simulate, and at what cadence.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PriceWindow {
+    /// First block or time, inclusive
+    pub from: Option<PriceBound>,
+    /// Last block or time, inclusive
+    pub to: Option<PriceBound>,
+    /// Resample to one price per this many seconds before slicing
+    pub resample_secs: Option<f64>,
+}
+
+impl PriceWindow {
+    /// Indices of `timestamps_ms` within the window, `None` if it's empty.
+    pub fn range(&self, timestamps_ms: &[i64]) -> Option<RangeInclusive<usize>> {
+        let last = timestamps_ms.len().checked_sub(1)?;
+        let ms = |t: NaiveDateTime| t.and_utc().timestamp_millis();
+        let start = match self.from {
+            None => 0,
+            Some(PriceBound::Block(b)) => b,
+            Some(PriceBound::Time(t)) => timestamps_ms.partition_point(|&ts| ts < ms(t)),
+        };
+        let end = match self.to {
+            None => last,
+            Some(PriceBound::Block(b)) => b.min(last),
+            Some(PriceBound::Time(t)) => timestamps_ms
+                .partition_point(|&ts| ts <= ms(t))
+                .checked_sub(1)?,
+        };
+        (start <= end).then_some(start..=end)
+    }
+
+    /// Resample `rows` if asked, then keep the window's rows. Fails if the
+    /// window is empty.
+    pub fn apply(&self, rows: &[(i64, f64)]) -> Result<Vec<(i64, f64)>, ZaiSimError> {
+        let rows = match self.resample_secs {
+            Some(secs) => resample(rows, secs),
+            None => rows.to_vec(),
+        };
+        let timestamps: Vec<i64> = rows.iter().map(|r| r.0).collect();
+        let range = self.range(&timestamps).ok_or_else(|| {
+            ZaiSimError::Parse(format!(
+                "No prices between {:?} and {:?}",
+                self.from, self.to
+            ))
+        })?;
+        Ok(rows[range].to_vec())
+    }
+}
+
+/// One hourly OHLCV candle (CryptoCompare format).
+#[derive(Debug, Clone, PartialEq)]
+pub struct HourlyCandle {
+    pub timestamp: u64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    /// Volume in ZEC
+    pub volume_from: f64,
+    /// Volume in the quote currency (USD)
+    pub volume_to: f64,
+}
+
+/// Load full hourly candles from a CryptoCompare CSV file.
+///
+/// Same format as `load_hourly_prices`, but keeps OHLC and both volume
+/// columns and reports bad rows as errors instead of panicking.
+pub fn load_hourly_candles(csv_path: &str) -> Result<Vec<HourlyCandle>, ZaiSimError> {
+    let mut reader = csv::Reader::from_path(csv_path)?;
+    let mut candles = Vec::new();
+    for result in reader.records() {
+        let record = result?;
+        let field = |i: usize| -> Result<f64, ZaiSimError> {
+            record
+                .get(i)
+                .ok_or_else(|| ZaiSimError::Parse(format!("Missing column {} in {}", i, csv_path)))?
+                .parse::<f64>()
+                .map_err(|e| {
+                    ZaiSimError::Parse(format!("Bad value in column {} of {}: {}", i, csv_path, e))
+                })
+        };
+        candles.push(HourlyCandle {
+            timestamp: field(0)? as u64,
+            open: field(2)?,
+            high: field(3)?,
+            low: field(4)?,
+            close: field(5)?,
+            volume_from: field(6)?,
+            volume_to: field(7)?,
+        });
+    }
+    if candles.is_empty() {
+        return Err(ZaiSimError::Parse(format!(
+            "CSV {} contained no data rows",
+            csv_path
+        )));
+    }
+    Ok(candles)
+}
+
 /// Linearly interpolate hourly prices to per-block prices.
 ///
 /// For N hourly prices, produces (N-1) * blocks_per_hour block prices.
@@ -60,17 +353,14 @@ pub fn interpolate_to_blocks(hourly_prices: &[f64], blocks_per_hour: usize) -> V
 
 /// Create a ScenarioConfig for historical replay.
 ///
-/// Mirrors `config_5m_200cr` but adjusts AMM reserves so the starting
-/// spot price matches the first hourly close price from the CSV.
+/// Mirrors `config_5m_200cr` but sets the starting AMM price to the first
+/// hourly close price from the CSV.
 ///
-/// - `amm_initial_zec = 100,000`
-/// - `amm_initial_zai = 100,000 * first_price`
+/// - AMM price `first_price`, 100,000 ZEC deep (`100,000 * first_price` ZAI)
 /// - `initial_redemption_price = first_price`
 /// - 200% collateral ratio, 240-block TWAP, Tick controller
 pub fn config_for_historical(first_price: f64) -> ScenarioConfig {
-    let mut config = ScenarioConfig::default();
-    config.amm_initial_zec = 100_000.0;
-    config.amm_initial_zai = 100_000.0 * first_price;
+    let mut config = ScenarioConfig::default().with_amm(first_price, 100_000.0 * first_price);
     config.cdp_config.min_ratio = 2.0;
     config.cdp_config.twap_window = 240;
     config.controller_config = ControllerConfig::default_tick();
@@ -0,0 +1,444 @@
+//! Accounting invariants checked after every block.
+//!
+//! Accounting bugs in the AMM, vault registry or liquidation engine tend to
+//! surface only as odd-looking metrics many blocks later. With
+//! `ScenarioConfig::invariants` set, an `InvariantChecker` runs after every
+//! `step` and checks that:
+//!
+//! - the AMM's `k` equals the product of its reserves, and `sqrt(k)` per LP
+//!   share never falls within a block (swap fees and penalties routed to
+//!   LPs raise it; adding and removing liquidity keep it)
+//! - LP share holdings sum to `total_lp_shares`
+//! - the registry's `total_debt` equals the sum of vault debts, with fees
+//!   due
+//! - no AMM reserve, vault, agent wallet, keeper or treasury balance is
+//!   negative
+//! - each liquidation pays out (debt repaid, penalty, owner surplus) no
+//!   more ZAI than it raised, and each redemption pays out exactly the
+//!   collateral it drew and cancels exactly the debt it redeemed
+//!
+//! Agent wallets are an open system: arbers replenish capital, miners earn
+//! block rewards, and bridges and external markets move funds in and out.
+//! ZEC and ZAI conservation is therefore checked where value moves between
+//! protocol components, not as a whole-economy total.
+//!
+//! Each violation carries the block's liquidations, redemptions and breaker
+//! actions as the events that may have triggered it. By default the checker
+//! panics on the first violation in debug builds and records violations in
+//! release builds.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::amm::Amm;
+use crate::scenario::Scenario;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InvariantConfig {
+    /// Relative tolerance for floating-point drift, scaled by the larger of
+    /// the compared values (at least 1)
+    pub tolerance: f64,
+    /// Panic with the diagnostic on the first violation instead of
+    /// recording it
+    pub panic_on_violation: bool,
+}
+
+impl Default for InvariantConfig {
+    fn default() -> Self {
+        InvariantConfig {
+            tolerance: 1e-6,
+            panic_on_violation: cfg!(debug_assertions),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Invariant {
+    /// `k` equals the product of the AMM reserves
+    AmmK,
+    /// `sqrt(k)` per LP share does not fall within a block
+    AmmKPerShare,
+    /// LP share holdings sum to `total_lp_shares`
+    LpShares,
+    /// The registry's `total_debt` equals the sum of vault debts, with
+    /// fees due
+    TotalDebt,
+    /// No balance is negative
+    NonNegative,
+    /// A liquidation pays out no more ZAI than it raised
+    LiquidationSettlement,
+    /// A redemption pays out the collateral it drew and cancels the debt
+    /// it redeemed
+    RedemptionSettlement,
+}
+
+impl Invariant {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Invariant::AmmK => "amm_k",
+            Invariant::AmmKPerShare => "amm_k_per_share",
+            Invariant::LpShares => "lp_shares",
+            Invariant::TotalDebt => "total_debt",
+            Invariant::NonNegative => "non_negative",
+            Invariant::LiquidationSettlement => "liquidation_settlement",
+            Invariant::RedemptionSettlement => "redemption_settlement",
+        }
+    }
+}
+
+/// One broken invariant, with what was off and the block's events.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InvariantViolation {
+    pub block: u64,
+    pub invariant: Invariant,
+    /// What was off, with the values involved
+    pub detail: String,
+    /// Liquidations, redemptions and breaker actions in the block
+    pub events: Vec<String>,
+}
+
+impl fmt::Display for InvariantViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invariant {} violated at block {}: {}",
+            self.invariant.name(),
+            self.block,
+            self.detail
+        )?;
+        if self.events.is_empty() {
+            write!(f, "\n  no liquidations, redemptions or breaker actions")?;
+        }
+        for event in &self.events {
+            write!(f, "\n  {}", event)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InvariantChecker {
+    pub config: InvariantConfig,
+    /// Violations found so far, oldest first
+    pub violations: Vec<InvariantViolation>,
+    /// `sqrt(k)` per LP share when the current block started
+    #[serde(skip)]
+    start_k_per_share: Option<f64>,
+}
+
+impl InvariantChecker {
+    pub fn new(config: InvariantConfig) -> Self {
+        InvariantChecker {
+            config,
+            violations: Vec::new(),
+            start_k_per_share: None,
+        }
+    }
+
+    /// Note the AMM state the end-of-block checks compare against.
+    pub fn begin_block(&mut self, amm: &Amm) {
+        self.start_k_per_share = k_per_share(amm);
+    }
+
+    /// Check every invariant after `block` and return how many were
+    /// broken. Violations are added to `violations`, or with
+    /// `panic_on_violation` the first one panics with its diagnostic.
+    pub fn check(&mut self, scenario: &Scenario, block: u64) -> usize {
+        let mut checks = Checks {
+            tolerance: self.config.tolerance,
+            found: Vec::new(),
+        };
+        checks.amm(&scenario.amm, self.start_k_per_share.take());
+        checks.registry(scenario);
+        checks.balances(scenario);
+        checks.settlements(scenario, block);
+        if checks.found.is_empty() {
+            return 0;
+        }
+
+        let events = block_events(scenario, block);
+        let found = checks.found.len();
+        for (invariant, detail) in checks.found {
+            let violation = InvariantViolation {
+                block,
+                invariant,
+                detail,
+                events: events.clone(),
+            };
+            if self.config.panic_on_violation {
+                panic!("{}", violation);
+            }
+            self.violations.push(violation);
+        }
+        found
+    }
+}
+
+/// `sqrt(k)` per LP share, while there are shares outstanding.
+fn k_per_share(amm: &Amm) -> Option<f64> {
+    (amm.total_lp_shares > 1e-9).then(|| amm.k.sqrt() / amm.total_lp_shares)
+}
+
+/// Broken invariants found so far in one block.
+struct Checks {
+    tolerance: f64,
+    found: Vec<(Invariant, String)>,
+}
+
+impl Checks {
+    fn equal(&mut self, invariant: Invariant, what: &str, expected: f64, actual: f64) {
+        let scale = expected.abs().max(actual.abs()).max(1.0);
+        let off = (actual - expected).abs();
+        if off > self.tolerance * scale || off.is_nan() {
+            self.found.push((
+                invariant,
+                format!(
+                    "{} is {} but should be {} (off by {:e})",
+                    what,
+                    actual,
+                    expected,
+                    actual - expected
+                ),
+            ));
+        }
+    }
+
+    fn non_negative(&mut self, what: &str, value: f64) {
+        if value < -self.tolerance || value.is_nan() {
+            self.found
+                .push((Invariant::NonNegative, format!("{} is {}", what, value)));
+        }
+    }
+
+    fn amm(&mut self, amm: &Amm, start_k_per_share: Option<f64>) {
+        self.non_negative("AMM ZEC reserve", amm.reserve_zec);
+        self.non_negative("AMM ZAI reserve", amm.reserve_zai);
+        self.non_negative("AMM total LP shares", amm.total_lp_shares);
+        self.equal(
+            Invariant::AmmK,
+            "AMM k",
+            amm.reserve_zec * amm.reserve_zai,
+            amm.k,
+        );
+
+        if let (Some(before), Some(after)) = (start_k_per_share, k_per_share(amm)) {
+            if after < before * (1.0 - self.tolerance) {
+                self.found.push((
+                    Invariant::AmmKPerShare,
+                    format!(
+                        "sqrt(k) per LP share fell from {} to {} ({:e})",
+                        before,
+                        after,
+                        after / before - 1.0
+                    ),
+                ));
+            }
+        }
+
+        let mut owners: Vec<_> = amm.lp_shares.iter().collect();
+        owners.sort_by(|a, b| a.0.cmp(b.0));
+        for (owner, &shares) in &owners {
+            self.non_negative(&format!("LP shares of {}", owner), shares);
+        }
+        let held: f64 = owners.iter().map(|(_, &shares)| shares).sum();
+        self.equal(
+            Invariant::LpShares,
+            "AMM total_lp_shares",
+            held,
+            amm.total_lp_shares,
+        );
+    }
+
+    fn registry(&mut self, scenario: &Scenario) {
+        let registry = &scenario.registry;
+        let mut ids: Vec<u64> = registry.vaults.keys().copied().collect();
+        ids.sort_unstable();
+        let mut debt = 0.0;
+        for id in ids {
+            let vault = &registry.vaults[&id];
+            self.non_negative(
+                &format!("vault {} ({}) collateral", id, vault.owner),
+                vault.collateral_zec,
+            );
+            self.non_negative(
+                &format!("vault {} ({}) debt", id, vault.owner),
+                vault.debt_zai,
+            );
+            debt += registry.debt_due(vault);
+        }
+        self.equal(
+            Invariant::TotalDebt,
+            "registry total_debt",
+            debt,
+            registry.total_debt,
+        );
+    }
+
+    fn balances(&mut self, scenario: &Scenario) {
+        let mut wallet = |who: &str, i: usize, zec: f64, zai: f64| {
+            self.non_negative(&format!("{} {} ZEC", who, i), zec);
+            self.non_negative(&format!("{} {} ZAI", who, i), zai);
+        };
+        for (i, a) in scenario.arbers.iter().enumerate() {
+            wallet("arber", i, a.zec_balance, a.zai_balance);
+        }
+        for (i, a) in scenario.demand_agents.iter().enumerate() {
+            wallet("demand agent", i, a.zec_balance, a.zai_balance);
+        }
+        for (i, a) in scenario.miners.iter().enumerate() {
+            wallet("miner", i, a.zec_balance, a.zai_balance);
+        }
+        for (i, a) in scenario.lp_agents.iter().enumerate() {
+            wallet("LP", i, a.zec_balance, a.zai_balance);
+        }
+        for (i, a) in scenario.attackers.iter().enumerate() {
+            wallet("attacker", i, a.zec_balance, a.zai_balance);
+        }
+        for (i, a) in scenario.redeemers.iter().enumerate() {
+            wallet("redeemer", i, a.zec_balance, a.zai_balance);
+        }
+        for (i, a) in scenario.basis_traders.iter().enumerate() {
+            wallet("basis trader", i, a.zec_balance, a.zai_balance);
+        }
+        for (i, a) in scenario.savers.iter().enumerate() {
+            wallet("saver", i, a.zec_balance, a.zai_balance);
+        }
+        for (i, a) in scenario.noise_traders.iter().enumerate() {
+            wallet("noise trader", i, a.zec_balance, a.zai_balance);
+        }
+        for (i, a) in scenario.basis_arbs.iter().enumerate() {
+            wallet("basis arbitrageur", i, a.zec_balance, a.zai_balance);
+        }
+        for (i, h) in scenario.cdp_holders.iter().enumerate() {
+            self.non_negative(&format!("CDP holder {} ZEC reserve", i), h.reserve_zec);
+            self.non_negative(&format!("CDP holder {} ZAI", i), h.zai_balance);
+        }
+        for (i, p) in scenario.vault_portfolios.iter().enumerate() {
+            self.non_negative(&format!("vault portfolio {} ZEC reserve", i), p.reserve_zec);
+        }
+
+        let engine = &scenario.liquidation_engine;
+        self.non_negative("keeper ZAI", engine.keeper_zai);
+        self.non_negative("keeper ZEC", engine.keeper_zec);
+        self.non_negative("treasury balance", scenario.treasury.balance_zai);
+        self.non_negative("insurance fund", scenario.treasury.insurance_fund_zai);
+    }
+
+    /// Liquidations and redemptions recorded in `block`.
+    fn settlements(&mut self, scenario: &Scenario, block: u64) {
+        let engine = &scenario.liquidation_engine;
+        for r in engine.history.iter().rev().take_while(|r| r.block >= block) {
+            let what = format!("vault {} liquidation", r.vault_id);
+            let raised = r.zai_from_amm + r.zai_from_keepers;
+            self.non_negative(&format!("{} collateral seized", what), r.collateral_seized);
+            self.non_negative(&format!("{} ZAI raised", what), raised);
+            self.non_negative(&format!("{} penalty", what), r.penalty_amount);
+            self.non_negative(&format!("{} owner surplus", what), r.surplus_to_owner);
+            self.non_negative(&format!("{} collateral returned", what), r.collateral_returned);
+            self.non_negative(&format!("{} bad debt", what), r.bad_debt);
+            self.non_negative(
+                &format!("{} penalty less keeper reward", what),
+                r.penalty_amount - r.keeper_reward,
+            );
+            let paid =
+                (r.debt_to_cover - r.bad_debt).max(0.0) + r.penalty_amount + r.surplus_to_owner;
+            if paid > raised + self.tolerance * raised.max(1.0) {
+                self.found.push((
+                    Invariant::LiquidationSettlement,
+                    format!(
+                        "{} paid out {} ZAI (debt {}, penalty {}, surplus {}) but raised {}",
+                        what,
+                        paid,
+                        r.debt_to_cover - r.bad_debt,
+                        r.penalty_amount,
+                        r.surplus_to_owner,
+                        raised
+                    ),
+                ));
+            }
+        }
+
+        for r in engine
+            .redemption_history
+            .iter()
+            .rev()
+            .take_while(|r| r.block >= block)
+        {
+            let what = format!("redemption by {}", r.redeemer);
+            let debt: f64 = r.vaults_touched.iter().map(|v| v.1).sum();
+            let collateral: f64 = r.vaults_touched.iter().map(|v| v.2).sum();
+            self.non_negative(&format!("{} ZEC received", what), r.zec_received);
+            self.non_negative(&format!("{} fee", what), r.fee_zec);
+            self.equal(
+                Invariant::RedemptionSettlement,
+                &format!("{} ZAI redeemed", what),
+                debt,
+                r.zai_redeemed,
+            );
+            self.equal(
+                Invariant::RedemptionSettlement,
+                &format!("{} ZEC paid out with fee", what),
+                collateral,
+                r.zec_received + r.fee_zec,
+            );
+        }
+    }
+}
+
+/// The block's liquidations, redemptions and breaker actions, for
+/// diagnostics.
+fn block_events(scenario: &Scenario, block: u64) -> Vec<String> {
+    let engine = &scenario.liquidation_engine;
+    let mut events: Vec<String> = engine
+        .history
+        .iter()
+        .rev()
+        .take_while(|r| r.block >= block)
+        .map(|r| {
+            format!(
+                "liquidation of vault {} ({}, {:?}): seized {} ZEC, raised {} ZAI, \
+                 covered {} ZAI debt, penalty {}, surplus {}, bad debt {}",
+                r.vault_id,
+                r.owner,
+                r.mode,
+                r.collateral_seized,
+                r.zai_from_amm + r.zai_from_keepers,
+                r.debt_to_cover,
+                r.penalty_amount,
+                r.surplus_to_owner,
+                r.bad_debt
+            )
+        })
+        .collect();
+    events.reverse();
+
+    let mut redemptions: Vec<String> = engine
+        .redemption_history
+        .iter()
+        .rev()
+        .take_while(|r| r.block >= block)
+        .map(|r| {
+            format!(
+                "redemption by {}: {} ZAI for {} ZEC (fee {}) from {} vaults, {} closed",
+                r.redeemer,
+                r.zai_redeemed,
+                r.zec_received,
+                r.fee_zec,
+                r.vaults_touched.len(),
+                r.vaults_closed.len()
+            )
+        })
+        .collect();
+    redemptions.reverse();
+    events.extend(redemptions);
+
+    events.extend(
+        scenario
+            .metrics
+            .breaker_events_at(block)
+            .iter()
+            .map(|(_, a)| format!("breaker action: {:?}", a)),
+    );
+    events
+}
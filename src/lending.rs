@@ -0,0 +1,240 @@
+//! External ZEC/ZAI lending market (side venue).
+//!
+//! A Compound/Aave-style money market with one pool per asset. Borrow rates
+//! follow a kinked utilization curve, so inventory gets expensive as a pool
+//! drains and runs out entirely at `max_utilization`. Arbitrageurs and
+//! attackers can source capital here instead of relying on fixed starting
+//! balances.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::cdp::apr_to_per_block;
+use crate::error::ZaiSimError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum LendingAsset {
+    Zec,
+    Zai,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LendingPoolConfig {
+    /// Lender deposits available at start
+    pub initial_supply: f64,
+    /// Annual borrow rate at zero utilization
+    pub base_rate: f64,
+    /// Rate added between 0 and `optimal_utilization`
+    pub slope_low: f64,
+    /// Rate added between `optimal_utilization` and 100%
+    pub slope_high: f64,
+    /// Kink of the rate curve (e.g., 0.8 = 80%)
+    pub optimal_utilization: f64,
+    /// Borrows beyond this utilization are rejected
+    pub max_utilization: f64,
+}
+
+impl LendingPoolConfig {
+    fn with_supply(initial_supply: f64) -> Self {
+        LendingPoolConfig {
+            initial_supply,
+            base_rate: 0.0,
+            slope_low: 0.04,
+            slope_high: 0.75,
+            optimal_utilization: 0.8,
+            max_utilization: 0.95,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LendingMarketConfig {
+    pub zec: LendingPoolConfig,
+    pub zai: LendingPoolConfig,
+}
+
+impl Default for LendingMarketConfig {
+    fn default() -> Self {
+        LendingMarketConfig {
+            zec: LendingPoolConfig::with_supply(20_000.0),
+            zai: LendingPoolConfig::with_supply(1_000_000.0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LendingPool {
+    pub config: LendingPoolConfig,
+    pub total_supply: f64,
+    pub total_borrowed: f64,
+    /// Cumulative borrow index; debt = scaled balance * index
+    pub borrow_index: f64,
+    pub total_interest_accrued: f64,
+    /// Scaled debt per borrower
+    positions: HashMap<String, f64>,
+}
+
+impl LendingPool {
+    pub fn new(config: LendingPoolConfig) -> Self {
+        let supply = config.initial_supply;
+        LendingPool {
+            config,
+            total_supply: supply,
+            total_borrowed: 0.0,
+            borrow_index: 1.0,
+            total_interest_accrued: 0.0,
+            positions: HashMap::new(),
+        }
+    }
+
+    pub fn utilization(&self) -> f64 {
+        if self.total_supply <= 0.0 {
+            return 0.0;
+        }
+        self.total_borrowed / self.total_supply
+    }
+
+    /// Annual borrow rate from the kinked utilization curve.
+    pub fn borrow_rate(&self) -> f64 {
+        let c = &self.config;
+        let u = self.utilization();
+        if u <= c.optimal_utilization {
+            c.base_rate + c.slope_low * u / c.optimal_utilization
+        } else {
+            let excess = (u - c.optimal_utilization) / (1.0 - c.optimal_utilization);
+            c.base_rate + c.slope_low + c.slope_high * excess
+        }
+    }
+
+    /// Amount that can still be borrowed before hitting `max_utilization`.
+    pub fn available(&self) -> f64 {
+        (self.total_supply * self.config.max_utilization - self.total_borrowed).max(0.0)
+    }
+
+    fn accrue(&mut self, blocks_elapsed: u64) {
+        if blocks_elapsed == 0 {
+            return;
+        }
+        let rate_per_block = apr_to_per_block(self.borrow_rate());
+        let multiplier = (1.0 + rate_per_block).powi(blocks_elapsed as i32);
+        let interest = self.total_borrowed * (multiplier - 1.0);
+
+        self.borrow_index *= multiplier;
+        self.total_borrowed += interest;
+        // Interest is paid through to lenders
+        self.total_supply += interest;
+        self.total_interest_accrued += interest;
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LendingMarket {
+    pub zec: LendingPool,
+    pub zai: LendingPool,
+    last_accrual_block: u64,
+}
+
+impl LendingMarket {
+    pub fn new(config: LendingMarketConfig) -> Self {
+        LendingMarket {
+            zec: LendingPool::new(config.zec),
+            zai: LendingPool::new(config.zai),
+            last_accrual_block: 0,
+        }
+    }
+
+    pub fn pool(&self, asset: LendingAsset) -> &LendingPool {
+        match asset {
+            LendingAsset::Zec => &self.zec,
+            LendingAsset::Zai => &self.zai,
+        }
+    }
+
+    fn pool_mut(&mut self, asset: LendingAsset) -> &mut LendingPool {
+        match asset {
+            LendingAsset::Zec => &mut self.zec,
+            LendingAsset::Zai => &mut self.zai,
+        }
+    }
+
+    /// Accrue interest on both pools up to `block`.
+    pub fn accrue(&mut self, block: u64) {
+        if block <= self.last_accrual_block {
+            return;
+        }
+        let elapsed = block - self.last_accrual_block;
+        self.zec.accrue(elapsed);
+        self.zai.accrue(elapsed);
+        self.last_accrual_block = block;
+    }
+
+    /// Current debt (principal + interest) of a borrower.
+    pub fn debt_of(&self, borrower: &str, asset: LendingAsset) -> f64 {
+        let pool = self.pool(asset);
+        pool.positions.get(borrower).copied().unwrap_or(0.0) * pool.borrow_index
+    }
+
+    /// Borrow up to `amount`, limited by pool liquidity. Returns the amount borrowed.
+    pub fn borrow(
+        &mut self,
+        borrower: &str,
+        asset: LendingAsset,
+        amount: f64,
+        block: u64,
+    ) -> Result<f64, ZaiSimError> {
+        if amount <= 0.0 {
+            return Err(ZaiSimError::InvalidInput(
+                "Borrow amount must be positive".to_string(),
+            ));
+        }
+        self.accrue(block);
+
+        let pool = self.pool_mut(asset);
+        let borrowed = amount.min(pool.available());
+        if borrowed <= 0.0 {
+            return Err(ZaiSimError::InsufficientLiquidity(format!(
+                "{:?} pool has nothing to lend",
+                asset
+            )));
+        }
+        pool.total_borrowed += borrowed;
+        let scaled = borrowed / pool.borrow_index;
+        *pool.positions.entry(borrower.to_string()).or_insert(0.0) += scaled;
+        Ok(borrowed)
+    }
+
+    /// Repay up to `amount` of a borrower's debt. Returns the amount repaid.
+    pub fn repay(
+        &mut self,
+        borrower: &str,
+        asset: LendingAsset,
+        amount: f64,
+        block: u64,
+    ) -> Result<f64, ZaiSimError> {
+        if amount <= 0.0 {
+            return Err(ZaiSimError::InvalidInput(
+                "Repay amount must be positive".to_string(),
+            ));
+        }
+        self.accrue(block);
+
+        let debt = self.debt_of(borrower, asset);
+        if debt <= 0.0 {
+            return Err(ZaiSimError::InvalidInput(format!(
+                "{} has no {:?} debt",
+                borrower, asset
+            )));
+        }
+        let repaid = amount.min(debt);
+
+        let pool = self.pool_mut(asset);
+        pool.total_borrowed = (pool.total_borrowed - repaid).max(0.0);
+        if repaid >= debt {
+            pool.positions.remove(borrower);
+        } else if let Some(scaled) = pool.positions.get_mut(borrower) {
+            *scaled -= repaid / pool.borrow_index;
+        }
+        Ok(repaid)
+    }
+}
@@ -1,13 +1,68 @@
+pub mod adoption;
+pub mod agent_metrics;
 pub mod agents;
 pub mod amm;
+pub mod attack_search;
+pub mod block_space;
+pub mod bridge;
+pub mod calibration;
 pub mod cdp;
+pub mod ceiling_policy;
 pub mod circuit_breaker;
 pub mod controller;
+#[cfg(feature = "net")]
 pub mod data_fetcher;
+pub mod depth;
+pub mod determinism;
+pub mod emission;
+pub mod error;
+pub mod expectations;
+pub mod external_market;
+pub mod faults;
+pub mod fixed;
+pub mod flash_attack;
+pub mod funding;
+#[cfg(feature = "fuzz")]
+pub mod fuzz;
+pub mod gas;
+pub mod governance;
 pub mod historical;
+pub mod invariants;
+pub mod lending;
 pub mod liquidation;
+#[cfg(feature = "net")]
+pub mod live;
+pub mod lp_attribution;
+pub mod metrics_sink;
+pub mod oracle;
+pub mod order_book;
 pub mod output;
+pub mod pdf;
+pub mod perp;
+pub mod persona;
+pub mod pool;
+pub mod presets;
+pub mod price_matrix;
+pub mod progress;
+pub mod protocol_liquidity;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod regress;
+pub mod reorg;
 pub mod report;
+pub mod routing;
+pub mod savings;
+pub mod shielded;
 pub mod scenario;
+pub mod scenario_builder;
+pub mod scenario_file;
 pub mod scenarios;
+pub mod sensitivity;
+pub mod serve;
+pub mod snapshot;
 pub mod sweep;
+pub mod treasury;
+pub mod vault_churn;
+pub mod volume;
+#[cfg(feature = "wasm")]
+pub mod wasm;
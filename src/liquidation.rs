@@ -1,7 +1,14 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
 use crate::amm::Amm;
-use crate::cdp::VaultRegistry;
+use crate::cdp::{DustPolicy, VaultRegistry};
+use crate::error::ZaiSimError;
+use crate::oracle::Oracle;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LiquidationConfig {
     /// Maximum liquidations allowed per block
     pub max_liquidations_per_block: u32,
@@ -11,12 +18,35 @@ pub struct LiquidationConfig {
     pub self_liquidation_penalty_pct: f64,
     /// Fraction of non-keeper penalty routed to LPs via AMM reserve injection (0.0 = none)
     pub liquidation_penalty_to_lps_pct: f64,
+    /// Explicit penalty split across sinks. `None` derives it from
+    /// `keeper_reward_pct` and `liquidation_penalty_to_lps_pct`.
+    pub penalty_routing: Option<PenaltyRouting>,
     /// Enable graduated (partial) liquidation for warning-zone vaults
     pub graduated_liquidation: bool,
     /// Fraction of vault collateral seized per block during graduated liquidation
     pub graduated_pct_per_block: f64,
     /// CR floor for graduated liquidation — vaults below this get full liquidation
     pub graduated_cr_floor: f64,
+    /// Fee on redemptions, as a fraction of the ZEC drawn from vaults
+    pub redemption_fee_pct: f64,
+    /// Blocks after a vault first becomes undercollateralized during which only
+    /// the owner may act (top-up, repay, self-liquidate). 0 = no grace period.
+    pub grace_period_blocks: u64,
+    /// Aave/Compound close factor: repay at most this fraction of a vault's
+    /// debt per liquidation and leave the vault open. `None` liquidates
+    /// whole vaults.
+    pub close_factor: Option<f64>,
+    /// Order in which eligible vaults are liquidated, which decides who is
+    /// reached before the per-block velocity limit
+    pub ordering: LiquidationOrdering,
+    /// Keepers buy seized collateral off-AMM at a discount to the
+    /// eligibility price, paying with their own ZAI. `None` sells
+    /// collateral on the AMM.
+    pub keeper_liquidity: Option<KeeperLiquidityConfig>,
+    /// How a partial liquidation that would leave debt under the floor
+    /// settles it
+    #[serde(default)]
+    pub dust_policy: DustPolicy,
 }
 
 impl Default for LiquidationConfig {
@@ -26,14 +56,235 @@ impl Default for LiquidationConfig {
             keeper_reward_pct: 0.50,
             self_liquidation_penalty_pct: 0.0,
             liquidation_penalty_to_lps_pct: 0.0,
+            penalty_routing: None,
             graduated_liquidation: false,
             graduated_pct_per_block: 0.10,
             graduated_cr_floor: 1.5,
+            redemption_fee_pct: 0.005,
+            grace_period_blocks: 0,
+            close_factor: None,
+            ordering: LiquidationOrdering::default(),
+            keeper_liquidity: None,
+            dust_policy: DustPolicy::default(),
+        }
+    }
+}
+
+/// Keepers standing ready to buy liquidated collateral directly, so that
+/// liquidations never touch the AMM.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KeeperLiquidityConfig {
+    /// Discount to the eligibility price at which keepers buy collateral
+    pub discount: f64,
+    /// ZAI keepers hold at the start
+    pub initial_zai: f64,
+    /// ZAI keepers receive each block, e.g. from selling collateral elsewhere
+    pub replenish_zai_per_block: f64,
+    /// Sell on the AMM when keepers cannot afford a lot; otherwise the
+    /// vault waits for keeper funds
+    pub amm_fallback: bool,
+}
+
+impl Default for KeeperLiquidityConfig {
+    fn default() -> Self {
+        KeeperLiquidityConfig {
+            discount: 0.05,
+            initial_zai: 1_000_000.0,
+            replenish_zai_per_block: 0.0,
+            amm_fallback: false,
+        }
+    }
+}
+
+impl LiquidationConfig {
+    /// The penalty split in effect: `penalty_routing` if set, otherwise the
+    /// legacy keeper/LP knobs with the remainder to the treasury.
+    pub fn routing(&self) -> PenaltyRouting {
+        self.penalty_routing.clone().unwrap_or_else(|| {
+            PenaltyRouting::from_legacy(self.keeper_reward_pct, self.liquidation_penalty_to_lps_pct)
+        })
+    }
+}
+
+/// A destination for collected liquidation penalties.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PenaltySink {
+    Keeper,
+    Lps,
+    InsuranceFund,
+    Treasury,
+    Burn,
+}
+
+impl PenaltySink {
+    pub fn all() -> [PenaltySink; 5] {
+        [
+            Self::Keeper,
+            Self::Lps,
+            Self::InsuranceFund,
+            Self::Treasury,
+            Self::Burn,
+        ]
+    }
+}
+
+impl FromStr for PenaltySink {
+    type Err = ZaiSimError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "keeper" => Ok(Self::Keeper),
+            "lps" => Ok(Self::Lps),
+            "insurance" | "insurance_fund" => Ok(Self::InsuranceFund),
+            "treasury" => Ok(Self::Treasury),
+            "burn" => Ok(Self::Burn),
+            _ => Err(ZaiSimError::Parse(format!(
+                "Unknown penalty sink: {} (use keeper, lps, insurance, treasury or burn)",
+                s
+            ))),
+        }
+    }
+}
+
+/// How a collected liquidation penalty is split. Shares are fractions of the
+/// penalty and must sum to one.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PenaltyRouting {
+    /// Paid to the keeper; spread pro rata over the other sinks when no
+    /// keeper is involved (transparent, self and system liquidations)
+    pub keeper: f64,
+    /// Injected into AMM ZAI reserves, accruing to LPs
+    pub lps: f64,
+    /// Treasury insurance fund, drawn on before the surplus to cover bad debt
+    pub insurance_fund: f64,
+    /// Treasury surplus buffer
+    pub treasury: f64,
+    /// Destroyed
+    pub burn: f64,
+}
+
+impl PenaltyRouting {
+    pub fn new(
+        keeper: f64,
+        lps: f64,
+        insurance_fund: f64,
+        treasury: f64,
+        burn: f64,
+    ) -> Result<Self, ZaiSimError> {
+        let routing = PenaltyRouting {
+            keeper,
+            lps,
+            insurance_fund,
+            treasury,
+            burn,
+        };
+        routing.validate()?;
+        Ok(routing)
+    }
+
+    /// The split implied by the single-knob config: keeper first, then a
+    /// fraction of the rest to LPs and the remainder to the treasury.
+    pub fn from_legacy(keeper_reward_pct: f64, penalty_to_lps_pct: f64) -> Self {
+        let rest = 1.0 - keeper_reward_pct;
+        PenaltyRouting {
+            keeper: keeper_reward_pct,
+            lps: rest * penalty_to_lps_pct,
+            insurance_fund: 0.0,
+            treasury: rest * (1.0 - penalty_to_lps_pct),
+            burn: 0.0,
+        }
+    }
+
+    /// Shares must be non-negative and sum to one.
+    pub fn validate(&self) -> Result<(), ZaiSimError> {
+        for sink in PenaltySink::all() {
+            let share = self.share(sink);
+            if !(0.0..=1.0).contains(&share) {
+                return Err(ZaiSimError::Config(format!(
+                    "Penalty share for {:?} out of range: {}",
+                    sink, share
+                )));
+            }
+        }
+        let total: f64 = PenaltySink::all().iter().map(|&s| self.share(s)).sum();
+        if (total - 1.0).abs() > 1e-9 {
+            return Err(ZaiSimError::Config(format!(
+                "Penalty shares sum to {}, not 1",
+                total
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn share(&self, sink: PenaltySink) -> f64 {
+        match sink {
+            PenaltySink::Keeper => self.keeper,
+            PenaltySink::Lps => self.lps,
+            PenaltySink::InsuranceFund => self.insurance_fund,
+            PenaltySink::Treasury => self.treasury,
+            PenaltySink::Burn => self.burn,
+        }
+    }
+
+    fn share_mut(&mut self, sink: PenaltySink) -> &mut f64 {
+        match sink {
+            PenaltySink::Keeper => &mut self.keeper,
+            PenaltySink::Lps => &mut self.lps,
+            PenaltySink::InsuranceFund => &mut self.insurance_fund,
+            PenaltySink::Treasury => &mut self.treasury,
+            PenaltySink::Burn => &mut self.burn,
+        }
+    }
+
+    /// Set one sink's share and rescale the others proportionally so the
+    /// split still sums to one. Used to sweep a single share coherently.
+    pub fn with_share(mut self, sink: PenaltySink, share: f64) -> Result<Self, ZaiSimError> {
+        if !(0.0..=1.0).contains(&share) {
+            return Err(ZaiSimError::Config(format!(
+                "Penalty share for {:?} out of range: {}",
+                sink, share
+            )));
+        }
+        let others: f64 = PenaltySink::all()
+            .iter()
+            .filter(|&&s| s != sink)
+            .map(|&s| self.share(s))
+            .sum();
+        for other in PenaltySink::all() {
+            if other == sink {
+                continue;
+            }
+            let v = self.share_mut(other);
+            *v = if others > 0.0 {
+                *v / others * (1.0 - share)
+            } else if other == PenaltySink::Treasury {
+                1.0 - share
+            } else {
+                0.0
+            };
         }
+        *self.share_mut(sink) = share;
+        self.validate()?;
+        Ok(self)
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+/// Priority among vaults eligible for liquidation in the same pass.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum LiquidationOrdering {
+    /// Ascending vault ID
+    #[default]
+    VaultId,
+    /// Lowest collateral ratio first
+    LowestCr,
+    /// Largest debt first
+    LargestDebt,
+    /// Least AMM slippage per unit of debt first, i.e. the vaults cheapest
+    /// to unwind against current depth
+    SmallestSlippage,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum LiquidationMode {
     Transparent,
     SelfLiquidation,
@@ -47,9 +298,32 @@ pub enum LiquidationMode {
     OracleLiquidation,
     /// Graduated (partial) liquidation: seize a fraction per block to deleverage
     GraduatedPartial,
+    /// Close-factor liquidation: part of the debt repaid, vault left open
+    CloseFactor,
+    /// Keepers buy the collateral off-AMM at a discount to the oracle price
+    KeeperPurchase,
+    /// Breaker-forced partial unwind of a vault not yet liquidatable
+    AutoDeleverage,
+}
+
+impl LiquidationMode {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Transparent => "transparent",
+            Self::SelfLiquidation => "self_liquidation",
+            Self::ChallengeResponse { .. } => "challenge_response",
+            Self::AmmLiquidation => "amm_liquidation",
+            Self::ZombieDetection => "zombie_detection",
+            Self::OracleLiquidation => "oracle_liquidation",
+            Self::GraduatedPartial => "graduated_partial",
+            Self::CloseFactor => "close_factor",
+            Self::KeeperPurchase => "keeper_purchase",
+            Self::AutoDeleverage => "auto_deleverage",
+        }
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LiquidationResult {
     pub vault_id: u64,
     pub owner: String,
@@ -57,32 +331,100 @@ pub struct LiquidationResult {
     pub collateral_seized: f64,
     pub debt_to_cover: f64,
     pub zai_from_amm: f64,
+    /// ZAI paid by keepers buying the collateral directly
+    #[serde(default)]
+    pub zai_from_keepers: f64,
     pub penalty_amount: f64,
     pub keeper_reward: f64,
     pub surplus_to_owner: f64,
+    /// ZEC left in a vault the liquidation closed, returned to the owner
+    #[serde(default)]
+    pub collateral_returned: f64,
     pub bad_debt: f64,
     pub block: u64,
 }
 
-#[derive(Debug)]
+/// Outcome of redeeming ZAI against the lowest-CR vaults.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedemptionResult {
+    pub redeemer: String,
+    pub zai_redeemed: f64,
+    /// ZEC paid to the redeemer, net of the redemption fee
+    pub zec_received: f64,
+    pub fee_zec: f64,
+    /// (vault_id, debt_reduced, collateral_drawn) per vault touched
+    pub vaults_touched: Vec<(u64, f64, f64)>,
+    /// (vault_id, owner, collateral returned) per vault fully redeemed and
+    /// closed
+    pub vaults_closed: Vec<(u64, String, f64)>,
+    /// Collateral returned to owners of closed vaults, in total
+    pub surplus_collateral_returned: f64,
+    pub block: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct LiquidationEngine {
     pub config: LiquidationConfig,
     pub total_bad_debt: f64,
+    /// Penalties routed to the treasury surplus
     pub total_penalties_collected: f64,
     pub total_keeper_rewards: f64,
+    pub total_penalties_to_lps: f64,
+    pub total_penalties_to_insurance: f64,
+    pub total_penalties_burned: f64,
+    pub total_redeemed_zai: f64,
+    pub total_redemption_fees_zec: f64,
     pub history: Vec<LiquidationResult>,
+    pub redemption_history: Vec<RedemptionResult>,
+    /// Vaults that recovered during their grace period without being liquidated
+    pub grace_recoveries: u32,
+    /// ZAI keepers have left to buy collateral with
+    #[serde(default)]
+    pub keeper_zai: f64,
+    /// Collateral keepers have bought off-AMM
+    #[serde(default)]
+    pub keeper_zec: f64,
+    /// Gas a keeper pays per liquidation (ZAI); keepers pass on lots whose
+    /// discount doesn't cover it
+    #[serde(default)]
+    pub gas_fee_zai: f64,
+    /// Liquidations that still fit in the current block, set by the
+    /// scenario under a block capacity limit; `None` for no limit
+    #[serde(default)]
+    pub block_capacity: Option<u32>,
+    /// Block at which each currently-unsafe vault entered its grace period
+    grace_started: HashMap<u64, u64>,
     liquidations_this_block: u32,
     current_block: u64,
 }
 
 impl LiquidationEngine {
     pub fn new(config: LiquidationConfig) -> Self {
+        if let Err(e) = config.routing().validate() {
+            panic!("Invalid penalty routing: {}", e);
+        }
+        let keeper_zai = config
+            .keeper_liquidity
+            .as_ref()
+            .map_or(0.0, |k| k.initial_zai);
         LiquidationEngine {
             config,
             total_bad_debt: 0.0,
             total_penalties_collected: 0.0,
             total_keeper_rewards: 0.0,
+            total_penalties_to_lps: 0.0,
+            total_penalties_to_insurance: 0.0,
+            total_penalties_burned: 0.0,
+            total_redeemed_zai: 0.0,
+            total_redemption_fees_zec: 0.0,
             history: Vec::new(),
+            redemption_history: Vec::new(),
+            grace_recoveries: 0,
+            keeper_zai,
+            keeper_zec: 0.0,
+            gas_fee_zai: 0.0,
+            block_capacity: None,
+            grace_started: HashMap::new(),
             liquidations_this_block: 0,
             current_block: 0,
         }
@@ -91,30 +433,170 @@ impl LiquidationEngine {
     /// Reset the per-block counter when advancing to a new block.
     fn advance_block(&mut self, block: u64) {
         if block > self.current_block {
+            if let Some(keepers) = &self.config.keeper_liquidity {
+                self.keeper_zai +=
+                    keepers.replenish_zai_per_block * (block - self.current_block) as f64;
+            }
             self.current_block = block;
             self.liquidations_this_block = 0;
         }
     }
 
-    fn check_velocity(&self) -> Result<(), String> {
+    /// Log a liquidation and add it to the history.
+    fn record(&mut self, result: &LiquidationResult) {
+        tracing::info!(
+            vault = result.vault_id,
+            owner = %result.owner,
+            mode = ?result.mode,
+            collateral_seized = result.collateral_seized,
+            debt_to_cover = result.debt_to_cover,
+            zai_from_amm = result.zai_from_amm,
+            zai_from_keepers = result.zai_from_keepers,
+            penalty = result.penalty_amount,
+            bad_debt = result.bad_debt,
+            "vault liquidated"
+        );
+        self.history.push(result.clone());
+    }
+
+    /// Split a collected penalty across the routing sinks and return the
+    /// keeper's cut. Without a keeper, its share goes pro rata to the rest.
+    fn route_penalty(&mut self, penalty: f64, with_keeper: bool, amm: &mut Amm) -> f64 {
+        if penalty <= 0.0 {
+            return 0.0;
+        }
+        let routing = self.config.routing();
+        let (keeper_reward, scale) = if with_keeper {
+            (penalty * routing.keeper, 1.0)
+        } else if routing.keeper < 1.0 {
+            (0.0, 1.0 / (1.0 - routing.keeper))
+        } else {
+            (0.0, 0.0)
+        };
+
+        // LPs are paid by injecting ZAI into AMM reserves
+        let to_lps = penalty * routing.lps * scale;
+        if to_lps > 0.0 {
+            amm.reserve_zai += to_lps;
+            amm.k = amm.reserve_zec * amm.reserve_zai;
+            amm.cumulative_fees_zai += to_lps;
+        }
+        let to_insurance = penalty * routing.insurance_fund * scale;
+        let burned = penalty * routing.burn * scale;
+
+        self.total_keeper_rewards += keeper_reward;
+        self.total_penalties_to_lps += to_lps;
+        self.total_penalties_to_insurance += to_insurance;
+        self.total_penalties_burned += burned;
+        // Treasury takes the remainder, so the split is exact
+        self.total_penalties_collected += penalty - keeper_reward - to_lps - to_insurance - burned;
+        keeper_reward
+    }
+
+    fn check_velocity(&self) -> Result<(), ZaiSimError> {
         if self.liquidations_this_block >= self.config.max_liquidations_per_block {
-            return Err(format!(
-                "Velocity limit reached: {} liquidations in block {}",
+            return Err(ZaiSimError::VelocityLimit(format!(
+                "{} liquidations in block {}",
                 self.liquidations_this_block, self.current_block
-            ));
+            )));
+        }
+        if self.block_capacity == Some(0) {
+            return Err(ZaiSimError::VelocityLimit(format!(
+                "block {} is full",
+                self.current_block
+            )));
         }
         Ok(())
     }
 
-    /// Scan all vaults and return IDs of those below min_ratio.
+    /// Count a liquidation against the velocity limit and block capacity.
+    fn count_liquidation(&mut self) {
+        self.liquidations_this_block += 1;
+        if let Some(room) = &mut self.block_capacity {
+            *room = room.saturating_sub(1);
+        }
+    }
+
+    /// Whether keepers or the system may liquidate `vault_id` at `block`.
+    /// Starts the vault's grace window the first time it is seen unsafe.
+    fn grace_allows(&mut self, vault_id: u64, block: u64) -> bool {
+        let grace = self.config.grace_period_blocks;
+        if grace == 0 {
+            return true;
+        }
+        let started = *self.grace_started.entry(vault_id).or_insert(block);
+        block >= started + grace
+    }
+
+    /// Refresh grace windows at this block's eligibility price. Vaults back
+    /// above min_ratio leave grace and count as recoveries; closed or
+    /// liquidated vaults are dropped.
+    pub fn update_grace(&mut self, registry: &VaultRegistry, price: f64) {
+        if self.config.grace_period_blocks == 0 {
+            return;
+        }
+        let min_ratio = registry.config.min_ratio;
+        let mut recovered = 0;
+        self.grace_started.retain(|id, _| match registry.vaults.get(id) {
+            Some(v) if v.debt_zai > 0.0 && registry.ratio_due(v, price) < min_ratio => true,
+            Some(_) => {
+                recovered += 1;
+                false
+            }
+            None => false,
+        });
+        self.grace_recoveries += recovered;
+    }
+
+    /// Number of vaults currently inside their grace window.
+    pub fn vaults_in_grace(&self, block: u64) -> u32 {
+        let grace = self.config.grace_period_blocks;
+        self.grace_started
+            .values()
+            .filter(|&&started| block < started + grace)
+            .count() as u32
+    }
+
+    /// Scan all vaults and return IDs of those below min_ratio, in
+    /// `ordering` priority.
     pub fn scan_liquidatable(&self, registry: &VaultRegistry, amm: &Amm) -> Vec<u64> {
-        let mut ids: Vec<u64> = registry
-            .vaults
-            .iter()
-            .filter(|(id, _)| registry.is_liquidatable(**id, amm))
-            .map(|(id, _)| *id)
+        let price = registry.get_price(amm);
+        let ids: Vec<u64> = registry
+            .parity_candidates(price / registry.config.min_ratio, f64::INFINITY)
+            .into_iter()
+            .filter(|id| registry.is_liquidatable(*id, amm))
             .collect();
+        self.prioritize(ids, registry, amm, price)
+    }
+
+    /// Sort `ids` by the configured ordering, collateral ratios taken at
+    /// `price`. Ties keep vault ID order.
+    pub fn prioritize(
+        &self,
+        mut ids: Vec<u64>,
+        registry: &VaultRegistry,
+        amm: &Amm,
+        price: f64,
+    ) -> Vec<u64> {
         ids.sort();
+        let key = |id: &u64| -> f64 {
+            let Some(vault) = registry.vaults.get(id) else {
+                return f64::INFINITY;
+            };
+            match self.config.ordering {
+                LiquidationOrdering::VaultId => 0.0,
+                LiquidationOrdering::LowestCr => registry.ratio_due(vault, price),
+                LiquidationOrdering::LargestDebt => -registry.debt_due(vault),
+                LiquidationOrdering::SmallestSlippage => {
+                    let zec = vault.collateral_zec;
+                    let slippage = zec * amm.spot_price() - amm.quote_zec_for_zai(zec);
+                    slippage / registry.debt_due(vault)
+                }
+            }
+        };
+        if self.config.ordering != LiquidationOrdering::VaultId {
+            ids.sort_by(|a, b| key(a).total_cmp(&key(b)));
+        }
         ids
     }
 
@@ -126,11 +608,11 @@ impl LiquidationEngine {
         vault_id: u64,
         mode: LiquidationMode,
         penalty_fraction: f64,
-        keeper_reward_fraction: f64,
+        with_keeper: bool,
         registry: &mut VaultRegistry,
         amm: &mut Amm,
         block: u64,
-    ) -> Result<LiquidationResult, String> {
+    ) -> Result<LiquidationResult, ZaiSimError> {
         self.advance_block(block);
         self.check_velocity()?;
 
@@ -148,31 +630,45 @@ impl LiquidationEngine {
                 | LiquidationMode::GraduatedPartial
         ) && !registry.is_liquidatable(vault_id, amm)
         {
-            return Err(format!("Vault {} is not liquidatable", vault_id));
+            return Err(ZaiSimError::NotLiquidatable(vault_id));
         }
 
         // Snapshot vault before removal
         let vault = registry
             .vaults
             .get(&vault_id)
-            .ok_or_else(|| format!("Vault {} not found", vault_id))?;
+            .ok_or(ZaiSimError::VaultNotFound(vault_id))?;
 
         let collateral_seized = vault.collateral_zec;
         let debt_to_cover = vault.debt_zai;
         let owner = vault.owner.clone();
 
         if debt_to_cover == 0.0 {
-            return Err("Cannot liquidate vault with no debt".to_string());
+            return Err(ZaiSimError::NoDebt(vault_id));
+        }
+
+        // With a close factor, keeper and system liquidations only repay part
+        if let Some(close_factor) = self.config.close_factor {
+            if mode != LiquidationMode::SelfLiquidation {
+                return self.execute_close_factor(
+                    vault_id,
+                    close_factor,
+                    penalty_fraction,
+                    with_keeper,
+                    registry,
+                    amm,
+                    block,
+                );
+            }
         }
 
         // Remove vault from registry and adjust total_debt
         registry.vaults.remove(&vault_id);
+        registry.reindex(vault_id);
         registry.total_debt -= debt_to_cover;
 
-        // Sell seized collateral on AMM
-        let zai_from_amm = amm
-            .swap_zec_for_zai(collateral_seized, block)
-            .unwrap_or(0.0);
+        // Sell seized collateral on the AMM, or a side pool route if it pays more
+        let zai_from_amm = amm.sell_zec(collateral_seized, block).unwrap_or(0.0);
 
         // Calculate penalty
         let penalty_amount = debt_to_cover * penalty_fraction;
@@ -192,23 +688,12 @@ impl LiquidationEngine {
             (debt_to_cover - zai_from_amm, 0.0, 0.0)
         };
 
-        // Keeper reward is a fraction of actual penalty collected
-        let keeper_reward = actual_penalty * keeper_reward_fraction;
-
-        // Route a share of the non-keeper penalty to LPs via AMM reserves
-        let lp_penalty_share =
-            (actual_penalty - keeper_reward) * self.config.liquidation_penalty_to_lps_pct;
-        if lp_penalty_share > 0.0 {
-            amm.reserve_zai += lp_penalty_share;
-            amm.k = amm.reserve_zec * amm.reserve_zai;
-            amm.cumulative_fees_zai += lp_penalty_share;
-        }
+        // Split the penalty between keeper, LPs, insurance, treasury and burn
+        let keeper_reward = self.route_penalty(actual_penalty, with_keeper, amm);
 
         // Update engine state
         self.total_bad_debt += bad_debt;
-        self.total_penalties_collected += actual_penalty - keeper_reward - lp_penalty_share;
-        self.total_keeper_rewards += keeper_reward;
-        self.liquidations_this_block += 1;
+        self.count_liquidation();
 
         let result = LiquidationResult {
             vault_id,
@@ -217,18 +702,250 @@ impl LiquidationEngine {
             collateral_seized,
             debt_to_cover,
             zai_from_amm,
+            zai_from_keepers: 0.0,
             penalty_amount: actual_penalty,
             keeper_reward,
             surplus_to_owner,
+            collateral_returned: 0.0,
             bad_debt,
             block,
         };
 
-        self.history.push(result.clone());
+        self.record(&result);
 
         Ok(result)
     }
 
+    /// Close-factor liquidation: repay `close_factor` of the vault's debt by
+    /// selling the collateral that covers it plus the penalty on the AMM,
+    /// and leave the vault open. Debt that would be left below the floor is
+    /// settled by the dust policy. A vault whose collateral cannot cover the
+    /// repayment is seized whole and closed, with any shortfall as bad debt.
+    #[allow(clippy::too_many_arguments)]
+    fn execute_close_factor(
+        &mut self,
+        vault_id: u64,
+        close_factor: f64,
+        penalty_fraction: f64,
+        with_keeper: bool,
+        registry: &mut VaultRegistry,
+        amm: &mut Amm,
+        block: u64,
+    ) -> Result<LiquidationResult, ZaiSimError> {
+        let vault = registry
+            .vaults
+            .get(&vault_id)
+            .ok_or(ZaiSimError::VaultNotFound(vault_id))?;
+        let owner = vault.owner.clone();
+        let debt = vault.debt_zai;
+        let collateral = vault.collateral_zec;
+
+        let repay = registry.config.liquidation_repayment(
+            debt,
+            debt * close_factor.clamp(0.0, 1.0),
+            self.config.dust_policy,
+        );
+
+        // ZEC whose AMM sale raises the repayment plus penalty
+        let target = repay * (1.0 + penalty_fraction);
+        let needed = if target < amm.reserve_zai {
+            amm.reserve_zec * target / ((amm.reserve_zai - target) * (1.0 - amm.swap_fee))
+        } else {
+            f64::INFINITY
+        };
+        let seize_all = needed >= collateral;
+        let collateral_seized = needed.min(collateral);
+        let zai_from_amm = amm.sell_zec(collateral_seized, block).unwrap_or(0.0);
+
+        // Proceeds settle debt first, then the penalty; a closed vault owes
+        // all of its debt
+        let owed = if seize_all { debt } else { repay };
+        let mut debt_repaid = zai_from_amm.min(owed);
+        let mut penalty = (zai_from_amm - debt_repaid).clamp(0.0, owed * penalty_fraction);
+        let surplus_to_owner = zai_from_amm - debt_repaid - penalty;
+        let mut bad_debt = if seize_all { debt - debt_repaid } else { 0.0 };
+
+        let vault = registry
+            .vaults
+            .get_mut(&vault_id)
+            .ok_or(ZaiSimError::VaultNotFound(vault_id))?;
+        vault.collateral_zec -= collateral_seized;
+        vault.debt_zai -= debt_repaid;
+        // Leftover collateral of a repaid vault goes back to its owner
+        let collateral_returned = if seize_all || vault.debt_zai <= 0.0 {
+            let left = vault.collateral_zec;
+            registry.vaults.remove(&vault_id);
+            registry.total_debt -= debt;
+            left
+        } else {
+            registry.total_debt -= debt_repaid;
+            let (used, written_off, left) = registry.clear_dust(vault_id, penalty)?;
+            penalty -= used;
+            debt_repaid += used;
+            bad_debt += written_off;
+            left
+        };
+        registry.reindex(vault_id);
+
+        let keeper_reward = self.route_penalty(penalty, with_keeper, amm);
+        self.total_bad_debt += bad_debt;
+        self.count_liquidation();
+
+        let result = LiquidationResult {
+            vault_id,
+            owner,
+            mode: LiquidationMode::CloseFactor,
+            collateral_seized,
+            debt_to_cover: debt_repaid,
+            zai_from_amm,
+            zai_from_keepers: 0.0,
+            penalty_amount: penalty,
+            keeper_reward,
+            surplus_to_owner,
+            collateral_returned,
+            bad_debt,
+            block,
+        };
+        self.record(&result);
+        Ok(result)
+    }
+
+    /// Keeper purchase: keepers pay the debt plus penalty in their own ZAI,
+    /// burned against the debt, and take collateral priced at `price` less
+    /// the keeper discount. Nothing is sold on the AMM. Collateral beyond
+    /// what the obligation buys is returned to the owner; a vault whose
+    /// collateral is worth less is bought whole, with the shortfall as bad
+    /// debt.
+    fn execute_keeper_purchase(
+        &mut self,
+        vault_id: u64,
+        price: f64,
+        registry: &mut VaultRegistry,
+        amm: &mut Amm,
+        block: u64,
+    ) -> Result<LiquidationResult, ZaiSimError> {
+        self.advance_block(block);
+        self.check_velocity()?;
+        let keepers = self
+            .config
+            .keeper_liquidity
+            .clone()
+            .ok_or_else(|| ZaiSimError::Config("no keeper liquidity configured".to_string()))?;
+
+        registry.accrue_fees(vault_id, block)?;
+        let vault = registry
+            .vaults
+            .get(&vault_id)
+            .ok_or(ZaiSimError::VaultNotFound(vault_id))?;
+        let owner = vault.owner.clone();
+        let debt = vault.debt_zai;
+        let collateral = vault.collateral_zec;
+        if debt == 0.0 {
+            return Err(ZaiSimError::NoDebt(vault_id));
+        }
+
+        let penalty_fraction = registry.config.liquidation_penalty;
+        let keeper_price = price * (1.0 - keepers.discount);
+        let obligation = debt * (1.0 + penalty_fraction);
+        let collateral_seized = (obligation / keeper_price).min(collateral);
+        let zai_from_keepers = collateral_seized * keeper_price;
+        if collateral_seized * price - zai_from_keepers < self.gas_fee_zai {
+            return Err(ZaiSimError::InsufficientLiquidity(format!(
+                "vault {} lot doesn't cover keeper gas of {:.2} ZAI",
+                vault_id, self.gas_fee_zai
+            )));
+        }
+        if zai_from_keepers > self.keeper_zai {
+            return Err(ZaiSimError::InsufficientLiquidity(format!(
+                "keepers hold {:.2} ZAI, vault {} needs {:.2}",
+                self.keeper_zai, vault_id, zai_from_keepers
+            )));
+        }
+        self.keeper_zai -= zai_from_keepers;
+        self.keeper_zec += collateral_seized;
+
+        // Collateral the keepers don't buy goes back to the owner
+        registry.vaults.remove(&vault_id);
+        registry.reindex(vault_id);
+        registry.total_debt -= debt;
+
+        // Payment settles debt first, then the penalty
+        let debt_repaid = zai_from_keepers.min(debt);
+        let penalty = zai_from_keepers - debt_repaid;
+        let bad_debt = debt - debt_repaid;
+        // Keepers are paid by the discount, not from the penalty
+        self.route_penalty(penalty, false, amm);
+
+        self.total_bad_debt += bad_debt;
+        self.count_liquidation();
+
+        let result = LiquidationResult {
+            vault_id,
+            owner,
+            mode: LiquidationMode::KeeperPurchase,
+            collateral_seized,
+            debt_to_cover: debt,
+            zai_from_amm: 0.0,
+            zai_from_keepers,
+            penalty_amount: penalty,
+            keeper_reward: 0.0,
+            surplus_to_owner: 0.0,
+            collateral_returned: collateral - collateral_seized,
+            bad_debt,
+            block,
+        };
+        self.record(&result);
+        Ok(result)
+    }
+
+    /// Keeper purchase liquidation of all vaults below min_ratio at `price`.
+    /// Lots keepers cannot afford, or that don't cover their gas, go
+    /// through the AMM when `amm_fallback` is set and otherwise wait.
+    pub fn keeper_liquidate(
+        &mut self,
+        registry: &mut VaultRegistry,
+        amm: &mut Amm,
+        block: u64,
+        price: f64,
+    ) -> Vec<LiquidationResult> {
+        let amm_fallback = self
+            .config
+            .keeper_liquidity
+            .as_ref()
+            .is_some_and(|k| k.amm_fallback);
+        let ids = self.scan_liquidatable_at_price(registry, price);
+        let ids = self.prioritize(ids, registry, amm, price);
+        let mut results = Vec::new();
+
+        for id in ids {
+            if !self.grace_allows(id, block) {
+                continue;
+            }
+            let result = match self.execute_keeper_purchase(id, price, registry, amm, block) {
+                Err(ZaiSimError::InsufficientLiquidity(_)) if amm_fallback => {
+                    let penalty_frac = registry.config.liquidation_penalty;
+                    self.execute_core(
+                        id,
+                        LiquidationMode::OracleLiquidation,
+                        penalty_frac,
+                        false,
+                        registry,
+                        amm,
+                        block,
+                    )
+                }
+                Err(ZaiSimError::InsufficientLiquidity(_)) => continue,
+                other => other,
+            };
+            match result {
+                Ok(result) => results.push(result),
+                Err(_) => break, // velocity limit hit
+            }
+        }
+
+        results
+    }
+
     /// Transparent liquidation: system auto-scans and liquidates all underwater vaults.
     pub fn transparent_liquidate(
         &mut self,
@@ -240,12 +957,15 @@ impl LiquidationEngine {
         let mut results = Vec::new();
 
         for id in ids {
+            if !self.grace_allows(id, block) {
+                continue;
+            }
             let penalty_frac = registry.config.liquidation_penalty;
             match self.execute_core(
                 id,
                 LiquidationMode::Transparent,
                 penalty_frac,
-                0.0, // no keeper in transparent mode
+                false, // no keeper in transparent mode
                 registry,
                 amm,
                 block,
@@ -265,7 +985,7 @@ impl LiquidationEngine {
         registry: &mut VaultRegistry,
         amm: &mut Amm,
         block: u64,
-    ) -> Result<LiquidationResult, String> {
+    ) -> Result<LiquidationResult, ZaiSimError> {
         // Self-liquidation is allowed even if vault is above min ratio
         // (owner may want to exit during volatile conditions)
         let penalty_frac =
@@ -275,7 +995,7 @@ impl LiquidationEngine {
             vault_id,
             LiquidationMode::SelfLiquidation,
             penalty_frac,
-            0.0,
+            false,
             registry,
             amm,
             block,
@@ -290,9 +1010,11 @@ impl LiquidationEngine {
         registry: &mut VaultRegistry,
         amm: &mut Amm,
         block: u64,
-    ) -> Result<LiquidationResult, String> {
+    ) -> Result<LiquidationResult, ZaiSimError> {
+        if registry.is_liquidatable(vault_id, amm) && !self.grace_allows(vault_id, block) {
+            return Err(ZaiSimError::InGracePeriod(vault_id));
+        }
         let penalty_frac = registry.config.liquidation_penalty;
-        let keeper_frac = self.config.keeper_reward_pct;
 
         self.execute_core(
             vault_id,
@@ -300,7 +1022,7 @@ impl LiquidationEngine {
                 keeper: keeper.to_string(),
             },
             penalty_frac,
-            keeper_frac,
+            true,
             registry,
             amm,
             block,
@@ -313,17 +1035,7 @@ impl LiquidationEngine {
         registry: &VaultRegistry,
         price: f64,
     ) -> Vec<u64> {
-        let mut ids: Vec<u64> = registry
-            .vaults
-            .iter()
-            .filter(|(_, vault)| {
-                vault.debt_zai > 0.0
-                    && vault.collateral_ratio(price) < registry.config.min_ratio
-            })
-            .map(|(id, _)| *id)
-            .collect();
-        ids.sort();
-        ids
+        registry.liquidatable_at_price(price)
     }
 
     /// Cascading spot-price liquidation: uses AMM spot price instead of TWAP.
@@ -339,18 +1051,22 @@ impl LiquidationEngine {
         loop {
             let spot_price = amm.spot_price();
             let ids = self.scan_liquidatable_at_price(registry, spot_price);
+            let ids = self.prioritize(ids, registry, amm, spot_price);
             if ids.is_empty() {
                 break;
             }
 
             let mut any_liquidated = false;
             for id in ids {
+                if !self.grace_allows(id, block) {
+                    continue;
+                }
                 let penalty_frac = registry.config.liquidation_penalty;
                 match self.execute_core(
                     id,
                     LiquidationMode::AmmLiquidation,
                     penalty_frac,
-                    0.0,
+                    false,
                     registry,
                     amm,
                     block,
@@ -379,35 +1095,42 @@ impl LiquidationEngine {
         block: u64,
         gap_threshold: f64,
     ) -> Vec<LiquidationResult> {
-        let twap = amm.get_twap(registry.config.twap_window);
+        let twap = registry.get_price(amm);
         let spot = amm.spot_price();
         let min_ratio = registry.config.min_ratio;
 
+        // Zombies are under water at spot but not at the TWAP
         let zombie_ids: Vec<u64> = registry
-            .vaults
-            .iter()
-            .filter(|(_, vault)| {
+            .parity_candidates(spot / min_ratio, twap / min_ratio)
+            .into_iter()
+            .filter(|id| {
+                let Some(vault) = registry.vaults.get(id) else {
+                    return false;
+                };
                 if vault.debt_zai == 0.0 {
                     return false;
                 }
-                let twap_ratio = vault.collateral_ratio(twap);
-                let spot_ratio = vault.collateral_ratio(spot);
+                let twap_ratio = registry.ratio_due(vault, twap);
+                let spot_ratio = registry.ratio_due(vault, spot);
                 // Zombie: safe by TWAP, unsafe by spot, gap above threshold
                 twap_ratio >= min_ratio
                     && spot_ratio < min_ratio
                     && (twap_ratio - spot_ratio) > gap_threshold
             })
-            .map(|(id, _)| *id)
             .collect();
+        let zombie_ids = self.prioritize(zombie_ids, registry, amm, spot);
 
         let mut results = Vec::new();
         for id in zombie_ids {
+            if !self.grace_allows(id, block) {
+                continue;
+            }
             let penalty_frac = registry.config.liquidation_penalty;
             match self.execute_core(
                 id,
                 LiquidationMode::ZombieDetection,
                 penalty_frac,
-                0.0,
+                false,
                 registry,
                 amm,
                 block,
@@ -439,15 +1162,19 @@ impl LiquidationEngine {
         oracle_price: f64,
     ) -> Vec<LiquidationResult> {
         let ids = self.scan_liquidatable_at_price(registry, oracle_price);
+        let ids = self.prioritize(ids, registry, amm, oracle_price);
         let mut results = Vec::new();
 
         for id in ids {
+            if !self.grace_allows(id, block) {
+                continue;
+            }
             let penalty_frac = registry.config.liquidation_penalty;
             match self.execute_core(
                 id,
                 LiquidationMode::OracleLiquidation,
                 penalty_frac,
-                0.0,
+                false,
                 registry,
                 amm,
                 block,
@@ -460,6 +1187,21 @@ impl LiquidationEngine {
         results
     }
 
+    /// `oracle_liquidate` at `oracle`'s price. Nothing is liquidated while
+    /// the oracle has no usable price (outage or expired report).
+    pub fn liquidate_with_oracle(
+        &mut self,
+        registry: &mut VaultRegistry,
+        amm: &mut Amm,
+        block: u64,
+        oracle: &dyn Oracle,
+    ) -> Vec<LiquidationResult> {
+        match oracle.price() {
+            Some(price) => self.oracle_liquidate(registry, amm, block, price),
+            None => Vec::new(),
+        }
+    }
+
     /// Scan vaults eligible for graduated (partial) liquidation.
     /// Returns IDs of vaults whose TWAP-based CR is between graduated_cr_floor and min_ratio.
     pub fn scan_graduated_eligible(
@@ -467,7 +1209,7 @@ impl LiquidationEngine {
         registry: &VaultRegistry,
         amm: &Amm,
     ) -> Vec<u64> {
-        let twap = amm.get_twap(registry.config.twap_window);
+        let twap = registry.get_price(amm);
         let min_ratio = registry.config.min_ratio;
         let cr_floor = self.config.graduated_cr_floor;
 
@@ -478,7 +1220,7 @@ impl LiquidationEngine {
                 if vault.debt_zai <= 0.0 {
                     return false;
                 }
-                let cr = vault.collateral_ratio(twap);
+                let cr = registry.ratio_due(vault, twap);
                 cr >= cr_floor && cr < min_ratio
             })
             .map(|(id, _)| *id)
@@ -489,14 +1231,18 @@ impl LiquidationEngine {
 
     /// Execute a graduated (partial) liquidation on a single vault.
     /// Seizes `graduated_pct_per_block` of collateral, sells on AMM,
-    /// reduces debt by the ZAI received. Vault survives unless debt <= debt_floor.
+    /// reduces debt by the ZAI received. A slice that would clear the debt,
+    /// or leave dust under the wipe policy, seizes the whole vault and
+    /// settles it like a full liquidation; dust left otherwise is cleared
+    /// out of the penalty.
     fn execute_graduated(
         &mut self,
         vault_id: u64,
         registry: &mut VaultRegistry,
         amm: &mut Amm,
         block: u64,
-    ) -> Result<LiquidationResult, String> {
+        mode: LiquidationMode,
+    ) -> Result<LiquidationResult, ZaiSimError> {
         self.advance_block(block);
         self.check_velocity()?;
 
@@ -506,79 +1252,96 @@ impl LiquidationEngine {
         let vault = registry
             .vaults
             .get(&vault_id)
-            .ok_or_else(|| format!("Vault {} not found", vault_id))?;
+            .ok_or(ZaiSimError::VaultNotFound(vault_id))?;
 
         if vault.debt_zai <= 0.0 {
-            return Err("Cannot liquidate vault with no debt".to_string());
+            return Err(ZaiSimError::NoDebt(vault_id));
         }
 
         let pct = self.config.graduated_pct_per_block;
-        let collateral_to_seize = vault.collateral_zec * pct;
         let owner = vault.owner.clone();
+        let debt = vault.debt_zai;
+        let collateral = vault.collateral_zec;
+        let penalty_fraction = registry.config.liquidation_penalty;
 
-        // Sell seized collateral on AMM
-        let zai_from_amm = amm
-            .swap_zec_for_zai(collateral_to_seize, block)
-            .unwrap_or(0.0);
+        // Debt the slice is expected to repay decides whether the vault
+        // goes whole
+        let planned = amm.quote_zec_for_zai(collateral * pct) / (1.0 + penalty_fraction);
+        let policy = self.config.dust_policy;
+        let seize_all = registry.config.liquidation_repayment(debt, planned, policy) >= debt;
+        let collateral_to_seize = if seize_all {
+            collateral
+        } else {
+            collateral * pct
+        };
 
-        // Split AMM proceeds into debt_covered + penalty
-        let penalty_fraction = registry.config.liquidation_penalty;
-        let debt_covered = zai_from_amm / (1.0 + penalty_fraction);
-        let actual_penalty = zai_from_amm - debt_covered;
+        // Sell seized collateral on the AMM, or a side pool route if it pays more
+        let zai_from_amm = amm.sell_zec(collateral_to_seize, block).unwrap_or(0.0);
 
-        // Cap debt reduction at actual vault debt
-        let vault = registry
-            .vaults
-            .get(&vault_id)
-            .ok_or_else(|| format!("Vault {} not found after AMM swap", vault_id))?;
-        let debt_reduction = debt_covered.min(vault.debt_zai);
-        let bad_debt = if debt_covered < 0.0 { -debt_covered } else { 0.0 };
-
-        // Route penalty share to LPs
-        let lp_penalty_share =
-            actual_penalty * self.config.liquidation_penalty_to_lps_pct;
-        if lp_penalty_share > 0.0 {
-            amm.reserve_zai += lp_penalty_share;
-            amm.k = amm.reserve_zec * amm.reserve_zai;
-            amm.cumulative_fees_zai += lp_penalty_share;
-        }
+        // Split AMM proceeds into debt reduction + penalty; a seized vault
+        // owes all of its debt, with the excess returned to the owner
+        let (mut debt_reduction, mut actual_penalty, surplus_to_owner, mut bad_debt) = if seize_all
+        {
+            let repaid = zai_from_amm.min(debt);
+            let penalty = (zai_from_amm - repaid).clamp(0.0, debt * penalty_fraction);
+            (
+                repaid,
+                penalty,
+                zai_from_amm - repaid - penalty,
+                debt - repaid,
+            )
+        } else {
+            let covered = (zai_from_amm / (1.0 + penalty_fraction)).min(debt);
+            (covered, zai_from_amm - covered, 0.0, 0.0)
+        };
 
         // Update vault in place
         let vault = registry
             .vaults
             .get_mut(&vault_id)
-            .ok_or_else(|| format!("Vault {} disappeared", vault_id))?;
+            .ok_or(ZaiSimError::VaultNotFound(vault_id))?;
         vault.collateral_zec -= collateral_to_seize;
         vault.debt_zai -= debt_reduction;
-
-        // Update registry total debt
+        let (left, collateral_left) = (vault.debt_zai, vault.collateral_zec);
         registry.total_debt -= debt_reduction;
 
-        // If vault debt is at or below floor (or zero), remove it entirely
-        if vault.debt_zai <= registry.config.debt_floor || vault.debt_zai <= 0.0 {
+        let collateral_returned = if seize_all || left <= 0.0 {
             registry.vaults.remove(&vault_id);
-        }
+            registry.total_debt -= left;
+            collateral_left
+        } else {
+            let (used, written_off, returned) = registry.clear_dust(vault_id, actual_penalty)?;
+            actual_penalty -= used;
+            debt_reduction += used;
+            bad_debt += written_off;
+            returned
+        };
+        registry.reindex(vault_id);
+
+        // Route the penalty (no keeper in graduated mode)
+        self.route_penalty(actual_penalty, false, amm);
 
         // Update engine state
         self.total_bad_debt += bad_debt;
-        self.total_penalties_collected += actual_penalty - lp_penalty_share;
-        self.liquidations_this_block += 1;
+        self.count_liquidation();
 
         let result = LiquidationResult {
             vault_id,
             owner,
-            mode: LiquidationMode::GraduatedPartial,
+            mode,
             collateral_seized: collateral_to_seize,
             debt_to_cover: debt_reduction,
             zai_from_amm,
+            zai_from_keepers: 0.0,
             penalty_amount: actual_penalty,
             keeper_reward: 0.0,
-            surplus_to_owner: 0.0,
+            surplus_to_owner,
+            collateral_returned,
             bad_debt,
             block,
         };
 
-        self.history.push(result.clone());
+        self.record(&result);
         Ok(result)
     }
 
@@ -596,10 +1359,20 @@ impl LiquidationEngine {
         }
 
         let ids = self.scan_graduated_eligible(registry, amm);
+        let ids = self.prioritize(ids, registry, amm, registry.get_price(amm));
         let mut results = Vec::new();
 
         for id in ids {
-            match self.execute_graduated(id, registry, amm, block) {
+            if !self.grace_allows(id, block) {
+                continue;
+            }
+            match self.execute_graduated(
+                id,
+                registry,
+                amm,
+                block,
+                LiquidationMode::GraduatedPartial,
+            ) {
                 Ok(result) => results.push(result),
                 Err(_) => break, // velocity limit hit
             }
@@ -607,4 +1380,132 @@ impl LiquidationEngine {
 
         results
     }
+
+    /// Auto-deleveraging: partially unwind `ids` in order through the
+    /// graduated engine, whether or not they are liquidatable. Each vault
+    /// has `graduated_pct_per_block` of its collateral sold and the
+    /// proceeds, less the liquidation penalty, repay its debt.
+    pub fn deleverage(
+        &mut self,
+        ids: &[u64],
+        registry: &mut VaultRegistry,
+        amm: &mut Amm,
+        block: u64,
+    ) -> Vec<LiquidationResult> {
+        let mut results = Vec::new();
+        for &id in ids {
+            match self.execute_graduated(id, registry, amm, block, LiquidationMode::AutoDeleverage)
+            {
+                Ok(result) => results.push(result),
+                Err(ZaiSimError::VelocityLimit(_)) => break,
+                Err(_) => continue,
+            }
+        }
+        results
+    }
+
+    /// Liquity-style redemption: exchange `zai_amount` ZAI for collateral at face
+    /// value (`1 / redemption_price` ZEC per ZAI), drawn from vaults in ascending
+    /// TWAP collateral ratio order.
+    ///
+    /// Vaults below 100% CR at the redemption price are skipped. A redemption that
+    /// would leave debt between zero and the debt floor is capped at the floor;
+    /// a fully redeemed vault is closed and its leftover collateral listed in
+    /// `vaults_closed` for the scenario to return to the owner. Returns the ZAI
+    /// actually redeemed, which may be less than requested.
+    pub fn redeem(
+        &mut self,
+        redeemer: &str,
+        zai_amount: f64,
+        redemption_price: f64,
+        registry: &mut VaultRegistry,
+        amm: &Amm,
+        block: u64,
+    ) -> Result<RedemptionResult, ZaiSimError> {
+        if zai_amount <= 0.0 {
+            return Err(ZaiSimError::InvalidInput(
+                "Redemption amount must be positive".to_string(),
+            ));
+        }
+        if redemption_price <= 0.0 {
+            return Err(ZaiSimError::InvalidInput(
+                "Redemption price must be positive".to_string(),
+            ));
+        }
+
+        let twap = registry.get_price(amm);
+        let mut remaining = zai_amount;
+        let mut zec_drawn = 0.0;
+        let mut vaults_touched = Vec::new();
+        let mut vaults_closed = Vec::new();
+        let mut surplus_collateral_returned = 0.0;
+
+        for id in registry.vaults_by_collateral_ratio(twap) {
+            if remaining <= 1e-9 {
+                break;
+            }
+            registry.accrue_fees(id, block)?;
+            let vault = match registry.vaults.get(&id) {
+                Some(v) => v,
+                None => continue,
+            };
+            if vault.collateral_ratio(redemption_price) < 1.0 {
+                continue;
+            }
+
+            let amount = registry.config.max_repayment(vault.debt_zai, remaining);
+            if amount <= 0.0 {
+                continue;
+            }
+
+            let collateral = amount / redemption_price;
+            let vault = registry.vaults.get_mut(&id).unwrap();
+            vault.debt_zai -= amount;
+            vault.collateral_zec -= collateral;
+            registry.total_debt -= amount;
+            remaining -= amount;
+            zec_drawn += collateral;
+            vaults_touched.push((id, amount, collateral));
+
+            if vault.debt_zai <= 1e-9 {
+                let closed = registry.vaults.remove(&id).unwrap();
+                registry.total_debt -= closed.debt_zai;
+                surplus_collateral_returned += closed.collateral_zec;
+                vaults_closed.push((id, closed.owner, closed.collateral_zec));
+            }
+            registry.reindex(id);
+        }
+
+        let zai_redeemed = zai_amount - remaining;
+        if zai_redeemed <= 0.0 {
+            return Err(ZaiSimError::InsufficientLiquidity(
+                "no vaults available for redemption".to_string(),
+            ));
+        }
+
+        let fee_zec = zec_drawn * self.config.redemption_fee_pct;
+        self.total_redeemed_zai += zai_redeemed;
+        self.total_redemption_fees_zec += fee_zec;
+
+        let result = RedemptionResult {
+            redeemer: redeemer.to_string(),
+            zai_redeemed,
+            zec_received: zec_drawn - fee_zec,
+            fee_zec,
+            vaults_touched,
+            vaults_closed,
+            surplus_collateral_returned,
+            block,
+        };
+        tracing::info!(
+            redeemer = %result.redeemer,
+            zai_redeemed = result.zai_redeemed,
+            zec_received = result.zec_received,
+            vaults_touched = result.vaults_touched.len(),
+            vaults_closed = ?result.vaults_closed,
+            "redemption"
+        );
+        self.redemption_history.push(result.clone());
+        Ok(result)
+    }
 }
@@ -0,0 +1,179 @@
+//! Live shadow deployment against a real-time price feed.
+//!
+//! Subscribes to Binance's trade stream, buckets trades into simulated
+//! blocks at the Zcash block cadence and steps a scenario with each block's
+//! last trade price, reporting rolling peg and liquidation stats as it goes.
+//! The scenario trades nothing real; it shows how a parameter set would
+//! behave against today's market.
+
+use rand::Rng;
+use std::thread;
+
+use crate::data_fetcher::RetryPolicy;
+use crate::error::ZaiSimError;
+use crate::output::{self, SummaryMetrics};
+use crate::scenario::{MetricsStore, Scenario};
+
+#[derive(Debug, Clone)]
+pub struct LiveConfig {
+    /// Wall-clock seconds per simulated block
+    pub block_time_secs: u64,
+    /// Print rolling stats every N blocks
+    pub report_every: u64,
+    /// Blocks covered by the rolling stats
+    pub window: usize,
+}
+
+impl Default for LiveConfig {
+    fn default() -> Self {
+        LiveConfig {
+            block_time_secs: 75,
+            report_every: 12, // 15 minutes
+            window: 48,       // 1 hour
+        }
+    }
+}
+
+/// Buckets timestamped trades into fixed-length blocks.
+///
+/// Block 1 starts at the first trade. A block closes at the last price seen
+/// before its end; blocks with no trades repeat the previous close.
+#[derive(Debug, Clone)]
+pub struct BlockClock {
+    block_ms: u64,
+    genesis_ms: Option<u64>,
+    current_block: u64,
+    last_price: f64,
+}
+
+impl BlockClock {
+    pub fn new(block_time_secs: u64) -> Self {
+        BlockClock {
+            block_ms: block_time_secs.max(1) * 1000,
+            genesis_ms: None,
+            current_block: 1,
+            last_price: 0.0,
+        }
+    }
+
+    /// Record a trade; returns `(block, close)` for every block it completes.
+    pub fn push(&mut self, timestamp_ms: u64, price: f64) -> Vec<(u64, f64)> {
+        let genesis = *self.genesis_ms.get_or_insert(timestamp_ms);
+        let block = timestamp_ms.saturating_sub(genesis) / self.block_ms + 1;
+
+        let mut closed = Vec::new();
+        while self.current_block < block {
+            closed.push((self.current_block, self.last_price));
+            self.current_block += 1;
+        }
+        self.last_price = price;
+        closed
+    }
+}
+
+/// A running shadow simulation fed by trades.
+pub struct LiveSession {
+    pub scenario: Scenario,
+    pub config: LiveConfig,
+    clock: BlockClock,
+    target_price: f64,
+}
+
+impl LiveSession {
+    /// Peg deviation is measured against the scenario's initial AMM price.
+    pub fn new(scenario: Scenario, config: LiveConfig) -> Self {
+        let target_price = scenario.config.initial_amm_price();
+        LiveSession {
+            clock: BlockClock::new(config.block_time_secs),
+            scenario,
+            config,
+            target_price,
+        }
+    }
+
+    /// Feed one trade, stepping the scenario through any blocks it closes.
+    /// Returns rolling stats when a report is due.
+    pub fn on_trade(&mut self, timestamp_ms: u64, price: f64) -> Option<SummaryMetrics> {
+        let mut report = None;
+        for (block, close) in self.clock.push(timestamp_ms, price) {
+            self.scenario.step(block, close);
+            if self.config.report_every > 0 && block % self.config.report_every == 0 {
+                report = Some(self.rolling_stats());
+            }
+        }
+        report
+    }
+
+    /// Summary over the last `window` blocks.
+    pub fn rolling_stats(&self) -> SummaryMetrics {
+        let metrics = &self.scenario.metrics;
+        let start = metrics.len().saturating_sub(self.config.window);
+        let window: MetricsStore = metrics.iter().skip(start).collect();
+        output::compute_summary(&window, self.target_price)
+    }
+}
+
+/// Parse a Binance trade stream message into `(trade_time_ms, price)`.
+pub fn parse_trade(text: &str) -> Option<(u64, f64)> {
+    let msg: serde_json::Value = serde_json::from_str(text).ok()?;
+    if msg["e"] != "trade" {
+        return None;
+    }
+    let timestamp_ms = msg["T"].as_u64()?;
+    let price = msg["p"].as_str()?.parse().ok()?;
+    Some((timestamp_ms, price))
+}
+
+/// Stream trades for `symbol` (e.g. zecusdt) and run a shadow simulation
+/// until the feed fails more than `retry.max_retries` times in a row.
+///
+/// `build` creates the scenario once the first trade price is known, so the
+/// AMM can be seeded at the market price. `on_report` receives the session
+/// and its rolling stats every `config.report_every` blocks. Binance drops
+/// connections after 24 hours; those are reconnected transparently.
+pub fn run_live(
+    symbol: &str,
+    config: &LiveConfig,
+    retry: &RetryPolicy,
+    build: impl FnOnce(f64) -> Scenario,
+    mut on_report: impl FnMut(&LiveSession, &SummaryMetrics),
+) -> Result<(), ZaiSimError> {
+    let url = format!(
+        "wss://stream.binance.com:9443/ws/{}@trade",
+        symbol.to_lowercase()
+    );
+    let mut build = Some(build);
+    let mut session: Option<LiveSession> = None;
+    let mut rng = rand::thread_rng();
+    let mut attempt = 0;
+
+    loop {
+        let err = match tungstenite::connect(url.as_str()) {
+            Ok((mut socket, _)) => loop {
+                let msg = match socket.read() {
+                    Ok(msg) => msg,
+                    Err(e) => break e,
+                };
+                let Some((timestamp_ms, price)) = msg.to_text().ok().and_then(parse_trade) else {
+                    continue;
+                };
+                attempt = 0;
+
+                let session = session.get_or_insert_with(|| {
+                    let build = build.take().expect("scenario is built once");
+                    LiveSession::new(build(price), config.clone())
+                });
+                if let Some(stats) = session.on_trade(timestamp_ms, price) {
+                    on_report(&*session, &stats);
+                }
+            },
+            Err(e) => e,
+        };
+
+        if attempt >= retry.max_retries {
+            return Err(ZaiSimError::from(err));
+        }
+        thread::sleep(retry.delay(attempt, rng.gen()));
+        attempt += 1;
+    }
+}
@@ -0,0 +1,216 @@
+//! LP return attribution.
+//!
+//! Splits what AMM liquidity providers earn into fees (swaps and any
+//! stability fees routed to LPs), liquidation penalties paid to LPs, and
+//! impermanent loss, per LP cohort. A cohort is an `Amm::lp_shares` owner
+//! with any `_<n>` index dropped, so `private_lp_0..4` form one cohort
+//! beside `genesis`, `lp_agent` and `protocol`.
+//!
+//! Impermanent loss is measured against holding what each cohort deposited:
+//! a constant-product position with holdings `x, y` is worth `2·√(x·y·P)`
+//! before fees against `x·P + y` held, at AMM price `P`. Withdrawals
+//! realize the loss on the part removed.
+
+use std::collections::{BTreeMap, VecDeque};
+
+use serde::{Deserialize, Serialize};
+
+use crate::amm::Amm;
+use crate::cdp::BLOCKS_PER_YEAR;
+
+/// Trailing blocks the fee APR is measured over (one day at 75 s blocks).
+pub const APR_WINDOW_BLOCKS: u64 = 1152;
+
+/// One cohort's attribution at the end of a block. All amounts in ZAI.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LpCohortMetrics {
+    pub cohort: String,
+    pub shares: f64,
+    /// Position at the AMM price
+    pub value_zai: f64,
+    /// Fees over the trailing window, annualized, as a fraction of `value_zai`
+    pub fee_apr: f64,
+    /// Cumulative swap and routed stability fees earned
+    pub fees_zai: f64,
+    /// Cumulative liquidation penalties earned
+    pub penalties_zai: f64,
+    /// Cumulative impermanent loss, realized and open (≤ 0)
+    pub il_zai: f64,
+    /// `fees_zai + penalties_zai + il_zai`
+    pub net_return_zai: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Cohort {
+    shares: f64,
+    /// What the open position would hold had it not been deposited
+    basis_zec: f64,
+    basis_zai: f64,
+    fees_zai: f64,
+    penalties_zai: f64,
+    realized_il_zai: f64,
+    /// (block, fees earned that block) over the APR window
+    recent_fees: VecDeque<(u64, f64)>,
+}
+
+impl Cohort {
+    /// Loss of the open position against holding its basis, at `price`.
+    fn open_il(&self, price: f64) -> f64 {
+        let lp = 2.0 * (self.basis_zec * self.basis_zai * price).sqrt();
+        lp - (self.basis_zec * price + self.basis_zai)
+    }
+}
+
+/// Per-cohort running attribution, updated once per block.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LpAttribution {
+    cohorts: BTreeMap<String, Cohort>,
+    seen_fees: f64,
+    seen_penalties: f64,
+}
+
+/// Cohort of an LP share owner: the owner with a trailing `_<n>` dropped.
+pub fn cohort_of(owner: &str) -> &str {
+    match owner.rsplit_once('_') {
+        Some((stem, index)) if !index.is_empty() && index.bytes().all(|b| b.is_ascii_digit()) => {
+            stem
+        }
+        _ => owner,
+    }
+}
+
+impl LpAttribution {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Attribute fee and penalty income since the last call to the shares
+    /// held over it, then pick up deposits and withdrawals.
+    /// `penalties_to_lps` is the liquidation engine's running total.
+    pub fn observe(
+        &mut self,
+        amm: &Amm,
+        penalties_to_lps: f64,
+        block: u64,
+    ) -> Vec<LpCohortMetrics> {
+        let income = amm.cumulative_fees_zai - self.seen_fees;
+        let penalties = (penalties_to_lps - self.seen_penalties).max(0.0);
+        let fees = (income - penalties).max(0.0);
+        self.seen_fees = amm.cumulative_fees_zai;
+        self.seen_penalties = penalties_to_lps;
+
+        let held: f64 = self.cohorts.values().map(|c| c.shares).sum();
+        for cohort in self.cohorts.values_mut() {
+            let fraction = if held > 0.0 {
+                cohort.shares / held
+            } else {
+                0.0
+            };
+            cohort.fees_zai += fees * fraction;
+            cohort.penalties_zai += penalties * fraction;
+            cohort.recent_fees.push_back((block, fees * fraction));
+            while cohort
+                .recent_fees
+                .front()
+                .is_some_and(|&(b, _)| b + APR_WINDOW_BLOCKS <= block)
+            {
+                cohort.recent_fees.pop_front();
+            }
+        }
+
+        // Sum in owner order so runs stay bit-for-bit reproducible
+        let mut owners: Vec<(&String, &f64)> = amm.lp_shares.iter().collect();
+        owners.sort_by(|a, b| a.0.cmp(b.0));
+        let mut shares: BTreeMap<&str, f64> = BTreeMap::new();
+        for (owner, s) in owners {
+            *shares.entry(cohort_of(owner)).or_insert(0.0) += s;
+        }
+        for name in self.cohorts.keys() {
+            shares.entry(name.as_str()).or_insert(0.0);
+        }
+        let shares: Vec<(String, f64)> = shares
+            .into_iter()
+            .map(|(name, s)| (name.to_string(), s))
+            .collect();
+
+        let price = amm.spot_price();
+        let total = amm.total_lp_shares;
+        for (name, now) in shares {
+            let cohort = self.cohorts.entry(name).or_default();
+            let change = now - cohort.shares;
+            if change > 0.0 && total > 0.0 {
+                // Deposits are proportional to reserves
+                cohort.basis_zec += change / total * amm.reserve_zec;
+                cohort.basis_zai += change / total * amm.reserve_zai;
+            } else if change < 0.0 && cohort.shares > 0.0 {
+                let removed = (-change / cohort.shares).min(1.0);
+                cohort.realized_il_zai += removed * cohort.open_il(price);
+                cohort.basis_zec *= 1.0 - removed;
+                cohort.basis_zai *= 1.0 - removed;
+            }
+            cohort.shares = now;
+        }
+
+        self.cohorts
+            .iter()
+            .map(|(name, c)| {
+                let value_zai = if total > 0.0 {
+                    c.shares / total * (amm.reserve_zai + amm.reserve_zec * price)
+                } else {
+                    0.0
+                };
+                let il_zai = c.realized_il_zai + c.open_il(price);
+                LpCohortMetrics {
+                    cohort: name.clone(),
+                    shares: c.shares,
+                    value_zai,
+                    fee_apr: annualize(&c.recent_fees, value_zai, block),
+                    fees_zai: c.fees_zai,
+                    penalties_zai: c.penalties_zai,
+                    il_zai,
+                    net_return_zai: c.fees_zai + c.penalties_zai + il_zai,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Fees in the window as an annual fraction of `value`.
+fn annualize(recent: &VecDeque<(u64, f64)>, value: f64, block: u64) -> f64 {
+    let Some(&(first, _)) = recent.front() else {
+        return 0.0;
+    };
+    if value <= 0.0 {
+        return 0.0;
+    }
+    let blocks = (block - first + 1) as f64;
+    let fees: f64 = recent.iter().map(|&(_, f)| f).sum();
+    fees / value * BLOCKS_PER_YEAR / blocks
+}
+
+/// Pool-wide totals across cohorts; `fee_apr` is value-weighted.
+pub fn pool_totals(cohorts: &[LpCohortMetrics]) -> LpCohortMetrics {
+    let mut total = LpCohortMetrics {
+        cohort: "pool".to_string(),
+        shares: 0.0,
+        value_zai: 0.0,
+        fee_apr: 0.0,
+        fees_zai: 0.0,
+        penalties_zai: 0.0,
+        il_zai: 0.0,
+        net_return_zai: 0.0,
+    };
+    for c in cohorts {
+        total.shares += c.shares;
+        total.value_zai += c.value_zai;
+        total.fee_apr += c.fee_apr * c.value_zai;
+        total.fees_zai += c.fees_zai;
+        total.penalties_zai += c.penalties_zai;
+        total.il_zai += c.il_zai;
+        total.net_return_zai += c.net_return_zai;
+    }
+    if total.value_zai > 0.0 {
+        total.fee_apr /= total.value_zai;
+    }
+    total
+}
@@ -1,28 +1,334 @@
 use chrono::NaiveDate;
-use clap::{Parser, Subcommand};
-use std::path::PathBuf;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
 
 use zai_sim::agents::*;
+use zai_sim::calibration::{self, CalibrationInputs};
+use zai_sim::data_fetcher::{Exchange, RetryPolicy, WickOrder};
+use zai_sim::depth;
+use zai_sim::determinism;
+use zai_sim::emission::{ChainCalendar, EmissionConfig};
+use zai_sim::error::ZaiSimError;
+use zai_sim::expectations;
+use zai_sim::faults::{Fault, FaultSchedule};
+use zai_sim::governance::{ParameterChange, ParameterSchedule};
+use zai_sim::historical::{self, PriceBound, PriceWindow};
+use zai_sim::liquidation::KeeperLiquidityConfig;
+use zai_sim::live::{self, LiveConfig};
 use zai_sim::output;
-use zai_sim::report;
+#[cfg(feature = "sqlite")]
+use zai_sim::output::SqliteStore;
+use zai_sim::perp::{self, PerpConfig};
+use zai_sim::persona::{self, Persona};
+use zai_sim::presets;
+use zai_sim::progress::{ProgressMode, ProgressMonitor};
+use zai_sim::regress;
+use zai_sim::report::{self, FailOn, ReportFormat, Verdict};
 use zai_sim::scenario::{Scenario, ScenarioConfig};
-use zai_sim::scenarios::ScenarioId;
-use zai_sim::sweep::SweepEngine;
+use zai_sim::scenario_file::{self, AgentRoster, ConfigSetting, ScenarioFile};
+use zai_sim::scenarios::{
+    parse_scenario_ids, ScenarioId, ScenarioMix, BLOCK_TIME_SECS, DEFAULT_BLOCKS,
+};
+use zai_sim::sensitivity;
+use zai_sim::serve::Dashboard;
+use zai_sim::snapshot;
+use zai_sim::sweep::{SamplingStrategy, SweepEngine, SweepRange};
+use zai_sim::volume::VolumeScalingConfig;
 
 #[derive(Parser)]
 #[command(name = "zai-sim", about = "Oracle-free CDP flatcoin simulator for Zcash")]
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+
+    /// Inline this local Chart.js build (e.g. chart.umd.min.js) into HTML
+    /// reports so they render without network access
+    #[arg(long, global = true, value_name = "CHART_JS")]
+    offline: Option<String>,
+
+    /// Print sweep progress as plain log lines instead of live progress
+    /// bars (the default when stderr is not a terminal, e.g. in CI)
+    #[arg(long, global = true)]
+    no_tui: bool,
+
+    /// Log liquidations, redemptions and breaker trips (-v), plus every
+    /// block (-vv), to stderr. RUST_LOG, if set, overrides this
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    verbose: u8,
+
+    /// Log line format
+    #[arg(long, global = true, value_enum, default_value = "text")]
+    log_format: OutputFormat,
+}
+
+/// Chart.js source to inline into reports, set once from `--offline`.
+static CHART_JS: OnceLock<String> = OnceLock::new();
+
+/// Save a Markdown report as-is, or rendered to PDF.
+fn save_markdown(md: &str, format: ReportFormat, path: &Path) -> Result<(), ZaiSimError> {
+    match format {
+        ReportFormat::Pdf => report::save_report(report::markdown_to_pdf(md), path),
+        _ => report::save_report(md, path),
+    }
+}
+
+/// Save an HTML report, inlining Chart.js when running `--offline`.
+fn save_html(html: &str, path: &Path) -> Result<(), ZaiSimError> {
+    match CHART_JS.get() {
+        Some(js) => report::save_report(report::inline_chart_js(html, js), path),
+        None => report::save_report(html, path),
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum OutputFormat {
+    Text,
+    Json,
+}
+
+/// Send library logs to stderr at the level `-v` asks for, or as RUST_LOG
+/// filters them (e.g. `RUST_LOG='zai_sim[block{block=412}]=debug'`).
+fn init_logging(verbose: u8, format: OutputFormat) {
+    use tracing_subscriber::EnvFilter;
+
+    let level = match verbose {
+        0 => "warn",
+        1 => "info",
+        2 => "debug",
+        _ => "trace",
+    };
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(level));
+    let logs = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr);
+    match format {
+        OutputFormat::Text => logs.init(),
+        OutputFormat::Json => logs.json().init(),
+    }
+}
+
+/// Agents to add to a run, beyond its arbitrageurs and miners.
+#[derive(Args)]
+struct AgentArgs {
+    /// Number of demand agents
+    #[arg(long, default_value = "0")]
+    demand_agents: usize,
+
+    /// Number of LP agents
+    #[arg(long, default_value = "0")]
+    lp_agents: usize,
+
+    /// Number of IL-aware LP agents
+    #[arg(long, default_value = "0")]
+    il_aware_lps: usize,
+
+    /// Number of CDP holders, each opening a vault at block 0
+    #[arg(long, default_value = "0")]
+    cdp_holders: usize,
+
+    /// Number of attackers
+    #[arg(long, default_value = "0")]
+    attackers: usize,
+
+    /// Number of basis arbitrageurs hedging AMM spot against the ZEC perp
+    #[arg(long, default_value = "0")]
+    basis_arbs: usize,
+
+    /// Number of owners each running several vaults at different ratios
+    #[arg(long, default_value = "0")]
+    vault_portfolios: usize,
+
+    /// Attacker strategy: a name (e.g. dump_and_revert) or a JSON object
+    /// with its type and fields (e.g. '{"type":"drip","zec_per_block":100,
+    /// "blocks":30,"unwind_blocks":3}')
+    #[arg(long)]
+    attack_strategy: Option<String>,
+
+    /// ZAI keepers hold to buy liquidated collateral directly (off unless
+    /// set)
+    #[arg(long)]
+    keeper_zai: Option<f64>,
+
+    /// Config overrides for one agent class as CLASS=JSON, repeatable (e.g.
+    /// --agent-config 'lp_agents={"il_threshold":0.03}'); CLASS keepers
+    /// overrides the keeper liquidity config
+    #[arg(long = "agent-config")]
+    agent_configs: Vec<String>,
+}
+
+impl AgentArgs {
+    /// Whether no agents are added or configured (keepers aside).
+    fn is_empty(&self) -> bool {
+        self.demand_agents
+            + self.lp_agents
+            + self.il_aware_lps
+            + self.cdp_holders
+            + self.attackers
+            + self.basis_arbs
+            + self.vault_portfolios
+            == 0
+            && self.attack_strategy.is_none()
+            && self.agent_configs.iter().all(|c| c.starts_with("keepers="))
+    }
+
+    /// Set up keepers in `config` and return these agents, plus `arbers`
+    /// arbitrageurs and `miners` miners, as a roster.
+    fn apply(
+        &self,
+        config: &mut ScenarioConfig,
+        arbers: usize,
+        miners: usize,
+    ) -> Result<AgentRoster, ZaiSimError> {
+        let mut overrides = std::collections::BTreeMap::new();
+        for spec in &self.agent_configs {
+            let (class, json) = spec.split_once('=').ok_or_else(|| {
+                ZaiSimError::Config(format!("--agent-config {}: expected CLASS=JSON", spec))
+            })?;
+            let value: serde_json::Value = serde_json::from_str(json)?;
+            if !value.is_object() {
+                return Err(ZaiSimError::Config(format!(
+                    "--agent-config {}: overrides must be a JSON object",
+                    class
+                )));
+            }
+            overrides.insert(class.to_string(), value);
+        }
+        if let Some(strategy) = &self.attack_strategy {
+            let strategy = if strategy.trim_start().starts_with('{') {
+                serde_json::from_str(strategy)?
+            } else {
+                serde_json::json!({ "type": strategy })
+            };
+            overrides
+                .entry("attackers".to_string())
+                .or_insert_with(|| serde_json::json!({}))["strategy"] = strategy;
+        }
+
+        let keepers = overrides.remove("keepers");
+        if self.keeper_zai.is_some() || keepers.is_some() {
+            let mut keeper = KeeperLiquidityConfig::default();
+            if let Some(zai) = self.keeper_zai {
+                keeper.initial_zai = zai;
+            }
+            if let Some(keepers) = keepers {
+                keeper = scenario_file::with_overrides(&keeper, &keepers)?;
+            }
+            config.liquidation_config.keeper_liquidity = Some(keeper);
+        }
+
+        let mut roster = AgentRoster::empty();
+        for (class, n) in [
+            ("arbers", arbers),
+            ("miners", miners),
+            ("demand_agents", self.demand_agents),
+            ("lp_agents", self.lp_agents),
+            ("il_aware_lps", self.il_aware_lps),
+            ("cdp_holders", self.cdp_holders),
+            ("attackers", self.attackers),
+            ("basis_arbs", self.basis_arbs),
+            ("vault_portfolios", self.vault_portfolios),
+        ] {
+            if n > 0 {
+                let o = overrides.remove(class).unwrap_or(serde_json::Value::Null);
+                *roster.class_mut(class)? = vec![o; n];
+            }
+        }
+        if let Some(class) = overrides.keys().next() {
+            roster.class_mut(class)?;
+            return Err(ZaiSimError::Config(format!(
+                "--agent-config {}: no {} agents to configure",
+                class, class
+            )));
+        }
+        Ok(roster)
+    }
+}
+
+/// Which rows of a price CSV to simulate, and at what cadence.
+#[derive(Args)]
+struct PriceArgs {
+    /// First block to simulate (a row, after resampling) or UTC date/time,
+    /// e.g. 2021-05-19 or 2021-05-19T12:00
+    #[arg(long)]
+    from: Option<PriceBound>,
+
+    /// Last block or UTC date/time to simulate, inclusive
+    #[arg(long)]
+    to: Option<PriceBound>,
+
+    /// Interpolate the CSV onto one price per interval, e.g. 75s for the
+    /// block time (default: one row per block, whatever its interval)
+    #[arg(long, value_parser = historical::parse_interval)]
+    resample: Option<f64>,
+}
+
+impl PriceArgs {
+    fn window(&self) -> PriceWindow {
+        PriceWindow {
+            from: self.from,
+            to: self.to,
+            resample_secs: self.resample,
+        }
+    }
+}
+
+/// Overrides of the scenario config a command starts from.
+#[derive(Args)]
+struct ConfigArgs {
+    /// YAML, TOML or JSON file of ScenarioConfig overrides (e.g.
+    /// configs/production.toml)
+    #[arg(long)]
+    config: Option<String>,
+
+    /// Config override KEY=VALUE, repeatable and applied after --config;
+    /// KEY is a sweep alias or a dotted ScenarioConfig path (e.g. --set
+    /// cdp_config.min_ratio=2.0 --set cdp_config.twap_kind=median)
+    #[arg(long = "set")]
+    settings: Vec<ConfigSetting>,
+}
+
+impl ConfigArgs {
+    /// `base` with the config file, then each setting, applied.
+    fn resolve(&self, base: ScenarioConfig) -> Result<ScenarioConfig, ZaiSimError> {
+        let mut config = match &self.config {
+            Some(path) => scenario_file::load_config(&PathBuf::from(path), &base)?,
+            None => base,
+        };
+        for setting in &self.settings {
+            setting.apply(&mut config)?;
+        }
+        Ok(config)
+    }
+
+    /// Like `resolve`, exiting with status 2 on a bad file or setting.
+    fn resolve_or_exit(&self, base: ScenarioConfig) -> ScenarioConfig {
+        self.resolve(base).unwrap_or_else(|e| {
+            eprintln!("Error in config overrides: {}", e);
+            std::process::exit(2);
+        })
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum PersonaKind {
+    Lp,
+    Vault,
 }
 
 #[derive(Subcommand)]
 enum Commands {
-    /// Fetch historical kline data from Binance
+    /// Fetch historical kline data from an exchange
     Fetch {
-        /// Trading pair (e.g., ZECUSDT)
-        #[arg(long, default_value = "ZECUSDT")]
-        pair: String,
+        /// Exchange to fetch from: binance, coinbase or kraken
+        #[arg(long, default_value = "binance")]
+        source: Exchange,
+
+        /// Trading pair in the venue's format (default: ZECUSDT, ZEC-USD or
+        /// ZECUSD); several, comma-separated or repeated, also write a
+        /// matrix CSV of their closes on shared timestamps
+        #[arg(long = "pair", value_delimiter = ',')]
+        pairs: Vec<String>,
 
         /// Start date (YYYY-MM-DD)
         #[arg(long)]
@@ -39,6 +345,60 @@ enum Commands {
         /// Output directory for CSV files
         #[arg(long, default_value = "data")]
         output_dir: String,
+
+        /// Retries per request on rate limiting (429) and server errors
+        #[arg(long, default_value = "5")]
+        max_retries: u32,
+    },
+
+    /// Fetch ZEC perpetual funding rates and open interest from Binance futures
+    FetchFunding {
+        /// Perp symbol
+        #[arg(long, default_value = "ZECUSDT")]
+        pair: String,
+
+        /// Start date (YYYY-MM-DD)
+        #[arg(long)]
+        start: String,
+
+        /// End date (YYYY-MM-DD)
+        #[arg(long)]
+        end: String,
+
+        /// Open interest sampling period (5m, 15m, 30m, 1h, 2h, 4h, 6h, 12h or 1d)
+        #[arg(long, default_value = "1h")]
+        period: String,
+
+        /// Output directory for CSV files
+        #[arg(long, default_value = "data")]
+        output_dir: String,
+
+        /// Retries per request on rate limiting (429) and server errors
+        #[arg(long, default_value = "5")]
+        max_retries: u32,
+    },
+
+    /// Sample Binance order-book depth and fit AMM/external market impact to it
+    FetchDepth {
+        /// Binance symbol
+        #[arg(long, default_value = "ZECUSDT")]
+        pair: String,
+
+        /// Snapshots to take (0 = only refit the existing file)
+        #[arg(long, default_value = "60")]
+        samples: usize,
+
+        /// Seconds between snapshots
+        #[arg(long, default_value = "60")]
+        every_secs: u64,
+
+        /// Order-book levels per side
+        #[arg(long, default_value = "1000")]
+        limit: u32,
+
+        /// Depth curve CSV; new snapshots are appended
+        #[arg(long, default_value = "data/depth_zecusdt.csv")]
+        output: String,
     },
 
     /// Run a single simulation scenario
@@ -47,6 +407,9 @@ enum Commands {
         #[arg(long)]
         prices: String,
 
+        #[command(flatten)]
+        price_window: PriceArgs,
+
         /// Output metrics CSV
         #[arg(long, default_value = "output/metrics.csv")]
         output: String,
@@ -58,6 +421,64 @@ enum Commands {
         /// Number of miners
         #[arg(long, default_value = "1")]
         miners: usize,
+
+        /// Write a checkpoint every N blocks (0 = off)
+        #[arg(long, default_value = "0")]
+        checkpoint_every: u64,
+
+        /// Checkpoint file path
+        #[arg(long, default_value = "output/checkpoint.json")]
+        checkpoint: String,
+
+        /// Resume from a checkpoint file instead of starting at block 1
+        #[arg(long)]
+        resume: Option<String>,
+
+        /// Step each block through its candle's OHLC path as intrablock
+        /// sub-steps: ohlc (open, high, low, close) or random (high or low first)
+        #[arg(long)]
+        wicks: Option<WickOrder>,
+
+        /// Date of block 1 (YYYY-MM-DD); miners then follow the halving
+        /// schedule instead of a static block reward
+        #[arg(long)]
+        start_date: Option<NaiveDate>,
+
+        /// Governance parameter change BLOCK:PARAM=VALUE, repeatable
+        /// (e.g. --change 400:min_ratio=2.5 --change 800:swap_fee=0.001)
+        #[arg(long = "change")]
+        changes: Vec<ParameterChange>,
+
+        /// Scheduled fault KIND:FROM-TO with KIND halt, oracle or amm,
+        /// repeatable (e.g. --fault halt:400-600 --fault oracle:650-700)
+        #[arg(long = "fault")]
+        faults: Vec<Fault>,
+
+        /// Scale miner sales, demand buying and arber replenishment by each
+        /// candle's volume relative to the median (needs a volume or
+        /// volume_from column)
+        #[arg(long)]
+        volume_scaling: bool,
+
+        /// Throughput follows (volume / median) ^ exponent; below 1 damps swings
+        #[arg(long, default_value = "1.0", requires = "volume_scaling")]
+        volume_exponent: f64,
+
+        /// Perp funding CSV from `fetch-funding`, replayed one rate per
+        /// 8-hour interval for basis arbitrageurs
+        #[arg(long)]
+        funding: Option<String>,
+
+        /// Open interest CSV from `fetch-funding`; its mean sets how far
+        /// arbitrage positions move funding
+        #[arg(long, requires = "funding")]
+        open_interest: Option<String>,
+
+        #[command(flatten)]
+        agents: AgentArgs,
+
+        #[command(flatten)]
+        config: ConfigArgs,
     },
 
     /// Run a parameter sweep
@@ -66,11 +487,14 @@ enum Commands {
         #[arg(long)]
         prices: String,
 
+        #[command(flatten)]
+        price_window: PriceArgs,
+
         /// Output directory for sweep results
         #[arg(long, default_value = "output/sweep")]
         output_dir: String,
 
-        /// Parameter to sweep (e.g., "min_ratio")
+        /// Parameter to sweep (e.g., "min_ratio", "amm_depth")
         #[arg(long)]
         param: String,
 
@@ -79,11 +503,44 @@ enum Commands {
         values: String,
     },
 
-    /// Run a stress scenario (1-13, or "all")
+    /// Run a stress scenario (1-13, "all", or a YAML/TOML scenario file)
     Stress {
-        /// Scenario ID (1-13) or 0 for all
-        #[arg(long)]
-        id: u8,
+        /// Scenario ID (1-13), 0 for all, a list with ranges run under one
+        /// index (e.g. 2,3,7-10), or a composition: 2+7 chains scenarios,
+        /// 3~13 overlays them
+        #[arg(long, required_unless_present_any = ["file", "historical"])]
+        id: Option<String>,
+
+        /// Replay a historical event preset (covid2020, black-thursday,
+        /// may2021, luna2022, bear2022, ftx2022, rally2024) at its real
+        /// price level with the standard historical roster; a window not
+        /// in --cache-dir is fetched from Binance first
+        #[arg(long, conflicts_with_all = ["id", "file", "prices"])]
+        historical: Option<String>,
+
+        /// Directory historical presets are read from and fetched into
+        #[arg(long, default_value = "data")]
+        cache_dir: String,
+
+        /// Run a scenario defined in a YAML or TOML file instead of a
+        /// built-in one; its price segments set the block count
+        #[arg(long, conflicts_with = "id")]
+        file: Option<String>,
+
+        /// Price CSV (with a close column, e.g. from `fetch` or data/) to
+        /// run the scenarios over instead of their generated paths; its
+        /// length sets the block count
+        #[arg(long, conflicts_with = "file")]
+        prices: Option<String>,
+
+        /// Keep --prices at their own level instead of scaling the path to
+        /// start at the initial redemption price
+        #[arg(long, requires = "prices")]
+        no_rescale: bool,
+
+        // Slicing and resampling of --prices
+        #[command(flatten)]
+        price_window: PriceArgs,
 
         /// Number of blocks to simulate
         #[arg(long, default_value = "1000")]
@@ -96,6 +553,172 @@ enum Commands {
         /// Random seed
         #[arg(long, default_value = "42")]
         seed: u64,
+
+        /// Result format on stdout (json moves progress output to stderr)
+        #[arg(long, value_enum, default_value = "text")]
+        format: OutputFormat,
+
+        /// Exit non-zero at this verdict: hard, soft or never
+        #[arg(long, default_value = "hard")]
+        fail_on: FailOn,
+
+        /// Report format: html, markdown (concise summary for issues and
+        /// PRs) or pdf (the same summary, printable)
+        #[arg(long, default_value = "html")]
+        report_format: ReportFormat,
+
+        /// Write a state snapshot every N blocks to snapshots.csv (0 = off)
+        #[arg(long, default_value = "0")]
+        snapshot_interval: u64,
+
+        /// Record per-agent balances, PnL and actions to agent_metrics.csv
+        #[arg(long)]
+        agent_metrics: bool,
+
+        /// Also store runs in this SQLite results database
+        #[arg(long)]
+        db: Option<String>,
+
+        /// Scheduled fault KIND:FROM-TO with KIND halt, oracle or amm,
+        /// repeatable; applies to built-in scenarios, compositions and
+        /// scenario files that set no `faults` of their own
+        #[arg(long = "fault")]
+        faults: Vec<Fault>,
+
+        // Added to built-in scenarios on top of their own agents
+        #[command(flatten)]
+        agents: AgentArgs,
+
+        #[command(flatten)]
+        config: ConfigArgs,
+    },
+
+    /// List the stress scenarios: what each simulates, its price path,
+    /// default agents and run length
+    Scenarios {
+        /// Describe only this scenario (1-13 or a name)
+        #[arg(long)]
+        id: Option<String>,
+
+        /// Number of blocks to describe the price path over
+        #[arg(long, default_value_t = DEFAULT_BLOCKS)]
+        blocks: usize,
+
+        /// Random seed (only liquidity_crisis's path depends on it)
+        #[arg(long, default_value = "42")]
+        seed: u64,
+
+        /// Listing format on stdout
+        #[arg(long, value_enum, default_value = "text")]
+        format: OutputFormat,
+    },
+
+    /// Diff two snapshots.csv files and report the earliest divergence
+    Diff {
+        /// Baseline snapshots CSV
+        #[arg(long)]
+        left: String,
+
+        /// Candidate snapshots CSV
+        #[arg(long)]
+        right: String,
+
+        /// Relative tolerance below which values are considered equal
+        #[arg(long, default_value = "1e-9")]
+        tolerance: f64,
+    },
+
+    /// Compare two saved runs (stress output directories) in an HTML report
+    Compare {
+        /// Baseline run directory
+        #[arg(long)]
+        a: String,
+
+        /// Candidate run directory
+        #[arg(long)]
+        b: String,
+
+        /// Output HTML file
+        #[arg(long, default_value = "comparison.html")]
+        output: String,
+    },
+
+    /// Run a scenario repeatedly and require bit-identical metrics
+    VerifyDeterminism {
+        /// Scenario ID (1-13)
+        #[arg(long, default_value = "1")]
+        id: u8,
+
+        /// Number of blocks to simulate
+        #[arg(long, default_value = "1000")]
+        blocks: usize,
+
+        /// Random seed
+        #[arg(long, default_value = "42")]
+        seed: u64,
+
+        /// Comma-separated rayon pool sizes to rerun under (e.g., "1,4,8");
+        /// each pool runs that many copies concurrently
+        #[arg(long)]
+        threads: Option<String>,
+    },
+
+    /// Follow one persona across all 13 stress scenarios
+    Persona {
+        /// Persona to follow: lp or vault
+        #[arg(long, value_enum)]
+        kind: PersonaKind,
+
+        /// LP capital in ZAI
+        #[arg(long, default_value = "100000")]
+        capital: f64,
+
+        /// Vault collateral ratio (e.g., 2.3 = 230%)
+        #[arg(long, default_value = "2.3")]
+        cr: f64,
+
+        /// Vault debt in ZAI
+        #[arg(long, default_value = "10000")]
+        debt: f64,
+
+        /// ZEC the vault owner keeps in reserve for top-ups
+        #[arg(long, default_value = "0")]
+        reserve: f64,
+
+        /// Number of blocks per scenario
+        #[arg(long, default_value = "1000")]
+        blocks: usize,
+
+        /// Runs per scenario, with seeds starting at --seed
+        #[arg(long, default_value = "1")]
+        runs: u64,
+
+        /// First random seed
+        #[arg(long, default_value = "42")]
+        seed: u64,
+
+        /// Output HTML report
+        #[arg(long, default_value = "output/persona.html")]
+        output: String,
+    },
+
+    /// Back-solve agent parameter ranges from historical price data
+    Calibrate {
+        /// Hourly ZECUSDT CSV (CryptoCompare format)
+        #[arg(long)]
+        data: String,
+
+        /// Observed DEX volume in ZEC per day
+        #[arg(long)]
+        dex_volume: f64,
+
+        /// AMM swap fee (e.g., 0.003 = 0.3%)
+        #[arg(long, default_value = "0.003")]
+        swap_fee: f64,
+
+        /// Output HTML report
+        #[arg(long, default_value = "output/calibration.html")]
+        output: String,
     },
 
     /// Run the full 4-stage parameter sweep
@@ -111,12 +734,213 @@ enum Commands {
         /// Random seed
         #[arg(long, default_value = "42")]
         seed: u64,
+
+        /// Also store results in this SQLite results database
+        #[arg(long)]
+        db: Option<String>,
+
+        #[command(flatten)]
+        config: ConfigArgs,
+    },
+
+    /// Sweep several parameters at once with grid, random or Latin hypercube sampling
+    SampleSweep {
+        /// Parameter range as name=min:max (repeatable); name is a sweep alias
+        /// or a dotted ScenarioConfig path such as cdp_config.debt_floor
+        #[arg(long = "range", required = true)]
+        ranges: Vec<SweepRange>,
+
+        /// Sampling strategy: grid, random or lhs
+        #[arg(long, default_value = "lhs")]
+        strategy: SamplingStrategy,
+
+        /// Number of parameter combinations to evaluate
+        #[arg(long, default_value = "50")]
+        samples: usize,
+
+        /// Comma-separated scenario IDs (1-13) or "all"
+        #[arg(long, default_value = "all")]
+        scenarios: String,
+
+        /// Number of blocks per scenario run
+        #[arg(long, default_value = "500")]
+        blocks: usize,
+
+        /// Random seed for sampling and scenario runs
+        #[arg(long, default_value = "42")]
+        seed: u64,
+
+        /// Output directory
+        #[arg(long, default_value = "output/sample_sweep")]
+        output_dir: String,
+
+        /// Also store results in this SQLite results database
+        #[arg(long)]
+        db: Option<String>,
+    },
+
+    /// Sobol sensitivity of outcomes to parameter ranges (Saltelli sample)
+    Sensitivity {
+        /// Parameter range as name=min:max (repeatable); name is a sweep alias
+        /// or a dotted ScenarioConfig path such as cdp_config.debt_floor
+        #[arg(long = "range", required = true)]
+        ranges: Vec<SweepRange>,
+
+        /// Comma-separated summary metrics to analyze
+        #[arg(long, default_value = "total_bad_debt,max_peg_deviation")]
+        outcomes: String,
+
+        /// Base sample count N; runs N x (parameters + 2) configurations
+        #[arg(long, default_value = "64")]
+        samples: usize,
+
+        /// Comma-separated scenario IDs (1-13) or "all"
+        #[arg(long, default_value = "all")]
+        scenarios: String,
+
+        /// Number of blocks per scenario run
+        #[arg(long, default_value = "500")]
+        blocks: usize,
+
+        /// Random seed for sampling and scenario runs
+        #[arg(long, default_value = "42")]
+        seed: u64,
+
+        /// Output CSV of indices
+        #[arg(long, default_value = "output/sensitivity.csv")]
+        output: String,
+    },
+
+    /// Shadow-run a scenario against Binance's live ZEC trade feed
+    Live {
+        /// Binance symbol to stream
+        #[arg(long, default_value = "ZECUSDT")]
+        symbol: String,
+
+        /// Number of arbitrageurs
+        #[arg(long, default_value = "1")]
+        arbers: usize,
+
+        /// Number of miners
+        #[arg(long, default_value = "1")]
+        miners: usize,
+
+        /// Wall-clock seconds per simulated block
+        #[arg(long, default_value = "75")]
+        block_time: u64,
+
+        /// Print rolling stats every N blocks
+        #[arg(long, default_value = "12")]
+        report_every: u64,
+
+        /// Blocks covered by the rolling stats
+        #[arg(long, default_value = "48")]
+        window: usize,
+    },
+
+    /// Query a SQLite results database
+    Query {
+        /// SQLite results database
+        #[arg(long)]
+        db: String,
+
+        /// Raw SQL to run instead of the summary aggregation
+        #[arg(long)]
+        sql: Option<String>,
+
+        /// Summary metric to aggregate (e.g., mean_peg_deviation)
+        #[arg(long, default_value = "mean_peg_deviation")]
+        metric: String,
+
+        /// Run column to group by: label, scenario, seed or verdict
+        #[arg(long, default_value = "scenario")]
+        by: String,
+    },
+
+    /// Serve an output directory's runs, reports and summaries over HTTP
+    Serve {
+        /// Output directory to serve
+        #[arg(long, default_value = "output")]
+        dir: String,
+
+        /// Address to listen on
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        addr: String,
+    },
+
+    /// Rerun all 13 stress scenarios and compare their summaries against a
+    /// stored baseline
+    Regress {
+        /// Baseline directory (holding baseline.json)
+        #[arg(long)]
+        baseline: String,
+
+        /// Record a new baseline instead of comparing
+        #[arg(long)]
+        update: bool,
+
+        /// Blocks per scenario when recording (comparisons use the
+        /// baseline's)
+        #[arg(long, default_value = "1000")]
+        blocks: usize,
+
+        /// Random seed when recording (comparisons use the baseline's)
+        #[arg(long, default_value = "42")]
+        seed: u64,
+
+        /// Allowed change relative to the baseline value
+        #[arg(long, default_value = "1e-9")]
+        rel_tol: f64,
+
+        /// Allowed absolute change on top of the relative tolerance
+        #[arg(long, default_value = "1e-12")]
+        abs_tol: f64,
     },
 }
 
-fn load_prices_from_csv(path: &str) -> Result<Vec<f64>, Box<dyn std::error::Error>> {
-    let klines = zai_sim::data_fetcher::load_csv(std::path::Path::new(path))?;
-    Ok(klines.iter().map(|k| k.close).collect())
+/// Per-block close prices of a CSV within `window`.
+fn load_prices_from_csv(path: &str, window: &PriceWindow) -> Result<Vec<f64>, ZaiSimError> {
+    let rows = historical::load_timed_prices(Path::new(path))?;
+    if let (None, Some(secs)) = (window.resample_secs, historical::row_interval_secs(&rows)) {
+        if (secs - BLOCK_TIME_SECS).abs() > 1.0 {
+            tracing::warn!(
+                "{} has rows {} s apart, each simulated as one {} s block; pass --resample 75s to match the block time",
+                path,
+                secs,
+                BLOCK_TIME_SECS
+            );
+        }
+    }
+    Ok(window.apply(&rows)?.into_iter().map(|(_, p)| p).collect())
+}
+
+/// Per-block candle volumes, windowed and resampled as the prices are.
+fn load_volumes_from_csv(path: &str, window: &PriceWindow) -> Result<Vec<f64>, ZaiSimError> {
+    let rows = historical::load_timed_volumes(Path::new(path))?;
+    Ok(window.apply(&rows)?.into_iter().map(|(_, v)| v).collect())
+}
+
+/// Per-block price paths: the close alone, or the candle's OHLC wick path.
+fn load_price_paths_from_csv(
+    path: &str,
+    wicks: Option<WickOrder>,
+    window: &PriceWindow,
+) -> Result<Vec<Vec<f64>>, ZaiSimError> {
+    let Some(order) = wicks else {
+        let prices = load_prices_from_csv(path, window)?;
+        return Ok(prices.into_iter().map(|p| vec![p]).collect());
+    };
+    if window.resample_secs.is_some() {
+        return Err(ZaiSimError::Config(
+            "--wicks steps through whole candles and can't be combined with --resample".into(),
+        ));
+    }
+    let klines = zai_sim::data_fetcher::load_csv(Path::new(path))?;
+    let timestamps: Vec<i64> = klines.iter().map(|k| k.timestamp_ms as i64).collect();
+    let range = window
+        .range(&timestamps)
+        .ok_or_else(|| ZaiSimError::Parse(format!("No prices in {} within the window", path)))?;
+    Ok(zai_sim::data_fetcher::ohlc_paths(&klines[range], order, 42))
 }
 
 fn run_scenario(
@@ -125,6 +949,12 @@ fn run_scenario(
     arber_count: usize,
     miner_count: usize,
 ) -> Scenario {
+    let mut scenario = build_scenario(config, arber_count, miner_count);
+    scenario.run(prices);
+    scenario
+}
+
+fn build_scenario(config: &ScenarioConfig, arber_count: usize, miner_count: usize) -> Scenario {
     let mut scenario = Scenario::new(config);
 
     for _ in 0..arber_count {
@@ -137,8 +967,6 @@ fn run_scenario(
             .miners
             .push(MinerAgent::new(MinerAgentConfig::default()));
     }
-
-    scenario.run(prices);
     scenario
 }
 
@@ -161,58 +989,183 @@ fn id_to_scenario(id: u8) -> Option<ScenarioId> {
     }
 }
 
+/// Parse a comma-separated list of scenario IDs, or "all".
+fn parse_scenario_list(list: &str) -> Vec<ScenarioId> {
+    parse_scenario_ids(list).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(2);
+    })
+}
+
+/// Progress output: stdout for text runs, stderr when stdout carries JSON.
+fn progress(format: OutputFormat, msg: &str) {
+    match format {
+        OutputFormat::Text => println!("{}", msg),
+        OutputFormat::Json => eprintln!("{}", msg),
+    }
+}
+
+/// Stands in for the results store when built without the `sqlite` feature,
+/// so `--db` fails loudly instead of being ignored.
+#[cfg(not(feature = "sqlite"))]
+enum SqliteStore {}
+
+#[cfg(not(feature = "sqlite"))]
+impl SqliteStore {
+    fn open(_path: &Path) -> Result<Self, ZaiSimError> {
+        Err(ZaiSimError::Config(
+            "SQLite results store needs a build with `--features sqlite`".to_string(),
+        ))
+    }
+
+    fn save_run(
+        &self,
+        _label: &str,
+        _scenario_name: &str,
+        _seed: u64,
+        _scenario: &Scenario,
+        _target_price: f64,
+    ) -> Result<i64, ZaiSimError> {
+        match *self {}
+    }
+
+    fn save_sweep_results(
+        &self,
+        _label: &str,
+        _results: &[zai_sim::sweep::SweepResult],
+    ) -> Result<(), ZaiSimError> {
+        match *self {}
+    }
+}
+
+/// Where and how stress runs write their results.
+struct StressOutput<'a> {
+    dir: &'a str,
+    format: OutputFormat,
+    report_format: ReportFormat,
+    store: Option<&'a SqliteStore>,
+    /// Files the runs' prices came from, hashed into each manifest
+    price_sources: Vec<PathBuf>,
+}
+
 fn run_stress_scenario(
     sid: ScenarioId,
+    config: &ScenarioConfig,
+    agents: &AgentRoster,
+    prices: Option<&[f64]>,
     blocks: usize,
     seed: u64,
-    output_dir: &str,
-) -> Option<(String, report::PassFailResult, output::SummaryMetrics)> {
-    let config = ScenarioConfig::default();
-    let target = config.initial_redemption_price;
-    println!(
-        "  [{:>2}] {} — {}",
-        sid as u8,
-        sid.name(),
-        sid.description()
+    out: &StressOutput,
+) -> Option<(ScenarioId, report::PassFailResult, output::SummaryMetrics)> {
+    progress(
+        out.format,
+        &format!("  [{:>2}] {} — {}", sid as u8, sid.name(), sid.description()),
     );
 
-    let scenario =
-        zai_sim::scenarios::run_stress(sid, &config, blocks, seed);
+    // The roster was checked by building it once before any run
+    let setup = |s: &mut Scenario| {
+        agents
+            .add_to(s, seed)
+            .expect("agent roster checked before running")
+    };
+    let scenario = match prices {
+        Some(prices) => zai_sim::scenarios::run_stress_on(sid, config, prices, seed, setup),
+        None => zai_sim::scenarios::run_stress_with(sid, config, blocks, seed, setup),
+    };
 
-    let dir = PathBuf::from(output_dir).join(sid.name());
-    let _ = output::save_all(&scenario, &config, target, &dir);
+    let (verdict, summary) = save_stress_run(sid.name(), &scenario, config, seed, out);
+    Some((sid, verdict, summary))
+}
 
-    // Generate HTML report
-    let html = report::generate_report(&scenario.metrics, &config, sid.name(), target);
-    let html_path = PathBuf::from(output_dir).join(format!("{}.html", sid.name()));
-    let _ = report::save_report(&html, &html_path);
+/// Save a finished stress run's outputs and report, print its one-line
+/// result and return its verdict and summary.
+fn save_stress_run(
+    name: &str,
+    scenario: &Scenario,
+    config: &ScenarioConfig,
+    seed: u64,
+    out: &StressOutput,
+) -> (report::PassFailResult, output::SummaryMetrics) {
+    let target = config.initial_redemption_price;
+    let dir = PathBuf::from(out.dir).join(name);
+    let manifest = out
+        .price_sources
+        .iter()
+        .try_fold(output::Manifest::new(config, &[seed]), |m, path| {
+            m.with_price_source(path)
+        });
+    let _ =
+        manifest.and_then(|m| output::save_all_with_manifest(scenario, config, target, &dir, &m));
+    if let Some(store) = out.store {
+        if let Err(e) = store.save_run("stress", name, seed, scenario, target) {
+            eprintln!("Error storing {} in database: {}", name, e);
+        }
+    }
+
+    let report_path =
+        PathBuf::from(out.dir).join(format!("{}.{}", name, out.report_format.extension()));
+    let _ = match out.report_format {
+        ReportFormat::Html => save_html(
+            &report::generate_report_with_liquidations(
+                &scenario.metrics,
+                &scenario.liquidation_engine.history,
+                config,
+                name,
+                target,
+            ),
+            &report_path,
+        ),
+        ReportFormat::Markdown | ReportFormat::Pdf => {
+            let md = report::generate_markdown_summary(&scenario.metrics, config, name, target);
+            save_markdown(&md, out.report_format, &report_path)
+        }
+    };
 
     let summary = output::compute_summary(&scenario.metrics, target);
     let verdict = report::evaluate_pass_fail(&scenario.metrics, target);
 
-    println!(
-        "       [{}] blocks={}, peg_dev={:.4}, liqs={}, bad_debt={:.2} -> {}",
-        verdict.overall.label(),
-        summary.total_blocks,
-        summary.mean_peg_deviation,
-        summary.total_liquidations,
-        summary.total_bad_debt,
-        dir.display()
+    progress(
+        out.format,
+        &format!(
+            "       [{}] blocks={}, peg_dev={:.4}, liqs={}, bad_debt={:.2} -> {}",
+            verdict.overall.label(),
+            summary.total_blocks,
+            summary.mean_peg_deviation,
+            summary.total_liquidations,
+            summary.total_bad_debt,
+            dir.display()
+        ),
     );
 
-    Some((sid.name().to_string(), verdict, summary))
+    (verdict, summary)
 }
 
 fn main() {
     let cli = Cli::parse();
+    init_logging(cli.verbose, cli.log_format);
+    if let Some(path) = &cli.offline {
+        match report::load_chart_js(Path::new(path)) {
+            Ok(js) => {
+                let _ = CHART_JS.set(js);
+            }
+            Err(e) => {
+                eprintln!("Error loading Chart.js for --offline: {}", e);
+                std::process::exit(2);
+            }
+        }
+    }
+
+    let progress_mode = ProgressMode::detect(cli.no_tui);
 
     match cli.command {
         Commands::Fetch {
-            pair,
+            source,
+            pairs,
             start,
             end,
             interval,
             output_dir,
+            max_retries,
         } => {
             let start_date = NaiveDate::parse_from_str(&start, "%Y-%m-%d")
                 .expect("Invalid start date (use YYYY-MM-DD)");
@@ -230,46 +1183,279 @@ fn main() {
                 .and_utc()
                 .timestamp_millis() as u64;
 
+            let source = source.source();
+            let retry = RetryPolicy {
+                max_retries,
+                ..RetryPolicy::default()
+            };
+            let csv_path = |pair: &str| {
+                let filename =
+                    format!("{}_{}_{}_{}.csv", pair.to_lowercase(), interval, start, end);
+                PathBuf::from(&output_dir).join(filename)
+            };
+
+            if pairs.len() > 1 {
+                println!(
+                    "Fetching {} {} from {} to {} on {}...",
+                    pairs.join(", "),
+                    interval,
+                    start,
+                    end,
+                    source.name()
+                );
+                let paths: Vec<PathBuf> = pairs.iter().map(|p| csv_path(p)).collect();
+                let jobs: Vec<(&str, &Path)> = pairs
+                    .iter()
+                    .map(String::as_str)
+                    .zip(paths.iter().map(PathBuf::as_path))
+                    .collect();
+                let matrix_path = csv_path(&format!("matrix_{}", pairs.join("_")));
+                let fetched = zai_sim::data_fetcher::fetch_matrix(
+                    source.as_ref(),
+                    &jobs,
+                    &interval,
+                    start_ms,
+                    end_ms,
+                    &retry,
+                )
+                .and_then(|(matrix, summaries)| {
+                    matrix.save(&matrix_path)?;
+                    Ok((matrix, summaries))
+                });
+                match fetched {
+                    Ok((matrix, summaries)) => {
+                        for ((pair, path), summary) in jobs.iter().zip(&summaries) {
+                            println!(
+                                "  {}: {} candles ({} resumed) -> {}",
+                                pair,
+                                summary.existing + summary.fetched,
+                                summary.existing,
+                                path.display()
+                            );
+                        }
+                        println!(
+                            "Aligned {} rows of {} pairs to {}",
+                            matrix.len(),
+                            matrix.pairs.len(),
+                            matrix_path.display()
+                        );
+                    }
+                    Err(e) => eprintln!("Error fetching data: {} (rerun to resume)", e),
+                }
+                return;
+            }
+
+            let pair = pairs
+                .into_iter()
+                .next()
+                .unwrap_or_else(|| source.default_pair().to_string());
             println!(
-                "Fetching {} {} from {} to {}...",
-                pair, interval, start, end
+                "Fetching {} {} from {} to {} on {}...",
+                pair,
+                interval,
+                start,
+                end,
+                source.name()
             );
+            let path = csv_path(&pair);
+
+            // Candles are appended as they arrive; rerunning after a failure
+            // continues from the last saved candle
+            match zai_sim::data_fetcher::fetch_to_csv(
+                source.as_ref(),
+                &pair,
+                &interval,
+                start_ms,
+                end_ms,
+                &retry,
+                &path,
+            ) {
+                Ok(summary) => {
+                    if summary.existing > 0 {
+                        println!("Resumed after {} saved candles", summary.existing);
+                    }
+                    println!("Fetched {} candles", summary.fetched);
+                    println!("Saved to {}", path.display());
+                }
+                Err(e) => eprintln!(
+                    "Error fetching data: {} (rerun to resume from {})",
+                    e,
+                    path.display()
+                ),
+            }
+        }
 
-            match zai_sim::data_fetcher::fetch_range(&pair, &interval, start_ms, end_ms) {
-                Ok(klines) => {
-                    println!("Fetched {} candles", klines.len());
+        Commands::FetchFunding {
+            pair,
+            start,
+            end,
+            period,
+            output_dir,
+            max_retries,
+        } => {
+            let start_date = NaiveDate::parse_from_str(&start, "%Y-%m-%d")
+                .expect("Invalid start date (use YYYY-MM-DD)");
+            let end_date = NaiveDate::parse_from_str(&end, "%Y-%m-%d")
+                .expect("Invalid end date (use YYYY-MM-DD)");
+            let start_ms = start_date
+                .and_hms_opt(0, 0, 0)
+                .unwrap()
+                .and_utc()
+                .timestamp_millis() as u64;
+            let end_ms = end_date
+                .and_hms_opt(23, 59, 59)
+                .unwrap()
+                .and_utc()
+                .timestamp_millis() as u64;
+            let retry = RetryPolicy {
+                max_retries,
+                ..RetryPolicy::default()
+            };
+            let csv_path = |kind: &str| {
+                let filename = format!("{}_{}_{}_{}.csv", kind, pair.to_lowercase(), start, end);
+                PathBuf::from(&output_dir).join(filename)
+            };
 
-                    let filename = format!(
-                        "{}_{}_{}_{}.csv",
-                        pair.to_lowercase(),
-                        interval,
-                        start,
-                        end
+            println!("Fetching {} funding from {} to {}...", pair, start, end);
+            let path = csv_path("funding");
+            match zai_sim::data_fetcher::fetch_funding(&pair, start_ms, end_ms, &retry)
+                .and_then(|rates| perp::save_funding(&rates, &path).map(|_| rates))
+            {
+                Ok(rates) => {
+                    let mean =
+                        rates.iter().map(|r| r.rate).sum::<f64>() / rates.len().max(1) as f64;
+                    println!(
+                        "Saved {} funding payments (mean {:.4}% per interval) to {}",
+                        rates.len(),
+                        mean * 100.0,
+                        path.display()
                     );
-                    let path = PathBuf::from(&output_dir).join(&filename);
+                }
+                Err(e) => {
+                    eprintln!("Error fetching funding: {}", e);
+                    std::process::exit(1);
+                }
+            }
+
+            // Binance serves only the last 30 days of open interest, so an
+            // older range has none
+            let path = csv_path("open_interest");
+            match zai_sim::data_fetcher::fetch_open_interest(
+                &pair, &period, start_ms, end_ms, &retry,
+            ) {
+                Ok(oi) if oi.is_empty() => {
+                    tracing::warn!(
+                        "No open interest for {} in range (Binance keeps 30 days)",
+                        pair
+                    )
+                }
+                Ok(oi) => match perp::save_open_interest(&oi, &path) {
+                    Ok(()) => println!(
+                        "Saved {} open interest points to {}",
+                        oi.len(),
+                        path.display()
+                    ),
+                    Err(e) => eprintln!("Error saving open interest: {}", e),
+                },
+                Err(e) => eprintln!("Error fetching open interest: {}", e),
+            }
+        }
 
-                    match zai_sim::data_fetcher::save_csv(&klines, &path) {
-                        Ok(()) => println!("Saved to {}", path.display()),
-                        Err(e) => eprintln!("Error saving CSV: {}", e),
+        Commands::FetchDepth {
+            pair,
+            samples,
+            every_secs,
+            limit,
+            output,
+        } => {
+            let path = PathBuf::from(&output);
+            for i in 0..samples {
+                if i > 0 {
+                    std::thread::sleep(std::time::Duration::from_secs(every_secs));
+                }
+                let curve = zai_sim::data_fetcher::fetch_depth(&pair, limit).and_then(|snapshot| {
+                    snapshot.curve(&depth::DEFAULT_LEVELS_BPS).ok_or_else(|| {
+                        ZaiSimError::Exchange(format!("{} order book is one-sided", pair))
+                    })
+                });
+                match curve.and_then(|c| depth::append_curves(std::slice::from_ref(&c), &path).map(|_| c)) {
+                    Ok(curve) => {
+                        let one_pct = curve.points.iter().find(|p| p.bps == 100.0);
+                        println!(
+                            "  [{}/{}] mid {:.2}, {:.0} bid / {:.0} ask within 1%",
+                            i + 1,
+                            samples,
+                            curve.mid,
+                            one_pct.map_or(0.0, |p| p.bid_notional),
+                            one_pct.map_or(0.0, |p| p.ask_notional),
+                        );
                     }
+                    Err(e) => eprintln!("  [{}/{}] Error sampling depth: {}", i + 1, samples, e),
+                }
+            }
+
+            let fit =
+                depth::load_curves(&path).and_then(|curves| calibration::calibrate_depth(&curves));
+            match fit {
+                Ok(fit) => {
+                    println!(
+                        "Fitted {} snapshots from {} (median mid {:.2})",
+                        fit.snapshots,
+                        path.display(),
+                        fit.mid
+                    );
+                    println!("  external market depth_zai  {:.0} per 1%", fit.depth_zai);
+                    println!(
+                        "  AMM reserves               {:.0} ZEC / {:.0} ZAI",
+                        fit.amm_zec_reserve, fit.amm_zai_reserve
+                    );
+                }
+                Err(e) => {
+                    eprintln!("Error fitting depth from {}: {}", path.display(), e);
+                    std::process::exit(1);
                 }
-                Err(e) => eprintln!("Error fetching data: {}", e),
             }
         }
 
         Commands::Run {
             prices,
+            price_window,
             output,
             arbers,
             miners,
+            checkpoint_every,
+            checkpoint,
+            resume,
+            wicks,
+            start_date,
+            changes,
+            faults,
+            volume_scaling,
+            volume_exponent,
+            funding,
+            open_interest,
+            agents,
+            config: config_args,
         } => {
-            let price_data = match load_prices_from_csv(&prices) {
+            let price_data = match load_price_paths_from_csv(&prices, wicks, &price_window.window())
+            {
                 Ok(p) => p,
                 Err(e) => {
                     eprintln!("Error loading prices: {}", e);
                     return;
                 }
             };
+            let volumes = if volume_scaling {
+                match load_volumes_from_csv(&prices, &price_window.window()) {
+                    Ok(v) => Some(v),
+                    Err(e) => {
+                        eprintln!("Error loading volumes: {}", e);
+                        return;
+                    }
+                }
+            } else {
+                None
+            };
 
             println!(
                 "Running scenario: {} blocks, {} arbers, {} miners",
@@ -278,8 +1464,96 @@ fn main() {
                 miners
             );
 
-            let config = ScenarioConfig::default();
-            let scenario = run_scenario(&price_data, &config, arbers, miners);
+            let scenario = match resume {
+                Some(path) => {
+                    let mut scenario = match Scenario::restore(&PathBuf::from(&path)) {
+                        Ok(s) => s,
+                        Err(e) => {
+                            eprintln!("Error loading checkpoint {}: {}", path, e);
+                            return;
+                        }
+                    };
+                    println!("Resuming from block {}", scenario.last_block());
+                    if !changes.is_empty()
+                        || !faults.is_empty()
+                        || volume_scaling
+                        || funding.is_some()
+                        || config_args.config.is_some()
+                        || !config_args.settings.is_empty()
+                    {
+                        tracing::warn!("--change, --fault, --volume-scaling, --funding, --config and --set are ignored when resuming; the checkpoint keeps its config");
+                    }
+                    scenario.config.checkpoint_interval = checkpoint_every;
+                    scenario.config.checkpoint_path = Some(PathBuf::from(&checkpoint));
+                    scenario.run_paths(&price_data);
+                    scenario
+                }
+                None => {
+                    let mut config = config_args.resolve_or_exit(ScenarioConfig {
+                        checkpoint_interval: checkpoint_every,
+                        checkpoint_path: Some(PathBuf::from(&checkpoint)),
+                        parameter_schedule: ParameterSchedule { changes },
+                        faults: FaultSchedule { faults },
+                        ..ScenarioConfig::default()
+                    });
+                    let roster = agents
+                        .apply(&mut config, arbers, miners)
+                        .unwrap_or_else(|e| {
+                            eprintln!("Error in agent flags: {}", e);
+                            std::process::exit(2);
+                        });
+                    if let Err(e) = config.parameter_schedule.validate(&config) {
+                        eprintln!("Error in --change: {}", e);
+                        std::process::exit(2);
+                    }
+                    if let Some(path) = &funding {
+                        let history = perp::load_funding(Path::new(path)).and_then(|rates| {
+                            let oi = match &open_interest {
+                                Some(p) => perp::load_open_interest(Path::new(p))?,
+                                None => Vec::new(),
+                            };
+                            Ok(PerpConfig::from_history(&rates, &oi))
+                        });
+                        match history {
+                            Ok(perp) => config.perp = Some(perp),
+                            Err(e) => {
+                                eprintln!("Error loading perp history: {}", e);
+                                return;
+                            }
+                        }
+                    }
+                    let mut scenario = Scenario::new(&config);
+                    if let Err(e) = roster.add_to(&mut scenario, 42) {
+                        eprintln!("Error in agent flags: {}", e);
+                        std::process::exit(2);
+                    }
+                    if let Some(volumes) = &volumes {
+                        let scaling = VolumeScalingConfig {
+                            exponent: volume_exponent,
+                            ..VolumeScalingConfig::default()
+                        };
+                        scenario.scale_by_volume(volumes, &scaling);
+                    }
+                    if let Some(date) = start_date {
+                        let emission = EmissionConfig {
+                            calendar: ChainCalendar::starting_at(date),
+                            ..EmissionConfig::default()
+                        };
+                        if let Some(block) = emission.next_halving_block(1) {
+                            println!(
+                                "Next halving at block {} ({})",
+                                block,
+                                emission.calendar.date(block)
+                            );
+                        }
+                        for miner in &mut scenario.miners {
+                            miner.config.emission = Some(emission.clone());
+                        }
+                    }
+                    scenario.run_paths(&price_data);
+                    scenario
+                }
+            };
 
             let out_path = PathBuf::from(&output);
             match scenario.save_metrics_csv(&out_path) {
@@ -294,11 +1568,12 @@ fn main() {
 
         Commands::Sweep {
             prices,
+            price_window,
             output_dir,
             param,
             values,
         } => {
-            let price_data = match load_prices_from_csv(&prices) {
+            let price_data = match load_prices_from_csv(&prices, &price_window.window()) {
                 Ok(p) => p,
                 Err(e) => {
                     eprintln!("Error loading prices: {}", e);
@@ -326,6 +1601,8 @@ fn main() {
                     "swap_fee" => config.amm_swap_fee = *val,
                     "liquidation_penalty" => config.cdp_config.liquidation_penalty = *val,
                     "stability_fee" => config.cdp_config.stability_fee_rate = *val,
+                    "amm_price" => config.amm_initial_price = Some(*val),
+                    "amm_depth" => config.amm_initial_depth = Some(*val),
                     _ => {
                         eprintln!("Unknown parameter: {}", param);
                         return;
@@ -345,35 +1622,568 @@ fn main() {
 
         Commands::Stress {
             id,
+            file,
+            historical,
+            cache_dir,
+            prices,
+            no_rescale,
+            price_window,
             blocks,
             output_dir,
             seed,
+            format,
+            fail_on,
+            report_format,
+            snapshot_interval,
+            agent_metrics,
+            db,
+            faults,
+            agents,
+            config: config_args,
         } => {
-            if id == 0 {
-                println!("Running all 13 stress scenarios ({} blocks each):", blocks);
-                let mut entries = Vec::new();
-                for sid in ScenarioId::all() {
-                    if let Some(entry) =
-                        run_stress_scenario(sid, blocks, seed, &output_dir)
-                    {
-                        entries.push(entry);
+            let historical = historical.map(|id| {
+                presets::preset(&id)
+                    .and_then(|preset| Ok((preset, preset.block_prices(Path::new(&cache_dir))?)))
+                    .unwrap_or_else(|e| {
+                        eprintln!("Error loading historical preset: {}", e);
+                        std::process::exit(2);
+                    })
+            });
+            let base = historical
+                .as_ref()
+                .map_or_else(ScenarioConfig::default, |(_, prices)| {
+                    presets::preset_config(prices)
+                });
+            let mut config = config_args.resolve_or_exit(ScenarioConfig {
+                snapshot_interval,
+                record_agent_metrics: agent_metrics,
+                faults: FaultSchedule { faults },
+                ..base
+            });
+            let roster = agents
+                .apply(&mut config, 0, 0)
+                .and_then(|roster| {
+                    roster.add_to(&mut Scenario::new(&config), seed)?;
+                    Ok(roster)
+                })
+                .unwrap_or_else(|e| {
+                    eprintln!("Error in agent flags: {}", e);
+                    std::process::exit(2);
+                });
+            let store = db.map(|path| match SqliteStore::open(&PathBuf::from(&path)) {
+                Ok(store) => store,
+                Err(e) => {
+                    eprintln!("Error opening database {}: {}", path, e);
+                    std::process::exit(2);
+                }
+            });
+            let mut out = StressOutput {
+                dir: &output_dir,
+                format,
+                report_format,
+                store: store.as_ref(),
+                price_sources: Vec::new(),
+            };
+
+            let price_path = prices.map(|path| {
+                let mut prices = load_prices_from_csv(&path, &price_window.window())
+                    .unwrap_or_else(|e| {
+                        eprintln!("Error loading prices: {}", e);
+                        std::process::exit(2);
+                    });
+                if !no_rescale {
+                    zai_sim::scenarios::rescale_prices(
+                        &mut prices,
+                        config.initial_redemption_price,
+                    );
+                }
+                out.price_sources.push(PathBuf::from(&path));
+                prices
+            });
+            let blocks = price_path.as_ref().map_or(blocks, Vec::len);
+
+            // A list or range runs like "all", just over fewer scenarios
+            let (mix, selected) = match &id {
+                Some(id) if id != "0" && !id.contains([',', '-']) => {
+                    match id.parse::<ScenarioMix>() {
+                        Ok(mix) => (Some(mix), Vec::new()),
+                        Err(e) => {
+                            eprintln!("{}", e);
+                            std::process::exit(2);
+                        }
+                    }
+                }
+                Some(id) => (None, parse_scenario_list(id)),
+                None => (None, ScenarioId::all()),
+            };
+
+            // Scenario files and compositions run once, without expectations
+            if (file.is_some()
+                || matches!(mix, Some(ScenarioMix::Chain(_) | ScenarioMix::Overlay(_))))
+                && (!agents.is_empty() || price_path.is_some())
+            {
+                eprintln!("Agent flags and --prices apply to built-in scenarios only, not files or compositions");
+                std::process::exit(2);
+            }
+            let custom = if let Some(path) = file {
+                let file = match ScenarioFile::load(&PathBuf::from(&path)) {
+                    Ok(f) => f,
+                    Err(e) => {
+                        eprintln!("Error loading scenario file: {}", e);
+                        std::process::exit(2);
+                    }
+                };
+                out.price_sources.push(PathBuf::from(&path));
+                progress(
+                    format,
+                    &format!("Running scenario file {} ({} blocks):", path, file.blocks()),
+                );
+                progress(format, &format!("  {} — {}", file.name, file.description));
+                let run = file.scenario_config(&config).and_then(|run_config| {
+                    file.run(&config, seed)
+                        .map(|scenario| (scenario, run_config))
+                });
+                match run {
+                    Ok((scenario, run_config)) => Some((file.name, scenario, run_config)),
+                    Err(e) => {
+                        eprintln!("Error running {}: {}", path, e);
+                        std::process::exit(2);
+                    }
+                }
+            } else if let Some((preset, prices)) = &historical {
+                out.price_sources
+                    .push(preset.cache_path(Path::new(&cache_dir)));
+                progress(
+                    format,
+                    &format!(
+                        "Running historical preset {} ({} blocks):",
+                        preset.id,
+                        prices.len()
+                    ),
+                );
+                progress(format, &format!("  {}", preset.description));
+                let mut scenario = Scenario::new_with_seed(&config, seed);
+                presets::add_standard_agents(&mut scenario);
+                if let Err(e) = roster.add_to(&mut scenario, seed) {
+                    eprintln!("Error in agent flags: {}", e);
+                    std::process::exit(2);
+                }
+                scenario.run(prices);
+                Some((preset.id.to_string(), scenario, config.clone()))
+            } else if let Some(mix @ (ScenarioMix::Chain(_) | ScenarioMix::Overlay(_))) = &mix {
+                progress(
+                    format,
+                    &format!("Running composed stress scenario ({} blocks):", blocks),
+                );
+                progress(format, &format!("  {}", mix.name()));
+                let scenario = zai_sim::scenarios::run_mix(mix, &config, blocks, seed);
+                Some((mix.name(), scenario, config.clone()))
+            } else {
+                None
+            };
+
+            if let Some((name, scenario, run_config)) = custom {
+                let (verdict, summary) = save_stress_run(&name, &scenario, &run_config, seed, &out);
+                if format == OutputFormat::Json {
+                    match output::single_stress_results_json(&name, &verdict, &summary) {
+                        Ok(json) => println!("{}", json),
+                        Err(e) => {
+                            eprintln!("Error serializing results: {}", e);
+                            std::process::exit(2);
+                        }
+                    }
+                }
+                if fail_on.is_failure(&verdict.overall) {
+                    std::process::exit(1);
+                }
+                return;
+            }
+
+            let mut runs = Vec::new();
+            if let Some(ScenarioMix::Single(sid)) = mix {
+                progress(
+                    format,
+                    &format!("Running stress scenario ({} blocks):", blocks),
+                );
+                runs.extend(run_stress_scenario(
+                    sid,
+                    &config,
+                    &roster,
+                    price_path.as_deref(),
+                    blocks,
+                    seed,
+                    &out,
+                ));
+            } else {
+                let which = if selected.len() == ScenarioId::all().len() {
+                    "all 13".to_string()
+                } else {
+                    selected.len().to_string()
+                };
+                progress(
+                    format,
+                    &format!(
+                        "Running {} stress scenarios ({} blocks each):",
+                        which, blocks
+                    ),
+                );
+                for sid in selected {
+                    if let Some(run) = run_stress_scenario(
+                        sid,
+                        &config,
+                        &roster,
+                        price_path.as_deref(),
+                        blocks,
+                        seed,
+                        &out,
+                    ) {
+                        runs.push(run);
                     }
                 }
+
+                if format == OutputFormat::Text && price_path.is_none() {
+                    // Acceptance expectations (calibrated at the reference config
+                    // and the generated price paths)
+                    println!("\nAcceptance expectations:");
+                    let mut met = 0;
+                    for (sid, verdict, summary) in &runs {
+                        let results = expectations::evaluate(*sid, verdict, summary);
+                        let failed: Vec<_> = results.iter().filter(|r| !r.passed).collect();
+                        if failed.is_empty() {
+                            met += 1;
+                            println!("  [PASS] {} ({} checks)", sid.name(), results.len());
+                        } else {
+                            for r in failed {
+                                println!(
+                                    "  [FAIL] {}: {} (actual {})",
+                                    sid.name(),
+                                    r.expectation.describe(),
+                                    r.actual
+                                );
+                            }
+                        }
+                    }
+                    println!("{} / {} scenarios met expectations", met, runs.len());
+                }
+
                 // Generate master summary
-                let master = report::generate_master_summary(&entries);
-                let master_path = PathBuf::from(&output_dir).join("index.html");
-                match report::save_report(&master, &master_path) {
-                    Ok(()) => println!("\nMaster summary: {}", master_path.display()),
+                let entries: Vec<_> = runs
+                    .iter()
+                    .map(|(sid, v, s)| (sid.name().to_string(), v.clone(), s.clone()))
+                    .collect();
+                let master_path =
+                    PathBuf::from(&output_dir).join(format!("index.{}", report_format.extension()));
+                let saved = match report_format {
+                    ReportFormat::Html => {
+                        save_html(&report::generate_master_summary(&entries), &master_path)
+                    }
+                    ReportFormat::Markdown | ReportFormat::Pdf => save_markdown(
+                        &report::generate_master_summary_markdown(&entries),
+                        report_format,
+                        &master_path,
+                    ),
+                };
+                match saved {
+                    Ok(()) => progress(
+                        format,
+                        &format!("\nMaster summary: {}", master_path.display()),
+                    ),
                     Err(e) => eprintln!("Error saving master summary: {}", e),
                 }
-            } else {
-                match id_to_scenario(id) {
-                    Some(sid) => {
-                        println!("Running stress scenario ({} blocks):", blocks);
-                        run_stress_scenario(sid, blocks, seed, &output_dir);
+            }
+
+            if format == OutputFormat::Json {
+                match output::stress_results_json(&runs) {
+                    Ok(json) => println!("{}", json),
+                    Err(e) => {
+                        eprintln!("Error serializing results: {}", e);
+                        std::process::exit(2);
+                    }
+                }
+            }
+
+            let worst = runs
+                .iter()
+                .fold(Verdict::Pass, |acc, (_, v, _)| acc.worst(v.overall.clone()));
+            if fail_on.is_failure(&worst) {
+                std::process::exit(1);
+            }
+        }
+
+        Commands::Scenarios {
+            id,
+            blocks,
+            seed,
+            format,
+        } => {
+            let ids = match id {
+                Some(id) => match id
+                    .parse::<u8>()
+                    .ok()
+                    .and_then(ScenarioId::from_id)
+                    .or_else(|| ScenarioId::from_name(&id))
+                {
+                    Some(sid) => vec![sid],
+                    None => {
+                        eprintln!("Invalid scenario: {} (1-13 or a name)", id);
+                        std::process::exit(2);
+                    }
+                },
+                None => ScenarioId::all(),
+            };
+            let infos: Vec<_> = ids.iter().map(|sid| sid.info(blocks, seed)).collect();
+
+            if format == OutputFormat::Json {
+                match serde_json::to_string_pretty(&infos) {
+                    Ok(json) => println!("{}", json),
+                    Err(e) => {
+                        eprintln!("Error serializing scenarios: {}", e);
+                        std::process::exit(2);
+                    }
+                }
+                return;
+            }
+            for info in &infos {
+                let agents: Vec<String> = info
+                    .agents
+                    .iter()
+                    .map(|(class, n)| format!("{} {}", n, class))
+                    .collect();
+                println!("{:>2}  {} — {}", info.id, info.name, info.description);
+                println!("    Prices:  {}", info.price_shape);
+                println!(
+                    "             ${:.2} start, ${:.2}-{:.2} range, ${:.2} end",
+                    info.start_price, info.min_price, info.max_price, info.end_price
+                );
+                println!("    Agents:  {}", agents.join(", "));
+                println!(
+                    "    Length:  {} blocks (~{:.1} h at 75 s blocks)",
+                    info.blocks, info.hours
+                );
+                println!();
+            }
+        }
+
+        Commands::Diff {
+            left,
+            right,
+            tolerance,
+        } => {
+            let load = |path: &str| match snapshot::load_snapshots_csv(std::path::Path::new(path)) {
+                Ok(s) => s,
+                Err(e) => {
+                    eprintln!("Error loading snapshots from {}: {}", path, e);
+                    std::process::exit(2);
+                }
+            };
+            let (a, b) = (load(&left), load(&right));
+            let diffs = snapshot::diff_snapshots(&a, &b, tolerance);
+
+            match diffs.first() {
+                None => println!("No divergence across {} snapshots", a.len().max(b.len())),
+                Some(first) => {
+                    println!(
+                        "First divergence at block {}: {} {} -> {}",
+                        first.block, first.field, first.left, first.right
+                    );
+                    for d in diffs.iter().skip(1).take_while(|d| d.block == first.block) {
+                        println!("  also {}: {} -> {}", d.field, d.left, d.right);
+                    }
+                    println!("{} differing fields in total", diffs.len());
+                    std::process::exit(1);
+                }
+            }
+        }
+
+        Commands::Compare {
+            a,
+            b,
+            output: out_path,
+        } => {
+            let load = |dir: &str| match output::load_run(std::path::Path::new(dir)) {
+                Ok(run) => run,
+                Err(e) => {
+                    eprintln!("Error loading run from {}: {}", dir, e);
+                    std::process::exit(2);
+                }
+            };
+            let (run_a, run_b) = (load(&a), load(&b));
+
+            let value = |v: Option<f64>| v.map_or_else(|| "-".to_string(), |v| format!("{:.4}", v));
+            for d in report::summary_deltas(&run_a, &run_b) {
+                let pct = d
+                    .pct_change()
+                    .map_or_else(|| "-".to_string(), |p| format!("{:+.2}%", p));
+                println!(
+                    "  {:<36} {:>14} {:>14} {:>10}",
+                    d.name,
+                    value(d.a),
+                    value(d.b),
+                    pct
+                );
+            }
+            let changes = report::config_diff(&run_a, &run_b);
+            println!("{} config settings differ", changes.len());
+
+            let html = report::generate_comparison(&run_a, &run_b);
+            if let Err(e) = save_html(&html, Path::new(&out_path)) {
+                eprintln!("Error writing {}: {}", out_path, e);
+                std::process::exit(1);
+            }
+            println!("Comparison report written to {}", out_path);
+        }
+
+        Commands::VerifyDeterminism {
+            id,
+            blocks,
+            seed,
+            threads,
+        } => {
+            let sid = id_to_scenario(id).unwrap_or_else(|| {
+                eprintln!("Invalid scenario ID: {} (must be 1-13)", id);
+                std::process::exit(2);
+            });
+            let thread_counts: Vec<usize> = match threads {
+                None => Vec::new(),
+                Some(list) => list
+                    .split(',')
+                    .map(|t| {
+                        t.trim().parse::<usize>().unwrap_or_else(|_| {
+                            eprintln!("Invalid thread count: {}", t);
+                            std::process::exit(2);
+                        })
+                    })
+                    .collect(),
+            };
+            println!(
+                "Verifying determinism: {} ({} blocks, seed {})",
+                sid.name(),
+                blocks,
+                seed
+            );
+
+            let report = determinism::verify_determinism(
+                sid,
+                &ScenarioConfig::default(),
+                blocks,
+                seed,
+                &thread_counts,
+            )
+            .unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                std::process::exit(2);
+            });
+
+            match &report.divergence {
+                None => println!(
+                    "Deterministic: {} run(s) bit-identical to the reference",
+                    report.runs_compared
+                ),
+                Some((threads, diffs)) => {
+                    let first = &diffs[0];
+                    println!(
+                        "Divergence with {} thread(s) at block {}: {} {} -> {}",
+                        threads, first.block, first.field, first.expected, first.actual
+                    );
+                    for d in diffs.iter().skip(1) {
+                        println!("  also {}: {} -> {}", d.field, d.expected, d.actual);
                     }
-                    None => eprintln!("Invalid scenario ID: {} (must be 1-13)", id),
+                    std::process::exit(1);
+                }
+            }
+        }
+
+        Commands::Persona {
+            kind,
+            capital,
+            cr,
+            debt,
+            reserve,
+            blocks,
+            runs,
+            seed,
+            output,
+        } => {
+            let persona = match kind {
+                PersonaKind::Lp => Persona::Lp {
+                    capital_zai: capital,
+                },
+                PersonaKind::Vault => Persona::VaultOwner {
+                    collateral_ratio: cr,
+                    debt_zai: debt,
+                    reserve_zec: reserve,
+                },
+            };
+            let seeds: Vec<u64> = (seed..seed + runs.max(1)).collect();
+            println!(
+                "Following persona: {} ({} blocks, {} run(s) per scenario)",
+                persona.describe(),
+                blocks,
+                seeds.len()
+            );
+
+            let result = persona::run_persona(&persona, &ScenarioConfig::default(), blocks, &seeds);
+            for s in &result.scenarios {
+                println!(
+                    "  {:<22} liquidated {:>5.1}%  mean P&L {:>+7.2}%  worst drawdown {:>6.2}%",
+                    s.scenario.name(),
+                    s.liquidation_probability * 100.0,
+                    s.mean_pnl_pct * 100.0,
+                    s.worst_drawdown_pct * 100.0
+                );
+            }
+
+            let html = report::generate_persona_report(&result);
+            let path = PathBuf::from(&output);
+            match save_html(&html, &path) {
+                Ok(()) => println!("Persona report: {}", path.display()),
+                Err(e) => eprintln!("Error saving persona report: {}", e),
+            }
+        }
+
+        Commands::Calibrate {
+            data,
+            dex_volume,
+            swap_fee,
+            output,
+        } => {
+            let candles = match historical::load_hourly_candles(&data) {
+                Ok(c) => c,
+                Err(e) => {
+                    eprintln!("Error loading {}: {}", data, e);
+                    std::process::exit(1);
                 }
+            };
+            let inputs = CalibrationInputs {
+                dex_volume_zec_per_day: dex_volume,
+                swap_fee,
+                ..CalibrationInputs::default()
+            };
+            let result = match calibration::calibrate(&data, &candles, &inputs) {
+                Ok(r) => r,
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            };
+            println!(
+                "Calibrating against {} ({} hours, DEX share {:.1}%)",
+                data,
+                result.hours,
+                result.dex_share * 100.0
+            );
+            for p in &result.params {
+                println!(
+                    "  {:<20} [{:.4}, {:.4}]  estimate {:.4}  (default {:.4})",
+                    p.name, p.low, p.high, p.estimate, p.default
+                );
+            }
+
+            let html = report::generate_calibration_report(&result);
+            let path = PathBuf::from(&output);
+            match save_html(&html, &path) {
+                Ok(()) => println!("Calibration report: {}", path.display()),
+                Err(e) => eprintln!("Error saving calibration report: {}", e),
             }
         }
 
@@ -381,20 +2191,36 @@ fn main() {
             blocks,
             output_dir,
             seed,
+            db,
+            config: config_args,
         } => {
+            let config = config_args.resolve_or_exit(ScenarioConfig::default());
             println!(
                 "Running 4-stage parameter sweep ({} blocks per scenario)...",
                 blocks
             );
 
-            let engine = SweepEngine::new(blocks, seed, 50.0);
+            let target = config.initial_redemption_price;
+            let monitor = Arc::new(ProgressMonitor::new(progress_mode, target));
+            let engine = SweepEngine::new(blocks, seed, target)
+                .with_config(config)
+                .with_progress(monitor.clone());
             let results = engine.run_full_sweep();
+            monitor.finish();
 
             let out_path = PathBuf::from(&output_dir);
             match output::save_sweep_results(&results, &out_path.join("sweep_results.csv")) {
                 Ok(()) => println!("Saved sweep results to {}", out_path.display()),
                 Err(e) => eprintln!("Error saving results: {}", e),
             }
+            if let Some(path) = db {
+                match SqliteStore::open(&PathBuf::from(&path))
+                    .and_then(|store| store.save_sweep_results("full_sweep", &results))
+                {
+                    Ok(()) => println!("Stored {} sweep results in {}", results.len(), path),
+                    Err(e) => eprintln!("Error storing sweep results: {}", e),
+                }
+            }
 
             // Print top results
             println!("\nTop configurations:");
@@ -412,5 +2238,310 @@ fn main() {
                 );
             }
         }
+
+        Commands::SampleSweep {
+            ranges,
+            strategy,
+            samples,
+            scenarios,
+            blocks,
+            seed,
+            output_dir,
+            db,
+        } => {
+            let scenario_ids = parse_scenario_list(&scenarios);
+            println!(
+                "Sampling {} points ({:?}) over {} parameters x {} scenarios ({} blocks each)",
+                samples,
+                strategy,
+                ranges.len(),
+                scenario_ids.len(),
+                blocks
+            );
+
+            let monitor = Arc::new(ProgressMonitor::new(progress_mode, 50.0));
+            let engine = SweepEngine::new(blocks, seed, 50.0).with_progress(monitor.clone());
+            let results = match engine.run_sampled(&ranges, strategy, samples, &scenario_ids) {
+                Ok(r) => r,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(2);
+                }
+            };
+            monitor.finish();
+
+            let out_path = PathBuf::from(&output_dir).join("sweep_results.csv");
+            match output::save_sweep_results(&results, &out_path) {
+                Ok(()) => println!("Saved {} results to {}", results.len(), out_path.display()),
+                Err(e) => eprintln!("Error saving results: {}", e),
+            }
+            if let Some(path) = db {
+                match SqliteStore::open(&PathBuf::from(&path))
+                    .and_then(|store| store.save_sweep_results("sample_sweep", &results))
+                {
+                    Ok(()) => println!("Stored {} sweep results in {}", results.len(), path),
+                    Err(e) => eprintln!("Error storing sweep results: {}", e),
+                }
+            }
+
+            println!("\nTop configurations:");
+            for (i, r) in results.iter().take(5).enumerate() {
+                let params_str: Vec<String> = r
+                    .params
+                    .iter()
+                    .map(|(n, v)| format!("{}={:.4}", n, v))
+                    .collect();
+                println!(
+                    "  #{}: score={:.6} [{}]",
+                    i + 1,
+                    r.overall_score,
+                    params_str.join(", ")
+                );
+            }
+        }
+
+        Commands::Sensitivity {
+            ranges,
+            outcomes,
+            samples,
+            scenarios,
+            blocks,
+            seed,
+            output,
+        } => {
+            let scenario_ids = parse_scenario_list(&scenarios);
+            let outcomes: Vec<String> = outcomes.split(',').map(|o| o.trim().to_string()).collect();
+            println!(
+                "Sensitivity: {} base samples x ({} parameters + 2) x {} scenarios ({} blocks each)",
+                samples,
+                ranges.len(),
+                scenario_ids.len(),
+                blocks
+            );
+
+            let report = match sensitivity::run_sensitivity(
+                &ranges,
+                &outcomes,
+                samples,
+                &scenario_ids,
+                blocks,
+                seed,
+            ) {
+                Ok(r) => r,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(2);
+                }
+            };
+
+            for o in &report.outcomes {
+                println!("\n{} (mean {:.6}, variance {:.6e})", o.outcome, o.mean, o.variance);
+                let mut indices = o.indices.clone();
+                indices.sort_by(|a, b| {
+                    b.total
+                        .partial_cmp(&a.total)
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                });
+                for idx in &indices {
+                    println!(
+                        "  {:<32} first-order {:>7.3}  total {:>7.3}",
+                        idx.param, idx.first_order, idx.total
+                    );
+                }
+            }
+
+            let path = PathBuf::from(&output);
+            match output::save_sensitivity_csv(&report, &path) {
+                Ok(()) => println!("\nSaved indices to {}", path.display()),
+                Err(e) => eprintln!("Error saving indices: {}", e),
+            }
+        }
+
+        Commands::Live {
+            symbol,
+            arbers,
+            miners,
+            block_time,
+            report_every,
+            window,
+        } => {
+            let config = LiveConfig {
+                block_time_secs: block_time,
+                report_every,
+                window,
+            };
+            println!(
+                "Streaming {} trades into {}s blocks (stats every {} blocks over the last {})...",
+                symbol, block_time, report_every, window
+            );
+
+            let result = live::run_live(
+                &symbol,
+                &config,
+                &RetryPolicy::default(),
+                |price| {
+                    println!("First trade at {:.2}; seeding AMM at that price", price);
+                    let config = ScenarioConfig {
+                        amm_initial_price: Some(price),
+                        ..ScenarioConfig::default()
+                    };
+                    build_scenario(&config, arbers, miners)
+                },
+                |session, stats| {
+                    let last = session.scenario.metrics.last();
+                    println!(
+                        "block {:>6}  ext {:>8.2}  amm {:>8.2}  peg dev mean {:>6.2}% max {:>6.2}%  liqs {:>3}  bad debt {:>10.2}{}",
+                        last.as_ref().map_or(0, |m| m.block),
+                        last.as_ref().map_or(0.0, |m| m.external_price),
+                        stats.final_amm_price,
+                        stats.mean_peg_deviation * 100.0,
+                        stats.max_peg_deviation * 100.0,
+                        stats.total_liquidations,
+                        stats.total_bad_debt,
+                        if last.is_some_and(|m| m.halted) { "  HALTED" } else { "" }
+                    );
+                },
+            );
+            if let Err(e) = result {
+                eprintln!("Live feed failed: {}", e);
+                std::process::exit(1);
+            }
+        }
+
+        Commands::Query {
+            db,
+            sql,
+            metric,
+            by,
+        } => {
+            if !std::path::Path::new(&db).exists() {
+                eprintln!("No results database at {}", db);
+                std::process::exit(2);
+            }
+            #[cfg(feature = "sqlite")]
+            {
+                let result = SqliteStore::open(&PathBuf::from(&db)).and_then(|store| match &sql {
+                    Some(sql) => store.query(sql),
+                    None => store.aggregate(&metric, &by),
+                });
+                match result {
+                    Ok(result) => {
+                        println!("{}", result.columns.join("\t"));
+                        for row in &result.rows {
+                            let cells: Vec<String> =
+                                row.iter().map(output::sql_value_text).collect();
+                            println!("{}", cells.join("\t"));
+                        }
+                    }
+                    Err(e) => {
+                        eprintln!("Query failed: {}", e);
+                        std::process::exit(2);
+                    }
+                }
+            }
+            #[cfg(not(feature = "sqlite"))]
+            {
+                let _ = (sql, metric, by);
+                eprintln!("Query failed: rebuild with `--features sqlite` to read results databases");
+                std::process::exit(2);
+            }
+        }
+
+        Commands::Serve { dir, addr } => {
+            if !Path::new(&dir).is_dir() {
+                eprintln!("No output directory at {}", dir);
+                std::process::exit(2);
+            }
+            let listener = match std::net::TcpListener::bind(&addr) {
+                Ok(listener) => listener,
+                Err(e) => {
+                    eprintln!("Error listening on {}: {}", addr, e);
+                    std::process::exit(2);
+                }
+            };
+            println!("Serving {} at http://{}/", dir, addr);
+            if let Err(e) = Dashboard::new(&dir).serve(listener) {
+                eprintln!("Server failed: {}", e);
+                std::process::exit(1);
+            }
+        }
+
+        Commands::Regress {
+            baseline,
+            update,
+            blocks,
+            seed,
+            rel_tol,
+            abs_tol,
+        } => {
+            let dir = Path::new(&baseline);
+            let config = ScenarioConfig::default();
+            if update {
+                let recorded = regress::Baseline::record(&config, blocks, seed);
+                if let Err(e) = recorded.save(dir) {
+                    eprintln!("Error writing baseline to {}: {}", baseline, e);
+                    std::process::exit(1);
+                }
+                println!(
+                    "Recorded baseline of {} scenarios ({} blocks, seed {}) in {}",
+                    recorded.scenarios.len(),
+                    blocks,
+                    seed,
+                    baseline
+                );
+                return;
+            }
+
+            let stored = regress::Baseline::load(dir).unwrap_or_else(|e| {
+                eprintln!(
+                    "Error loading baseline from {}: {} (record one with --update)",
+                    baseline, e
+                );
+                std::process::exit(2);
+            });
+            println!(
+                "Comparing {} scenarios against {} ({} blocks, seed {})",
+                stored.scenarios.len(),
+                baseline,
+                stored.blocks,
+                stored.seed
+            );
+            let tolerance = regress::Tolerance {
+                relative: rel_tol,
+                absolute: abs_tol,
+            };
+            let report = regress::regress(&stored, &config, tolerance);
+
+            let show = |v: Option<f64>| v.map_or("non-finite".to_string(), |v| format!("{}", v));
+            for d in &report.deltas {
+                println!(
+                    "  {:<4} {:<20} {:<28} {} -> {}{}",
+                    if d.within_tolerance { "ok" } else { "FAIL" },
+                    d.scenario,
+                    d.metric,
+                    show(d.baseline),
+                    show(d.actual),
+                    d.delta().map_or(String::new(), |x| format!(" ({:+e})", x))
+                );
+            }
+            for name in &report.unmatched {
+                println!(
+                    "  FAIL {} is in only one of baseline and current runs",
+                    name
+                );
+            }
+            if report.passed() {
+                println!(
+                    "No regressions: {} metric(s) changed within tolerance",
+                    report.deltas.len()
+                );
+            } else {
+                println!(
+                    "{} metric(s) beyond tolerance",
+                    report.regressions().count()
+                );
+                std::process::exit(1);
+            }
+        }
     }
 }
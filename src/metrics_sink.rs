@@ -0,0 +1,388 @@
+//! Streaming per-block metrics for long runs.
+//!
+//! A `Scenario` normally keeps every block's `BlockMetrics` in memory and
+//! writes them out at the end, which is too much for million-block runs and
+//! loses everything if the process dies. With `Scenario::stream_metrics`
+//! each block is handed to a `MetricsSink` as it finishes, flushed every
+//! `StreamConfig::flush_every` blocks, and only the last
+//! `StreamConfig::retain` blocks are kept in memory.
+
+use std::fs::{File, OpenOptions};
+use std::path::{Path, PathBuf};
+#[cfg(feature = "parquet")]
+use std::sync::Arc;
+
+#[cfg(feature = "parquet")]
+use arrow_array::builder::{Float64Builder, ListBuilder, StringBuilder, StructBuilder};
+#[cfg(feature = "parquet")]
+use arrow_array::{ArrayRef, BooleanArray, Float64Array, RecordBatch, UInt32Array, UInt64Array};
+#[cfg(feature = "parquet")]
+use arrow_schema::{DataType, Field};
+#[cfg(feature = "parquet")]
+use parquet::arrow::ArrowWriter;
+#[cfg(feature = "parquet")]
+use parquet::basic::Compression;
+#[cfg(feature = "parquet")]
+use parquet::file::properties::WriterProperties;
+
+use crate::error::ZaiSimError;
+#[cfg(feature = "sqlite")]
+use crate::output::SqliteStore;
+#[cfg(any(feature = "sqlite", feature = "parquet"))]
+use crate::scenario::MetricsStore;
+use crate::scenario::{metrics_csv_header, metrics_csv_row, BlockMetrics};
+#[cfg(feature = "parquet")]
+use crate::scenario::{LP_COHORT_COLUMNS, METRICS_CSV_COLUMNS};
+
+/// Destination for streamed per-block metrics.
+pub trait MetricsSink: Send {
+    /// Record one block. May buffer until the next `flush`.
+    fn write(&mut self, metrics: &BlockMetrics) -> Result<(), ZaiSimError>;
+
+    /// Persist everything written so far.
+    fn flush(&mut self) -> Result<(), ZaiSimError>;
+}
+
+/// How a scenario streams its metrics.
+#[derive(Debug, Clone)]
+pub struct StreamConfig {
+    /// Flush the sink every this many blocks (at least 1); data since the
+    /// last flush is lost if the process dies
+    pub flush_every: u64,
+    /// Keep only this many most recent blocks in `Scenario::metrics`
+    /// (at least 1). `None` keeps them all.
+    pub retain: Option<usize>,
+}
+
+impl Default for StreamConfig {
+    fn default() -> Self {
+        Self {
+            flush_every: 1000,
+            retain: None,
+        }
+    }
+}
+
+/// Streams metrics to a CSV file with the same columns as
+/// `Scenario::save_metrics_csv`.
+///
+/// LP cohort columns follow the cohorts seen so far. When a new cohort
+/// appears the file is rewritten once with the wider header, earlier rows
+/// getting blank cells for it.
+pub struct CsvSink {
+    path: PathBuf,
+    writer: csv::Writer<File>,
+    cohorts: Option<Vec<String>>,
+}
+
+impl CsvSink {
+    pub fn create(path: &Path) -> Result<Self, ZaiSimError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        Ok(Self {
+            path: path.to_path_buf(),
+            writer: csv::Writer::from_path(path)?,
+            cohorts: None,
+        })
+    }
+
+    /// Rewrite the file under the header for `cohorts`, padding the rows
+    /// already written, then keep appending to it.
+    fn widen_header(&mut self, cohorts: &[String]) -> Result<(), ZaiSimError> {
+        self.writer.flush()?;
+        let header = metrics_csv_header(cohorts);
+        let tmp = self.path.with_extension("csv.tmp");
+        let mut reader = csv::Reader::from_path(&self.path)?;
+        let mut widened = csv::Writer::from_path(&tmp)?;
+        widened.write_record(&header)?;
+        for record in reader.records() {
+            let mut record = record?;
+            while record.len() < header.len() {
+                record.push_field("");
+            }
+            widened.write_record(&record)?;
+        }
+        widened.flush()?;
+        drop(widened);
+        std::fs::rename(&tmp, &self.path)?;
+        self.writer = csv::Writer::from_writer(OpenOptions::new().append(true).open(&self.path)?);
+        Ok(())
+    }
+}
+
+impl MetricsSink for CsvSink {
+    fn write(&mut self, metrics: &BlockMetrics) -> Result<(), ZaiSimError> {
+        match &self.cohorts {
+            None => {
+                let cohorts: Vec<String> = metrics
+                    .lp_cohorts
+                    .iter()
+                    .map(|c| c.cohort.clone())
+                    .collect();
+                self.writer.write_record(metrics_csv_header(&cohorts))?;
+                self.cohorts = Some(cohorts);
+            }
+            Some(known) => {
+                let added: Vec<String> = metrics
+                    .lp_cohorts
+                    .iter()
+                    .filter(|c| !known.contains(&c.cohort))
+                    .map(|c| c.cohort.clone())
+                    .collect();
+                if !added.is_empty() {
+                    let mut cohorts = known.clone();
+                    cohorts.extend(added);
+                    self.widen_header(&cohorts)?;
+                    self.cohorts = Some(cohorts);
+                }
+            }
+        }
+        let cohorts = self.cohorts.as_deref().unwrap_or_default();
+        self.writer
+            .write_record(metrics_csv_row(metrics, cohorts))?;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), ZaiSimError> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Streams metrics to a directory of Parquet files, one `part-NNNNN.parquet`
+/// per flush, with the `timeseries.csv` columns plus an `lp_cohorts` list of
+/// per-cohort structs.
+///
+/// Every part is a complete file, so a run that dies keeps all parts flushed
+/// before it. Read the directory as one dataset, e.g. with
+/// `pandas.read_parquet(dir)`.
+#[cfg(feature = "parquet")]
+pub struct ParquetSink {
+    dir: PathBuf,
+    parts: usize,
+    pending: MetricsStore,
+}
+
+#[cfg(feature = "parquet")]
+impl ParquetSink {
+    /// Write parts into `dir`, creating it if needed.
+    pub fn create(dir: &Path) -> Result<Self, ZaiSimError> {
+        std::fs::create_dir_all(dir)?;
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            parts: 0,
+            pending: MetricsStore::default(),
+        })
+    }
+}
+
+#[cfg(feature = "parquet")]
+impl MetricsSink for ParquetSink {
+    fn write(&mut self, metrics: &BlockMetrics) -> Result<(), ZaiSimError> {
+        self.pending.push(metrics.clone());
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), ZaiSimError> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        let batch = record_batch(&self.pending)?;
+        let path = self.dir.join(format!("part-{:05}.parquet", self.parts));
+        // Written aside and renamed so a crash never leaves a torn part
+        let tmp = path.with_extension("parquet.tmp");
+        let props = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .build();
+        let mut writer = ArrowWriter::try_new(File::create(&tmp)?, batch.schema(), Some(props))?;
+        writer.write(&batch)?;
+        writer.close()?;
+        std::fs::rename(&tmp, &path)?;
+        self.parts += 1;
+        self.pending.truncate(0);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "parquet")]
+impl Drop for ParquetSink {
+    fn drop(&mut self) {
+        if let Err(e) = self.flush() {
+            tracing::warn!("flushing streamed metrics failed: {}", e);
+        }
+    }
+}
+
+/// One Arrow column per `timeseries.csv` column, in the same order.
+#[cfg(feature = "parquet")]
+fn record_batch(rows: &MetricsStore) -> Result<RecordBatch, ZaiSimError> {
+    let float = |v: &[f64]| -> ArrayRef { Arc::new(Float64Array::from(v.to_vec())) };
+    let flag = |v: &[bool]| -> ArrayRef { Arc::new(BooleanArray::from(v.to_vec())) };
+    let uint32 = |v: &[u32]| -> ArrayRef { Arc::new(UInt32Array::from(v.to_vec())) };
+    let uint64 = |v: &[u64]| -> ArrayRef { Arc::new(UInt64Array::from(v.to_vec())) };
+    let count = |v: &[usize]| -> ArrayRef {
+        Arc::new(UInt64Array::from_iter_values(v.iter().map(|n| *n as u64)))
+    };
+    let columns = vec![
+        uint64(&rows.block),
+        float(&rows.external_price),
+        float(&rows.amm_spot_price),
+        float(&rows.twap_price),
+        float(&rows.redemption_price),
+        float(&rows.redemption_rate),
+        float(&rows.total_debt),
+        float(&rows.amm_reserve_zec),
+        float(&rows.amm_reserve_zai),
+        uint64(&rows.vault_count),
+        uint32(&rows.liquidation_count),
+        float(&rows.bad_debt),
+        float(&rows.debt_ceiling),
+        flag(&rows.minting_paused),
+        flag(&rows.halted),
+        float(&rows.total_collateral),
+        float(&rows.total_lp_shares),
+        float(&rows.arber_zai_total),
+        uint32(&rows.zombie_vault_count),
+        float(&rows.max_zombie_gap),
+        float(&rows.mean_collateral_ratio_twap),
+        float(&rows.mean_collateral_ratio_ext),
+        float(&rows.arber_zec_total),
+        float(&rows.cumulative_fees_zai),
+        float(&rows.cumulative_il_pct),
+        uint32(&rows.graduated_liquidation_count),
+        flag(&rows.partial_halted),
+        float(&rows.cumulative_redeemed_zai),
+        float(&rows.treasury_balance),
+        float(&rows.uncovered_bad_debt),
+        float(&rows.lending_zec_utilization),
+        float(&rows.lending_zec_borrow_rate),
+        uint32(&rows.vaults_in_grace),
+        float(&rows.penalty_to_keepers),
+        float(&rows.penalty_to_lps),
+        float(&rows.penalty_to_insurance),
+        float(&rows.penalty_to_treasury),
+        float(&rows.penalty_burned),
+        float(&rows.insurance_fund_balance),
+        float(&rows.lp_fee_apr),
+        float(&rows.lp_penalties_zai),
+        float(&rows.lp_il_zai),
+        float(&rows.lp_net_return_zai),
+        float(&rows.savings_rate),
+        float(&rows.savings_deposits_zai),
+        float(&rows.savings_interest_paid_zai),
+        float(&rows.funding_rate),
+        float(&rows.funding_charged_zai),
+        float(&rows.gas_fee_zec),
+        float(&rows.gas_paid_zec),
+        float(&rows.block_time_secs),
+        uint32(&rows.deferred_agents),
+        uint32(&rows.reorgs),
+        flag(&rows.network_halted),
+        flag(&rows.amm_paused),
+        flag(&rows.oracle_outage),
+        float(&rows.shielded_zec),
+        float(&rows.shielded_zai),
+        float(&rows.bridge_outstanding_zai),
+        count(&rows.demand_users),
+        float(&rows.debt_ceiling_utilization),
+        count(&rows.vaults_waiting),
+        flag(&rows.redemption_capped),
+        count(&rows.deleveraged_vaults),
+        count(&rows.self_liquidations),
+        count(&rows.vault_arrivals),
+        count(&rows.vault_closures),
+        count(&rows.dust_vaults),
+        float(&rows.perp_funding_rate),
+        float(&rows.basis_arb_perp_zec),
+    ];
+    let mut named: Vec<(&str, ArrayRef)> =
+        METRICS_CSV_COLUMNS.iter().copied().zip(columns).collect();
+    named.push(("lp_cohorts", lp_cohorts(rows)));
+    Ok(RecordBatch::try_from_iter(named).map_err(parquet::errors::ParquetError::from)?)
+}
+
+/// Each block's LP cohorts as a list of `{cohort, <LP_COHORT_COLUMNS>}`.
+#[cfg(feature = "parquet")]
+fn lp_cohorts(rows: &MetricsStore) -> ArrayRef {
+    let mut fields = vec![Field::new("cohort", DataType::Utf8, false)];
+    fields.extend(
+        LP_COHORT_COLUMNS
+            .iter()
+            .map(|c| Field::new(*c, DataType::Float64, false)),
+    );
+    let mut list = ListBuilder::new(StructBuilder::from_fields(fields, 0));
+    for cohorts in &rows.lp_cohorts {
+        let entries = list.values();
+        for c in cohorts {
+            // Builder types match the fields above
+            entries
+                .field_builder::<StringBuilder>(0)
+                .unwrap()
+                .append_value(&c.cohort);
+            let values = [
+                c.fee_apr,
+                c.fees_zai,
+                c.penalties_zai,
+                c.il_zai,
+                c.net_return_zai,
+            ];
+            for (i, v) in values.into_iter().enumerate() {
+                entries
+                    .field_builder::<Float64Builder>(i + 1)
+                    .unwrap()
+                    .append_value(v);
+            }
+            entries.append(true);
+        }
+        list.append(true);
+    }
+    Arc::new(list.finish())
+}
+
+/// Streams metrics into the `block_metrics` table of a `SqliteStore`, one
+/// transaction per flush.
+#[cfg(feature = "sqlite")]
+pub struct SqliteSink {
+    store: SqliteStore,
+    run_id: i64,
+    pending: MetricsStore,
+}
+
+#[cfg(feature = "sqlite")]
+impl SqliteSink {
+    /// Append to the run `run_id`, as returned by `SqliteStore::insert_run`.
+    pub fn new(store: SqliteStore, run_id: i64) -> Self {
+        Self {
+            store,
+            run_id,
+            pending: MetricsStore::default(),
+        }
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl MetricsSink for SqliteSink {
+    fn write(&mut self, metrics: &BlockMetrics) -> Result<(), ZaiSimError> {
+        self.pending.push(metrics.clone());
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), ZaiSimError> {
+        if !self.pending.is_empty() {
+            self.store
+                .append_block_metrics(self.run_id, &self.pending)?;
+            self.pending.truncate(0);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl Drop for SqliteSink {
+    fn drop(&mut self) {
+        if let Err(e) = self.flush() {
+            tracing::warn!("flushing streamed metrics failed: {}", e);
+        }
+    }
+}
@@ -0,0 +1,295 @@
+//! Price oracles for liquidation and the circuit breakers.
+//!
+//! An `OracleFeed` says where a price comes from and how it is processed.
+//! Feeds nest, so "the median of external spot and a 12-block AMM TWAP,
+//! delayed 4 blocks" is a single value. `OracleFailure`s inject faults on
+//! top of the feed over block ranges: stale reports, outages and
+//! manipulation spikes. `PriceOracle` combines the two behind the `Oracle`
+//! trait, which is what liquidation and the TWAP breaker consume.
+
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::amm::Amm;
+
+/// What an oracle can observe at a block.
+pub struct OracleInputs<'a> {
+    pub block: u64,
+    /// External (CEX) ZEC price this block
+    pub external_price: f64,
+    pub amm: &'a Amm,
+}
+
+/// A ZEC price source.
+pub trait Oracle {
+    /// Advance to `inputs.block` and return the new price, as `price` would.
+    fn update(&mut self, inputs: &OracleInputs) -> Option<f64>;
+
+    /// Latest usable price; `None` during an outage or once the last report
+    /// is too old.
+    fn price(&self) -> Option<f64>;
+}
+
+/// Where an oracle's price comes from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OracleFeed {
+    /// External market spot price
+    ExternalSpot,
+    /// AMM spot price
+    AmmSpot,
+    /// AMM TWAP over `window` blocks
+    AmmTwap { window: u64 },
+    /// Median of several feeds
+    Median { feeds: Vec<OracleFeed> },
+    /// `feed` as it was `blocks` blocks ago (its first price until then)
+    Delayed { feed: Box<OracleFeed>, blocks: u64 },
+    /// `feed`, moving at most `max_step_pct` per block from the last price
+    BoundedStep {
+        feed: Box<OracleFeed>,
+        max_step_pct: f64,
+    },
+}
+
+impl OracleFeed {
+    pub fn median(feeds: Vec<OracleFeed>) -> Self {
+        OracleFeed::Median { feeds }
+    }
+
+    pub fn delayed(self, blocks: u64) -> Self {
+        OracleFeed::Delayed {
+            feed: Box::new(self),
+            blocks,
+        }
+    }
+
+    pub fn bounded_step(self, max_step_pct: f64) -> Self {
+        OracleFeed::BoundedStep {
+            feed: Box::new(self),
+            max_step_pct,
+        }
+    }
+}
+
+/// A fault injected over blocks `from_block..=to_block`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OracleFailure {
+    /// No new reports; the last one stays in use until it exceeds
+    /// `max_age_blocks`
+    Stale { from_block: u64, to_block: u64 },
+    /// No price at all
+    Outage { from_block: u64, to_block: u64 },
+    /// Reports are multiplied by `factor` (e.g. 0.7 for a 30% downward
+    /// manipulation)
+    Spike {
+        from_block: u64,
+        to_block: u64,
+        factor: f64,
+    },
+}
+
+impl OracleFailure {
+    pub fn covers(&self, block: u64) -> bool {
+        let (from, to) = match self {
+            Self::Stale {
+                from_block,
+                to_block,
+            }
+            | Self::Outage {
+                from_block,
+                to_block,
+            }
+            | Self::Spike {
+                from_block,
+                to_block,
+                ..
+            } => (*from_block, *to_block),
+        };
+        (from..=to).contains(&block)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OracleConfig {
+    pub feed: OracleFeed,
+    #[serde(default)]
+    pub failures: Vec<OracleFailure>,
+    /// Reports older than this many blocks are not used (`None` = never
+    /// expire)
+    #[serde(default)]
+    pub max_age_blocks: Option<u64>,
+}
+
+impl Default for OracleConfig {
+    fn default() -> Self {
+        OracleConfig::new(OracleFeed::ExternalSpot)
+    }
+}
+
+impl OracleConfig {
+    pub fn new(feed: OracleFeed) -> Self {
+        OracleConfig {
+            feed,
+            failures: Vec::new(),
+            max_age_blocks: Some(48), // ~1 hour
+        }
+    }
+
+    pub fn with_failure(mut self, failure: OracleFailure) -> Self {
+        self.failures.push(failure);
+        self
+    }
+}
+
+/// An `OracleFeed` with failures applied.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceOracle {
+    pub config: OracleConfig,
+    state: FeedState,
+    /// Latest report and the block it was made at
+    report: Option<(f64, u64)>,
+    block: u64,
+}
+
+impl PriceOracle {
+    pub fn new(config: OracleConfig) -> Self {
+        PriceOracle {
+            state: FeedState::new(&config.feed),
+            config,
+            report: None,
+            block: 0,
+        }
+    }
+
+    /// Block of the latest report, if any.
+    pub fn last_report_block(&self) -> Option<u64> {
+        self.report.map(|(_, at)| at)
+    }
+}
+
+impl Oracle for PriceOracle {
+    fn update(&mut self, inputs: &OracleInputs) -> Option<f64> {
+        let block = inputs.block;
+        self.block = block;
+        // The feed keeps sampling through failures so delays stay in step
+        let sample = self.state.sample(&self.config.feed, inputs);
+
+        let (mut stale, mut outage, mut factor) = (false, false, 1.0);
+        for failure in self.config.failures.iter().filter(|f| f.covers(block)) {
+            match failure {
+                OracleFailure::Stale { .. } => stale = true,
+                OracleFailure::Outage { .. } => outage = true,
+                OracleFailure::Spike { factor: f, .. } => factor *= f,
+            }
+        }
+
+        if outage {
+            self.report = None;
+        } else if !stale && sample.is_finite() {
+            self.report = Some((sample * factor, block));
+        }
+        self.price()
+    }
+
+    fn price(&self) -> Option<f64> {
+        let (price, at) = self.report?;
+        match self.config.max_age_blocks {
+            Some(max_age) if self.block.saturating_sub(at) > max_age => None,
+            _ => Some(price),
+        }
+    }
+}
+
+/// Per-feed state, mirroring the shape of the `OracleFeed` tree.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+enum FeedState {
+    Leaf,
+    Median(Vec<FeedState>),
+    Delayed {
+        inner: Box<FeedState>,
+        history: VecDeque<f64>,
+    },
+    BoundedStep {
+        inner: Box<FeedState>,
+        last: Option<f64>,
+    },
+}
+
+impl FeedState {
+    fn new(feed: &OracleFeed) -> Self {
+        match feed {
+            OracleFeed::Median { feeds } => {
+                FeedState::Median(feeds.iter().map(FeedState::new).collect())
+            }
+            OracleFeed::Delayed { feed, .. } => FeedState::Delayed {
+                inner: Box::new(FeedState::new(feed)),
+                history: VecDeque::new(),
+            },
+            OracleFeed::BoundedStep { feed, .. } => FeedState::BoundedStep {
+                inner: Box::new(FeedState::new(feed)),
+                last: None,
+            },
+            _ => FeedState::Leaf,
+        }
+    }
+
+    /// This block's price from `feed`. NaN if there is none (an empty
+    /// median).
+    fn sample(&mut self, feed: &OracleFeed, inputs: &OracleInputs) -> f64 {
+        match (feed, self) {
+            (OracleFeed::ExternalSpot, _) => inputs.external_price,
+            (OracleFeed::AmmSpot, _) => inputs.amm.spot_price(),
+            (OracleFeed::AmmTwap { window }, _) => inputs.amm.get_twap(*window),
+            (OracleFeed::Median { feeds }, FeedState::Median(states)) => {
+                let mut prices: Vec<f64> = feeds
+                    .iter()
+                    .zip(states.iter_mut())
+                    .map(|(f, s)| s.sample(f, inputs))
+                    .collect();
+                median(&mut prices)
+            }
+            (OracleFeed::Delayed { feed, blocks }, FeedState::Delayed { inner, history }) => {
+                history.push_back(inner.sample(feed, inputs));
+                while history.len() as u64 > blocks + 1 {
+                    history.pop_front();
+                }
+                history[0]
+            }
+            (
+                OracleFeed::BoundedStep { feed, max_step_pct },
+                FeedState::BoundedStep { inner, last },
+            ) => {
+                let target = inner.sample(feed, inputs);
+                let price = match *last {
+                    Some(prev) if target.is_finite() => {
+                        target.clamp(prev * (1.0 - max_step_pct), prev * (1.0 + max_step_pct))
+                    }
+                    Some(prev) => prev,
+                    None => target,
+                };
+                *last = Some(price).filter(|p| p.is_finite());
+                price
+            }
+            // State built for a different feed: start over
+            (feed, state) => {
+                *state = FeedState::new(feed);
+                state.sample(feed, inputs)
+            }
+        }
+    }
+}
+
+fn median(prices: &mut [f64]) -> f64 {
+    if prices.is_empty() {
+        return f64::NAN;
+    }
+    prices.sort_by(f64::total_cmp);
+    let mid = prices.len() / 2;
+    if prices.len() % 2 == 1 {
+        prices[mid]
+    } else {
+        (prices[mid - 1] + prices[mid]) / 2.0
+    }
+}
@@ -0,0 +1,200 @@
+//! Limit-order-book ZEC/ZAI venue.
+//!
+//! Market makers quote a static depth profile around a reference price:
+//! each level offers a fixed ZEC size at a fixed offset from mid, on both
+//! sides. Takers walk the book level by level, so price impact is flat
+//! within a level and jumps between levels — unlike the AMM's smooth
+//! curve — and a book has finite depth: an order larger than the remaining
+//! levels cannot fill. Consumed depth is replenished gradually each block.
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::ZaiSimError;
+
+/// One price level, mirrored on the bid and ask side.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DepthLevel {
+    /// Distance from mid as a fraction (0.01 = bids 1% below, asks 1% above)
+    pub offset_pct: f64,
+    /// ZEC offered at this level when fully replenished
+    pub size_zec: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderBookConfig {
+    /// Levels from the inside out
+    pub levels: Vec<DepthLevel>,
+    /// Fee on each fill, as a fraction of the proceeds
+    pub taker_fee: f64,
+    /// Fraction of consumed depth restored each block
+    pub replenish_rate: f64,
+}
+
+impl Default for OrderBookConfig {
+    fn default() -> Self {
+        let level = |offset_pct, size_zec| DepthLevel {
+            offset_pct,
+            size_zec,
+        };
+        OrderBookConfig {
+            levels: vec![
+                level(0.001, 25.0),
+                level(0.0025, 50.0),
+                level(0.005, 100.0),
+                level(0.01, 200.0),
+                level(0.02, 400.0),
+                level(0.05, 800.0),
+            ],
+            taker_fee: 0.001,
+            replenish_rate: 0.2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderBook {
+    pub config: OrderBookConfig,
+    /// Reference price the levels are quoted around (ZAI per ZEC)
+    pub mid: f64,
+    /// Remaining ZEC at each bid level
+    pub bids: Vec<f64>,
+    /// Remaining ZEC at each ask level
+    pub asks: Vec<f64>,
+    /// Total ZEC traded against the book
+    pub cumulative_volume_zec: f64,
+}
+
+impl OrderBook {
+    pub fn new(config: OrderBookConfig, mid: f64) -> Self {
+        let full: Vec<f64> = config.levels.iter().map(|l| l.size_zec).collect();
+        OrderBook {
+            config,
+            mid,
+            bids: full.clone(),
+            asks: full,
+            cumulative_volume_zec: 0.0,
+        }
+    }
+
+    /// Re-center the quotes on `mid`; remaining sizes are kept.
+    pub fn reprice(&mut self, mid: f64) {
+        self.mid = mid;
+    }
+
+    /// Restore `replenish_rate` of each level's consumed depth.
+    pub fn replenish(&mut self) {
+        let rate = self.config.replenish_rate.clamp(0.0, 1.0);
+        for (i, level) in self.config.levels.iter().enumerate() {
+            self.bids[i] += (level.size_zec - self.bids[i]) * rate;
+            self.asks[i] += (level.size_zec - self.asks[i]) * rate;
+        }
+    }
+
+    pub fn bid_price(&self, level: usize) -> f64 {
+        self.mid * (1.0 - self.config.levels[level].offset_pct)
+    }
+
+    pub fn ask_price(&self, level: usize) -> f64 {
+        self.mid * (1.0 + self.config.levels[level].offset_pct)
+    }
+
+    /// Innermost bid level with size left.
+    pub fn best_bid(&self) -> Option<usize> {
+        self.bids.iter().position(|&size| size > 1e-9)
+    }
+
+    /// Innermost ask level with size left.
+    pub fn best_ask(&self) -> Option<usize> {
+        self.asks.iter().position(|&size| size > 1e-9)
+    }
+
+    pub fn bid_depth_zec(&self) -> f64 {
+        self.bids.iter().sum()
+    }
+
+    pub fn ask_depth_zec(&self) -> f64 {
+        self.asks.iter().sum()
+    }
+
+    /// ZAI received for selling `zec_in` into the bids, without trading.
+    /// Zero if the bids cannot absorb it all.
+    pub fn quote_zec_for_zai(&self, zec_in: f64) -> f64 {
+        self.fill_bids(zec_in).map_or(0.0, |(zai, _)| zai)
+    }
+
+    /// ZEC received for spending `zai_in` on the asks, without trading.
+    /// Zero if the asks cannot absorb it all.
+    pub fn quote_zai_for_zec(&self, zai_in: f64) -> f64 {
+        self.fill_asks(zai_in).map_or(0.0, |(zec, _)| zec)
+    }
+
+    pub fn swap_zec_for_zai(&mut self, zec_in: f64) -> Result<f64, ZaiSimError> {
+        if zec_in <= 0.0 {
+            return Err(ZaiSimError::InvalidInput(
+                "Input must be positive".to_string(),
+            ));
+        }
+        let (zai_out, bids) = self.fill_bids(zec_in).ok_or_else(|| {
+            ZaiSimError::InsufficientLiquidity(format!(
+                "order book bids hold {:.4} ZEC, order is {:.4}",
+                self.bid_depth_zec(),
+                zec_in
+            ))
+        })?;
+        self.bids = bids;
+        self.cumulative_volume_zec += zec_in;
+        Ok(zai_out)
+    }
+
+    pub fn swap_zai_for_zec(&mut self, zai_in: f64) -> Result<f64, ZaiSimError> {
+        if zai_in <= 0.0 {
+            return Err(ZaiSimError::InvalidInput(
+                "Input must be positive".to_string(),
+            ));
+        }
+        let (zec_out, asks) = self.fill_asks(zai_in).ok_or_else(|| {
+            ZaiSimError::InsufficientLiquidity(format!(
+                "order book asks cannot absorb {:.4} ZAI",
+                zai_in
+            ))
+        })?;
+        self.asks = asks;
+        self.cumulative_volume_zec += zec_out;
+        Ok(zec_out)
+    }
+
+    /// Walk the bids with `zec_in`: proceeds and the remaining sizes.
+    fn fill_bids(&self, zec_in: f64) -> Option<(f64, Vec<f64>)> {
+        let mut bids = self.bids.clone();
+        let mut left = zec_in;
+        let mut zai_out = 0.0;
+        for (i, size) in bids.iter_mut().enumerate() {
+            let fill = left.min(*size);
+            zai_out += fill * self.bid_price(i);
+            *size -= fill;
+            left -= fill;
+            if left <= 0.0 {
+                return Some((zai_out * (1.0 - self.config.taker_fee), bids));
+            }
+        }
+        None
+    }
+
+    /// Walk the asks spending `zai_in`: ZEC bought and the remaining sizes.
+    fn fill_asks(&self, zai_in: f64) -> Option<(f64, Vec<f64>)> {
+        let mut asks = self.asks.clone();
+        let mut left = zai_in * (1.0 - self.config.taker_fee);
+        let mut zec_out = 0.0;
+        for (i, size) in asks.iter_mut().enumerate() {
+            let price = self.ask_price(i);
+            let fill = (left / price).min(*size);
+            zec_out += fill;
+            *size -= fill;
+            left -= fill * price;
+            if left <= 1e-12 * zai_in {
+                return Some((zec_out, asks));
+            }
+        }
+        None
+    }
+}
@@ -1,6 +1,21 @@
+use crate::agent_metrics::save_agent_metrics_csv;
 use crate::circuit_breaker::BreakerAction;
-use crate::scenario::{BlockMetrics, Scenario, ScenarioConfig};
+use crate::error::ZaiSimError;
+use crate::expectations::{self, ExpectationResult};
+#[cfg(feature = "sqlite")]
+use crate::report::evaluate_pass_fail;
+use crate::report::{PassFailResult, Verdict};
+#[cfg(feature = "sqlite")]
+use crate::scenario::BlockMetrics;
+use crate::scenario::{MetricsStore, Scenario, ScenarioConfig};
+use crate::scenarios::ScenarioId;
+use crate::sensitivity::SensitivityReport;
+use crate::snapshot::save_snapshots_csv;
 use crate::sweep::SweepResult;
+#[cfg(feature = "sqlite")]
+use rusqlite::{params, params_from_iter, Connection};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::path::Path;
 
 /// A discrete event extracted from simulation metrics.
@@ -12,7 +27,7 @@ pub struct Event {
 }
 
 /// Summary statistics for a simulation run.
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize)]
 pub struct SummaryMetrics {
     pub total_blocks: u64,
     pub mean_peg_deviation: f64,
@@ -23,54 +38,101 @@ pub struct SummaryMetrics {
     pub breaker_triggers: u32,
     pub halt_blocks: u64,
     pub pause_blocks: u64,
+    pub partial_halt_blocks: u64,
     pub mean_amm_price: f64,
     pub min_amm_price: f64,
     pub max_amm_price: f64,
     pub final_amm_price: f64,
     pub final_redemption_price: f64,
     pub final_debt_ceiling: f64,
+    pub final_treasury_balance: f64,
+    pub uncovered_bad_debt: f64,
+    /// Largest fall of the AMM price from a running peak, as a fraction of
+    /// that peak
+    pub max_drawdown: f64,
+    /// Blocks with the AMM price below the target
+    pub under_peg_blocks: u64,
+    /// Mean of the worst 5% of per-block peg deviations
+    pub cvar_95_deviation: f64,
+    /// Mean of the worst 1% of per-block peg deviations
+    pub cvar_99_deviation: f64,
+    /// Largest fall of the system collateral ratio (collateral at the TWAP
+    /// over total debt) from a running peak, as a fraction of that peak
+    pub collateral_ratio_drawdown: f64,
 }
 
 /// Extract discrete events from simulation metrics.
-pub fn extract_events(metrics: &[BlockMetrics]) -> Vec<Event> {
+pub fn extract_events(metrics: &MetricsStore) -> Vec<Event> {
     let mut events = Vec::new();
+    let mut breaker_events = metrics.breaker_events.iter().peekable();
 
-    for m in metrics {
-        if m.liquidation_count > 0 {
+    for (i, &block) in metrics.block.iter().enumerate() {
+        if metrics.liquidation_count[i] > 0 {
             events.push(Event {
-                block: m.block,
+                block,
                 event_type: "liquidation".to_string(),
                 details: format!(
                     "count={},bad_debt={:.2}",
-                    m.liquidation_count, m.bad_debt
+                    metrics.liquidation_count[i], metrics.bad_debt[i]
                 ),
             });
         }
 
-        for action in &m.breaker_actions {
+        while let Some((_, action)) = breaker_events.next_if(|(b, _)| *b <= block) {
             match action {
                 BreakerAction::None => {}
                 BreakerAction::PauseMinting { blocks, reason } => {
                     events.push(Event {
-                        block: m.block,
+                        block,
                         event_type: "pause_minting".to_string(),
                         details: format!("blocks={},{}", blocks, reason),
                     });
                 }
                 BreakerAction::ReduceDebtCeiling { new_ceiling, reason } => {
                     events.push(Event {
-                        block: m.block,
+                        block,
                         event_type: "reduce_ceiling".to_string(),
                         details: format!("ceiling={:.0},{}", new_ceiling, reason),
                     });
                 }
                 BreakerAction::EmergencyHalt { reason } => {
                     events.push(Event {
-                        block: m.block,
+                        block,
                         event_type: "emergency_halt".to_string(),
                         details: reason.clone(),
                     });
                 }
+                BreakerAction::PartialHalt { reason } => {
+                    events.push(Event {
+                        block,
+                        event_type: "partial_halt".to_string(),
+                        details: reason.clone(),
+                    });
+                }
+                BreakerAction::CapRedemptionDrift {
+                    capped_price,
+                    reason,
+                } => {
+                    events.push(Event {
+                        block,
+                        event_type: "cap_redemption_drift".to_string(),
+                        details: format!("price={:.4},{}", capped_price, reason),
+                    });
+                }
+                BreakerAction::RateLimitMinting { blocks, reason } => {
+                    events.push(Event {
+                        block,
+                        event_type: "rate_limit_minting".to_string(),
+                        details: format!("blocks={},{}", blocks, reason),
+                    });
+                }
+                BreakerAction::AutoDeleverage { vault_ids, reason } => {
+                    events.push(Event {
+                        block,
+                        event_type: "auto_deleverage".to_string(),
+                        details: format!("vaults={},{}", vault_ids.len(), reason),
+                    });
+                }
             }
         }
     }
@@ -79,7 +141,7 @@ pub fn extract_events(metrics: &[BlockMetrics]) -> Vec<Event> {
 }
 
 /// Compute summary statistics from simulation metrics.
-pub fn compute_summary(metrics: &[BlockMetrics], target_price: f64) -> SummaryMetrics {
+pub fn compute_summary(metrics: &MetricsStore, target_price: f64) -> SummaryMetrics {
     if metrics.is_empty() {
         return SummaryMetrics {
             total_blocks: 0,
@@ -91,65 +153,108 @@ pub fn compute_summary(metrics: &[BlockMetrics], target_price: f64) -> SummaryMe
             breaker_triggers: 0,
             halt_blocks: 0,
             pause_blocks: 0,
+            partial_halt_blocks: 0,
             mean_amm_price: 0.0,
             min_amm_price: 0.0,
             max_amm_price: 0.0,
             final_amm_price: 0.0,
             final_redemption_price: 0.0,
             final_debt_ceiling: 0.0,
+            final_treasury_balance: 0.0,
+            uncovered_bad_debt: 0.0,
+            max_drawdown: 0.0,
+            under_peg_blocks: 0,
+            cvar_95_deviation: 0.0,
+            cvar_99_deviation: 0.0,
+            collateral_ratio_drawdown: 0.0,
         };
     }
 
     let n = metrics.len() as f64;
+    let amm_prices = &metrics.amm_spot_price;
 
-    let deviations: Vec<f64> = metrics
+    let deviations: Vec<f64> = amm_prices
         .iter()
-        .map(|m| ((m.amm_spot_price - target_price) / target_price).abs())
+        .map(|p| ((p - target_price) / target_price).abs())
         .collect();
 
-    let amm_prices: Vec<f64> = metrics.iter().map(|m| m.amm_spot_price).collect();
-
-    let total_liqs: u32 = metrics.iter().map(|m| m.liquidation_count).sum();
+    let count = |flags: &[bool]| flags.iter().filter(|f| **f).count() as u64;
 
-    let trigger_count: u32 = metrics
-        .iter()
-        .map(|m| {
-            m.breaker_actions
-                .iter()
-                .filter(|a| **a != BreakerAction::None)
-                .count() as u32
-        })
-        .sum();
+    // Blocks without debt have no collateral ratio
+    let collateral_ratios: Vec<f64> = metrics
+        .collateral_ratio(&metrics.twap_price)
+        .into_iter()
+        .zip(&metrics.total_debt)
+        .filter(|(_, d)| **d > 0.0)
+        .map(|(r, _)| r)
+        .collect();
 
-    let last = metrics.last().unwrap();
+    let last = metrics.len() - 1;
 
     SummaryMetrics {
         total_blocks: metrics.len() as u64,
         mean_peg_deviation: deviations.iter().sum::<f64>() / n,
         max_peg_deviation: deviations.iter().cloned().fold(0.0_f64, f64::max),
         final_peg_deviation: *deviations.last().unwrap(),
-        total_liquidations: total_liqs,
-        total_bad_debt: last.bad_debt,
-        breaker_triggers: trigger_count,
-        halt_blocks: metrics.iter().filter(|m| m.halted).count() as u64,
-        pause_blocks: metrics.iter().filter(|m| m.minting_paused).count() as u64,
+        total_liquidations: metrics.liquidation_count.iter().sum(),
+        total_bad_debt: metrics.bad_debt[last],
+        breaker_triggers: metrics
+            .breaker_events
+            .iter()
+            .filter(|(_, a)| *a != BreakerAction::None)
+            .count() as u32,
+        halt_blocks: count(&metrics.halted),
+        pause_blocks: count(&metrics.minting_paused),
+        partial_halt_blocks: count(&metrics.partial_halted),
         mean_amm_price: amm_prices.iter().sum::<f64>() / n,
         min_amm_price: amm_prices.iter().cloned().fold(f64::INFINITY, f64::min),
         max_amm_price: amm_prices
             .iter()
             .cloned()
             .fold(f64::NEG_INFINITY, f64::max),
-        final_amm_price: last.amm_spot_price,
-        final_redemption_price: last.redemption_price,
-        final_debt_ceiling: last.debt_ceiling,
+        final_amm_price: amm_prices[last],
+        final_redemption_price: metrics.redemption_price[last],
+        final_debt_ceiling: metrics.debt_ceiling[last],
+        final_treasury_balance: metrics.treasury_balance[last],
+        uncovered_bad_debt: metrics.uncovered_bad_debt[last],
+        max_drawdown: max_drawdown(amm_prices),
+        under_peg_blocks: amm_prices.iter().filter(|p| **p < target_price).count() as u64,
+        cvar_95_deviation: cvar(&deviations, 0.95),
+        cvar_99_deviation: cvar(&deviations, 0.99),
+        collateral_ratio_drawdown: max_drawdown(&collateral_ratios),
+    }
+}
+
+/// Largest fall of `values` from a running peak to a later trough, as a
+/// fraction of the peak; 0 for a series that never falls.
+pub fn max_drawdown(values: &[f64]) -> f64 {
+    let mut peak = f64::NEG_INFINITY;
+    let mut worst = 0.0_f64;
+    for &v in values {
+        peak = peak.max(v);
+        if peak > 0.0 {
+            worst = worst.max((peak - v) / peak);
+        }
+    }
+    worst
+}
+
+/// Conditional value at risk: the mean of the worst `1 - confidence` share
+/// of `values`, taking at least one value.
+pub fn cvar(values: &[f64], confidence: f64) -> f64 {
+    if values.is_empty() {
+        return 0.0;
     }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| b.total_cmp(a));
+    // Tolerance keeps e.g. 100 * (1 - 0.95) = 5.000000000000004 at 5 values
+    let share = values.len() as f64 * (1.0 - confidence) - 1e-9;
+    let tail = (share.ceil() as usize).clamp(1, values.len());
+    sorted[..tail].iter().sum::<f64>() / tail as f64
 }
 
 /// Save events to CSV.
-pub fn save_events_csv(
-    events: &[Event],
-    path: &Path,
-) -> Result<(), Box<dyn std::error::Error>> {
+pub fn save_events_csv(events: &[Event], path: &Path) -> Result<(), ZaiSimError> {
     if let Some(parent) = path.parent() {
         std::fs::create_dir_all(parent)?;
     }
@@ -164,10 +269,7 @@ pub fn save_events_csv(
 }
 
 /// Save summary metrics to JSON.
-pub fn save_metrics_json(
-    summary: &SummaryMetrics,
-    path: &Path,
-) -> Result<(), Box<dyn std::error::Error>> {
+pub fn save_metrics_json(summary: &SummaryMetrics, path: &Path) -> Result<(), ZaiSimError> {
     if let Some(parent) = path.parent() {
         std::fs::create_dir_all(parent)?;
     }
@@ -184,10 +286,15 @@ pub fn save_metrics_json(
     "total": {},
     "bad_debt": {:.2}
   }},
+  "treasury": {{
+    "final_balance": {:.2},
+    "uncovered_bad_debt": {:.2}
+  }},
   "breakers": {{
     "trigger_count": {},
     "halt_blocks": {},
-    "pause_blocks": {}
+    "pause_blocks": {},
+    "partial_halt_blocks": {}
   }},
   "amm_price": {{
     "mean": {:.4},
@@ -195,6 +302,13 @@ pub fn save_metrics_json(
     "max": {:.4},
     "final": {:.4}
   }},
+  "tail_risk": {{
+    "max_drawdown": {:.6},
+    "under_peg_blocks": {},
+    "cvar_95_deviation": {:.6},
+    "cvar_99_deviation": {:.6},
+    "collateral_ratio_drawdown": {:.6}
+  }},
   "final_redemption_price": {:.6},
   "final_debt_ceiling": {:.0}
 }}"#,
@@ -204,13 +318,21 @@ pub fn save_metrics_json(
         summary.final_peg_deviation,
         summary.total_liquidations,
         summary.total_bad_debt,
+        summary.final_treasury_balance,
+        summary.uncovered_bad_debt,
         summary.breaker_triggers,
         summary.halt_blocks,
         summary.pause_blocks,
+        summary.partial_halt_blocks,
         summary.mean_amm_price,
         summary.min_amm_price,
         summary.max_amm_price,
         summary.final_amm_price,
+        summary.max_drawdown,
+        summary.under_peg_blocks,
+        summary.cvar_95_deviation,
+        summary.cvar_99_deviation,
+        summary.collateral_ratio_drawdown,
         summary.final_redemption_price,
         summary.final_debt_ceiling,
     );
@@ -219,11 +341,63 @@ pub fn save_metrics_json(
     Ok(())
 }
 
+#[derive(Serialize)]
+struct StressScenarioJson<'a> {
+    scenario: &'a str,
+    verdict: &'a PassFailResult,
+    summary: &'a SummaryMetrics,
+    expectations: Vec<ExpectationResult>,
+}
+
+#[derive(Serialize)]
+struct StressRunJson<'a> {
+    overall: Verdict,
+    scenarios: Vec<StressScenarioJson<'a>>,
+}
+
+/// Machine-readable stress results: the worst verdict across all scenarios,
+/// plus each scenario's verdict, summary and expectation checks.
+pub fn stress_results_json(
+    entries: &[(ScenarioId, PassFailResult, SummaryMetrics)],
+) -> Result<String, ZaiSimError> {
+    let overall = entries
+        .iter()
+        .fold(Verdict::Pass, |acc, (_, v, _)| acc.worst(v.overall.clone()));
+    let scenarios = entries
+        .iter()
+        .map(|(sid, verdict, summary)| StressScenarioJson {
+            scenario: sid.name(),
+            verdict,
+            summary,
+            expectations: expectations::evaluate(*sid, verdict, summary),
+        })
+        .collect();
+    Ok(serde_json::to_string_pretty(&StressRunJson {
+        overall,
+        scenarios,
+    })?)
+}
+
+/// `stress_results_json` for a single run without acceptance expectations
+/// (a scenario file or a composition).
+pub fn single_stress_results_json(
+    name: &str,
+    verdict: &PassFailResult,
+    summary: &SummaryMetrics,
+) -> Result<String, ZaiSimError> {
+    Ok(serde_json::to_string_pretty(&StressRunJson {
+        overall: verdict.overall.clone(),
+        scenarios: vec![StressScenarioJson {
+            scenario: name,
+            verdict,
+            summary,
+            expectations: Vec::new(),
+        }],
+    })?)
+}
+
 /// Save configuration to TOML format.
-pub fn save_config_toml(
-    config: &ScenarioConfig,
-    path: &Path,
-) -> Result<(), Box<dyn std::error::Error>> {
+pub fn save_config_toml(config: &ScenarioConfig, path: &Path) -> Result<(), ZaiSimError> {
     if let Some(parent) = path.parent() {
         std::fs::create_dir_all(parent)?;
     }
@@ -244,6 +418,14 @@ twap_window = {}
 [controller]
 initial_redemption_price = {:.2}
 
+[liquidation]
+amm_liquidation = {}
+external_oracle = {}
+graduated = {}
+graduated_pct_per_block = {:.4}
+graduated_cr_floor = {:.2}
+zombie_detector = {}
+
 [circuit_breaker.twap]
 max_twap_change_pct = {:.2}
 short_window = {}
@@ -262,8 +444,8 @@ reduction_factor = {:.2}
 growth_rate_per_block = {:.4}
 deviation_threshold = {:.2}
 "#,
-        config.amm_initial_zec,
-        config.amm_initial_zai,
+        config.amm_reserves().0,
+        config.amm_reserves().1,
         config.amm_swap_fee,
         config.cdp_config.min_ratio,
         config.cdp_config.liquidation_penalty,
@@ -271,6 +453,12 @@ deviation_threshold = {:.2}
         config.cdp_config.stability_fee_rate,
         config.cdp_config.twap_window,
         config.initial_redemption_price,
+        config.use_amm_liquidation,
+        config.use_external_oracle_for_liquidation,
+        config.use_graduated_liquidation,
+        config.liquidation_config.graduated_pct_per_block,
+        config.liquidation_config.graduated_cr_floor,
+        config.zombie_detector,
         config.twap_breaker_config.max_twap_change_pct,
         config.twap_breaker_config.short_window,
         config.twap_breaker_config.long_window,
@@ -289,11 +477,77 @@ deviation_threshold = {:.2}
     Ok(())
 }
 
+/// A file a run's prices were read from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PriceSource {
+    pub path: String,
+    /// SHA-256 of the file contents, hex-encoded
+    pub sha256: String,
+    pub bytes: u64,
+}
+
+impl PriceSource {
+    pub fn from_file(path: &Path) -> Result<Self, ZaiSimError> {
+        let contents = std::fs::read(path)?;
+        Ok(Self {
+            path: path.display().to_string(),
+            sha256: format!("{:x}", Sha256::digest(&contents)),
+            bytes: contents.len() as u64,
+        })
+    }
+}
+
+/// Everything needed to reproduce a run, saved as `manifest.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Manifest {
+    pub crate_version: String,
+    /// Commit the binary was built from, when built inside a git checkout
+    pub git_hash: Option<String>,
+    /// UTC, RFC 3339
+    pub created_at: String,
+    pub seeds: Vec<u64>,
+    /// The full config the run used, including fields `config.toml` leaves
+    /// out
+    pub config: ScenarioConfig,
+    pub price_sources: Vec<PriceSource>,
+}
+
+impl Manifest {
+    /// A manifest for runs of `config` with `seeds`, stamped now.
+    pub fn new(config: &ScenarioConfig, seeds: &[u64]) -> Self {
+        Self {
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            git_hash: option_env!("ZAI_SIM_GIT_HASH").map(str::to_string),
+            created_at: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            seeds: seeds.to_vec(),
+            config: config.clone(),
+            price_sources: Vec::new(),
+        }
+    }
+
+    /// Record (and hash) a price file the run read.
+    pub fn with_price_source(mut self, path: &Path) -> Result<Self, ZaiSimError> {
+        self.price_sources.push(PriceSource::from_file(path)?);
+        Ok(self)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), ZaiSimError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Read the `manifest.json` in a `save_all` output directory.
+    pub fn load(dir: &Path) -> Result<Self, ZaiSimError> {
+        let text = std::fs::read_to_string(dir.join("manifest.json"))?;
+        Ok(serde_json::from_str(&text)?)
+    }
+}
+
 /// Save sweep results to CSV.
-pub fn save_sweep_results(
-    results: &[SweepResult],
-    path: &Path,
-) -> Result<(), Box<dyn std::error::Error>> {
+pub fn save_sweep_results(results: &[SweepResult], path: &Path) -> Result<(), ZaiSimError> {
     if let Some(parent) = path.parent() {
         std::fs::create_dir_all(parent)?;
     }
@@ -321,14 +575,49 @@ pub fn save_sweep_results(
     Ok(())
 }
 
-/// Save all outputs for a scenario run to a directory.
+/// Save Sobol indices as one row per outcome and parameter.
+pub fn save_sensitivity_csv(report: &SensitivityReport, path: &Path) -> Result<(), ZaiSimError> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut wtr = csv::Writer::from_path(path)?;
+    wtr.write_record(["outcome", "param", "first_order", "total"])?;
+    for o in &report.outcomes {
+        for idx in &o.indices {
+            wtr.write_record([
+                o.outcome.clone(),
+                idx.param.clone(),
+                format!("{:.6}", idx.first_order),
+                format!("{:.6}", idx.total),
+            ])?;
+        }
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+/// Save all outputs for a scenario run to a directory, with a manifest of
+/// its config and seed.
 pub fn save_all(
     scenario: &Scenario,
     config: &ScenarioConfig,
     target_price: f64,
     output_dir: &Path,
-) -> Result<(), Box<dyn std::error::Error>> {
+) -> Result<(), ZaiSimError> {
+    let manifest = Manifest::new(config, &[scenario.seed]);
+    save_all_with_manifest(scenario, config, target_price, output_dir, &manifest)
+}
+
+/// `save_all` with a caller-built manifest, e.g. one listing price files.
+pub fn save_all_with_manifest(
+    scenario: &Scenario,
+    config: &ScenarioConfig,
+    target_price: f64,
+    output_dir: &Path,
+    manifest: &Manifest,
+) -> Result<(), ZaiSimError> {
     std::fs::create_dir_all(output_dir)?;
+    manifest.save(&output_dir.join("manifest.json"))?;
 
     scenario.save_metrics_csv(&output_dir.join("timeseries.csv"))?;
 
@@ -340,5 +629,584 @@ pub fn save_all(
 
     save_config_toml(config, &output_dir.join("config.toml"))?;
 
+    if !scenario.snapshots.is_empty() {
+        save_snapshots_csv(&scenario.snapshots, &output_dir.join("snapshots.csv"))?;
+    }
+
+    if let Some(collector) = &scenario.agent_metrics {
+        save_agent_metrics_csv(&collector.samples, &output_dir.join("agent_metrics.csv"))?;
+    }
+
     Ok(())
 }
+
+/// A run read back from a `save_all` output directory.
+#[derive(Debug, Clone)]
+pub struct SavedRun {
+    /// Directory name
+    pub name: String,
+    /// `timeseries.csv` columns in file order. Booleans read as 0/1 and
+    /// other non-numeric values as NaN.
+    pub series: Vec<(String, Vec<f64>)>,
+    /// `config.toml` settings keyed by dotted path, e.g. `cdp.min_ratio`
+    pub config: Vec<(String, String)>,
+    /// Numeric `metrics.json` values keyed by dotted path
+    pub summary: Vec<(String, f64)>,
+}
+
+impl SavedRun {
+    pub fn series(&self, column: &str) -> Option<&[f64]> {
+        self.series
+            .iter()
+            .find(|(name, _)| name == column)
+            .map(|(_, values)| values.as_slice())
+    }
+}
+
+/// Load the time series, config and summary written by `save_all`.
+pub fn load_run(dir: &Path) -> Result<SavedRun, ZaiSimError> {
+    let mut rdr = csv::Reader::from_path(dir.join("timeseries.csv"))?;
+    let mut series: Vec<(String, Vec<f64>)> = rdr
+        .headers()?
+        .iter()
+        .map(|h| (h.to_string(), Vec::new()))
+        .collect();
+    for result in rdr.records() {
+        let record = result?;
+        for ((_, values), field) in series.iter_mut().zip(record.iter()) {
+            values.push(match field {
+                "true" => 1.0,
+                "false" => 0.0,
+                _ => field.parse().unwrap_or(f64::NAN),
+            });
+        }
+    }
+
+    let text = std::fs::read_to_string(dir.join("config.toml"))?;
+    let table: toml::Table = toml::from_str(&text)
+        .map_err(|e| ZaiSimError::Parse(format!("{}: {}", dir.display(), e)))?;
+    let mut config = Vec::new();
+    flatten_toml("", &table, &mut config);
+
+    let text = std::fs::read_to_string(dir.join("metrics.json"))?;
+    let mut summary = Vec::new();
+    flatten_json("", &serde_json::from_str(&text)?, &mut summary);
+
+    Ok(SavedRun {
+        name: dir.file_name().map_or_else(
+            || dir.display().to_string(),
+            |n| n.to_string_lossy().into_owned(),
+        ),
+        series,
+        config,
+        summary,
+    })
+}
+
+fn flatten_toml(prefix: &str, table: &toml::Table, out: &mut Vec<(String, String)>) {
+    for (key, value) in table {
+        let path = format!("{}{}", prefix, key);
+        match value {
+            toml::Value::Table(inner) => flatten_toml(&format!("{}.", path), inner, out),
+            other => out.push((path, other.to_string())),
+        }
+    }
+}
+
+fn flatten_json(prefix: &str, value: &serde_json::Value, out: &mut Vec<(String, f64)>) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, inner) in map {
+                let path = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", prefix, key)
+                };
+                flatten_json(&path, inner, out);
+            }
+        }
+        other => {
+            if let Some(v) = other.as_f64() {
+                out.push((prefix.to_string(), v));
+            }
+        }
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+// SQLite results store
+// ═══════════════════════════════════════════════════════════════════════
+
+/// Per-block columns, named as in `timeseries.csv`.
+#[cfg(feature = "sqlite")]
+const BLOCK_COLUMNS: &[&str] = &[
+    "block",
+    "external_price",
+    "amm_spot_price",
+    "twap_price",
+    "redemption_price",
+    "redemption_rate",
+    "total_debt",
+    "reserve_zec",
+    "reserve_zai",
+    "vault_count",
+    "liquidations",
+    "bad_debt",
+    "breaker_triggers",
+    "debt_ceiling",
+    "minting_paused",
+    "halted",
+    "total_collateral",
+    "total_lp_shares",
+    "arber_zai_total",
+    "zombie_vault_count",
+    "max_zombie_gap",
+    "mean_cr_twap",
+    "mean_cr_ext",
+    "arber_zec_total",
+    "cumulative_fees_zai",
+    "cumulative_il_pct",
+    "graduated_liquidations",
+    "partial_halted",
+    "cumulative_redeemed_zai",
+    "treasury_balance",
+    "uncovered_bad_debt",
+    "lending_zec_utilization",
+    "lending_zec_borrow_rate",
+    "vaults_in_grace",
+    "penalty_to_keepers",
+    "penalty_to_lps",
+    "penalty_to_insurance",
+    "penalty_to_treasury",
+    "penalty_burned",
+    "insurance_fund_balance",
+];
+
+#[cfg(feature = "sqlite")]
+fn block_values(m: &BlockMetrics) -> Vec<SqlValue> {
+    let int = |v: u64| SqlValue::Integer(v as i64);
+    let flag = |b: bool| SqlValue::Integer(b as i64);
+    let triggers = m
+        .breaker_actions
+        .iter()
+        .filter(|a| **a != BreakerAction::None)
+        .count();
+    vec![
+        int(m.block),
+        SqlValue::Real(m.external_price),
+        SqlValue::Real(m.amm_spot_price),
+        SqlValue::Real(m.twap_price),
+        SqlValue::Real(m.redemption_price),
+        SqlValue::Real(m.redemption_rate),
+        SqlValue::Real(m.total_debt),
+        SqlValue::Real(m.amm_reserve_zec),
+        SqlValue::Real(m.amm_reserve_zai),
+        int(m.vault_count),
+        int(m.liquidation_count as u64),
+        SqlValue::Real(m.bad_debt),
+        int(triggers as u64),
+        SqlValue::Real(m.debt_ceiling),
+        flag(m.minting_paused),
+        flag(m.halted),
+        SqlValue::Real(m.total_collateral),
+        SqlValue::Real(m.total_lp_shares),
+        SqlValue::Real(m.arber_zai_total),
+        int(m.zombie_vault_count as u64),
+        SqlValue::Real(m.max_zombie_gap),
+        SqlValue::Real(m.mean_collateral_ratio_twap),
+        SqlValue::Real(m.mean_collateral_ratio_ext),
+        SqlValue::Real(m.arber_zec_total),
+        SqlValue::Real(m.cumulative_fees_zai),
+        SqlValue::Real(m.cumulative_il_pct),
+        int(m.graduated_liquidation_count as u64),
+        flag(m.partial_halted),
+        SqlValue::Real(m.cumulative_redeemed_zai),
+        SqlValue::Real(m.treasury_balance),
+        SqlValue::Real(m.uncovered_bad_debt),
+        SqlValue::Real(m.lending_zec_utilization),
+        SqlValue::Real(m.lending_zec_borrow_rate),
+        int(m.vaults_in_grace as u64),
+        SqlValue::Real(m.penalty_to_keepers),
+        SqlValue::Real(m.penalty_to_lps),
+        SqlValue::Real(m.penalty_to_insurance),
+        SqlValue::Real(m.penalty_to_treasury),
+        SqlValue::Real(m.penalty_burned),
+        SqlValue::Real(m.insurance_fund_balance),
+    ]
+}
+
+/// Summary columns, named as the `SummaryMetrics` fields.
+pub const SUMMARY_COLUMNS: &[&str] = &[
+    "total_blocks",
+    "mean_peg_deviation",
+    "max_peg_deviation",
+    "final_peg_deviation",
+    "total_liquidations",
+    "total_bad_debt",
+    "breaker_triggers",
+    "halt_blocks",
+    "pause_blocks",
+    "partial_halt_blocks",
+    "mean_amm_price",
+    "min_amm_price",
+    "max_amm_price",
+    "final_amm_price",
+    "final_redemption_price",
+    "final_debt_ceiling",
+    "final_treasury_balance",
+    "uncovered_bad_debt",
+    "max_drawdown",
+    "under_peg_blocks",
+    "cvar_95_deviation",
+    "cvar_99_deviation",
+    "collateral_ratio_drawdown",
+];
+
+#[cfg(feature = "sqlite")]
+fn summary_values(s: &SummaryMetrics) -> Vec<SqlValue> {
+    let int = |v: u64| SqlValue::Integer(v as i64);
+    vec![
+        int(s.total_blocks),
+        SqlValue::Real(s.mean_peg_deviation),
+        SqlValue::Real(s.max_peg_deviation),
+        SqlValue::Real(s.final_peg_deviation),
+        int(s.total_liquidations as u64),
+        SqlValue::Real(s.total_bad_debt),
+        int(s.breaker_triggers as u64),
+        int(s.halt_blocks),
+        int(s.pause_blocks),
+        int(s.partial_halt_blocks),
+        SqlValue::Real(s.mean_amm_price),
+        SqlValue::Real(s.min_amm_price),
+        SqlValue::Real(s.max_amm_price),
+        SqlValue::Real(s.final_amm_price),
+        SqlValue::Real(s.final_redemption_price),
+        SqlValue::Real(s.final_debt_ceiling),
+        SqlValue::Real(s.final_treasury_balance),
+        SqlValue::Real(s.uncovered_bad_debt),
+        SqlValue::Real(s.max_drawdown),
+        int(s.under_peg_blocks),
+        SqlValue::Real(s.cvar_95_deviation),
+        SqlValue::Real(s.cvar_99_deviation),
+        SqlValue::Real(s.collateral_ratio_drawdown),
+    ]
+}
+
+/// Run columns that `SqliteStore::aggregate` can group by.
+#[cfg(feature = "sqlite")]
+pub const RUN_GROUP_COLUMNS: &[&str] = &["label", "scenario", "seed", "verdict"];
+
+/// `INSERT INTO table (run_id, cols...) VALUES (?, ...)`
+#[cfg(feature = "sqlite")]
+fn insert_sql(table: &str, columns: &[&str]) -> String {
+    format!(
+        "INSERT INTO {} (run_id, {}) VALUES (?{})",
+        table,
+        columns.join(", "),
+        ", ?".repeat(columns.len())
+    )
+}
+
+/// A value read back from a query.
+#[cfg(feature = "sqlite")]
+pub use rusqlite::types::Value as SqlValue;
+
+/// Result of an ad-hoc query: column names and rows.
+#[cfg(feature = "sqlite")]
+#[derive(Debug, Clone)]
+pub struct QueryResult {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<SqlValue>>,
+}
+
+/// Render a query value for plain-text output; BLOBs are shown as SQL hex
+/// literals.
+#[cfg(feature = "sqlite")]
+pub fn sql_value_text(value: &SqlValue) -> String {
+    match value {
+        SqlValue::Null => "NULL".to_string(),
+        SqlValue::Integer(i) => i.to_string(),
+        SqlValue::Real(r) => r.to_string(),
+        SqlValue::Text(s) => s.clone(),
+        SqlValue::Blob(b) => {
+            let hex: String = b.iter().map(|byte| format!("{:02X}", byte)).collect();
+            format!("X'{}'", hex)
+        }
+    }
+}
+
+/// Single-file SQLite store for run configs, per-block metrics, summaries
+/// and verdicts, so large sweeps and Monte Carlo batches can be analyzed with
+/// SQL instead of hundreds of CSVs.
+#[cfg(feature = "sqlite")]
+pub struct SqliteStore {
+    conn: Connection,
+}
+
+#[cfg(feature = "sqlite")]
+impl SqliteStore {
+    /// Open (or create) the store at `path` and ensure the schema exists.
+    pub fn open(path: &Path) -> Result<Self, ZaiSimError> {
+        if let Some(parent) = path.parent() {
+            if !parent.as_os_str().is_empty() {
+                std::fs::create_dir_all(parent)?;
+            }
+        }
+        let conn = Connection::open(path)?;
+
+        // Block numbers stay integers so `MAX(block)` and joins on them do too
+        let block_cols: Vec<String> = BLOCK_COLUMNS
+            .iter()
+            .map(|c| match *c {
+                "block" => "block INTEGER NOT NULL".to_string(),
+                c => format!("{} REAL", c),
+            })
+            .collect();
+        let summary_cols: Vec<String> =
+            SUMMARY_COLUMNS.iter().map(|c| format!("{} REAL", c)).collect();
+        conn.execute_batch(&format!(
+            r#"
+CREATE TABLE IF NOT EXISTS runs (
+    id INTEGER PRIMARY KEY,
+    label TEXT NOT NULL,
+    scenario TEXT NOT NULL,
+    seed INTEGER NOT NULL,
+    blocks INTEGER NOT NULL,
+    target_price REAL NOT NULL,
+    verdict TEXT,
+    config TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_runs_label ON runs (label);
+CREATE INDEX IF NOT EXISTS idx_runs_scenario ON runs (scenario);
+
+CREATE TABLE IF NOT EXISTS block_metrics (
+    run_id INTEGER NOT NULL REFERENCES runs (id),
+    {}
+);
+CREATE INDEX IF NOT EXISTS idx_block_metrics_run ON block_metrics (run_id, block);
+
+CREATE TABLE IF NOT EXISTS summaries (
+    run_id INTEGER PRIMARY KEY REFERENCES runs (id),
+    {}
+);
+
+CREATE TABLE IF NOT EXISTS verdicts (
+    run_id INTEGER NOT NULL REFERENCES runs (id),
+    criterion TEXT NOT NULL,
+    passed INTEGER NOT NULL,
+    severity TEXT NOT NULL,
+    details TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_verdicts_run ON verdicts (run_id);
+
+CREATE TABLE IF NOT EXISTS sweep_results (
+    id INTEGER PRIMARY KEY,
+    label TEXT NOT NULL,
+    overall_score REAL NOT NULL
+);
+CREATE TABLE IF NOT EXISTS sweep_params (
+    result_id INTEGER NOT NULL REFERENCES sweep_results (id),
+    name TEXT NOT NULL,
+    value REAL NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_sweep_params ON sweep_params (result_id, name);
+CREATE TABLE IF NOT EXISTS sweep_scores (
+    result_id INTEGER NOT NULL REFERENCES sweep_results (id),
+    scenario TEXT NOT NULL,
+    score REAL NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_sweep_scores ON sweep_scores (result_id, scenario);
+"#,
+            block_cols.join(",\n    "),
+            summary_cols.join(",\n    "),
+        ))?;
+
+        Ok(Self { conn })
+    }
+
+    /// Run `f` inside a transaction, rolling back if it fails.
+    fn transaction<T>(&self, f: impl FnOnce() -> Result<T, ZaiSimError>) -> Result<T, ZaiSimError> {
+        let tx = self.conn.unchecked_transaction()?;
+        let v = f()?;
+        tx.commit()?;
+        Ok(v)
+    }
+
+    /// Record a run's configuration and return its id.
+    pub fn insert_run(
+        &self,
+        label: &str,
+        scenario: &str,
+        seed: u64,
+        blocks: usize,
+        target_price: f64,
+        config: &ScenarioConfig,
+    ) -> Result<i64, ZaiSimError> {
+        let config_json = serde_json::to_string(config)?;
+        self.conn.execute(
+            "INSERT INTO runs (label, scenario, seed, blocks, target_price, config) \
+             VALUES (?, ?, ?, ?, ?, ?)",
+            params![
+                label,
+                scenario,
+                seed as i64,
+                blocks as i64,
+                target_price,
+                config_json
+            ],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    pub fn insert_block_metrics(
+        &self,
+        run_id: i64,
+        metrics: &MetricsStore,
+    ) -> Result<(), ZaiSimError> {
+        let mut stmt = self
+            .conn
+            .prepare(&insert_sql("block_metrics", BLOCK_COLUMNS))?;
+        for m in metrics {
+            let mut row = vec![SqlValue::Integer(run_id)];
+            row.extend(block_values(&m));
+            stmt.execute(params_from_iter(row))?;
+        }
+        Ok(())
+    }
+
+    /// Append per-block metrics to a run in one transaction, e.g. a chunk
+    /// streamed by `metrics_sink::SqliteSink`.
+    pub fn append_block_metrics(
+        &self,
+        run_id: i64,
+        metrics: &MetricsStore,
+    ) -> Result<(), ZaiSimError> {
+        self.transaction(|| self.insert_block_metrics(run_id, metrics))
+    }
+
+    pub fn insert_summary(&self, run_id: i64, summary: &SummaryMetrics) -> Result<(), ZaiSimError> {
+        let mut row = vec![SqlValue::Integer(run_id)];
+        row.extend(summary_values(summary));
+        self.conn.execute(
+            &insert_sql("summaries", SUMMARY_COLUMNS),
+            params_from_iter(row),
+        )?;
+        Ok(())
+    }
+
+    pub fn insert_verdict(&self, run_id: i64, verdict: &PassFailResult) -> Result<(), ZaiSimError> {
+        self.conn.execute(
+            "UPDATE runs SET verdict = ? WHERE id = ?",
+            params![verdict.overall.label(), run_id],
+        )?;
+        let mut stmt = self.conn.prepare(
+            "INSERT INTO verdicts (run_id, criterion, passed, severity, details) \
+             VALUES (?, ?, ?, ?, ?)",
+        )?;
+        for c in &verdict.criteria {
+            stmt.execute(params![
+                run_id,
+                c.name,
+                c.passed,
+                c.severity.label(),
+                c.details
+            ])?;
+        }
+        Ok(())
+    }
+
+    /// Store a finished run: config, per-block metrics, summary and verdict.
+    pub fn save_run(
+        &self,
+        label: &str,
+        scenario_name: &str,
+        seed: u64,
+        scenario: &Scenario,
+        target_price: f64,
+    ) -> Result<i64, ZaiSimError> {
+        let summary = compute_summary(&scenario.metrics, target_price);
+        let verdict = evaluate_pass_fail(&scenario.metrics, target_price);
+        self.transaction(|| {
+            let run_id = self.insert_run(
+                label,
+                scenario_name,
+                seed,
+                scenario.metrics.len(),
+                target_price,
+                &scenario.config,
+            )?;
+            self.insert_block_metrics(run_id, &scenario.metrics)?;
+            self.insert_summary(run_id, &summary)?;
+            self.insert_verdict(run_id, &verdict)?;
+            Ok(run_id)
+        })
+    }
+
+    /// Store sweep results: one row per configuration, with its parameters
+    /// and per-scenario scores in side tables.
+    pub fn save_sweep_results(
+        &self,
+        label: &str,
+        results: &[SweepResult],
+    ) -> Result<(), ZaiSimError> {
+        self.transaction(|| {
+            for r in results {
+                self.conn.execute(
+                    "INSERT INTO sweep_results (label, overall_score) VALUES (?, ?)",
+                    params![label, r.overall_score],
+                )?;
+                let id = self.conn.last_insert_rowid();
+                for (name, value) in &r.params {
+                    self.conn.execute(
+                        "INSERT INTO sweep_params (result_id, name, value) VALUES (?, ?, ?)",
+                        params![id, name, value],
+                    )?;
+                }
+                for (sid, score) in &r.scores {
+                    self.conn.execute(
+                        "INSERT INTO sweep_scores (result_id, scenario, score) VALUES (?, ?, ?)",
+                        params![id, sid.name(), score],
+                    )?;
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// Run arbitrary SQL and return every row.
+    pub fn query(&self, sql: &str) -> Result<QueryResult, ZaiSimError> {
+        let mut stmt = self.conn.prepare(sql)?;
+        let columns: Vec<String> = stmt.column_names().into_iter().map(String::from).collect();
+        let rows = stmt
+            .query_map([], |row| (0..columns.len()).map(|i| row.get(i)).collect())?
+            .collect::<Result<_, _>>()?;
+        Ok(QueryResult { columns, rows })
+    }
+
+    /// Count, mean, min and max of a summary metric, grouped by a run column
+    /// (`label`, `scenario`, `seed` or `verdict`).
+    pub fn aggregate(&self, metric: &str, group_by: &str) -> Result<QueryResult, ZaiSimError> {
+        if !SUMMARY_COLUMNS.contains(&metric) {
+            return Err(ZaiSimError::UnknownMetric(format!(
+                "'{}' (expected one of: {})",
+                metric,
+                SUMMARY_COLUMNS.join(", ")
+            )));
+        }
+        if !RUN_GROUP_COLUMNS.contains(&group_by) {
+            return Err(ZaiSimError::InvalidInput(format!(
+                "cannot group by '{}' (expected one of: {})",
+                group_by,
+                RUN_GROUP_COLUMNS.join(", ")
+            )));
+        }
+        self.query(&format!(
+            "SELECT r.{g} AS {g}, COUNT(*) AS runs, AVG(s.{m}) AS mean, \
+             MIN(s.{m}) AS min, MAX(s.{m}) AS max \
+             FROM runs r JOIN summaries s ON s.run_id = r.id \
+             GROUP BY r.{g} ORDER BY r.{g}",
+            g = group_by,
+            m = metric
+        ))
+    }
+}
@@ -0,0 +1,132 @@
+//! Minimal PDF writer for plain-text documents.
+//!
+//! Lays out lines of monospaced text on A4 pages with the standard Courier
+//! fonts, which every PDF reader provides, so no font or layout library is
+//! needed. Enough for printable report summaries; no graphics.
+
+const PAGE_WIDTH: f64 = 595.0;
+const PAGE_HEIGHT: f64 = 842.0;
+const MARGIN: f64 = 50.0;
+const FONT_SIZE: f64 = 9.0;
+const TITLE_SIZE: f64 = 14.0;
+const LEADING: f64 = 12.0;
+/// Courier glyphs are 0.6 em wide.
+const CHARS_PER_LINE: usize = ((PAGE_WIDTH - 2.0 * MARGIN) / (0.6 * FONT_SIZE)) as usize;
+const LINES_PER_PAGE: usize = ((PAGE_HEIGHT - 2.0 * MARGIN) / LEADING) as usize;
+
+/// Render `lines` under a bold `title` as a PDF document. Long lines wrap,
+/// and characters outside printable ASCII are transliterated or replaced.
+pub fn text_document(title: &str, lines: &[String]) -> Vec<u8> {
+    let mut wrapped = Vec::new();
+    for line in lines {
+        let line = to_ascii(line);
+        let chars: Vec<char> = line.chars().collect();
+        if chars.is_empty() {
+            wrapped.push(String::new());
+        }
+        for chunk in chars.chunks(CHARS_PER_LINE) {
+            wrapped.push(chunk.iter().collect());
+        }
+    }
+    // The title takes two lines of the first page
+    let mut pages: Vec<&[String]> = Vec::new();
+    let first = wrapped.len().min(LINES_PER_PAGE - 2);
+    pages.push(&wrapped[..first]);
+    pages.extend(wrapped[first..].chunks(LINES_PER_PAGE));
+
+    // Objects 1-4 are the catalog, page tree and fonts; each page then
+    // takes a page object and a content stream.
+    let page_ids: Vec<usize> = (0..pages.len()).map(|i| 5 + 2 * i).collect();
+    let mut objects = vec![
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            page_ids
+                .iter()
+                .map(|id| format!("{} 0 R", id))
+                .collect::<Vec<_>>()
+                .join(" "),
+            pages.len()
+        ),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Courier >>".to_string(),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Courier-Bold >>".to_string(),
+    ];
+    for (i, page) in pages.iter().enumerate() {
+        let mut content = format!(
+            "BT\n{} TL\n{} {} Td\n",
+            LEADING,
+            MARGIN,
+            PAGE_HEIGHT - MARGIN - LEADING
+        );
+        if i == 0 {
+            content.push_str(&format!(
+                "/F2 {} Tf\n({}) Tj\nT* T*\n",
+                TITLE_SIZE,
+                escape(&to_ascii(title))
+            ));
+        }
+        content.push_str(&format!("/F1 {} Tf\n", FONT_SIZE));
+        for line in page.iter() {
+            content.push_str(&format!("({}) Tj T*\n", escape(line)));
+        }
+        content.push_str("ET");
+
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] \
+             /Resources << /Font << /F1 3 0 R /F2 4 0 R >> >> /Contents {} 0 R >>",
+            PAGE_WIDTH,
+            PAGE_HEIGHT,
+            page_ids[i] + 1
+        ));
+        objects.push(format!(
+            "<< /Length {} >>\nstream\n{}\nendstream",
+            content.len(),
+            content
+        ));
+    }
+
+    let mut pdf = String::from("%PDF-1.4\n");
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.push_str(&format!("{} 0 obj\n{}\nendobj\n", i + 1, object));
+    }
+    let xref = pdf.len();
+    pdf.push_str(&format!(
+        "xref\n0 {}\n0000000000 65535 f \n",
+        objects.len() + 1
+    ));
+    for offset in offsets {
+        pdf.push_str(&format!("{:010} 00000 n \n", offset));
+    }
+    pdf.push_str(&format!(
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+        objects.len() + 1,
+        xref
+    ));
+    pdf.into_bytes()
+}
+
+/// Map text onto printable ASCII, which the standard fonts render as-is.
+fn to_ascii(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            ' '..='~' => out.push(c),
+            '\t' => out.push_str("    "),
+            '—' | '–' | '─' | '═' => out.push('-'),
+            '≥' => out.push_str(">="),
+            '≤' => out.push_str("<="),
+            '×' => out.push('x'),
+            _ => out.push('?'),
+        }
+    }
+    out
+}
+
+/// Escape the characters that delimit PDF string literals.
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('(', "\\(")
+        .replace(')', "\\)")
+}
@@ -0,0 +1,176 @@
+//! ZEC perpetual futures funding and open interest.
+//!
+//! A perp's funding rate is what longs pay shorts each interval to hold the
+//! contract near the index. When funding is rich, basis arbitrageurs buy
+//! spot ZEC (here on the AMM, with ZAI) and short the perp to collect it,
+//! and do the reverse when it is negative, so perp funding drives AMM flow.
+//! Their hedges in turn press funding back toward zero in proportion to
+//! their share of open interest, so a ZAI demand shock that moves the AMM
+//! also shows up in the perp.
+//!
+//! `fetch-funding` records Binance's historical funding rates and open
+//! interest; `PerpConfig::from_history` replays them.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::ZaiSimError;
+
+/// Binance pays ZEC perp funding every 8 hours: 384 blocks of 75 s.
+pub const FUNDING_INTERVAL_BLOCKS: u64 = 384;
+
+/// One historical funding payment.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FundingSample {
+    pub timestamp_ms: u64,
+    /// Rate for the interval (0.0001 = 0.01%); positive = longs pay shorts
+    pub rate: f64,
+    /// Perp mark price at the payment (0 where the venue doesn't report it)
+    pub mark_price: f64,
+}
+
+/// Open interest at one point in time.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OpenInterestSample {
+    pub timestamp_ms: u64,
+    /// Open contracts in ZEC
+    pub open_interest_zec: f64,
+    /// Open interest at the mark price, in the quote currency
+    pub notional: f64,
+}
+
+fn save_rows<T: Serialize>(rows: &[T], path: &Path) -> Result<(), ZaiSimError> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut wtr = csv::Writer::from_path(path)?;
+    for row in rows {
+        wtr.serialize(row)?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+fn load_rows<T: for<'de> Deserialize<'de>>(path: &Path) -> Result<Vec<T>, ZaiSimError> {
+    let mut rdr = csv::Reader::from_path(path)?;
+    let mut rows = Vec::new();
+    for row in rdr.deserialize() {
+        rows.push(row?);
+    }
+    Ok(rows)
+}
+
+pub fn save_funding(samples: &[FundingSample], path: &Path) -> Result<(), ZaiSimError> {
+    save_rows(samples, path)
+}
+
+pub fn load_funding(path: &Path) -> Result<Vec<FundingSample>, ZaiSimError> {
+    load_rows(path)
+}
+
+pub fn save_open_interest(samples: &[OpenInterestSample], path: &Path) -> Result<(), ZaiSimError> {
+    save_rows(samples, path)
+}
+
+pub fn load_open_interest(path: &Path) -> Result<Vec<OpenInterestSample>, ZaiSimError> {
+    load_rows(path)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PerpConfig {
+    /// Funding rate per interval when there is no schedule (0.0001 =
+    /// 0.01% per 8 h, Binance's baseline)
+    pub funding_rate: f64,
+    /// Rates for successive intervals from block 1, e.g. from
+    /// `fetch-funding`; the last one holds once the schedule runs out
+    #[serde(default)]
+    pub funding_schedule: Vec<f64>,
+    /// Blocks between funding payments
+    pub funding_interval_blocks: u64,
+    /// Open interest in ZEC that arbitrage positions are measured against
+    pub open_interest_zec: f64,
+    /// Funding rate change per interval if arbitrageurs were short all of
+    /// open interest
+    pub funding_impact: f64,
+    /// Cap on the funding rate per interval, either way
+    pub funding_cap: f64,
+}
+
+impl Default for PerpConfig {
+    fn default() -> Self {
+        PerpConfig {
+            funding_rate: 0.0001,
+            funding_schedule: Vec::new(),
+            funding_interval_blocks: FUNDING_INTERVAL_BLOCKS,
+            open_interest_zec: 200_000.0,
+            funding_impact: 0.01,
+            funding_cap: 0.0075,
+        }
+    }
+}
+
+impl PerpConfig {
+    /// Defaults with the historical funding rates as the schedule and the
+    /// mean of `open_interest` (if any) as the open interest.
+    pub fn from_history(funding: &[FundingSample], open_interest: &[OpenInterestSample]) -> Self {
+        let mut config = PerpConfig {
+            funding_schedule: funding.iter().map(|s| s.rate).collect(),
+            ..PerpConfig::default()
+        };
+        if !open_interest.is_empty() {
+            config.open_interest_zec = open_interest
+                .iter()
+                .map(|s| s.open_interest_zec)
+                .sum::<f64>()
+                / open_interest.len() as f64;
+        }
+        config
+    }
+}
+
+/// The perp as basis arbitrageurs see it: an exogenous funding rate,
+/// pushed back by their own net position.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PerpMarket {
+    pub config: PerpConfig,
+    /// ZEC the basis arbitrageurs are net short on the perp (negative = long)
+    pub arb_short_zec: f64,
+    /// Funding received by the arbitrageurs so far (ZAI; negative = paid)
+    pub total_funding_zai: f64,
+}
+
+impl PerpMarket {
+    pub fn new(config: PerpConfig) -> Self {
+        PerpMarket {
+            config,
+            arb_short_zec: 0.0,
+            total_funding_zai: 0.0,
+        }
+    }
+
+    /// Funding rate at `block` before arbitrage: the scheduled rate for its
+    /// interval, or the flat rate without a schedule.
+    pub fn base_rate(&self, block: u64) -> f64 {
+        let interval = block.saturating_sub(1) / self.config.funding_interval_blocks.max(1);
+        let schedule = &self.config.funding_schedule;
+        schedule
+            .get(interval as usize)
+            .or(schedule.last())
+            .copied()
+            .unwrap_or(self.config.funding_rate)
+    }
+
+    /// Funding rate at `block`, with the arbitrageurs' shorts pressing it
+    /// down (and longs up) by their share of open interest.
+    pub fn funding_rate(&self, block: u64) -> f64 {
+        let share = self.arb_short_zec / self.config.open_interest_zec.max(f64::EPSILON);
+        let cap = self.config.funding_cap.abs();
+        (self.base_rate(block) - self.config.funding_impact * share).clamp(-cap, cap)
+    }
+
+    /// Whether funding is paid at the end of `block`.
+    pub fn is_funding_block(&self, block: u64) -> bool {
+        block > 0 && block.is_multiple_of(self.config.funding_interval_blocks.max(1))
+    }
+}
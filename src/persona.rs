@@ -0,0 +1,212 @@
+//! Cross-run portfolio report for a single persona.
+//!
+//! Follows one user (an LP of a given size, or a vault owner at a given
+//! collateral ratio) through every stress scenario and summarizes what
+//! happens to them: final P&L, how often they are liquidated and their
+//! worst drawdown. This turns system-level results into user-facing risk
+//! disclosures.
+
+use crate::agents::{CdpHolder, CdpHolderConfig, LpAgent, LpAgentConfig};
+use crate::scenario::ScenarioConfig;
+use crate::scenarios::{run_stress_with, ScenarioId};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Persona {
+    /// LP depositing `capital_zai` of value, split evenly at the initial AMM price
+    Lp { capital_zai: f64 },
+    /// Vault owner opening at `collateral_ratio` with `debt_zai` drawn, and
+    /// `reserve_zec` set aside to top up if the ratio falls by a quarter
+    VaultOwner {
+        collateral_ratio: f64,
+        debt_zai: f64,
+        reserve_zec: f64,
+    },
+}
+
+impl Persona {
+    pub fn describe(&self) -> String {
+        match self {
+            Persona::Lp { capital_zai } => format!("LP with {:.0} ZAI of liquidity", capital_zai),
+            Persona::VaultOwner {
+                collateral_ratio,
+                debt_zai,
+                reserve_zec,
+            } => format!(
+                "Vault owner at {:.0}% CR, {:.0} ZAI debt, {:.1} ZEC reserve",
+                collateral_ratio * 100.0,
+                debt_zai,
+                reserve_zec
+            ),
+        }
+    }
+}
+
+/// The persona's result in one scenario run.
+#[derive(Debug, Clone)]
+pub struct PersonaOutcome {
+    pub scenario: ScenarioId,
+    pub seed: u64,
+    pub initial_value_zai: f64,
+    pub final_pnl_zai: f64,
+    pub final_pnl_pct: f64,
+    pub liquidated: bool,
+    pub liquidation_block: Option<u64>,
+    /// Largest peak-to-trough fall in value, as a fraction of the peak
+    pub worst_drawdown_pct: f64,
+}
+
+/// Outcomes for one scenario across seeds.
+#[derive(Debug, Clone)]
+pub struct PersonaScenarioSummary {
+    pub scenario: ScenarioId,
+    pub mean_pnl_pct: f64,
+    pub worst_pnl_pct: f64,
+    pub liquidation_probability: f64,
+    pub worst_drawdown_pct: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct PersonaReport {
+    pub persona: Persona,
+    pub blocks: usize,
+    pub outcomes: Vec<PersonaOutcome>,
+    pub scenarios: Vec<PersonaScenarioSummary>,
+    /// Fraction of all runs in which the persona was liquidated
+    pub liquidation_probability: f64,
+    pub worst_pnl_pct: f64,
+    pub worst_drawdown_pct: f64,
+}
+
+fn max_drawdown(values: &[f64]) -> f64 {
+    let mut peak = f64::MIN;
+    let mut worst = 0.0f64;
+    for &v in values {
+        peak = peak.max(v);
+        if peak > 0.0 {
+            worst = worst.max((peak - v) / peak);
+        }
+    }
+    worst
+}
+
+/// Run `persona` through one stress scenario.
+pub fn run_persona_scenario(
+    persona: &Persona,
+    id: ScenarioId,
+    config: &ScenarioConfig,
+    blocks: usize,
+    seed: u64,
+) -> PersonaOutcome {
+    let config = ScenarioConfig {
+        record_agent_metrics: true,
+        ..config.clone()
+    };
+    let initial_price = config.initial_amm_price();
+
+    let mut agent_id = String::new();
+    let mut vault_id = None;
+    let scenario = run_stress_with(id, &config, blocks, seed, |scenario| match persona {
+        Persona::Lp { capital_zai } => {
+            agent_id = format!("lp_{}", scenario.lp_agents.len());
+            scenario.lp_agents.push(LpAgent::new(LpAgentConfig {
+                initial_zec: capital_zai / 2.0 / initial_price,
+                initial_zai: capital_zai / 2.0,
+                ..LpAgentConfig::default()
+            }));
+        }
+        Persona::VaultOwner {
+            collateral_ratio,
+            debt_zai,
+            reserve_zec,
+        } => {
+            agent_id = format!("cdp_holder_{}", scenario.cdp_holders.len());
+            let mut holder = CdpHolder::new(CdpHolderConfig {
+                target_ratio: *collateral_ratio,
+                action_threshold_ratio: collateral_ratio * 0.75,
+                reserve_zec: *reserve_zec,
+                initial_collateral: collateral_ratio * debt_zai / initial_price,
+                initial_debt: *debt_zai,
+            });
+            vault_id = holder
+                .open_vault(&mut scenario.registry, &scenario.amm, 0)
+                .ok();
+            scenario.cdp_holders.push(holder);
+        }
+    });
+
+    let collector = scenario
+        .agent_metrics
+        .as_ref()
+        .expect("agent metrics are enabled");
+    let series = collector.series(&agent_id);
+    let mut values: Vec<f64> = series.iter().map(|s| s.value_zai).collect();
+    let initial = series
+        .first()
+        .map_or(0.0, |s| s.value_zai - s.pnl_zai);
+
+    let liquidations: Vec<_> = scenario
+        .liquidation_engine
+        .history
+        .iter()
+        .filter(|r| Some(r.vault_id) == vault_id)
+        .collect();
+    // Surplus from a liquidation is paid out in ZAI and is no longer in the vault
+    let surplus: f64 = liquidations.iter().map(|r| r.surplus_to_owner).sum();
+    if let Some(last) = values.last_mut() {
+        *last += surplus;
+    }
+
+    let final_value = values.last().copied().unwrap_or(initial);
+    let final_pnl = final_value - initial;
+    PersonaOutcome {
+        scenario: id,
+        seed,
+        initial_value_zai: initial,
+        final_pnl_zai: final_pnl,
+        final_pnl_pct: if initial > 0.0 { final_pnl / initial } else { 0.0 },
+        liquidated: !liquidations.is_empty(),
+        liquidation_block: liquidations.first().map(|r| r.block),
+        worst_drawdown_pct: max_drawdown(&values),
+    }
+}
+
+/// Run `persona` through every stress scenario for each seed.
+pub fn run_persona(
+    persona: &Persona,
+    config: &ScenarioConfig,
+    blocks: usize,
+    seeds: &[u64],
+) -> PersonaReport {
+    let mut outcomes = Vec::new();
+    let mut scenarios = Vec::new();
+
+    for id in ScenarioId::all() {
+        let runs: Vec<PersonaOutcome> = seeds
+            .iter()
+            .map(|&seed| run_persona_scenario(persona, id, config, blocks, seed))
+            .collect();
+        let n = runs.len().max(1) as f64;
+        scenarios.push(PersonaScenarioSummary {
+            scenario: id,
+            mean_pnl_pct: runs.iter().map(|o| o.final_pnl_pct).sum::<f64>() / n,
+            worst_pnl_pct: runs.iter().map(|o| o.final_pnl_pct).fold(f64::INFINITY, f64::min),
+            liquidation_probability: runs.iter().filter(|o| o.liquidated).count() as f64 / n,
+            worst_drawdown_pct: runs.iter().map(|o| o.worst_drawdown_pct).fold(0.0, f64::max),
+        });
+        outcomes.extend(runs);
+    }
+
+    let total = outcomes.len().max(1) as f64;
+    PersonaReport {
+        persona: persona.clone(),
+        blocks,
+        liquidation_probability: outcomes.iter().filter(|o| o.liquidated).count() as f64 / total,
+        worst_pnl_pct: outcomes.iter().map(|o| o.final_pnl_pct).fold(f64::INFINITY, f64::min),
+        worst_drawdown_pct: outcomes
+            .iter()
+            .map(|o| o.worst_drawdown_pct)
+            .fold(0.0, f64::max),
+        outcomes,
+        scenarios,
+    }
+}
@@ -0,0 +1,260 @@
+//! Two-asset pools with a selectable invariant.
+//!
+//! The ZEC/ZAI `Amm` is always constant product. Pools here can instead use
+//! a Curve-style StableSwap invariant, which trades nearly 1:1 near the peg
+//! and steepens away from it — the shape of a ZAI/USDC venue.
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::ZaiSimError;
+
+/// Newton iterations before the StableSwap solvers give up.
+const MAX_ITERATIONS: usize = 255;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Asset {
+    Zec,
+    Zai,
+    /// A USD stablecoin (e.g. wrapped USDC)
+    Usd,
+}
+
+/// Pool invariant.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Curve {
+    /// x · y = k
+    ConstantProduct,
+    /// Curve's two-coin StableSwap: constant sum near balance, constant
+    /// product far from it. Higher `amplification` keeps the price flat
+    /// over a wider range.
+    StableSwap { amplification: f64 },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PoolConfig {
+    pub base: Asset,
+    pub quote: Asset,
+    pub reserve_base: f64,
+    pub reserve_quote: f64,
+    pub swap_fee: f64,
+    pub curve: Curve,
+}
+
+impl PoolConfig {
+    /// A ZAI/USD StableSwap pool with `depth` of each side.
+    pub fn zai_usd(depth: f64, amplification: f64) -> Self {
+        PoolConfig {
+            base: Asset::Zai,
+            quote: Asset::Usd,
+            reserve_base: depth,
+            reserve_quote: depth,
+            swap_fee: 0.0004,
+            curve: Curve::StableSwap { amplification },
+        }
+    }
+}
+
+/// A pool of `base` against `quote`. Prices are in quote per base.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Pool {
+    pub base: Asset,
+    pub quote: Asset,
+    pub reserve_base: f64,
+    pub reserve_quote: f64,
+    pub swap_fee: f64,
+    pub curve: Curve,
+    /// Total swap fees collected, in quote terms
+    pub cumulative_fees_quote: f64,
+}
+
+impl Pool {
+    pub fn new(config: &PoolConfig) -> Self {
+        Pool {
+            base: config.base,
+            quote: config.quote,
+            reserve_base: config.reserve_base,
+            reserve_quote: config.reserve_quote,
+            swap_fee: config.swap_fee,
+            curve: config.curve,
+            cumulative_fees_quote: 0.0,
+        }
+    }
+
+    /// Marginal price of base in quote, before fees.
+    pub fn spot_price(&self) -> f64 {
+        let (x, y) = (self.reserve_base, self.reserve_quote);
+        match self.curve {
+            Curve::ConstantProduct => y / x,
+            Curve::StableSwap { amplification } => {
+                // -dy/dx along the invariant surface
+                let ann = amplification * 4.0;
+                let d = stableswap_d(x, y, ann);
+                let d3 = d.powi(3);
+                (ann + d3 / (4.0 * x * x * y)) / (ann + d3 / (4.0 * x * y * y))
+            }
+        }
+    }
+
+    /// Quote received for selling `base_in`, without trading.
+    pub fn quote_out(&self, base_in: f64) -> f64 {
+        self.amount_out(self.reserve_base, self.reserve_quote, base_in)
+    }
+
+    /// Base received for selling `quote_in`, without trading.
+    pub fn base_out(&self, quote_in: f64) -> f64 {
+        self.amount_out(self.reserve_quote, self.reserve_base, quote_in)
+    }
+
+    pub fn swap_base_for_quote(&mut self, base_in: f64) -> Result<f64, ZaiSimError> {
+        if base_in <= 0.0 {
+            return Err(ZaiSimError::InvalidInput(
+                "Input must be positive".to_string(),
+            ));
+        }
+        let out = self.quote_out(base_in);
+        if out <= 0.0 || out >= self.reserve_quote {
+            return Err(ZaiSimError::InsufficientLiquidity(
+                "swap output is not positive".to_string(),
+            ));
+        }
+        self.cumulative_fees_quote += base_in * self.swap_fee * self.spot_price();
+        self.reserve_base += base_in;
+        self.reserve_quote -= out;
+        Ok(out)
+    }
+
+    pub fn swap_quote_for_base(&mut self, quote_in: f64) -> Result<f64, ZaiSimError> {
+        if quote_in <= 0.0 {
+            return Err(ZaiSimError::InvalidInput(
+                "Input must be positive".to_string(),
+            ));
+        }
+        let out = self.base_out(quote_in);
+        if out <= 0.0 || out >= self.reserve_base {
+            return Err(ZaiSimError::InsufficientLiquidity(
+                "swap output is not positive".to_string(),
+            ));
+        }
+        self.cumulative_fees_quote += quote_in * self.swap_fee;
+        self.reserve_quote += quote_in;
+        self.reserve_base -= out;
+        Ok(out)
+    }
+
+    /// Whether the pool trades `sell` for `buy`.
+    pub fn trades(&self, sell: Asset, buy: Asset) -> bool {
+        (sell, buy) == (self.base, self.quote) || (sell, buy) == (self.quote, self.base)
+    }
+
+    /// The other asset of a trade selling `sell`.
+    pub fn counter_asset(&self, sell: Asset) -> Asset {
+        if sell == self.base {
+            self.quote
+        } else {
+            self.base
+        }
+    }
+
+    /// Output for selling `amount` of `sell`, without trading.
+    pub fn quote(&self, sell: Asset, amount: f64) -> f64 {
+        if sell == self.base {
+            self.quote_out(amount)
+        } else {
+            self.base_out(amount)
+        }
+    }
+
+    pub fn swap(&mut self, sell: Asset, amount: f64) -> Result<f64, ZaiSimError> {
+        if sell == self.base {
+            self.swap_base_for_quote(amount)
+        } else {
+            self.swap_quote_for_base(amount)
+        }
+    }
+
+    /// Trade the price to `target` (quote per base), as an outside
+    /// arbitrageur would.
+    pub fn trade_to_price(&mut self, target: f64) {
+        let spot = self.spot_price();
+        if target.is_nan() || target <= 0.0 || (spot - target).abs() <= 1e-9 * target {
+            return;
+        }
+        let sell_base = spot > target;
+        let (sell, reserve) = if sell_base {
+            (self.base, self.reserve_base)
+        } else {
+            (self.quote, self.reserve_quote)
+        };
+        let price_after = |amount: f64| {
+            let mut trial = self.clone();
+            trial.swap(sell, amount).map(|_| trial.spot_price())
+        };
+        // Bisect on the trade size; a failed trial trade overshoots
+        let (mut lo, mut hi) = (0.0, reserve * 1000.0);
+        for _ in 0..100 {
+            let mid = (lo + hi) / 2.0;
+            let overshoots = match price_after(mid) {
+                Ok(price) if sell_base => price < target,
+                Ok(price) => price > target,
+                Err(_) => true,
+            };
+            if overshoots {
+                hi = mid;
+            } else {
+                lo = mid;
+            }
+        }
+        if lo > 0.0 {
+            let _ = self.swap(sell, lo);
+        }
+    }
+
+    /// Output of a trade of `amount_in`, the fee staying in the pool.
+    fn amount_out(&self, reserve_in: f64, reserve_out: f64, amount_in: f64) -> f64 {
+        let new_reserve_in = reserve_in + amount_in * (1.0 - self.swap_fee);
+        let new_reserve_out = match self.curve {
+            Curve::ConstantProduct => reserve_in * reserve_out / new_reserve_in,
+            Curve::StableSwap { amplification } => {
+                let ann = amplification * 4.0;
+                let d = stableswap_d(reserve_in, reserve_out, ann);
+                stableswap_y(new_reserve_in, d, ann)
+            }
+        };
+        reserve_out - new_reserve_out
+    }
+}
+
+/// StableSwap invariant D for balances `x`, `y` with `ann` = A · n^n.
+fn stableswap_d(x: f64, y: f64, ann: f64) -> f64 {
+    let s = x + y;
+    if s == 0.0 {
+        return 0.0;
+    }
+    let mut d = s;
+    for _ in 0..MAX_ITERATIONS {
+        let d_p = d.powi(3) / (4.0 * x * y);
+        let next = (ann * s + 2.0 * d_p) * d / ((ann - 1.0) * d + 3.0 * d_p);
+        if (next - d).abs() <= 1e-12 * d {
+            return next;
+        }
+        d = next;
+    }
+    d
+}
+
+/// Balance of the other coin that keeps invariant `d` when one holds `x`.
+fn stableswap_y(x: f64, d: f64, ann: f64) -> f64 {
+    let c = d.powi(3) / (4.0 * x * ann);
+    let b = x + d / ann;
+    let mut y = d;
+    for _ in 0..MAX_ITERATIONS {
+        let next = (y * y + c) / (2.0 * y + b - d);
+        if (next - y).abs() <= 1e-12 * y {
+            return next;
+        }
+        y = next;
+    }
+    y
+}
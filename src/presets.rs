@@ -0,0 +1,184 @@
+//! Historical event presets: curated ZEC windows to replay real crashes.
+//!
+//! Each preset names an hourly price window. The ones bundled in `data/`
+//! load from there; any other is fetched from Binance (`net` feature) into
+//! the cache directory on first use and read back afterwards. A replay runs
+//! the window at its real price level with the standard historical roster
+//! (one arber, miner, demand agent and CDP holder), as in the historical
+//! replay suite.
+
+use std::path::{Path, PathBuf};
+
+use crate::agents::*;
+use crate::error::ZaiSimError;
+use crate::historical::{config_for_historical, interpolate_to_blocks, load_close_prices};
+use crate::scenario::{Scenario, ScenarioConfig};
+
+/// 75-second blocks per hourly candle.
+pub const BLOCKS_PER_HOUR: usize = 48;
+
+/// A curated historical window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HistoricalPreset {
+    /// Name on the command line (`stress --historical may2021`)
+    pub id: &'static str,
+    pub description: &'static str,
+    /// First day of the window (YYYY-MM-DD, UTC)
+    pub start: &'static str,
+    /// Last day of the window, inclusive
+    pub end: &'static str,
+    /// Hourly CSV in the cache directory
+    pub file: &'static str,
+}
+
+pub const PRESETS: &[HistoricalPreset] = &[
+    HistoricalPreset {
+        id: "covid2020",
+        description: "COVID sell-off run-up, ZEC from $63 to $49",
+        start: "2020-02-20",
+        end: "2020-03-01",
+        file: "covid_initial_2020_hourly.csv",
+    },
+    HistoricalPreset {
+        id: "black-thursday",
+        description: "Black Thursday, March 2020: ZEC from $42 to $25",
+        start: "2020-03-11",
+        end: "2020-03-17",
+        file: "black_thursday_2020_hourly.csv",
+    },
+    HistoricalPreset {
+        id: "may2021",
+        description: "May 2021 crash, ZEC from $305 to $149",
+        start: "2021-05-10",
+        end: "2021-05-25",
+        file: "may_2021_crash_hourly.csv",
+    },
+    HistoricalPreset {
+        id: "luna2022",
+        description: "LUNA/UST collapse, May 2022: ZEC from $135 to $106",
+        start: "2022-05-05",
+        end: "2022-05-15",
+        file: "luna_ust_2022_hourly.csv",
+    },
+    HistoricalPreset {
+        id: "bear2022",
+        description: "2022 bear market, April to June: a three-month grind down",
+        start: "2022-04-01",
+        end: "2022-06-30",
+        file: "bear_2022_hourly.csv",
+    },
+    HistoricalPreset {
+        id: "ftx2022",
+        description: "FTX collapse, November 2022: ZEC from $54 to $40",
+        start: "2022-11-06",
+        end: "2022-11-15",
+        file: "ftx_collapse_2022_hourly.csv",
+    },
+    HistoricalPreset {
+        id: "rally2024",
+        description: "November 2024 rally, ZEC from $37 to $59",
+        start: "2024-11-01",
+        end: "2024-12-01",
+        file: "rally_2024_hourly.csv",
+    },
+];
+
+/// Look up a preset by id.
+pub fn preset(id: &str) -> Result<&'static HistoricalPreset, ZaiSimError> {
+    PRESETS.iter().find(|p| p.id == id).ok_or_else(|| {
+        let ids: Vec<&str> = PRESETS.iter().map(|p| p.id).collect();
+        ZaiSimError::InvalidInput(format!(
+            "unknown historical preset '{}' (available: {})",
+            id,
+            ids.join(", ")
+        ))
+    })
+}
+
+impl HistoricalPreset {
+    /// Where the preset's hourly CSV lives under `cache_dir`.
+    pub fn cache_path(&self, cache_dir: &Path) -> PathBuf {
+        cache_dir.join(self.file)
+    }
+
+    /// Hourly closes for the window, from the cache or, failing that,
+    /// fetched from Binance into it.
+    pub fn hourly_prices(&self, cache_dir: &Path) -> Result<Vec<f64>, ZaiSimError> {
+        let path = self.cache_path(cache_dir);
+        if !path.exists() {
+            self.download(&path)?;
+        }
+        load_close_prices(&path)
+    }
+
+    /// Per-block prices for the window.
+    pub fn block_prices(&self, cache_dir: &Path) -> Result<Vec<f64>, ZaiSimError> {
+        let hourly = self.hourly_prices(cache_dir)?;
+        if hourly.len() < 2 {
+            return Err(ZaiSimError::Parse(format!(
+                "{} needs at least 2 hourly prices",
+                self.cache_path(cache_dir).display()
+            )));
+        }
+        Ok(interpolate_to_blocks(&hourly, BLOCKS_PER_HOUR))
+    }
+
+    #[cfg(feature = "net")]
+    fn download(&self, path: &Path) -> Result<(), ZaiSimError> {
+        use crate::data_fetcher::{fetch_to_csv, Binance, RetryPolicy};
+        use chrono::NaiveDate;
+
+        let day_ms = |date: &str, h: u32, m: u32, s: u32| -> Result<u64, ZaiSimError> {
+            let date = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .map_err(|e| ZaiSimError::Parse(format!("{}: {}", date, e)))?;
+            Ok(date
+                .and_hms_opt(h, m, s)
+                .unwrap()
+                .and_utc()
+                .timestamp_millis() as u64)
+        };
+        let start_ms = day_ms(self.start, 0, 0, 0)?;
+        let end_ms = day_ms(self.end, 23, 59, 59)?;
+        fetch_to_csv(
+            &Binance,
+            "ZECUSDT",
+            "1h",
+            start_ms,
+            end_ms,
+            &RetryPolicy::default(),
+            path,
+        )?;
+        Ok(())
+    }
+
+    #[cfg(not(feature = "net"))]
+    fn download(&self, path: &Path) -> Result<(), ZaiSimError> {
+        Err(ZaiSimError::Config(format!(
+            "{} is not cached at {} and fetching it needs the `net` feature",
+            self.id,
+            path.display()
+        )))
+    }
+}
+
+/// The historical replay config for a window starting at `block_prices[0]`.
+pub fn preset_config(block_prices: &[f64]) -> ScenarioConfig {
+    config_for_historical(block_prices.first().copied().unwrap_or(50.0))
+}
+
+/// The standard historical roster: one arbitrageur, miner, demand agent
+/// and CDP holder with default configs.
+pub fn add_standard_agents(scenario: &mut Scenario) {
+    scenario
+        .arbers
+        .push(Arbitrageur::new(ArbitrageurConfig::default()));
+    scenario
+        .miners
+        .push(MinerAgent::new(MinerAgentConfig::default()));
+    scenario
+        .demand_agents
+        .push(DemandAgent::new(DemandAgentConfig::default()));
+    scenario
+        .cdp_holders
+        .push(CdpHolder::new(CdpHolderConfig::default()));
+}
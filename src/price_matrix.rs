@@ -0,0 +1,234 @@
+//! Aligned close prices of several trading pairs.
+//!
+//! `fetch --pair ZECUSDT,ZECBTC,BTCUSDT` writes one CSV column per pair on
+//! the timestamps every pair has a candle for. The matrix's log-return
+//! correlations drive `simulate`, which draws new price paths that move
+//! together the way the fetched pairs did, for multi-collateral and
+//! cross-rate scenarios.
+
+use std::path::Path;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rand_distr::{Distribution, StandardNormal};
+
+use crate::error::ZaiSimError;
+
+/// Close prices of `pairs` on shared timestamps.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PriceMatrix {
+    pub pairs: Vec<String>,
+    pub timestamps_ms: Vec<u64>,
+    /// One price series per pair, each as long as `timestamps_ms`
+    pub prices: Vec<Vec<f64>>,
+}
+
+impl PriceMatrix {
+    /// Join `(timestamp_ms, close)` series on the timestamps all of them
+    /// have; the others are dropped. Fails on no series or a repeated pair.
+    pub fn align(series: Vec<(String, Vec<(u64, f64)>)>) -> Result<Self, ZaiSimError> {
+        if series.is_empty() {
+            return Err(ZaiSimError::Config("No pairs to align".into()));
+        }
+        let mut pairs: Vec<String> = Vec::new();
+        for (pair, _) in &series {
+            if pairs.contains(pair) {
+                return Err(ZaiSimError::Config(format!("Pair {} given twice", pair)));
+            }
+            pairs.push(pair.clone());
+        }
+
+        let mut timestamps_ms: Vec<u64> = series[0].1.iter().map(|(t, _)| *t).collect();
+        for (_, rows) in &series[1..] {
+            timestamps_ms.retain(|t| rows.binary_search_by_key(t, |(ts, _)| *ts).is_ok());
+        }
+        let prices = series
+            .iter()
+            .map(|(_, rows)| {
+                timestamps_ms
+                    .iter()
+                    .map(|t| rows[rows.binary_search_by_key(t, |(ts, _)| *ts).unwrap()].1)
+                    .collect()
+            })
+            .collect();
+        Ok(Self {
+            pairs,
+            timestamps_ms,
+            prices,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.timestamps_ms.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.timestamps_ms.is_empty()
+    }
+
+    /// Price series of `pair`.
+    pub fn column(&self, pair: &str) -> Option<&[f64]> {
+        let i = self.pairs.iter().position(|p| p == pair)?;
+        Some(&self.prices[i])
+    }
+
+    /// Write as CSV: `timestamp_ms` then one column per pair.
+    pub fn save(&self, path: &Path) -> Result<(), ZaiSimError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut wtr = csv::Writer::from_path(path)?;
+        wtr.write_record(
+            std::iter::once("timestamp_ms").chain(self.pairs.iter().map(String::as_str)),
+        )?;
+        for (row, t) in self.timestamps_ms.iter().enumerate() {
+            wtr.write_record(
+                std::iter::once(t.to_string())
+                    .chain(self.prices.iter().map(|col| col[row].to_string())),
+            )?;
+        }
+        wtr.flush()?;
+        Ok(())
+    }
+
+    /// Read a CSV written by `save`.
+    pub fn load(path: &Path) -> Result<Self, ZaiSimError> {
+        let mut rdr = csv::Reader::from_path(path)?;
+        let headers = rdr.headers()?.clone();
+        if headers.get(0) != Some("timestamp_ms") || headers.len() < 2 {
+            return Err(ZaiSimError::Parse(format!(
+                "{} is not a price matrix (expected timestamp_ms and pair columns)",
+                path.display()
+            )));
+        }
+        let pairs: Vec<String> = headers.iter().skip(1).map(str::to_string).collect();
+        let mut timestamps_ms = Vec::new();
+        let mut prices = vec![Vec::new(); pairs.len()];
+        for result in rdr.records() {
+            let record = result?;
+            timestamps_ms.push(record[0].parse()?);
+            for (col, series) in prices.iter_mut().enumerate() {
+                series.push(record[col + 1].parse()?);
+            }
+        }
+        Ok(Self {
+            pairs,
+            timestamps_ms,
+            prices,
+        })
+    }
+
+    /// Per-step log returns of each pair.
+    pub fn log_returns(&self) -> Vec<Vec<f64>> {
+        self.prices
+            .iter()
+            .map(|series| series.windows(2).map(|w| (w[1] / w[0]).ln()).collect())
+            .collect()
+    }
+
+    /// Covariance matrix of the pairs' log returns.
+    pub fn covariance(&self) -> Vec<Vec<f64>> {
+        let returns = self.log_returns();
+        let means: Vec<f64> = returns.iter().map(|r| mean(r)).collect();
+        let n = returns.first().map_or(0, Vec::len);
+        let denom = n.saturating_sub(1).max(1) as f64;
+        (0..returns.len())
+            .map(|i| {
+                (0..returns.len())
+                    .map(|j| {
+                        (0..n)
+                            .map(|k| (returns[i][k] - means[i]) * (returns[j][k] - means[j]))
+                            .sum::<f64>()
+                            / denom
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Correlation matrix of the pairs' log returns (0 where a pair's
+    /// price never moves).
+    pub fn correlation(&self) -> Vec<Vec<f64>> {
+        let cov = self.covariance();
+        let sd: Vec<f64> = (0..cov.len()).map(|i| cov[i][i].sqrt()).collect();
+        (0..cov.len())
+            .map(|i| {
+                (0..cov.len())
+                    .map(|j| {
+                        if sd[i] > 0.0 && sd[j] > 0.0 {
+                            cov[i][j] / (sd[i] * sd[j])
+                        } else {
+                            0.0
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// `steps` new prices per pair from the first row, as driftless
+    /// log-normal walks with this matrix's return covariance, so the pairs
+    /// keep their historical volatilities and co-movement. Timestamps
+    /// advance by the median row spacing.
+    pub fn simulate(&self, steps: usize, seed: u64) -> PriceMatrix {
+        let chol = cholesky(&self.covariance());
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut current: Vec<f64> = self
+            .prices
+            .iter()
+            .map(|s| s.first().copied().unwrap_or(0.0))
+            .collect();
+        let variances: Vec<f64> = (0..chol.len())
+            .map(|i| chol[i].iter().map(|l| l * l).sum())
+            .collect();
+        let mut prices = vec![Vec::with_capacity(steps); current.len()];
+        for step in 0..steps {
+            let z: Vec<f64> = (0..chol.len())
+                .map(|_| StandardNormal.sample(&mut rng))
+                .collect();
+            for (i, series) in prices.iter_mut().enumerate() {
+                if step > 0 {
+                    let shock: f64 = (0..=i).map(|k| chol[i][k] * z[k]).sum();
+                    current[i] *= (shock - variances[i] / 2.0).exp();
+                }
+                series.push(current[i]);
+            }
+        }
+
+        let start = self.timestamps_ms.first().copied().unwrap_or(0);
+        let mut gaps: Vec<u64> = self.timestamps_ms.windows(2).map(|w| w[1] - w[0]).collect();
+        gaps.sort_unstable();
+        let step = gaps.get(gaps.len() / 2).copied().unwrap_or(60_000);
+        PriceMatrix {
+            pairs: self.pairs.clone(),
+            timestamps_ms: (0..steps as u64).map(|i| start + i * step).collect(),
+            prices,
+        }
+    }
+}
+
+fn mean(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        0.0
+    } else {
+        values.iter().sum::<f64>() / values.len() as f64
+    }
+}
+
+/// Lower-triangular `L` with `L * L^T = m`. Pivots that round to zero or
+/// below (perfectly collinear pairs) are clamped to zero.
+fn cholesky(m: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    let n = m.len();
+    let mut l = vec![vec![0.0; n]; n];
+    for i in 0..n {
+        for j in 0..=i {
+            let sum: f64 = (0..j).map(|k| l[i][k] * l[j][k]).sum();
+            if i == j {
+                l[i][j] = (m[i][i] - sum).max(0.0).sqrt();
+            } else if l[j][j] > 0.0 {
+                l[i][j] = (m[i][j] - sum) / l[j][j];
+            }
+        }
+    }
+    l
+}
@@ -0,0 +1,257 @@
+//! Live progress for long sweeps and Monte Carlo runs.
+//!
+//! A `ProgressMonitor` attached to a `SweepEngine` is told about every
+//! finished run. In `Bars` mode it draws on stderr: an overall bar with
+//! running pass / soft fail / hard fail tallies and a sparkline of the
+//! latest run's peg deviation, and one bar per scenario. `Log` mode (for CI
+//! logs, or whenever stderr is not a terminal) prints the same tallies as a
+//! plain line every few percent instead.
+
+use std::io::IsTerminal;
+use std::sync::Mutex;
+
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+
+use crate::report::{evaluate_pass_fail, Verdict};
+use crate::scenario::Scenario;
+use crate::scenarios::ScenarioId;
+
+/// Sparkline levels, lowest first.
+const SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Width of the peg deviation sparkline, in characters.
+const SPARKLINE_WIDTH: usize = 40;
+
+/// Log lines per stage in `Log` mode.
+const LOG_LINES: u64 = 20;
+
+/// How progress is shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgressMode {
+    /// Progress bars redrawn in place on stderr
+    Bars,
+    /// A plain line on stderr every few percent
+    Log,
+}
+
+impl ProgressMode {
+    /// `Bars` on an interactive terminal unless `no_tui`, else `Log`.
+    pub fn detect(no_tui: bool) -> Self {
+        if no_tui || !std::io::stderr().is_terminal() {
+            ProgressMode::Log
+        } else {
+            ProgressMode::Bars
+        }
+    }
+}
+
+/// Runs finished so far and their verdicts.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProgressTally {
+    pub done: u64,
+    pub total: u64,
+    pub pass: u64,
+    pub soft_fail: u64,
+    pub hard_fail: u64,
+}
+
+impl ProgressTally {
+    fn record(&mut self, verdict: Verdict) {
+        self.done += 1;
+        match verdict {
+            Verdict::Pass => self.pass += 1,
+            Verdict::SoftFail => self.soft_fail += 1,
+            Verdict::HardFail => self.hard_fail += 1,
+        }
+    }
+}
+
+impl std::fmt::Display for ProgressTally {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "pass {}  soft {}  hard {}",
+            self.pass, self.soft_fail, self.hard_fail
+        )
+    }
+}
+
+/// `values` as a `width`-character sparkline scaled from zero to their
+/// maximum; each character shows the largest value in its share of blocks.
+pub fn sparkline(values: &[f64], width: usize) -> String {
+    if values.is_empty() || width == 0 {
+        return String::new();
+    }
+    let width = width.min(values.len());
+    let buckets: Vec<f64> = (0..width)
+        .map(|i| {
+            let (from, to) = (i * values.len() / width, (i + 1) * values.len() / width);
+            values[from..to].iter().cloned().fold(0.0, f64::max)
+        })
+        .collect();
+    let max = buckets.iter().cloned().fold(0.0, f64::max);
+    buckets
+        .iter()
+        .map(|v| {
+            let level = if max > 0.0 {
+                (v / max * (SPARKS.len() - 1) as f64).round() as usize
+            } else {
+                0
+            };
+            SPARKS[level.min(SPARKS.len() - 1)]
+        })
+        .collect()
+}
+
+struct Bars {
+    multi: MultiProgress,
+    overall: ProgressBar,
+    scenarios: Vec<(ScenarioId, ProgressBar)>,
+}
+
+struct State {
+    stage: String,
+    tally: ProgressTally,
+    /// Runs done at the last log line
+    logged: u64,
+    bars: Option<Bars>,
+}
+
+/// Tracks finished runs across the stages of a sweep and shows them as
+/// `mode` says.
+pub struct ProgressMonitor {
+    mode: ProgressMode,
+    target_price: f64,
+    state: Mutex<State>,
+}
+
+impl ProgressMonitor {
+    /// Verdicts and peg deviations are measured against `target_price`.
+    pub fn new(mode: ProgressMode, target_price: f64) -> Self {
+        Self {
+            mode,
+            target_price,
+            state: Mutex::new(State {
+                stage: String::new(),
+                tally: ProgressTally::default(),
+                logged: 0,
+                bars: None,
+            }),
+        }
+    }
+
+    pub fn mode(&self) -> ProgressMode {
+        self.mode
+    }
+
+    /// Expect `runs_per_scenario` more runs of each of `scenarios`, under
+    /// the label `stage`.
+    pub fn start_stage(&self, stage: &str, scenarios: &[ScenarioId], runs_per_scenario: u64) {
+        let mut state = self.state.lock().unwrap();
+        let added = runs_per_scenario * scenarios.len() as u64;
+        state.stage = stage.to_string();
+        state.tally.total += added;
+
+        match self.mode {
+            ProgressMode::Log => eprintln!("{}: {} runs", stage, added),
+            ProgressMode::Bars => {
+                let bars = state.bars.get_or_insert_with(|| {
+                    let multi = MultiProgress::new();
+                    let overall = multi.add(ProgressBar::new(0));
+                    overall.set_style(
+                        ProgressStyle::with_template(
+                            "{prefix:>20} [{bar:30.cyan/blue}] {pos}/{len} {elapsed_precise} eta {eta}\n{msg}",
+                        )
+                        .unwrap()
+                        .progress_chars("=> "),
+                    );
+                    Bars {
+                        multi,
+                        overall,
+                        scenarios: Vec::new(),
+                    }
+                });
+                bars.overall.set_prefix(stage.to_string());
+                bars.overall.inc_length(added);
+                for &id in scenarios {
+                    let bar = match bars.scenarios.iter().find(|(s, _)| *s == id) {
+                        Some((_, bar)) => bar.clone(),
+                        None => {
+                            let bar = bars.multi.add(ProgressBar::new(0));
+                            bar.set_style(
+                                ProgressStyle::with_template("{prefix:>20} [{bar:30}] {pos}/{len}")
+                                    .unwrap()
+                                    .progress_chars("=> "),
+                            );
+                            bar.set_prefix(id.name());
+                            bars.scenarios.push((id, bar.clone()));
+                            bar
+                        }
+                    };
+                    bar.inc_length(runs_per_scenario);
+                }
+            }
+        }
+    }
+
+    /// Count a finished run of `id`.
+    pub fn record(&self, id: ScenarioId, scenario: &Scenario) {
+        let target = self.target_price;
+        let verdict = evaluate_pass_fail(&scenario.metrics, target).overall;
+        let deviations: Vec<f64> = scenario
+            .metrics
+            .amm_spot_price
+            .iter()
+            .map(|p| ((p - target) / target).abs())
+            .collect();
+
+        let mut state = self.state.lock().unwrap();
+        state.tally.record(verdict);
+        let max_dev = deviations.iter().cloned().fold(0.0, f64::max);
+        let status = format!(
+            "{}  peg {} max {:.2}%",
+            state.tally,
+            sparkline(&deviations, SPARKLINE_WIDTH),
+            max_dev * 100.0
+        );
+
+        match self.mode {
+            ProgressMode::Log => {
+                let step = (state.tally.total / LOG_LINES).max(1);
+                if state.tally.done - state.logged >= step || state.tally.done == state.tally.total
+                {
+                    state.logged = state.tally.done;
+                    eprintln!(
+                        "{}: {}/{}  {}",
+                        state.stage, state.tally.done, state.tally.total, status
+                    );
+                }
+            }
+            ProgressMode::Bars => {
+                if let Some(bars) = &state.bars {
+                    bars.overall.inc(1);
+                    bars.overall.set_message(status);
+                    if let Some((_, bar)) = bars.scenarios.iter().find(|(s, _)| *s == id) {
+                        bar.inc(1);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Runs finished so far.
+    pub fn tally(&self) -> ProgressTally {
+        self.state.lock().unwrap().tally.clone()
+    }
+
+    /// Leave the bars drawn at their final state.
+    pub fn finish(&self) {
+        let state = self.state.lock().unwrap();
+        if let Some(bars) = &state.bars {
+            for (_, bar) in &bars.scenarios {
+                bar.finish();
+            }
+            bars.overall.finish();
+        }
+    }
+}
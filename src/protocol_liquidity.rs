@@ -0,0 +1,233 @@
+//! Protocol-owned liquidity (POL).
+//!
+//! The protocol seeds the AMM with its own LP position and grows it from a
+//! share of the treasury's stability-fee and liquidation-penalty income.
+//! Income is buffered as ZAI and deployed — half swapped for ZEC, the pair
+//! added as liquidity — once the buffer is large enough and the AMM trades
+//! close to the external price. With `never_withdraw` the position is never
+//! removed, so it keeps a floor under pool depth when private LPs leave.
+
+use serde::{Deserialize, Serialize};
+
+use crate::amm::Amm;
+use crate::error::ZaiSimError;
+use crate::treasury::Treasury;
+
+/// `Amm::lp_shares` key of the protocol's position.
+pub const POL_OWNER: &str = "protocol";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProtocolLiquidityConfig {
+    /// ZEC seeded at genesis, matched with ZAI at the pool price
+    pub initial_zec: f64,
+    /// Fraction of treasury stability-fee income diverted to POL
+    pub fee_share: f64,
+    /// Fraction of treasury liquidation-penalty income diverted to POL
+    pub penalty_share: f64,
+    /// Buffered ZAI needed before a deployment
+    pub min_deploy_zai: f64,
+    /// Deploy only while the AMM is within this fraction of the external price
+    pub max_deploy_deviation: f64,
+    /// Stop growing once POL holds this fraction of LP shares
+    pub max_pool_share: f64,
+    /// Never remove the position, even above `max_pool_share`
+    pub never_withdraw: bool,
+}
+
+impl Default for ProtocolLiquidityConfig {
+    fn default() -> Self {
+        ProtocolLiquidityConfig {
+            initial_zec: 1000.0,
+            fee_share: 0.5,
+            penalty_share: 0.0,
+            min_deploy_zai: 1000.0,
+            max_deploy_deviation: 0.02,
+            max_pool_share: 0.5,
+            never_withdraw: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProtocolLiquidity {
+    pub config: ProtocolLiquidityConfig,
+    /// Diverted income not yet deployed (ZAI)
+    pub buffer_zai: f64,
+    /// ZEC bought for or returned from the position, awaiting deployment
+    pub buffer_zec: f64,
+    /// Total ZAI taken from the treasury
+    pub total_funded_zai: f64,
+    /// ZAI deployed into the pool, including the ZAI side of the seed
+    pub total_deployed_zai: f64,
+    /// Swap fees earned on the position (ZAI)
+    pub fees_earned_zai: f64,
+    seen_fees: f64,
+    seen_penalties: f64,
+    seen_amm_fees: f64,
+}
+
+impl ProtocolLiquidity {
+    pub fn new(config: ProtocolLiquidityConfig) -> Self {
+        ProtocolLiquidity {
+            config,
+            buffer_zai: 0.0,
+            buffer_zec: 0.0,
+            total_funded_zai: 0.0,
+            total_deployed_zai: 0.0,
+            fees_earned_zai: 0.0,
+            seen_fees: 0.0,
+            seen_penalties: 0.0,
+            seen_amm_fees: 0.0,
+        }
+    }
+
+    /// Add the genesis position to `amm`.
+    pub fn seed(&mut self, amm: &mut Amm) -> Result<f64, ZaiSimError> {
+        self.seen_amm_fees = amm.cumulative_fees_zai;
+        if self.config.initial_zec <= 0.0 {
+            return Ok(0.0);
+        }
+        let zai = self.config.initial_zec * amm.spot_price();
+        let shares = amm.add_liquidity(self.config.initial_zec, zai, POL_OWNER)?;
+        self.total_deployed_zai += zai;
+        Ok(shares)
+    }
+
+    pub fn shares(&self, amm: &Amm) -> f64 {
+        amm.lp_shares.get(POL_OWNER).copied().unwrap_or(0.0)
+    }
+
+    /// Fraction of the pool's LP shares held by the protocol.
+    pub fn pool_share(&self, amm: &Amm) -> f64 {
+        if amm.total_lp_shares > 0.0 {
+            self.shares(amm) / amm.total_lp_shares
+        } else {
+            0.0
+        }
+    }
+
+    /// Position plus buffers, valued at the AMM price (ZAI).
+    pub fn value_zai(&self, amm: &Amm) -> f64 {
+        let spot = amm.spot_price();
+        let pool = self.pool_share(amm) * (amm.reserve_zai + amm.reserve_zec * spot);
+        pool + self.buffer_zai + self.buffer_zec * spot
+    }
+
+    /// Credit the position's share of swap fees since the last call.
+    pub fn accrue_fees(&mut self, amm: &Amm) {
+        let new_fees = amm.cumulative_fees_zai - self.seen_amm_fees;
+        self.seen_amm_fees = amm.cumulative_fees_zai;
+        if new_fees > 0.0 {
+            self.fees_earned_zai += new_fees * self.pool_share(amm);
+        }
+    }
+
+    /// Take this POL's share of the treasury's new fee and penalty income.
+    pub fn fund(&mut self, treasury: &mut Treasury) {
+        let fees = treasury.total_fees_collected - self.seen_fees;
+        let penalties = treasury.total_penalties_collected - self.seen_penalties;
+        self.seen_fees = treasury.total_fees_collected;
+        self.seen_penalties = treasury.total_penalties_collected;
+
+        let wanted =
+            fees.max(0.0) * self.config.fee_share + penalties.max(0.0) * self.config.penalty_share;
+        // Bad debt may already have drawn the surplus down
+        let amount = wanted.min(treasury.balance_zai);
+        if amount > 0.0 {
+            treasury.balance_zai -= amount;
+            self.buffer_zai += amount;
+            self.total_funded_zai += amount;
+        }
+    }
+
+    /// Deploy the buffer while under `max_pool_share`, or withdraw the
+    /// excess above it unless `never_withdraw`. Returns the change in LP
+    /// shares.
+    pub fn rebalance(&mut self, amm: &mut Amm, external_price: f64, block: u64) -> f64 {
+        let share = self.pool_share(amm);
+        if share > self.config.max_pool_share {
+            return if self.config.never_withdraw {
+                0.0
+            } else {
+                self.withdraw_excess(amm)
+            };
+        }
+
+        let spot = amm.spot_price();
+        let deviation = (spot - external_price).abs() / external_price;
+        let buffered = self.buffer_zai + self.buffer_zec * spot;
+        if buffered < self.config.min_deploy_zai || deviation > self.config.max_deploy_deviation {
+            return 0.0;
+        }
+
+        // Even out the buffers at the pool price, then add them as a pair
+        let zai_excess = (self.buffer_zai - self.buffer_zec * spot) / 2.0;
+        if zai_excess > 0.0 {
+            if let Ok(zec_out) = amm.swap_zai_for_zec(zai_excess, block) {
+                self.buffer_zai -= zai_excess;
+                self.buffer_zec += zec_out;
+            }
+        } else if zai_excess < 0.0 {
+            let zec_in = -zai_excess / spot;
+            if let Ok(zai_out) = amm.swap_zec_for_zai(zec_in, block) {
+                self.buffer_zec -= zec_in;
+                self.buffer_zai += zai_out;
+            }
+        }
+        let spot = amm.spot_price();
+        // Shares s keep (held + s) / (total + s) at or below the cap
+        let max = self.config.max_pool_share;
+        let room = if max >= 1.0 {
+            f64::INFINITY
+        } else {
+            (max * amm.total_lp_shares - self.shares(amm)) / (1.0 - max)
+        };
+        let room_zec = room / amm.total_lp_shares * amm.reserve_zec;
+        let zec = self.buffer_zec.min(self.buffer_zai / spot).min(room_zec);
+        let zai = zec * spot;
+        if zec <= 0.0 {
+            return 0.0;
+        }
+        match amm.add_liquidity(zec, zai, POL_OWNER) {
+            Ok(shares) => {
+                self.buffer_zec -= zec;
+                self.buffer_zai -= zai;
+                self.total_deployed_zai += zai;
+                shares
+            }
+            Err(_) => 0.0,
+        }
+    }
+
+    /// Remove `shares` of the position into the buffers.
+    pub fn withdraw(&mut self, amm: &mut Amm, shares: f64) -> Result<(f64, f64), ZaiSimError> {
+        if self.config.never_withdraw {
+            return Err(ZaiSimError::Config(
+                "protocol-owned liquidity is never withdrawn".to_string(),
+            ));
+        }
+        let (zec, zai) = amm.remove_liquidity(shares, POL_OWNER)?;
+        self.buffer_zec += zec;
+        self.buffer_zai += zai;
+        Ok((zec, zai))
+    }
+
+    /// Withdraw down to `max_pool_share`. Returns the (negative) change in
+    /// LP shares.
+    fn withdraw_excess(&mut self, amm: &mut Amm) -> f64 {
+        let max = self.config.max_pool_share;
+        if max >= 1.0 {
+            return 0.0;
+        }
+        let held = self.shares(amm);
+        // Removing s shares leaves (held - s) / (total - s) = max
+        let excess = (held - max * amm.total_lp_shares) / (1.0 - max);
+        if excess <= 0.0 {
+            return 0.0;
+        }
+        match self.withdraw(amm, excess.min(held)) {
+            Ok(_) => -excess.min(held),
+            Err(_) => 0.0,
+        }
+    }
+}
@@ -0,0 +1,234 @@
+//! Python bindings (`python` feature), built as the `zai_sim` module with
+//! `maturin develop --release`.
+//!
+//! Configs cross the boundary as dicts of `ScenarioConfig` fields, merged
+//! over the defaults so a dict only needs the fields it changes. Per-block
+//! metrics come back as a dict of columns, ready for `pandas.DataFrame`:
+//!
+//! ```python
+//! import pandas as pd, zai_sim
+//! s = zai_sim.run_stress("black_thursday", {"amm_swap_fee": 0.003}, 1000, 42)
+//! df = pd.DataFrame(s.metrics())
+//! print(s.summary()["max_peg_deviation"])
+//! ```
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::error::ZaiSimError;
+use crate::output::compute_summary;
+use crate::scenario::{Scenario, ScenarioConfig};
+use crate::scenarios::{self, ScenarioId};
+use crate::sweep::SweepEngine;
+
+impl From<ZaiSimError> for PyErr {
+    fn from(e: ZaiSimError) -> Self {
+        PyValueError::new_err(e.to_string())
+    }
+}
+
+/// Through Python's `json` module, so nested configs and metrics keep their
+/// serde shape.
+fn to_py<T: Serialize>(py: Python<'_>, value: &T) -> PyResult<PyObject> {
+    let text = serde_json::to_string(value).map_err(ZaiSimError::from)?;
+    Ok(py
+        .import_bound("json")?
+        .call_method1("loads", (text,))?
+        .unbind())
+}
+
+fn from_py(py: Python<'_>, obj: &Bound<'_, PyAny>) -> PyResult<Value> {
+    let text: String = py
+        .import_bound("json")?
+        .call_method1("dumps", (obj,))?
+        .extract()?;
+    Ok(serde_json::from_str(&text).map_err(ZaiSimError::from)?)
+}
+
+/// The default config with `overrides` (a dict, or `None`) applied.
+fn config_from_py(
+    py: Python<'_>,
+    overrides: Option<&Bound<'_, PyAny>>,
+) -> PyResult<ScenarioConfig> {
+    let overrides = match overrides {
+        Some(overrides) => from_py(py, overrides)?,
+        None => Value::Null,
+    };
+    Ok(ScenarioConfig::with_overrides(overrides)?)
+}
+
+fn scenario_id(name: &str) -> PyResult<ScenarioId> {
+    ScenarioId::from_name(name)
+        .ok_or_else(|| ZaiSimError::Config(format!("unknown scenario '{}'", name)).into())
+}
+
+/// One key per `BlockMetrics` field, each a list over blocks.
+fn columns(scenario: &Scenario) -> PyResult<Map<String, Value>> {
+    let mut columns = Map::new();
+    for m in &scenario.metrics {
+        let Value::Object(row) = serde_json::to_value(m).map_err(ZaiSimError::from)? else {
+            continue;
+        };
+        for (key, value) in row {
+            if let Value::Array(column) = columns
+                .entry(key)
+                .or_insert_with(|| Value::Array(Vec::with_capacity(scenario.metrics.len())))
+            {
+                column.push(value);
+            }
+        }
+    }
+    Ok(columns)
+}
+
+/// The default `ScenarioConfig` as a dict.
+#[pyfunction]
+fn default_config(py: Python<'_>) -> PyResult<PyObject> {
+    to_py(py, &ScenarioConfig::default())
+}
+
+/// Names of the built-in stress scenarios.
+#[pyfunction]
+fn scenario_names() -> Vec<&'static str> {
+    ScenarioId::all().iter().map(|id| id.name()).collect()
+}
+
+/// A simulation run: build it from a config, `run` it over an external
+/// price path, then read `metrics()` and `summary()`.
+#[pyclass(name = "Scenario", unsendable)]
+struct PyScenario {
+    inner: Scenario,
+    target_price: f64,
+}
+
+#[pymethods]
+impl PyScenario {
+    #[new]
+    #[pyo3(signature = (config = None, seed = 42))]
+    fn new(py: Python<'_>, config: Option<&Bound<'_, PyAny>>, seed: u64) -> PyResult<Self> {
+        let config = config_from_py(py, config)?;
+        Ok(Self {
+            inner: Scenario::new_with_seed(&config, seed),
+            target_price: config.initial_redemption_price,
+        })
+    }
+
+    /// Run one block per external ZEC price.
+    fn run(&mut self, prices: Vec<f64>) {
+        self.inner.run(&prices);
+    }
+
+    fn config(&self, py: Python<'_>) -> PyResult<PyObject> {
+        to_py(py, &self.inner.config)
+    }
+
+    /// Per-block metrics as a dict of columns.
+    fn metrics(&self, py: Python<'_>) -> PyResult<PyObject> {
+        to_py(py, &columns(&self.inner)?)
+    }
+
+    /// `SummaryMetrics` of the blocks run so far, against the initial
+    /// redemption price unless `target_price` is given.
+    #[pyo3(signature = (target_price = None))]
+    fn summary(&self, py: Python<'_>, target_price: Option<f64>) -> PyResult<PyObject> {
+        let target = target_price.unwrap_or(self.target_price);
+        to_py(py, &compute_summary(&self.inner.metrics, target))
+    }
+
+    fn __len__(&self) -> usize {
+        self.inner.metrics.len()
+    }
+}
+
+/// Run a built-in stress scenario by name.
+#[pyfunction]
+#[pyo3(signature = (scenario, config = None, blocks = 1000, seed = 42))]
+fn run_stress(
+    py: Python<'_>,
+    scenario: &str,
+    config: Option<&Bound<'_, PyAny>>,
+    blocks: usize,
+    seed: u64,
+) -> PyResult<PyScenario> {
+    let id = scenario_id(scenario)?;
+    let config = config_from_py(py, config)?;
+    let target_price = config.initial_redemption_price;
+    Ok(PyScenario {
+        inner: scenarios::run_stress(id, &config, blocks, seed),
+        target_price,
+    })
+}
+
+/// Score each parameter set (a dict of sweep parameter names to values)
+/// over `iterations` seeds of each scenario, in parallel. Returns one dict
+/// per set with its `params`, per-scenario `scores` and `overall_score`.
+#[pyfunction]
+#[pyo3(signature = (configs, scenarios = None, iterations = 10, blocks = 1000, seed = 42, target_price = 50.0))]
+fn run_monte_carlo(
+    py: Python<'_>,
+    configs: Vec<Bound<'_, PyDict>>,
+    scenarios: Option<Vec<String>>,
+    iterations: usize,
+    blocks: usize,
+    seed: u64,
+    target_price: f64,
+) -> PyResult<PyObject> {
+    let ids = match scenarios {
+        Some(names) => names
+            .iter()
+            .map(String::as_str)
+            .map(scenario_id)
+            .collect::<PyResult<Vec<_>>>()?,
+        None => ScenarioId::all(),
+    };
+    // In dict order, which matters for the penalty shares; unknown names
+    // are rejected here since the sweep ignores them
+    let configs = configs
+        .iter()
+        .map(|d| {
+            d.iter()
+                .map(|(k, v)| Ok((k.extract()?, v.extract()?)))
+                .collect::<PyResult<Vec<(String, f64)>>>()
+        })
+        .collect::<PyResult<Vec<_>>>()?;
+    let mut probe = ScenarioConfig::default();
+    for (name, value) in configs.iter().flatten() {
+        SweepEngine::set_param(&mut probe, name, *value)?;
+    }
+    let engine = SweepEngine::new(blocks, seed, target_price);
+    let results = py.allow_threads(|| engine.run_monte_carlo(&configs, &ids, iterations));
+    let results: Vec<Value> = results
+        .iter()
+        .map(|r| {
+            let params: Map<String, Value> = r
+                .params
+                .iter()
+                .map(|(k, v)| (k.clone(), (*v).into()))
+                .collect();
+            let scores: Map<String, Value> = r
+                .scores
+                .iter()
+                .map(|(id, s)| (id.name().to_string(), (*s).into()))
+                .collect();
+            serde_json::json!({
+                "params": params,
+                "scores": scores,
+                "overall_score": r.overall_score,
+            })
+        })
+        .collect();
+    to_py(py, &results)
+}
+
+#[pymodule]
+fn zai_sim(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyScenario>()?;
+    m.add_function(wrap_pyfunction!(default_config, m)?)?;
+    m.add_function(wrap_pyfunction!(scenario_names, m)?)?;
+    m.add_function(wrap_pyfunction!(run_stress, m)?)?;
+    m.add_function(wrap_pyfunction!(run_monte_carlo, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,172 @@
+//! Golden-run regression checks.
+//!
+//! A baseline records the summary metrics of all 13 stress scenarios at a
+//! fixed block count and seed. Rerunning them and comparing against it
+//! shows whether a change moved any simulation outcome, and by how much.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::error::ZaiSimError;
+use crate::output::{compute_summary, SUMMARY_COLUMNS};
+use crate::scenario::ScenarioConfig;
+use crate::scenarios::{run_stress, ScenarioId};
+
+/// File a baseline is stored in, inside the baseline directory.
+pub const BASELINE_FILE: &str = "baseline.json";
+
+/// Summary metrics of every stress scenario from one set of runs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Baseline {
+    pub blocks: usize,
+    pub seed: u64,
+    /// `SUMMARY_COLUMNS` values keyed by scenario name, then column
+    /// (non-finite values as `None`)
+    pub scenarios: BTreeMap<String, BTreeMap<String, Option<f64>>>,
+}
+
+impl Baseline {
+    /// Run every stress scenario with `config` for `blocks` blocks.
+    pub fn record(config: &ScenarioConfig, blocks: usize, seed: u64) -> Self {
+        let scenarios = ScenarioId::all()
+            .par_iter()
+            .map(|&id| {
+                let scenario = run_stress(id, config, blocks, seed);
+                let summary = compute_summary(&scenario.metrics, config.initial_redemption_price);
+                let json = serde_json::to_value(&summary).unwrap_or_default();
+                let values = SUMMARY_COLUMNS
+                    .iter()
+                    .map(|c| (c.to_string(), json[*c].as_f64().filter(|v| v.is_finite())))
+                    .collect();
+                (id.name().to_string(), values)
+            })
+            .collect();
+        Self {
+            blocks,
+            seed,
+            scenarios,
+        }
+    }
+
+    /// Read `baseline.json` from `dir`.
+    pub fn load(dir: &Path) -> Result<Self, ZaiSimError> {
+        let text = std::fs::read_to_string(dir.join(BASELINE_FILE))?;
+        Ok(serde_json::from_str(&text)?)
+    }
+
+    /// Write `baseline.json` into `dir`, creating it if needed.
+    pub fn save(&self, dir: &Path) -> Result<(), ZaiSimError> {
+        std::fs::create_dir_all(dir)?;
+        std::fs::write(dir.join(BASELINE_FILE), serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// How far a metric may move before it counts as a regression: by at most
+/// `absolute + relative * |baseline|`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tolerance {
+    pub relative: f64,
+    pub absolute: f64,
+}
+
+impl Default for Tolerance {
+    fn default() -> Self {
+        Self {
+            relative: 1e-9,
+            absolute: 1e-12,
+        }
+    }
+}
+
+impl Tolerance {
+    fn allows(&self, baseline: Option<f64>, actual: Option<f64>) -> bool {
+        match (baseline, actual) {
+            (Some(b), Some(a)) => (a - b).abs() <= self.absolute + self.relative * b.abs(),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+}
+
+/// A metric whose value changed from the baseline.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricDelta {
+    pub scenario: String,
+    pub metric: String,
+    pub baseline: Option<f64>,
+    pub actual: Option<f64>,
+    pub within_tolerance: bool,
+}
+
+impl MetricDelta {
+    /// `actual - baseline`, when both are finite.
+    pub fn delta(&self) -> Option<f64> {
+        Some(self.actual? - self.baseline?)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RegressionReport {
+    /// Every changed metric, in scenario then column order
+    pub deltas: Vec<MetricDelta>,
+    /// Scenarios in only one of the baseline and the current runs
+    pub unmatched: Vec<String>,
+}
+
+impl RegressionReport {
+    /// Whether every metric is within tolerance of the baseline.
+    pub fn passed(&self) -> bool {
+        self.unmatched.is_empty() && self.deltas.iter().all(|d| d.within_tolerance)
+    }
+
+    /// Changes beyond tolerance.
+    pub fn regressions(&self) -> impl Iterator<Item = &MetricDelta> {
+        self.deltas.iter().filter(|d| !d.within_tolerance)
+    }
+}
+
+/// Every metric of `current` that differs from `baseline`.
+pub fn compare(baseline: &Baseline, current: &Baseline, tolerance: Tolerance) -> RegressionReport {
+    let mut report = RegressionReport::default();
+    for (name, expected) in &baseline.scenarios {
+        let Some(actual) = current.scenarios.get(name) else {
+            report.unmatched.push(name.clone());
+            continue;
+        };
+        for (metric, &b) in expected {
+            let a = actual.get(metric).copied().flatten();
+            if a.map(f64::to_bits) != b.map(f64::to_bits) {
+                report.deltas.push(MetricDelta {
+                    scenario: name.clone(),
+                    metric: metric.clone(),
+                    baseline: b,
+                    actual: a,
+                    within_tolerance: tolerance.allows(b, a),
+                });
+            }
+        }
+    }
+    report.unmatched.extend(
+        current
+            .scenarios
+            .keys()
+            .filter(|name| !baseline.scenarios.contains_key(*name))
+            .cloned(),
+    );
+    report
+}
+
+/// Rerun the stress scenarios at `baseline`'s block count and seed and
+/// compare them against it.
+pub fn regress(
+    baseline: &Baseline,
+    config: &ScenarioConfig,
+    tolerance: Tolerance,
+) -> RegressionReport {
+    let current = Baseline::record(config, baseline.blocks, baseline.seed);
+    compare(baseline, &current, tolerance)
+}
@@ -0,0 +1,104 @@
+//! Chain reorganization fault injection.
+//!
+//! Zcash sees reorgs of one or two blocks in normal operation. With a reorg
+//! model, after each block there is a chance the latest blocks are
+//! orphaned: the simulation rolls back to the state before them and mines
+//! replacement blocks at the same external prices. The replacements run
+//! arbitrageurs after the other agents instead of before them, agents in
+//! reverse order and fresh randomness, so AMM trades, liquidations, TWAP
+//! observations and breaker state from the orphaned blocks are unwound and
+//! rebuilt differently.
+//!
+//! Block metrics, snapshots, agent samples and settlements from orphaned
+//! blocks are dropped, so a run's output always describes the canonical
+//! chain. Streamed metrics are held back until no reorg can reach them.
+
+use std::collections::VecDeque;
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReorgConfig {
+    /// Chance per block that the latest blocks are reorged
+    pub probability: f64,
+    /// Deepest reorg in blocks; each reorg picks a depth from 1 to this
+    pub max_depth: u64,
+}
+
+impl Default for ReorgConfig {
+    fn default() -> Self {
+        ReorgConfig {
+            probability: 0.01,
+            max_depth: 2,
+        }
+    }
+}
+
+/// One injected reorg.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReorgEvent {
+    /// Chain tip when the reorg happened
+    pub block: u64,
+    /// Blocks orphaned and re-mined
+    pub depth: u64,
+    pub orphaned_liquidations: u32,
+    pub replayed_liquidations: u32,
+    /// AMM spot at the tip before and after the reorg
+    pub orphaned_spot_price: f64,
+    pub replayed_spot_price: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReorgInjector {
+    pub config: ReorgConfig,
+    pub events: Vec<ReorgEvent>,
+    rng: ChaCha12Rng,
+    /// Serialized state before each of the latest blocks, oldest first
+    #[serde(skip)]
+    states: VecDeque<(u64, Vec<u8>)>,
+}
+
+impl ReorgInjector {
+    pub fn new(config: ReorgConfig, seed: u64) -> Self {
+        ReorgInjector {
+            config,
+            events: Vec::new(),
+            rng: ChaCha12Rng::seed_from_u64(seed),
+            states: VecDeque::new(),
+        }
+    }
+
+    /// Keep `state`, taken before `block`, as a rollback point. Only the
+    /// latest `max_depth` are kept.
+    pub fn push_state(&mut self, block: u64, state: Vec<u8>) {
+        self.states.push_back((block, state));
+        while self.states.len() as u64 > self.config.max_depth {
+            self.states.pop_front();
+        }
+    }
+
+    /// Decide whether the chain tip reorgs now. Returns the first orphaned
+    /// block and the state before it; later rollback points are dropped.
+    pub fn draw(&mut self) -> Option<(u64, Vec<u8>)> {
+        if self.states.is_empty() || self.rng.gen::<f64>() >= self.config.probability {
+            return None;
+        }
+        let depth = self.rng.gen_range(1..=self.states.len());
+        let keep = self.states.len() - depth;
+        let point = self.states.remove(keep);
+        self.states.truncate(keep);
+        point
+    }
+
+    /// Drop the rollback points, making every block so far final.
+    pub fn finalize(&mut self) {
+        self.states.clear();
+    }
+
+    /// Seed for the replacement blocks' randomness.
+    pub fn fork_seed(&mut self) -> u64 {
+        self.rng.gen()
+    }
+}
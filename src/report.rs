@@ -1,14 +1,24 @@
-use crate::output::SummaryMetrics;
-use crate::scenario::{BlockMetrics, ScenarioConfig};
+use crate::calibration::CalibrationReport;
+use crate::error::ZaiSimError;
+use crate::liquidation::{LiquidationMode, LiquidationResult};
+use crate::output::{SavedRun, SummaryMetrics};
+use crate::persona::PersonaReport;
+use crate::scenario::{MetricsStore, ScenarioConfig};
+use serde::Serialize;
 use std::path::Path;
+use std::str::FromStr;
 
 const BLOCKS_PER_HOUR: u64 = 48;
 
+/// Where generated reports load Chart.js from unless it is inlined.
+pub const CHART_JS_CDN: &str = "https://cdn.jsdelivr.net/npm/chart.js@4";
+
 // ═══════════════════════════════════════════════════════════════════════
 // Pass / Fail types
 // ═══════════════════════════════════════════════════════════════════════
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum Verdict {
     Pass,
     SoftFail,
@@ -31,9 +41,61 @@ impl Verdict {
             Self::HardFail => "hard-fail",
         }
     }
+
+    /// Severity ordering: Pass (0) < SoftFail (1) < HardFail (2).
+    pub fn rank(&self) -> u8 {
+        match self {
+            Self::Pass => 0,
+            Self::SoftFail => 1,
+            Self::HardFail => 2,
+        }
+    }
+
+    /// The more severe of two verdicts.
+    pub fn worst(self, other: Verdict) -> Verdict {
+        if other.rank() > self.rank() {
+            other
+        } else {
+            self
+        }
+    }
 }
 
-#[derive(Debug, Clone)]
+/// Verdict at which a CLI run should exit non-zero.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FailOn {
+    HardFail,
+    SoftFail,
+    Never,
+}
+
+impl FailOn {
+    pub fn is_failure(&self, verdict: &Verdict) -> bool {
+        match self {
+            Self::HardFail => *verdict == Verdict::HardFail,
+            Self::SoftFail => *verdict != Verdict::Pass,
+            Self::Never => false,
+        }
+    }
+}
+
+impl FromStr for FailOn {
+    type Err = ZaiSimError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "hard" | "hard-fail" => Ok(Self::HardFail),
+            "soft" | "soft-fail" => Ok(Self::SoftFail),
+            "never" => Ok(Self::Never),
+            _ => Err(ZaiSimError::Parse(format!(
+                "Unknown fail-on level: {} (use hard, soft or never)",
+                s
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct CriterionResult {
     pub name: String,
     pub passed: bool,
@@ -41,7 +103,7 @@ pub struct CriterionResult {
     pub details: String,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct PassFailResult {
     pub overall: Verdict,
     pub criteria: Vec<CriterionResult>,
@@ -51,18 +113,14 @@ pub struct PassFailResult {
 // Pass / Fail evaluation
 // ═══════════════════════════════════════════════════════════════════════
 
-pub fn evaluate_pass_fail(metrics: &[BlockMetrics], target_price: f64) -> PassFailResult {
+pub fn evaluate_pass_fail(metrics: &MetricsStore, target_price: f64) -> PassFailResult {
     let mut criteria = Vec::new();
     let mut worst = Verdict::Pass;
 
     // --- Hard fail: insolvency ---
-    let insolvent = metrics.iter().any(|m| {
-        if m.total_debt > 0.0 {
-            let collateral_value = m.total_collateral * m.twap_price;
-            collateral_value < m.total_debt
-        } else {
-            false
-        }
+    let insolvent = (0..metrics.len()).any(|i| {
+        let collateral_value = metrics.total_collateral[i] * metrics.twap_price[i];
+        metrics.total_debt[i] > 0.0 && collateral_value < metrics.total_debt[i]
     });
     criteria.push(CriterionResult {
         name: "Solvency".into(),
@@ -79,11 +137,8 @@ pub fn evaluate_pass_fail(metrics: &[BlockMetrics], target_price: f64) -> PassFa
     }
 
     // --- Hard fail: bad debt > 5% ---
-    let max_debt = metrics
-        .iter()
-        .map(|m| m.total_debt)
-        .fold(1.0_f64, f64::max);
-    let final_bad_debt = metrics.last().map(|m| m.bad_debt).unwrap_or(0.0);
+    let max_debt = metrics.total_debt.iter().cloned().fold(1.0_f64, f64::max);
+    let final_bad_debt = metrics.bad_debt.last().copied().unwrap_or(0.0);
     let bad_debt_pct = final_bad_debt / max_debt * 100.0;
     let bad_debt_fail = bad_debt_pct > 5.0;
     criteria.push(CriterionResult {
@@ -97,15 +152,13 @@ pub fn evaluate_pass_fail(metrics: &[BlockMetrics], target_price: f64) -> PassFa
     }
 
     // --- Hard fail: death spiral ---
-    let death_spiral = if metrics.len() > 200 {
-        let initial = metrics[0].amm_spot_price;
-        let final_price = metrics.last().unwrap().amm_spot_price;
+    let prices = &metrics.amm_spot_price;
+    let death_spiral = if prices.len() > 200 {
+        let initial = prices[0];
+        let final_price = prices[prices.len() - 1];
         // Price dropped >90% and last 100 blocks show no recovery
         let dropped = final_price < initial * 0.1;
-        let last_100: Vec<f64> = metrics[metrics.len().saturating_sub(100)..]
-            .iter()
-            .map(|m| m.amm_spot_price)
-            .collect();
+        let last_100 = &prices[prices.len().saturating_sub(100)..];
         let no_recovery = last_100.iter().all(|&p| p < initial * 0.15);
         dropped && no_recovery
     } else {
@@ -128,8 +181,8 @@ pub fn evaluate_pass_fail(metrics: &[BlockMetrics], target_price: f64) -> PassFa
     // --- Soft fail: peg deviation >20% for >1 hour (48 blocks) ---
     let mut consecutive_deviation = 0u64;
     let mut max_consecutive = 0u64;
-    for m in metrics {
-        let dev = ((m.amm_spot_price - target_price) / target_price).abs();
+    for p in prices {
+        let dev = ((p - target_price) / target_price).abs();
         if dev > 0.20 {
             consecutive_deviation += 1;
             max_consecutive = max_consecutive.max(consecutive_deviation);
@@ -205,17 +258,85 @@ pub fn evaluate_pass_fail(metrics: &[BlockMetrics], target_price: f64) -> PassFa
     }
 }
 
-fn compute_recovery_blocks(metrics: &[BlockMetrics], target: f64, threshold: f64) -> u64 {
+/// A pass/fail check over a run's metrics, for embedders adding their own
+/// criteria alongside the built-in ones (see `evaluate_with_criteria`).
+pub trait Criterion {
+    fn name(&self) -> &str;
+
+    /// Verdict the run gets when this criterion fails.
+    fn severity(&self) -> Verdict;
+
+    fn evaluate(&self, metrics: &MetricsStore) -> CriterionResult;
+}
+
+/// A criterion from a closure returning whether the run passed and a
+/// one-line explanation.
+pub struct FnCriterion<F> {
+    pub name: String,
+    pub severity: Verdict,
+    pub check: F,
+}
+
+impl<F: Fn(&MetricsStore) -> (bool, String)> FnCriterion<F> {
+    pub fn new(name: &str, severity: Verdict, check: F) -> Self {
+        Self {
+            name: name.to_string(),
+            severity,
+            check,
+        }
+    }
+}
+
+impl<F: Fn(&MetricsStore) -> (bool, String)> Criterion for FnCriterion<F> {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn severity(&self) -> Verdict {
+        self.severity.clone()
+    }
+
+    fn evaluate(&self, metrics: &MetricsStore) -> CriterionResult {
+        let (passed, details) = (self.check)(metrics);
+        CriterionResult {
+            name: self.name.clone(),
+            passed,
+            severity: self.severity.clone(),
+            details,
+        }
+    }
+}
+
+/// `evaluate_pass_fail` followed by the `custom` criteria; a failing custom
+/// criterion lowers the overall verdict to its severity.
+pub fn evaluate_with_criteria(
+    metrics: &MetricsStore,
+    target_price: f64,
+    custom: &[Box<dyn Criterion>],
+) -> PassFailResult {
+    let mut result = evaluate_pass_fail(metrics, target_price);
+    for criterion in custom {
+        let outcome = criterion.evaluate(metrics);
+        if !outcome.passed {
+            result.overall = result.overall.worst(outcome.severity.clone());
+        }
+        result.criteria.push(outcome);
+    }
+    result
+}
+
+/// Blocks between the first and last block whose peg deviation exceeds `threshold`.
+pub fn compute_recovery_blocks(metrics: &MetricsStore, target: f64, threshold: f64) -> u64 {
     let mut first_deviation: Option<u64> = None;
     let mut last_deviation: Option<u64> = None;
 
-    for m in metrics {
-        let dev = ((m.amm_spot_price - target) / target).abs();
+    for (&block, p) in metrics.block.iter().zip(&metrics.amm_spot_price) {
+        let dev = ((p - target) / target).abs();
         if dev > threshold {
             if first_deviation.is_none() {
-                first_deviation = Some(m.block);
+                first_deviation = Some(block);
             }
-            last_deviation = Some(m.block);
+            last_deviation = Some(block);
         }
     }
 
@@ -225,15 +346,78 @@ fn compute_recovery_blocks(metrics: &[BlockMetrics], target: f64, threshold: f64
     }
 }
 
-fn price_stats(metrics: &[BlockMetrics]) -> (f64, f64) {
+/// One breaker intervention: when it triggered, what it did and when the
+/// restriction it imposed lifted.
+#[derive(Debug, Clone, Serialize)]
+pub struct BreakerEpisode {
+    pub block: u64,
+    /// Event type, as given by `output::extract_events`
+    pub kind: String,
+    pub details: String,
+    /// First block the restriction no longer held, `None` if it lasted to
+    /// the end of the run. One-off actions release on their own block.
+    pub released_at: Option<u64>,
+}
+
+/// Whether the restriction behind a breaker event of `kind` is in force at
+/// the `i`th block; `None` for one-off actions.
+fn breaker_in_force(kind: &str, metrics: &MetricsStore, i: usize) -> Option<bool> {
+    match kind {
+        "pause_minting" | "rate_limit_minting" => Some(metrics.minting_paused[i]),
+        "emergency_halt" => Some(metrics.halted[i]),
+        "partial_halt" => Some(metrics.partial_halted[i]),
+        "cap_redemption_drift" => Some(metrics.redemption_capped[i]),
+        "auto_deleverage" => Some(metrics.deleveraged_vaults[i] > 0),
+        _ => None,
+    }
+}
+
+/// Breaker interventions in trigger order. A breaker triggering again while
+/// its restriction is still in force extends the open episode.
+pub fn breaker_timeline(metrics: &MetricsStore) -> Vec<BreakerEpisode> {
+    let mut episodes: Vec<BreakerEpisode> = Vec::new();
+    let mut open: Vec<usize> = Vec::new();
+    let mut events = crate::output::extract_events(metrics).into_iter().peekable();
+    for (i, &block) in metrics.block.iter().enumerate() {
+        // Restrictions set during a block show from the next one
+        open.retain(|&e| {
+            let in_force = breaker_in_force(&episodes[e].kind, metrics, i) == Some(true);
+            if !in_force {
+                episodes[e].released_at = Some(block);
+            }
+            in_force
+        });
+        while let Some(event) = events.next_if(|e| e.block <= block) {
+            if event.event_type == "liquidation"
+                || open.iter().any(|&e| episodes[e].kind == event.event_type)
+            {
+                continue;
+            }
+            let one_off = breaker_in_force(&event.event_type, metrics, i).is_none();
+            if !one_off {
+                open.push(episodes.len());
+            }
+            episodes.push(BreakerEpisode {
+                block,
+                kind: event.event_type,
+                details: event.details,
+                released_at: one_off.then_some(block),
+            });
+        }
+    }
+    episodes
+}
+
+fn price_stats(metrics: &MetricsStore) -> (f64, f64) {
     if metrics.is_empty() {
         return (0.0, 0.0);
     }
     let n = metrics.len() as f64;
-    let mean = metrics.iter().map(|m| m.amm_spot_price).sum::<f64>() / n;
+    let mean = metrics.amm_spot_price.iter().sum::<f64>() / n;
     let variance = metrics
+        .amm_spot_price
         .iter()
-        .map(|m| (m.amm_spot_price - mean).powi(2))
+        .map(|p| (p - mean).powi(2))
         .sum::<f64>()
         / n;
     (mean, variance.sqrt())
@@ -258,12 +442,72 @@ fn js_array_u32(data: &[u32]) -> String {
     format!("[{}]", items.join(","))
 }
 
+fn js_array_flags(data: &[bool]) -> String {
+    let items: Vec<&str> = data.iter().map(|v| if *v { "1" } else { "0" }).collect();
+    format!("[{}]", items.join(","))
+}
+
+/// A liquidation as embedded in the report's table.
+#[derive(Serialize)]
+struct LiquidationRow<'a> {
+    block: u64,
+    mode: &'static str,
+    vault: u64,
+    owner: &'a str,
+    collateral: f64,
+    amm: f64,
+    debt: f64,
+    bad_debt: f64,
+    keeper: Option<&'a str>,
+    keeper_reward: f64,
+}
+
+fn liquidations_to_json(liquidations: &[LiquidationResult]) -> String {
+    let rows: Vec<LiquidationRow> = liquidations
+        .iter()
+        .map(|r| LiquidationRow {
+            block: r.block,
+            mode: r.mode.name(),
+            vault: r.vault_id,
+            owner: &r.owner,
+            collateral: r.collateral_seized,
+            amm: r.zai_from_amm,
+            debt: r.debt_to_cover,
+            bad_debt: r.bad_debt,
+            keeper: match &r.mode {
+                LiquidationMode::ChallengeResponse { keeper } => Some(keeper.as_str()),
+                _ => None,
+            },
+            keeper_reward: r.keeper_reward,
+        })
+        .collect();
+    serde_json::to_string(&rows).unwrap_or_else(|_| "[]".to_string())
+}
+
+pub(crate) fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
 // ═══════════════════════════════════════════════════════════════════════
 // Main report generation
 // ═══════════════════════════════════════════════════════════════════════
 
 pub fn generate_report(
-    metrics: &[BlockMetrics],
+    metrics: &MetricsStore,
+    config: &ScenarioConfig,
+    scenario_name: &str,
+    target_price: f64,
+) -> String {
+    generate_report_with_liquidations(metrics, &[], config, scenario_name, target_price)
+}
+
+/// `generate_report`, with a sortable table and size histogram of the
+/// individual `liquidations` of the run.
+pub fn generate_report_with_liquidations(
+    metrics: &MetricsStore,
+    liquidations: &[LiquidationResult],
     config: &ScenarioConfig,
     scenario_name: &str,
     target_price: f64,
@@ -272,50 +516,49 @@ pub fn generate_report(
     let summary = crate::output::compute_summary(metrics, target_price);
 
     // Extract data series
-    let blocks: Vec<u64> = metrics.iter().map(|m| m.block).collect();
-    let ext_prices: Vec<f64> = metrics.iter().map(|m| m.external_price).collect();
-    let spot_prices: Vec<f64> = metrics.iter().map(|m| m.amm_spot_price).collect();
-    let twap_prices: Vec<f64> = metrics.iter().map(|m| m.twap_price).collect();
-    let redemption_prices: Vec<f64> = metrics.iter().map(|m| m.redemption_price).collect();
-    let redemption_rates: Vec<f64> = metrics.iter().map(|m| m.redemption_rate).collect();
-    let total_debt: Vec<f64> = metrics.iter().map(|m| m.total_debt).collect();
-    let reserve_zec: Vec<f64> = metrics.iter().map(|m| m.amm_reserve_zec).collect();
-    let reserve_zai: Vec<f64> = metrics.iter().map(|m| m.amm_reserve_zai).collect();
-    let liq_counts: Vec<u32> = metrics.iter().map(|m| m.liquidation_count).collect();
-    let bad_debt: Vec<f64> = metrics.iter().map(|m| m.bad_debt).collect();
-    let total_collateral: Vec<f64> = metrics.iter().map(|m| m.total_collateral).collect();
-    let total_lp: Vec<f64> = metrics.iter().map(|m| m.total_lp_shares).collect();
-    let arber_zai: Vec<f64> = metrics.iter().map(|m| m.arber_zai_total).collect();
-    let arber_zec: Vec<f64> = metrics.iter().map(|m| m.arber_zec_total).collect();
-    let cum_fees: Vec<f64> = metrics.iter().map(|m| m.cumulative_fees_zai).collect();
-    let cum_il: Vec<f64> = metrics.iter().map(|m| m.cumulative_il_pct * 100.0).collect();
-    let zombie_counts: Vec<u32> = metrics.iter().map(|m| m.zombie_vault_count).collect();
-    let cr_ext: Vec<f64> = metrics
-        .iter()
-        .map(|m| {
-            if m.total_debt > 0.0 {
-                m.total_collateral * m.external_price / m.total_debt
-            } else {
-                0.0
-            }
-        })
-        .collect();
+    let percent = |values: &[f64]| values.iter().map(|v| v * 100.0).collect::<Vec<f64>>();
+    let cum_il = percent(&metrics.cumulative_il_pct);
+    let pol_share = percent(&metrics.pol_pool_share);
+    let lp_apr = percent(&metrics.lp_fee_apr);
+    let cr_ext = metrics.collateral_ratio(&metrics.external_price);
+    let episodes = breaker_timeline(metrics);
 
     // Derived series
     let amm_k: Vec<f64> = metrics
+        .amm_reserve_zec
         .iter()
-        .map(|m| m.amm_reserve_zec * m.amm_reserve_zai)
-        .collect();
-    let coll_ratio: Vec<f64> = metrics
-        .iter()
-        .map(|m| {
-            if m.total_debt > 0.0 {
-                m.total_collateral * m.twap_price / m.total_debt
-            } else {
-                0.0
-            }
-        })
+        .zip(&metrics.amm_reserve_zai)
+        .map(|(zec, zai)| zec * zai)
         .collect();
+    let coll_ratio = metrics.collateral_ratio(&metrics.twap_price);
+    let MetricsStore {
+        block: blocks,
+        external_price: ext_prices,
+        amm_spot_price: spot_prices,
+        twap_price: twap_prices,
+        redemption_price: redemption_prices,
+        redemption_rate: redemption_rates,
+        total_debt,
+        amm_reserve_zec: reserve_zec,
+        amm_reserve_zai: reserve_zai,
+        liquidation_count: liq_counts,
+        bad_debt,
+        treasury_balance: treasury,
+        total_collateral,
+        total_lp_shares: total_lp,
+        arber_zai_total: arber_zai,
+        arber_zec_total: arber_zec,
+        cumulative_fees_zai: cum_fees,
+        pol_fees_earned_zai: pol_fees,
+        lp_penalties_zai: lp_penalties,
+        lp_il_zai: lp_il,
+        lp_net_return_zai: lp_net,
+        zombie_vault_count: zombie_counts,
+        halted,
+        partial_halted,
+        minting_paused,
+        ..
+    } = metrics;
 
     format!(
         r#"<!DOCTYPE html>
@@ -324,7 +567,7 @@ pub fn generate_report(
 <meta charset="UTF-8">
 <meta name="viewport" content="width=device-width, initial-scale=1.0">
 <title>ZAI Report — {scenario_name}</title>
-<script src="https://cdn.jsdelivr.net/npm/chart.js@4"></script>
+<script src="{CHART_JS_CDN}"></script>
 <style>
 *{{margin:0;padding:0;box-sizing:border-box}}
 body{{font-family:-apple-system,BlinkMacSystemFont,'Segoe UI',Roboto,sans-serif;background:#f5f5f5;color:#333}}
@@ -419,6 +662,36 @@ footer{{text-align:center;padding:16px;color:#999;font-size:0.8em}}
  <div class="chart-box"><h4>Arber Capital</h4><canvas id="c9"></canvas></div>
  <div class="chart-box"><h4>LP Economics</h4><canvas id="c10"></canvas></div>
 </div>
+<div class="chart-row">
+ <div class="chart-box"><h4>LP Return Attribution</h4><canvas id="c11"></canvas></div>
+ <div class="chart-box"><h4>Breaker Timeline</h4><canvas id="c12"></canvas></div>
+</div>
+
+<section>
+<h3>Breaker Events</h3>
+<div style="max-height:400px;overflow-y:auto">
+<table>
+<tr><th>Block</th><th>Breaker</th><th>Action</th><th>Released</th><th>Blocks</th></tr>
+{breaker_rows}
+</table>
+</div>
+</section>
+
+<section>
+<h3>Liquidations</h3>
+<div class="chart-box" style="box-shadow:none;padding:0;margin-bottom:16px"><canvas id="c13"></canvas></div>
+<div style="display:flex;gap:12px;align-items:center;margin-bottom:8px">
+<select id="lqMode" onchange="renderLiq()"><option value="">All modes</option></select>
+<input id="lqFilter" oninput="renderLiq()" placeholder="Filter by vault, owner or keeper" style="padding:4px 8px;flex:1;max-width:320px">
+<span id="lqCount" style="color:#666;font-size:0.85em"></span>
+</div>
+<div style="max-height:500px;overflow-y:auto">
+<table>
+<thead><tr style="cursor:pointer"><th onclick="sortLiq('block')">Block</th><th onclick="sortLiq('mode')">Mode</th><th onclick="sortLiq('vault')">Vault</th><th onclick="sortLiq('owner')">Owner</th><th onclick="sortLiq('collateral')">Collateral (ZEC)</th><th onclick="sortLiq('amm')">AMM Proceeds (ZAI)</th><th onclick="sortLiq('debt')">Debt Covered (ZAI)</th><th onclick="sortLiq('bad_debt')">Bad Debt (ZAI)</th><th onclick="sortLiq('keeper_reward')">Keeper</th></tr></thead>
+<tbody id="lqBody"></tbody>
+</table>
+</div>
+</section>
 
 <section>
 <h3>Pass / Fail Criteria</h3>
@@ -462,25 +735,43 @@ const D={{
  fees:{js_fees},
  il:{js_il},
  crext:{js_cr_ext},
- zombies:{js_zombies}
+ zombies:{js_zombies},
+ treas:{js_treasury},
+ polshare:{js_pol_share},
+ polfees:{js_pol_fees},
+ lpapr:{js_lp_apr},
+ lppen:{js_lp_penalties},
+ lpil:{js_lp_il},
+ lpnet:{js_lp_net},
+ halt:{js_halt},
+ phalt:{js_phalt},
+ mpause:{js_mpause}
 }};
+const EP={js_episodes};
+const LQ={js_liquidations};
 const mkDs=(l,c,d,o)=>{{let s={{label:l,data:d,borderColor:c,backgroundColor:c+'22',borderWidth:1.5,pointRadius:0,fill:false,tension:0.1}};if(o)Object.assign(s,o);return s}};
 const lineOpts=(title,yLabel,extra)=>{{let o={{responsive:true,maintainAspectRatio:false,plugins:{{title:{{display:true,text:title}},legend:{{position:'bottom',labels:{{boxWidth:12,font:{{size:11}}}}}}}},scales:{{x:{{title:{{display:true,text:'Block'}},ticks:{{maxTicksLimit:10}}}},y:{{title:{{display:true,text:yLabel}},beginAtZero:false}}}}}};if(extra)Object.assign(o.scales,extra);return o}};
 const y2={{y2:{{position:'right',grid:{{drawOnChartArea:false}},title:{{display:true,text:''}}}}}};
+// Halt and pause shading, drawn on a hidden 0-1 axis
+const SHADE={{display:false,min:0,max:1}};
+const shade=(l,c,f)=>mkDs(l,c,f.map(v=>v?1:null),{{yAxisID:'shade',backgroundColor:c+'33',borderWidth:0,fill:'origin',stepped:true}});
+const shades=()=>[shade('Halted','#757575',D.halt),shade('Partial Halt','#ff9800',D.phalt),shade('Minting Paused','#03a9f4',D.mpause)];
 
 // 1. Price Comparison
 new Chart(document.getElementById('c1'),{{type:'line',data:{{labels:B,datasets:[
  mkDs('External','#4285f4',D.ext),
  mkDs('Spot','#ea8c00',D.spot),
  mkDs('TWAP','#34a853',D.twap),
- mkDs('Redemption','#ea4335',D.redp,{{borderDash:[6,3]}})
-]}},options:lineOpts('Price Comparison','ZAI/ZEC Price')}});
+ mkDs('Redemption','#ea4335',D.redp,{{borderDash:[6,3]}}),
+ ...shades()
+]}},options:lineOpts('Price Comparison','ZAI/ZEC Price',{{shade:SHADE}})}});
 
 // 2. System Health
 new Chart(document.getElementById('c2'),{{type:'line',data:{{labels:B,datasets:[
  mkDs('Collateral Ratio','#9c27b0',D.cr),
  mkDs('Total Debt','#009688',D.debt,{{yAxisID:'y2'}}),
- mkDs('AMM ZAI Reserve','#ff9800',D.rzai,{{yAxisID:'y2'}})
+ mkDs('AMM ZAI Reserve','#ff9800',D.rzai,{{yAxisID:'y2'}}),
+ mkDs('Treasury Balance','#3f51b5',D.treas,{{yAxisID:'y2',borderDash:[4,2]}})
 ]}},options:lineOpts('System Health','Collateral Ratio',{{y2:{{position:'right',grid:{{drawOnChartArea:false}},title:{{display:true,text:'ZAI'}}}}}})
 }});
 
@@ -520,8 +811,9 @@ new Chart(document.getElementById('c6'),{{type:'line',data:{{labels:B,datasets:[
  new Chart(document.getElementById('c7'),{{type:'line',data:{{labels:B,datasets:[
   mkDs('External','#4285f4',D.ext),
   mkDs('AMM Spot','#ea8c00',D.spot),
-  mkDs('Gap %','#e91e63',gap,{{yAxisID:'y2',fill:true,backgroundColor:'#e91e6333'}})
- ]}},options:lineOpts('AMM vs External Price','Price',{{y2:{{position:'right',grid:{{drawOnChartArea:false}},title:{{display:true,text:'Gap %'}}}}}})}});
+  mkDs('Gap %','#e91e63',gap,{{yAxisID:'y2',fill:true,backgroundColor:'#e91e6333'}}),
+  ...shades()
+ ]}},options:lineOpts('AMM vs External Price','Price',{{y2:{{position:'right',grid:{{drawOnChartArea:false}},title:{{display:true,text:'Gap %'}}}},shade:SHADE}})}});
 }})();
 
 // 8. Zombie Vault CR Gap
@@ -548,8 +840,68 @@ new Chart(document.getElementById('c8'),{{type:'line',data:{{labels:B,datasets:[
  new Chart(document.getElementById('c10'),{{type:'line',data:{{labels:B,datasets:[
   mkDs('Cumulative Fees (ZAI)','#34a853',D.fees),
   mkDs('Impermanent Loss %','#ea4335',D.il),
-  mkDs('Net (Fees + IL)','#9c27b0',netPnl,{{borderDash:[6,3]}})
- ]}},options:lineOpts('LP Economics','ZAI / %')}});
+  mkDs('Net (Fees + IL)','#9c27b0',netPnl,{{borderDash:[6,3]}}),
+  mkDs('POL Fees (ZAI)','#3f51b5',D.polfees),
+  mkDs('POL Pool Share %','#757575',D.polshare,{{yAxisID:'y2',borderDash:[4,2]}})
+ ]}},options:lineOpts('LP Economics','ZAI / %',{{y2:{{position:'right',grid:{{drawOnChartArea:false}},title:{{display:true,text:'POL share %'}}}}}})}});
+}})();
+
+// 11. LP Return Attribution
+(()=>{{
+ const lpFees=D.lpnet.map((n,i)=>n-D.lppen[i]-D.lpil[i]);
+ new Chart(document.getElementById('c11'),{{type:'line',data:{{labels:B,datasets:[
+  mkDs('Fees (ZAI)','#34a853',lpFees),
+  mkDs('Penalties (ZAI)','#ff9800',D.lppen),
+  mkDs('Impermanent Loss (ZAI)','#ea4335',D.lpil),
+  mkDs('Net Return (ZAI)','#9c27b0',D.lpnet,{{borderDash:[6,3]}}),
+  mkDs('Fee APR %','#4285f4',D.lpapr,{{yAxisID:'y2'}})
+ ]}},options:lineOpts('LP Return Attribution','ZAI',{{y2:{{position:'right',grid:{{drawOnChartArea:false}},title:{{display:true,text:'Fee APR %'}}}}}})}});
+}})();
+
+// 12. Breaker Timeline: one bar per episode, from trigger to release
+(()=>{{
+ const last=B.length?B[B.length-1]:0;
+ const kinds=[...new Set(EP.map(e=>e.kind))];
+ const bars=EP.map(e=>({{x:[e.block,Math.max(e.released_at??last,e.block+1)],y:e.kind}}));
+ new Chart(document.getElementById('c12'),{{type:'bar',data:{{labels:kinds,datasets:[
+  {{label:'Active',data:bars,backgroundColor:'#ea433599',borderColor:'#ea4335',borderWidth:1,barPercentage:0.6}}
+ ]}},options:{{indexAxis:'y',responsive:true,maintainAspectRatio:false,plugins:{{title:{{display:true,text:'Breaker Timeline'}},legend:{{display:false}},tooltip:{{callbacks:{{label:c=>EP[c.dataIndex].details}}}}}},scales:{{x:{{type:'linear',min:B.length?B[0]:0,max:last,title:{{display:true,text:'Block'}}}}}}}}}});
+}})();
+
+// 13. Liquidation Sizes: histogram of debt covered per liquidation
+(()=>{{
+ const sizes=LQ.map(r=>r.debt);
+ if(!sizes.length)return;
+ const lo=sizes.reduce((a,b)=>Math.min(a,b)),hi=sizes.reduce((a,b)=>Math.max(a,b));
+ const n=20,w=(hi-lo)/n||1;
+ const counts=new Array(n).fill(0);
+ sizes.forEach(v=>counts[Math.min(n-1,Math.floor((v-lo)/w))]++);
+ const labels=counts.map((_,i)=>(lo+i*w).toFixed(0)+'-'+(lo+(i+1)*w).toFixed(0));
+ new Chart(document.getElementById('c13'),{{type:'bar',data:{{labels:labels,datasets:[
+  {{label:'Liquidations',data:counts,backgroundColor:'#e91e6366',borderColor:'#e91e63',borderWidth:1}}
+ ]}},options:{{responsive:true,maintainAspectRatio:false,plugins:{{title:{{display:true,text:'Liquidation Sizes'}},legend:{{display:false}}}},scales:{{x:{{title:{{display:true,text:'Debt Covered (ZAI)'}}}},y:{{title:{{display:true,text:'Count'}},beginAtZero:true}}}}}}}});
+}})();
+
+// Liquidation table: filter by mode and text, sort by any column
+const esc=s=>String(s).replace(/[&<>]/g,c=>({{'&':'&amp;','<':'&lt;','>':'&gt;'}})[c]);
+let lqSort={{key:'block',asc:true}};
+function sortLiq(k){{
+ lqSort=lqSort.key===k?{{key:k,asc:!lqSort.asc}}:{{key:k,asc:true}};
+ renderLiq();
+}}
+function renderLiq(){{
+ const mode=document.getElementById('lqMode').value;
+ const q=document.getElementById('lqFilter').value.toLowerCase();
+ const rows=LQ.filter(r=>(!mode||r.mode===mode)&&(!q||[r.vault,r.owner,r.keeper||''].join(' ').toLowerCase().includes(q)));
+ const k=lqSort.key,s=lqSort.asc?1:-1;
+ rows.sort((a,b)=>{{const x=a[k]??'',y=b[k]??'';return x>y?s:x<y?-s:0}});
+ document.getElementById('lqBody').innerHTML=rows.length?rows.map(r=>'<tr><td>'+r.block+'</td><td>'+r.mode+'</td><td>'+r.vault+'</td><td>'+esc(r.owner)+'</td><td>'+r.collateral.toFixed(4)+'</td><td>'+r.amm.toFixed(2)+'</td><td>'+r.debt.toFixed(2)+'</td><td>'+r.bad_debt.toFixed(2)+'</td><td>'+(r.keeper?esc(r.keeper)+' ':'')+r.keeper_reward.toFixed(2)+'</td></tr>').join(''):'<tr><td colspan="9">No liquidations recorded</td></tr>';
+ document.getElementById('lqCount').textContent=rows.length+' of '+LQ.length+' liquidations';
+}}
+(()=>{{
+ const sel=document.getElementById('lqMode');
+ [...new Set(LQ.map(r=>r.mode))].forEach(m=>sel.add(new Option(m,m)));
+ renderLiq();
 }})();
 
 // Config and summary data for downloads
@@ -565,10 +917,10 @@ function downloadBlob(data,filename,mime){{
 }}
 
 function downloadCSV(){{
- const headers=['block','external_price','amm_spot_price','twap_price','redemption_price','redemption_rate','total_debt','reserve_zec','reserve_zai','liquidations','bad_debt','total_collateral','collateral_ratio','k','lp_shares','arber_zai','arber_zec','cumulative_fees','il_pct','cr_ext','zombies'];
+ const headers=['block','external_price','amm_spot_price','twap_price','redemption_price','redemption_rate','total_debt','reserve_zec','reserve_zai','liquidations','bad_debt','total_collateral','collateral_ratio','k','lp_shares','arber_zai','arber_zec','cumulative_fees','il_pct','cr_ext','zombies','pol_share_pct','pol_fees','lp_fee_apr_pct','lp_penalties','lp_il','lp_net_return'];
  let csv=headers.join(',')+'\n';
  for(let i=0;i<B.length;i++){{
-  csv+=[B[i],D.ext[i],D.spot[i],D.twap[i],D.redp[i],D.redr[i],D.debt[i],D.rzec[i],D.rzai[i],D.liqs[i],D.bd[i],D.coll[i],D.cr[i],D.k[i],D.lp[i],D.arb[i],D.arbzec[i],D.fees[i],D.il[i],D.crext[i],D.zombies[i]].join(',')+'\n';
+  csv+=[B[i],D.ext[i],D.spot[i],D.twap[i],D.redp[i],D.redr[i],D.debt[i],D.rzec[i],D.rzai[i],D.liqs[i],D.bd[i],D.coll[i],D.cr[i],D.k[i],D.lp[i],D.arb[i],D.arbzec[i],D.fees[i],D.il[i],D.crext[i],D.zombies[i],D.polshare[i],D.polfees[i],D.lpapr[i],D.lppen[i],D.lpil[i],D.lpnet[i]].join(',')+'\n';
  }}
  downloadBlob(csv,'{scenario_name}.csv','text/csv');
 }}
@@ -594,8 +946,8 @@ function downloadSummary(){{
         breaker_triggers = summary.breaker_triggers,
         halt_blocks = summary.halt_blocks,
         final_price = summary.final_amm_price,
-        amm_zec = config.amm_initial_zec,
-        amm_zai = config.amm_initial_zai,
+        amm_zec = config.amm_reserves().0,
+        amm_zai = config.amm_reserves().1,
         swap_fee = config.amm_swap_fee,
         min_ratio = config.cdp_config.min_ratio,
         liq_penalty = config.cdp_config.liquidation_penalty,
@@ -606,27 +958,40 @@ function downloadSummary(){{
         debt_ceil = config.debt_ceiling_config.initial_ceiling,
         target_price = target_price,
         criteria_rows = criteria_html(&verdict),
-        js_blocks = js_array_u64(&blocks),
-        js_ext = js_array_f64(&ext_prices),
-        js_spot = js_array_f64(&spot_prices),
-        js_twap = js_array_f64(&twap_prices),
-        js_redp = js_array_f64(&redemption_prices),
-        js_redr = js_array_f64(&redemption_rates),
-        js_debt = js_array_f64(&total_debt),
-        js_rzec = js_array_f64(&reserve_zec),
-        js_rzai = js_array_f64(&reserve_zai),
-        js_liqs = js_array_u32(&liq_counts),
-        js_bd = js_array_f64(&bad_debt),
-        js_coll = js_array_f64(&total_collateral),
+        breaker_rows = breaker_timeline_html(&episodes),
+        js_blocks = js_array_u64(blocks),
+        js_ext = js_array_f64(ext_prices),
+        js_spot = js_array_f64(spot_prices),
+        js_twap = js_array_f64(twap_prices),
+        js_redp = js_array_f64(redemption_prices),
+        js_redr = js_array_f64(redemption_rates),
+        js_debt = js_array_f64(total_debt),
+        js_rzec = js_array_f64(reserve_zec),
+        js_rzai = js_array_f64(reserve_zai),
+        js_liqs = js_array_u32(liq_counts),
+        js_bd = js_array_f64(bad_debt),
+        js_coll = js_array_f64(total_collateral),
         js_cr = js_array_f64(&coll_ratio),
         js_k = js_array_f64(&amm_k),
-        js_lp = js_array_f64(&total_lp),
-        js_arb = js_array_f64(&arber_zai),
-        js_arb_zec = js_array_f64(&arber_zec),
-        js_fees = js_array_f64(&cum_fees),
+        js_lp = js_array_f64(total_lp),
+        js_arb = js_array_f64(arber_zai),
+        js_arb_zec = js_array_f64(arber_zec),
+        js_fees = js_array_f64(cum_fees),
         js_il = js_array_f64(&cum_il),
         js_cr_ext = js_array_f64(&cr_ext),
-        js_zombies = js_array_u32(&zombie_counts),
+        js_zombies = js_array_u32(zombie_counts),
+        js_treasury = js_array_f64(treasury),
+        js_pol_share = js_array_f64(&pol_share),
+        js_pol_fees = js_array_f64(pol_fees),
+        js_lp_apr = js_array_f64(&lp_apr),
+        js_lp_penalties = js_array_f64(lp_penalties),
+        js_lp_il = js_array_f64(lp_il),
+        js_lp_net = js_array_f64(lp_net),
+        js_halt = js_array_flags(halted),
+        js_phalt = js_array_flags(partial_halted),
+        js_mpause = js_array_flags(minting_paused),
+        js_episodes = serde_json::to_string(&episodes).unwrap_or_else(|_| "[]".to_string()),
+        js_liquidations = liquidations_to_json(liquidations),
         js_config_json = config_to_json(config),
         js_summary_json = summary_to_json(&summary),
     )
@@ -645,11 +1010,33 @@ fn criteria_html(result: &PassFailResult) -> String {
     rows
 }
 
+fn breaker_timeline_html(episodes: &[BreakerEpisode]) -> String {
+    if episodes.is_empty() {
+        return "<tr><td colspan=\"5\">No breaker triggered</td></tr>\n".to_string();
+    }
+    let mut rows = String::new();
+    for e in episodes {
+        let (released, blocks) = match e.released_at {
+            Some(r) => (r.to_string(), (r - e.block).to_string()),
+            None => ("end of run".to_string(), "-".to_string()),
+        };
+        rows.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+            e.block,
+            e.kind,
+            html_escape(&e.details),
+            released,
+            blocks
+        ));
+    }
+    rows
+}
+
 fn config_to_json(config: &ScenarioConfig) -> String {
     format!(
         r#"{{"amm_initial_zec":{:.1},"amm_initial_zai":{:.1},"swap_fee":{:.4},"min_ratio":{:.2},"liquidation_penalty":{:.2},"stability_fee_rate":{:.4},"debt_floor":{:.0},"twap_window":{},"initial_redemption_price":{:.2},"stochastic":{},"noise_sigma":{:.4}}}"#,
-        config.amm_initial_zec,
-        config.amm_initial_zai,
+        config.amm_reserves().0,
+        config.amm_reserves().1,
         config.amm_swap_fee,
         config.cdp_config.min_ratio,
         config.cdp_config.liquidation_penalty,
@@ -804,14 +1191,996 @@ fn scenarios_to_js(entries: &[(String, PassFailResult, SummaryMetrics)]) -> Stri
     format!("[{}]", items.join(","))
 }
 
+/// One-page report following a single persona across all stress scenarios.
+pub fn generate_persona_report(report: &PersonaReport) -> String {
+    let mut rows = String::new();
+    for s in &report.scenarios {
+        let cls = if s.liquidation_probability > 0.0 {
+            "hard-fail"
+        } else if s.worst_pnl_pct < 0.0 {
+            "soft-fail"
+        } else {
+            "pass"
+        };
+        rows.push_str(&format!(
+            "<tr>\
+             <td>{name}</td>\
+             <td><span class=\"badge {cls}\">{liq:.0}%</span></td>\
+             <td>{mean:+.2}%</td>\
+             <td>{worst:+.2}%</td>\
+             <td>{dd:.2}%</td>\
+             </tr>\n",
+            name = s.scenario.name(),
+            cls = cls,
+            liq = s.liquidation_probability * 100.0,
+            mean = s.mean_pnl_pct * 100.0,
+            worst = s.worst_pnl_pct * 100.0,
+            dd = s.worst_drawdown_pct * 100.0,
+        ));
+    }
+
+    let runs_per_scenario = report.outcomes.len() / report.scenarios.len().max(1);
+    let liquidated_in: Vec<&str> = report
+        .scenarios
+        .iter()
+        .filter(|s| s.liquidation_probability > 0.0)
+        .map(|s| s.scenario.name())
+        .collect();
+    let disclosure = if liquidated_in.is_empty() {
+        "Not liquidated in any stress scenario.".to_string()
+    } else {
+        format!(
+            "Liquidated in {:.0}% of runs ({}).",
+            report.liquidation_probability * 100.0,
+            liquidated_in.join(", ")
+        )
+    };
+
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="UTF-8">
+<title>ZAI Simulation — Persona Report</title>
+<style>
+*{{margin:0;padding:0;box-sizing:border-box}}
+body{{font-family:-apple-system,BlinkMacSystemFont,'Segoe UI',Roboto,sans-serif;background:#f5f5f5;color:#333}}
+header{{background:#1a1a2e;color:#fff;padding:24px 32px}}
+header h1{{font-size:1.4em;font-weight:500}}
+.summary-line{{margin-top:8px;font-size:1em;opacity:0.9}}
+main{{max-width:1200px;margin:0 auto;padding:24px}}
+section{{background:#fff;border-radius:8px;box-shadow:0 1px 3px rgba(0,0,0,0.1);padding:24px;margin-bottom:20px}}
+section h3{{margin-bottom:12px}}
+section p{{line-height:1.6}}
+table{{width:100%;border-collapse:collapse;font-size:0.9em}}
+th,td{{padding:10px 14px;text-align:left;border-bottom:1px solid #e0e0e0}}
+th{{background:#f8f9fa;font-weight:600}}
+.badge{{padding:3px 10px;border-radius:3px;font-weight:700;font-size:0.8em}}
+.badge.pass{{background:#34a853;color:#fff}}
+.badge.soft-fail{{background:#ea8c00;color:#fff}}
+.badge.hard-fail{{background:#ea4335;color:#fff}}
+footer{{text-align:center;padding:16px;color:#999;font-size:0.8em}}
+</style>
+</head>
+<body>
+<header>
+ <h1>ZAI Simulation — Persona Report</h1>
+ <div class="summary-line">{persona} · {blocks} blocks · {runs} run(s) per scenario</div>
+</header>
+<main>
+<section>
+<h3>Risk Disclosure</h3>
+<p>{disclosure}</p>
+<p>Worst final P&amp;L: {worst_pnl:+.2}% · Worst drawdown: {worst_dd:.2}%</p>
+</section>
+<section>
+<table>
+<tr>
+ <th>Scenario</th><th>Liquidation Probability</th><th>Mean Final P&amp;L</th>
+ <th>Worst Final P&amp;L</th><th>Worst Drawdown</th>
+</tr>
+{rows}
+</table>
+</section>
+</main>
+<footer>Generated by zai-sim</footer>
+</body>
+</html>"#,
+        persona = report.persona.describe(),
+        blocks = report.blocks,
+        runs = runs_per_scenario,
+        disclosure = disclosure,
+        worst_pnl = report.worst_pnl_pct * 100.0,
+        worst_dd = report.worst_drawdown_pct * 100.0,
+        rows = rows,
+    )
+}
+
+/// Calibrated agent parameter ranges against their current defaults.
+pub fn generate_calibration_report(report: &CalibrationReport) -> String {
+    let mut rows = String::new();
+    for p in &report.params {
+        let cls = if p.default >= p.low && p.default <= p.high {
+            "pass"
+        } else {
+            "soft-fail"
+        };
+        rows.push_str(&format!(
+            "<tr>\
+             <td>{name}</td>\
+             <td>{low:.4}</td>\
+             <td><strong>{est:.4}</strong></td>\
+             <td>{high:.4}</td>\
+             <td><span class=\"badge {cls}\">{default:.4}</span></td>\
+             <td>{basis}</td>\
+             </tr>\n",
+            name = p.name,
+            low = p.low,
+            est = p.estimate,
+            high = p.high,
+            cls = cls,
+            default = p.default,
+            basis = p.basis,
+        ));
+    }
+
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="UTF-8">
+<title>ZAI Simulation — Calibration Report</title>
+<style>
+*{{margin:0;padding:0;box-sizing:border-box}}
+body{{font-family:-apple-system,BlinkMacSystemFont,'Segoe UI',Roboto,sans-serif;background:#f5f5f5;color:#333}}
+header{{background:#1a1a2e;color:#fff;padding:24px 32px}}
+header h1{{font-size:1.4em;font-weight:500}}
+.summary-line{{margin-top:8px;font-size:1em;opacity:0.9}}
+main{{max-width:1200px;margin:0 auto;padding:24px}}
+section{{background:#fff;border-radius:8px;box-shadow:0 1px 3px rgba(0,0,0,0.1);padding:24px;margin-bottom:20px}}
+section h3{{margin-bottom:12px}}
+section p{{line-height:1.6}}
+table{{width:100%;border-collapse:collapse;font-size:0.9em}}
+th,td{{padding:10px 14px;text-align:left;border-bottom:1px solid #e0e0e0}}
+th{{background:#f8f9fa;font-weight:600}}
+.badge{{padding:3px 10px;border-radius:3px;font-weight:700;font-size:0.8em}}
+.badge.pass{{background:#34a853;color:#fff}}
+.badge.soft-fail{{background:#ea8c00;color:#fff}}
+footer{{text-align:center;padding:16px;color:#999;font-size:0.8em}}
+</style>
+</head>
+<body>
+<header>
+ <h1>ZAI Simulation — Calibration Report</h1>
+ <div class="summary-line">{source} · {hours} hourly candles</div>
+</header>
+<main>
+<section>
+<h3>Market Inputs</h3>
+<p>Mean CEX volume: {volume:.0} ZEC/hour · DEX share of volume: {dex_share:.1}%</p>
+<p>Median hourly move: {ret:.2}% · Median hourly range: {range:.2}%</p>
+</section>
+<section>
+<table>
+<tr>
+ <th>Parameter</th><th>Low</th><th>Estimate</th><th>High</th>
+ <th>Current Default</th><th>Basis</th>
+</tr>
+{rows}
+</table>
+</section>
+</main>
+<footer>Generated by zai-sim</footer>
+</body>
+</html>"#,
+        source = report.source,
+        hours = report.hours,
+        volume = report.mean_hourly_volume_zec,
+        dex_share = report.dex_share * 100.0,
+        ret = report.median_abs_return_pct,
+        range = report.median_range_pct,
+        rows = rows,
+    )
+}
+
 // ═══════════════════════════════════════════════════════════════════════
-// File I/O
+// Run comparison (A/B)
 // ═══════════════════════════════════════════════════════════════════════
 
-pub fn save_report(html: &str, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+/// One summary metric across the two runs of a comparison.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricDelta {
+    pub name: String,
+    pub a: Option<f64>,
+    pub b: Option<f64>,
+}
+
+impl MetricDelta {
+    /// Change from A to B as a percentage of A; `None` if either run lacks
+    /// the metric or A is zero.
+    pub fn pct_change(&self) -> Option<f64> {
+        match (self.a, self.b) {
+            (Some(a), Some(b)) if a != 0.0 => Some((b - a) / a.abs() * 100.0),
+            _ => None,
+        }
+    }
+}
+
+/// A config setting that differs between two runs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigChange {
+    pub key: String,
+    pub a: Option<String>,
+    pub b: Option<String>,
+}
+
+/// Keys of `a` in order, then those only in `b`.
+fn union_keys<'a, T>(a: &'a [(String, T)], b: &'a [(String, T)]) -> Vec<&'a str> {
+    let mut keys: Vec<&str> = a.iter().map(|(k, _)| k.as_str()).collect();
+    for (k, _) in b {
+        if !keys.contains(&k.as_str()) {
+            keys.push(k);
+        }
+    }
+    keys
+}
+
+fn lookup<'a, T>(entries: &'a [(String, T)], key: &str) -> Option<&'a T> {
+    entries.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+}
+
+/// Every summary metric of either run, with its value in each.
+pub fn summary_deltas(run_a: &SavedRun, run_b: &SavedRun) -> Vec<MetricDelta> {
+    union_keys(&run_a.summary, &run_b.summary)
+        .into_iter()
+        .map(|name| MetricDelta {
+            name: name.to_string(),
+            a: lookup(&run_a.summary, name).copied(),
+            b: lookup(&run_b.summary, name).copied(),
+        })
+        .collect()
+}
+
+/// The config settings that differ between the runs.
+pub fn config_diff(run_a: &SavedRun, run_b: &SavedRun) -> Vec<ConfigChange> {
+    union_keys(&run_a.config, &run_b.config)
+        .into_iter()
+        .map(|key| ConfigChange {
+            key: key.to_string(),
+            a: lookup(&run_a.config, key).cloned(),
+            b: lookup(&run_b.config, key).cloned(),
+        })
+        .filter(|c| c.a != c.b)
+        .collect()
+}
+
+fn opt_cell(v: Option<String>) -> String {
+    v.map_or_else(|| "—".to_string(), |v| html_escape(&v))
+}
+
+/// HTML report overlaying two saved runs' price, debt and liquidation
+/// series, with their config and summary differences.
+pub fn generate_comparison(run_a: &SavedRun, run_b: &SavedRun) -> String {
+    let mut summary_rows = String::new();
+    for d in summary_deltas(run_a, run_b) {
+        let delta = match (d.a, d.b) {
+            (Some(a), Some(b)) => format!("{:+.4}", b - a),
+            _ => "—".to_string(),
+        };
+        let (pct, class) = match d.pct_change() {
+            Some(p) if p.abs() < 1e-9 => ("0.00%".to_string(), ""),
+            Some(p) => (format!("{:+.2}%", p), if p > 0.0 { "up" } else { "down" }),
+            None => ("—".to_string(), ""),
+        };
+        summary_rows.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td class=\"{}\">{}</td></tr>\n",
+            d.name,
+            opt_cell(d.a.map(|v| format!("{:.4}", v))),
+            opt_cell(d.b.map(|v| format!("{:.4}", v))),
+            delta,
+            class,
+            pct
+        ));
+    }
+
+    let changes = config_diff(run_a, run_b);
+    let mut config_rows = String::new();
+    for c in &changes {
+        config_rows.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+            c.key,
+            opt_cell(c.a.clone()),
+            opt_cell(c.b.clone())
+        ));
+    }
+    if changes.is_empty() {
+        config_rows.push_str("<tr><td colspan=\"3\">Configs are identical</td></tr>\n");
+    }
+
+    let series = |run: &SavedRun, column: &str| js_array_f64(run.series(column).unwrap_or(&[]));
+    let blocks_a = run_a.series("block").unwrap_or(&[]);
+    let blocks_b = run_b.series("block").unwrap_or(&[]);
+    let blocks: Vec<u64> = if blocks_a.len() >= blocks_b.len() {
+        blocks_a
+    } else {
+        blocks_b
+    }
+    .iter()
+    .map(|b| *b as u64)
+    .collect();
+
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="UTF-8">
+<meta name="viewport" content="width=device-width, initial-scale=1.0">
+<title>ZAI Comparison — {name_a} vs {name_b}</title>
+<script src="{CHART_JS_CDN}"></script>
+<style>
+*{{margin:0;padding:0;box-sizing:border-box}}
+body{{font-family:-apple-system,BlinkMacSystemFont,'Segoe UI',Roboto,sans-serif;background:#f5f5f5;color:#333}}
+header{{background:#1a1a2e;color:#fff;padding:24px 32px}}
+header h1{{font-size:1.4em;font-weight:500}}
+header h2{{font-size:1.1em;font-weight:300;opacity:0.8}}
+main{{max-width:1400px;margin:0 auto;padding:24px}}
+section{{background:#fff;border-radius:8px;box-shadow:0 1px 3px rgba(0,0,0,0.1);padding:24px;margin-bottom:20px}}
+section h3{{font-size:1.1em;margin-bottom:16px;color:#1a1a2e;border-bottom:2px solid #e0e0e0;padding-bottom:8px}}
+table{{width:100%;border-collapse:collapse;font-size:0.9em}}
+th,td{{padding:8px 12px;text-align:left;border-bottom:1px solid #e0e0e0}}
+th{{background:#f8f9fa;font-weight:600}}
+td.up{{color:#34a853}}
+td.down{{color:#ea4335}}
+.chart-row{{display:grid;grid-template-columns:1fr 1fr;gap:20px;margin-bottom:20px}}
+@media(max-width:900px){{.chart-row{{grid-template-columns:1fr}}}}
+.chart-box{{background:#fff;border-radius:8px;box-shadow:0 1px 3px rgba(0,0,0,0.1);padding:16px}}
+.chart-box h4{{font-size:0.95em;margin-bottom:8px;color:#555}}
+canvas{{width:100%!important;height:300px!important}}
+footer{{text-align:center;padding:16px;color:#999;font-size:0.8em}}
+</style>
+</head>
+<body>
+<header>
+ <h1>ZAI Run Comparison</h1>
+ <h2>A: {name_a} &nbsp;vs&nbsp; B: {name_b}</h2>
+</header>
+<main>
+
+<section>
+<h3>Summary Metrics</h3>
+<table>
+<tr><th>Metric</th><th>A</th><th>B</th><th>Δ</th><th>Δ%</th></tr>
+{summary_rows}
+</table>
+</section>
+
+<section>
+<h3>Config Differences</h3>
+<table>
+<tr><th>Setting</th><th>A</th><th>B</th></tr>
+{config_rows}
+</table>
+</section>
+
+<div class="chart-row">
+ <div class="chart-box"><h4>AMM Price</h4><canvas id="c1"></canvas></div>
+ <div class="chart-box"><h4>Redemption Price</h4><canvas id="c2"></canvas></div>
+</div>
+<div class="chart-row">
+ <div class="chart-box"><h4>Total Debt</h4><canvas id="c3"></canvas></div>
+ <div class="chart-box"><h4>Cumulative Liquidations</h4><canvas id="c4"></canvas></div>
+</div>
+
+</main>
+<footer>Generated by zai-sim</footer>
+
+<script>
+const B={js_blocks};
+const A={{ext:{a_ext},spot:{a_spot},redp:{a_redp},debt:{a_debt},liqs:{a_liqs},bd:{a_bd}}};
+const Z={{ext:{b_ext},spot:{b_spot},redp:{b_redp},debt:{b_debt},liqs:{b_liqs},bd:{b_bd}}};
+const cum=d=>{{let s=0;return d.map(v=>s+=v)}};
+const mkDs=(l,c,d,o)=>{{let s={{label:l,data:d,borderColor:c,backgroundColor:c+'22',borderWidth:1.5,pointRadius:0,fill:false,tension:0.1}};if(o)Object.assign(s,o);return s}};
+const lineOpts=(title,yLabel,extra)=>{{let o={{responsive:true,maintainAspectRatio:false,plugins:{{title:{{display:true,text:title}},legend:{{position:'bottom',labels:{{boxWidth:12,font:{{size:11}}}}}}}},scales:{{x:{{title:{{display:true,text:'Block'}},ticks:{{maxTicksLimit:10}}}},y:{{title:{{display:true,text:yLabel}},beginAtZero:false}}}}}};if(extra)Object.assign(o.scales,extra);return o}};
+
+// 1. AMM Price, against A's external price
+new Chart(document.getElementById('c1'),{{type:'line',data:{{labels:B,datasets:[
+ mkDs('External (A)','#9e9e9e',A.ext,{{borderDash:[4,2]}}),
+ mkDs('Spot A','#4285f4',A.spot),
+ mkDs('Spot B','#ea8c00',Z.spot)
+]}},options:lineOpts('AMM Price','ZAI/ZEC Price')}});
+
+// 2. Redemption Price
+new Chart(document.getElementById('c2'),{{type:'line',data:{{labels:B,datasets:[
+ mkDs('A','#4285f4',A.redp),
+ mkDs('B','#ea8c00',Z.redp)
+]}},options:lineOpts('Redemption Price','Price')}});
+
+// 3. Total Debt
+new Chart(document.getElementById('c3'),{{type:'line',data:{{labels:B,datasets:[
+ mkDs('A','#4285f4',A.debt),
+ mkDs('B','#ea8c00',Z.debt)
+]}},options:lineOpts('Total Debt','ZAI')}});
+
+// 4. Cumulative Liquidations, with bad debt
+new Chart(document.getElementById('c4'),{{type:'line',data:{{labels:B,datasets:[
+ mkDs('Liquidations A','#4285f4',cum(A.liqs)),
+ mkDs('Liquidations B','#ea8c00',cum(Z.liqs)),
+ mkDs('Bad Debt A','#4285f4',A.bd,{{yAxisID:'y2',borderDash:[6,3]}}),
+ mkDs('Bad Debt B','#ea8c00',Z.bd,{{yAxisID:'y2',borderDash:[6,3]}})
+]}},options:lineOpts('Cumulative Liquidations','Count',{{y2:{{position:'right',grid:{{drawOnChartArea:false}},title:{{display:true,text:'Cumulative Bad Debt'}}}}}})}});
+</script>
+</body>
+</html>"#,
+        name_a = html_escape(&run_a.name),
+        name_b = html_escape(&run_b.name),
+        summary_rows = summary_rows,
+        config_rows = config_rows,
+        js_blocks = js_array_u64(&blocks),
+        a_ext = series(run_a, "external_price"),
+        a_spot = series(run_a, "amm_spot_price"),
+        a_redp = series(run_a, "redemption_price"),
+        a_debt = series(run_a, "total_debt"),
+        a_liqs = series(run_a, "liquidations"),
+        a_bd = series(run_a, "bad_debt"),
+        b_ext = series(run_b, "external_price"),
+        b_spot = series(run_b, "amm_spot_price"),
+        b_redp = series(run_b, "redemption_price"),
+        b_debt = series(run_b, "total_debt"),
+        b_liqs = series(run_b, "liquidations"),
+        b_bd = series(run_b, "bad_debt"),
+    )
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+// Monte Carlo
+// ═══════════════════════════════════════════════════════════════════════
+
+/// What a Monte Carlo report keeps of one seed's run.
+#[derive(Debug, Clone)]
+pub struct MonteCarloRun {
+    pub seed: u64,
+    pub blocks: Vec<u64>,
+    pub amm_price: Vec<f64>,
+    pub total_debt: Vec<f64>,
+    pub bad_debt: f64,
+    pub max_peg_deviation: f64,
+    pub verdict: Verdict,
+}
+
+impl MonteCarloRun {
+    pub fn from_metrics(seed: u64, metrics: &MetricsStore, target_price: f64) -> Self {
+        let summary = crate::output::compute_summary(metrics, target_price);
+        Self {
+            seed,
+            blocks: metrics.block.clone(),
+            amm_price: metrics.amm_spot_price.clone(),
+            total_debt: metrics.total_debt.clone(),
+            bad_debt: summary.total_bad_debt,
+            max_peg_deviation: summary.max_peg_deviation,
+            verdict: evaluate_pass_fail(metrics, target_price).overall,
+        }
+    }
+}
+
+/// Percentiles drawn in fan charts: the 5-95 and 25-75 bands and the median.
+pub const FAN_PERCENTILES: [f64; 5] = [0.05, 0.25, 0.5, 0.75, 0.95];
+
+/// Percentile `p` (0-1) of ascending `sorted` values, interpolating
+/// linearly between ranks.
+pub fn percentile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let idx = p.clamp(0.0, 1.0) * (sorted.len() - 1) as f64;
+    let lo = idx.floor() as usize;
+    let hi = (lo + 1).min(sorted.len() - 1);
+    let frac = idx - lo as f64;
+    sorted[lo] * (1.0 - frac) + sorted[hi] * frac
+}
+
+/// Per-block `percentiles` of a series across runs: one band per
+/// percentile, as long as the shortest run.
+pub fn fan_bands(series: &[&[f64]], percentiles: &[f64]) -> Vec<Vec<f64>> {
+    let len = series.iter().map(|s| s.len()).min().unwrap_or(0);
+    let mut bands = vec![Vec::with_capacity(len); percentiles.len()];
+    let mut column = Vec::with_capacity(series.len());
+    for i in 0..len {
+        column.clear();
+        column.extend(series.iter().map(|s| s[i]));
+        column.sort_by(f64::total_cmp);
+        for (band, p) in bands.iter_mut().zip(percentiles) {
+            band.push(percentile(&column, *p));
+        }
+    }
+    bands
+}
+
+/// `bins` equal-width bins over the range of `values`, as (lower edge,
+/// upper edge, count). Identical values share one unit-wide bin range.
+pub fn histogram(values: &[f64], bins: usize) -> Vec<(f64, f64, usize)> {
+    if values.is_empty() || bins == 0 {
+        return Vec::new();
+    }
+    let lo = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let hi = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let width = if hi > lo {
+        (hi - lo) / bins as f64
+    } else {
+        1.0
+    };
+    let mut counts = vec![0; bins];
+    for v in values {
+        counts[(((v - lo) / width) as usize).min(bins - 1)] += 1;
+    }
+    counts
+        .into_iter()
+        .enumerate()
+        .map(|(i, c)| (lo + i as f64 * width, lo + (i + 1) as f64 * width, c))
+        .collect()
+}
+
+/// Empirical CDF of `values`: each distinct value, ascending, with the
+/// fraction of values at or below it.
+pub fn empirical_cdf(values: &[f64]) -> Vec<(f64, f64)> {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let n = sorted.len() as f64;
+    let mut points: Vec<(f64, f64)> = Vec::new();
+    for (i, v) in sorted.into_iter().enumerate() {
+        let frac = (i + 1) as f64 / n;
+        match points.last_mut() {
+            Some(last) if last.0 == v => last.1 = frac,
+            _ => points.push((v, frac)),
+        }
+    }
+    points
+}
+
+fn js_bands(bands: &[Vec<f64>]) -> String {
+    let items: Vec<String> = bands.iter().map(|b| js_array_f64(b)).collect();
+    format!("[{}]", items.join(","))
+}
+
+fn js_histogram(bins: &[(f64, f64, usize)], scale: f64) -> String {
+    let labels: Vec<String> = bins
+        .iter()
+        .map(|(lo, hi, _)| format!("\"{:.2}-{:.2}\"", lo * scale, hi * scale))
+        .collect();
+    let counts: Vec<String> = bins.iter().map(|b| b.2.to_string()).collect();
+    format!(
+        "{{labels:[{}],counts:[{}]}}",
+        labels.join(","),
+        counts.join(",")
+    )
+}
+
+fn js_cdf(points: &[(f64, f64)], scale: f64) -> String {
+    let items: Vec<String> = points
+        .iter()
+        .map(|(x, y)| format!("{{x:{:.4},y:{:.4}}}", x * scale, y))
+        .collect();
+    format!("[{}]", items.join(","))
+}
+
+/// Monte Carlo report for one scenario run across seeds: percentile fan
+/// charts of AMM price and total debt over time, and histograms and CDFs of
+/// final bad debt and max peg deviation, so the tails are visible.
+pub fn generate_monte_carlo_report(scenario_name: &str, runs: &[MonteCarloRun]) -> String {
+    let n = runs.len();
+    let pass = runs.iter().filter(|r| r.verdict == Verdict::Pass).count();
+    let pass_pct = if n > 0 {
+        pass as f64 / n as f64 * 100.0
+    } else {
+        0.0
+    };
+
+    let prices: Vec<&[f64]> = runs.iter().map(|r| r.amm_price.as_slice()).collect();
+    let debts: Vec<&[f64]> = runs.iter().map(|r| r.total_debt.as_slice()).collect();
+    let price_bands = fan_bands(&prices, &FAN_PERCENTILES);
+    let debt_bands = fan_bands(&debts, &FAN_PERCENTILES);
+    let len = price_bands.first().map_or(0, |b| b.len());
+    let blocks: Vec<u64> = runs
+        .first()
+        .map(|r| r.blocks.iter().copied().take(len).collect())
+        .unwrap_or_default();
+
+    let bad_debt: Vec<f64> = runs.iter().map(|r| r.bad_debt).collect();
+    let max_dev: Vec<f64> = runs.iter().map(|r| r.max_peg_deviation).collect();
+
+    let mut stat_rows = String::new();
+    for (label, values, scale, unit) in [
+        ("Bad Debt", &bad_debt, 1.0, ""),
+        ("Max Peg Deviation", &max_dev, 100.0, "%"),
+    ] {
+        let mut sorted = values.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        let cells: Vec<String> = [0.0, 0.05, 0.25, 0.5, 0.75, 0.95, 0.99, 1.0]
+            .iter()
+            .map(|p| format!("<td>{:.2}{}</td>", percentile(&sorted, *p) * scale, unit))
+            .collect();
+        stat_rows.push_str(&format!("<tr><td>{}</td>{}</tr>\n", label, cells.concat()));
+    }
+
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="UTF-8">
+<meta name="viewport" content="width=device-width, initial-scale=1.0">
+<title>ZAI Monte Carlo — {name}</title>
+<script src="{CHART_JS_CDN}"></script>
+<style>
+*{{margin:0;padding:0;box-sizing:border-box}}
+body{{font-family:-apple-system,BlinkMacSystemFont,'Segoe UI',Roboto,sans-serif;background:#f5f5f5;color:#333}}
+header{{background:#1a1a2e;color:#fff;padding:24px 32px}}
+header h1{{font-size:1.4em;font-weight:500}}
+header h2{{font-size:1.1em;font-weight:300;opacity:0.8}}
+main{{max-width:1400px;margin:0 auto;padding:24px}}
+section{{background:#fff;border-radius:8px;box-shadow:0 1px 3px rgba(0,0,0,0.1);padding:24px;margin-bottom:20px}}
+section h3{{font-size:1.1em;margin-bottom:16px;color:#1a1a2e;border-bottom:2px solid #e0e0e0;padding-bottom:8px}}
+table{{width:100%;border-collapse:collapse;font-size:0.9em}}
+th,td{{padding:8px 12px;text-align:left;border-bottom:1px solid #e0e0e0}}
+th{{background:#f8f9fa;font-weight:600}}
+.chart-row{{display:grid;grid-template-columns:1fr 1fr;gap:20px;margin-bottom:20px}}
+@media(max-width:900px){{.chart-row{{grid-template-columns:1fr}}}}
+.chart-box{{background:#fff;border-radius:8px;box-shadow:0 1px 3px rgba(0,0,0,0.1);padding:16px}}
+.chart-box h4{{font-size:0.95em;margin-bottom:8px;color:#555}}
+canvas{{width:100%!important;height:300px!important}}
+footer{{text-align:center;padding:16px;color:#999;font-size:0.8em}}
+</style>
+</head>
+<body>
+<header>
+ <h1>ZAI Monte Carlo — {name}</h1>
+ <h2>{n} seeds, {pass} pass ({pass_pct:.0}%)</h2>
+</header>
+<main>
+
+<section>
+<h3>Distribution Across Seeds</h3>
+<table>
+<tr><th>Metric</th><th>Min</th><th>P5</th><th>P25</th><th>Median</th><th>P75</th><th>P95</th><th>P99</th><th>Max</th></tr>
+{stat_rows}
+</table>
+</section>
+
+<div class="chart-row">
+ <div class="chart-box"><h4>AMM Price Percentiles</h4><canvas id="c1"></canvas></div>
+ <div class="chart-box"><h4>Total Debt Percentiles</h4><canvas id="c2"></canvas></div>
+</div>
+<div class="chart-row">
+ <div class="chart-box"><h4>Bad Debt Distribution</h4><canvas id="c3"></canvas></div>
+ <div class="chart-box"><h4>Bad Debt CDF</h4><canvas id="c4"></canvas></div>
+</div>
+<div class="chart-row">
+ <div class="chart-box"><h4>Max Peg Deviation Distribution</h4><canvas id="c5"></canvas></div>
+ <div class="chart-box"><h4>Max Peg Deviation CDF</h4><canvas id="c6"></canvas></div>
+</div>
+
+</main>
+<footer>Generated by zai-sim</footer>
+
+<script>
+const B={js_blocks};
+const PRICE={js_price};
+const DEBT={js_debt};
+const BD_HIST={js_bd_hist};
+const DEV_HIST={js_dev_hist};
+const BD_CDF={js_bd_cdf};
+const DEV_CDF={js_dev_cdf};
+const opts=(title,xLabel,yLabel,extra)=>{{let o={{responsive:true,maintainAspectRatio:false,plugins:{{title:{{display:true,text:title}},legend:{{position:'bottom',labels:{{boxWidth:12,font:{{size:11}}}}}}}},scales:{{x:{{title:{{display:true,text:xLabel}},ticks:{{maxTicksLimit:10}}}},y:{{title:{{display:true,text:yLabel}},beginAtZero:false}}}}}};if(extra)Object.assign(o.scales,extra);return o}};
+// Bands are [P5,P25,P50,P75,P95]; each band fills down to the dataset before it
+const fan=(id,title,yLabel,b,c)=>new Chart(document.getElementById(id),{{type:'line',data:{{labels:B,datasets:[
+ {{label:'P95',data:b[4],borderColor:c+'55',borderWidth:1,pointRadius:0,fill:false}},
+ {{label:'P5',data:b[0],borderColor:c+'55',backgroundColor:c+'22',borderWidth:1,pointRadius:0,fill:'-1'}},
+ {{label:'P75',data:b[3],borderColor:c+'88',borderWidth:1,pointRadius:0,fill:false}},
+ {{label:'P25',data:b[1],borderColor:c+'88',backgroundColor:c+'44',borderWidth:1,pointRadius:0,fill:'-1'}},
+ {{label:'Median',data:b[2],borderColor:c,borderWidth:2,pointRadius:0,fill:false}}
+]}},options:opts(title,'Block',yLabel)}});
+const hist=(id,title,xLabel,h,c)=>new Chart(document.getElementById(id),{{type:'bar',data:{{labels:h.labels,datasets:[
+ {{label:'Seeds',data:h.counts,backgroundColor:c+'66',borderColor:c,borderWidth:1}}
+]}},options:opts(title,xLabel,'Seeds',{{y:{{beginAtZero:true,title:{{display:true,text:'Seeds'}}}}}})}});
+const cdf=(id,title,xLabel,d,c)=>new Chart(document.getElementById(id),{{type:'line',data:{{datasets:[
+ {{label:'Fraction of seeds',data:d,borderColor:c,backgroundColor:c+'22',borderWidth:2,pointRadius:2,stepped:true,fill:true}}
+]}},options:opts(title,xLabel,'P(X ≤ x)',{{x:{{type:'linear',title:{{display:true,text:xLabel}}}},y:{{min:0,max:1,title:{{display:true,text:'P(X ≤ x)'}}}}}})}});
+
+fan('c1','AMM Price','ZAI/ZEC Price',PRICE,'#4285f4');
+fan('c2','Total Debt','ZAI',DEBT,'#ea8c00');
+hist('c3','Final Bad Debt','Bad Debt (ZAI)',BD_HIST,'#ea4335');
+cdf('c4','Final Bad Debt','Bad Debt (ZAI)',BD_CDF,'#ea4335');
+hist('c5','Max Peg Deviation','Max Peg Deviation (%)',DEV_HIST,'#9c27b0');
+cdf('c6','Max Peg Deviation','Max Peg Deviation (%)',DEV_CDF,'#9c27b0');
+</script>
+</body>
+</html>"#,
+        name = html_escape(scenario_name),
+        n = n,
+        pass = pass,
+        pass_pct = pass_pct,
+        stat_rows = stat_rows,
+        js_blocks = js_array_u64(&blocks),
+        js_price = js_bands(&price_bands),
+        js_debt = js_bands(&debt_bands),
+        js_bd_hist = js_histogram(&histogram(&bad_debt, 20), 1.0),
+        js_dev_hist = js_histogram(&histogram(&max_dev, 20), 100.0),
+        js_bd_cdf = js_cdf(&empirical_cdf(&bad_debt), 1.0),
+        js_dev_cdf = js_cdf(&empirical_cdf(&max_dev), 100.0),
+    )
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+// Markdown / PDF summaries
+// ═══════════════════════════════════════════════════════════════════════
+
+/// Format of the per-scenario and master summary reports.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReportFormat {
+    Html,
+    Markdown,
+    Pdf,
+}
+
+impl ReportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Html => "html",
+            Self::Markdown => "md",
+            Self::Pdf => "pdf",
+        }
+    }
+}
+
+impl FromStr for ReportFormat {
+    type Err = ZaiSimError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "html" => Ok(Self::Html),
+            "markdown" | "md" => Ok(Self::Markdown),
+            "pdf" => Ok(Self::Pdf),
+            _ => Err(ZaiSimError::Parse(format!(
+                "Unknown report format: {} (use html, markdown or pdf)",
+                s
+            ))),
+        }
+    }
+}
+
+/// Escape text for a Markdown table cell.
+fn md_cell(s: &str) -> String {
+    s.replace('|', "\\|").replace('\n', " ")
+}
+
+/// Concise Markdown summary of a run: verdict, criteria table and key
+/// metrics, for pasting into issues and pull requests.
+pub fn generate_markdown_summary(
+    metrics: &MetricsStore,
+    config: &ScenarioConfig,
+    scenario_name: &str,
+    target_price: f64,
+) -> String {
+    let verdict = evaluate_pass_fail(metrics, target_price);
+    let summary = crate::output::compute_summary(metrics, target_price);
+    let (amm_zec, amm_zai) = config.amm_reserves();
+
+    let mut md = format!(
+        "## ZAI Report — {}\n\n**Verdict: {}**\n\n",
+        scenario_name,
+        verdict.overall.label()
+    );
+    md.push_str("| Criterion | Result | Severity | Details |\n|---|---|---|---|\n");
+    for c in &verdict.criteria {
+        md.push_str(&format!(
+            "| {} | {} | {} | {} |\n",
+            md_cell(&c.name),
+            if c.passed { "PASS" } else { "FAIL" },
+            c.severity.label(),
+            md_cell(&c.details)
+        ));
+    }
+
+    md.push_str("\n| Metric | Value |\n|---|---:|\n");
+    let rows = [
+        ("Blocks", summary.total_blocks.to_string()),
+        (
+            "Mean peg deviation",
+            format!("{:.2}%", summary.mean_peg_deviation * 100.0),
+        ),
+        (
+            "Max peg deviation",
+            format!("{:.2}%", summary.max_peg_deviation * 100.0),
+        ),
+        (
+            "Final peg deviation",
+            format!("{:.2}%", summary.final_peg_deviation * 100.0),
+        ),
+        (
+            "Peg deviation CVaR 95% / 99%",
+            format!(
+                "{:.2}% / {:.2}%",
+                summary.cvar_95_deviation * 100.0,
+                summary.cvar_99_deviation * 100.0
+            ),
+        ),
+        ("Blocks under peg", summary.under_peg_blocks.to_string()),
+        (
+            "AMM price max drawdown",
+            format!("{:.2}%", summary.max_drawdown * 100.0),
+        ),
+        (
+            "Collateral ratio drawdown",
+            format!("{:.2}%", summary.collateral_ratio_drawdown * 100.0),
+        ),
+        ("Liquidations", summary.total_liquidations.to_string()),
+        ("Bad debt", format!("{:.2}", summary.total_bad_debt)),
+        ("Breaker triggers", summary.breaker_triggers.to_string()),
+        ("Halt blocks", summary.halt_blocks.to_string()),
+        (
+            "AMM price (min / mean / max)",
+            format!(
+                "{:.2} / {:.2} / {:.2}",
+                summary.min_amm_price, summary.mean_amm_price, summary.max_amm_price
+            ),
+        ),
+        ("Final AMM price", format!("{:.4}", summary.final_amm_price)),
+        (
+            "Final redemption price",
+            format!("{:.4}", summary.final_redemption_price),
+        ),
+    ];
+    for (name, value) in rows {
+        md.push_str(&format!("| {} | {} |\n", name, value));
+    }
+
+    md.push_str(&format!(
+        "\nAMM {:.0} ZEC / {:.0} ZAI, fee {:.2}%, min ratio {:.0}%, \
+         liquidation penalty {:.0}%, target {:.2}\n",
+        amm_zec,
+        amm_zai,
+        config.amm_swap_fee * 100.0,
+        config.cdp_config.min_ratio * 100.0,
+        config.cdp_config.liquidation_penalty * 100.0,
+        target_price
+    ));
+    md
+}
+
+/// Markdown table of every scenario's verdict and headline metrics, linking
+/// to each scenario's own summary.
+pub fn generate_master_summary_markdown(
+    entries: &[(String, PassFailResult, SummaryMetrics)],
+) -> String {
+    let pass_count = entries
+        .iter()
+        .filter(|(_, r, _)| r.overall == Verdict::Pass)
+        .count();
+    let mut md = format!(
+        "## ZAI Stress Test Summary\n\n**{} / {} scenarios pass**\n\n",
+        pass_count,
+        entries.len()
+    );
+    md.push_str(
+        "| Scenario | Verdict | Mean peg dev | Bad debt | Liquidations | Halt blocks | Final price |\n\
+         |---|---|---:|---:|---:|---:|---:|\n",
+    );
+    for (name, result, summary) in entries {
+        md.push_str(&format!(
+            "| [{name}]({name}.md) | {} | {:.2}% | {:.2} | {} | {} | {:.2} |\n",
+            result.overall.label(),
+            summary.mean_peg_deviation * 100.0,
+            summary.total_bad_debt,
+            summary.total_liquidations,
+            summary.halt_blocks,
+            summary.final_amm_price,
+        ));
+    }
+    md
+}
+
+/// Render a Markdown summary as a printable PDF: the heading becomes the
+/// title, tables are laid out in aligned columns and emphasis is dropped.
+pub fn markdown_to_pdf(markdown: &str) -> Vec<u8> {
+    let mut title = String::new();
+    let mut lines = Vec::new();
+    let mut table: Vec<Vec<String>> = Vec::new();
+    for line in markdown.lines().chain(std::iter::once("")) {
+        let line = line.trim_end();
+        if line.starts_with('|') {
+            // Skip the |---| separator row
+            if !line.chars().all(|c| "|-: ".contains(c)) {
+                table.push(
+                    line.trim_matches('|')
+                        .split(" | ")
+                        .map(|c| md_plain(c.trim()))
+                        .collect(),
+                );
+            }
+            continue;
+        }
+        if !table.is_empty() {
+            lines.extend(aligned_table(&table));
+            table.clear();
+        }
+        if let Some(heading) = line.strip_prefix("## ") {
+            title = heading.to_string();
+        } else {
+            lines.push(line.replace("**", ""));
+        }
+    }
+    while lines.first().is_some_and(|l| l.is_empty()) {
+        lines.remove(0);
+    }
+    crate::pdf::text_document(&title, &lines)
+}
+
+/// Undo Markdown table escaping and reduce a `[text](link)` cell to its text.
+fn md_plain(cell: &str) -> String {
+    let cell = cell.replace("\\|", "|");
+    match cell.strip_prefix('[').and_then(|c| c.split_once("](")) {
+        Some((text, _)) => text.to_string(),
+        None => cell,
+    }
+}
+
+/// Pad table cells to a common width per column, with a rule under the
+/// header row.
+fn aligned_table(rows: &[Vec<String>]) -> Vec<String> {
+    let columns = rows.iter().map(|r| r.len()).max().unwrap_or(0);
+    let widths: Vec<usize> = (0..columns)
+        .map(|i| {
+            rows.iter()
+                .filter_map(|r| r.get(i))
+                .map(|c| c.chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    let mut out = Vec::new();
+    for (i, row) in rows.iter().enumerate() {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .map(|(c, w)| format!("{:<w$}", c, w = *w))
+            .collect();
+        out.push(cells.join("  ").trim_end().to_string());
+        if i == 0 {
+            let width = widths.iter().sum::<usize>() + 2 * columns.saturating_sub(1);
+            out.push("-".repeat(width));
+        }
+    }
+    out
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+// File I/O
+// ═══════════════════════════════════════════════════════════════════════
+
+/// Read a local Chart.js UMD build (e.g. `chart.umd.min.js`) for inlining
+/// into offline reports.
+pub fn load_chart_js(path: &Path) -> Result<String, ZaiSimError> {
+    let js = std::fs::read_to_string(path)?;
+    if !js.contains("Chart") {
+        return Err(ZaiSimError::InvalidInput(format!(
+            "{} does not look like a Chart.js build",
+            path.display()
+        )));
+    }
+    Ok(js)
+}
+
+/// Replace the Chart.js CDN script tag with the library itself so the report
+/// renders without network access. Reports without charts are unchanged.
+pub fn inline_chart_js(html: &str, chart_js: &str) -> String {
+    let tag = format!(r#"<script src="{CHART_JS_CDN}"></script>"#);
+    // A literal `</script` inside the library would end the element early
+    let body = chart_js.replace("</script", "<\\/script");
+    html.replacen(&tag, &format!("<script>\n{}\n</script>", body), 1)
+}
+
+pub fn save_report(contents: impl AsRef<[u8]>, path: &Path) -> Result<(), ZaiSimError> {
     if let Some(parent) = path.parent() {
         std::fs::create_dir_all(parent)?;
     }
-    std::fs::write(path, html)?;
+    std::fs::write(path, contents)?;
     Ok(())
 }
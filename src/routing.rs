@@ -0,0 +1,252 @@
+//! Trade routing across the ZEC/ZAI AMM and its side pools.
+//!
+//! With liquidity fragmented over several venues, selling ZEC for ZAI can
+//! go straight into the AMM, into a ZEC/ZAI side pool or order book, or
+//! through another asset (ZEC/USD, then ZAI/USD). `Amm::best_route` quotes every direct and
+//! two-hop path and picks the one paying the most. Traders that just need
+//! to convert (miners, demand agents, liquidations) use
+//! `sell_zec` and `buy_zec`; arbitrageurs and attackers, which target the
+//! AMM's own price, keep swapping against it directly.
+
+use serde::{Deserialize, Serialize};
+
+use crate::amm::Amm;
+use crate::error::ZaiSimError;
+use crate::pool::Asset;
+
+/// Cap on AMM/order book arbitrage rounds per call; each round clears a
+/// book level or closes the gap.
+const MAX_ARBITRAGE_ROUNDS: usize = 64;
+
+/// Where a hop trades.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Venue {
+    /// The ZEC/ZAI AMM
+    Amm,
+    /// `Amm::pools[i]`
+    Pool(usize),
+    /// `Amm::order_book`
+    OrderBook,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Hop {
+    pub venue: Venue,
+    pub sell: Asset,
+    pub buy: Asset,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Route {
+    pub hops: Vec<Hop>,
+    /// Quoted output of the whole route
+    pub amount_out: f64,
+}
+
+impl Amm {
+    /// Venues trading `sell` for `buy`.
+    fn venues(&self, sell: Asset, buy: Asset) -> Vec<Hop> {
+        let mut hops = Vec::new();
+        if (sell, buy) == (Asset::Zec, Asset::Zai) || (sell, buy) == (Asset::Zai, Asset::Zec) {
+            hops.push(Hop {
+                venue: Venue::Amm,
+                sell,
+                buy,
+            });
+            if self.order_book.is_some() {
+                hops.push(Hop {
+                    venue: Venue::OrderBook,
+                    sell,
+                    buy,
+                });
+            }
+        }
+        for (i, pool) in self.pools.iter().enumerate() {
+            if pool.trades(sell, buy) {
+                hops.push(Hop {
+                    venue: Venue::Pool(i),
+                    sell,
+                    buy,
+                });
+            }
+        }
+        hops
+    }
+
+    /// Direct and two-hop paths from `sell` to `buy`, each venue used at
+    /// most once.
+    fn paths(&self, sell: Asset, buy: Asset) -> Vec<Vec<Hop>> {
+        let mut paths: Vec<Vec<Hop>> = self
+            .venues(sell, buy)
+            .into_iter()
+            .map(|h| vec![h])
+            .collect();
+        for (i, pool) in self.pools.iter().enumerate() {
+            if pool.base != sell && pool.quote != sell {
+                continue;
+            }
+            let mid = pool.counter_asset(sell);
+            if mid == buy {
+                continue;
+            }
+            let first = Hop {
+                venue: Venue::Pool(i),
+                sell,
+                buy: mid,
+            };
+            for second in self.venues(mid, buy) {
+                if second.venue != first.venue {
+                    paths.push(vec![first, second]);
+                }
+            }
+        }
+        paths
+    }
+
+    fn quote_hop(&self, hop: &Hop, amount: f64) -> f64 {
+        match hop.venue {
+            Venue::Amm if hop.sell == Asset::Zec => self.quote_zec_for_zai(amount),
+            Venue::Amm => self.quote_zai_for_zec(amount),
+            Venue::Pool(i) => self.pools[i].quote(hop.sell, amount),
+            Venue::OrderBook => match &self.order_book {
+                Some(book) if hop.sell == Asset::Zec => book.quote_zec_for_zai(amount),
+                Some(book) => book.quote_zai_for_zec(amount),
+                None => 0.0,
+            },
+        }
+    }
+
+    fn swap_hop(&mut self, hop: &Hop, amount: f64, block: u64) -> Result<f64, ZaiSimError> {
+        match hop.venue {
+            Venue::Amm if hop.sell == Asset::Zec => self.swap_zec_for_zai(amount, block),
+            Venue::Amm => self.swap_zai_for_zec(amount, block),
+            Venue::Pool(i) => self.pools[i].swap(hop.sell, amount),
+            Venue::OrderBook => {
+                let book = self.order_book.as_mut().ok_or_else(|| {
+                    ZaiSimError::InsufficientLiquidity("no order book".to_string())
+                })?;
+                if hop.sell == Asset::Zec {
+                    book.swap_zec_for_zai(amount)
+                } else {
+                    book.swap_zai_for_zec(amount)
+                }
+            }
+        }
+    }
+
+    /// The path paying the most for `amount` of `sell`, if any venue
+    /// trades the pair.
+    pub fn best_route(&self, sell: Asset, buy: Asset, amount: f64) -> Option<Route> {
+        self.paths(sell, buy)
+            .into_iter()
+            .map(|hops| {
+                let amount_out = hops.iter().fold(amount, |a, hop| self.quote_hop(hop, a));
+                Route { hops, amount_out }
+            })
+            .max_by(|a, b| a.amount_out.total_cmp(&b.amount_out))
+    }
+
+    /// Trade `amount` of `sell` for `buy` along the best route. Returns the
+    /// amount received.
+    pub fn swap_routed(
+        &mut self,
+        sell: Asset,
+        buy: Asset,
+        amount: f64,
+        block: u64,
+    ) -> Result<f64, ZaiSimError> {
+        let route = self.best_route(sell, buy, amount).ok_or_else(|| {
+            ZaiSimError::InsufficientLiquidity(format!("no venue trades {:?} for {:?}", sell, buy))
+        })?;
+        let mut out = amount;
+        for hop in &route.hops {
+            out = self.swap_hop(hop, out, block)?;
+        }
+        Ok(out)
+    }
+
+    /// Sell ZEC for ZAI wherever pays most; the same as `swap_zec_for_zai`
+    /// without side pools or an order book.
+    pub fn sell_zec(&mut self, zec_in: f64, block: u64) -> Result<f64, ZaiSimError> {
+        if self.pools.is_empty() && self.order_book.is_none() {
+            return self.swap_zec_for_zai(zec_in, block);
+        }
+        self.swap_routed(Asset::Zec, Asset::Zai, zec_in, block)
+    }
+
+    /// Buy ZEC with ZAI wherever pays most; the same as `swap_zai_for_zec`
+    /// without side pools or an order book.
+    pub fn buy_zec(&mut self, zai_in: f64, block: u64) -> Result<f64, ZaiSimError> {
+        if self.pools.is_empty() && self.order_book.is_none() {
+            return self.swap_zai_for_zec(zai_in, block);
+        }
+        self.swap_routed(Asset::Zai, Asset::Zec, zai_in, block)
+    }
+
+    /// Trade the AMM against the order book until neither side's best
+    /// price beats the other's after fees, as an outside arbitrageur
+    /// would. Returns the ZEC moved between them.
+    pub fn arbitrage_order_book(&mut self, block: u64) -> f64 {
+        let mut moved = 0.0;
+        for _ in 0..MAX_ARBITRAGE_ROUNDS {
+            let Some(book) = &self.order_book else {
+                break;
+            };
+            let fee = self.swap_fee;
+            let book_fee = book.config.taker_fee;
+            let spot = self.spot_price();
+
+            // Book bid above the AMM's ask: buy ZEC on the AMM, sell into the book
+            if let Some(level) = book.best_bid() {
+                let bid = book.bid_price(level) * (1.0 - book_fee);
+                if bid > spot / (1.0 - fee) {
+                    // ZEC that lifts the AMM's marginal ask to the bid
+                    let target_spot = bid * (1.0 - fee);
+                    let zec =
+                        (self.reserve_zec - (self.k / target_spot).sqrt()).min(book.bids[level]);
+                    if zec <= 1e-9 {
+                        break;
+                    }
+                    let zai_in = self.reserve_zai * zec / ((self.reserve_zec - zec) * (1.0 - fee));
+                    let Ok(zec_out) = self.swap_zai_for_zec(zai_in, block) else {
+                        break;
+                    };
+                    if let Some(book) = &mut self.order_book {
+                        let _ = book.swap_zec_for_zai(zec_out.min(book.bids[level]));
+                    }
+                    moved += zec_out;
+                    continue;
+                }
+            }
+
+            // Book ask below the AMM's bid: buy ZEC from the book, sell on the AMM
+            if let Some(level) = book.best_ask() {
+                let ask = book.ask_price(level) / (1.0 - book_fee);
+                if ask < spot * (1.0 - fee) {
+                    // ZEC that pushes the AMM's marginal bid down to the ask
+                    let target_spot = ask / (1.0 - fee);
+                    let zec =
+                        ((self.k / target_spot).sqrt() - self.reserve_zec).min(book.asks[level]);
+                    if zec <= 1e-9 {
+                        break;
+                    }
+                    let zai_in = zec * ask;
+                    let zec_out = match &mut self.order_book {
+                        Some(book) => book.swap_zai_for_zec(zai_in),
+                        None => break,
+                    };
+                    let Ok(zec_out) = zec_out else {
+                        break;
+                    };
+                    if self.swap_zec_for_zai(zec_out, block).is_err() {
+                        break;
+                    }
+                    moved += zec_out;
+                    continue;
+                }
+            }
+            break;
+        }
+        moved
+    }
+}
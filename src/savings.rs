@@ -0,0 +1,142 @@
+//! ZAI savings rate (DSR).
+//!
+//! Holders lock ZAI in the savings module and earn an annual rate paid out
+//! of the treasury surplus, which stability fees fill. The rate follows the
+//! controller: a negative redemption rate (ZAI trading below redemption)
+//! raises it to draw ZAI off the market, a positive one lowers it.
+//!
+//! Deposits are tracked as shares of a growing index, like Maker's `pot`:
+//! a deposit of `z` ZAI mints `z / chi` shares and interest raises `chi`.
+//! Only interest the surplus can pay is credited; the rest is recorded as
+//! unfunded.
+
+use serde::{Deserialize, Serialize};
+
+use crate::cdp::{apr_to_per_block, BLOCKS_PER_YEAR};
+use crate::error::ZaiSimError;
+use crate::treasury::Treasury;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavingsConfig {
+    /// Annual savings rate with the controller at rest
+    pub base_rate: f64,
+    /// Annual savings rate added per unit of annualized redemption rate,
+    /// against its sign
+    pub rate_sensitivity: f64,
+    /// Lower bound on the annual savings rate
+    pub min_rate: f64,
+    /// Upper bound on the annual savings rate
+    pub max_rate: f64,
+}
+
+impl Default for SavingsConfig {
+    fn default() -> Self {
+        SavingsConfig {
+            base_rate: 0.02,
+            rate_sensitivity: 1.0,
+            min_rate: 0.0,
+            max_rate: 0.2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavingsModule {
+    pub config: SavingsConfig,
+    /// Current annual savings rate
+    pub rate: f64,
+    /// ZAI redeemable per share
+    pub chi: f64,
+    pub total_shares: f64,
+    /// Interest paid to savers from the surplus (ZAI)
+    pub total_interest_paid_zai: f64,
+    /// Interest owed at `rate` that the surplus could not cover (ZAI)
+    pub unfunded_interest_zai: f64,
+    pub last_block: u64,
+}
+
+impl SavingsModule {
+    pub fn new(config: SavingsConfig) -> Self {
+        let rate = config.base_rate.clamp(config.min_rate, config.max_rate);
+        SavingsModule {
+            config,
+            rate,
+            chi: 1.0,
+            total_shares: 0.0,
+            total_interest_paid_zai: 0.0,
+            unfunded_interest_zai: 0.0,
+            last_block: 0,
+        }
+    }
+
+    /// ZAI locked in the module, interest included.
+    pub fn total_deposits_zai(&self) -> f64 {
+        self.total_shares * self.chi
+    }
+
+    /// ZAI redeemable for `shares`.
+    pub fn balance_of(&self, shares: f64) -> f64 {
+        shares * self.chi
+    }
+
+    /// Lock `zai`. Returns the shares minted.
+    pub fn deposit(&mut self, zai: f64) -> Result<f64, ZaiSimError> {
+        if zai <= 0.0 {
+            return Err(ZaiSimError::InvalidInput(
+                "savings deposit must be positive".to_string(),
+            ));
+        }
+        let shares = zai / self.chi;
+        self.total_shares += shares;
+        Ok(shares)
+    }
+
+    /// Burn `shares`. Returns the ZAI released.
+    pub fn withdraw(&mut self, shares: f64) -> Result<f64, ZaiSimError> {
+        if shares <= 0.0 {
+            return Err(ZaiSimError::InvalidInput(
+                "savings withdrawal must be positive".to_string(),
+            ));
+        }
+        if shares > self.total_shares {
+            return Err(ZaiSimError::InsufficientBalance {
+                what: "savings shares".to_string(),
+                have: self.total_shares,
+                requested: shares,
+            });
+        }
+        self.total_shares -= shares;
+        Ok(shares * self.chi)
+    }
+
+    /// Set the annual rate from the controller's per-block redemption rate.
+    pub fn set_rate(&mut self, redemption_rate: f64) -> f64 {
+        let c = &self.config;
+        let rate = c.base_rate - c.rate_sensitivity * redemption_rate * BLOCKS_PER_YEAR;
+        self.rate = rate.clamp(c.min_rate, c.max_rate);
+        self.rate
+    }
+
+    /// Pay interest since the last call at the current rate, out of the
+    /// treasury surplus. Returns the ZAI paid.
+    pub fn accrue(&mut self, treasury: &mut Treasury, block: u64) -> f64 {
+        if block <= self.last_block {
+            return 0.0;
+        }
+        let blocks = block - self.last_block;
+        self.last_block = block;
+        let deposits = self.total_deposits_zai();
+        if deposits <= 0.0 || self.rate <= 0.0 {
+            return 0.0;
+        }
+
+        let growth = (1.0 + apr_to_per_block(self.rate)).powi(blocks as i32) - 1.0;
+        let owed = deposits * growth;
+        let paid = owed.min(treasury.balance_zai.max(0.0));
+        treasury.balance_zai -= paid;
+        self.chi *= 1.0 + paid / deposits;
+        self.total_interest_paid_zai += paid;
+        self.unfunded_interest_zai += owed - paid;
+        paid
+    }
+}
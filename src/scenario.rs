@@ -1,15 +1,49 @@
+use std::io::Write;
+use std::ops::{Bound, RangeBounds};
+use std::path::{Path, PathBuf};
+
+use crate::adoption::{Adoption, AdoptionConfig};
+use crate::agent_metrics::{AgentMetricsCollector, AgentSample};
 use crate::agents::*;
-use crate::amm::Amm;
+use crate::amm::{Amm, DynamicFee, DynamicFeeConfig, PriceObservation};
+use crate::block_space::{BlockSpace, BlockSpaceConfig};
+use crate::bridge::{Bridge, BridgeConfig, BridgeDirection, BridgeTransfer};
 use crate::cdp::{CdpConfig, VaultRegistry};
+use crate::ceiling_policy::{CeilingInputs, CeilingPolicy, CeilingPolicyConfig};
 use crate::circuit_breaker::*;
 use crate::controller::{Controller, ControllerConfig};
-use crate::liquidation::{LiquidationConfig, LiquidationEngine};
+use crate::error::ZaiSimError;
+use crate::external_market::{ExternalMarket, PriceFeedbackConfig};
+use crate::faults::FaultSchedule;
+use crate::funding::{FundingRate, FundingRateConfig};
+use crate::gas::{GasConfig, GasMarket};
+use crate::governance::{apply_changes, GovernanceAgent, ParameterChange, ParameterSchedule};
+use crate::invariants::{InvariantChecker, InvariantConfig};
+use crate::lending::{LendingAsset, LendingMarket, LendingMarketConfig};
+use crate::liquidation::{
+    LiquidationConfig, LiquidationEngine, LiquidationMode, LiquidationResult, RedemptionResult,
+};
+use crate::lp_attribution::{pool_totals, LpAttribution, LpCohortMetrics};
+use crate::metrics_sink::{MetricsSink, StreamConfig};
+use crate::oracle::{Oracle, OracleConfig, OracleInputs, PriceOracle};
+use crate::order_book::{OrderBook, OrderBookConfig};
+use crate::perp::{PerpConfig, PerpMarket};
+use crate::pool::{Asset, Pool, PoolConfig};
+use crate::protocol_liquidity::{ProtocolLiquidity, ProtocolLiquidityConfig};
+use crate::reorg::{ReorgConfig, ReorgEvent, ReorgInjector};
+use crate::savings::{SavingsConfig, SavingsModule};
+use crate::shielded::{ShieldedPool, ShieldedPoolConfig};
+use crate::snapshot::StateSnapshot;
+use crate::treasury::{Treasury, TreasuryConfig};
+use crate::vault_churn::{VaultChurn, VaultChurnConfig};
+use crate::volume::{VolumeProfile, VolumeScalingConfig};
 
-use rand::rngs::StdRng;
 use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
 
 /// Per-block metrics snapshot.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BlockMetrics {
     pub block: u64,
     pub external_price: f64,
@@ -41,13 +75,712 @@ pub struct BlockMetrics {
     pub cumulative_il_pct: f64,
     // Graduated liquidation metrics
     pub graduated_liquidation_count: u32,
+    /// Graded (partial) halt active this block
+    pub partial_halted: bool,
+    pub cumulative_redeemed_zai: f64,
+    /// Treasury surplus buffer (ZAI)
+    pub treasury_balance: f64,
+    /// Bad debt not covered by the treasury or debt auctions (ZAI)
+    pub uncovered_bad_debt: f64,
+    /// Lending-market ZEC pool utilization (0 when no market is configured)
+    pub lending_zec_utilization: f64,
+    /// Lending-market annual ZEC borrow rate
+    pub lending_zec_borrow_rate: f64,
+    /// Undercollateralized vaults still inside their liquidation grace window
+    pub vaults_in_grace: u32,
+    // Cumulative liquidation-penalty receipts per sink (ZAI)
+    pub penalty_to_keepers: f64,
+    pub penalty_to_lps: f64,
+    pub penalty_to_insurance: f64,
+    pub penalty_to_treasury: f64,
+    pub penalty_burned: f64,
+    /// Treasury insurance fund balance (ZAI)
+    pub insurance_fund_balance: f64,
+    /// Endogenous impact on the external price as a fraction of the scripted
+    /// price (0 unless `price_feedback` is configured)
+    pub external_price_impact: f64,
+    /// Price reported by the configured oracle; `None` without one or while
+    /// it has no usable price
+    #[serde(default)]
+    pub oracle_price: Option<f64>,
+    /// AMM swap fee in effect this block
+    #[serde(default)]
+    pub swap_fee: f64,
+    /// Spot price of each side pool (quote per base), in `config.pools` order
+    #[serde(default)]
+    pub side_pool_prices: Vec<f64>,
+    /// ZEC left on the order book's bids, if configured
+    #[serde(default)]
+    pub order_book_bid_depth: Option<f64>,
+    /// ZEC left on the order book's asks, if configured
+    #[serde(default)]
+    pub order_book_ask_depth: Option<f64>,
+    /// Fraction of AMM LP shares owned by the protocol
+    #[serde(default)]
+    pub pol_pool_share: f64,
+    /// Protocol-owned position plus undeployed buffer, at the AMM price (ZAI)
+    #[serde(default)]
+    pub pol_value_zai: f64,
+    /// Cumulative swap fees earned by protocol-owned liquidity (ZAI)
+    #[serde(default)]
+    pub pol_fees_earned_zai: f64,
+    /// Pool-wide LP fee income over the trailing day, annualized, as a
+    /// fraction of LP value
+    #[serde(default)]
+    pub lp_fee_apr: f64,
+    /// Cumulative liquidation penalties paid to LPs, attributed by share (ZAI)
+    #[serde(default)]
+    pub lp_penalties_zai: f64,
+    /// Cumulative impermanent loss of all LPs against holding (ZAI, ≤ 0)
+    #[serde(default)]
+    pub lp_il_zai: f64,
+    /// LP fees + penalties + impermanent loss (ZAI)
+    #[serde(default)]
+    pub lp_net_return_zai: f64,
+    /// The same attribution per LP cohort
+    #[serde(default)]
+    pub lp_cohorts: Vec<LpCohortMetrics>,
+    /// Annual ZAI savings rate (0 without a savings module)
+    #[serde(default)]
+    pub savings_rate: f64,
+    /// ZAI locked in savings, interest included
+    #[serde(default)]
+    pub savings_deposits_zai: f64,
+    /// Cumulative savings interest paid from the treasury surplus
+    #[serde(default)]
+    pub savings_interest_paid_zai: f64,
+    /// Per-block funding rate on ZAI balances (negative while charging)
+    #[serde(default)]
+    pub funding_rate: f64,
+    /// Cumulative demurrage charged on ZAI balances
+    #[serde(default)]
+    pub funding_charged_zai: f64,
+    /// Gas per action this block (ZEC; 0 without a gas model)
+    #[serde(default)]
+    pub gas_fee_zec: f64,
+    /// Cumulative gas paid by agents and keepers (ZEC)
+    #[serde(default)]
+    pub gas_paid_zec: f64,
+    /// Interval of this block (seconds; 0 without a block space model)
+    #[serde(default)]
+    pub block_time_secs: f64,
+    /// Agents crowded out of this block, queued for the next
+    #[serde(default)]
+    pub deferred_agents: u32,
+    /// Reorgs injected so far
+    #[serde(default)]
+    pub reorgs: u32,
+    /// Scheduled chain halt in effect
+    #[serde(default)]
+    pub network_halted: bool,
+    /// AMM closed to swaps by a scheduled pause or halt
+    #[serde(default)]
+    pub amm_paused: bool,
+    /// Scheduled oracle outage in effect
+    #[serde(default)]
+    pub oracle_outage: bool,
+    /// Agent ZEC in the shielded pool, including unshields in flight
+    #[serde(default)]
+    pub shielded_zec: f64,
+    /// Agent ZAI in the shielded pool, including unshields in flight
+    #[serde(default)]
+    pub shielded_zai: f64,
+    /// Value queued on or crossing the bridge (ZAI)
+    #[serde(default)]
+    pub bridge_outstanding_zai: f64,
+    /// Demand agents in the roster after this block's adoption step
+    #[serde(default)]
+    pub demand_users: usize,
+    /// Total debt as a fraction of the debt ceiling
+    #[serde(default)]
+    pub debt_ceiling_utilization: f64,
+    /// CDP holders waiting for room under the ceiling
+    #[serde(default)]
+    pub vaults_waiting: usize,
+    /// Redemption price held at the edge of the drift limiter's band
+    #[serde(default)]
+    pub redemption_capped: bool,
+    /// Vaults partially unwound by the auto-deleveraging breaker
+    #[serde(default)]
+    pub deleveraged_vaults: usize,
+    /// Vaults their owners self-liquidated this block
+    #[serde(default)]
+    pub self_liquidations: usize,
+    /// Vaults opened by arriving holders this block
+    #[serde(default)]
+    pub vault_arrivals: usize,
+    /// Holder vaults closed voluntarily this block
+    #[serde(default)]
+    pub vault_closures: usize,
+    /// Vaults with debt under the floor at the end of the block
+    #[serde(default)]
+    pub dust_vaults: usize,
+    /// Perp funding rate per interval this block (0 without a perp)
+    #[serde(default)]
+    pub perp_funding_rate: f64,
+    /// Basis arbitrageurs' net perp position (ZEC; negative = short)
+    #[serde(default)]
+    pub basis_arb_perp_zec: f64,
+}
+
+/// A run's per-block metrics, stored as one column per `BlockMetrics` field
+/// so each series the summary and reports read is contiguous. Breaker
+/// actions go to `breaker_events` instead of a column, as most blocks have
+/// none.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MetricsStore {
+    pub block: Vec<u64>,
+    pub external_price: Vec<f64>,
+    pub amm_spot_price: Vec<f64>,
+    pub twap_price: Vec<f64>,
+    pub redemption_price: Vec<f64>,
+    pub redemption_rate: Vec<f64>,
+    pub total_debt: Vec<f64>,
+    pub amm_reserve_zec: Vec<f64>,
+    pub amm_reserve_zai: Vec<f64>,
+    pub vault_count: Vec<u64>,
+    pub liquidation_count: Vec<u32>,
+    pub bad_debt: Vec<f64>,
+    pub debt_ceiling: Vec<f64>,
+    pub minting_paused: Vec<bool>,
+    pub halted: Vec<bool>,
+    pub total_collateral: Vec<f64>,
+    pub total_lp_shares: Vec<f64>,
+    pub arber_zai_total: Vec<f64>,
+    pub zombie_vault_count: Vec<u32>,
+    pub max_zombie_gap: Vec<f64>,
+    pub mean_collateral_ratio_twap: Vec<f64>,
+    pub mean_collateral_ratio_ext: Vec<f64>,
+    pub arber_zec_total: Vec<f64>,
+    pub cumulative_fees_zai: Vec<f64>,
+    pub cumulative_il_pct: Vec<f64>,
+    pub graduated_liquidation_count: Vec<u32>,
+    pub partial_halted: Vec<bool>,
+    pub cumulative_redeemed_zai: Vec<f64>,
+    pub treasury_balance: Vec<f64>,
+    pub uncovered_bad_debt: Vec<f64>,
+    pub lending_zec_utilization: Vec<f64>,
+    pub lending_zec_borrow_rate: Vec<f64>,
+    pub vaults_in_grace: Vec<u32>,
+    pub penalty_to_keepers: Vec<f64>,
+    pub penalty_to_lps: Vec<f64>,
+    pub penalty_to_insurance: Vec<f64>,
+    pub penalty_to_treasury: Vec<f64>,
+    pub penalty_burned: Vec<f64>,
+    pub insurance_fund_balance: Vec<f64>,
+    pub external_price_impact: Vec<f64>,
+    pub oracle_price: Vec<Option<f64>>,
+    pub swap_fee: Vec<f64>,
+    pub side_pool_prices: Vec<Vec<f64>>,
+    pub order_book_bid_depth: Vec<Option<f64>>,
+    pub order_book_ask_depth: Vec<Option<f64>>,
+    pub pol_pool_share: Vec<f64>,
+    pub pol_value_zai: Vec<f64>,
+    pub pol_fees_earned_zai: Vec<f64>,
+    pub lp_fee_apr: Vec<f64>,
+    pub lp_penalties_zai: Vec<f64>,
+    pub lp_il_zai: Vec<f64>,
+    pub lp_net_return_zai: Vec<f64>,
+    pub lp_cohorts: Vec<Vec<LpCohortMetrics>>,
+    pub savings_rate: Vec<f64>,
+    pub savings_deposits_zai: Vec<f64>,
+    pub savings_interest_paid_zai: Vec<f64>,
+    pub funding_rate: Vec<f64>,
+    pub funding_charged_zai: Vec<f64>,
+    pub gas_fee_zec: Vec<f64>,
+    pub gas_paid_zec: Vec<f64>,
+    pub block_time_secs: Vec<f64>,
+    pub deferred_agents: Vec<u32>,
+    pub reorgs: Vec<u32>,
+    pub network_halted: Vec<bool>,
+    pub amm_paused: Vec<bool>,
+    pub oracle_outage: Vec<bool>,
+    pub shielded_zec: Vec<f64>,
+    pub shielded_zai: Vec<f64>,
+    pub bridge_outstanding_zai: Vec<f64>,
+    pub demand_users: Vec<usize>,
+    pub debt_ceiling_utilization: Vec<f64>,
+    pub vaults_waiting: Vec<usize>,
+    pub redemption_capped: Vec<bool>,
+    pub deleveraged_vaults: Vec<usize>,
+    pub self_liquidations: Vec<usize>,
+    pub vault_arrivals: Vec<usize>,
+    pub vault_closures: Vec<usize>,
+    pub dust_vaults: Vec<usize>,
+    pub perp_funding_rate: Vec<f64>,
+    pub basis_arb_perp_zec: Vec<f64>,
+    /// Breaker actions in the order they fired, with the block of each
+    pub breaker_events: Vec<(u64, BreakerAction)>,
+}
+
+/// Resizing every column of a `MetricsStore` alike, whatever it holds.
+trait Column {
+    fn reserve_rows(&mut self, additional: usize);
+    fn truncate_rows(&mut self, len: usize);
+    fn remove_first_rows(&mut self, n: usize);
+}
+
+impl<T> Column for Vec<T> {
+    fn reserve_rows(&mut self, additional: usize) {
+        self.reserve(additional);
+    }
+
+    fn truncate_rows(&mut self, len: usize) {
+        self.truncate(len);
+    }
+
+    fn remove_first_rows(&mut self, n: usize) {
+        self.drain(..n);
+    }
+}
+
+impl MetricsStore {
+    /// An empty store with room for `blocks` blocks.
+    pub fn with_capacity(blocks: usize) -> Self {
+        let mut store = Self::default();
+        store.reserve(blocks);
+        store
+    }
+
+    /// Every column but `breaker_events`, which is sized by actions fired.
+    fn columns_mut(&mut self) -> [&mut dyn Column; 80] {
+        [
+            &mut self.block,
+            &mut self.external_price,
+            &mut self.amm_spot_price,
+            &mut self.twap_price,
+            &mut self.redemption_price,
+            &mut self.redemption_rate,
+            &mut self.total_debt,
+            &mut self.amm_reserve_zec,
+            &mut self.amm_reserve_zai,
+            &mut self.vault_count,
+            &mut self.liquidation_count,
+            &mut self.bad_debt,
+            &mut self.debt_ceiling,
+            &mut self.minting_paused,
+            &mut self.halted,
+            &mut self.total_collateral,
+            &mut self.total_lp_shares,
+            &mut self.arber_zai_total,
+            &mut self.zombie_vault_count,
+            &mut self.max_zombie_gap,
+            &mut self.mean_collateral_ratio_twap,
+            &mut self.mean_collateral_ratio_ext,
+            &mut self.arber_zec_total,
+            &mut self.cumulative_fees_zai,
+            &mut self.cumulative_il_pct,
+            &mut self.graduated_liquidation_count,
+            &mut self.partial_halted,
+            &mut self.cumulative_redeemed_zai,
+            &mut self.treasury_balance,
+            &mut self.uncovered_bad_debt,
+            &mut self.lending_zec_utilization,
+            &mut self.lending_zec_borrow_rate,
+            &mut self.vaults_in_grace,
+            &mut self.penalty_to_keepers,
+            &mut self.penalty_to_lps,
+            &mut self.penalty_to_insurance,
+            &mut self.penalty_to_treasury,
+            &mut self.penalty_burned,
+            &mut self.insurance_fund_balance,
+            &mut self.external_price_impact,
+            &mut self.oracle_price,
+            &mut self.swap_fee,
+            &mut self.side_pool_prices,
+            &mut self.order_book_bid_depth,
+            &mut self.order_book_ask_depth,
+            &mut self.pol_pool_share,
+            &mut self.pol_value_zai,
+            &mut self.pol_fees_earned_zai,
+            &mut self.lp_fee_apr,
+            &mut self.lp_penalties_zai,
+            &mut self.lp_il_zai,
+            &mut self.lp_net_return_zai,
+            &mut self.lp_cohorts,
+            &mut self.savings_rate,
+            &mut self.savings_deposits_zai,
+            &mut self.savings_interest_paid_zai,
+            &mut self.funding_rate,
+            &mut self.funding_charged_zai,
+            &mut self.gas_fee_zec,
+            &mut self.gas_paid_zec,
+            &mut self.block_time_secs,
+            &mut self.deferred_agents,
+            &mut self.reorgs,
+            &mut self.network_halted,
+            &mut self.amm_paused,
+            &mut self.oracle_outage,
+            &mut self.shielded_zec,
+            &mut self.shielded_zai,
+            &mut self.bridge_outstanding_zai,
+            &mut self.demand_users,
+            &mut self.debt_ceiling_utilization,
+            &mut self.vaults_waiting,
+            &mut self.redemption_capped,
+            &mut self.deleveraged_vaults,
+            &mut self.self_liquidations,
+            &mut self.vault_arrivals,
+            &mut self.vault_closures,
+            &mut self.dust_vaults,
+            &mut self.perp_funding_rate,
+            &mut self.basis_arb_perp_zec,
+        ]
+    }
+
+    pub fn reserve(&mut self, additional: usize) {
+        for column in self.columns_mut() {
+            column.reserve_rows(additional);
+        }
+    }
+
+    /// Append one block, moving its breaker actions to `breaker_events`.
+    pub fn push(&mut self, m: BlockMetrics) {
+        self.block.push(m.block);
+        self.external_price.push(m.external_price);
+        self.amm_spot_price.push(m.amm_spot_price);
+        self.twap_price.push(m.twap_price);
+        self.redemption_price.push(m.redemption_price);
+        self.redemption_rate.push(m.redemption_rate);
+        self.total_debt.push(m.total_debt);
+        self.amm_reserve_zec.push(m.amm_reserve_zec);
+        self.amm_reserve_zai.push(m.amm_reserve_zai);
+        self.vault_count.push(m.vault_count);
+        self.liquidation_count.push(m.liquidation_count);
+        self.bad_debt.push(m.bad_debt);
+        self.breaker_events
+            .extend(m.breaker_actions.into_iter().map(|a| (m.block, a)));
+        self.debt_ceiling.push(m.debt_ceiling);
+        self.minting_paused.push(m.minting_paused);
+        self.halted.push(m.halted);
+        self.total_collateral.push(m.total_collateral);
+        self.total_lp_shares.push(m.total_lp_shares);
+        self.arber_zai_total.push(m.arber_zai_total);
+        self.zombie_vault_count.push(m.zombie_vault_count);
+        self.max_zombie_gap.push(m.max_zombie_gap);
+        self.mean_collateral_ratio_twap
+            .push(m.mean_collateral_ratio_twap);
+        self.mean_collateral_ratio_ext
+            .push(m.mean_collateral_ratio_ext);
+        self.arber_zec_total.push(m.arber_zec_total);
+        self.cumulative_fees_zai.push(m.cumulative_fees_zai);
+        self.cumulative_il_pct.push(m.cumulative_il_pct);
+        self.graduated_liquidation_count
+            .push(m.graduated_liquidation_count);
+        self.partial_halted.push(m.partial_halted);
+        self.cumulative_redeemed_zai.push(m.cumulative_redeemed_zai);
+        self.treasury_balance.push(m.treasury_balance);
+        self.uncovered_bad_debt.push(m.uncovered_bad_debt);
+        self.lending_zec_utilization.push(m.lending_zec_utilization);
+        self.lending_zec_borrow_rate.push(m.lending_zec_borrow_rate);
+        self.vaults_in_grace.push(m.vaults_in_grace);
+        self.penalty_to_keepers.push(m.penalty_to_keepers);
+        self.penalty_to_lps.push(m.penalty_to_lps);
+        self.penalty_to_insurance.push(m.penalty_to_insurance);
+        self.penalty_to_treasury.push(m.penalty_to_treasury);
+        self.penalty_burned.push(m.penalty_burned);
+        self.insurance_fund_balance.push(m.insurance_fund_balance);
+        self.external_price_impact.push(m.external_price_impact);
+        self.oracle_price.push(m.oracle_price);
+        self.swap_fee.push(m.swap_fee);
+        self.side_pool_prices.push(m.side_pool_prices);
+        self.order_book_bid_depth.push(m.order_book_bid_depth);
+        self.order_book_ask_depth.push(m.order_book_ask_depth);
+        self.pol_pool_share.push(m.pol_pool_share);
+        self.pol_value_zai.push(m.pol_value_zai);
+        self.pol_fees_earned_zai.push(m.pol_fees_earned_zai);
+        self.lp_fee_apr.push(m.lp_fee_apr);
+        self.lp_penalties_zai.push(m.lp_penalties_zai);
+        self.lp_il_zai.push(m.lp_il_zai);
+        self.lp_net_return_zai.push(m.lp_net_return_zai);
+        self.lp_cohorts.push(m.lp_cohorts);
+        self.savings_rate.push(m.savings_rate);
+        self.savings_deposits_zai.push(m.savings_deposits_zai);
+        self.savings_interest_paid_zai
+            .push(m.savings_interest_paid_zai);
+        self.funding_rate.push(m.funding_rate);
+        self.funding_charged_zai.push(m.funding_charged_zai);
+        self.gas_fee_zec.push(m.gas_fee_zec);
+        self.gas_paid_zec.push(m.gas_paid_zec);
+        self.block_time_secs.push(m.block_time_secs);
+        self.deferred_agents.push(m.deferred_agents);
+        self.reorgs.push(m.reorgs);
+        self.network_halted.push(m.network_halted);
+        self.amm_paused.push(m.amm_paused);
+        self.oracle_outage.push(m.oracle_outage);
+        self.shielded_zec.push(m.shielded_zec);
+        self.shielded_zai.push(m.shielded_zai);
+        self.bridge_outstanding_zai.push(m.bridge_outstanding_zai);
+        self.demand_users.push(m.demand_users);
+        self.debt_ceiling_utilization
+            .push(m.debt_ceiling_utilization);
+        self.vaults_waiting.push(m.vaults_waiting);
+        self.redemption_capped.push(m.redemption_capped);
+        self.deleveraged_vaults.push(m.deleveraged_vaults);
+        self.self_liquidations.push(m.self_liquidations);
+        self.vault_arrivals.push(m.vault_arrivals);
+        self.vault_closures.push(m.vault_closures);
+        self.dust_vaults.push(m.dust_vaults);
+        self.perp_funding_rate.push(m.perp_funding_rate);
+        self.basis_arb_perp_zec.push(m.basis_arb_perp_zec);
+    }
+
+    pub fn len(&self) -> usize {
+        self.block.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.block.is_empty()
+    }
+
+    /// The `i`th block's metrics, or `None` past the end.
+    pub fn get(&self, i: usize) -> Option<BlockMetrics> {
+        (i < self.len()).then(|| self.row(i))
+    }
+
+    /// The `i`th block's metrics.
+    ///
+    /// # Panics
+    ///
+    /// If `i` is out of bounds.
+    pub fn row(&self, i: usize) -> BlockMetrics {
+        BlockMetrics {
+            block: self.block[i],
+            external_price: self.external_price[i],
+            amm_spot_price: self.amm_spot_price[i],
+            twap_price: self.twap_price[i],
+            redemption_price: self.redemption_price[i],
+            redemption_rate: self.redemption_rate[i],
+            total_debt: self.total_debt[i],
+            amm_reserve_zec: self.amm_reserve_zec[i],
+            amm_reserve_zai: self.amm_reserve_zai[i],
+            vault_count: self.vault_count[i],
+            liquidation_count: self.liquidation_count[i],
+            bad_debt: self.bad_debt[i],
+            breaker_actions: self
+                .breaker_events_at(self.block[i])
+                .iter()
+                .map(|(_, a)| a.clone())
+                .collect(),
+            debt_ceiling: self.debt_ceiling[i],
+            minting_paused: self.minting_paused[i],
+            halted: self.halted[i],
+            total_collateral: self.total_collateral[i],
+            total_lp_shares: self.total_lp_shares[i],
+            arber_zai_total: self.arber_zai_total[i],
+            zombie_vault_count: self.zombie_vault_count[i],
+            max_zombie_gap: self.max_zombie_gap[i],
+            mean_collateral_ratio_twap: self.mean_collateral_ratio_twap[i],
+            mean_collateral_ratio_ext: self.mean_collateral_ratio_ext[i],
+            arber_zec_total: self.arber_zec_total[i],
+            cumulative_fees_zai: self.cumulative_fees_zai[i],
+            cumulative_il_pct: self.cumulative_il_pct[i],
+            graduated_liquidation_count: self.graduated_liquidation_count[i],
+            partial_halted: self.partial_halted[i],
+            cumulative_redeemed_zai: self.cumulative_redeemed_zai[i],
+            treasury_balance: self.treasury_balance[i],
+            uncovered_bad_debt: self.uncovered_bad_debt[i],
+            lending_zec_utilization: self.lending_zec_utilization[i],
+            lending_zec_borrow_rate: self.lending_zec_borrow_rate[i],
+            vaults_in_grace: self.vaults_in_grace[i],
+            penalty_to_keepers: self.penalty_to_keepers[i],
+            penalty_to_lps: self.penalty_to_lps[i],
+            penalty_to_insurance: self.penalty_to_insurance[i],
+            penalty_to_treasury: self.penalty_to_treasury[i],
+            penalty_burned: self.penalty_burned[i],
+            insurance_fund_balance: self.insurance_fund_balance[i],
+            external_price_impact: self.external_price_impact[i],
+            oracle_price: self.oracle_price[i],
+            swap_fee: self.swap_fee[i],
+            side_pool_prices: self.side_pool_prices[i].clone(),
+            order_book_bid_depth: self.order_book_bid_depth[i],
+            order_book_ask_depth: self.order_book_ask_depth[i],
+            pol_pool_share: self.pol_pool_share[i],
+            pol_value_zai: self.pol_value_zai[i],
+            pol_fees_earned_zai: self.pol_fees_earned_zai[i],
+            lp_fee_apr: self.lp_fee_apr[i],
+            lp_penalties_zai: self.lp_penalties_zai[i],
+            lp_il_zai: self.lp_il_zai[i],
+            lp_net_return_zai: self.lp_net_return_zai[i],
+            lp_cohorts: self.lp_cohorts[i].clone(),
+            savings_rate: self.savings_rate[i],
+            savings_deposits_zai: self.savings_deposits_zai[i],
+            savings_interest_paid_zai: self.savings_interest_paid_zai[i],
+            funding_rate: self.funding_rate[i],
+            funding_charged_zai: self.funding_charged_zai[i],
+            gas_fee_zec: self.gas_fee_zec[i],
+            gas_paid_zec: self.gas_paid_zec[i],
+            block_time_secs: self.block_time_secs[i],
+            deferred_agents: self.deferred_agents[i],
+            reorgs: self.reorgs[i],
+            network_halted: self.network_halted[i],
+            amm_paused: self.amm_paused[i],
+            oracle_outage: self.oracle_outage[i],
+            shielded_zec: self.shielded_zec[i],
+            shielded_zai: self.shielded_zai[i],
+            bridge_outstanding_zai: self.bridge_outstanding_zai[i],
+            demand_users: self.demand_users[i],
+            debt_ceiling_utilization: self.debt_ceiling_utilization[i],
+            vaults_waiting: self.vaults_waiting[i],
+            redemption_capped: self.redemption_capped[i],
+            deleveraged_vaults: self.deleveraged_vaults[i],
+            self_liquidations: self.self_liquidations[i],
+            vault_arrivals: self.vault_arrivals[i],
+            vault_closures: self.vault_closures[i],
+            dust_vaults: self.dust_vaults[i],
+            perp_funding_rate: self.perp_funding_rate[i],
+            basis_arb_perp_zec: self.basis_arb_perp_zec[i],
+        }
+    }
+
+    pub fn first(&self) -> Option<BlockMetrics> {
+        self.get(0)
+    }
+
+    pub fn last(&self) -> Option<BlockMetrics> {
+        self.len().checked_sub(1).map(|i| self.row(i))
+    }
+
+    /// Each block's metrics, in order.
+    pub fn iter(&self) -> Rows<'_> {
+        self.rows(..)
+    }
+
+    /// The metrics of the blocks at `range`, in order.
+    ///
+    /// # Panics
+    ///
+    /// If `range` is out of bounds.
+    pub fn rows(&self, range: impl RangeBounds<usize>) -> Rows<'_> {
+        let start = match range.start_bound() {
+            Bound::Included(&i) => i,
+            Bound::Excluded(&i) => i + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&i) => i + 1,
+            Bound::Excluded(&i) => i,
+            Bound::Unbounded => self.len(),
+        };
+        assert!(
+            start <= end && end <= self.len(),
+            "rows {}..{} out of range for {} blocks",
+            start,
+            end,
+            self.len()
+        );
+        Rows {
+            store: self,
+            range: start..end,
+        }
+    }
+
+    /// Breaker actions that fired in `block`.
+    pub fn breaker_events_at(&self, block: u64) -> &[(u64, BreakerAction)] {
+        let start = self.breaker_events.partition_point(|(b, _)| *b < block);
+        let end = self.breaker_events.partition_point(|(b, _)| *b <= block);
+        &self.breaker_events[start..end]
+    }
+
+    /// Keep the first `len` blocks.
+    pub fn truncate(&mut self, len: usize) {
+        if let Some(&cut) = self.block.get(len) {
+            let kept = self.breaker_events.partition_point(|(b, _)| *b < cut);
+            self.breaker_events.truncate(kept);
+        }
+        for column in self.columns_mut() {
+            column.truncate_rows(len);
+        }
+    }
+
+    /// Drop the first `n` blocks.
+    pub fn remove_first(&mut self, n: usize) {
+        let n = n.min(self.len());
+        let dropped = match self.block.get(n) {
+            Some(&kept) => self.breaker_events.partition_point(|(b, _)| *b < kept),
+            None => self.breaker_events.len(),
+        };
+        self.breaker_events.drain(..dropped);
+        for column in self.columns_mut() {
+            column.remove_first_rows(n);
+        }
+    }
+
+    /// System collateral ratio per block, valuing collateral at `prices`;
+    /// 0 for blocks without debt.
+    pub fn collateral_ratio(&self, prices: &[f64]) -> Vec<f64> {
+        self.total_collateral
+            .iter()
+            .zip(&self.total_debt)
+            .zip(prices)
+            .map(|((c, d), p)| if *d > 0.0 { c * p / d } else { 0.0 })
+            .collect()
+    }
+}
+
+impl FromIterator<BlockMetrics> for MetricsStore {
+    fn from_iter<I: IntoIterator<Item = BlockMetrics>>(iter: I) -> Self {
+        let mut store = Self::default();
+        store.extend(iter);
+        store
+    }
+}
+
+impl Extend<BlockMetrics> for MetricsStore {
+    fn extend<I: IntoIterator<Item = BlockMetrics>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for m in iter {
+            self.push(m);
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a MetricsStore {
+    type Item = BlockMetrics;
+    type IntoIter = Rows<'a>;
+
+    fn into_iter(self) -> Rows<'a> {
+        self.iter()
+    }
+}
+
+/// Iterator over the blocks of a `MetricsStore`, each assembled into a
+/// `BlockMetrics`.
+pub struct Rows<'a> {
+    store: &'a MetricsStore,
+    range: std::ops::Range<usize>,
+}
+
+impl Iterator for Rows<'_> {
+    type Item = BlockMetrics;
+
+    fn next(&mut self) -> Option<BlockMetrics> {
+        self.range.next().map(|i| self.store.row(i))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.range.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for Rows<'_> {
+    fn next_back(&mut self) -> Option<BlockMetrics> {
+        self.range.next_back().map(|i| self.store.row(i))
+    }
 }
 
+impl ExactSizeIterator for Rows<'_> {}
+
 /// Configuration for a scenario run.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ScenarioConfig {
     pub amm_initial_zec: f64,
     pub amm_initial_zai: f64,
+    /// Initial AMM price (ZAI per ZEC); overrides the reserve ratio when set
+    pub amm_initial_price: Option<f64>,
+    /// Initial ZAI-side AMM reserve; overrides `amm_initial_zai` when set
+    pub amm_initial_depth: Option<f64>,
     pub amm_swap_fee: f64,
     pub cdp_config: CdpConfig,
     pub controller_config: ControllerConfig,
@@ -75,6 +808,111 @@ pub struct ScenarioConfig {
     pub use_external_oracle_for_liquidation: bool,
     /// Graduated (partial) liquidation: deleverage warning-zone vaults gradually
     pub use_graduated_liquidation: bool,
+    /// Response when the cascade breaker fires: full halt or graded restrictions
+    pub halt_mode: HaltMode,
+    /// Surplus buffer and debt-auction parameters
+    pub treasury_config: TreasuryConfig,
+    /// External ZEC/ZAI lending market; `None` disables the side venue
+    pub lending_market: Option<LendingMarketConfig>,
+    /// Record a `StateSnapshot` every N blocks for regression diffing (0 = off)
+    pub snapshot_interval: u64,
+    /// Write a full-state checkpoint to `checkpoint_path` every N blocks (0 = off)
+    pub checkpoint_interval: u64,
+    pub checkpoint_path: Option<PathBuf>,
+    /// Record per-agent balances, PnL and actions every block
+    pub record_agent_metrics: bool,
+    /// Let the simulation's own external ZEC flows move the external price;
+    /// `None` keeps the price series purely exogenous
+    pub price_feedback: Option<PriceFeedbackConfig>,
+    /// Governance parameter changes applied at the start of their block
+    #[serde(default)]
+    pub parameter_schedule: ParameterSchedule,
+    /// Oracle feeding liquidation and the TWAP breaker. Overrides
+    /// `use_external_oracle_for_liquidation` and `use_amm_liquidation` for
+    /// the main liquidation pass; `None` keeps their price selection.
+    #[serde(default)]
+    pub oracle: Option<OracleConfig>,
+    /// Raise the AMM fee above `amm_swap_fee` in volatile or one-sided
+    /// markets; `None` keeps it fixed
+    #[serde(default)]
+    pub dynamic_fee: Option<DynamicFeeConfig>,
+    /// Pools beside the ZEC/ZAI AMM, e.g. ZEC/USD and a ZAI/USD
+    /// StableSwap venue; needs-driven trades route across them
+    #[serde(default)]
+    pub pools: Vec<PoolConfig>,
+    /// ZEC/ZAI limit order book beside the AMM, quoted around the external
+    /// price; `None` for AMM-only markets
+    #[serde(default)]
+    pub order_book: Option<OrderBookConfig>,
+    /// Protocol-owned AMM liquidity funded from treasury income; `None`
+    /// leaves the pool to private LPs
+    #[serde(default)]
+    pub protocol_liquidity: Option<ProtocolLiquidityConfig>,
+    /// ZAI savings rate paid from the treasury surplus; `None` for no
+    /// savings module
+    #[serde(default)]
+    pub savings: Option<SavingsConfig>,
+    /// Charge the controller's rate on ZAI balances while ZAI trades above
+    /// par; `None` leaves it to the redemption price
+    #[serde(default)]
+    pub funding_rate: Option<FundingRateConfig>,
+    /// Noise and momentum traders spawned from the run seed; `None` adds none
+    #[serde(default)]
+    pub noise_traders: Option<NoiseTraderPopulation>,
+    /// ZEC perp whose funding basis arbitrageurs collect; `None` leaves
+    /// them idle
+    #[serde(default)]
+    pub perp: Option<PerpConfig>,
+    /// Per-action transaction fee on agent trades, vault operations and
+    /// liquidations; `None` makes actions free
+    #[serde(default)]
+    pub gas: Option<GasConfig>,
+    /// Block-time jitter and per-block transaction capacity; `None` keeps
+    /// blocks uniform and unbounded
+    #[serde(default)]
+    pub block_space: Option<BlockSpaceConfig>,
+    /// Chain reorg injection; `None` keeps every block final
+    #[serde(default)]
+    pub reorg: Option<ReorgConfig>,
+    /// Scheduled halts, oracle outages and AMM pauses
+    #[serde(default)]
+    pub faults: FaultSchedule,
+    /// Part of agent funds held shielded and unshielded with a delay;
+    /// `None` keeps all funds transparent
+    #[serde(default)]
+    pub shielded_pool: Option<ShieldedPoolConfig>,
+    /// Latency and capacity limits on arbers' external capital; `None`
+    /// delivers it instantly
+    #[serde(default)]
+    pub bridge: Option<BridgeConfig>,
+    /// Demand agents join and leave along an adoption curve tied to the
+    /// peg; `None` keeps the roster fixed
+    #[serde(default)]
+    pub adoption: Option<AdoptionConfig>,
+    /// CDP holders arrive and close their vaults at random, with rates tied
+    /// to the price trend and stability fee; `None` keeps the vault book
+    /// fixed
+    #[serde(default)]
+    pub vault_churn: Option<VaultChurnConfig>,
+    /// Grow and shrink the debt ceiling from AMM depth, collateralization
+    /// and bad debt; `None` leaves it to the circuit breaker
+    #[serde(default)]
+    pub ceiling_policy: Option<CeilingPolicyConfig>,
+    /// Cap on redemption price drift per window; `None` leaves the
+    /// controller unbounded
+    #[serde(default)]
+    pub redemption_drift_limiter: Option<RedemptionDriftConfig>,
+    /// Cap on net new debt per window; `None` for no limit
+    #[serde(default)]
+    pub mint_rate_limiter: Option<MintRateLimiterConfig>,
+    /// Proactive deleveraging of the riskiest vaults while system
+    /// collateralization is thin; `None` to rely on liquidation alone
+    #[serde(default)]
+    pub auto_deleverage: Option<AutoDeleverageConfig>,
+    /// Check accounting invariants after every block; `None` skips the
+    /// checks
+    #[serde(default)]
+    pub invariants: Option<InvariantConfig>,
 }
 
 impl Default for ScenarioConfig {
@@ -82,6 +920,8 @@ impl Default for ScenarioConfig {
         ScenarioConfig {
             amm_initial_zec: 10000.0,
             amm_initial_zai: 500000.0,
+            amm_initial_price: None,
+            amm_initial_depth: None,
             amm_swap_fee: 0.003,
             cdp_config: CdpConfig::default(),
             controller_config: ControllerConfig::default_pi(),
@@ -101,18 +941,122 @@ impl Default for ScenarioConfig {
             stability_fee_to_lps: false,
             use_external_oracle_for_liquidation: false,
             use_graduated_liquidation: false,
+            halt_mode: HaltMode::Full,
+            treasury_config: TreasuryConfig::default(),
+            lending_market: None,
+            snapshot_interval: 0,
+            checkpoint_interval: 0,
+            checkpoint_path: None,
+            record_agent_metrics: false,
+            price_feedback: None,
+            parameter_schedule: ParameterSchedule::default(),
+            oracle: None,
+            dynamic_fee: None,
+            pools: Vec::new(),
+            order_book: None,
+            protocol_liquidity: None,
+            savings: None,
+            funding_rate: None,
+            perp: None,
+            noise_traders: None,
+            gas: None,
+            block_space: None,
+            reorg: None,
+            faults: FaultSchedule::default(),
+            shielded_pool: None,
+            bridge: None,
+            adoption: None,
+            vault_churn: None,
+            ceiling_policy: None,
+            redemption_drift_limiter: None,
+            mint_rate_limiter: None,
+            auto_deleverage: None,
+            invariants: None,
+        }
+    }
+}
+
+impl ScenarioConfig {
+    /// Set the initial AMM price and ZAI-side depth; reserves are derived
+    /// from them (e.g. price 50, depth 5M = 100K ZEC / 5M ZAI).
+    pub fn with_amm(mut self, price: f64, depth: f64) -> Self {
+        self.amm_initial_price = Some(price);
+        self.amm_initial_depth = Some(depth);
+        self
+    }
+
+    /// The oracle config with the fault schedule's outages, and stale
+    /// reports while the chain is halted, added to its failures.
+    fn oracle_config(&self) -> Option<OracleConfig> {
+        self.oracle.clone().map(|mut oracle| {
+            oracle.failures.extend(self.faults.oracle_failures());
+            oracle
+        })
+    }
+
+    /// Initial AMM price in ZAI per ZEC.
+    pub fn initial_amm_price(&self) -> f64 {
+        self.amm_initial_price
+            .unwrap_or(self.amm_initial_zai / self.amm_initial_zec)
+    }
+
+    /// Initial AMM reserves `(zec, zai)`, derived from `amm_initial_price`
+    /// and `amm_initial_depth` where set.
+    pub fn amm_reserves(&self) -> (f64, f64) {
+        let zai = self.amm_initial_depth.unwrap_or(self.amm_initial_zai);
+        (zai / self.initial_amm_price(), zai)
+    }
+
+    /// The default config with the fields in `overrides` (a JSON object of
+    /// `ScenarioConfig` fields, nested objects merged field by field)
+    /// replaced. For the Python and WASM bindings.
+    pub fn with_overrides(overrides: serde_json::Value) -> Result<Self, ZaiSimError> {
+        fn merge(base: &mut serde_json::Value, overrides: serde_json::Value) {
+            match (base, overrides) {
+                (serde_json::Value::Object(base), serde_json::Value::Object(overrides)) => {
+                    for (key, value) in overrides {
+                        match base.get_mut(&key) {
+                            Some(slot) => merge(slot, value),
+                            None => {
+                                base.insert(key, value);
+                            }
+                        }
+                    }
+                }
+                (base, overrides) => *base = overrides,
+            }
+        }
+
+        let mut config = serde_json::to_value(Self::default())?;
+        match overrides {
+            serde_json::Value::Null => {}
+            serde_json::Value::Object(_) => merge(&mut config, overrides),
+            _ => {
+                return Err(ZaiSimError::Config(
+                    "config overrides must be an object".into(),
+                ))
+            }
         }
+        Ok(serde_json::from_value(config)?)
     }
 }
 
 /// The full simulation state.
+#[derive(Serialize, Deserialize)]
 pub struct Scenario {
     pub amm: Amm,
     pub registry: VaultRegistry,
     pub controller: Controller,
     pub liquidation_engine: LiquidationEngine,
     pub breakers: CircuitBreakerEngine,
-    pub metrics: Vec<BlockMetrics>,
+    pub treasury: Treasury,
+    pub lending_market: Option<LendingMarket>,
+    /// External market absorbing simulated ZEC flows, when `price_feedback` is set
+    pub external_market: Option<ExternalMarket>,
+    pub metrics: MetricsStore,
+    pub snapshots: Vec<StateSnapshot>,
+    /// Per-agent time series, when `record_agent_metrics` is set
+    pub agent_metrics: Option<AgentMetricsCollector>,
 
     // Agents
     pub arbers: Vec<Arbitrageur>,
@@ -122,11 +1066,157 @@ pub struct Scenario {
     pub lp_agents: Vec<LpAgent>,
     pub il_aware_lps: Vec<IlAwareLpAgent>,
     pub attackers: Vec<Attacker>,
+    pub redeemers: Vec<RedeemerAgent>,
+    pub basis_traders: Vec<BasisTrader>,
+    #[serde(default)]
+    pub governance_agents: Vec<GovernanceAgent>,
+    #[serde(default)]
+    pub savers: Vec<SaverAgent>,
+    #[serde(default)]
+    pub noise_traders: Vec<NoiseTrader>,
+    #[serde(default)]
+    pub basis_arbs: Vec<BasisArbAgent>,
+    #[serde(default)]
+    pub vault_portfolios: Vec<VaultPortfolio>,
+    /// Price oracle, when `oracle` is configured
+    #[serde(default)]
+    pub oracle: Option<PriceOracle>,
+    /// Volatility-responsive fee state, when `dynamic_fee` is configured
+    #[serde(default)]
+    pub dynamic_fee: Option<DynamicFee>,
+    /// Protocol-owned liquidity, when `protocol_liquidity` is configured
+    #[serde(default)]
+    pub protocol_liquidity: Option<ProtocolLiquidity>,
+    /// ZAI savings module, when `savings` is configured
+    #[serde(default)]
+    pub savings: Option<SavingsModule>,
+    /// Demurrage on ZAI balances, when `funding_rate` is configured
+    #[serde(default)]
+    pub funding_rate: Option<FundingRate>,
+    /// ZEC perp funding and the basis arbitrageurs' share of it, when
+    /// `perp` is configured
+    #[serde(default)]
+    pub perp: Option<PerpMarket>,
+    /// Transaction fee market, when `gas` is configured
+    #[serde(default)]
+    pub gas: Option<GasMarket>,
+    /// Block intervals and transaction capacity, when `block_space` is
+    /// configured
+    #[serde(default)]
+    pub block_space: Option<BlockSpace>,
+    /// Reorg draws and history, when `reorg` is configured
+    #[serde(default)]
+    pub reorg: Option<ReorgInjector>,
+    /// Agents' shielded funds, when `shielded_pool` is configured
+    #[serde(default)]
+    pub shielded_pool: Option<ShieldedPool>,
+    /// Transfers of external capital, when `bridge` is configured
+    #[serde(default)]
+    pub bridge: Option<Bridge>,
+    /// User growth and churn, when `adoption` is configured
+    #[serde(default)]
+    pub adoption: Option<Adoption>,
+    /// Vault arrivals and closures, when `vault_churn` is configured
+    #[serde(default)]
+    pub vault_churn: Option<VaultChurn>,
+    /// Debt ceiling course and vault waiting line, when `ceiling_policy`
+    /// is configured
+    #[serde(default)]
+    pub ceiling_policy: Option<CeilingPolicy>,
+    /// Fee, penalty and impermanent-loss attribution per LP cohort
+    #[serde(default)]
+    pub lp_attribution: LpAttribution,
+    /// Accounting checks and their findings, when `invariants` is
+    /// configured
+    #[serde(default)]
+    pub invariants: Option<InvariantChecker>,
+    /// Per-block agent throughput from historical volume, when set by
+    /// `scale_by_volume`
+    #[serde(default)]
+    pub volume_profile: Option<VolumeProfile>,
 
     // Stochastic state
     pub config: ScenarioConfig,
-    rng: StdRng,
+    /// Seed passed to `new_with_seed`
+    #[serde(default)]
+    pub seed: u64,
+    rng: ChaCha12Rng,
     miner_sell_countdowns: Vec<u64>,
+    /// Net direction of the noise traders' trades in the last block (-1 to 1)
+    #[serde(default)]
+    noise_herd: f64,
+    /// Liquidations (total, graduated) from this block's intrablock sub-steps,
+    /// folded into the block's metrics by `step`
+    #[serde(skip)]
+    intrablock_liquidations: (u32, u32),
+    /// ZEC sold onto the external market so far this block
+    #[serde(skip)]
+    external_zec_flow: f64,
+    /// Set while re-mining blocks after a reorg, so agents act in reverse
+    /// order
+    #[serde(skip)]
+    replaying: bool,
+    /// Instrumentation hooks; not saved in checkpoints
+    #[serde(skip)]
+    hooks: Hooks,
+}
+
+/// Hook run with mutable access to the scenario before or after a block.
+pub type StepHook = Box<dyn FnMut(&mut Scenario, u64) + Send>;
+
+/// Hook observing each finished block.
+pub type BlockHook = Box<dyn FnMut(&BlockSnapshot) + Send>;
+
+/// What an `on_block` hook sees once a block has been stepped.
+pub struct BlockSnapshot<'a> {
+    pub block: u64,
+    /// Metrics recorded for this block
+    pub metrics: &'a BlockMetrics,
+    /// Full state after the block
+    pub scenario: &'a Scenario,
+}
+
+#[derive(Default)]
+struct Hooks {
+    before_step: Vec<StepHook>,
+    after_step: Vec<StepHook>,
+    on_block: Vec<BlockHook>,
+    stream: Option<MetricsStream>,
+}
+
+/// A run's append-only history, moved out of the scenario while a reorg
+/// rollback point is taken.
+struct RunHistory {
+    metrics: MetricsStore,
+    snapshots: Vec<StateSnapshot>,
+    samples: Option<Vec<AgentSample>>,
+    price_observations: Vec<PriceObservation>,
+    liquidations: Vec<LiquidationResult>,
+    redemptions: Vec<RedemptionResult>,
+}
+
+/// Where and how `stream_metrics` sends each block's metrics.
+struct MetricsStream {
+    sink: Box<dyn MetricsSink>,
+    config: StreamConfig,
+    unflushed: u64,
+    /// Last block sent to the sink
+    streamed: u64,
+}
+
+impl MetricsStream {
+    /// Send the blocks of `metrics` after the last one streamed, up to
+    /// `last`.
+    fn send(&mut self, metrics: &MetricsStore, last: u64) -> Result<(), ZaiSimError> {
+        let start = metrics.block.partition_point(|b| *b <= self.streamed);
+        let end = metrics.block.partition_point(|b| *b <= last);
+        for i in start..end {
+            self.sink.write(&metrics.row(i))?;
+            self.streamed = metrics.block[i];
+            self.unflushed += 1;
+        }
+        Ok(())
+    }
 }
 
 impl Scenario {
@@ -135,8 +1225,36 @@ impl Scenario {
     }
 
     pub fn new_with_seed(config: &ScenarioConfig, seed: u64) -> Self {
+        let mut breakers = CircuitBreakerEngine::new(
+            config.twap_breaker_config.clone(),
+            config.cascade_breaker_config.clone(),
+            config.debt_ceiling_config.clone(),
+        );
+        breakers.halt_mode = config.halt_mode.clone();
+        breakers.redemption_limiter = config
+            .redemption_drift_limiter
+            .clone()
+            .map(RedemptionDriftLimiter::new);
+        breakers.mint_limiter = config.mint_rate_limiter.clone().map(MintRateLimiter::new);
+        breakers.auto_deleverage = config
+            .auto_deleverage
+            .clone()
+            .map(AutoDeleverageBreaker::new);
+        let (reserve_zec, reserve_zai) = config.amm_reserves();
+        let mut amm = Amm::new(reserve_zec, reserve_zai, config.amm_swap_fee);
+        amm.pools = config.pools.iter().map(Pool::new).collect();
+        amm.order_book = config
+            .order_book
+            .clone()
+            .map(|c| OrderBook::new(c, config.initial_amm_price()));
+        let protocol_liquidity = config.protocol_liquidity.clone().map(|c| {
+            let mut pol = ProtocolLiquidity::new(c);
+            let _ = pol.seed(&mut amm);
+            pol
+        });
+
         Scenario {
-            amm: Amm::new(config.amm_initial_zec, config.amm_initial_zai, config.amm_swap_fee),
+            amm,
             registry: VaultRegistry::new(config.cdp_config.clone()),
             controller: Controller::new(
                 config.controller_config.clone(),
@@ -144,12 +1262,18 @@ impl Scenario {
                 0,
             ),
             liquidation_engine: LiquidationEngine::new(config.liquidation_config.clone()),
-            breakers: CircuitBreakerEngine::new(
-                config.twap_breaker_config.clone(),
-                config.cascade_breaker_config.clone(),
-                config.debt_ceiling_config.clone(),
-            ),
-            metrics: Vec::new(),
+            breakers,
+            treasury: Treasury::new(config.treasury_config.clone()),
+            lending_market: config.lending_market.clone().map(LendingMarket::new),
+            external_market: config
+                .price_feedback
+                .as_ref()
+                .map(|f| ExternalMarket::new(f.market.clone())),
+            metrics: MetricsStore::default(),
+            snapshots: Vec::new(),
+            agent_metrics: config
+                .record_agent_metrics
+                .then(AgentMetricsCollector::new),
             arbers: Vec::new(),
             demand_agents: Vec::new(),
             miners: Vec::new(),
@@ -157,153 +1281,1263 @@ impl Scenario {
             lp_agents: Vec::new(),
             il_aware_lps: Vec::new(),
             attackers: Vec::new(),
+            redeemers: Vec::new(),
+            basis_traders: Vec::new(),
+            governance_agents: Vec::new(),
+            savers: Vec::new(),
+            noise_traders: config
+                .noise_traders
+                .as_ref()
+                .map_or_else(Vec::new, |p| p.spawn(seed.wrapping_add(0xFEED))),
+            basis_arbs: Vec::new(),
+            vault_portfolios: Vec::new(),
+            oracle: config.oracle_config().map(PriceOracle::new),
+            dynamic_fee: config.dynamic_fee.clone().map(DynamicFee::new),
+            protocol_liquidity,
+            savings: config.savings.clone().map(SavingsModule::new),
+            funding_rate: config.funding_rate.clone().map(FundingRate::new),
+            perp: config.perp.clone().map(PerpMarket::new),
+            gas: config.gas.clone().map(GasMarket::new),
+            block_space: config
+                .block_space
+                .clone()
+                .map(|c| BlockSpace::new(c, seed.wrapping_add(0xB10C))),
+            reorg: config
+                .reorg
+                .clone()
+                .map(|c| ReorgInjector::new(c, seed.wrapping_add(0x4E06))),
+            shielded_pool: config.shielded_pool.clone().map(ShieldedPool::new),
+            bridge: config
+                .bridge
+                .clone()
+                .map(|c| Bridge::new(c, seed.wrapping_add(0xB81D))),
+            adoption: config.adoption.clone().map(Adoption::new),
+            vault_churn: config
+                .vault_churn
+                .clone()
+                .map(|c| VaultChurn::new(c, seed.wrapping_add(0xC4A2))),
+            ceiling_policy: config.ceiling_policy.clone().map(CeilingPolicy::new),
+            lp_attribution: LpAttribution::new(),
+            invariants: config.invariants.clone().map(InvariantChecker::new),
+            volume_profile: None,
             config: config.clone(),
-            rng: StdRng::seed_from_u64(seed.wrapping_add(0xBEEF)),
+            seed,
+            rng: ChaCha12Rng::seed_from_u64(seed.wrapping_add(0xBEEF)),
             miner_sell_countdowns: Vec::new(),
+            noise_herd: 0.0,
+            intrablock_liquidations: (0, 0),
+            external_zec_flow: 0.0,
+            replaying: false,
+            hooks: Hooks::default(),
         }
     }
 
-    /// Run the simulation for a given price series.
-    /// `external_prices` maps block number to external ZEC price.
-    pub fn run(&mut self, external_prices: &[f64]) {
-        // Initialize LP agents
-        for lp in &mut self.lp_agents {
-            lp.provide_liquidity(&mut self.amm);
-        }
+    /// Scale miner sales, demand buying and arber capital replenishment by
+    /// `volumes`, one per block (e.g. kline volumes), relative to their
+    /// median.
+    pub fn scale_by_volume(&mut self, volumes: &[f64], config: &VolumeScalingConfig) {
+        self.volume_profile = Some(VolumeProfile::new(volumes, config));
+    }
 
-        // Initialize IL-aware LP agents
-        for lp in &mut self.il_aware_lps {
-            lp.provide_liquidity(&mut self.amm);
+    /// Hand this block's volume multiplier to the agents it throttles.
+    fn apply_volume_scale(&mut self, block: u64) {
+        let Some(profile) = &self.volume_profile else {
+            return;
+        };
+        let scale = Some(profile.factor(block));
+        for arber in &mut self.arbers {
+            arber.volume_scale = scale;
+        }
+        for demand in &mut self.demand_agents {
+            demand.volume_scale = scale;
         }
+        for miner in &mut self.miners {
+            miner.volume_scale = scale;
+        }
+    }
 
-        // Initialize CDP holders
-        for holder in &mut self.cdp_holders {
-            let _ = holder.open_vault(&mut self.registry, &self.amm, 0);
+    /// Call `hook` before each block is stepped, e.g. to apply a governance
+    /// parameter change at a given block.
+    pub fn before_step(&mut self, hook: impl FnMut(&mut Scenario, u64) + Send + 'static) {
+        self.hooks.before_step.push(Box::new(hook));
+    }
+
+    /// Call `hook` after each block is stepped and its metrics recorded.
+    pub fn after_step(&mut self, hook: impl FnMut(&mut Scenario, u64) + Send + 'static) {
+        self.hooks.after_step.push(Box::new(hook));
+    }
+
+    /// Call `hook` with a read-only view of each finished block, e.g. to
+    /// collect custom metrics.
+    pub fn on_block(&mut self, hook: impl FnMut(&BlockSnapshot) + Send + 'static) {
+        self.hooks.on_block.push(Box::new(hook));
+    }
+
+    /// Send each block's metrics to `sink` as it finishes, flushing every
+    /// `config.flush_every` blocks and at the end of each run, and keep only
+    /// the last `config.retain` blocks in `metrics`, so long runs use bounded
+    /// memory and a crash loses at most one chunk.
+    ///
+    /// With `retain` set, end-of-run summaries and reports only see the
+    /// retained blocks. With reorgs, a block is sent once it is deeper than
+    /// the deepest reorg, so the sink never sees an orphaned block.
+    pub fn stream_metrics(&mut self, sink: impl MetricsSink + 'static, config: StreamConfig) {
+        self.hooks.stream = Some(MetricsStream {
+            sink: Box::new(sink),
+            config,
+            unflushed: 0,
+            streamed: self.last_block(),
+        });
+    }
+
+    /// Send the blocks still held back for reorgs to the metrics sink set
+    /// by `stream_metrics`, if any, and flush it. Those blocks become final:
+    /// later reorgs don't reach past them.
+    pub fn flush_metrics(&mut self) -> Result<(), ZaiSimError> {
+        let Some(stream) = &mut self.hooks.stream else {
+            return Ok(());
+        };
+        stream.send(&self.metrics, u64::MAX)?;
+        stream.sink.flush()?;
+        stream.unflushed = 0;
+        if let Some(reorg) = &mut self.reorg {
+            reorg.finalize();
         }
+        Ok(())
+    }
 
-        // Initialize miner sell countdowns for stochastic mode
-        if self.config.stochastic && self.miner_sell_countdowns.is_empty() {
-            for _ in 0..self.miners.len() {
-                let countdown = self.rng.gen_range(1..=self.config.miner_batch_window);
-                self.miner_sell_countdowns.push(countdown);
+    /// Stream the blocks a reorg at `block` can no longer orphan and trim
+    /// the in-memory history.
+    fn stream_block(&mut self, block: u64) {
+        let Some(stream) = &mut self.hooks.stream else {
+            return;
+        };
+        let depth = self.reorg.as_ref().map_or(0, |r| r.config.max_depth);
+        if let Err(e) = stream.send(&self.metrics, block.saturating_sub(depth)) {
+            tracing::warn!(block, "streaming metrics failed: {}", e);
+        }
+        if stream.unflushed >= stream.config.flush_every.max(1) {
+            if let Err(e) = stream.sink.flush() {
+                tracing::warn!(block, "flushing metrics failed: {}", e);
             }
+            stream.unflushed = 0;
         }
-
-        for (i, &ext_price) in external_prices.iter().enumerate() {
-            let block = i as u64 + 1;
-            self.step(block, ext_price);
+        if let Some(retain) = stream.config.retain {
+            // Blocks not yet streamed stay
+            let sent = self
+                .metrics
+                .block
+                .partition_point(|b| *b <= stream.streamed);
+            let excess = self.metrics.len().saturating_sub(retain.max(1)).min(sent);
+            self.metrics.remove_first(excess);
         }
     }
 
-    /// Execute a single block of the simulation.
-    pub fn step(&mut self, block: u64, external_price: f64) {
-        let halted = self.breakers.is_halted(block);
-        let minting_paused = self.breakers.is_minting_paused(block);
-        let redemption_price = self.controller.redemption_price;
-        let stochastic = self.config.stochastic;
-
-        // (1) External price is provided as parameter
+    fn run_step_hooks(&mut self, block: u64, which: fn(&mut Hooks) -> &mut Vec<StepHook>) {
+        let mut hooks = std::mem::take(which(&mut self.hooks));
+        for hook in &mut hooks {
+            hook(self, block);
+        }
+        // Keep any hooks registered from inside a hook, after the existing ones
+        let added = std::mem::replace(which(&mut self.hooks), hooks);
+        which(&mut self.hooks).extend(added);
+    }
 
-        // (2) Arbitrageurs trade
-        if !halted {
-            let global_rate = self.config.arber_activity_rate;
-            for arber in &mut self.arbers {
-                // Use per-arber activity_rate if set below 1.0, else global fallback
-                let rate = if arber.config.activity_rate < 1.0 {
-                    arber.config.activity_rate
-                } else {
-                    global_rate
-                };
-                if stochastic && self.rng.gen::<f64>() >= rate {
-                    continue;
-                }
-                arber.act(&mut self.amm, external_price, block);
+    fn run_block_hooks(&mut self, block: u64) {
+        let mut hooks = std::mem::take(&mut self.hooks.on_block);
+        if let Some(metrics) = self.metrics.last() {
+            let snapshot = BlockSnapshot {
+                block,
+                metrics: &metrics,
+                scenario: self,
+            };
+            for hook in &mut hooks {
+                hook(&snapshot);
             }
         }
+        self.hooks.on_block = hooks;
+    }
 
-        // (3) CDP holders act
-        if !halted {
-            for holder in &mut self.cdp_holders {
-                holder.act(&mut self.registry, &self.amm, block);
+    /// External price after endogenous impact: the scripted price moved by
+    /// the simulation's accumulated external flows, if feedback is enabled.
+    fn effective_external_price(&mut self, block: u64, scripted_price: f64) -> f64 {
+        match &mut self.external_market {
+            Some(market) => {
+                market.recover(block);
+                market.price(scripted_price)
             }
+            None => scripted_price,
         }
+    }
 
-        // (4) Demand agents act
-        if !halted {
-            let jitter = self.config.demand_jitter_blocks;
-            for demand in &mut self.demand_agents {
-                // Stochastic: skip with probability jitter/(jitter+20)
-                if stochastic && self.rng.gen_range(0..jitter + 20) < jitter {
-                    continue;
+    /// Apply a changed config mid-run. Protocol parameters take effect from
+    /// the next block; state is kept, so the AMM reserves, vaults and the
+    /// lending market are not rebuilt from the new config.
+    pub fn reconfigure(&mut self, config: ScenarioConfig) {
+        self.amm.swap_fee = config.amm_swap_fee;
+        self.registry.config = config.cdp_config.clone();
+        self.controller.config = config.controller_config.clone();
+        self.liquidation_engine.config = config.liquidation_config.clone();
+        self.breakers.twap_breaker.config = config.twap_breaker_config.clone();
+        self.breakers.cascade_breaker.config = config.cascade_breaker_config.clone();
+        self.breakers.debt_ceiling.config = config.debt_ceiling_config.clone();
+        self.breakers.halt_mode = config.halt_mode.clone();
+        self.breakers.redemption_limiter = match (
+            self.breakers.redemption_limiter.take(),
+            &config.redemption_drift_limiter,
+        ) {
+            (Some(mut limiter), Some(c)) => {
+                limiter.config = c.clone();
+                Some(limiter)
+            }
+            (_, c) => c.clone().map(RedemptionDriftLimiter::new),
+        };
+        self.breakers.mint_limiter =
+            match (self.breakers.mint_limiter.take(), &config.mint_rate_limiter) {
+                (Some(mut limiter), Some(c)) => {
+                    limiter.config = c.clone();
+                    Some(limiter)
                 }
-                demand.act(&mut self.amm, redemption_price, block);
+                (_, c) => c.clone().map(MintRateLimiter::new),
+            };
+        self.breakers.auto_deleverage = match (
+            self.breakers.auto_deleverage.take(),
+            &config.auto_deleverage,
+        ) {
+            (Some(mut breaker), Some(c)) => {
+                breaker.config = c.clone();
+                Some(breaker)
             }
-        }
-
-        // (4b) Miners act
-        if !halted {
-            if stochastic && !self.miner_sell_countdowns.is_empty() {
-                for i in 0..self.miners.len() {
-                    // Always receive block reward
-                    self.miners[i].zec_balance += self.miners[i].config.block_reward;
-
-                    self.miner_sell_countdowns[i] =
-                        self.miner_sell_countdowns[i].saturating_sub(1);
-                    if self.miner_sell_countdowns[i] == 0 {
-                        // Batch sell accumulated ZEC
-                        let sell_frac = self.miners[i].config.miner_sell_fraction;
-                        let amm_frac = self.miners[i].config.miner_amm_fraction;
-                        let sell_amount =
-                            self.miners[i].zec_balance * sell_frac * amm_frac;
-                        if sell_amount > 0.001 {
-                            if let Ok(zai_out) =
-                                self.amm.swap_zec_for_zai(sell_amount, block)
-                            {
-                                self.miners[i].zec_balance -= sell_amount;
-                                self.miners[i].zai_balance += zai_out;
-                            }
+            (_, c) => c.clone().map(AutoDeleverageBreaker::new),
+        };
+        self.treasury.config = config.treasury_config.clone();
+        self.external_market = match (self.external_market.take(), &config.price_feedback) {
+            (Some(mut market), Some(feedback)) => {
+                market.config = feedback.market.clone();
+                Some(market)
+            }
+            (None, Some(feedback)) => Some(ExternalMarket::new(feedback.market.clone())),
+            (_, None) => None,
+        };
+        // A changed feed starts from scratch; failures and max age apply in place
+        self.oracle = match (self.oracle.take(), config.oracle_config()) {
+            (Some(mut oracle), Some(c)) if oracle.config.feed == c.feed => {
+                oracle.config = c;
+                Some(oracle)
+            }
+            (_, c) => c.map(PriceOracle::new),
+        };
+        // An existing position stays in the pool whatever the new config
+        self.protocol_liquidity = match (self.protocol_liquidity.take(), &config.protocol_liquidity)
+        {
+            (Some(mut pol), Some(c)) => {
+                pol.config = c.clone();
+                Some(pol)
+            }
+            (None, Some(c)) => {
+                let mut pol = ProtocolLiquidity::new(c.clone());
+                let _ = pol.seed(&mut self.amm);
+                Some(pol)
+            }
+            (_, None) => None,
+        };
+        // Deposits and accrued interest survive a rate change
+        self.savings = match (self.savings.take(), &config.savings) {
+            (Some(mut savings), Some(c)) => {
+                savings.config = c.clone();
+                Some(savings)
+            }
+            (None, Some(c)) => Some(SavingsModule::new(c.clone())),
+            (_, None) => None,
+        };
+        self.funding_rate = match (self.funding_rate.take(), &config.funding_rate) {
+            (Some(mut funding), Some(c)) => {
+                funding.config = c.clone();
+                Some(funding)
+            }
+            (_, c) => c.clone().map(FundingRate::new),
+        };
+        // Arbitrage positions carry over to the new funding terms
+        self.perp = match (self.perp.take(), &config.perp) {
+            (Some(mut perp), Some(c)) => {
+                perp.config = c.clone();
+                Some(perp)
+            }
+            (_, c) => c.clone().map(PerpMarket::new),
+        };
+        self.gas = match (self.gas.take(), &config.gas) {
+            (Some(mut gas), Some(c)) => {
+                gas.config = c.clone();
+                Some(gas)
+            }
+            (_, c) => c.clone().map(GasMarket::new),
+        };
+        self.block_space = match (self.block_space.take(), &config.block_space) {
+            (Some(mut space), Some(c)) => {
+                space.config = c.clone();
+                Some(space)
+            }
+            (None, Some(c)) => Some(BlockSpace::new(c.clone(), self.rng.gen())),
+            (_, None) => None,
+        };
+        self.reorg = match (self.reorg.take(), &config.reorg) {
+            (Some(mut reorg), Some(c)) => {
+                reorg.config = c.clone();
+                Some(reorg)
+            }
+            (None, Some(c)) => Some(ReorgInjector::new(c.clone(), self.rng.gen())),
+            (_, None) => None,
+        };
+        // Dropping the model hands shielded funds back to their owners
+        self.shielded_pool = match (self.shielded_pool.take(), &config.shielded_pool) {
+            (Some(mut pool), Some(c)) => {
+                pool.config = c.clone();
+                Some(pool)
+            }
+            (Some(mut pool), None) => {
+                self.for_each_wallet(|id, zec, zai| pool.release(id, zec, zai));
+                None
+            }
+            (None, c) => c.clone().map(ShieldedPool::new),
+        };
+        // Dropping the bridge lands deposits at once and calls off
+        // withdrawals
+        self.bridge = match (self.bridge.take(), &config.bridge) {
+            (Some(mut bridge), Some(c)) => {
+                bridge.config = c.clone();
+                Some(bridge)
+            }
+            (Some(bridge), None) => {
+                for arber in &mut self.arbers {
+                    arber.bridged = false;
+                }
+                for transfer in bridge.queued.iter().chain(&bridge.in_transit) {
+                    if let Some(arber) = Self::bridge_owner(&mut self.arbers, transfer) {
+                        match transfer.asset {
+                            Asset::Zec => arber.zec_balance += transfer.amount,
+                            _ => arber.zai_balance += transfer.amount,
                         }
-                        let bw = self.config.miner_batch_window;
-                        self.miner_sell_countdowns[i] = self.rng.gen_range(1..=bw);
                     }
                 }
-            } else {
-                for miner in &mut self.miners {
-                    miner.act(&mut self.amm, block);
+                None
+            }
+            (None, Some(c)) => Some(Bridge::new(c.clone(), self.rng.gen())),
+            (None, None) => None,
+        };
+        // Users who joined stay when the model is dropped
+        self.adoption = match (self.adoption.take(), &config.adoption) {
+            (Some(mut adoption), Some(c)) => {
+                adoption.config = c.clone();
+                Some(adoption)
+            }
+            (None, c) => c.clone().map(Adoption::new),
+            (_, None) => None,
+        };
+        // Vaults opened by arrivals stay open when the process is dropped
+        self.vault_churn = match (self.vault_churn.take(), &config.vault_churn) {
+            (Some(mut churn), Some(c)) => {
+                churn.config = c.clone();
+                Some(churn)
+            }
+            (None, Some(c)) => Some(VaultChurn::new(c.clone(), self.rng.gen())),
+            (_, None) => None,
+        };
+        // Without a policy the ceiling no longer binds: waiting vaults open
+        self.ceiling_policy = match (self.ceiling_policy.take(), &config.ceiling_policy) {
+            (Some(mut policy), Some(c)) => {
+                policy.config = c.clone();
+                Some(policy)
+            }
+            (Some(policy), None) => {
+                let block = self.last_block();
+                for i in policy.waiting {
+                    if let Some(holder) = self.cdp_holders.get_mut(i) {
+                        let _ = holder.open_vault(&mut self.registry, &self.amm, block);
+                    }
                 }
+                None
+            }
+            (None, c) => c.clone().map(CeilingPolicy::new),
+        };
+        self.dynamic_fee = match (self.dynamic_fee.take(), &config.dynamic_fee) {
+            (Some(mut fee), Some(c)) => {
+                fee.config = c.clone();
+                Some(fee)
             }
+            (None, Some(c)) => Some(DynamicFee::new(c.clone())),
+            (_, None) => None,
+        };
+        // Existing pools keep their reserves; only fee and curve change
+        let pools = &mut self.amm.pools;
+        pools.truncate(config.pools.len());
+        for (pool, c) in pools.iter_mut().zip(&config.pools) {
+            pool.swap_fee = c.swap_fee;
+            pool.curve = c.curve;
         }
-
-        // (4c) LPs act
-        if !halted {
-            for lp in &mut self.lp_agents {
-                lp.act(&mut self.amm);
+        let existing = pools.len();
+        pools.extend(config.pools[existing..].iter().map(Pool::new));
+        // The book keeps its mid and fills unless its levels change
+        self.amm.order_book = match (self.amm.order_book.take(), &config.order_book) {
+            (Some(mut book), Some(c)) if book.config.levels.len() == c.levels.len() => {
+                book.config = c.clone();
+                Some(book)
             }
-            for lp in &mut self.il_aware_lps {
-                lp.act(&mut self.amm, external_price);
+            (old, Some(c)) => {
+                let mid = old.map_or(self.amm.spot_price(), |b| b.mid);
+                Some(OrderBook::new(c.clone(), mid))
             }
-        }
+            (_, None) => None,
+        };
+        self.config = config;
+    }
 
-        // (4e) Stability fee routing to LPs
-        if self.config.stability_fee_to_lps {
-            let fee_delta = self.registry.accrue_all_fees(block);
-            if fee_delta > 0.0 {
-                self.amm.reserve_zai += fee_delta;
-                self.amm.k = self.amm.reserve_zec * self.amm.reserve_zai;
-                self.amm.cumulative_fees_zai += fee_delta;
-            }
+    /// Apply the parameter schedule's and governance agents' changes for
+    /// `block`.
+    fn apply_parameter_changes(&mut self, block: u64) {
+        let due: Vec<ParameterChange> = self
+            .config
+            .parameter_schedule
+            .due(block)
+            .chain(self.governance_agents.iter().flat_map(|a| a.due(block)))
+            .cloned()
+            .collect();
+        if due.is_empty() {
+            return;
+        }
+        let (config, failed) = apply_changes(&self.config, &due);
+        for (change, e) in failed {
+            tracing::warn!(
+                block,
+                "parameter change {} = {} failed: {}",
+                change.param,
+                change.value,
+                e
+            );
         }
+        self.reconfigure(config);
+    }
+
+    /// Load a scenario from a checkpoint written by `save_checkpoint`.
+    pub fn restore(path: &Path) -> Result<Scenario, ZaiSimError> {
+        let file = std::fs::File::open(path)?;
+        let scenario = serde_json::from_reader(std::io::BufReader::new(file))?;
+        Ok(scenario)
+    }
 
-        // (4d) Attackers act
-        for attacker in &mut self.attackers {
-            attacker.act(&mut self.amm, block);
+    /// Write the full simulation state (AMM, registry, controller, breakers,
+    /// agents and RNG) to `path`. The file is replaced atomically, so an
+    /// interrupted write never clobbers the previous checkpoint.
+    pub fn save_checkpoint(&self, path: &Path) -> Result<(), ZaiSimError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
         }
+        let tmp = path.with_extension("tmp");
+        let file = std::fs::File::create(&tmp)?;
+        let mut writer = std::io::BufWriter::new(file);
+        serde_json::to_writer(&mut writer, self)?;
+        writer.flush()?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
 
-        // (5) AMM records price for TWAP
-        self.amm.record_price(block);
+    /// Last block that has been simulated (0 before the first step).
+    pub fn last_block(&self) -> u64 {
+        self.metrics.block.last().copied().unwrap_or(0)
+    }
 
-        // (6a) Graduated liquidation pass: partially liquidate warning-zone vaults
-        let graduated_results = if self.config.use_graduated_liquidation {
+    /// Run the simulation for a given price series.
+    /// `external_prices` maps block number to external ZEC price.
+    /// A scenario restored from a checkpoint resumes after its last block.
+    pub fn run(&mut self, external_prices: &[f64]) {
+        self.run_with(external_prices.len(), |i| {
+            std::slice::from_ref(&external_prices[i])
+        });
+    }
+
+    /// Run the simulation with an intrablock price path per block (e.g. a
+    /// kline's open → high → low → close). See `step_path`.
+    pub fn run_paths(&mut self, price_paths: &[Vec<f64>]) {
+        self.run_with(price_paths.len(), |i| price_paths[i].as_slice());
+    }
+
+    fn run_with<'a>(&mut self, blocks: usize, path: impl Fn(usize) -> &'a [f64]) {
+        let start = self.last_block();
+        if start == 0 {
+            self.initialize_agents();
+            // Cost basis of the initial LP positions
+            self.lp_attribution.observe(
+                &self.amm,
+                self.liquidation_engine.total_penalties_to_lps,
+                0,
+            );
+            if let Some(mut collector) = self.agent_metrics.take() {
+                collector.set_baseline(self);
+                self.agent_metrics = Some(collector);
+            }
+        }
+        // Grow the history once up front, unless streaming keeps it short
+        if !matches!(&self.hooks.stream, Some(s) if s.config.retain.is_some()) {
+            self.metrics.reserve(blocks.saturating_sub(start as usize));
+        }
+
+        for i in start as usize..blocks {
+            let block = i as u64 + 1;
+            self.save_reorg_point(block);
+            self.step_path(block, path(i));
+            self.inject_reorg(block, &path);
+
+            let interval = self.config.checkpoint_interval;
+            if interval > 0 && block.is_multiple_of(interval) {
+                if let Some(path) = self.config.checkpoint_path.clone() {
+                    if let Err(e) = self.save_checkpoint(&path) {
+                        tracing::warn!(block, "checkpoint failed: {}", e);
+                    }
+                }
+            }
+        }
+        if let Err(e) = self.flush_metrics() {
+            tracing::warn!("flushing metrics failed: {}", e);
+        }
+    }
+
+    /// Keep the state before `block` as a rollback point for reorgs. The
+    /// run's history is left out, so a rollback point costs the same at any
+    /// block; a rollback trims it instead.
+    fn save_reorg_point(&mut self, block: u64) {
+        let Some(mut reorg) = self.reorg.take() else {
+            return;
+        };
+        let history = self.take_history();
+        match serde_json::to_vec(self) {
+            Ok(state) => reorg.push_state(block, state),
+            Err(e) => tracing::warn!(block, "reorg point failed: {}", e),
+        }
+        self.restore_history(history, u64::MAX);
+        self.reorg = Some(reorg);
+    }
+
+    /// Move out the run's append-only history: block metrics, snapshots,
+    /// agent samples, AMM price observations and settlements.
+    fn take_history(&mut self) -> RunHistory {
+        RunHistory {
+            metrics: std::mem::take(&mut self.metrics),
+            snapshots: std::mem::take(&mut self.snapshots),
+            samples: self
+                .agent_metrics
+                .as_mut()
+                .map(|c| std::mem::take(&mut c.samples)),
+            price_observations: self.amm.take_observations(),
+            liquidations: std::mem::take(&mut self.liquidation_engine.history),
+            redemptions: std::mem::take(&mut self.liquidation_engine.redemption_history),
+        }
+    }
+
+    /// Put back history from `take_history`, dropping entries from block
+    /// `from` on.
+    fn restore_history(&mut self, history: RunHistory, from: u64) {
+        fn before<T>(mut entries: Vec<T>, from: u64, block: impl Fn(&T) -> u64) -> Vec<T> {
+            entries.truncate(entries.partition_point(|e| block(e) < from));
+            entries
+        }
+        self.metrics = history.metrics;
+        self.metrics
+            .truncate(self.metrics.block.partition_point(|b| *b < from));
+        self.snapshots = before(history.snapshots, from, |s| s.block);
+        if let (Some(collector), Some(samples)) = (&mut self.agent_metrics, history.samples) {
+            collector.samples = before(samples, from, |s| s.block);
+        }
+        self.amm
+            .restore_observations(history.price_observations, from);
+        self.liquidation_engine.history = before(history.liquidations, from, |r| r.block);
+        self.liquidation_engine.redemption_history =
+            before(history.redemptions, from, |r| r.block);
+    }
+
+    /// After `block`, maybe reorg: roll back to before the first orphaned
+    /// block and re-mine up to `block` at the same external prices, with
+    /// arbitrageurs last, agents in reverse order and fresh randomness.
+    fn inject_reorg<'a>(&mut self, block: u64, path: &impl Fn(usize) -> &'a [f64]) {
+        let Some((from, state)) = self.reorg.as_mut().and_then(|r| r.draw()) else {
+            return;
+        };
+        let mut forked: Scenario = match serde_json::from_slice(&state) {
+            Ok(s) => s,
+            Err(e) => {
+                tracing::warn!(block, "reorg failed: {}", e);
+                return;
+            }
+        };
+        let orphaned = |s: &Scenario| -> u32 {
+            let start = s.metrics.block.partition_point(|b| *b < from);
+            s.metrics.liquidation_count[start..].iter().sum()
+        };
+        let mut event = ReorgEvent {
+            block,
+            depth: block - from + 1,
+            orphaned_liquidations: orphaned(self),
+            replayed_liquidations: 0,
+            orphaned_spot_price: self.amm.spot_price(),
+            replayed_spot_price: 0.0,
+        };
+
+        forked.restore_history(self.take_history(), from);
+        forked.hooks = std::mem::take(&mut self.hooks);
+        forked.reorg = self.reorg.take();
+        *self = forked;
+        if let Some(reorg) = &mut self.reorg {
+            self.rng = ChaCha12Rng::seed_from_u64(reorg.fork_seed());
+            // Counted before re-mining so the replacement blocks see it
+            reorg.events.push(event.clone());
+        }
+
+        self.replaying = true;
+        for b in from..=block {
+            self.save_reorg_point(b);
+            self.step_path(b, path(b as usize - 1));
+        }
+        self.replaying = false;
+
+        event.replayed_liquidations = orphaned(self);
+        event.replayed_spot_price = self.amm.spot_price();
+        if let Some(last) = self.reorg.as_mut().and_then(|r| r.events.last_mut()) {
+            *last = event;
+        }
+    }
+
+    /// Put LPs' initial liquidity in the AMM and open CDP holders' vaults.
+    /// `run` does this before the first block; agents already set up (e.g.
+    /// by `ScenarioBuilder::build`) are left alone.
+    pub fn initialize_agents(&mut self) {
+        // Initialize LP agents
+        for lp in self.lp_agents.iter_mut().filter(|lp| !lp.is_providing) {
+            lp.provide_liquidity(&mut self.amm);
+        }
+
+        // Initialize IL-aware LP agents
+        for lp in self.il_aware_lps.iter_mut().filter(|lp| !lp.is_providing) {
+            lp.provide_liquidity(&mut self.amm);
+        }
+
+        // Initialize CDP holders that have not opened a vault yet; under a
+        // ceiling policy those that don't fit wait in line
+        for (i, holder) in self.cdp_holders.iter_mut().enumerate() {
+            if holder.vault_id.is_none() {
+                if let Some(policy) = &mut self.ceiling_policy {
+                    if policy.waiting.contains(&i) {
+                        continue;
+                    }
+                    let fits = self
+                        .breakers
+                        .debt_ceiling
+                        .can_mint(self.registry.total_debt, holder.config.initial_debt);
+                    if !fits || !policy.waiting.is_empty() {
+                        policy.waiting.push_back(i);
+                        continue;
+                    }
+                }
+                let _ = holder.open_vault(&mut self.registry, &self.amm, 0);
+            }
+        }
+        for portfolio in self.vault_portfolios.iter_mut().filter(|p| !p.opened) {
+            let _ = portfolio.open_vaults(&mut self.registry, &self.amm, 0);
+        }
+
+        // Initialize miner sell countdowns for stochastic mode
+        if self.config.stochastic && self.miner_sell_countdowns.is_empty() {
+            for _ in 0..self.miners.len() {
+                let countdown = self.rng.gen_range(1..=self.config.miner_batch_window);
+                self.miner_sell_countdowns.push(countdown);
+            }
+        }
+    }
+
+    /// Step one block through an intrablock price path. Every price but the
+    /// last is a sub-step where arbitrageurs trade and liquidations run
+    /// against it, so a wick inside a candle can liquidate vaults and feed
+    /// the cascade breaker; the last price drives the full `step`. Hooks run
+    /// once per block, around the whole path.
+    pub fn step_path(&mut self, block: u64, path: &[f64]) {
+        let Some((&close, wicks)) = path.split_last() else {
+            return;
+        };
+        // Events from this block (liquidations, breakers) carry its number
+        let _span = tracing::debug_span!("block", block).entered();
+        if let Some(checker) = &mut self.invariants {
+            checker.begin_block(&self.amm);
+        }
+        // Stability fees compound up to this block at the rate in force
+        // before it, so reads during the block see fees due up to it and a
+        // fee change made now compounds from the next
+        self.registry.drip(block);
+        self.apply_parameter_changes(block);
+        self.run_step_hooks(block, |h| &mut h.before_step);
+        self.apply_volume_scale(block);
+        self.update_swap_fee(block);
+        // Gas is repriced from last block's congestion, and this block's
+        // interval and capacity drawn, before any sub-step trades
+        self.price_gas();
+        if let Some(space) = &mut self.block_space {
+            space.begin_block();
+        }
+        for &price in wicks {
+            self.substep(block, price);
+        }
+        self.step_block(block, close);
+        if let Some(i) = self.metrics.len().checked_sub(1) {
+            let m = &self.metrics;
+            tracing::debug!(
+                external_price = close,
+                amm_price = m.amm_spot_price[i],
+                redemption_price = m.redemption_price[i],
+                total_debt = m.total_debt[i],
+                liquidations = m.liquidation_count[i],
+                "block done"
+            );
+        }
+        self.run_step_hooks(block, |h| &mut h.after_step);
+        self.check_invariants(block);
+        self.run_block_hooks(block);
+        self.stream_block(block);
+    }
+
+    /// Run the invariant checker, if configured, over the state after
+    /// `block`.
+    fn check_invariants(&mut self, block: u64) {
+        if let Some(mut checker) = self.invariants.take() {
+            checker.check(self, block);
+            self.invariants = Some(checker);
+        }
+    }
+
+    /// Set this block's AMM fee from the dynamic fee, if configured.
+    fn update_swap_fee(&mut self, block: u64) {
+        if let Some(fee) = &mut self.dynamic_fee {
+            // The observation this block's first swap would record anyway
+            self.amm.record_price(block);
+            self.amm.swap_fee = fee.update(&self.amm, self.config.amm_swap_fee);
+        }
+    }
+
+    /// Outside arbitrage: trade each side pool to the price implied by the
+    /// external ZEC price and the AMM's ZAI price (USD at par). The order
+    /// book is re-quoted around the external price and partly refilled.
+    fn rebalance_pools(&mut self, external_price: f64) {
+        let zai_usd = external_price / self.amm.spot_price();
+        let usd_value = |asset: Asset| match asset {
+            Asset::Zec => external_price,
+            Asset::Zai => zai_usd,
+            Asset::Usd => 1.0,
+        };
+        for pool in &mut self.amm.pools {
+            pool.trade_to_price(usd_value(pool.base) / usd_value(pool.quote));
+        }
+        if let Some(book) = &mut self.amm.order_book {
+            book.reprice(external_price);
+            book.replenish();
+        }
+    }
+
+    /// Take the funding charge from agent wallet ZAI (arbers, demand
+    /// agents, miners, redeemers, basis traders, savers and noise traders)
+    /// and, if
+    /// configured, savings deposits, into the treasury surplus.
+    fn charge_funding(&mut self) {
+        let Some(funding) = &mut self.funding_rate else {
+            return;
+        };
+        let mut charged = 0.0;
+        for a in &mut self.arbers {
+            charged += funding.charge(&mut a.zai_balance);
+        }
+        for d in &mut self.demand_agents {
+            charged += funding.charge(&mut d.zai_balance);
+        }
+        for m in &mut self.miners {
+            charged += funding.charge(&mut m.zai_balance);
+        }
+        for r in &mut self.redeemers {
+            charged += funding.charge(&mut r.zai_balance);
+        }
+        for t in &mut self.basis_traders {
+            charged += funding.charge(&mut t.zai_balance);
+        }
+        for s in &mut self.savers {
+            charged += funding.charge(&mut s.zai_balance);
+        }
+        for t in &mut self.noise_traders {
+            charged += funding.charge(&mut t.zai_balance);
+        }
+        for a in &mut self.basis_arbs {
+            charged += funding.charge(&mut a.zai_balance);
+        }
+        for h in &mut self.cdp_holders {
+            charged += funding.charge(&mut h.zai_balance);
+        }
+        if let Some(savings) = &mut self.savings {
+            charged += funding.charge_savings(savings);
+        }
+        self.treasury.deposit_surplus(charged);
+    }
+
+    /// Settle every agent's wallet against its shielded funds: deliver
+    /// arrived unshields and move funds toward the shielded fraction.
+    fn settle_shielded(&mut self, block: u64) {
+        let Some(mut pool) = self.shielded_pool.take() else {
+            return;
+        };
+        self.for_each_wallet(|id, zec, zai| pool.settle(id, zec, zai, block));
+        self.shielded_pool = Some(pool);
+    }
+
+    /// Move arbers' external capital across the bridge: request this
+    /// block's top-ups, then credit what lands. ZAI withdrawn to buy ZEC is
+    /// sold off-chain on arrival and the ZEC deposited back.
+    fn settle_bridge(&mut self, block: u64, external_price: f64) {
+        let Some(bridge) = &mut self.bridge else {
+            return;
+        };
+        for (i, arber) in self.arbers.iter_mut().enumerate() {
+            arber.bridged = true;
+            let rate = arber.replenish_rate();
+            if rate <= 0.0 {
+                continue;
+            }
+            let id = format!("arber_{}", i);
+            bridge.request(&id, Asset::Zai, rate, BridgeDirection::Deposit);
+            if external_price <= 0.0 || arber.zai_balance <= 0.0 {
+                continue;
+            }
+            // Low on ZEC, counting what is already on its way
+            let inbound_zec = bridge.pending(&id, Asset::Zec, BridgeDirection::Deposit)
+                + bridge.pending(&id, Asset::Zai, BridgeDirection::Withdraw) / external_price;
+            if arber.zec_balance + inbound_zec < 10.0 {
+                let convert = rate.min(arber.zai_balance);
+                arber.zai_balance -= convert;
+                bridge.request(&id, Asset::Zai, convert, BridgeDirection::Withdraw);
+            }
+        }
+        for transfer in bridge.advance(block, external_price) {
+            let Some(arber) = Self::bridge_owner(&mut self.arbers, &transfer) else {
+                continue;
+            };
+            match (transfer.direction, transfer.asset) {
+                (BridgeDirection::Deposit, Asset::Zec) => arber.zec_balance += transfer.amount,
+                (BridgeDirection::Deposit, _) => arber.zai_balance += transfer.amount,
+                (BridgeDirection::Withdraw, _) => {
+                    let zec = arber.buy_zec_externally(transfer.amount, external_price, block);
+                    bridge.request(&transfer.agent, Asset::Zec, zec, BridgeDirection::Deposit);
+                }
+            }
+        }
+    }
+
+    /// The arber a bridge transfer belongs to.
+    fn bridge_owner<'a>(
+        arbers: &'a mut [Arbitrageur],
+        transfer: &BridgeTransfer,
+    ) -> Option<&'a mut Arbitrageur> {
+        let i = transfer
+            .agent
+            .strip_prefix("arber_")?
+            .parse::<usize>()
+            .ok()?;
+        arbers.get_mut(i)
+    }
+
+    /// Grow or shrink the demand roster along the adoption curve. Users
+    /// who leave are the newest; each takes back its shielded funds and
+    /// sells its ZAI on the way out.
+    fn adopt_users(&mut self, block: u64, redemption_price: f64) {
+        let Some(adoption) = &mut self.adoption else {
+            return;
+        };
+        let deviation_pct = (self.amm.spot_price() / redemption_price - 1.0) * 100.0;
+        let target = adoption.step(deviation_pct, self.demand_agents.len());
+        while self.demand_agents.len() < target {
+            self.demand_agents
+                .push(DemandAgent::new(adoption.config.new_agent.clone()));
+            adoption.total_joined += 1;
+        }
+        while self.demand_agents.len() > target {
+            let id = format!("demand_{}", self.demand_agents.len() - 1);
+            // A full block holds the departure over to the next
+            if !Self::admit(&mut self.block_space, || id.clone()) {
+                break;
+            }
+            let mut leaver = self.demand_agents.pop().unwrap();
+            if let Some(pool) = &mut self.shielded_pool {
+                pool.release(&id, &mut leaver.zec_balance, &mut leaver.zai_balance);
+            }
+            adoption.total_churned += 1;
+            let zai = leaver.zai_balance;
+            if zai <= 0.01 {
+                continue;
+            }
+            if let Ok(zec_out) = self.amm.buy_zec(zai, block) {
+                adoption.exit_zai_sold += zai;
+                let action = AgentAction::PanicSellZai {
+                    zai_spent: zai,
+                    zec_received: zec_out,
+                };
+                Self::fill(&mut self.block_space, std::slice::from_ref(&action));
+                if let Some(collector) = &mut self.agent_metrics {
+                    collector.note(&id, &action);
+                }
+            }
+        }
+    }
+
+    /// Open vaults for CDP holders waiting on the ceiling policy, in order,
+    /// while they fit under the ceiling and the minting rate limit.
+    fn admit_waiting_vaults(&mut self, block: u64) {
+        let Some(policy) = &mut self.ceiling_policy else {
+            return;
+        };
+        while let Some(&i) = policy.waiting.front() {
+            let Some(holder) = self.cdp_holders.get_mut(i) else {
+                policy.waiting.pop_front();
+                continue;
+            };
+            let debt = holder.config.initial_debt;
+            let total = self.registry.total_debt;
+            if !self.breakers.debt_ceiling.can_mint(total, debt)
+                || !self.breakers.mint_allowed(total, debt)
+            {
+                break;
+            }
+            if !Self::admit(&mut self.block_space, || format!("cdp_holder_{}", i)) {
+                break;
+            }
+            policy.waiting.pop_front();
+            if holder
+                .open_vault(&mut self.registry, &self.amm, block)
+                .is_ok()
+            {
+                policy.vaults_admitted += 1;
+                if let Some(space) = &mut self.block_space {
+                    space.fill_count(1);
+                }
+            }
+        }
+    }
+
+    /// Return the leftover collateral of vaults closed by redemptions since
+    /// `from` in the redemption history to their owners.
+    fn return_redeemed_collateral(&mut self, from: usize) {
+        let closed: Vec<(u64, String, f64)> = self.liquidation_engine.redemption_history[from..]
+            .iter()
+            .flat_map(|r| r.vaults_closed.iter().cloned())
+            .collect();
+        for (id, owner, collateral) in closed {
+            self.return_collateral(id, &owner, collateral);
+        }
+    }
+
+    /// Return the collateral left in a closed vault to its owner: a CDP
+    /// holder's reserve, or a portfolio owner's by name.
+    fn return_collateral(&mut self, vault_id: u64, owner: &str, collateral: f64) {
+        if let Some(holder) = self.cdp_holders.iter_mut().find(|h| h.vault_id == Some(vault_id)) {
+            holder.vault_id = None;
+            holder.reserve_zec += collateral;
+        } else if let Some(portfolio) = self.vault_portfolios.iter_mut().find(|p| p.owner == owner)
+        {
+            portfolio.reserve_zec += collateral;
+        }
+    }
+
+    /// Draw this block's vault arrivals and closures. Arrivals join the
+    /// CDP holder roster and open their vault at once if it fits under the
+    /// ceiling and minting limits, and are turned away otherwise; a closing
+    /// holder repays its debt and takes its collateral back into reserve.
+    fn churn_vaults(&mut self, block: u64, minting_paused: bool) {
+        let Some(churn) = &mut self.vault_churn else {
+            return;
+        };
+        let price = self.amm.spot_price();
+        let open = self
+            .cdp_holders
+            .iter()
+            .filter(|h| h.vault_id.is_some())
+            .count();
+        let arrivals = churn.step(price, self.registry.config.stability_fee_rate, open);
+
+        for (i, holder) in self.cdp_holders.iter_mut().enumerate() {
+            let Some(vault_id) = holder.vault_id else {
+                continue;
+            };
+            if !churn.closes() {
+                continue;
+            }
+            if !Self::admit(&mut self.block_space, || format!("cdp_holder_{}", i)) {
+                continue;
+            }
+            if let Ok((collateral, _)) = self.registry.close_vault(vault_id, block) {
+                holder.vault_id = None;
+                holder.reserve_zec += collateral;
+                churn.total_closed += 1;
+                if let Some(space) = &mut self.block_space {
+                    space.fill_count(1);
+                }
+            }
+        }
+
+        for config in arrivals {
+            let debt = config.initial_debt;
+            let total = self.registry.total_debt;
+            let id = format!("cdp_holder_{}", self.cdp_holders.len());
+            if minting_paused
+                || !self.breakers.debt_ceiling.can_mint(total, debt)
+                || !self.breakers.mint_allowed(total, debt)
+                || !Self::admit(&mut self.block_space, || id.clone())
+            {
+                churn.total_refused += 1;
+                continue;
+            }
+            let mut holder = CdpHolder::new(config);
+            if holder
+                .open_vault(&mut self.registry, &self.amm, block)
+                .is_ok()
+            {
+                self.cdp_holders.push(holder);
+                churn.total_opened += 1;
+                if let Some(space) = &mut self.block_space {
+                    space.fill_count(1);
+                }
+            } else {
+                churn.total_refused += 1;
+            }
+        }
+    }
+
+    /// Call `f` with each wallet-holding agent's id and ZEC and ZAI
+    /// balances.
+    fn for_each_wallet(&mut self, mut f: impl FnMut(&str, &mut f64, &mut f64)) {
+        for (i, a) in self.arbers.iter_mut().enumerate() {
+            f(
+                &format!("arber_{}", i),
+                &mut a.zec_balance,
+                &mut a.zai_balance,
+            );
+        }
+        for (i, d) in self.demand_agents.iter_mut().enumerate() {
+            f(
+                &format!("demand_{}", i),
+                &mut d.zec_balance,
+                &mut d.zai_balance,
+            );
+        }
+        for (i, m) in self.miners.iter_mut().enumerate() {
+            f(
+                &format!("miner_{}", i),
+                &mut m.zec_balance,
+                &mut m.zai_balance,
+            );
+        }
+        for (i, r) in self.redeemers.iter_mut().enumerate() {
+            f(
+                &format!("redeemer_{}", i),
+                &mut r.zec_balance,
+                &mut r.zai_balance,
+            );
+        }
+        for (i, t) in self.basis_traders.iter_mut().enumerate() {
+            f(
+                &format!("basis_{}", i),
+                &mut t.zec_balance,
+                &mut t.zai_balance,
+            );
+        }
+        for (i, s) in self.savers.iter_mut().enumerate() {
+            f(
+                &format!("saver_{}", i),
+                &mut s.zec_balance,
+                &mut s.zai_balance,
+            );
+        }
+        for (i, t) in self.noise_traders.iter_mut().enumerate() {
+            f(
+                &format!("noise_{}", i),
+                &mut t.zec_balance,
+                &mut t.zai_balance,
+            );
+        }
+        for (i, a) in self.basis_arbs.iter_mut().enumerate() {
+            f(
+                &format!("basis_arb_{}", i),
+                &mut a.zec_balance,
+                &mut a.zai_balance,
+            );
+        }
+    }
+
+    /// Open this block's gas market and pass the fee to the arbers, CDP
+    /// holders and keepers that price it into their decisions.
+    fn price_gas(&mut self) {
+        let Some(gas) = &mut self.gas else {
+            return;
+        };
+        gas.begin_block();
+        let fee_zai = gas.fee_zai(self.amm.spot_price());
+        for arber in &mut self.arbers {
+            arber.gas_fee_zai = fee_zai;
+        }
+        for holder in &mut self.cdp_holders {
+            holder.topup_policy.tx_fee_zec = gas.fee_zec;
+        }
+        self.liquidation_engine.gas_fee_zai = fee_zai;
+    }
+
+    /// Whether the agent named by `agent` gets room in this block; always
+    /// true without a block space model.
+    fn admit(space: &mut Option<BlockSpace>, agent: impl FnOnce() -> String) -> bool {
+        space.as_mut().is_none_or(|s| s.admit(&agent()))
+    }
+
+    /// Agents of one kind in acting order: as listed, or reversed while
+    /// re-mining after a reorg.
+    fn in_order<T>(agents: &mut [T], reversed: bool) -> Vec<(usize, &mut T)> {
+        let mut order: Vec<(usize, &mut T)> = agents.iter_mut().enumerate().collect();
+        if reversed {
+            order.reverse();
+        }
+        order
+    }
+
+    /// Arbitrageurs trade the AMM toward `external_price`, then the AMM
+    /// and order book are arbitraged against each other.
+    fn arbitrage(&mut self, block: u64, external_price: f64) {
+        let global_rate = self.config.arber_activity_rate;
+        for (i, arber) in Self::in_order(&mut self.arbers, self.replaying) {
+            // Use per-arber activity_rate if set below 1.0, else global fallback
+            let rate = if arber.config.activity_rate < 1.0 {
+                arber.config.activity_rate
+            } else {
+                global_rate
+            };
+            if self.config.stochastic && self.rng.gen::<f64>() >= rate {
+                continue;
+            }
+            if !Self::admit(&mut self.block_space, || format!("arber_{}", i)) {
+                continue;
+            }
+            let actions = arber.act(&mut self.amm, external_price, block);
+            Self::fill(&mut self.block_space, &actions);
+            if let Some(gas) = &mut self.gas {
+                let price = self.amm.spot_price();
+                gas.charge(
+                    &actions,
+                    &mut arber.zec_balance,
+                    &mut arber.zai_balance,
+                    price,
+                );
+            }
+            if let Some(collector) = &mut self.agent_metrics {
+                for action in &actions {
+                    collector.note(&format!("arber_{}", i), action);
+                }
+            }
+        }
+        // (2a) The AMM and order book are arbitraged against each other
+        self.amm.arbitrage_order_book(block);
+    }
+
+    /// Take the block space used by `actions`.
+    fn fill(space: &mut Option<BlockSpace>, actions: &[AgentAction]) {
+        if let Some(space) = space {
+            space.fill(actions);
+        }
+    }
+
+    /// Intrablock sub-step: arbitrageurs trade at `external_price`, then the
+    /// liquidation pass runs. No other agents act and no metrics are recorded.
+    fn substep(&mut self, block: u64, external_price: f64) {
+        let external_price = self.effective_external_price(block, external_price);
+        if !self.breakers.is_halted(block) && !self.config.faults.is_halted(block) {
+            if let Some(graded) = self.breakers.graded_restrictions(block) {
+                self.amm.max_swap_fraction = Some(graded.max_swap_pct_of_reserve);
+                self.amm.lp_withdrawal_budget =
+                    Some(self.amm.total_lp_shares * graded.max_lp_withdrawal_pct_per_block);
+            }
+            if self.config.faults.is_amm_closed(block) {
+                self.amm.max_swap_fraction = Some(0.0);
+                self.amm.lp_withdrawal_budget = Some(0.0);
+            }
+            for (i, arber) in Self::in_order(&mut self.arbers, self.replaying) {
+                if !Self::admit(&mut self.block_space, || format!("arber_{}", i)) {
+                    continue;
+                }
+                let actions = arber.act(&mut self.amm, external_price, block);
+                Self::fill(&mut self.block_space, &actions);
+                if let Some(gas) = &mut self.gas {
+                    let price = self.amm.spot_price();
+                    gas.charge(
+                        &actions,
+                        &mut arber.zec_balance,
+                        &mut arber.zai_balance,
+                        price,
+                    );
+                }
+                if let Some(collector) = &mut self.agent_metrics {
+                    for action in &actions {
+                        collector.note(&format!("arber_{}", i), action);
+                    }
+                }
+            }
+            self.amm.max_swap_fraction = None;
+            self.amm.lp_withdrawal_budget = None;
+        }
+
+        let (total, graduated) = self.run_liquidations(block, external_price);
+        if let Some(gas) = &mut self.gas {
+            gas.charge_liquidations(total);
+        }
+        self.intrablock_liquidations.0 += total;
+        self.intrablock_liquidations.1 += graduated;
+    }
+
+    /// Liquidation pass (grace refresh, graduated, main mode and zombie
+    /// detection) at `external_price`. Returns `(total, graduated)` counts.
+    pub(crate) fn run_liquidations(&mut self, block: u64, external_price: f64) -> (u32, u32) {
+        // Nothing sells collateral while the chain is down or the AMM paused
+        if self.config.faults.is_amm_closed(block) {
+            return (0, 0);
+        }
+        let oracle_down = self.config.faults.is_oracle_down(block);
+
+        // Liquidations share the block's transaction capacity with agents
+        self.liquidation_engine.block_capacity =
+            self.block_space.as_ref().and_then(|s| s.liquidation_room());
+
+        // (5b) Refresh liquidation grace windows at this block's eligibility price
+        let eligibility_price = match &self.oracle {
+            Some(oracle) => oracle.price(),
+            None if self.config.use_external_oracle_for_liquidation => {
+                (!oracle_down).then_some(external_price)
+            }
+            None if self.config.use_amm_liquidation => Some(self.amm.spot_price()),
+            None => Some(self.registry.get_price(&self.amm)),
+        };
+        // Grace windows hold while the oracle is down
+        if let Some(price) = eligibility_price {
+            self.liquidation_engine.update_grace(&self.registry, price);
+        }
+
+        // (5c) Auto-deleveraging: while the system is thinly collateralized,
+        // the riskiest vaults are unwound before they become liquidatable
+        let targets = self.breakers.deleverage_targets(&self.registry, &self.amm);
+        let deleverage_results = if targets.is_empty() {
+            Vec::new()
+        } else {
+            self.liquidation_engine
+                .deleverage(&targets, &mut self.registry, &mut self.amm, block)
+        };
+        if let Some(breaker) = &mut self.breakers.auto_deleverage {
+            let ids: Vec<u64> = deleverage_results.iter().map(|r| r.vault_id).collect();
+            breaker.record(&ids);
+        }
+
+        // (6a) Graduated liquidation pass: partially liquidate warning-zone vaults
+        let graduated_results = if self.config.use_graduated_liquidation {
             self.liquidation_engine
                 .graduated_liquidate(&mut self.registry, &mut self.amm, block)
         } else {
@@ -311,13 +2545,42 @@ impl Scenario {
         };
 
         // (6b & 7) Liquidation engine scans and executes
-        let liq_results = if self.config.use_external_oracle_for_liquidation {
+        let liq_results = if self.liquidation_engine.config.keeper_liquidity.is_some() {
+            // Keepers buy collateral off-AMM at the eligibility price
+            match eligibility_price {
+                Some(price) => self.liquidation_engine.keeper_liquidate(
+                    &mut self.registry,
+                    &mut self.amm,
+                    block,
+                    price,
+                ),
+                None => Vec::new(),
+            }
+        } else if let Some(oracle) = &self.oracle {
+            // Configured oracle decides eligibility; collateral sells through the AMM
+            self.liquidation_engine.liquidate_with_oracle(
+                &mut self.registry,
+                &mut self.amm,
+                block,
+                oracle,
+            )
+        } else if self.config.use_external_oracle_for_liquidation && oracle_down {
+            // The external feed is down: eligibility can't be judged
+            Vec::new()
+        } else if self.config.use_external_oracle_for_liquidation {
             // Oracle mode: use external price for eligibility, sell through AMM
-            self.liquidation_engine
-                .oracle_liquidate(&mut self.registry, &mut self.amm, block, external_price)
+            self.liquidation_engine.oracle_liquidate(
+                &mut self.registry,
+                &mut self.amm,
+                block,
+                external_price,
+            )
         } else if self.config.use_amm_liquidation {
-            self.liquidation_engine
-                .cascading_spot_liquidate(&mut self.registry, &mut self.amm, block)
+            self.liquidation_engine.cascading_spot_liquidate(
+                &mut self.registry,
+                &mut self.amm,
+                block,
+            )
         } else {
             self.liquidation_engine
                 .transparent_liquidate(&mut self.registry, &mut self.amm, block)
@@ -335,29 +2598,681 @@ impl Scenario {
             Vec::new()
         };
 
-        let liq_count = (graduated_results.len() + liq_results.len() + zombie_liq_results.len()) as u32;
+        // Owners of vaults closed with collateral to spare get it back
+        for r in deleverage_results
+            .iter()
+            .chain(&graduated_results)
+            .chain(&liq_results)
+            .chain(&zombie_liq_results)
+            .filter(|r| r.collateral_returned > 0.0)
+        {
+            self.return_collateral(r.vault_id, &r.owner, r.collateral_returned);
+        }
+
+        // Keepers hedge part of the seized collateral on the external market
+        if let Some(feedback) = &self.config.price_feedback {
+            let seized: f64 = deleverage_results
+                .iter()
+                .chain(&graduated_results)
+                .chain(&liq_results)
+                .chain(&zombie_liq_results)
+                .map(|r| r.collateral_seized)
+                .sum();
+            self.external_zec_flow += seized * feedback.liquidation_external_share;
+        }
+
+        let total = graduated_results.len() + liq_results.len() + zombie_liq_results.len();
+        if let Some(space) = &mut self.block_space {
+            space.fill_count((total + deleverage_results.len()) as u32);
+        }
+        self.liquidation_engine.block_capacity = None;
+        (total as u32, graduated_results.len() as u32)
+    }
+
+    /// Execute a single block of the simulation, running any hooks.
+    pub fn step(&mut self, block: u64, external_price: f64) {
+        self.step_path(block, std::slice::from_ref(&external_price));
+    }
+
+    fn step_block(&mut self, block: u64, external_price: f64) {
+        let scripted_price = external_price;
+        let external_price = self.effective_external_price(block, scripted_price);
+        let breaker_halted = self.breakers.is_halted(block);
+        // A scheduled chain halt stops every agent, as a breaker halt does
+        let network_halted = self.config.faults.is_halted(block);
+        let halted = breaker_halted || network_halted;
+        let amm_paused = self.config.faults.is_amm_closed(block);
+        let minting_paused = self.breakers.is_minting_paused(block);
+        let partial_halted = self.breakers.is_partially_halted(block);
+        let redemption_price = self.controller.redemption_price;
+        let stochastic = self.config.stochastic;
+        let vault_churn_start = self
+            .vault_churn
+            .as_ref()
+            .map_or((0, 0), |c| (c.total_opened, c.total_closed));
+
+        // Graded halt: cap agent swaps and LP withdrawals for this block
+        if let Some(graded) = self.breakers.graded_restrictions(block) {
+            self.amm.max_swap_fraction = Some(graded.max_swap_pct_of_reserve);
+            self.amm.lp_withdrawal_budget =
+                Some(self.amm.total_lp_shares * graded.max_lp_withdrawal_pct_per_block);
+        }
+        if amm_paused {
+            self.amm.max_swap_fraction = Some(0.0);
+            self.amm.lp_withdrawal_budget = Some(0.0);
+        }
+
+        // (1) External price is provided as parameter
+
+        // (1a) Side pools and the order book are re-priced to it from outside
+        self.rebalance_pools(external_price);
+
+        // (1b) Lending market accrues interest; arbers refill inventory
+        if let Some(market) = &mut self.lending_market {
+            market.accrue(block);
+            let arbers: &mut [Arbitrageur] = if network_halted {
+                &mut []
+            } else {
+                &mut self.arbers
+            };
+            for (i, arber) in Self::in_order(arbers, self.replaying) {
+                let borrower = format!("arber_{}", i);
+                let actions = arber.manage_inventory(&borrower, market, block);
+                if let Some(gas) = &mut self.gas {
+                    let price = self.amm.spot_price();
+                    gas.charge(
+                        &actions,
+                        &mut arber.zec_balance,
+                        &mut arber.zai_balance,
+                        price,
+                    );
+                }
+                if let Some(collector) = &mut self.agent_metrics {
+                    for action in &actions {
+                        collector.note(&borrower, action);
+                    }
+                }
+            }
+        }
+
+        // (1c) Funding charge on ZAI balances at last block's rate
+        self.charge_funding();
+
+        // (1d) Unshields land and wallets rebalance against the shielded
+        // pool, and (1e) arbers' external capital crosses the bridge;
+        // nothing moves while the chain is down
+        if !network_halted {
+            self.settle_shielded(block);
+            self.settle_bridge(block, external_price);
+        }
+
+        // (2) Arbitrageurs trade; a re-mined block puts them last instead
+        if !halted && !self.replaying {
+            self.arbitrage(block, external_price);
+        }
+
+        // (3) Waiting vaults open as the ceiling allows, then CDP holders act
+        if !halted {
+            if !minting_paused {
+                self.admit_waiting_vaults(block);
+            }
+            self.churn_vaults(block, minting_paused);
+            for (i, holder) in Self::in_order(&mut self.cdp_holders, self.replaying) {
+                if !Self::admit(&mut self.block_space, || format!("cdp_holder_{}", i)) {
+                    continue;
+                }
+                let transactions = holder.transactions();
+                let total_debt = self.registry.total_debt;
+                let breakers = &self.breakers;
+                let can_borrow = |amount: f64| {
+                    !minting_paused
+                        && breakers.debt_ceiling.can_mint(total_debt, amount)
+                        && breakers.mint_allowed(total_debt, amount)
+                };
+                let mut action =
+                    holder.act_with_amm(&mut self.registry, &mut self.amm, can_borrow, block);
+                let exit = holder.self_liquidate_if_rational(
+                    &mut self.liquidation_engine,
+                    &mut self.registry,
+                    &mut self.amm,
+                    block,
+                );
+                if !matches!(exit, AgentAction::None) {
+                    action = exit;
+                }
+                // Only top-ups, repayments, re-borrows and self-liquidations
+                // go on chain; holders pay their fees at the gas price
+                // themselves
+                let sent = holder.transactions() - transactions;
+                if let Some(space) = &mut self.block_space {
+                    space.fill_count(sent);
+                }
+                if let Some(gas) = &mut self.gas {
+                    for _ in 0..sent {
+                        gas.record(holder.topup_policy.tx_fee_zec);
+                    }
+                }
+                if let Some(collector) = &mut self.agent_metrics {
+                    collector.note(&format!("cdp_holder_{}", i), &action);
+                }
+            }
+            // Portfolio owners defend their riskiest vaults first
+            for (i, portfolio) in Self::in_order(&mut self.vault_portfolios, self.replaying) {
+                if !Self::admit(&mut self.block_space, || format!("portfolio_{}", i)) {
+                    continue;
+                }
+                let transactions = portfolio.topups + portfolio.merges;
+                let action = portfolio.act(&mut self.registry, &self.amm, block);
+                if let Some(space) = &mut self.block_space {
+                    space.fill_count(portfolio.topups + portfolio.merges - transactions);
+                }
+                if let Some(collector) = &mut self.agent_metrics {
+                    collector.note(&format!("portfolio_{}", i), &action);
+                }
+            }
+        }
+
+        // (4) Users join and leave along the adoption curve, then demand
+        // agents act
+        if !halted {
+            self.adopt_users(block, redemption_price);
+            let jitter = self.config.demand_jitter_blocks;
+            let holding_cost = self.funding_rate.as_ref().map_or(0.0, |f| f.holding_cost());
+            for (i, demand) in Self::in_order(&mut self.demand_agents, self.replaying) {
+                // Stochastic: skip with probability jitter/(jitter+20)
+                if stochastic && self.rng.gen_range(0..jitter + 20) < jitter {
+                    continue;
+                }
+                if !Self::admit(&mut self.block_space, || format!("demand_{}", i)) {
+                    continue;
+                }
+                let action =
+                    demand.act_with_carry(&mut self.amm, redemption_price, holding_cost, block);
+                Self::fill(&mut self.block_space, std::slice::from_ref(&action));
+                if let Some(gas) = &mut self.gas {
+                    let price = self.amm.spot_price();
+                    gas.charge(
+                        std::slice::from_ref(&action),
+                        &mut demand.zec_balance,
+                        &mut demand.zai_balance,
+                        price,
+                    );
+                }
+                if let Some(collector) = &mut self.agent_metrics {
+                    collector.note(&format!("demand_{}", i), &action);
+                }
+            }
+        }
+
+        // (4b) Miners act
+        if !halted {
+            if stochastic && !self.miner_sell_countdowns.is_empty() {
+                let mut order: Vec<usize> = (0..self.miners.len()).collect();
+                if self.replaying {
+                    order.reverse();
+                }
+                for i in order {
+                    // Always receive block reward
+                    self.miners[i].zec_balance += self.miners[i].config.reward_at(block);
+
+                    self.miner_sell_countdowns[i] =
+                        self.miner_sell_countdowns[i].saturating_sub(1);
+                    if self.miner_sell_countdowns[i] == 0 {
+                        // A full block holds the sale over to the next
+                        if !Self::admit(&mut self.block_space, || format!("miner_{}", i)) {
+                            continue;
+                        }
+                        // Batch sell accumulated ZEC
+                        let sell_frac = self.miners[i].config.miner_sell_fraction;
+                        let amm_frac = self.miners[i].config.miner_amm_fraction;
+                        let scale = self.miners[i].volume_scale.unwrap_or(1.0);
+                        let sell_amount =
+                            (self.miners[i].zec_balance * sell_frac * amm_frac * scale)
+                                .min(self.miners[i].zec_balance);
+                        if sell_amount > 0.001 {
+                            if let Ok(zai_out) = self.amm.sell_zec(sell_amount, block) {
+                                let miner = &mut self.miners[i];
+                                miner.zec_balance -= sell_amount;
+                                miner.zai_balance += zai_out;
+                                if let Some(space) = &mut self.block_space {
+                                    space.fill_count(1);
+                                }
+                                if let Some(gas) = &mut self.gas {
+                                    let price = self.amm.spot_price();
+                                    gas.pay(&mut miner.zec_balance, &mut miner.zai_balance, price);
+                                }
+                                if let Some(collector) = &mut self.agent_metrics {
+                                    let action = AgentAction::MinerSell {
+                                        zec_sold: sell_amount,
+                                        zai_received: zai_out,
+                                    };
+                                    collector.note(&format!("miner_{}", i), &action);
+                                }
+                            }
+                        }
+                        let bw = self.config.miner_batch_window;
+                        self.miner_sell_countdowns[i] = self.rng.gen_range(1..=bw);
+                    }
+                }
+            } else {
+                for (i, miner) in Self::in_order(&mut self.miners, self.replaying) {
+                    // The block reward needs no block space; the sale does
+                    if !Self::admit(&mut self.block_space, || format!("miner_{}", i)) {
+                        miner.zec_balance += miner.config.reward_at(block);
+                        continue;
+                    }
+                    let action = miner.act(&mut self.amm, block);
+                    Self::fill(&mut self.block_space, std::slice::from_ref(&action));
+                    if let Some(gas) = &mut self.gas {
+                        let price = self.amm.spot_price();
+                        gas.charge(
+                            std::slice::from_ref(&action),
+                            &mut miner.zec_balance,
+                            &mut miner.zai_balance,
+                            price,
+                        );
+                    }
+                    if let Some(collector) = &mut self.agent_metrics {
+                        collector.note(&format!("miner_{}", i), &action);
+                    }
+                }
+            }
+
+            // The share of miner sales not routed through the AMM goes to
+            // external markets
+            if self.external_market.is_some() {
+                self.external_zec_flow += self
+                    .miners
+                    .iter()
+                    .map(|m| {
+                        m.config.reward_at(block)
+                            * m.config.miner_sell_fraction
+                            * (1.0 - m.config.miner_amm_fraction)
+                    })
+                    .sum::<f64>();
+            }
+        }
+
+        // (4c) LPs act
+        if !halted {
+            for (i, lp) in Self::in_order(&mut self.lp_agents, self.replaying) {
+                if !Self::admit(&mut self.block_space, || format!("lp_{}", i)) {
+                    continue;
+                }
+                let action = lp.act(&mut self.amm);
+                Self::fill(&mut self.block_space, std::slice::from_ref(&action));
+                if let Some(gas) = &mut self.gas {
+                    let price = self.amm.spot_price();
+                    gas.charge(
+                        std::slice::from_ref(&action),
+                        &mut lp.zec_balance,
+                        &mut lp.zai_balance,
+                        price,
+                    );
+                }
+                if let Some(collector) = &mut self.agent_metrics {
+                    collector.note(&format!("lp_{}", i), &action);
+                }
+            }
+            for (i, lp) in Self::in_order(&mut self.il_aware_lps, self.replaying) {
+                if !Self::admit(&mut self.block_space, || format!("il_lp_{}", i)) {
+                    continue;
+                }
+                let action = lp.act(&mut self.amm, external_price);
+                Self::fill(&mut self.block_space, std::slice::from_ref(&action));
+                // Paid from the withdrawal it is charged for
+                if let Some(gas) = &mut self.gas {
+                    let price = self.amm.spot_price();
+                    gas.charge(
+                        std::slice::from_ref(&action),
+                        &mut lp.withdrawn_zec,
+                        &mut lp.withdrawn_zai,
+                        price,
+                    );
+                }
+                if let Some(collector) = &mut self.agent_metrics {
+                    collector.note(&format!("il_lp_{}", i), &action);
+                }
+            }
+        }
+
+        // (4d) Stability fee routing to LPs
+        let mut fees_to_lps = 0.0;
+        if self.config.stability_fee_to_lps {
+            let fee_delta = self.registry.accrue_all_fees(block);
+            fees_to_lps = fee_delta;
+            if fee_delta > 0.0 {
+                self.amm.reserve_zai += fee_delta;
+                self.amm.k = self.amm.reserve_zec * self.amm.reserve_zai;
+                self.amm.cumulative_fees_zai += fee_delta;
+            }
+        }
+
+        // (4e) Redeemers act; owners of vaults they close get the leftover
+        // collateral back
+        if !halted {
+            let redemptions = self.liquidation_engine.redemption_history.len();
+            for (i, redeemer) in Self::in_order(&mut self.redeemers, self.replaying) {
+                if !Self::admit(&mut self.block_space, || format!("redeemer_{}", i)) {
+                    continue;
+                }
+                let action = redeemer.act(
+                    &mut self.amm,
+                    &mut self.registry,
+                    &mut self.liquidation_engine,
+                    redemption_price,
+                    block,
+                );
+                Self::fill(&mut self.block_space, std::slice::from_ref(&action));
+                if let Some(gas) = &mut self.gas {
+                    let price = self.amm.spot_price();
+                    gas.charge(
+                        std::slice::from_ref(&action),
+                        &mut redeemer.zec_balance,
+                        &mut redeemer.zai_balance,
+                        price,
+                    );
+                }
+                if let Some(collector) = &mut self.agent_metrics {
+                    collector.note(&format!("redeemer_{}", i), &action);
+                }
+            }
+            self.return_redeemed_collateral(redemptions);
+        }
+
+        // (4f) Basis traders bet on controller-driven convergence to redemption price
+        if !halted {
+            let redemption_rate = self.controller.redemption_rate;
+            for (i, trader) in Self::in_order(&mut self.basis_traders, self.replaying) {
+                if !Self::admit(&mut self.block_space, || format!("basis_{}", i)) {
+                    continue;
+                }
+                let action = trader.act(&mut self.amm, redemption_price, redemption_rate, block);
+                Self::fill(&mut self.block_space, std::slice::from_ref(&action));
+                if let Some(gas) = &mut self.gas {
+                    let price = self.amm.spot_price();
+                    gas.charge(
+                        std::slice::from_ref(&action),
+                        &mut trader.zec_balance,
+                        &mut trader.zai_balance,
+                        price,
+                    );
+                }
+                if let Some(collector) = &mut self.agent_metrics {
+                    collector.note(&format!("basis_{}", i), &action);
+                }
+            }
+        }
+
+        // (4g) Savers move ZAI between the AMM and the savings module
+        if !halted {
+            if let Some(savings) = &mut self.savings {
+                for (i, saver) in Self::in_order(&mut self.savers, self.replaying) {
+                    if !Self::admit(&mut self.block_space, || format!("saver_{}", i)) {
+                        continue;
+                    }
+                    let action = saver.act(&mut self.amm, savings, redemption_price, block);
+                    Self::fill(&mut self.block_space, std::slice::from_ref(&action));
+                    if let Some(gas) = &mut self.gas {
+                        let price = self.amm.spot_price();
+                        gas.charge(
+                            std::slice::from_ref(&action),
+                            &mut saver.zec_balance,
+                            &mut saver.zai_balance,
+                            price,
+                        );
+                    }
+                    if let Some(collector) = &mut self.agent_metrics {
+                        collector.note(&format!("saver_{}", i), &action);
+                    }
+                }
+            }
+        }
+
+        // (4h) Noise traders trade on noise, trend and last block's crowd
+        if !halted && !self.noise_traders.is_empty() {
+            let herd = self.noise_herd;
+            for (i, trader) in Self::in_order(&mut self.noise_traders, self.replaying) {
+                if !Self::admit(&mut self.block_space, || format!("noise_{}", i)) {
+                    continue;
+                }
+                let action = trader.act(&mut self.amm, herd, block);
+                Self::fill(&mut self.block_space, std::slice::from_ref(&action));
+                if let Some(gas) = &mut self.gas {
+                    let price = self.amm.spot_price();
+                    gas.charge(
+                        std::slice::from_ref(&action),
+                        &mut trader.zec_balance,
+                        &mut trader.zai_balance,
+                        price,
+                    );
+                }
+                if let Some(collector) = &mut self.agent_metrics {
+                    collector.note(&format!("noise_{}", i), &action);
+                }
+            }
+            self.noise_herd = self
+                .noise_traders
+                .iter()
+                .map(|t| t.last_direction)
+                .sum::<f64>()
+                / self.noise_traders.len() as f64;
+        }
+
+        // (4i) Basis arbitrageurs hedge AMM spot against the perp; funding
+        // is paid off-chain every interval, halted or not
+        if let Some(perp) = &mut self.perp {
+            let funding_rate = perp.funding_rate(block);
+            if !halted {
+                for (i, arb) in Self::in_order(&mut self.basis_arbs, self.replaying) {
+                    if !Self::admit(&mut self.block_space, || format!("basis_arb_{}", i)) {
+                        continue;
+                    }
+                    let action = arb.act(&mut self.amm, funding_rate, external_price, block);
+                    Self::fill(&mut self.block_space, std::slice::from_ref(&action));
+                    if let Some(gas) = &mut self.gas {
+                        let price = self.amm.spot_price();
+                        gas.charge(
+                            std::slice::from_ref(&action),
+                            &mut arb.zec_balance,
+                            &mut arb.zai_balance,
+                            price,
+                        );
+                    }
+                    if let Some(collector) = &mut self.agent_metrics {
+                        collector.note(&format!("basis_arb_{}", i), &action);
+                    }
+                }
+            }
+            if perp.is_funding_block(block) {
+                for arb in &mut self.basis_arbs {
+                    perp.total_funding_zai += arb.receive_funding(funding_rate, external_price);
+                }
+            }
+            perp.arb_short_zec = -self.basis_arbs.iter().map(|a| a.perp_zec).sum::<f64>();
+        }
+
+        // (4j) Attackers act, borrowing capital from the lending market if needed
+        let attackers: &mut [Attacker] = if network_halted {
+            &mut []
+        } else {
+            &mut self.attackers
+        };
+        for (i, attacker) in Self::in_order(attackers, self.replaying) {
+            let borrower = format!("attacker_{}", i);
+            if !Self::admit(&mut self.block_space, || borrower.clone()) {
+                continue;
+            }
+            let mut actions = Vec::new();
+            if let Some(market) = &mut self.lending_market {
+                actions.push(attacker.settle_funding(&borrower, market, block));
+            }
+            let zec_before = attacker.zec_balance;
+            let action = attacker.act(&mut self.amm, block);
+            // Exiting: ZEC bought back on the AMM is cashed out externally
+            if matches!(&action, AgentAction::AttackSwap { direction, .. } if direction == "buy_zec")
+            {
+                self.external_zec_flow += attacker.zec_balance - zec_before;
+            }
+            actions.push(action);
+            if let Some(market) = &mut self.lending_market {
+                actions.push(attacker.settle_funding(&borrower, market, block));
+            }
+            Self::fill(&mut self.block_space, &actions);
+            if let Some(gas) = &mut self.gas {
+                let price = self.amm.spot_price();
+                gas.charge(
+                    &actions,
+                    &mut attacker.zec_balance,
+                    &mut attacker.zai_balance,
+                    price,
+                );
+            }
+            if let Some(collector) = &mut self.agent_metrics {
+                for action in &actions {
+                    collector.note(&borrower, action);
+                }
+            }
+        }
+
+        // (4k) On a re-mined block arbitrageurs close the gap left by
+        // everyone else
+        if !halted && self.replaying {
+            self.arbitrage(block, external_price);
+        }
+
+        // Liquidations are never capped by graded-halt restrictions; a
+        // paused AMM stays closed to the treasury and POL too
+        self.amm.max_swap_fraction = amm_paused.then_some(0.0);
+        self.amm.lp_withdrawal_budget = amm_paused.then_some(0.0);
+
+        // (5) AMM records price for TWAP
+        self.amm.record_price(block);
+
+        // (5a) Oracle reports this block's price; intrablock sub-steps use
+        // the previous report
+        if let Some(oracle) = &mut self.oracle {
+            oracle.update(&OracleInputs {
+                block,
+                external_price,
+                amm: &self.amm,
+            });
+        }
+
+        // (5b–7) Liquidations, plus any already run in intrablock sub-steps
+        let (liq_count, graduated_count) = self.run_liquidations(block, external_price);
+        if let Some(gas) = &mut self.gas {
+            gas.charge_liquidations(liq_count);
+        }
+        let (wick_count, wick_graduated) = std::mem::take(&mut self.intrablock_liquidations);
+        let liq_count = liq_count + wick_count;
+        let graduated_count = graduated_count + wick_graduated;
 
         // Record liquidations for cascade breaker
         self.breakers.record_liquidations(block, liq_count);
 
-        // (8) Controller updates redemption rate
+        // (7b) Treasury collects surplus, absorbs bad debt, auctions any shortfall
+        self.treasury
+            .sync(&self.registry, &self.liquidation_engine, fees_to_lps);
+        self.treasury.run_debt_auction(&mut self.amm, block);
+
+        // (7c) Protocol-owned liquidity takes its cut of the new surplus and
+        // deploys it into the AMM
+        if let Some(pol) = &mut self.protocol_liquidity {
+            pol.accrue_fees(&self.amm);
+            pol.fund(&mut self.treasury);
+            if !halted {
+                pol.rebalance(&mut self.amm, external_price, block);
+            }
+        }
+
+        // (7d) Savers are paid interest from what surplus remains
+        if let Some(savings) = &mut self.savings {
+            savings.accrue(&mut self.treasury, block);
+        }
+
+        // (8) Controller updates redemption rate, held to the drift limit;
+        // the savings rate follows it
         let market_price = self.amm.spot_price();
         self.controller.update(market_price, block);
+        let (redemption_price, drift_action) = self
+            .breakers
+            .limit_redemption_drift(self.controller.redemption_price, block);
+        self.controller.redemption_price = redemption_price;
+        if let Some(savings) = &mut self.savings {
+            savings.set_rate(self.controller.redemption_rate);
+        }
+        if let Some(funding) = &mut self.funding_rate {
+            funding.update(self.controller.redemption_rate);
+        }
 
         // (9) Circuit breaker checks
-        let breaker_actions = self.breakers.check_all(
+        let mut breaker_actions = self.breakers.check_all_with_oracle(
             &self.amm,
             &self.registry,
             self.controller.redemption_price,
             block,
+            self.oracle.as_ref().map(|o| o as &dyn Oracle),
         );
+        if drift_action != BreakerAction::None {
+            breaker_actions.insert(0, drift_action);
+        }
+        for action in breaker_actions
+            .iter()
+            .filter(|a| **a != BreakerAction::None)
+        {
+            tracing::info!(?action, "circuit breaker tripped");
+        }
+
+        // (9a) The ceiling policy moves the debt ceiling
+        if let Some(policy) = &mut self.ceiling_policy {
+            let price = self.registry.get_price(&self.amm);
+            let inputs = CeilingInputs {
+                amm_depth_zai: self.amm.reserve_zai,
+                total_debt: self.registry.total_debt,
+                collateral_value: self
+                    .registry
+                    .vaults
+                    .values()
+                    .map(|v| v.collateral_zec)
+                    .sum::<f64>()
+                    * price,
+                total_bad_debt: self.liquidation_engine.total_bad_debt,
+            };
+            policy.adjust(&mut self.breakers.debt_ceiling, &inputs);
+        }
+
+        // (9b) This block's external ZEC sales move the external price
+        let external_zec_flow = std::mem::take(&mut self.external_zec_flow);
+        if let Some(market) = &mut self.external_market {
+            if external_zec_flow > 0.0 {
+                market.sell_zec(external_zec_flow, scripted_price, block);
+            }
+        }
+
+        self.amm.max_swap_fraction = None;
+        self.amm.lp_withdrawal_budget = None;
 
         // (10) Record metrics
+        let pol = self.protocol_liquidity.as_ref();
+        let lp_cohorts = self.lp_attribution.observe(
+            &self.amm,
+            self.liquidation_engine.total_penalties_to_lps,
+            block,
+        );
+        let lp_pool = pool_totals(&lp_cohorts);
+        let deleveraged_vaults = breaker_actions
+            .iter()
+            .map(|a| match a {
+                BreakerAction::AutoDeleverage { vault_ids, .. } => vault_ids.len(),
+                _ => 0,
+            })
+            .sum();
         let mut metrics = BlockMetrics {
             block,
             external_price,
             amm_spot_price: self.amm.spot_price(),
-            twap_price: self.amm.get_twap(self.registry.config.twap_window),
+            twap_price: self.registry.get_price(&self.amm),
             redemption_price: self.controller.redemption_price,
             redemption_rate: self.controller.redemption_rate,
             total_debt: self.registry.total_debt,
@@ -369,7 +3284,7 @@ impl Scenario {
             breaker_actions,
             debt_ceiling: self.breakers.debt_ceiling.current_ceiling,
             minting_paused,
-            halted,
+            halted: breaker_halted,
             total_collateral: self
                 .registry
                 .vaults
@@ -385,11 +3300,110 @@ impl Scenario {
             arber_zec_total: self.arbers.iter().map(|a| a.zec_balance).sum::<f64>(),
             cumulative_fees_zai: self.amm.cumulative_fees_zai,
             cumulative_il_pct: self.amm.impermanent_loss(self.config.initial_redemption_price),
-            graduated_liquidation_count: graduated_results.len() as u32,
+            graduated_liquidation_count: graduated_count,
+            partial_halted,
+            cumulative_redeemed_zai: self.liquidation_engine.total_redeemed_zai,
+            treasury_balance: self.treasury.balance_zai,
+            uncovered_bad_debt: self.treasury.uncovered_bad_debt,
+            lending_zec_utilization: self
+                .lending_market
+                .as_ref()
+                .map_or(0.0, |m| m.pool(LendingAsset::Zec).utilization()),
+            lending_zec_borrow_rate: self
+                .lending_market
+                .as_ref()
+                .map_or(0.0, |m| m.pool(LendingAsset::Zec).borrow_rate()),
+            vaults_in_grace: self.liquidation_engine.vaults_in_grace(block),
+            penalty_to_keepers: self.liquidation_engine.total_keeper_rewards,
+            penalty_to_lps: self.liquidation_engine.total_penalties_to_lps,
+            penalty_to_insurance: self.liquidation_engine.total_penalties_to_insurance,
+            penalty_to_treasury: self.liquidation_engine.total_penalties_collected,
+            penalty_burned: self.liquidation_engine.total_penalties_burned,
+            insurance_fund_balance: self.treasury.insurance_fund_zai,
+            external_price_impact: self.external_market.as_ref().map_or(0.0, |m| m.impact),
+            oracle_price: self.oracle.as_ref().and_then(|o| o.price()),
+            swap_fee: self.amm.swap_fee,
+            side_pool_prices: self.amm.pools.iter().map(|p| p.spot_price()).collect(),
+            order_book_bid_depth: self.amm.order_book.as_ref().map(|b| b.bid_depth_zec()),
+            order_book_ask_depth: self.amm.order_book.as_ref().map(|b| b.ask_depth_zec()),
+            pol_pool_share: pol.map_or(0.0, |p| p.pool_share(&self.amm)),
+            pol_value_zai: pol.map_or(0.0, |p| p.value_zai(&self.amm)),
+            pol_fees_earned_zai: pol.map_or(0.0, |p| p.fees_earned_zai),
+            lp_fee_apr: lp_pool.fee_apr,
+            lp_penalties_zai: lp_pool.penalties_zai,
+            lp_il_zai: lp_pool.il_zai,
+            lp_net_return_zai: lp_pool.net_return_zai,
+            lp_cohorts,
+            savings_rate: self.savings.as_ref().map_or(0.0, |s| s.rate),
+            savings_deposits_zai: self
+                .savings
+                .as_ref()
+                .map_or(0.0, |s| s.total_deposits_zai()),
+            savings_interest_paid_zai: self
+                .savings
+                .as_ref()
+                .map_or(0.0, |s| s.total_interest_paid_zai),
+            funding_rate: self.funding_rate.as_ref().map_or(0.0, |f| f.rate),
+            funding_charged_zai: self
+                .funding_rate
+                .as_ref()
+                .map_or(0.0, |f| f.total_charged_zai),
+            gas_fee_zec: self.gas.as_ref().map_or(0.0, |g| g.fee_zec),
+            gas_paid_zec: self
+                .gas
+                .as_ref()
+                .map_or(0.0, |g| g.total_fees_zec + g.keeper_fees_zec),
+            block_time_secs: self.block_space.as_ref().map_or(0.0, |s| s.block_time_secs),
+            deferred_agents: self
+                .block_space
+                .as_ref()
+                .map_or(0, |s| s.deferred.len() as u32),
+            reorgs: self.reorg.as_ref().map_or(0, |r| r.events.len() as u32),
+            network_halted,
+            amm_paused,
+            oracle_outage: self.config.faults.is_oracle_down(block),
+            shielded_zec: self.shielded_pool.as_ref().map_or(0.0, |p| p.totals().0),
+            shielded_zai: self.shielded_pool.as_ref().map_or(0.0, |p| p.totals().1),
+            bridge_outstanding_zai: self
+                .bridge
+                .as_ref()
+                .map_or(0.0, |b| b.outstanding_zai(external_price)),
+            demand_users: self.demand_agents.len(),
+            debt_ceiling_utilization: if self.breakers.debt_ceiling.current_ceiling > 0.0 {
+                self.registry.total_debt / self.breakers.debt_ceiling.current_ceiling
+            } else {
+                0.0
+            },
+            vaults_waiting: self.ceiling_policy.as_ref().map_or(0, |p| p.waiting.len()),
+            redemption_capped: self
+                .breakers
+                .redemption_limiter
+                .as_ref()
+                .is_some_and(|l| l.is_capping()),
+            deleveraged_vaults,
+            self_liquidations: self
+                .liquidation_engine
+                .history
+                .iter()
+                .rev()
+                .take_while(|r| r.block == block)
+                .filter(|r| r.mode == LiquidationMode::SelfLiquidation)
+                .count(),
+            vault_arrivals: self
+                .vault_churn
+                .as_ref()
+                .map_or(0, |c| (c.total_opened - vault_churn_start.0) as usize),
+            vault_closures: self
+                .vault_churn
+                .as_ref()
+                .map_or(0, |c| (c.total_closed - vault_churn_start.1) as usize),
+            dust_vaults: self.registry.dust_vault_count(),
+            perp_funding_rate: self.perp.as_ref().map_or(0.0, |p| p.funding_rate(block)),
+            basis_arb_perp_zec: self.basis_arbs.iter().map(|a| a.perp_zec).sum(),
         };
 
         // Compute zombie vault metrics
-        let twap = self.amm.get_twap(self.registry.config.twap_window);
+        let twap = self.registry.get_price(&self.amm);
         let min_ratio = self.registry.config.min_ratio;
         let mut zombie_count = 0u32;
         let mut max_gap = 0.0f64;
@@ -399,8 +3413,8 @@ impl Scenario {
 
         for vault in self.registry.vaults.values() {
             if vault.debt_zai > 0.0 {
-                let twap_ratio = vault.collateral_ratio(twap);
-                let ext_ratio = vault.collateral_ratio(external_price);
+                let twap_ratio = self.registry.ratio_due(vault, twap);
+                let ext_ratio = self.registry.ratio_due(vault, external_price);
                 twap_ratios_sum += twap_ratio;
                 ext_ratios_sum += ext_ratio;
                 vault_with_debt += 1;
@@ -423,77 +3437,233 @@ impl Scenario {
         metrics.max_zombie_gap = max_gap;
 
         self.metrics.push(metrics);
+
+        // (11) Periodic state snapshot
+        let k = self.config.snapshot_interval;
+        if k > 0 && block.is_multiple_of(k) {
+            let snapshot = StateSnapshot::capture(self, block);
+            self.snapshots.push(snapshot);
+        }
+
+        // (12) Per-agent time series
+        if let Some(mut collector) = self.agent_metrics.take() {
+            collector.record(self, block);
+            self.agent_metrics = Some(collector);
+        }
+
+        // (13) Governance reacts to this block; passed proposals take
+        // effect after the voting delay
+        if !self.governance_agents.is_empty() {
+            if let Some(m) = self.metrics.last() {
+                for agent in &mut self.governance_agents {
+                    agent.observe(&m);
+                }
+            }
+        }
     }
 
     /// Export metrics to CSV.
-    pub fn save_metrics_csv(
-        &self,
-        path: &std::path::Path,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn save_metrics_csv(&self, path: &std::path::Path) -> Result<(), ZaiSimError> {
         if let Some(parent) = path.parent() {
             std::fs::create_dir_all(parent)?;
         }
         let mut wtr = csv::Writer::from_path(path)?;
-        wtr.write_record([
-            "block",
-            "external_price",
-            "amm_spot_price",
-            "twap_price",
-            "redemption_price",
-            "redemption_rate",
-            "total_debt",
-            "reserve_zec",
-            "reserve_zai",
-            "vault_count",
-            "liquidations",
-            "bad_debt",
-            "debt_ceiling",
-            "minting_paused",
-            "halted",
-            "total_collateral",
-            "total_lp_shares",
-            "arber_zai_total",
-            "zombie_vault_count",
-            "max_zombie_gap",
-            "mean_cr_twap",
-            "mean_cr_ext",
-            "arber_zec_total",
-            "cumulative_fees_zai",
-            "cumulative_il_pct",
-            "graduated_liquidations",
-        ])?;
-
+        // One column group per LP cohort seen in the run
+        let mut cohorts: Vec<&str> = Vec::new();
+        for c in self.metrics.lp_cohorts.iter().flatten() {
+            if !cohorts.contains(&c.cohort.as_str()) {
+                cohorts.push(&c.cohort);
+            }
+        }
+        wtr.write_record(metrics_csv_header(&cohorts))?;
         for m in &self.metrics {
-            wtr.write_record(&[
-                m.block.to_string(),
-                format!("{:.4}", m.external_price),
-                format!("{:.4}", m.amm_spot_price),
-                format!("{:.4}", m.twap_price),
-                format!("{:.6}", m.redemption_price),
-                format!("{:.12}", m.redemption_rate),
-                format!("{:.2}", m.total_debt),
-                format!("{:.2}", m.amm_reserve_zec),
-                format!("{:.2}", m.amm_reserve_zai),
-                m.vault_count.to_string(),
-                m.liquidation_count.to_string(),
-                format!("{:.2}", m.bad_debt),
-                format!("{:.0}", m.debt_ceiling),
-                m.minting_paused.to_string(),
-                m.halted.to_string(),
-                format!("{:.2}", m.total_collateral),
-                format!("{:.2}", m.total_lp_shares),
-                format!("{:.2}", m.arber_zai_total),
-                m.zombie_vault_count.to_string(),
-                format!("{:.4}", m.max_zombie_gap),
-                format!("{:.4}", m.mean_collateral_ratio_twap),
-                format!("{:.4}", m.mean_collateral_ratio_ext),
-                format!("{:.2}", m.arber_zec_total),
-                format!("{:.2}", m.cumulative_fees_zai),
-                format!("{:.6}", m.cumulative_il_pct),
-                m.graduated_liquidation_count.to_string(),
-            ])?;
+            wtr.write_record(metrics_csv_row(&m, &cohorts))?;
         }
         wtr.flush()?;
         Ok(())
     }
 }
+
+/// `timeseries.csv` columns, before one group of `LP_COHORT_COLUMNS` per
+/// LP cohort.
+pub(crate) const METRICS_CSV_COLUMNS: &[&str] = &[
+    "block",
+    "external_price",
+    "amm_spot_price",
+    "twap_price",
+    "redemption_price",
+    "redemption_rate",
+    "total_debt",
+    "reserve_zec",
+    "reserve_zai",
+    "vault_count",
+    "liquidations",
+    "bad_debt",
+    "debt_ceiling",
+    "minting_paused",
+    "halted",
+    "total_collateral",
+    "total_lp_shares",
+    "arber_zai_total",
+    "zombie_vault_count",
+    "max_zombie_gap",
+    "mean_cr_twap",
+    "mean_cr_ext",
+    "arber_zec_total",
+    "cumulative_fees_zai",
+    "cumulative_il_pct",
+    "graduated_liquidations",
+    "partial_halted",
+    "cumulative_redeemed_zai",
+    "treasury_balance",
+    "uncovered_bad_debt",
+    "lending_zec_utilization",
+    "lending_zec_borrow_rate",
+    "vaults_in_grace",
+    "penalty_to_keepers",
+    "penalty_to_lps",
+    "penalty_to_insurance",
+    "penalty_to_treasury",
+    "penalty_burned",
+    "insurance_fund_balance",
+    "lp_fee_apr",
+    "lp_penalties_zai",
+    "lp_il_zai",
+    "lp_net_return_zai",
+    "savings_rate",
+    "savings_deposits_zai",
+    "savings_interest_paid_zai",
+    "funding_rate",
+    "funding_charged_zai",
+    "gas_fee_zec",
+    "gas_paid_zec",
+    "block_time_secs",
+    "deferred_agents",
+    "reorgs",
+    "network_halted",
+    "amm_paused",
+    "oracle_outage",
+    "shielded_zec",
+    "shielded_zai",
+    "bridge_outstanding_zai",
+    "demand_users",
+    "debt_ceiling_utilization",
+    "vaults_waiting",
+    "redemption_capped",
+    "deleveraged_vaults",
+    "self_liquidations",
+    "vault_arrivals",
+    "vault_closures",
+    "dust_vaults",
+    "perp_funding_rate",
+    "basis_arb_perp_zec",
+];
+
+/// Columns written for each LP cohort, as `lp_<cohort>_<column>`.
+pub(crate) const LP_COHORT_COLUMNS: [&str; 5] = [
+    "fee_apr",
+    "fees_zai",
+    "penalties_zai",
+    "il_zai",
+    "net_return_zai",
+];
+
+/// `timeseries.csv` header for the given LP `cohorts`.
+pub(crate) fn metrics_csv_header<S: AsRef<str>>(cohorts: &[S]) -> Vec<String> {
+    let mut header: Vec<String> = METRICS_CSV_COLUMNS.iter().map(|s| s.to_string()).collect();
+    for cohort in cohorts {
+        for column in LP_COHORT_COLUMNS {
+            header.push(format!("lp_{}_{}", cohort.as_ref(), column));
+        }
+    }
+    header
+}
+
+/// One `timeseries.csv` row; cohorts missing from the block are left blank.
+pub(crate) fn metrics_csv_row<S: AsRef<str>>(m: &BlockMetrics, cohorts: &[S]) -> Vec<String> {
+    let mut row = vec![
+        m.block.to_string(),
+        format!("{:.4}", m.external_price),
+        format!("{:.4}", m.amm_spot_price),
+        format!("{:.4}", m.twap_price),
+        format!("{:.6}", m.redemption_price),
+        format!("{:.12}", m.redemption_rate),
+        format!("{:.2}", m.total_debt),
+        format!("{:.2}", m.amm_reserve_zec),
+        format!("{:.2}", m.amm_reserve_zai),
+        m.vault_count.to_string(),
+        m.liquidation_count.to_string(),
+        format!("{:.2}", m.bad_debt),
+        format!("{:.0}", m.debt_ceiling),
+        m.minting_paused.to_string(),
+        m.halted.to_string(),
+        format!("{:.2}", m.total_collateral),
+        format!("{:.2}", m.total_lp_shares),
+        format!("{:.2}", m.arber_zai_total),
+        m.zombie_vault_count.to_string(),
+        format!("{:.4}", m.max_zombie_gap),
+        format!("{:.4}", m.mean_collateral_ratio_twap),
+        format!("{:.4}", m.mean_collateral_ratio_ext),
+        format!("{:.2}", m.arber_zec_total),
+        format!("{:.2}", m.cumulative_fees_zai),
+        format!("{:.6}", m.cumulative_il_pct),
+        m.graduated_liquidation_count.to_string(),
+        m.partial_halted.to_string(),
+        format!("{:.2}", m.cumulative_redeemed_zai),
+        format!("{:.2}", m.treasury_balance),
+        format!("{:.2}", m.uncovered_bad_debt),
+        format!("{:.4}", m.lending_zec_utilization),
+        format!("{:.4}", m.lending_zec_borrow_rate),
+        m.vaults_in_grace.to_string(),
+        format!("{:.2}", m.penalty_to_keepers),
+        format!("{:.2}", m.penalty_to_lps),
+        format!("{:.2}", m.penalty_to_insurance),
+        format!("{:.2}", m.penalty_to_treasury),
+        format!("{:.2}", m.penalty_burned),
+        format!("{:.2}", m.insurance_fund_balance),
+        format!("{:.6}", m.lp_fee_apr),
+        format!("{:.2}", m.lp_penalties_zai),
+        format!("{:.2}", m.lp_il_zai),
+        format!("{:.2}", m.lp_net_return_zai),
+        format!("{:.6}", m.savings_rate),
+        format!("{:.2}", m.savings_deposits_zai),
+        format!("{:.2}", m.savings_interest_paid_zai),
+        format!("{:.12}", m.funding_rate),
+        format!("{:.2}", m.funding_charged_zai),
+        format!("{:.6}", m.gas_fee_zec),
+        format!("{:.6}", m.gas_paid_zec),
+        format!("{:.2}", m.block_time_secs),
+        m.deferred_agents.to_string(),
+        m.reorgs.to_string(),
+        m.network_halted.to_string(),
+        m.amm_paused.to_string(),
+        m.oracle_outage.to_string(),
+        format!("{:.4}", m.shielded_zec),
+        format!("{:.2}", m.shielded_zai),
+        format!("{:.2}", m.bridge_outstanding_zai),
+        m.demand_users.to_string(),
+        format!("{:.4}", m.debt_ceiling_utilization),
+        m.vaults_waiting.to_string(),
+        m.redemption_capped.to_string(),
+        m.deleveraged_vaults.to_string(),
+        m.self_liquidations.to_string(),
+        m.vault_arrivals.to_string(),
+        m.vault_closures.to_string(),
+        m.dust_vaults.to_string(),
+        format!("{:.8}", m.perp_funding_rate),
+        format!("{:.4}", m.basis_arb_perp_zec),
+    ];
+    for cohort in cohorts {
+        match m.lp_cohorts.iter().find(|c| c.cohort == cohort.as_ref()) {
+            Some(c) => row.extend([
+                format!("{:.6}", c.fee_apr),
+                format!("{:.2}", c.fees_zai),
+                format!("{:.2}", c.penalties_zai),
+                format!("{:.2}", c.il_zai),
+                format!("{:.2}", c.net_return_zai),
+            ]),
+            None => row.extend(std::iter::repeat_n(String::new(), LP_COHORT_COLUMNS.len())),
+        }
+    }
+    row
+}
@@ -0,0 +1,210 @@
+//! Fluent construction of scenarios with agents and vaults.
+//!
+//! ```no_run
+//! use zai_sim::agents::{ArbitrageurConfig, LpAgentConfig};
+//! use zai_sim::scenario_builder::{ScenarioBuilder, VaultPopulation};
+//!
+//! let mut scenario = ScenarioBuilder::default()
+//!     .with_amm(100_000.0, 5_000_000.0)
+//!     .with_arbers(2, ArbitrageurConfig::default())
+//!     .with_lps(1, LpAgentConfig::default())
+//!     .with_vault_population(VaultPopulation::new(25, 1000.0, 1.6, 2.3))
+//!     .with_seed(7)
+//!     .build()
+//!     .unwrap();
+//! scenario.run(&vec![50.0; 1000]);
+//! ```
+
+use crate::agents::*;
+use crate::error::ZaiSimError;
+use crate::scenario::{Scenario, ScenarioConfig};
+use crate::scenarios::{add_agents, ScenarioId};
+
+/// Vaults opened directly in the registry at block 0, with collateral
+/// ratios spread evenly from `min_cr` to `max_cr` at the AMM's opening
+/// price.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VaultPopulation {
+    pub count: usize,
+    pub debt_zai: f64,
+    pub min_cr: f64,
+    pub max_cr: f64,
+    /// Vaults are owned by `{owner_prefix}_{i}`
+    pub owner_prefix: String,
+}
+
+impl VaultPopulation {
+    pub fn new(count: usize, debt_zai: f64, min_cr: f64, max_cr: f64) -> Self {
+        Self {
+            count,
+            debt_zai,
+            min_cr,
+            max_cr,
+            owner_prefix: "vault".to_string(),
+        }
+    }
+
+    pub fn with_owner_prefix(mut self, prefix: &str) -> Self {
+        self.owner_prefix = prefix.to_string();
+        self
+    }
+
+    /// Collateral ratio of vault `i`.
+    pub fn cr(&self, i: usize) -> f64 {
+        if self.count <= 1 {
+            return self.min_cr;
+        }
+        self.min_cr + (self.max_cr - self.min_cr) * i as f64 / (self.count - 1) as f64
+    }
+}
+
+/// Builds a `Scenario` from a config, agents and vaults. `build` puts the
+/// LPs' liquidity in the AMM and opens the CDP holders' and population
+/// vaults, so the scenario is ready to `run` or step.
+#[derive(Debug, Clone)]
+pub struct ScenarioBuilder {
+    config: ScenarioConfig,
+    seed: u64,
+    stress_agents: Option<ScenarioId>,
+    arbers: Vec<ArbitrageurConfig>,
+    miners: Vec<MinerAgentConfig>,
+    demand_agents: Vec<DemandAgentConfig>,
+    cdp_holders: Vec<CdpHolderConfig>,
+    lps: Vec<LpAgentConfig>,
+    attackers: Vec<AttackerConfig>,
+    vaults: Vec<VaultPopulation>,
+}
+
+impl Default for ScenarioBuilder {
+    fn default() -> Self {
+        Self::new(ScenarioConfig::default())
+    }
+}
+
+impl ScenarioBuilder {
+    /// A scenario of `config` with no agents, seeded 42 like `Scenario::new`.
+    pub fn new(config: ScenarioConfig) -> Self {
+        Self {
+            config,
+            seed: 42,
+            stress_agents: None,
+            arbers: Vec::new(),
+            miners: Vec::new(),
+            demand_agents: Vec::new(),
+            cdp_holders: Vec::new(),
+            lps: Vec::new(),
+            attackers: Vec::new(),
+            vaults: Vec::new(),
+        }
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Change the config in place, e.g. `|c| c.cdp_config.min_ratio = 2.0`.
+    pub fn with_config(mut self, f: impl FnOnce(&mut ScenarioConfig)) -> Self {
+        f(&mut self.config);
+        self
+    }
+
+    /// Initial AMM reserves (clearing any price or depth override).
+    pub fn with_amm(mut self, zec: f64, zai: f64) -> Self {
+        self.config.amm_initial_zec = zec;
+        self.config.amm_initial_zai = zai;
+        self.config.amm_initial_price = None;
+        self.config.amm_initial_depth = None;
+        self
+    }
+
+    /// The agents a built-in stress scenario runs with (one arber, one
+    /// miner, plus the scenario's own), added before any others.
+    pub fn with_stress_agents(mut self, id: ScenarioId) -> Self {
+        self.stress_agents = Some(id);
+        self
+    }
+
+    pub fn with_arbers(mut self, n: usize, config: ArbitrageurConfig) -> Self {
+        self.arbers.extend(std::iter::repeat_n(config, n));
+        self
+    }
+
+    pub fn with_miners(mut self, n: usize, config: MinerAgentConfig) -> Self {
+        self.miners.extend(std::iter::repeat_n(config, n));
+        self
+    }
+
+    pub fn with_demand_agents(mut self, n: usize, config: DemandAgentConfig) -> Self {
+        self.demand_agents.extend(std::iter::repeat_n(config, n));
+        self
+    }
+
+    pub fn with_cdp_holders(mut self, n: usize, config: CdpHolderConfig) -> Self {
+        self.cdp_holders.extend(std::iter::repeat_n(config, n));
+        self
+    }
+
+    pub fn with_lps(mut self, n: usize, config: LpAgentConfig) -> Self {
+        self.lps.extend(std::iter::repeat_n(config, n));
+        self
+    }
+
+    pub fn with_attacker(mut self, config: AttackerConfig) -> Self {
+        self.attackers.push(config);
+        self
+    }
+
+    pub fn with_vault_population(mut self, population: VaultPopulation) -> Self {
+        self.vaults.push(population);
+        self
+    }
+
+    pub fn config(&self) -> &ScenarioConfig {
+        &self.config
+    }
+
+    /// Create the scenario, add the agents, put the LPs' liquidity in and
+    /// open every vault. Fails if a population vault can't be opened, e.g.
+    /// below the minimum ratio or over the debt ceiling.
+    pub fn build(self) -> Result<Scenario, ZaiSimError> {
+        let mut scenario = Scenario::new_with_seed(&self.config, self.seed);
+        if let Some(id) = self.stress_agents {
+            add_agents(id, &mut scenario);
+        }
+        scenario
+            .arbers
+            .extend(self.arbers.into_iter().map(Arbitrageur::new));
+        scenario
+            .miners
+            .extend(self.miners.into_iter().map(MinerAgent::new));
+        scenario
+            .demand_agents
+            .extend(self.demand_agents.into_iter().map(DemandAgent::new));
+        scenario
+            .cdp_holders
+            .extend(self.cdp_holders.into_iter().map(CdpHolder::new));
+        scenario
+            .lp_agents
+            .extend(self.lps.into_iter().map(LpAgent::new));
+        scenario
+            .attackers
+            .extend(self.attackers.into_iter().map(Attacker::new));
+        scenario.initialize_agents();
+
+        let price = scenario.amm.spot_price();
+        for population in &self.vaults {
+            for i in 0..population.count {
+                let collateral = population.cr(i) * population.debt_zai / price;
+                scenario.registry.open_vault(
+                    &format!("{}_{}", population.owner_prefix, i),
+                    collateral,
+                    population.debt_zai,
+                    0,
+                    &scenario.amm,
+                )?;
+            }
+        }
+        Ok(scenario)
+    }
+}
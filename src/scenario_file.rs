@@ -0,0 +1,487 @@
+use std::path::Path;
+use std::str::FromStr;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rand_distr::{Distribution, Normal};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::agents::*;
+use crate::error::ZaiSimError;
+use crate::governance::{GovernanceAgent, GovernanceAgentConfig};
+use crate::perp::{PerpConfig, PerpMarket};
+use crate::scenario::{Scenario, ScenarioConfig};
+use crate::scenarios::{apply_price_noise, generate_prices, ScenarioId};
+use crate::sweep::SweepEngine;
+
+/// A stress scenario declared in YAML or TOML instead of code.
+///
+/// ```yaml
+/// name: double_dip
+/// config:
+///   use_amm_liquidation: true
+///   cdp_config: { min_ratio: 1.8 }
+/// prices:
+///   - { type: hold, price: 50, blocks: 200 }
+///   - { type: ramp, to: 25, blocks: 50 }
+///   - { type: walk, sigma: 1.5, blocks: 300, min: 10 }
+///   - { type: builtin, scenario: flash_crash, blocks: 400 }
+/// agents:
+///   arbers: [{}, { arb_latency_sell_blocks: 0 }]
+///   attackers: [{ attack_capital_zec: 8000, attack_at_block: 600 }]
+/// events:
+///   - { at_block: 500, set: { amm_swap_fee: 0.01 } }
+/// ```
+///
+/// `config`, every agent entry and every event's `set` are overrides:
+/// nested maps are merged field by field onto the defaults (or, for events,
+/// onto the config in force), so only the changed fields need spelling out.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScenarioFile {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Overrides applied to the base `ScenarioConfig`
+    #[serde(default)]
+    pub config: Value,
+    /// Price path segments, concatenated in order
+    pub prices: Vec<PriceSegment>,
+    #[serde(default)]
+    pub agents: AgentRoster,
+    /// Config changes applied mid-run
+    #[serde(default)]
+    pub events: Vec<ConfigChange>,
+}
+
+/// One piece of the external price path.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PriceSegment {
+    /// Constant price; defaults to where the previous segment ended
+    Hold { blocks: usize, price: Option<f64> },
+    /// Linear move to `to`, starting from `from` or the previous price
+    Ramp {
+        blocks: usize,
+        to: f64,
+        from: Option<f64>,
+    },
+    /// Gaussian random walk with per-block step `sigma`, clamped to
+    /// [`min`, `max`] (default 1 and unbounded)
+    Walk {
+        blocks: usize,
+        sigma: f64,
+        from: Option<f64>,
+        min: Option<f64>,
+        max: Option<f64>,
+    },
+    /// The price path of one of the built-in stress scenarios, by name
+    Builtin { scenario: String, blocks: usize },
+}
+
+impl PriceSegment {
+    pub fn blocks(&self) -> usize {
+        match self {
+            Self::Hold { blocks, .. }
+            | Self::Ramp { blocks, .. }
+            | Self::Walk { blocks, .. }
+            | Self::Builtin { blocks, .. } => *blocks,
+        }
+    }
+}
+
+/// Agents to add, one override object per agent. Lists left out of the
+/// file keep the built-in baseline of one default arber and one default
+/// miner; an empty list removes them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AgentRoster {
+    pub arbers: Vec<Value>,
+    pub miners: Vec<Value>,
+    pub demand_agents: Vec<Value>,
+    pub cdp_holders: Vec<Value>,
+    pub lp_agents: Vec<Value>,
+    pub il_aware_lps: Vec<Value>,
+    pub attackers: Vec<Value>,
+    pub redeemers: Vec<Value>,
+    pub basis_traders: Vec<Value>,
+    pub savers: Vec<Value>,
+    pub noise_traders: Vec<Value>,
+    pub governance_agents: Vec<Value>,
+    pub basis_arbs: Vec<Value>,
+    pub vault_portfolios: Vec<Value>,
+}
+
+impl Default for AgentRoster {
+    fn default() -> Self {
+        AgentRoster {
+            arbers: vec![Value::Null],
+            miners: vec![Value::Null],
+            demand_agents: Vec::new(),
+            cdp_holders: Vec::new(),
+            lp_agents: Vec::new(),
+            il_aware_lps: Vec::new(),
+            attackers: Vec::new(),
+            redeemers: Vec::new(),
+            basis_traders: Vec::new(),
+            savers: Vec::new(),
+            noise_traders: Vec::new(),
+            governance_agents: Vec::new(),
+            basis_arbs: Vec::new(),
+            vault_portfolios: Vec::new(),
+        }
+    }
+}
+
+impl AgentRoster {
+    /// A roster with no agents, not even the baseline arber and miner.
+    pub fn empty() -> Self {
+        AgentRoster {
+            arbers: Vec::new(),
+            miners: Vec::new(),
+            ..AgentRoster::default()
+        }
+    }
+
+    /// The override list of agent class `class`, named as in the file
+    /// (e.g. `lp_agents`).
+    pub fn class_mut(&mut self, class: &str) -> Result<&mut Vec<Value>, ZaiSimError> {
+        Ok(match class {
+            "arbers" => &mut self.arbers,
+            "miners" => &mut self.miners,
+            "demand_agents" => &mut self.demand_agents,
+            "cdp_holders" => &mut self.cdp_holders,
+            "lp_agents" => &mut self.lp_agents,
+            "il_aware_lps" => &mut self.il_aware_lps,
+            "attackers" => &mut self.attackers,
+            "redeemers" => &mut self.redeemers,
+            "basis_traders" => &mut self.basis_traders,
+            "savers" => &mut self.savers,
+            "noise_traders" => &mut self.noise_traders,
+            "governance_agents" => &mut self.governance_agents,
+            "basis_arbs" => &mut self.basis_arbs,
+            "vault_portfolios" => &mut self.vault_portfolios,
+            _ => {
+                return Err(ZaiSimError::Config(format!(
+                    "Unknown agent class: {}",
+                    class
+                )))
+            }
+        })
+    }
+
+    /// Add one agent per override object to `scenario`. Noise traders draw
+    /// from their own streams of `seed`.
+    pub fn add_to(&self, scenario: &mut Scenario, seed: u64) -> Result<(), ZaiSimError> {
+        for o in &self.arbers {
+            let c = with_overrides(&ArbitrageurConfig::default(), o)?;
+            scenario.arbers.push(Arbitrageur::new(c));
+        }
+        for o in &self.miners {
+            let c = with_overrides(&MinerAgentConfig::default(), o)?;
+            scenario.miners.push(MinerAgent::new(c));
+        }
+        for o in &self.demand_agents {
+            let c = with_overrides(&DemandAgentConfig::default(), o)?;
+            scenario.demand_agents.push(DemandAgent::new(c));
+        }
+        for o in &self.cdp_holders {
+            let c = with_overrides(&CdpHolderConfig::default(), o)?;
+            scenario.cdp_holders.push(CdpHolder::new(c));
+        }
+        for o in &self.lp_agents {
+            let c = with_overrides(&LpAgentConfig::default(), o)?;
+            scenario.lp_agents.push(LpAgent::new(c));
+        }
+        for (i, o) in self.il_aware_lps.iter().enumerate() {
+            let c = with_overrides(&IlAwareLpConfig::default(), o)?;
+            scenario
+                .il_aware_lps
+                .push(IlAwareLpAgent::new(c, &format!("il_lp_{}", i)));
+        }
+        for o in &self.attackers {
+            // A strategy replaces the default one rather than merging into
+            // it, since strategies have different fields
+            let mut o = o.clone();
+            let strategy = o.as_object_mut().and_then(|m| m.remove("strategy"));
+            let mut c = with_overrides(&AttackerConfig::default(), &o)?;
+            if let Some(s) = strategy {
+                c.strategy = serde_json::from_value(s)
+                    .map_err(|e| ZaiSimError::Config(format!("strategy: {}", e)))?;
+            }
+            scenario.attackers.push(Attacker::new(c));
+        }
+        for o in &self.redeemers {
+            let c = with_overrides(&RedeemerConfig::default(), o)?;
+            scenario.redeemers.push(RedeemerAgent::new(c));
+        }
+        for o in &self.basis_traders {
+            let c = with_overrides(&BasisTraderConfig::default(), o)?;
+            scenario.basis_traders.push(BasisTrader::new(c));
+        }
+        for o in &self.savers {
+            let c = with_overrides(&SaverAgentConfig::default(), o)?;
+            scenario.savers.push(SaverAgent::new(c));
+        }
+        // Each trader draws from its own stream of the run seed
+        for (i, o) in self.noise_traders.iter().enumerate() {
+            let c = with_overrides(&NoiseTraderConfig::default(), o)?;
+            scenario
+                .noise_traders
+                .push(NoiseTrader::new(c, seed.wrapping_add(i as u64)));
+        }
+        for o in &self.governance_agents {
+            let c = with_overrides(&GovernanceAgentConfig::default(), o)?;
+            scenario.governance_agents.push(GovernanceAgent::new(c));
+        }
+        for o in &self.basis_arbs {
+            let c = with_overrides(&BasisArbConfig::default(), o)?;
+            scenario.basis_arbs.push(BasisArbAgent::new(c));
+        }
+        for o in &self.vault_portfolios {
+            let c = with_overrides(&VaultPortfolioConfig::default(), o)?;
+            let owner = format!("portfolio_{}", scenario.vault_portfolios.len());
+            scenario
+                .vault_portfolios
+                .push(VaultPortfolio::new(c, &owner));
+        }
+        // Basis arbitrageurs need a perp to trade against
+        if !self.basis_arbs.is_empty() && scenario.perp.is_none() {
+            let perp = scenario.config.perp.get_or_insert_with(PerpConfig::default);
+            scenario.perp = Some(PerpMarket::new(perp.clone()));
+        }
+        Ok(())
+    }
+}
+
+/// Config overrides that take effect from block `at_block` onwards.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigChange {
+    pub at_block: u64,
+    pub set: Value,
+}
+
+impl ScenarioFile {
+    /// Load a scenario file; `.toml` files are read as TOML, anything else
+    /// as YAML.
+    pub fn load(path: &Path) -> Result<Self, ZaiSimError> {
+        let text = std::fs::read_to_string(path)?;
+        let parsed = match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => Self::from_toml_str(&text),
+            _ => Self::from_yaml_str(&text),
+        };
+        parsed.map_err(|e| ZaiSimError::Parse(format!("{}: {}", path.display(), e)))
+    }
+
+    pub fn from_yaml_str(text: &str) -> Result<Self, ZaiSimError> {
+        serde_yaml::from_str(text).map_err(|e| ZaiSimError::Parse(e.to_string()))
+    }
+
+    pub fn from_toml_str(text: &str) -> Result<Self, ZaiSimError> {
+        toml::from_str(text).map_err(|e| ZaiSimError::Parse(e.to_string()))
+    }
+
+    /// Total blocks across all price segments.
+    pub fn blocks(&self) -> usize {
+        self.prices.iter().map(PriceSegment::blocks).sum()
+    }
+
+    /// Build the external price path. `seed` drives random walks and
+    /// stochastic built-in paths.
+    pub fn price_path(&self, seed: u64) -> Result<Vec<f64>, ZaiSimError> {
+        let mut prices: Vec<f64> = Vec::with_capacity(self.blocks());
+        for (i, segment) in self.prices.iter().enumerate() {
+            let last = prices.last().copied();
+            let start = |given: Option<f64>| {
+                given.or(last).ok_or_else(|| {
+                    ZaiSimError::Config(format!("Price segment {} needs a starting price", i + 1))
+                })
+            };
+            match segment {
+                PriceSegment::Hold { blocks, price } => {
+                    let p = start(*price)?;
+                    prices.extend(std::iter::repeat_n(p, *blocks));
+                }
+                PriceSegment::Ramp { blocks, to, from } => {
+                    let p0 = start(*from)?;
+                    prices
+                        .extend((1..=*blocks).map(|b| p0 + (to - p0) * b as f64 / *blocks as f64));
+                }
+                PriceSegment::Walk {
+                    blocks,
+                    sigma,
+                    from,
+                    min,
+                    max,
+                } => {
+                    let mut p = start(*from)?;
+                    let normal = Normal::new(0.0, *sigma).map_err(|e| {
+                        ZaiSimError::Config(format!("Price segment {}: {}", i + 1, e))
+                    })?;
+                    let mut rng = StdRng::seed_from_u64(seed.wrapping_add(i as u64));
+                    let (lo, hi) = (min.unwrap_or(1.0), max.unwrap_or(f64::INFINITY));
+                    for _ in 0..*blocks {
+                        p = (p + normal.sample(&mut rng)).clamp(lo, hi);
+                        prices.push(p);
+                    }
+                }
+                PriceSegment::Builtin { scenario, blocks } => {
+                    let id = ScenarioId::all()
+                        .into_iter()
+                        .find(|id| id.name() == scenario.as_str())
+                        .ok_or_else(|| {
+                            ZaiSimError::Config(format!("Unknown built-in scenario: {}", scenario))
+                        })?;
+                    prices.extend(generate_prices(id, *blocks, seed));
+                }
+            }
+        }
+        Ok(prices)
+    }
+
+    /// `base` with the file's config overrides applied.
+    pub fn scenario_config(&self, base: &ScenarioConfig) -> Result<ScenarioConfig, ZaiSimError> {
+        with_overrides(base, &self.config)
+    }
+
+    /// Create the scenario and its agents without running it.
+    pub fn build(&self, config: &ScenarioConfig, seed: u64) -> Result<Scenario, ZaiSimError> {
+        let mut scenario = Scenario::new_with_seed(config, seed);
+        self.agents.add_to(&mut scenario, seed)?;
+        Ok(scenario)
+    }
+
+    /// Build and run the scenario on top of `base`, applying each event's
+    /// config change before its block.
+    pub fn run(&self, base: &ScenarioConfig, seed: u64) -> Result<Scenario, ZaiSimError> {
+        let config = self.scenario_config(base)?;
+        let mut prices = self.price_path(seed)?;
+        if config.stochastic {
+            apply_price_noise(&mut prices, config.noise_sigma, seed);
+        }
+
+        // Resolve every change up front so a bad override fails before the run
+        let mut events = self.events.clone();
+        events.sort_by_key(|e| e.at_block);
+        let mut changes = Vec::with_capacity(events.len());
+        let mut current = config.clone();
+        for event in &events {
+            if event.at_block == 0 || event.at_block as usize > prices.len() {
+                return Err(ZaiSimError::Config(format!(
+                    "Event at block {} is outside the {}-block price path",
+                    event.at_block,
+                    prices.len()
+                )));
+            }
+            current = with_overrides(&current, &event.set)?;
+            changes.push((event.at_block as usize, current.clone()));
+        }
+
+        let mut scenario = self.build(&config, seed)?;
+        for (at_block, config) in changes {
+            // Running zero blocks would initialize the agents twice
+            if at_block > 1 {
+                scenario.run(&prices[..at_block - 1]);
+            }
+            scenario.reconfigure(config);
+        }
+        scenario.run(&prices);
+        Ok(scenario)
+    }
+}
+
+/// One `--set` override, e.g. `cdp_config.min_ratio=2.0`: a sweep alias or
+/// dotted `ScenarioConfig` path, and a value read as JSON (falling back to
+/// a plain string, so `cdp_config.twap_kind=median` works unquoted).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigSetting {
+    pub path: String,
+    pub value: Value,
+}
+
+impl FromStr for ConfigSetting {
+    type Err = ZaiSimError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (path, value) = s
+            .split_once('=')
+            .filter(|(path, _)| !path.trim().is_empty())
+            .ok_or_else(|| ZaiSimError::Parse(format!("Invalid setting: {} (use key=value)", s)))?;
+        let value = value.trim();
+        Ok(ConfigSetting {
+            path: path.trim().to_string(),
+            value: serde_json::from_str(value).unwrap_or_else(|_| Value::String(value.to_string())),
+        })
+    }
+}
+
+impl ConfigSetting {
+    /// Apply to `config`. Numbers go through `SweepEngine::set_param`, so
+    /// sweep aliases like `min_ratio` work too; other values are merged in
+    /// at their path.
+    pub fn apply(&self, config: &mut ScenarioConfig) -> Result<(), ZaiSimError> {
+        if let Some(n) = self.value.as_f64() {
+            return SweepEngine::set_param(config, &self.path, n);
+        }
+        let overrides = self
+            .path
+            .rsplit('.')
+            .fold(self.value.clone(), |value, key| {
+                Value::Object(serde_json::Map::from_iter([(key.to_string(), value)]))
+            });
+        *config = with_overrides(config, &overrides)
+            .map_err(|e| ZaiSimError::Config(format!("{}: {}", self.path, e)))?;
+        Ok(())
+    }
+}
+
+/// Read a file of `ScenarioConfig` overrides (TOML if `.toml`, JSON if
+/// `.json`, else YAML) and merge it onto `base`.
+pub fn load_config(path: &Path, base: &ScenarioConfig) -> Result<ScenarioConfig, ZaiSimError> {
+    let text = std::fs::read_to_string(path)?;
+    let parse_error = |e: String| ZaiSimError::Parse(format!("{}: {}", path.display(), e));
+    let overrides: Value = match path.extension().and_then(|e| e.to_str()) {
+        Some("toml") => {
+            let table: toml::Table =
+                toml::from_str(&text).map_err(|e| parse_error(e.to_string()))?;
+            serde_json::to_value(table)?
+        }
+        Some("json") => serde_json::from_str(&text)?,
+        _ => serde_yaml::from_str(&text).map_err(|e| parse_error(e.to_string()))?,
+    };
+    with_overrides(base, &overrides).map_err(|e| parse_error(e.to_string()))
+}
+
+/// `base` with `overrides` deep-merged onto it. Keys that `base` does not
+/// have are rejected rather than silently ignored.
+pub fn with_overrides<T: Serialize + DeserializeOwned>(
+    base: &T,
+    overrides: &Value,
+) -> Result<T, ZaiSimError> {
+    let mut value = serde_json::to_value(base)?;
+    if !overrides.is_null() {
+        merge(&mut value, overrides, "")?;
+    }
+    serde_json::from_value(value).map_err(|e| ZaiSimError::Config(e.to_string()))
+}
+
+fn merge(base: &mut Value, overrides: &Value, path: &str) -> Result<(), ZaiSimError> {
+    match (base, overrides) {
+        (Value::Object(fields), Value::Object(changes)) => {
+            for (key, change) in changes {
+                let name = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                let field = fields
+                    .get_mut(key)
+                    .ok_or_else(|| ZaiSimError::Config(format!("Unknown field: {}", name)))?;
+                merge(field, change, &name)?;
+            }
+        }
+        (base, change) => *base = change.clone(),
+    }
+    Ok(())
+}
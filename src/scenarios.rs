@@ -1,10 +1,18 @@
+use std::str::FromStr;
+
 use crate::agents::*;
+use crate::error::ZaiSimError;
 use crate::scenario::{Scenario, ScenarioConfig};
 use rand::rngs::StdRng;
 use rand::SeedableRng;
 use rand_distr::{Distribution, Normal};
+use serde::Serialize;
+
+/// Block count the stress scenarios are calibrated for.
+pub const DEFAULT_BLOCKS: usize = 1000;
 
-const DEFAULT_BLOCKS: usize = 1000;
+/// Seconds per block, for turning block counts into wall-clock time.
+pub const BLOCK_TIME_SECS: f64 = 75.0;
 
 /// Identifier for each of the 13 stress scenarios.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -26,6 +34,26 @@ pub enum ScenarioId {
 }
 
 impl ScenarioId {
+    /// Scenario with numeric ID `id` (1-13).
+    pub fn from_id(id: u8) -> Option<ScenarioId> {
+        Self::all().into_iter().find(|s| *s as u8 == id)
+    }
+
+    /// Scenario named `name`, as returned by `name()`.
+    pub fn from_name(name: &str) -> Option<ScenarioId> {
+        Self::all().into_iter().find(|s| s.name() == name)
+    }
+
+    /// This scenario followed by `next`.
+    pub fn chain(self, next: impl Into<ScenarioMix>) -> ScenarioMix {
+        ScenarioMix::from(self).chain(next)
+    }
+
+    /// This scenario's price moves and agents combined with `other`'s.
+    pub fn overlay(self, other: impl Into<ScenarioMix>) -> ScenarioMix {
+        ScenarioMix::from(self).overlay(other)
+    }
+
     pub fn all() -> Vec<ScenarioId> {
         use ScenarioId::*;
         vec![
@@ -80,6 +108,137 @@ impl ScenarioId {
             Self::SequencerDowntime => "Network pause then resume with price gap",
         }
     }
+
+    /// The external price path, with phases as fractions of the run.
+    pub fn price_shape(&self) -> &'static str {
+        match self {
+            Self::SteadyState => "Flat at $50",
+            Self::BlackThursday => {
+                "$50 until 25%, linear crash to $20 over 10%, recovery to $35 over 25%, then flat"
+            }
+            Self::FlashCrash => {
+                "$50 until 50%, drop to $25 over 10 blocks, back to $48 over 50 blocks, then flat"
+            }
+            Self::SustainedBear => "Linear decline from $50 to $15",
+            Self::TwapManipulation => {
+                "Flat at $50, with 2-block spikes to $100 every 100 blocks after block 200"
+            }
+            Self::LiquidityCrisis => {
+                "Seeded random walk from $50 (sigma $2 per block), clamped to $10-120"
+            }
+            Self::BankRun => "$50 until 33%, then an accelerating slide to $20",
+            Self::BullMarket => "Linear rise from $30 to $100",
+            Self::OracleComparison => "Sine wave around $50, amplitude $15, period 50 blocks",
+            Self::CombinedStress => {
+                "Decline to $40 by 25%, crash to $25, recovery to $35 by 50% and $45 by 75%, then flat"
+            }
+            Self::DemandShock => "$50 until 33%, surge to $70 by 50%, then a slide to $40",
+            Self::MinerCapitulation => {
+                "Three waves stepping down from $50 by $10, each a $8 dip and $5 rebound"
+            }
+            Self::SequencerDowntime => "$50 until 60%, then a gap down to $35",
+        }
+    }
+
+    /// What this scenario simulates: descriptions, the agents `add_agents`
+    /// gives it and its price path at `blocks` blocks.
+    pub fn info(&self, blocks: usize, seed: u64) -> ScenarioInfo {
+        let mut scenario = Scenario::new(&ScenarioConfig::default());
+        add_agents(*self, &mut scenario);
+        let agents = [
+            ("arbers", scenario.arbers.len()),
+            ("miners", scenario.miners.len()),
+            ("demand_agents", scenario.demand_agents.len()),
+            ("cdp_holders", scenario.cdp_holders.len()),
+            ("lp_agents", scenario.lp_agents.len()),
+            ("il_aware_lps", scenario.il_aware_lps.len()),
+            ("attackers", scenario.attackers.len()),
+        ]
+        .into_iter()
+        .filter(|(_, n)| *n > 0)
+        .map(|(class, n)| (class.to_string(), n))
+        .collect();
+
+        let prices = generate_prices(*self, blocks, seed);
+        let first = prices.first().copied().unwrap_or(0.0);
+        ScenarioInfo {
+            id: *self as u8,
+            name: self.name(),
+            description: self.description(),
+            price_shape: self.price_shape(),
+            agents,
+            blocks,
+            hours: blocks as f64 * BLOCK_TIME_SECS / 3600.0,
+            start_price: first,
+            min_price: prices.iter().copied().fold(first, f64::min),
+            max_price: prices.iter().copied().fold(first, f64::max),
+            end_price: prices.last().copied().unwrap_or(0.0),
+        }
+    }
+}
+
+/// Scenarios listed as comma-separated IDs, names and ID ranges, e.g.
+/// `2,3,7-10` or `black_thursday,flash_crash`; `all` or `0` for all 13.
+/// Duplicates are dropped, keeping the first.
+pub fn parse_scenario_ids(s: &str) -> Result<Vec<ScenarioId>, ZaiSimError> {
+    let s = s.trim();
+    if s == "all" || s == "0" {
+        return Ok(ScenarioId::all());
+    }
+    let invalid = |p: &str| ZaiSimError::Parse(format!("Invalid scenario: {} (1-13 or a name)", p));
+    let id = |p: &str| {
+        p.trim()
+            .parse::<u8>()
+            .ok()
+            .and_then(ScenarioId::from_id)
+            .ok_or_else(|| invalid(p))
+    };
+    let mut ids = Vec::new();
+    for part in s.split(',').map(str::trim) {
+        let range = match part.split_once('-') {
+            Some((from, to)) => {
+                let (from, to) = (id(from)?, id(to)?);
+                if from as u8 > to as u8 {
+                    return Err(ZaiSimError::Parse(format!(
+                        "Invalid scenario range: {} (must be ascending)",
+                        part
+                    )));
+                }
+                (from as u8..=to as u8)
+                    .filter_map(ScenarioId::from_id)
+                    .collect()
+            }
+            None => vec![id(part)
+                .ok()
+                .or_else(|| ScenarioId::from_name(part))
+                .ok_or_else(|| invalid(part))?],
+        };
+        for sid in range {
+            if !ids.contains(&sid) {
+                ids.push(sid);
+            }
+        }
+    }
+    Ok(ids)
+}
+
+/// A stress scenario's description, default agents and price path, as
+/// listed by the `scenarios` command.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ScenarioInfo {
+    pub id: u8,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub price_shape: &'static str,
+    /// Agent counts by class, baseline arber and miner included
+    pub agents: Vec<(String, usize)>,
+    pub blocks: usize,
+    /// Simulated time at `BLOCK_TIME_SECS` per block
+    pub hours: f64,
+    pub start_price: f64,
+    pub min_price: f64,
+    pub max_price: f64,
+    pub end_price: f64,
 }
 
 /// Apply multiplicative noise to a price path.
@@ -116,14 +275,22 @@ pub fn generate_prices(id: ScenarioId, blocks: usize, seed: u64) -> Vec<f64> {
 
 /// Add appropriate agents to a scenario based on scenario type.
 pub fn add_agents(id: ScenarioId, scenario: &mut Scenario) {
-    // All scenarios get at least one arber and one miner
+    add_baseline_agents(scenario);
+    add_scenario_agents(id, scenario);
+}
+
+/// The agents every scenario gets: one arber and one miner.
+fn add_baseline_agents(scenario: &mut Scenario) {
     scenario
         .arbers
         .push(Arbitrageur::new(ArbitrageurConfig::default()));
     scenario
         .miners
         .push(MinerAgent::new(MinerAgentConfig::default()));
+}
 
+/// The agents specific to scenario `id`, on top of the baseline.
+fn add_scenario_agents(id: ScenarioId, scenario: &mut Scenario) {
     match id {
         ScenarioId::BankRun => {
             // Demand agents configured for panic selling
@@ -143,6 +310,7 @@ pub fn add_agents(id: ScenarioId, scenario: &mut Scenario) {
                 attack_capital_zec: 5000.0,
                 hold_blocks: 3,
                 attack_at_block: 500,
+                ..AttackerConfig::default()
             }));
         }
         ScenarioId::MinerCapitulation => {
@@ -185,21 +353,255 @@ pub fn run_stress(
     blocks: usize,
     seed: u64,
 ) -> Scenario {
-    let mut prices = generate_prices(id, blocks, seed);
+    run_stress_with(id, config, blocks, seed, |_| {})
+}
+
+/// Build a stress scenario, let `setup` add extra agents, then run it.
+pub fn run_stress_with(
+    id: ScenarioId,
+    config: &ScenarioConfig,
+    blocks: usize,
+    seed: u64,
+    setup: impl FnOnce(&mut Scenario),
+) -> Scenario {
+    let prices = generate_prices(id, blocks, seed);
+    run_stress_on(id, config, &prices, seed, setup)
+}
+
+/// Build stress scenario `id` with its agents (plus `setup`'s) but run it
+/// over `prices`, e.g. a historical crash, instead of its generated path.
+pub fn run_stress_on(
+    id: ScenarioId,
+    config: &ScenarioConfig,
+    prices: &[f64],
+    seed: u64,
+    setup: impl FnOnce(&mut Scenario),
+) -> Scenario {
+    let mut prices = prices.to_vec();
     if config.stochastic {
         apply_price_noise(&mut prices, config.noise_sigma, seed);
     }
     let mut scenario = Scenario::new_with_seed(config, seed);
     add_agents(id, &mut scenario);
+    setup(&mut scenario);
     scenario.run(&prices);
     scenario
 }
 
+/// Scale `prices` so the path starts at `start`, keeping its relative
+/// moves, e.g. to push a $300 ZEC crash through a $50 scenario config.
+pub fn rescale_prices(prices: &mut [f64], start: f64) {
+    if let Some(&first) = prices.first().filter(|p| **p > 0.0) {
+        let scale = start / first;
+        prices.iter_mut().for_each(|p| *p *= scale);
+    }
+}
+
 /// Build and run with default config and block count.
 pub fn run_stress_default(id: ScenarioId) -> Scenario {
     run_stress(id, &ScenarioConfig::default(), DEFAULT_BLOCKS, 42)
 }
 
+// ═══════════════════════════════════════════════════════════════════════
+// Scenario Composition
+// ═══════════════════════════════════════════════════════════════════════
+
+/// A composition of stress scenarios.
+///
+/// A chain runs its parts one after another, splitting the blocks evenly;
+/// each part's price path is rescaled to start where the previous one
+/// ended, and its scheduled attacks are shifted to its own start. An
+/// overlay runs its parts over the same blocks: the first part sets the
+/// price level and every other part multiplies in its relative moves.
+/// Either way every part's agents join one shared baseline arber and miner.
+///
+/// Parsed from strings like `2+7` (BlackThursday then BankRun) or `3~13`
+/// (FlashCrash overlaid with SequencerDowntime); `~` binds tighter than
+/// `+`, and parts may be IDs or names.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScenarioMix {
+    Single(ScenarioId),
+    Chain(Vec<ScenarioMix>),
+    Overlay(Vec<ScenarioMix>),
+}
+
+impl From<ScenarioId> for ScenarioMix {
+    fn from(id: ScenarioId) -> Self {
+        ScenarioMix::Single(id)
+    }
+}
+
+impl FromStr for ScenarioMix {
+    type Err = ZaiSimError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let part = |p: &str| {
+            let p = p.trim();
+            p.parse::<u8>()
+                .ok()
+                .and_then(ScenarioId::from_id)
+                .or_else(|| ScenarioId::from_name(p))
+                .map(ScenarioMix::Single)
+                .ok_or_else(|| {
+                    ZaiSimError::Parse(format!("Invalid scenario: {} (1-13 or a name)", p))
+                })
+        };
+        let chain = s
+            .split('+')
+            .map(|segment| {
+                let layers = segment
+                    .split('~')
+                    .map(part)
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(ScenarioMix::Overlay(layers).simplify())
+            })
+            .collect::<Result<Vec<_>, ZaiSimError>>()?;
+        Ok(ScenarioMix::Chain(chain).simplify())
+    }
+}
+
+impl ScenarioMix {
+    /// This mix followed by `next`.
+    pub fn chain(self, next: impl Into<ScenarioMix>) -> ScenarioMix {
+        let mut parts = match self {
+            ScenarioMix::Chain(parts) => parts,
+            other => vec![other],
+        };
+        match next.into() {
+            ScenarioMix::Chain(more) => parts.extend(more),
+            other => parts.push(other),
+        }
+        ScenarioMix::Chain(parts)
+    }
+
+    /// This mix with `other` overlaid on the same blocks.
+    pub fn overlay(self, other: impl Into<ScenarioMix>) -> ScenarioMix {
+        let mut layers = match self {
+            ScenarioMix::Overlay(layers) => layers,
+            other => vec![other],
+        };
+        match other.into() {
+            ScenarioMix::Overlay(more) => layers.extend(more),
+            other => layers.push(other),
+        }
+        ScenarioMix::Overlay(layers)
+    }
+
+    /// Unwrap one-part chains and overlays.
+    fn simplify(self) -> ScenarioMix {
+        match self {
+            ScenarioMix::Chain(mut parts) | ScenarioMix::Overlay(mut parts) if parts.len() == 1 => {
+                parts.remove(0)
+            }
+            other => other,
+        }
+    }
+
+    /// Name for reports and output directories, e.g. `black_thursday+bank_run`.
+    pub fn name(&self) -> String {
+        match self {
+            ScenarioMix::Single(id) => id.name().to_string(),
+            ScenarioMix::Chain(parts) => parts
+                .iter()
+                .map(ScenarioMix::name)
+                .collect::<Vec<_>>()
+                .join("+"),
+            ScenarioMix::Overlay(layers) => layers
+                .iter()
+                .map(|layer| match layer {
+                    ScenarioMix::Chain(_) => format!("({})", layer.name()),
+                    _ => layer.name(),
+                })
+                .collect::<Vec<_>>()
+                .join("~"),
+        }
+    }
+
+    /// Generate the composed price path.
+    pub fn generate_prices(&self, blocks: usize, seed: u64) -> Vec<f64> {
+        match self {
+            ScenarioMix::Single(id) => generate_prices(*id, blocks, seed),
+            ScenarioMix::Chain(parts) => {
+                let mut prices: Vec<f64> = Vec::with_capacity(blocks);
+                for (part, len) in parts.iter().zip(chain_lengths(blocks, parts.len())) {
+                    let mut segment = part.generate_prices(len, seed);
+                    if let (Some(&last), Some(&first)) = (prices.last(), segment.first()) {
+                        let scale = last / first;
+                        segment.iter_mut().for_each(|p| *p *= scale);
+                    }
+                    prices.extend(segment);
+                }
+                prices
+            }
+            ScenarioMix::Overlay(layers) => {
+                let mut prices = vec![1.0; blocks];
+                for (i, layer) in layers.iter().enumerate() {
+                    let path = layer.generate_prices(blocks, seed);
+                    // The first layer sets the level, the rest only move it
+                    let base = match path.first() {
+                        Some(&first) if i > 0 => first,
+                        _ => 1.0,
+                    };
+                    for (p, q) in prices.iter_mut().zip(&path) {
+                        *p *= q / base;
+                    }
+                }
+                prices
+            }
+        }
+    }
+
+    /// Add every part's scenario-specific agents, shifting attacks in
+    /// chained parts to that part's first block.
+    fn add_agents(&self, scenario: &mut Scenario, offset: u64, blocks: usize) {
+        match self {
+            ScenarioMix::Single(id) => {
+                let first_attacker = scenario.attackers.len();
+                add_scenario_agents(*id, scenario);
+                for attacker in &mut scenario.attackers[first_attacker..] {
+                    attacker.config.attack_at_block += offset;
+                }
+            }
+            ScenarioMix::Chain(parts) => {
+                let mut start = offset;
+                for (part, len) in parts.iter().zip(chain_lengths(blocks, parts.len())) {
+                    part.add_agents(scenario, start, len);
+                    start += len as u64;
+                }
+            }
+            ScenarioMix::Overlay(layers) => {
+                for layer in layers {
+                    layer.add_agents(scenario, offset, blocks);
+                }
+            }
+        }
+    }
+}
+
+/// Split `blocks` evenly over `parts` chained segments; the last one takes
+/// the remainder.
+fn chain_lengths(blocks: usize, parts: usize) -> Vec<usize> {
+    let each = blocks / parts.max(1);
+    let mut lengths = vec![each; parts];
+    if let Some(last) = lengths.last_mut() {
+        *last += blocks - each * parts;
+    }
+    lengths
+}
+
+/// Build and run a composed stress scenario.
+pub fn run_mix(mix: &ScenarioMix, config: &ScenarioConfig, blocks: usize, seed: u64) -> Scenario {
+    let mut prices = mix.generate_prices(blocks, seed);
+    if config.stochastic {
+        apply_price_noise(&mut prices, config.noise_sigma, seed);
+    }
+    let mut scenario = Scenario::new_with_seed(config, seed);
+    add_baseline_agents(&mut scenario);
+    mix.add_agents(&mut scenario, 0, blocks);
+    scenario.run(&prices);
+    scenario
+}
+
 // ═══════════════════════════════════════════════════════════════════════
 // Price Path Generators
 // ═══════════════════════════════════════════════════════════════════════
@@ -0,0 +1,232 @@
+//! Global sensitivity analysis with Sobol indices.
+//!
+//! Draws a Saltelli sample over chosen parameter ranges and estimates, for
+//! each outcome, the first-order index (variance explained by a parameter
+//! alone) and the total index (including its interactions). Parameters with
+//! a total index near zero can be left at their defaults in finer sweeps.
+
+use rayon::prelude::*;
+
+use crate::error::ZaiSimError;
+use crate::output::{compute_summary, SUMMARY_COLUMNS};
+use crate::scenario::ScenarioConfig;
+use crate::scenarios::{run_stress, ScenarioId};
+use crate::sweep::{SamplingStrategy, SweepEngine, SweepRange};
+
+/// Sobol indices of one parameter for one outcome.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SobolIndex {
+    pub param: String,
+    pub first_order: f64,
+    pub total: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct OutcomeSensitivity {
+    pub outcome: String,
+    pub mean: f64,
+    pub variance: f64,
+    /// One entry per parameter, in range order
+    pub indices: Vec<SobolIndex>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SensitivityReport {
+    pub base_samples: usize,
+    /// Model evaluations: `base_samples * (params + 2)`
+    pub evaluations: usize,
+    pub outcomes: Vec<OutcomeSensitivity>,
+}
+
+/// Mean and population variance of both base samples together.
+fn mean_variance(f_a: &[f64], f_b: &[f64]) -> (f64, f64) {
+    let n = (f_a.len() + f_b.len()).max(1) as f64;
+    let mean = f_a.iter().chain(f_b).sum::<f64>() / n;
+    let variance = f_a
+        .iter()
+        .chain(f_b)
+        .map(|y| (y - mean).powi(2))
+        .sum::<f64>()
+        / n;
+    (mean, variance)
+}
+
+/// Saltelli (2010) first-order and Jansen total-effect estimators.
+///
+/// `f_a` and `f_b` are the outcomes at the two base matrices; `f_ab[i]` is
+/// the outcome at A with column `i` taken from B. Returns
+/// `(first_order, total)` per parameter; both are zero when the outcome does
+/// not vary. Outcomes are centered first, which leaves the estimators
+/// unbiased but keeps a large mean from swamping a small variance.
+pub fn sobol_indices(f_a: &[f64], f_b: &[f64], f_ab: &[Vec<f64>]) -> Vec<(f64, f64)> {
+    let n = f_a.len() as f64;
+    let (mean, variance) = mean_variance(f_a, f_b);
+
+    f_ab.iter()
+        .map(|f_i| {
+            if variance <= 0.0 || n == 0.0 {
+                return (0.0, 0.0);
+            }
+            let first: f64 = f_b
+                .iter()
+                .zip(f_i)
+                .zip(f_a)
+                .map(|((b, ab), a)| (b - mean) * (ab - a))
+                .sum::<f64>()
+                / n;
+            let total: f64 = f_a
+                .iter()
+                .zip(f_i)
+                .map(|(a, ab)| (a - ab).powi(2))
+                .sum::<f64>()
+                / (2.0 * n);
+            (first / variance, total / variance)
+        })
+        .collect()
+}
+
+/// Run a Saltelli analysis of `model` over `ranges`.
+///
+/// `model` maps one point (parameter values in range order) to one value
+/// per entry of `outcomes`. The two base matrices come from a single Latin
+/// hypercube over the doubled parameter space, so `base_samples` points
+/// cost `base_samples * (ranges.len() + 2)` model evaluations.
+pub fn saltelli<F>(
+    ranges: &[SweepRange],
+    outcomes: &[String],
+    base_samples: usize,
+    seed: u64,
+    model: F,
+) -> Result<SensitivityReport, ZaiSimError>
+where
+    F: Fn(&[f64]) -> Vec<f64> + Sync,
+{
+    let d = ranges.len();
+    if d == 0 {
+        return Err(ZaiSimError::InvalidInput(
+            "Sensitivity analysis needs at least one parameter range".to_string(),
+        ));
+    }
+    if base_samples < 2 {
+        return Err(ZaiSimError::InvalidInput(
+            "Sensitivity analysis needs at least 2 base samples".to_string(),
+        ));
+    }
+
+    let doubled: Vec<SweepRange> = ranges.iter().chain(ranges).cloned().collect();
+    let rows = SweepEngine::sample(
+        &doubled,
+        SamplingStrategy::LatinHypercube,
+        base_samples,
+        seed,
+    );
+    let a: Vec<Vec<f64>> = rows
+        .iter()
+        .map(|r| r[..d].iter().map(|(_, v)| *v).collect())
+        .collect();
+    let b: Vec<Vec<f64>> = rows
+        .iter()
+        .map(|r| r[d..].iter().map(|(_, v)| *v).collect())
+        .collect();
+
+    // Points in evaluation order: A, B, then A_B^i for each parameter i.
+    let mut points = Vec::with_capacity(base_samples * (d + 2));
+    points.extend(a.iter().cloned());
+    points.extend(b.iter().cloned());
+    for i in 0..d {
+        for (ra, rb) in a.iter().zip(&b) {
+            let mut p = ra.clone();
+            p[i] = rb[i];
+            points.push(p);
+        }
+    }
+    let values: Vec<Vec<f64>> = points.par_iter().map(|p| model(p)).collect();
+
+    let column = |k: usize, block: usize| -> Vec<f64> {
+        values[block * base_samples..(block + 1) * base_samples]
+            .iter()
+            .map(|v| v.get(k).copied().unwrap_or(0.0))
+            .collect()
+    };
+    let outcomes = outcomes
+        .iter()
+        .enumerate()
+        .map(|(k, outcome)| {
+            let f_a = column(k, 0);
+            let f_b = column(k, 1);
+            let f_ab: Vec<Vec<f64>> = (0..d).map(|i| column(k, i + 2)).collect();
+            let (mean, variance) = mean_variance(&f_a, &f_b);
+            OutcomeSensitivity {
+                outcome: outcome.clone(),
+                mean,
+                variance,
+                indices: ranges
+                    .iter()
+                    .zip(sobol_indices(&f_a, &f_b, &f_ab))
+                    .map(|(r, (first_order, total))| SobolIndex {
+                        param: r.name.clone(),
+                        first_order,
+                        total,
+                    })
+                    .collect(),
+            }
+        })
+        .collect();
+
+    Ok(SensitivityReport {
+        base_samples,
+        evaluations: values.len(),
+        outcomes,
+    })
+}
+
+/// Sobol indices of summary metrics (`SummaryMetrics` field names such as
+/// `total_bad_debt` or `max_peg_deviation`) over `ranges`. Each outcome is
+/// averaged across `scenarios`, all run at `seed`.
+pub fn run_sensitivity(
+    ranges: &[SweepRange],
+    outcomes: &[String],
+    base_samples: usize,
+    scenarios: &[ScenarioId],
+    blocks: usize,
+    seed: u64,
+) -> Result<SensitivityReport, ZaiSimError> {
+    if scenarios.is_empty() {
+        return Err(ZaiSimError::InvalidInput(
+            "Sensitivity analysis needs at least one scenario".to_string(),
+        ));
+    }
+    for outcome in outcomes {
+        if !SUMMARY_COLUMNS.contains(&outcome.as_str()) {
+            return Err(ZaiSimError::UnknownMetric(format!(
+                "{} (use one of {})",
+                outcome,
+                SUMMARY_COLUMNS.join(", ")
+            )));
+        }
+    }
+    let mut probe = ScenarioConfig::default();
+    for r in ranges {
+        SweepEngine::set_param(&mut probe, &r.name, r.min)?;
+    }
+
+    saltelli(ranges, outcomes, base_samples, seed, |point| {
+        let mut config = ScenarioConfig::default();
+        for (r, v) in ranges.iter().zip(point) {
+            let _ = SweepEngine::set_param(&mut config, &r.name, *v);
+        }
+        let mut totals = vec![0.0; outcomes.len()];
+        for &sid in scenarios {
+            let scenario = run_stress(sid, &config, blocks, seed);
+            let summary = compute_summary(&scenario.metrics, config.initial_redemption_price);
+            let json = serde_json::to_value(&summary).unwrap_or_default();
+            for (total, outcome) in totals.iter_mut().zip(outcomes) {
+                *total += json[outcome.as_str()].as_f64().unwrap_or(0.0);
+            }
+        }
+        totals
+            .into_iter()
+            .map(|t| t / scenarios.len() as f64)
+            .collect()
+    })
+}
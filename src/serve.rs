@@ -0,0 +1,414 @@
+//! Dashboard server for an output directory (`zai-sim serve`).
+//!
+//! Lists the saved runs under the directory, serves the generated reports
+//! and files as they are, and answers JSON queries for summaries, so a team
+//! can browse sweep results from one place. A minimal HTTP/1.1 server on
+//! `std::net`, one thread per connection, each dropped if the client stalls
+//! past a timeout; it only reads, and never serves anything outside the
+//! directory.
+//!
+//! | Path | Response |
+//! |------|----------|
+//! | `/` | HTML list of runs with links to their reports |
+//! | `/api/runs` | JSON list of runs |
+//! | `/api/runs/<run>` | The run's `metrics.json` |
+//! | `/api/summaries` | Every run's `metrics.json`, keyed by run |
+//! | `/api/sweep_results` | Rows of `sweep_results.csv` as JSON objects |
+//! | anything else | The file at that path under the directory |
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::error::ZaiSimError;
+use crate::report::html_escape;
+
+/// Report formats a run's report may have been saved in, in preference
+/// order.
+const REPORT_EXTENSIONS: [&str; 3] = ["html", "md", "pdf"];
+
+/// How long a connection may stall reading the request or taking the
+/// response before it is dropped.
+pub const DEFAULT_IO_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A saved run: a directory holding a `metrics.json` written by
+/// `output::save_all`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RunEntry {
+    /// Directory path relative to the served root, `/`-separated
+    pub name: String,
+    /// Report beside the directory (`<name>.html` etc.), relative to the root
+    pub report: Option<String>,
+    /// Whether the run's `timeseries.csv` is present
+    pub has_timeseries: bool,
+}
+
+/// An HTTP response.
+#[derive(Debug, Clone)]
+pub struct Response {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    fn ok(content_type: &'static str, body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            status: 200,
+            content_type,
+            body: body.into(),
+        }
+    }
+
+    fn json(value: &impl Serialize) -> Self {
+        match serde_json::to_vec_pretty(value) {
+            Ok(body) => Self::ok("application/json", body),
+            Err(e) => Self::error(500, &e.to_string()),
+        }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Self {
+            status,
+            content_type: "text/plain; charset=utf-8",
+            body: format!("{} {}\n", status, message).into_bytes(),
+        }
+    }
+
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            _ => "Internal Server Error",
+        }
+    }
+}
+
+fn content_type(path: &Path) -> &'static str {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("html") => "text/html; charset=utf-8",
+        Some("json") => "application/json",
+        Some("csv") => "text/csv; charset=utf-8",
+        Some("md") => "text/markdown; charset=utf-8",
+        Some("pdf") => "application/pdf",
+        Some("js") => "text/javascript",
+        Some("toml") | Some("txt") => "text/plain; charset=utf-8",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Decode `%XX` escapes; `None` if one is malformed or the result is not
+/// UTF-8.
+fn percent_decode(s: &str) -> Option<String> {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = std::str::from_utf8(bytes.get(i + 1..i + 3)?).ok()?;
+            out.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(out).ok()
+}
+
+/// A CSV field as a JSON number where it parses as one.
+fn csv_value(field: &str) -> Value {
+    field
+        .parse::<f64>()
+        .ok()
+        .and_then(serde_json::Number::from_f64)
+        .map_or_else(|| Value::String(field.to_string()), Value::Number)
+}
+
+/// Serves one output directory.
+#[derive(Debug, Clone)]
+pub struct Dashboard {
+    root: PathBuf,
+    io_timeout: Duration,
+}
+
+impl Dashboard {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            io_timeout: DEFAULT_IO_TIMEOUT,
+        }
+    }
+
+    /// Drop connections that stall for longer than `timeout` on a read or
+    /// write (default `DEFAULT_IO_TIMEOUT`).
+    pub fn with_io_timeout(mut self, timeout: Duration) -> Self {
+        self.io_timeout = timeout;
+        self
+    }
+
+    /// Saved runs under the root at any depth, sorted by name.
+    pub fn runs(&self) -> Result<Vec<RunEntry>, ZaiSimError> {
+        let mut runs = Vec::new();
+        self.find_runs(&self.root, &mut runs)?;
+        runs.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(runs)
+    }
+
+    fn find_runs(&self, dir: &Path, runs: &mut Vec<RunEntry>) -> Result<(), ZaiSimError> {
+        for entry in std::fs::read_dir(dir)? {
+            let entry = entry?;
+            // Not following symlinks, which could loop
+            if !entry.file_type()?.is_dir() {
+                continue;
+            }
+            let path = entry.path();
+            if path.join("metrics.json").is_file() {
+                let name = self.relative(&path);
+                let file_name = entry.file_name().to_string_lossy().into_owned();
+                let report = REPORT_EXTENSIONS
+                    .iter()
+                    .map(|ext| dir.join(format!("{}.{}", file_name, ext)))
+                    .find(|p| p.is_file())
+                    .map(|p| self.relative(&p));
+                runs.push(RunEntry {
+                    name,
+                    report,
+                    has_timeseries: path.join("timeseries.csv").is_file(),
+                });
+            }
+            self.find_runs(&path, runs)?;
+        }
+        Ok(())
+    }
+
+    fn relative(&self, path: &Path) -> String {
+        let rel = path.strip_prefix(&self.root).unwrap_or(path);
+        let parts: Vec<_> = rel
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect();
+        parts.join("/")
+    }
+
+    /// The file a request path names under the root, or `None` if it would
+    /// leave the root.
+    fn resolve(&self, rel: &str) -> Option<PathBuf> {
+        let mut path = self.root.clone();
+        for component in Path::new(rel).components() {
+            match component {
+                Component::Normal(part) => path.push(part),
+                Component::CurDir => {}
+                _ => return None,
+            }
+        }
+        Some(path)
+    }
+
+    /// Answer a request for `target` (path and optional query string).
+    pub fn handle(&self, method: &str, target: &str) -> Response {
+        if method != "GET" && method != "HEAD" {
+            return Response::error(405, "only GET is supported");
+        }
+        let path = target.split(['?', '#']).next().unwrap_or("");
+        let Some(path) = percent_decode(path) else {
+            return Response::error(400, "malformed path");
+        };
+        let path = path.trim_start_matches('/');
+
+        let result = match path {
+            "" => self.index(),
+            "api/runs" => self.runs().map(|runs| Response::json(&runs)),
+            "api/summaries" => self.summaries(),
+            "api/sweep_results" => self.sweep_results(),
+            _ => match path.strip_prefix("api/runs/") {
+                Some(run) => self.summary(run.trim_end_matches('/')),
+                None => self.file(path),
+            },
+        };
+        result.unwrap_or_else(|e| match e {
+            ZaiSimError::Io(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                Response::error(404, "not found")
+            }
+            ZaiSimError::InvalidInput(msg) => Response::error(400, &msg),
+            e => Response::error(500, &e.to_string()),
+        })
+    }
+
+    fn index(&self) -> Result<Response, ZaiSimError> {
+        let runs = self.runs()?;
+        let mut rows = String::new();
+        for run in &runs {
+            let name = html_escape(&run.name);
+            let report = run.report.as_ref().map_or_else(
+                || "—".to_string(),
+                |r| format!("<a href=\"/{}\">report</a>", html_escape(r)),
+            );
+            let timeseries = if run.has_timeseries {
+                format!("<a href=\"/{}/timeseries.csv\">timeseries.csv</a>", name)
+            } else {
+                "—".to_string()
+            };
+            rows.push_str(&format!(
+                "<tr><td>{name}</td><td>{report}</td>\
+                 <td><a href=\"/api/runs/{name}\">summary</a></td><td>{timeseries}</td></tr>\n",
+            ));
+        }
+        let mut links = String::new();
+        for (file, label) in [
+            ("index.html", "Master summary"),
+            ("sweep_results.csv", "Sweep results"),
+        ] {
+            if self.root.join(file).is_file() {
+                links.push_str(&format!("<li><a href=\"/{}\">{}</a></li>", file, label));
+            }
+        }
+
+        Ok(Response::ok(
+            "text/html; charset=utf-8",
+            format!(
+                r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="UTF-8">
+<title>ZAI Simulation — Runs</title>
+<style>
+body{{font-family:-apple-system,BlinkMacSystemFont,'Segoe UI',Roboto,sans-serif;background:#f5f5f5;color:#333;margin:0}}
+header{{background:#1a1a2e;color:#fff;padding:24px 32px}}
+header h1{{font-size:1.4em;font-weight:500;margin:0}}
+main{{max-width:1200px;margin:0 auto;padding:24px}}
+section{{background:#fff;border-radius:8px;box-shadow:0 1px 3px rgba(0,0,0,0.1);padding:24px;margin-bottom:20px}}
+table{{width:100%;border-collapse:collapse;font-size:0.9em}}
+th,td{{padding:10px 14px;text-align:left;border-bottom:1px solid #e0e0e0}}
+th{{background:#f8f9fa;font-weight:600}}
+a{{color:#4285f4;text-decoration:none}}
+a:hover{{text-decoration:underline}}
+</style>
+</head>
+<body>
+<header><h1>ZAI Simulation — {count} runs in {root}</h1></header>
+<main>
+<section><ul>{links}<li><a href="/api/summaries">All summaries (JSON)</a></li></ul></section>
+<section>
+<table>
+<tr><th>Run</th><th>Report</th><th>Summary</th><th>Time series</th></tr>
+{rows}</table>
+</section>
+</main>
+</body>
+</html>
+"#,
+                count = runs.len(),
+                root = html_escape(&self.root.display().to_string()),
+                links = links,
+                rows = rows,
+            ),
+        ))
+    }
+
+    fn read_summary(&self, run: &str) -> Result<Value, ZaiSimError> {
+        let dir = self
+            .resolve(run)
+            .ok_or_else(|| ZaiSimError::InvalidInput(format!("bad run name: {}", run)))?;
+        let text = std::fs::read_to_string(dir.join("metrics.json"))?;
+        Ok(serde_json::from_str(&text)?)
+    }
+
+    fn summary(&self, run: &str) -> Result<Response, ZaiSimError> {
+        Ok(Response::json(&self.read_summary(run)?))
+    }
+
+    fn summaries(&self) -> Result<Response, ZaiSimError> {
+        let mut all = Map::new();
+        for run in self.runs()? {
+            let summary = self.read_summary(&run.name)?;
+            all.insert(run.name, summary);
+        }
+        Ok(Response::json(&all))
+    }
+
+    fn sweep_results(&self) -> Result<Response, ZaiSimError> {
+        let mut rdr = csv::Reader::from_path(self.root.join("sweep_results.csv"))?;
+        let headers = rdr.headers()?.clone();
+        let mut rows = Vec::new();
+        for record in rdr.records() {
+            let row: Map<String, Value> = headers
+                .iter()
+                .zip(record?.iter())
+                .map(|(h, v)| (h.to_string(), csv_value(v)))
+                .collect();
+            rows.push(row);
+        }
+        Ok(Response::json(&rows))
+    }
+
+    fn file(&self, rel: &str) -> Result<Response, ZaiSimError> {
+        let Some(path) = self.resolve(rel).filter(|p| p.is_file()) else {
+            return Ok(Response::error(404, "not found"));
+        };
+        Ok(Response::ok(content_type(&path), std::fs::read(&path)?))
+    }
+
+    /// Read one request from `stream` and write the response.
+    fn respond(&self, mut stream: TcpStream) -> Result<(), ZaiSimError> {
+        stream.set_read_timeout(Some(self.io_timeout))?;
+        stream.set_write_timeout(Some(self.io_timeout))?;
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line)?;
+        // Headers are not used; read past them
+        let mut line = String::new();
+        while reader.read_line(&mut line)? > 2 {
+            line.clear();
+        }
+
+        let mut parts = request_line.split_whitespace();
+        let response = match (parts.next(), parts.next()) {
+            (Some(method), Some(target)) => self.handle(method, target),
+            _ => Response::error(400, "malformed request"),
+        };
+        write!(
+            stream,
+            "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            response.status,
+            response.reason(),
+            response.content_type,
+            response.body.len()
+        )?;
+        if !request_line.starts_with("HEAD ") {
+            stream.write_all(&response.body)?;
+        }
+        stream.flush()?;
+        Ok(())
+    }
+
+    /// Serve connections on `listener` indefinitely. A failed accept (the
+    /// client hung up first, or the process is out of file descriptors) is
+    /// logged and the server backs off briefly before accepting again.
+    pub fn serve(self, listener: TcpListener) -> Result<(), ZaiSimError> {
+        let dashboard = std::sync::Arc::new(self);
+        for stream in listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(e) => {
+                    tracing::warn!("accept failed: {}", e);
+                    std::thread::sleep(Duration::from_millis(50));
+                    continue;
+                }
+            };
+            let dashboard = dashboard.clone();
+            std::thread::spawn(move || {
+                if let Err(e) = dashboard.respond(stream) {
+                    tracing::warn!("request failed: {}", e);
+                }
+            });
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,172 @@
+//! Shielded-pool holdings and unshielding delay.
+//!
+//! Zcash users keep much of their balance in the shielded pool, and moving
+//! it out to trade takes time: a note has to be spent, the transaction
+//! mined and its outputs confirmed. Without a shielded model every agent's
+//! full balance is at the AMM's door. With one, each agent keeps
+//! `shielded_fraction` of its ZEC and ZAI shielded. Funds above the
+//! transparent share are shielded at once; a shortfall is unshielded in
+//! batches of at most the configured size, each arriving
+//! `unshield_delay_blocks` later, one batch per asset per agent per block.
+//!
+//! Arbers therefore trade from a smaller wallet and refill it slowly after a
+//! large move, and a bank run can only sell the transparent part of its
+//! ZAI before the rest trickles out.
+
+use std::collections::{BTreeMap, VecDeque};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ShieldedPoolConfig {
+    /// Fraction of each agent's ZEC and ZAI held shielded
+    pub shielded_fraction: f64,
+    /// Blocks from requesting an unshield until the funds can be traded
+    pub unshield_delay_blocks: u64,
+    /// Largest ZEC unshield per batch
+    pub max_batch_zec: f64,
+    /// Largest ZAI unshield per batch
+    pub max_batch_zai: f64,
+}
+
+impl Default for ShieldedPoolConfig {
+    fn default() -> Self {
+        ShieldedPoolConfig {
+            shielded_fraction: 0.5,
+            unshield_delay_blocks: 10,
+            max_batch_zec: 100.0,
+            max_batch_zai: 5000.0,
+        }
+    }
+}
+
+/// An unshield on its way to the agent's wallet.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Unshield {
+    arrives_at_block: u64,
+    zec: f64,
+    zai: f64,
+}
+
+/// One agent's shielded funds.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ShieldedAccount {
+    pub zec: f64,
+    pub zai: f64,
+    in_flight: VecDeque<Unshield>,
+}
+
+impl ShieldedAccount {
+    /// ZEC and ZAI unshielded but not yet arrived.
+    pub fn in_flight(&self) -> (f64, f64) {
+        self.in_flight
+            .iter()
+            .fold((0.0, 0.0), |(zec, zai), u| (zec + u.zec, zai + u.zai))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShieldedPool {
+    pub config: ShieldedPoolConfig,
+    /// Accounts by agent id (`arber_0`, `demand_3`, …)
+    pub accounts: BTreeMap<String, ShieldedAccount>,
+    pub total_unshielded_zec: f64,
+    pub total_unshielded_zai: f64,
+}
+
+impl ShieldedPool {
+    pub fn new(config: ShieldedPoolConfig) -> Self {
+        ShieldedPool {
+            config,
+            accounts: BTreeMap::new(),
+            total_unshielded_zec: 0.0,
+            total_unshielded_zai: 0.0,
+        }
+    }
+
+    /// Settle `agent`'s wallet at `block`: deliver unshields that have
+    /// arrived, shield any surplus over the transparent share and request
+    /// a batch toward any shortfall.
+    pub fn settle(&mut self, agent: &str, zec: &mut f64, zai: &mut f64, block: u64) {
+        let config = &self.config;
+        let account = self.accounts.entry(agent.to_string()).or_default();
+        while let Some(front) = account.in_flight.front() {
+            if front.arrives_at_block > block {
+                break;
+            }
+            let arrived = account.in_flight.pop_front().unwrap();
+            *zec += arrived.zec;
+            *zai += arrived.zai;
+            self.total_unshielded_zec += arrived.zec;
+            self.total_unshielded_zai += arrived.zai;
+        }
+
+        let (zec_in_flight, zai_in_flight) = account.in_flight();
+        let fraction = config.shielded_fraction.clamp(0.0, 1.0);
+        let zec_request = Self::rebalance(
+            zec,
+            &mut account.zec,
+            zec_in_flight,
+            fraction,
+            config.max_batch_zec,
+        );
+        let zai_request = Self::rebalance(
+            zai,
+            &mut account.zai,
+            zai_in_flight,
+            fraction,
+            config.max_batch_zai,
+        );
+        if zec_request > 0.0 || zai_request > 0.0 {
+            account.in_flight.push_back(Unshield {
+                arrives_at_block: block + config.unshield_delay_blocks,
+                zec: zec_request,
+                zai: zai_request,
+            });
+        }
+    }
+
+    /// Move one asset between wallet and pool toward the target split.
+    /// A surplus is shielded at once; returns the amount to unshield
+    /// toward a shortfall, already taken from the pool.
+    fn rebalance(
+        wallet: &mut f64,
+        shielded: &mut f64,
+        in_flight: f64,
+        fraction: f64,
+        max_batch: f64,
+    ) -> f64 {
+        let total = wallet.max(0.0) + *shielded + in_flight;
+        let target = total * (1.0 - fraction);
+        // What's already on its way counts toward the wallet
+        let excess = *wallet + in_flight - target;
+        if excess > 0.0 {
+            let surplus = excess.min(wallet.max(0.0));
+            *wallet -= surplus;
+            *shielded += surplus;
+            0.0
+        } else {
+            let batch = (-excess).min(*shielded).min(max_batch.max(0.0));
+            *shielded -= batch;
+            batch
+        }
+    }
+
+    /// Return all of `agent`'s shielded and in-flight funds to its wallet
+    /// at once.
+    pub fn release(&mut self, agent: &str, zec: &mut f64, zai: &mut f64) {
+        if let Some(account) = self.accounts.remove(agent) {
+            let (zec_in_flight, zai_in_flight) = account.in_flight();
+            *zec += account.zec + zec_in_flight;
+            *zai += account.zai + zai_in_flight;
+        }
+    }
+
+    /// ZEC and ZAI held shielded, including unshields in flight.
+    pub fn totals(&self) -> (f64, f64) {
+        self.accounts.values().fold((0.0, 0.0), |(zec, zai), a| {
+            let (zec_in_flight, zai_in_flight) = a.in_flight();
+            (zec + a.zec + zec_in_flight, zai + a.zai + zai_in_flight)
+        })
+    }
+}
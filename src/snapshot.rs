@@ -0,0 +1,222 @@
+//! Compact state snapshots for regression diffing.
+//!
+//! A snapshot captures the AMM reserves, debt, a vault collateral-ratio
+//! histogram and the controller state. Recording one every K blocks and
+//! diffing two runs snapshot-by-snapshot pinpoints the earliest block at
+//! which a code or config change altered the trajectory.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::ZaiSimError;
+use crate::scenario::Scenario;
+
+/// Upper bounds of the TWAP collateral-ratio histogram buckets.
+/// Six buckets: <1.0, 1.0–1.25, 1.25–1.5, 1.5–2.0, 2.0–3.0, >=3.0.
+pub const CR_BUCKET_BOUNDS: [f64; 5] = [1.0, 1.25, 1.5, 2.0, 3.0];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StateSnapshot {
+    pub block: u64,
+    pub reserve_zec: f64,
+    pub reserve_zai: f64,
+    pub total_debt: f64,
+    pub total_collateral: f64,
+    pub vault_count: u64,
+    pub cr_histogram: [u32; 6],
+    pub redemption_price: f64,
+    pub redemption_rate: f64,
+    pub controller_integral: f64,
+}
+
+impl StateSnapshot {
+    pub fn capture(scenario: &Scenario, block: u64) -> Self {
+        let twap = scenario.registry.get_price(&scenario.amm);
+        let mut cr_histogram = [0u32; 6];
+        for vault in scenario.registry.vaults.values() {
+            let cr = scenario.registry.ratio_due(vault, twap);
+            let bucket = CR_BUCKET_BOUNDS
+                .iter()
+                .position(|&bound| cr < bound)
+                .unwrap_or(CR_BUCKET_BOUNDS.len());
+            cr_histogram[bucket] += 1;
+        }
+
+        StateSnapshot {
+            block,
+            reserve_zec: scenario.amm.reserve_zec,
+            reserve_zai: scenario.amm.reserve_zai,
+            total_debt: scenario.registry.total_debt,
+            total_collateral: scenario
+                .registry
+                .vaults
+                .values()
+                .map(|v| v.collateral_zec)
+                .sum(),
+            vault_count: scenario.registry.vaults.len() as u64,
+            cr_histogram,
+            redemption_price: scenario.controller.redemption_price,
+            redemption_rate: scenario.controller.redemption_rate,
+            controller_integral: scenario.controller.integral,
+        }
+    }
+
+    /// Named numeric fields, in the order they are compared and written.
+    fn fields(&self) -> Vec<(String, f64)> {
+        let mut fields = vec![
+            ("reserve_zec".to_string(), self.reserve_zec),
+            ("reserve_zai".to_string(), self.reserve_zai),
+            ("total_debt".to_string(), self.total_debt),
+            ("total_collateral".to_string(), self.total_collateral),
+            ("vault_count".to_string(), self.vault_count as f64),
+        ];
+        for (i, count) in self.cr_histogram.iter().enumerate() {
+            fields.push((format!("cr_bucket_{}", i), *count as f64));
+        }
+        fields.push(("redemption_price".to_string(), self.redemption_price));
+        fields.push(("redemption_rate".to_string(), self.redemption_rate));
+        fields.push(("controller_integral".to_string(), self.controller_integral));
+        fields
+    }
+}
+
+/// A field that differs between two runs at a snapshot block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnapshotDiff {
+    pub block: u64,
+    pub field: String,
+    pub left: f64,
+    pub right: f64,
+}
+
+fn differs(a: f64, b: f64, rel_tol: f64) -> bool {
+    let scale = a.abs().max(b.abs());
+    if scale == 0.0 {
+        return false;
+    }
+    (a - b).abs() / scale > rel_tol
+}
+
+/// Compare two runs snapshot-by-snapshot (matched by block) and return every
+/// field whose relative difference exceeds `rel_tol`, in block order.
+/// A block present in only one run is reported as field `snapshot_missing`
+/// with 1.0 on the side that has it.
+pub fn diff_snapshots(
+    left: &[StateSnapshot],
+    right: &[StateSnapshot],
+    rel_tol: f64,
+) -> Vec<SnapshotDiff> {
+    let mut diffs = Vec::new();
+    let (mut i, mut j) = (0, 0);
+
+    while i < left.len() || j < right.len() {
+        let lb = left.get(i).map(|s| s.block);
+        let rb = right.get(j).map(|s| s.block);
+        match (lb, rb) {
+            (Some(l), Some(r)) if l == r => {
+                let lf = left[i].fields();
+                let rf = right[j].fields();
+                for ((field, a), (_, b)) in lf.into_iter().zip(rf) {
+                    if differs(a, b, rel_tol) {
+                        diffs.push(SnapshotDiff {
+                            block: l,
+                            field,
+                            left: a,
+                            right: b,
+                        });
+                    }
+                }
+                i += 1;
+                j += 1;
+            }
+            (Some(l), r) if r.is_none_or(|r| l < r) => {
+                diffs.push(SnapshotDiff {
+                    block: l,
+                    field: "snapshot_missing".to_string(),
+                    left: 1.0,
+                    right: 0.0,
+                });
+                i += 1;
+            }
+            (_, Some(r)) => {
+                diffs.push(SnapshotDiff {
+                    block: r,
+                    field: "snapshot_missing".to_string(),
+                    left: 0.0,
+                    right: 1.0,
+                });
+                j += 1;
+            }
+            _ => break,
+        }
+    }
+
+    diffs
+}
+
+/// The earliest block at which two runs diverge, if any.
+pub fn first_divergence(
+    left: &[StateSnapshot],
+    right: &[StateSnapshot],
+    rel_tol: f64,
+) -> Option<SnapshotDiff> {
+    diff_snapshots(left, right, rel_tol).into_iter().next()
+}
+
+/// Save snapshots to CSV.
+pub fn save_snapshots_csv(snapshots: &[StateSnapshot], path: &Path) -> Result<(), ZaiSimError> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut wtr = csv::Writer::from_path(path)?;
+
+    let mut header = vec!["block".to_string()];
+    if let Some(first) = snapshots.first() {
+        header.extend(first.fields().into_iter().map(|(name, _)| name));
+    }
+    wtr.write_record(&header)?;
+
+    for s in snapshots {
+        let mut record = vec![s.block.to_string()];
+        record.extend(s.fields().into_iter().map(|(_, v)| format!("{:e}", v)));
+        wtr.write_record(&record)?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+/// Load snapshots written by `save_snapshots_csv`.
+pub fn load_snapshots_csv(path: &Path) -> Result<Vec<StateSnapshot>, ZaiSimError> {
+    let mut rdr = csv::Reader::from_path(path)?;
+    let mut snapshots = Vec::new();
+
+    for result in rdr.records() {
+        let record = result?;
+        if record.len() != 15 {
+            return Err(ZaiSimError::Parse(format!(
+                "Expected 15 snapshot columns, got {}",
+                record.len()
+            )));
+        }
+        let f = |i: usize| -> Result<f64, ZaiSimError> { Ok(record[i].parse::<f64>()?) };
+        let mut cr_histogram = [0u32; 6];
+        for (k, bucket) in cr_histogram.iter_mut().enumerate() {
+            *bucket = f(6 + k)? as u32;
+        }
+        snapshots.push(StateSnapshot {
+            block: record[0].parse()?,
+            reserve_zec: f(1)?,
+            reserve_zai: f(2)?,
+            total_debt: f(3)?,
+            total_collateral: f(4)?,
+            vault_count: f(5)? as u64,
+            cr_histogram,
+            redemption_price: f(12)?,
+            redemption_rate: f(13)?,
+            controller_integral: f(14)?,
+        });
+    }
+
+    Ok(snapshots)
+}
@@ -1,6 +1,14 @@
+use crate::error::ZaiSimError;
+use crate::liquidation::PenaltySink;
+use crate::progress::ProgressMonitor;
 use crate::scenario::ScenarioConfig;
 use crate::scenarios::{run_stress, ScenarioId};
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
 use rayon::prelude::*;
+use std::str::FromStr;
+use std::sync::Arc;
 
 /// A parameter to sweep over.
 #[derive(Debug, Clone)]
@@ -9,6 +17,71 @@ pub struct SweepParam {
     pub values: Vec<f64>,
 }
 
+/// A continuous range to sample a parameter from, e.g. `min_ratio=1.5:2.5`.
+///
+/// `name` is a sweep alias (`min_ratio`, `swap_fee`, ...) or a dotted path to
+/// any numeric `ScenarioConfig` field (`cdp_config.debt_floor`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct SweepRange {
+    pub name: String,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl FromStr for SweepRange {
+    type Err = ZaiSimError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ZaiSimError::Parse(format!("Invalid range: {} (use name=min:max)", s));
+        let (name, range) = s.split_once('=').ok_or_else(invalid)?;
+        let (min, max) = range.split_once(':').ok_or_else(invalid)?;
+        let parse = |v: &str| {
+            v.trim()
+                .parse::<f64>()
+                .map_err(|_| ZaiSimError::Parse(format!("Invalid number in range {}: {}", s, v)))
+        };
+        let (min, max) = (parse(min)?, parse(max)?);
+        if min > max {
+            return Err(ZaiSimError::Parse(format!(
+                "Invalid range {}: min is above max",
+                s
+            )));
+        }
+        Ok(SweepRange {
+            name: name.trim().to_string(),
+            min,
+            max,
+        })
+    }
+}
+
+/// How points are drawn from a set of `SweepRange`s.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SamplingStrategy {
+    /// Evenly spaced grid with the same number of points on every axis
+    Grid,
+    /// Independent uniform draws
+    Random,
+    /// Latin hypercube: each axis split into N strata, each used exactly once
+    LatinHypercube,
+}
+
+impl FromStr for SamplingStrategy {
+    type Err = ZaiSimError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "grid" => Ok(Self::Grid),
+            "random" => Ok(Self::Random),
+            "lhs" | "latin-hypercube" => Ok(Self::LatinHypercube),
+            _ => Err(ZaiSimError::Parse(format!(
+                "Unknown sampling strategy: {} (use grid, random or lhs)",
+                s
+            ))),
+        }
+    }
+}
+
 /// Result of evaluating one parameter combination.
 #[derive(Debug, Clone)]
 pub struct SweepResult {
@@ -22,6 +95,10 @@ pub struct SweepEngine {
     pub blocks: usize,
     pub seed: u64,
     pub target_price: f64,
+    /// Told about every finished run, for live progress
+    pub progress: Option<Arc<ProgressMonitor>>,
+    /// Config every sweep point starts from before its params are applied
+    pub base_config: ScenarioConfig,
 }
 
 impl SweepEngine {
@@ -30,6 +107,32 @@ impl SweepEngine {
             blocks,
             seed,
             target_price,
+            progress: None,
+            base_config: ScenarioConfig::default(),
+        }
+    }
+
+    /// Sweep around `config` instead of the defaults.
+    pub fn with_config(mut self, config: ScenarioConfig) -> Self {
+        self.base_config = config;
+        self
+    }
+
+    /// Report each finished run to `progress`.
+    pub fn with_progress(mut self, progress: Arc<ProgressMonitor>) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    fn start_stage(&self, stage: &str, scenarios: &[ScenarioId], runs: usize) {
+        if let Some(progress) = &self.progress {
+            progress.start_stage(stage, scenarios, runs as u64);
+        }
+    }
+
+    fn record(&self, id: ScenarioId, scenario: &crate::scenario::Scenario) {
+        if let Some(progress) = &self.progress {
+            progress.record(id, scenario);
         }
     }
 
@@ -43,34 +146,28 @@ impl SweepEngine {
         // Peg stability: mean absolute deviation
         let mean_dev: f64 = scenario
             .metrics
+            .amm_spot_price
             .iter()
-            .map(|m| ((m.amm_spot_price - self.target_price) / self.target_price).abs())
+            .map(|p| ((p - self.target_price) / self.target_price).abs())
             .sum::<f64>()
             / n;
 
         // Bad debt ratio
-        let bad_debt = scenario
-            .metrics
-            .last()
-            .map(|m| m.bad_debt)
-            .unwrap_or(0.0);
+        let bad_debt = scenario.metrics.bad_debt.last().copied().unwrap_or(0.0);
         let max_debt = scenario
             .metrics
+            .total_debt
             .iter()
-            .map(|m| m.total_debt)
+            .cloned()
             .fold(1.0_f64, f64::max);
         let bad_debt_ratio = bad_debt / max_debt;
 
         // Halt ratio
-        let halt_blocks = scenario.metrics.iter().filter(|m| m.halted).count() as f64;
+        let halt_blocks = scenario.metrics.halted.iter().filter(|h| **h).count() as f64;
         let halt_ratio = halt_blocks / n;
 
         // Liquidation intensity
-        let total_liqs: u32 = scenario
-            .metrics
-            .iter()
-            .map(|m| m.liquidation_count)
-            .sum();
+        let total_liqs: u32 = scenario.metrics.liquidation_count.iter().sum();
         let liq_ratio = total_liqs as f64 / n;
 
         -(0.4 * mean_dev + 0.3 * bad_debt_ratio + 0.2 * halt_ratio + 0.1 * liq_ratio)
@@ -79,22 +176,154 @@ impl SweepEngine {
     /// Apply parameter overrides to a config.
     fn apply_params(config: &mut ScenarioConfig, params: &[(String, f64)]) {
         for (name, val) in params {
-            match name.as_str() {
-                "min_ratio" => config.cdp_config.min_ratio = *val,
-                "swap_fee" => config.amm_swap_fee = *val,
-                "liquidation_penalty" => config.cdp_config.liquidation_penalty = *val,
-                "stability_fee_rate" => config.cdp_config.stability_fee_rate = *val,
-                "twap_breaker_threshold" => {
-                    config.twap_breaker_config.max_twap_change_pct = *val
+            let _ = Self::set_param(config, name, *val);
+        }
+    }
+
+    /// Set one parameter by sweep alias or dotted `ScenarioConfig` path.
+    ///
+    /// `penalty_to_<sink>` (keeper, lps, insurance, treasury, burn) sets one
+    /// share of the penalty routing and rescales the rest to keep the sum at
+    /// one. Integer fields are rounded; `Option` fields that are `None` are set
+    /// to `Some(value)`.
+    pub fn set_param(config: &mut ScenarioConfig, name: &str, val: f64) -> Result<(), ZaiSimError> {
+        match name {
+            "min_ratio" => config.cdp_config.min_ratio = val,
+            "swap_fee" => config.amm_swap_fee = val,
+            "liquidation_penalty" => config.cdp_config.liquidation_penalty = val,
+            "stability_fee_rate" => config.cdp_config.stability_fee_rate = val,
+            "twap_breaker_threshold" => config.twap_breaker_config.max_twap_change_pct = val,
+            "cascade_max_liqs" => {
+                config.cascade_breaker_config.max_liquidations_in_window = val as u32
+            }
+            name if name.starts_with("penalty_to_") => {
+                let sink: PenaltySink = name["penalty_to_".len()..].parse()?;
+                let routing = config.liquidation_config.routing().with_share(sink, val)?;
+                config.liquidation_config.penalty_routing = Some(routing);
+            }
+            path => {
+                let mut json = serde_json::to_value(&*config)?;
+                let mut field = &mut json;
+                for key in path.split('.') {
+                    field = field
+                        .as_object_mut()
+                        .and_then(|obj| obj.get_mut(key))
+                        .ok_or_else(|| {
+                            ZaiSimError::Config(format!("Unknown sweep parameter: {}", path))
+                        })?;
                 }
-                "cascade_max_liqs" => {
-                    config.cascade_breaker_config.max_liquidations_in_window = *val as u32
+                *field = match field {
+                    serde_json::Value::Number(n) if n.is_u64() || n.is_i64() => {
+                        serde_json::json!(val.round() as i64)
+                    }
+                    serde_json::Value::Number(_) | serde_json::Value::Null => {
+                        serde_json::json!(val)
+                    }
+                    _ => {
+                        return Err(ZaiSimError::Config(format!(
+                            "Sweep parameter is not numeric: {}",
+                            path
+                        )))
+                    }
+                };
+                *config = serde_json::from_value(json).map_err(|e| {
+                    ZaiSimError::Config(format!("Cannot set {} = {}: {}", path, val, e))
+                })?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Draw `samples` points from `ranges` with the given strategy.
+    ///
+    /// Grid sampling uses the largest per-axis count whose product fits the
+    /// budget, so it may return fewer than `samples` points.
+    pub fn sample(
+        ranges: &[SweepRange],
+        strategy: SamplingStrategy,
+        samples: usize,
+        seed: u64,
+    ) -> Vec<Vec<(String, f64)>> {
+        if ranges.is_empty() || samples == 0 {
+            return vec![];
+        }
+        let mut rng = ChaCha12Rng::seed_from_u64(seed);
+        let at = |r: &SweepRange, u: f64| r.min + u * (r.max - r.min);
+
+        match strategy {
+            SamplingStrategy::Grid => {
+                let dims = ranges.len() as u32;
+                let mut per_axis = 1usize;
+                while (per_axis + 1).checked_pow(dims).is_some_and(|n| n <= samples) {
+                    per_axis += 1;
                 }
-                _ => {}
+                let params: Vec<SweepParam> = ranges
+                    .iter()
+                    .map(|r| SweepParam {
+                        name: r.name.clone(),
+                        values: (0..per_axis)
+                            .map(|i| match per_axis {
+                                1 => at(r, 0.5),
+                                n => at(r, i as f64 / (n - 1) as f64),
+                            })
+                            .collect(),
+                    })
+                    .collect();
+                Self::cartesian_product(&params)
+            }
+            SamplingStrategy::Random => (0..samples)
+                .map(|_| {
+                    ranges
+                        .iter()
+                        .map(|r| (r.name.clone(), at(r, rng.gen::<f64>())))
+                        .collect()
+                })
+                .collect(),
+            SamplingStrategy::LatinHypercube => {
+                let strata: Vec<Vec<usize>> = ranges
+                    .iter()
+                    .map(|_| {
+                        let mut perm: Vec<usize> = (0..samples).collect();
+                        perm.shuffle(&mut rng);
+                        perm
+                    })
+                    .collect();
+                (0..samples)
+                    .map(|i| {
+                        ranges
+                            .iter()
+                            .zip(&strata)
+                            .map(|(r, perm)| {
+                                let u = (perm[i] as f64 + rng.gen::<f64>()) / samples as f64;
+                                (r.name.clone(), at(r, u))
+                            })
+                            .collect()
+                    })
+                    .collect()
             }
         }
     }
 
+    /// Evaluate `samples` points drawn from `ranges` across `scenarios`,
+    /// best first. Fails up front if any range names an unknown parameter.
+    pub fn run_sampled(
+        &self,
+        ranges: &[SweepRange],
+        strategy: SamplingStrategy,
+        samples: usize,
+        scenarios: &[ScenarioId],
+    ) -> Result<Vec<SweepResult>, ZaiSimError> {
+        let mut probe = self.base_config.clone();
+        for r in ranges {
+            Self::set_param(&mut probe, &r.name, r.min)?;
+        }
+        let combos = Self::sample(ranges, strategy, samples, self.seed);
+        self.start_stage("samples", scenarios, combos.len());
+        let mut results = self.evaluate(&combos, scenarios);
+        Self::sort_results(&mut results);
+        Ok(results)
+    }
+
     /// Generate all parameter combinations (cartesian product).
     fn cartesian_product(params: &[SweepParam]) -> Vec<Vec<(String, f64)>> {
         if params.is_empty() {
@@ -121,7 +350,16 @@ impl SweepEngine {
         scenarios: &[ScenarioId],
     ) -> Vec<SweepResult> {
         let combos = Self::cartesian_product(params);
+        self.start_stage("grid", scenarios, combos.len());
+        self.evaluate(&combos, scenarios)
+    }
 
+    /// Evaluate each param combo across `scenarios` at the engine's seed.
+    fn evaluate(
+        &self,
+        combos: &[Vec<(String, f64)>],
+        scenarios: &[ScenarioId],
+    ) -> Vec<SweepResult> {
         combos
             .par_iter()
             .map(|combo| {
@@ -129,9 +367,10 @@ impl SweepEngine {
                 let mut total = 0.0;
 
                 for &sid in scenarios {
-                    let mut config = ScenarioConfig::default();
+                    let mut config = self.base_config.clone();
                     Self::apply_params(&mut config, combo);
                     let scenario = run_stress(sid, &config, self.blocks, self.seed);
+                    self.record(sid, &scenario);
                     let s = self.score(&scenario);
                     scores.push((sid, s));
                     total += s;
@@ -153,6 +392,7 @@ impl SweepEngine {
         scenarios: &[ScenarioId],
         iterations: usize,
     ) -> Vec<SweepResult> {
+        self.start_stage("monte carlo", scenarios, configs.len() * iterations);
         configs
             .par_iter()
             .map(|combo| {
@@ -166,9 +406,10 @@ impl SweepEngine {
                 for iter in 0..iterations {
                     let seed = self.seed.wrapping_add(iter as u64);
                     for entry in &mut scenario_totals {
-                        let mut config = ScenarioConfig::default();
+                        let mut config = self.base_config.clone();
                         Self::apply_params(&mut config, combo);
                         let scenario = run_stress(entry.0, &config, self.blocks, seed);
+                        self.record(entry.0, &scenario);
                         let s = self.score(&scenario);
                         entry.1 += s;
                         entry.2 += 1;
@@ -0,0 +1,221 @@
+//! Protocol treasury: surplus buffer and bad-debt auctions.
+//!
+//! Stability fees and liquidation penalties accrue to the treasury as ZAI
+//! surplus, and penalties routed to insurance fill a separate insurance fund.
+//! Bad debt is absorbed from the insurance fund first, then the surplus;
+//! whatever neither can cover becomes uncovered debt that debt auctions
+//! recapitalize by minting a governance-token proxy and selling it for ZAI,
+//! which is burned.
+
+use serde::{Deserialize, Serialize};
+
+use crate::amm::Amm;
+use crate::cdp::VaultRegistry;
+use crate::liquidation::LiquidationEngine;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum DebtAuctionMode {
+    /// Uncovered bad debt stays on the books
+    Disabled,
+    /// Mint governance tokens at a discount and sell them for ZAI (MakerDAO flop)
+    GovernanceToken {
+        /// Reference governance-token price in ZAI
+        token_price_zai: f64,
+        /// Discount offered to bidders (0.05 = tokens sold 5% below reference)
+        discount: f64,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TreasuryConfig {
+    pub auction_mode: DebtAuctionMode,
+    /// Maximum ZAI raised per auction
+    pub auction_lot_zai: f64,
+    /// Minimum blocks between auctions
+    pub auction_interval_blocks: u64,
+    /// Bidders source the ZAI they bid by buying it on the AMM with ZEC
+    pub bidders_buy_zai_on_amm: bool,
+}
+
+impl Default for TreasuryConfig {
+    fn default() -> Self {
+        TreasuryConfig {
+            auction_mode: DebtAuctionMode::GovernanceToken {
+                token_price_zai: 1000.0,
+                discount: 0.05,
+            },
+            auction_lot_zai: 5000.0,
+            auction_interval_blocks: 10,
+            bidders_buy_zai_on_amm: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DebtAuction {
+    pub block: u64,
+    pub zai_raised: f64,
+    pub gov_tokens_minted: f64,
+    /// ZEC bidders sold on the AMM to source the ZAI (0 if sourced externally)
+    pub zec_sold_on_amm: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Treasury {
+    pub config: TreasuryConfig,
+    /// Surplus buffer in ZAI
+    pub balance_zai: f64,
+    /// Insurance fund in ZAI, funded by penalty routing
+    pub insurance_fund_zai: f64,
+    pub total_fees_collected: f64,
+    pub total_penalties_collected: f64,
+    pub total_insurance_received: f64,
+    pub total_bad_debt_absorbed: f64,
+    /// Bad debt not yet covered by surplus or auctions
+    pub uncovered_bad_debt: f64,
+    pub total_auction_proceeds: f64,
+    pub total_gov_tokens_minted: f64,
+    pub auctions: Vec<DebtAuction>,
+    last_auction_block: Option<u64>,
+    // Running totals already credited, so each sync only picks up new income
+    seen_fees: f64,
+    seen_penalties: f64,
+    seen_insurance: f64,
+    seen_bad_debt: f64,
+}
+
+impl Treasury {
+    pub fn new(config: TreasuryConfig) -> Self {
+        Treasury {
+            config,
+            balance_zai: 0.0,
+            insurance_fund_zai: 0.0,
+            total_fees_collected: 0.0,
+            total_penalties_collected: 0.0,
+            total_insurance_received: 0.0,
+            total_bad_debt_absorbed: 0.0,
+            uncovered_bad_debt: 0.0,
+            total_auction_proceeds: 0.0,
+            total_gov_tokens_minted: 0.0,
+            auctions: Vec::new(),
+            last_auction_block: None,
+            seen_fees: 0.0,
+            seen_penalties: 0.0,
+            seen_insurance: 0.0,
+            seen_bad_debt: 0.0,
+        }
+    }
+
+    /// Credit surplus income in ZAI.
+    pub fn deposit_surplus(&mut self, amount: f64) {
+        if amount > 0.0 {
+            self.balance_zai += amount;
+        }
+    }
+
+    /// Absorb bad debt from the insurance fund, then the surplus buffer.
+    /// Returns the uncovered shortfall.
+    pub fn absorb_bad_debt(&mut self, amount: f64) -> f64 {
+        if amount <= 0.0 {
+            return 0.0;
+        }
+        let from_insurance = amount.min(self.insurance_fund_zai);
+        self.insurance_fund_zai -= from_insurance;
+        let absorbed = (amount - from_insurance).min(self.balance_zai);
+        self.balance_zai -= absorbed;
+        self.total_bad_debt_absorbed += from_insurance + absorbed;
+
+        let shortfall = amount - from_insurance - absorbed;
+        self.uncovered_bad_debt += shortfall;
+        shortfall
+    }
+
+    /// Pick up fees, penalties and bad debt accrued since the last sync.
+    /// `fees_routed_to_lps` is the part of this block's fee accrual that was
+    /// paid to LPs instead of the treasury.
+    pub fn sync(
+        &mut self,
+        registry: &VaultRegistry,
+        engine: &LiquidationEngine,
+        fees_routed_to_lps: f64,
+    ) {
+        let fees = registry.total_fees_accrued - self.seen_fees - fees_routed_to_lps;
+        self.seen_fees = registry.total_fees_accrued;
+        if fees > 0.0 {
+            self.total_fees_collected += fees;
+            self.deposit_surplus(fees);
+        }
+
+        let penalties = engine.total_penalties_collected - self.seen_penalties;
+        self.seen_penalties = engine.total_penalties_collected;
+        if penalties > 0.0 {
+            self.total_penalties_collected += penalties;
+            self.deposit_surplus(penalties);
+        }
+
+        let insurance = engine.total_penalties_to_insurance - self.seen_insurance;
+        self.seen_insurance = engine.total_penalties_to_insurance;
+        if insurance > 0.0 {
+            self.total_insurance_received += insurance;
+            self.insurance_fund_zai += insurance;
+        }
+
+        let bad_debt = engine.total_bad_debt - self.seen_bad_debt;
+        self.seen_bad_debt = engine.total_bad_debt;
+        self.absorb_bad_debt(bad_debt);
+    }
+
+    /// Run a debt auction if uncovered bad debt remains and the interval has passed.
+    pub fn run_debt_auction(&mut self, amm: &mut Amm, block: u64) -> Option<DebtAuction> {
+        if self.uncovered_bad_debt <= 0.0 {
+            return None;
+        }
+        let (token_price_zai, discount) = match self.config.auction_mode {
+            DebtAuctionMode::Disabled => return None,
+            DebtAuctionMode::GovernanceToken {
+                token_price_zai,
+                discount,
+            } => (token_price_zai, discount),
+        };
+        if let Some(last) = self.last_auction_block {
+            if block < last + self.config.auction_interval_blocks {
+                return None;
+            }
+        }
+
+        let lot = self.uncovered_bad_debt.min(self.config.auction_lot_zai);
+        let mut zai_raised = lot;
+        let mut zec_sold_on_amm = 0.0;
+
+        if self.config.bidders_buy_zai_on_amm {
+            // Invert the constant-product formula to find the ZEC input for `lot` ZAI out
+            let fee_mult = 1.0 - amm.swap_fee;
+            if lot >= amm.reserve_zai {
+                return None;
+            }
+            let zec_in = amm.reserve_zec * lot / ((amm.reserve_zai - lot) * fee_mult);
+            match amm.swap_zec_for_zai(zec_in, block) {
+                Ok(zai_out) => {
+                    zai_raised = zai_out.min(lot);
+                    zec_sold_on_amm = zec_in;
+                }
+                Err(_) => return None,
+            }
+        }
+
+        let tokens = zai_raised / (token_price_zai * (1.0 - discount));
+        self.uncovered_bad_debt -= zai_raised;
+        self.total_auction_proceeds += zai_raised;
+        self.total_gov_tokens_minted += tokens;
+        self.last_auction_block = Some(block);
+
+        let auction = DebtAuction {
+            block,
+            zai_raised,
+            gov_tokens_minted: tokens,
+            zec_sold_on_amm,
+        };
+        self.auctions.push(auction.clone());
+        Some(auction)
+    }
+}
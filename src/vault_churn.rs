@@ -0,0 +1,167 @@
+//! Stochastic vault arrivals and closures.
+//!
+//! A scenario's vault book is normally set up once and only shrinks as
+//! vaults are liquidated. With a churn process it lives instead: every open
+//! holder vault closes with `closure_rate` per block, and new CDP holders
+//! arrive as a Poisson process, each opening a vault at a collateral ratio
+//! drawn uniformly from `ratio_range` with log-normal debt around
+//! `mean_debt`. Arrivals replace the vaults expected to close, at
+//! `closure_rate` times the book size, and never fall below `arrival_rate`
+//! per block, so on a flat market a book holds its size and a small one
+//! fills up.
+//!
+//! Both rates respond to the ZEC price trend (spot over its moving average,
+//! minus one) and the stability fee: arrivals scale by
+//! `exp(trend_sensitivity * trend - fee_sensitivity * (fee - reference_fee))`
+//! and closures by the inverse, so a rally at a cheap fee grows the book
+//! and a slide or an expensive fee winds it down.
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use rand_distr::{Distribution, Poisson, StandardNormal};
+use serde::{Deserialize, Serialize};
+
+use crate::agents::CdpHolderConfig;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VaultChurnConfig {
+    /// Least expected new vaults per block on a flat trend at the reference
+    /// fee, however small the book
+    pub arrival_rate: f64,
+    /// Per-block chance an open holder vault closes on a flat trend at the
+    /// reference fee
+    pub closure_rate: f64,
+    /// Log change in both rates per unit of trend (spot / average - 1)
+    pub trend_sensitivity: f64,
+    /// Span of the price moving average the trend is measured against
+    /// (blocks)
+    pub trend_memory_blocks: u64,
+    /// Annual stability fee at which the base rates apply
+    pub reference_fee_rate: f64,
+    /// Log change in both rates per unit of annual fee over the reference
+    pub fee_sensitivity: f64,
+    /// Collateral ratios new vaults open at, drawn uniformly
+    pub ratio_range: (f64, f64),
+    /// Median debt of a new vault (ZAI)
+    pub mean_debt: f64,
+    /// Log-normal spread of new vault debt
+    pub debt_sigma: f64,
+    /// Configuration for arriving holders; collateral and debt are drawn
+    pub new_holder: CdpHolderConfig,
+}
+
+impl Default for VaultChurnConfig {
+    fn default() -> Self {
+        VaultChurnConfig {
+            arrival_rate: 0.01,
+            closure_rate: 0.0005,
+            trend_sensitivity: 3.0,
+            trend_memory_blocks: 576, // ~12 hours
+            reference_fee_rate: 0.02,
+            fee_sensitivity: 50.0,
+            ratio_range: (2.0, 4.0),
+            mean_debt: 1000.0,
+            debt_sigma: 0.5,
+            new_holder: CdpHolderConfig::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VaultChurn {
+    pub config: VaultChurnConfig,
+    /// Moving average of the ZEC price; `None` until the first step
+    pub average_price: Option<f64>,
+    /// Per-block closure chance from the last step
+    pub closure_probability: f64,
+    pub total_opened: u64,
+    pub total_closed: u64,
+    /// Arrivals turned away by the debt ceiling, minting limits or a pause
+    pub total_refused: u64,
+    rng: ChaCha12Rng,
+}
+
+impl VaultChurn {
+    pub fn new(config: VaultChurnConfig, seed: u64) -> Self {
+        VaultChurn {
+            config,
+            average_price: None,
+            closure_probability: 0.0,
+            total_opened: 0,
+            total_closed: 0,
+            total_refused: 0,
+            rng: ChaCha12Rng::seed_from_u64(seed),
+        }
+    }
+
+    /// Price trend against the moving average: spot / average - 1.
+    pub fn trend(&self, price: f64) -> f64 {
+        match self.average_price {
+            Some(average) if average > 0.0 && price.is_finite() => price / average - 1.0,
+            _ => 0.0,
+        }
+    }
+
+    /// Log multiplier on arrivals for `trend` and annual `fee_rate`;
+    /// closures take its negative.
+    pub fn sentiment(&self, trend: f64, fee_rate: f64) -> f64 {
+        let c = &self.config;
+        c.trend_sensitivity * trend - c.fee_sensitivity * (fee_rate - c.reference_fee_rate)
+    }
+
+    /// Expected arrivals per block for `trend` and `fee_rate` with
+    /// `open_vaults` in the book.
+    pub fn arrival_rate(&self, trend: f64, fee_rate: f64, open_vaults: usize) -> f64 {
+        let replacement = self.config.closure_rate.clamp(0.0, 1.0) * open_vaults as f64;
+        let base = self.config.arrival_rate.max(replacement).max(0.0);
+        base * self.sentiment(trend, fee_rate).exp()
+    }
+
+    /// Per-block closure chance of an open vault for `trend` and
+    /// `fee_rate`.
+    pub fn closure_rate(&self, trend: f64, fee_rate: f64) -> f64 {
+        (self.config.closure_rate.max(0.0) * (-self.sentiment(trend, fee_rate)).exp()).min(1.0)
+    }
+
+    /// Advance one block at ZEC `price` and annual `fee_rate` with
+    /// `open_vaults` in the book: update the trend and closure chance and
+    /// draw this block's arrivals, each a holder config with its vault
+    /// sized at `price`.
+    pub fn step(&mut self, price: f64, fee_rate: f64, open_vaults: usize) -> Vec<CdpHolderConfig> {
+        let trend = self.trend(price);
+        if price.is_finite() && price > 0.0 {
+            let alpha = 2.0 / (self.config.trend_memory_blocks as f64 + 1.0);
+            let average = self.average_price.get_or_insert(price);
+            *average += alpha * (price - *average);
+        }
+        self.closure_probability = self.closure_rate(trend, fee_rate);
+
+        let lambda = self.arrival_rate(trend, fee_rate, open_vaults);
+        let arrivals = match Poisson::new(lambda) {
+            Ok(poisson) if price > 0.0 => poisson.sample(&mut self.rng) as usize,
+            _ => 0,
+        };
+        (0..arrivals).map(|_| self.sample_holder(price)).collect()
+    }
+
+    /// Draw whether one open vault closes this block.
+    pub fn closes(&mut self) -> bool {
+        self.closure_probability > 0.0 && self.rng.gen::<f64>() < self.closure_probability
+    }
+
+    fn sample_holder(&mut self, price: f64) -> CdpHolderConfig {
+        let (lo, hi) = self.config.ratio_range;
+        let ratio = if hi > lo {
+            self.rng.gen_range(lo..hi)
+        } else {
+            lo
+        };
+        let z: f64 = self.rng.sample(StandardNormal);
+        let debt = self.config.mean_debt * (self.config.debt_sigma * z).exp();
+        CdpHolderConfig {
+            initial_collateral: ratio * debt / price,
+            initial_debt: debt,
+            ..self.config.new_holder.clone()
+        }
+    }
+}
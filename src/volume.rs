@@ -0,0 +1,70 @@
+//! Agent throughput scaled by historical trading volume.
+//!
+//! Klines carry each candle's traded volume. A volume profile turns that
+//! into a per-block multiplier relative to the series' median candle, so
+//! miner selling, demand buying and arber capital replenishment thin out on
+//! quiet weekends and nights and pick up when the market is busy.
+
+use serde::{Deserialize, Serialize};
+
+/// How candle volume maps to agent throughput.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VolumeScalingConfig {
+    /// Throughput scales as `(volume / median volume) ^ exponent`;
+    /// 1.0 is proportional, 0.5 damps swings
+    pub exponent: f64,
+    /// Floor on the multiplier, so empty candles don't stop agents entirely
+    pub min_scale: f64,
+    /// Cap on the multiplier for volume spikes
+    pub max_scale: f64,
+}
+
+impl Default for VolumeScalingConfig {
+    fn default() -> Self {
+        VolumeScalingConfig {
+            exponent: 1.0,
+            min_scale: 0.1,
+            max_scale: 5.0,
+        }
+    }
+}
+
+/// Per-block throughput multipliers derived from candle volumes.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VolumeProfile {
+    /// Multiplier for block `i + 1`
+    pub factors: Vec<f64>,
+}
+
+impl VolumeProfile {
+    /// Multipliers for one volume per block, relative to the median of the
+    /// positive volumes. A series with no positive volume scales nothing.
+    pub fn new(volumes: &[f64], config: &VolumeScalingConfig) -> Self {
+        let mut positive: Vec<f64> = volumes.iter().copied().filter(|v| *v > 0.0).collect();
+        if positive.is_empty() {
+            return VolumeProfile {
+                factors: vec![1.0; volumes.len()],
+            };
+        }
+        positive.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        let median = positive[positive.len() / 2];
+        let factors = volumes
+            .iter()
+            .map(|v| {
+                (v.max(0.0) / median)
+                    .powf(config.exponent)
+                    .clamp(config.min_scale, config.max_scale)
+            })
+            .collect();
+        VolumeProfile { factors }
+    }
+
+    /// Multiplier for `block` (1-based); 1.0 past the end of the series.
+    pub fn factor(&self, block: u64) -> f64 {
+        (block as usize)
+            .checked_sub(1)
+            .and_then(|i| self.factors.get(i))
+            .copied()
+            .unwrap_or(1.0)
+    }
+}
@@ -0,0 +1,147 @@
+//! Browser API for the web playground (`wasm` feature).
+//!
+//! Build with the native-only features off:
+//!
+//! ```text
+//! wasm-pack build --target web -- --no-default-features --features wasm
+//! ```
+//!
+//! Configs are plain objects of `ScenarioConfig` fields merged over the
+//! defaults, and per-block metrics come back one `Float64Array` per field:
+//!
+//! ```js
+//! import init, { Playground } from "./pkg/zai_sim.js";
+//! await init();
+//! const sim = Playground.stress("black_thursday", { amm_swap_fee: 0.003 }, 1000, 42);
+//! plot(sim.series("block"), sim.series("amm_spot_price"));
+//! console.log(sim.summary().max_peg_deviation);
+//! ```
+
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::error::ZaiSimError;
+use crate::output::compute_summary;
+use crate::scenario::{Scenario, ScenarioConfig};
+use crate::scenarios::{self, ScenarioId};
+
+/// Plain objects and arrays rather than `Map`s, so results read like JSON.
+fn to_js<T: Serialize>(value: &T) -> Result<JsValue, JsError> {
+    Ok(value.serialize(&serde_wasm_bindgen::Serializer::json_compatible())?)
+}
+
+fn config_from_js(overrides: JsValue) -> Result<ScenarioConfig, JsError> {
+    let overrides = if overrides.is_undefined() || overrides.is_null() {
+        serde_json::Value::Null
+    } else {
+        serde_wasm_bindgen::from_value(overrides)?
+    };
+    Ok(ScenarioConfig::with_overrides(overrides)?)
+}
+
+/// The default `ScenarioConfig` as an object.
+#[wasm_bindgen(js_name = defaultConfig)]
+pub fn default_config() -> Result<JsValue, JsError> {
+    to_js(&ScenarioConfig::default())
+}
+
+/// Names of the built-in stress scenarios.
+#[wasm_bindgen(js_name = scenarioNames)]
+pub fn scenario_names() -> Box<[JsValue]> {
+    ScenarioId::all()
+        .iter()
+        .map(|id| JsValue::from_str(id.name()))
+        .collect()
+}
+
+/// A simulation the page drives: `run` it over external prices (repeated
+/// calls continue where the last stopped), then chart its `series`.
+#[wasm_bindgen]
+pub struct Playground {
+    scenario: Scenario,
+    target_price: f64,
+}
+
+#[wasm_bindgen]
+impl Playground {
+    /// An empty run of `config` (an object of overrides, or undefined).
+    #[wasm_bindgen(constructor)]
+    pub fn new(config: JsValue, seed: u32) -> Result<Playground, JsError> {
+        let config = config_from_js(config)?;
+        Ok(Self {
+            scenario: Scenario::new_with_seed(&config, seed as u64),
+            target_price: config.initial_redemption_price,
+        })
+    }
+
+    /// A built-in stress scenario run for `blocks` blocks.
+    pub fn stress(
+        name: &str,
+        config: JsValue,
+        blocks: usize,
+        seed: u32,
+    ) -> Result<Playground, JsError> {
+        let id = ScenarioId::from_name(name)
+            .ok_or_else(|| ZaiSimError::Config(format!("unknown scenario '{}'", name)))?;
+        let config = config_from_js(config)?;
+        Ok(Self {
+            scenario: scenarios::run_stress(id, &config, blocks, seed as u64),
+            target_price: config.initial_redemption_price,
+        })
+    }
+
+    /// Simulate one block per external ZEC price.
+    pub fn run(&mut self, prices: &[f64]) {
+        self.scenario.run(prices);
+    }
+
+    /// Blocks simulated so far.
+    #[wasm_bindgen(getter)]
+    pub fn blocks(&self) -> usize {
+        self.scenario.metrics.len()
+    }
+
+    /// The config being run.
+    pub fn config(&self) -> Result<JsValue, JsError> {
+        to_js(&self.scenario.config)
+    }
+
+    /// Names of the per-block metrics `series` accepts.
+    pub fn fields(&self) -> Result<Box<[JsValue]>, JsError> {
+        let Some(first) = self.scenario.metrics.first() else {
+            return Ok(Box::default());
+        };
+        let serde_json::Value::Object(row) = serde_json::to_value(first)? else {
+            return Ok(Box::default());
+        };
+        Ok(row
+            .iter()
+            .filter(|(_, v)| v.is_number() || v.is_boolean())
+            .map(|(k, _)| JsValue::from_str(k))
+            .collect())
+    }
+
+    /// One numeric `BlockMetrics` field over the run (booleans as 0 and 1).
+    pub fn series(&self, field: &str) -> Result<Vec<f64>, JsError> {
+        let unknown = || ZaiSimError::UnknownMetric(field.to_string());
+        let series: Result<Vec<f64>, ZaiSimError> = self
+            .scenario
+            .metrics
+            .iter()
+            .map(|m| {
+                let row = serde_json::to_value(m)?;
+                match row.get(field).ok_or_else(unknown)? {
+                    serde_json::Value::Bool(b) => Ok(*b as u8 as f64),
+                    v => v.as_f64().ok_or_else(unknown),
+                }
+            })
+            .collect();
+        Ok(series?)
+    }
+
+    /// `SummaryMetrics` of the blocks run so far, against the initial
+    /// redemption price.
+    pub fn summary(&self) -> Result<JsValue, JsError> {
+        to_js(&compute_summary(&self.scenario.metrics, self.target_price))
+    }
+}
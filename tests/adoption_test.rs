@@ -0,0 +1,146 @@
+//! Demand adoption curve and user growth.
+//!
+//! With an adoption model the demand roster grows along a Bass diffusion
+//! curve while the peg holds and churns when it doesn't.
+
+use zai_sim::adoption::{Adoption, AdoptionConfig};
+use zai_sim::agents::{DemandAgent, DemandAgentConfig};
+use zai_sim::scenario::{Scenario, ScenarioConfig};
+use zai_sim::scenarios::{add_agents, generate_prices, ScenarioId};
+
+fn logistic() -> AdoptionConfig {
+    AdoptionConfig {
+        market_size: 100.0,
+        innovation_rate: 0.0,
+        imitation_rate: 0.01,
+        base_churn_rate: 0.0,
+        ..AdoptionConfig::default()
+    }
+}
+
+#[test]
+fn test_logistic_growth_under_a_held_peg() {
+    let mut adoption = Adoption::new(logistic());
+    let mut roster = 10;
+    let mut sizes = Vec::new();
+    for _ in 0..2000 {
+        roster = adoption.step(0.0, roster);
+        sizes.push(roster);
+    }
+    assert!(sizes.windows(2).all(|w| w[1] >= w[0]));
+    assert_eq!(*sizes.last().unwrap(), 100);
+    // S-curve: the middle of the climb is the steepest part
+    let at = |n: usize| sizes.iter().position(|&s| s >= n).unwrap();
+    assert!(at(60) - at(40) < at(30) - at(10));
+    assert!(at(60) - at(40) < at(90) - at(70));
+
+    // Without innovators an empty market stays empty; with them it starts
+    assert_eq!(Adoption::new(logistic()).step(0.0, 0), 0);
+    let mut seeded = Adoption::new(AdoptionConfig {
+        innovation_rate: 0.001,
+        ..logistic()
+    });
+    let mut roster = 0;
+    for _ in 0..100 {
+        roster = seeded.step(0.0, roster);
+    }
+    assert!(roster > 0);
+}
+
+#[test]
+fn test_poor_peg_stops_growth_and_churns() {
+    let mut adoption = Adoption::new(logistic());
+    let mut roster = 50;
+    for _ in 0..2000 {
+        roster = adoption.step(10.0, roster);
+    }
+    assert!(adoption.peg_deviation_pct > 9.9);
+    // 0.16% of users leave per block: about e^-3 of them remain
+    assert!(roster > 0 && roster < 5, "{}", roster);
+
+    // A deviation inside the tolerance slows joining without churn
+    let mut held = Adoption::new(logistic());
+    let mut wobbly = Adoption::new(logistic());
+    let (mut a, mut b) = (50, 50);
+    for _ in 0..100 {
+        a = held.step(0.0, a);
+        b = wobbly.step(1.0, b);
+    }
+    assert!(b > 50 && b < a);
+}
+
+fn run(id: ScenarioId, adoption: Option<AdoptionConfig>, blocks: usize) -> Scenario {
+    let config = ScenarioConfig {
+        adoption,
+        ..ScenarioConfig::default()
+    };
+    let mut scenario = Scenario::new_with_seed(&config, 42);
+    add_agents(id, &mut scenario);
+    scenario.run(&generate_prices(id, blocks, 42));
+    scenario
+}
+
+#[test]
+fn test_leaving_users_sell_their_zai() {
+    let config = ScenarioConfig {
+        adoption: Some(AdoptionConfig {
+            innovation_rate: 0.0,
+            imitation_rate: 0.0,
+            base_churn_rate: 0.01,
+            ..AdoptionConfig::default()
+        }),
+        ..ScenarioConfig::default()
+    };
+    let mut scenario = Scenario::new_with_seed(&config, 42);
+    add_agents(ScenarioId::SteadyState, &mut scenario);
+    for _ in 0..5 {
+        scenario
+            .demand_agents
+            .push(DemandAgent::new(DemandAgentConfig::default()));
+    }
+    scenario.run(&generate_prices(ScenarioId::SteadyState, 500, 42));
+
+    let users: Vec<usize> = scenario.metrics.iter().map(|m| m.demand_users).collect();
+    assert!(users.windows(2).all(|w| w[1] <= w[0]));
+    assert!(*users.last().unwrap() < 5);
+    assert_eq!(scenario.demand_agents.len(), *users.last().unwrap());
+    let adoption = scenario.adoption.as_ref().unwrap();
+    assert_eq!(adoption.total_joined, 0);
+    assert_eq!(adoption.total_churned as usize, 5 - users.last().unwrap());
+    assert!(adoption.exit_zai_sold > 0.0);
+}
+
+#[test]
+fn test_fixed_roster_without_adoption() {
+    let scenario = run(ScenarioId::DemandShock, None, 300);
+    assert!(scenario.adoption.is_none());
+    assert!(scenario.metrics.iter().all(|m| m.demand_users == 1));
+}
+
+#[test]
+fn test_adoption_follows_peg_quality() {
+    println!("\n{:<16} users  joined  churned  exit ZAI sold", "");
+    for id in [ScenarioId::SteadyState, ScenarioId::SustainedBear] {
+        let scenario = run(id, Some(AdoptionConfig::default()), 5000);
+        let adoption = scenario.adoption.as_ref().unwrap();
+        if id == ScenarioId::SteadyState {
+            assert!(adoption.total_joined > 0);
+        }
+        assert_eq!(
+            scenario.demand_agents.len() as u64,
+            adoption.total_joined - adoption.total_churned
+        );
+        assert_eq!(
+            scenario.metrics.last().unwrap().demand_users,
+            scenario.demand_agents.len()
+        );
+        println!(
+            "{:<16} {:>5}  {:>6}  {:>7}  {:>13.0}",
+            id.name(),
+            scenario.demand_agents.len(),
+            adoption.total_joined,
+            adoption.total_churned,
+            adoption.exit_zai_sold
+        );
+    }
+}
@@ -0,0 +1,135 @@
+//! Per-agent time-series metrics.
+//!
+//! With `record_agent_metrics` set, every agent's balances, PnL and actions
+//! are recorded each block, keyed by agent id.
+
+use zai_sim::agent_metrics::{save_agent_metrics_csv, AgentSample};
+use zai_sim::agents::{
+    Arbitrageur, ArbitrageurConfig, IlAwareLpAgent, IlAwareLpConfig, MinerAgent, MinerAgentConfig,
+};
+use zai_sim::scenario::{Scenario, ScenarioConfig};
+use zai_sim::scenarios::{generate_prices, ScenarioId};
+
+const BLOCKS: usize = 1000;
+
+fn run(record: bool) -> Scenario {
+    let config = ScenarioConfig {
+        record_agent_metrics: record,
+        ..ScenarioConfig::default()
+    };
+    let mut scenario = Scenario::new_with_seed(&config, 42);
+    for zai in [20_000.0, 100_000.0] {
+        scenario.arbers.push(Arbitrageur::new(ArbitrageurConfig {
+            initial_zai_balance: zai,
+            initial_zec_balance: zai / 50.0,
+            ..ArbitrageurConfig::default()
+        }));
+    }
+    scenario
+        .miners
+        .push(MinerAgent::new(MinerAgentConfig::default()));
+    for (i, threshold) in [-0.01, -0.05].into_iter().enumerate() {
+        scenario.il_aware_lps.push(IlAwareLpAgent::new(
+            IlAwareLpConfig {
+                initial_zec: 2000.0,
+                initial_zai: 100_000.0,
+                withdrawal_threshold: threshold,
+                withdrawal_rate: 0.10,
+            },
+            &format!("il_lp_{}", i),
+        ));
+    }
+    scenario.run(&generate_prices(ScenarioId::SustainedBear, BLOCKS, 42));
+    scenario
+}
+
+/// First block at which `pred` holds for an agent's sample.
+fn first_block(series: &[&AgentSample], pred: impl Fn(&AgentSample) -> bool) -> Option<u64> {
+    series.iter().find(|s| pred(s)).map(|s| s.block)
+}
+
+#[test]
+fn test_disabled_by_default() {
+    assert!(run(false).agent_metrics.is_none());
+}
+
+#[test]
+fn test_one_sample_per_agent_per_block() {
+    let scenario = run(true);
+    let collector = scenario.agent_metrics.as_ref().unwrap();
+    // 2 arbers + 1 miner + 2 IL-aware LPs
+    assert_eq!(collector.samples.len(), BLOCKS * 5);
+
+    let arber = collector.series("arber_0");
+    assert_eq!(arber.len(), BLOCKS);
+    assert!(arber.windows(2).all(|w| w[0].block + 1 == w[1].block));
+    assert_eq!(arber[0].kind, "arber");
+
+    let traded: u32 = arber.iter().map(|s| s.actions).sum();
+    assert!(traded > 0);
+    assert!(arber
+        .iter()
+        .any(|s| s.actions > 0 && s.last_action != "none"));
+}
+
+#[test]
+fn test_smaller_arber_exhausts_first() {
+    let scenario = run(true);
+    let collector = scenario.agent_metrics.as_ref().unwrap();
+    // In a bear market arbers sell ZEC into the AMM until they run out
+    let exhausted = |id: &str| {
+        let series = collector.series(id);
+        let initial = series[0].zec_balance;
+        first_block(&series, |s| s.zec_balance < initial * 0.01)
+    };
+
+    let small = exhausted("arber_0").expect("small arber should run out of ZEC");
+    let large = exhausted("arber_1").expect("large arber should run out of ZEC");
+    assert!(small < large, "small {} vs large {}", small, large);
+}
+
+#[test]
+fn test_il_aware_lp_withdrawal_timing_differs() {
+    let scenario = run(true);
+    let collector = scenario.agent_metrics.as_ref().unwrap();
+    let first_withdrawal = |id: &str| {
+        let series = collector.series(id);
+        let initial = series[0].lp_shares;
+        first_block(&series, |s| s.lp_shares < initial)
+    };
+
+    let nervous = first_withdrawal("il_lp_0").expect("-1% threshold LP should withdraw");
+    let patient = first_withdrawal("il_lp_1").expect("-5% threshold LP should withdraw");
+    assert!(
+        nervous < patient,
+        "nervous {} vs patient {}",
+        nervous,
+        patient
+    );
+}
+
+#[test]
+fn test_pnl_is_value_change_from_start() {
+    let scenario = run(true);
+    let collector = scenario.agent_metrics.as_ref().unwrap();
+    for id in ["arber_0", "arber_1", "miner_0", "il_lp_0"] {
+        let series = collector.series(id);
+        let baseline = series[0].value_zai - series[0].pnl_zai;
+        for s in &series {
+            assert!((s.value_zai - s.pnl_zai - baseline).abs() < 1e-6);
+        }
+    }
+}
+
+#[test]
+fn test_csv_export() {
+    let scenario = run(true);
+    let collector = scenario.agent_metrics.as_ref().unwrap();
+    let path = std::env::temp_dir().join("zai_sim_agent_metrics.csv");
+    save_agent_metrics_csv(&collector.samples, &path).unwrap();
+
+    let mut rdr = csv::Reader::from_path(&path).unwrap();
+    let headers = rdr.headers().unwrap().clone();
+    assert_eq!(&headers[1], "agent_id");
+    assert_eq!(rdr.records().count(), collector.samples.len());
+}
@@ -0,0 +1,55 @@
+//! Initial AMM price and depth.
+//!
+//! `amm_initial_price` and `amm_initial_depth` derive the reserves, so depth
+//! can be swept without moving the starting price.
+
+use zai_sim::historical::config_for_historical;
+use zai_sim::scenario::{Scenario, ScenarioConfig};
+
+#[test]
+fn test_default_reserves_unchanged() {
+    let config = ScenarioConfig::default();
+    assert_eq!(config.amm_reserves(), (10_000.0, 500_000.0));
+    assert_eq!(config.initial_amm_price(), 50.0);
+}
+
+#[test]
+fn test_price_and_depth_derive_reserves() {
+    let config = ScenarioConfig::default().with_amm(40.0, 5_000_000.0);
+    assert_eq!(config.amm_reserves(), (125_000.0, 5_000_000.0));
+
+    let scenario = Scenario::new(&config);
+    assert!((scenario.amm.spot_price() - 40.0).abs() < 1e-12);
+    assert_eq!(scenario.amm.reserve_zai, 5_000_000.0);
+}
+
+#[test]
+fn test_depth_sweep_keeps_price() {
+    for depth in [250_000.0, 1_000_000.0, 5_000_000.0, 20_000_000.0] {
+        let config = ScenarioConfig {
+            amm_initial_depth: Some(depth),
+            ..ScenarioConfig::default()
+        };
+        let scenario = Scenario::new(&config);
+        assert!((scenario.amm.spot_price() - 50.0).abs() < 1e-9);
+        assert_eq!(scenario.amm.reserve_zai, depth);
+    }
+}
+
+#[test]
+fn test_price_override_keeps_depth() {
+    let config = ScenarioConfig {
+        amm_initial_price: Some(25.0),
+        ..ScenarioConfig::default()
+    };
+    assert_eq!(config.amm_reserves(), (20_000.0, 500_000.0));
+}
+
+#[test]
+fn test_historical_config_starts_at_first_close() {
+    let config = config_for_historical(37.5);
+    let scenario = Scenario::new(&config);
+    assert!((scenario.amm.spot_price() - 37.5).abs() < 1e-12);
+    assert!((scenario.amm.reserve_zec - 100_000.0).abs() < 1e-6);
+    assert_eq!(config.initial_redemption_price, 37.5);
+}
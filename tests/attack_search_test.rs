@@ -0,0 +1,108 @@
+//! Attack cost/profit search.
+//!
+//! The search runs a grid of built-in attackers against one config,
+//! refines around the worst of them and ranks outcomes by attacker profit
+//! or by griefing ratio.
+
+use zai_sim::agents::AttackStrategy;
+use zai_sim::attack_search::{
+    AttackCandidate, AttackKind, AttackObjective, AttackSearch, AttackSearchSpace,
+};
+use zai_sim::scenario::{Scenario, ScenarioConfig};
+use zai_sim::scenarios::ScenarioId;
+
+/// Twenty 1,000 ZAI vaults at CR 1.6, just above the 1.5 minimum.
+fn open_vaults(scenario: &mut Scenario) {
+    for i in 0..20 {
+        scenario
+            .registry
+            .open_vault(&format!("vault_{}", i), 32.0, 1000.0, 0, &scenario.amm)
+            .unwrap();
+    }
+}
+
+fn search() -> AttackSearch {
+    let mut search = AttackSearch::new(ScenarioConfig::default(), ScenarioId::SteadyState, 300, 42);
+    search.setup = open_vaults;
+    search
+}
+
+fn space() -> AttackSearchSpace {
+    AttackSearchSpace {
+        kinds: vec![AttackKind::DumpAndRevert, AttackKind::Drip],
+        capital_zec: vec![1_000.0, 20_000.0],
+        start_blocks: vec![100],
+        durations: vec![3, 30],
+        refine_rounds: 1,
+    }
+}
+
+#[test]
+fn test_candidate_maps_to_attacker() {
+    let candidate = AttackCandidate {
+        kind: AttackKind::Drip,
+        capital_zec: 3_000.0,
+        start_block: 50,
+        duration: 30,
+    };
+    let config = candidate.attacker_config();
+    assert_eq!(config.attack_capital_zec, 3_000.0);
+    assert_eq!(config.attack_at_block, 50);
+    assert_eq!(
+        config.strategy,
+        AttackStrategy::Drip {
+            zec_per_block: 100.0,
+            blocks: 30,
+            unwind_blocks: 3,
+        }
+    );
+    assert_eq!(space().candidates().len(), 8);
+    assert_eq!(AttackSearchSpace::default_for(1000).candidates().len(), 120);
+}
+
+#[test]
+fn test_evaluate_is_deterministic() {
+    let candidate = AttackCandidate {
+        kind: AttackKind::DumpAndRevert,
+        capital_zec: 20_000.0,
+        start_block: 100,
+        duration: 30,
+    };
+    let search = search();
+    let a = search.evaluate(&candidate);
+    let b = search.evaluate(&candidate);
+    assert_eq!(a.attacker_pnl_zai, b.attacker_pnl_zai);
+    assert_eq!(a.bad_debt, b.bad_debt);
+    assert_eq!(a.liquidations, b.liquidations);
+    // Selling into the pool and buying back after arbers step in costs money
+    assert!(a.attacker_pnl_zai < 0.0);
+}
+
+#[test]
+fn test_search_ranks_outcomes() {
+    let search = search();
+    let by_profit = search.search(&space(), AttackObjective::MaxProfit);
+    assert!(by_profit.len() >= 8);
+    assert!(by_profit
+        .windows(2)
+        .all(|w| w[0].attacker_pnl_zai >= w[1].attacker_pnl_zai));
+    // Every candidate is run once
+    for (i, o) in by_profit.iter().enumerate() {
+        assert!(by_profit[i + 1..]
+            .iter()
+            .all(|other| other.candidate != o.candidate));
+    }
+
+    let by_griefing = search.search(&space(), AttackObjective::CheapestGriefing);
+    let best = &by_griefing[0];
+    if by_griefing.iter().any(|o| o.bad_debt > 0.0) {
+        assert!(best.bad_debt > 0.0);
+        assert!(by_griefing
+            .iter()
+            .filter(|o| o.bad_debt > 0.0)
+            .all(|o| o.griefing_ratio >= best.griefing_ratio));
+    }
+
+    println!("\nMost profitable: {:?}", by_profit[0]);
+    println!("Cheapest griefing: {:?}", best);
+}
@@ -0,0 +1,219 @@
+//! Built-in attacker strategies.
+//!
+//! Besides the single dump and revert, attackers can drip sales over many
+//! blocks, oscillate around the TWAP window, dump and buy the liquidation
+//! cascade, or squeeze the peg, so attack tests don't each carry their own
+//! whale.
+
+use zai_sim::agents::{AgentAction, AttackPhase, AttackStrategy, Attacker, AttackerConfig};
+use zai_sim::amm::Amm;
+use zai_sim::controller::ControllerConfig;
+use zai_sim::scenario::{Scenario, ScenarioConfig};
+use zai_sim::scenarios::{add_agents, ScenarioId};
+
+fn attacker(capital: f64, strategy: AttackStrategy) -> Attacker {
+    Attacker::new(AttackerConfig {
+        attack_capital_zec: capital,
+        attack_at_block: 10,
+        strategy,
+        ..AttackerConfig::default()
+    })
+}
+
+/// Direction of an attack swap, or "none".
+fn direction(action: &AgentAction) -> &str {
+    match action {
+        AgentAction::AttackSwap { direction, .. } => direction,
+        _ => "none",
+    }
+}
+
+#[test]
+fn test_drip_sells_then_unwinds() {
+    let mut amm = Amm::new(10000.0, 500000.0, 0.003);
+    let mut a = attacker(
+        1000.0,
+        AttackStrategy::Drip {
+            zec_per_block: 100.0,
+            blocks: 5,
+            unwind_blocks: 2,
+        },
+    );
+    assert_eq!(direction(&a.act(&mut amm, 9)), "none");
+    for block in 10..15 {
+        assert_eq!(direction(&a.act(&mut amm, block)), "sell_zec");
+    }
+    assert_eq!(a.zec_balance, 500.0);
+
+    // Half the ZAI back, then the rest
+    let zai = a.zai_balance;
+    assert_eq!(direction(&a.act(&mut amm, 15)), "buy_zec");
+    assert!((a.zai_balance - zai / 2.0).abs() < 1e-6);
+    assert_eq!(direction(&a.act(&mut amm, 16)), "buy_zec");
+    assert_eq!(a.zai_balance, 0.0);
+    assert_eq!(a.phase, AttackPhase::Done);
+    assert!(a.zec_balance < 1000.0, "round trip pays fees");
+}
+
+#[test]
+fn test_twap_oscillation_swings() {
+    let mut amm = Amm::new(10000.0, 500000.0, 0.003);
+    let mut a = attacker(
+        1000.0,
+        AttackStrategy::TwapOscillation {
+            swing_zec: 500.0,
+            half_period: 3,
+            cycles: 2,
+        },
+    );
+    let mut swaps = Vec::new();
+    for block in 1..=30 {
+        let action = a.act(&mut amm, block);
+        if direction(&action) != "none" {
+            swaps.push((block, direction(&action).to_string()));
+        }
+    }
+    let expected = [
+        (10, "sell_zec"),
+        (13, "buy_zec"),
+        (16, "sell_zec"),
+        (19, "buy_zec"),
+    ];
+    assert_eq!(swaps.len(), expected.len());
+    for ((block, dir), (want_block, want_dir)) in swaps.iter().zip(expected) {
+        assert_eq!((*block, dir.as_str()), (want_block, want_dir));
+    }
+    assert_eq!(a.phase, AttackPhase::Done);
+    assert!(a.zec_balance > 990.0 && a.zec_balance < 1000.0);
+}
+
+#[test]
+fn test_liquidation_hunt_buys_the_cascade() {
+    let hunt = AttackStrategy::LiquidationHunt {
+        buy_drop_pct: 10.0,
+        max_hold_blocks: 50,
+    };
+    let mut amm = Amm::new(10000.0, 500000.0, 0.003);
+    let mut a = attacker(1000.0, hunt.clone());
+    assert_eq!(direction(&a.act(&mut amm, 10)), "sell_zec");
+    assert_eq!(a.zec_balance, 0.0);
+    assert_eq!(direction(&a.act(&mut amm, 11)), "none");
+
+    // Liquidations dump another 1,000 ZEC: spot falls ~16% below the
+    // post-dump price and the attacker buys
+    amm.swap_zec_for_zai(1000.0, 11).unwrap();
+    assert_eq!(direction(&a.act(&mut amm, 12)), "buy_zec");
+    assert_eq!(a.phase, AttackPhase::Done);
+    // Bought back below its own selling price
+    assert!(a.zec_balance > 1000.0, "{}", a.zec_balance);
+
+    // No cascade: gives up after the hold
+    let mut amm = Amm::new(10000.0, 500000.0, 0.003);
+    let mut a = attacker(
+        1000.0,
+        AttackStrategy::LiquidationHunt {
+            buy_drop_pct: 10.0,
+            max_hold_blocks: 5,
+        },
+    );
+    a.act(&mut amm, 10);
+    for block in 11..15 {
+        assert_eq!(direction(&a.act(&mut amm, block)), "none");
+    }
+    assert_eq!(direction(&a.act(&mut amm, 15)), "buy_zec");
+}
+
+#[test]
+fn test_peg_squeeze_holds_premium() {
+    let mut amm = Amm::new(10000.0, 500000.0, 0.003);
+    let mut a = attacker(
+        1000.0,
+        AttackStrategy::PegSqueeze {
+            target_premium_pct: 3.0,
+            zec_per_block: 50.0,
+            hold_blocks: 20,
+        },
+    );
+    let target = 50.0 / 1.03;
+
+    // Buys ZAI until it trades at the premium, then stops
+    let mut block = 10;
+    while direction(&a.act(&mut amm, block)) == "sell_zec" {
+        block += 1;
+    }
+    assert!(amm.spot_price() <= target);
+    assert!(a.zec_balance > 0.0);
+
+    // Arbitrage pulls ZAI back toward par; the attacker leans on it again
+    amm.swap_zai_for_zec(20_000.0, block).unwrap();
+    assert!(amm.spot_price() > target);
+    assert_eq!(direction(&a.act(&mut amm, block + 1)), "sell_zec");
+
+    // Sells the ZAI back at the end of the hold
+    for b in block + 2..30 {
+        a.act(&mut amm, b);
+    }
+    assert_eq!(direction(&a.act(&mut amm, 30)), "buy_zec");
+    assert_eq!(a.zai_balance, 0.0);
+    assert_eq!(a.phase, AttackPhase::Done);
+}
+
+#[test]
+fn test_default_config_keeps_dump_and_revert() {
+    let yaml = "{ attack_capital_zec: 2000, hold_blocks: 3, attack_at_block: 5 }";
+    let config: AttackerConfig = serde_yaml::from_str(yaml).unwrap();
+    assert_eq!(config.strategy, AttackStrategy::DumpAndRevert);
+
+    let yaml = "{ attack_capital_zec: 2000, hold_blocks: 3, attack_at_block: 5, \
+                strategy: { type: drip, zec_per_block: 10, blocks: 20, unwind_blocks: 5 } }";
+    let config: AttackerConfig = serde_yaml::from_str(yaml).unwrap();
+    assert!(matches!(
+        config.strategy,
+        AttackStrategy::Drip { blocks: 20, .. }
+    ));
+}
+
+/// F-043's sustained manipulation (1K ZEC a block for 100 blocks against a
+/// $5M pool) as a built-in attacker instead of a hand-rolled whale.
+#[test]
+fn test_sustained_drip_in_scenario() {
+    let mut config = ScenarioConfig {
+        amm_initial_zec: 100_000.0,
+        amm_initial_zai: 5_000_000.0,
+        controller_config: ControllerConfig::default_tick(),
+        ..ScenarioConfig::default()
+    };
+    config.cdp_config.min_ratio = 2.0;
+    config.cdp_config.twap_window = 240;
+    config.liquidation_config.max_liquidations_per_block = 50;
+
+    let mut scenario = Scenario::new_with_seed(&config, 42);
+    add_agents(ScenarioId::SteadyState, &mut scenario);
+    for i in 0..25 {
+        let cr = 2.1 + i as f64 * 0.70 / 24.0;
+        scenario
+            .registry
+            .open_vault(&format!("vault_{}", i), cr * 20.0, 1000.0, 0, &scenario.amm)
+            .unwrap();
+    }
+    scenario.attackers.push(Attacker::new(AttackerConfig {
+        attack_capital_zec: 100_000.0,
+        attack_at_block: 241,
+        strategy: AttackStrategy::Drip {
+            zec_per_block: 1_000.0,
+            blocks: 100,
+            unwind_blocks: 10,
+        },
+        ..AttackerConfig::default()
+    }));
+    scenario.run(&vec![50.0; 1000]);
+
+    let attacker = &scenario.attackers[0];
+    assert_eq!(attacker.phase, AttackPhase::Done);
+    let liquidations: u32 = scenario.metrics.iter().map(|m| m.liquidation_count).sum();
+    assert!(liquidations > 0, "the drip should push vaults under");
+    println!(
+        "\nSustained drip: {} liquidations, bad debt {:.2}, attacker ends with {:.2} ZEC",
+        liquidations, scenario.liquidation_engine.total_bad_debt, attacker.zec_balance
+    );
+}
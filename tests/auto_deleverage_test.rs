@@ -0,0 +1,180 @@
+//! Auto-deleveraging breaker.
+//!
+//! While the system collateral ratio is below a threshold the breaker has
+//! the riskiest vaults partially unwound through the graduated engine,
+//! before they become liquidatable, and logs the vaults it touched.
+
+use zai_sim::agents::{CdpHolder, CdpHolderConfig};
+use zai_sim::amm::Amm;
+use zai_sim::cdp::{CdpConfig, VaultRegistry};
+use zai_sim::circuit_breaker::*;
+use zai_sim::liquidation::{LiquidationConfig, LiquidationEngine, LiquidationMode};
+use zai_sim::output::extract_events;
+use zai_sim::scenario::{Scenario, ScenarioConfig};
+use zai_sim::scenarios::{add_agents, generate_prices, ScenarioId};
+
+/// Vaults at CR 1.6, ~1.72 and 5.0 at a price of 50: system CR ~2.14.
+fn setup() -> (Amm, VaultRegistry, [u64; 3]) {
+    let amm = Amm::new(10000.0, 500000.0, 0.003);
+    let mut registry = VaultRegistry::new(CdpConfig::default());
+    let ids = [
+        registry
+            .open_vault("risky", 100.0, 3125.0, 1, &amm)
+            .unwrap(),
+        registry.open_vault("thin", 100.0, 2900.0, 1, &amm).unwrap(),
+        registry.open_vault("safe", 100.0, 1000.0, 1, &amm).unwrap(),
+    ];
+    (amm, registry, ids)
+}
+
+#[test]
+fn test_selects_riskiest_vaults_with_hysteresis() {
+    let (amm, registry, [risky, thin, _]) = setup();
+    let mut breaker = AutoDeleverageBreaker::new(AutoDeleverageConfig {
+        engage_below_cr: 2.2,
+        release_above_cr: 2.3,
+        ..AutoDeleverageConfig::default()
+    });
+    assert_eq!(breaker.select(&registry, &amm), vec![risky, thin]);
+    assert!(breaker.engaged);
+
+    breaker.config.max_vaults_per_block = 1;
+    assert_eq!(breaker.select(&registry, &amm), vec![risky]);
+
+    // Between the thresholds the breaker stays engaged
+    breaker.config.engage_below_cr = 2.0;
+    assert_eq!(breaker.select(&registry, &amm), vec![risky]);
+    breaker.config.release_above_cr = 2.1;
+    assert!(breaker.select(&registry, &amm).is_empty());
+    assert!(!breaker.engaged);
+    assert!(breaker.select(&registry, &amm).is_empty());
+    assert_eq!((breaker.trigger_count, breaker.engaged_blocks), (1, 3));
+}
+
+#[test]
+fn test_liquidatable_vaults_left_to_liquidation() {
+    let (amm, mut registry, [risky, thin, _]) = setup();
+    // At a 1.65 minimum ratio the 1.6 vault is already liquidatable
+    registry.config.min_ratio = 1.65;
+    let mut breaker = AutoDeleverageBreaker::new(AutoDeleverageConfig {
+        engage_below_cr: 2.2,
+        ..AutoDeleverageConfig::default()
+    });
+    assert_eq!(breaker.select(&registry, &amm), vec![thin]);
+    assert!(registry.is_liquidatable(risky, &amm));
+}
+
+#[test]
+fn test_deleverage_unwinds_part_of_a_healthy_vault() {
+    let (mut amm, mut registry, [risky, thin, _]) = setup();
+    let mut engine = LiquidationEngine::new(LiquidationConfig::default());
+    assert!(!registry.is_liquidatable(risky, &amm));
+    let due = registry.debt_at(risky, 2).unwrap();
+
+    let results = engine.deleverage(&[risky, thin], &mut registry, &mut amm, 2);
+    assert_eq!(results.len(), 2);
+    assert!(results
+        .iter()
+        .all(|r| r.mode == LiquidationMode::AutoDeleverage && r.bad_debt == 0.0));
+    let vault = &registry.vaults[&risky];
+    assert!((vault.collateral_zec - 90.0).abs() < 1e-9);
+    assert!((vault.debt_zai - (due - results[0].debt_to_cover)).abs() < 1e-9);
+    assert!(results[0].debt_to_cover > 0.0);
+}
+
+#[test]
+fn test_engine_logs_deleveraged_vaults() {
+    let (amm, registry, [risky, thin, _]) = setup();
+    let mut breakers = CircuitBreakerEngine::new(
+        TwapBreakerConfig::default(),
+        CascadeBreakerConfig::default(),
+        DebtCeilingConfig::default(),
+    );
+    assert!(breakers.deleverage_targets(&registry, &amm).is_empty());
+
+    breakers.auto_deleverage = Some(AutoDeleverageBreaker::new(AutoDeleverageConfig {
+        engage_below_cr: 2.2,
+        ..AutoDeleverageConfig::default()
+    }));
+    let targets = breakers.deleverage_targets(&registry, &amm);
+    assert_eq!(targets, vec![risky, thin]);
+    breakers.auto_deleverage.as_mut().unwrap().record(&targets);
+
+    let actions = breakers.check_all(&amm, &registry, 50.0, 2);
+    assert!(matches!(
+        actions.as_slice(),
+        [BreakerAction::AutoDeleverage { vault_ids, .. }] if *vault_ids == targets
+    ));
+    // Reported once
+    assert!(breakers.check_all(&amm, &registry, 50.0, 3).is_empty());
+    assert_eq!(
+        breakers
+            .auto_deleverage
+            .as_ref()
+            .unwrap()
+            .vaults_deleveraged,
+        2
+    );
+}
+
+fn sustained_bear(auto_deleverage: Option<AutoDeleverageConfig>) -> Scenario {
+    let config = ScenarioConfig {
+        auto_deleverage,
+        ..ScenarioConfig::default()
+    };
+    let mut scenario = Scenario::new_with_seed(&config, 42);
+    add_agents(ScenarioId::SustainedBear, &mut scenario);
+    // Vaults opened between 2.5x and ~1.56x collateralized
+    for i in 0..10 {
+        scenario.cdp_holders.push(CdpHolder::new(CdpHolderConfig {
+            reserve_zec: 10.0,
+            initial_collateral: 50.0,
+            initial_debt: 1000.0 + 600.0 * i as f64 / 9.0,
+            ..CdpHolderConfig::default()
+        }));
+    }
+    scenario.run(&generate_prices(ScenarioId::SustainedBear, 3000, 42));
+    scenario
+}
+
+#[test]
+fn test_proactive_vs_reactive_in_sustained_bear() {
+    let reactive = sustained_bear(None);
+    assert!(reactive.metrics.iter().all(|m| m.deleveraged_vaults == 0));
+
+    let proactive = sustained_bear(Some(AutoDeleverageConfig::default()));
+    let deleveraged: usize = proactive.metrics.iter().map(|m| m.deleveraged_vaults).sum();
+    assert!(deleveraged > 0);
+    let breaker = proactive.breakers.auto_deleverage.as_ref().unwrap();
+    assert_eq!(deleveraged as u64, breaker.vaults_deleveraged);
+    assert!(breaker.trigger_count > 0);
+
+    let events = extract_events(&proactive.metrics);
+    assert_eq!(
+        events
+            .iter()
+            .filter(|e| e.event_type == "auto_deleverage")
+            .count(),
+        proactive
+            .metrics
+            .iter()
+            .filter(|m| m.deleveraged_vaults > 0)
+            .count()
+    );
+
+    println!("\nSustained bear   liquidations  deleveraged  bad debt  final debt");
+    for (name, s) in [("reactive", &reactive), ("proactive", &proactive)] {
+        let last = s.metrics.last().unwrap();
+        println!(
+            "{:<16} {:>12}  {:>11}  {:>8.2}  {:>10.0}",
+            name,
+            s.metrics.iter().map(|m| m.liquidation_count).sum::<u32>(),
+            s.metrics
+                .iter()
+                .map(|m| m.deleveraged_vaults)
+                .sum::<usize>(),
+            last.bad_debt,
+            last.total_debt
+        );
+    }
+}
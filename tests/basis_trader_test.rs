@@ -0,0 +1,112 @@
+//! Redemption-basis trader.
+//!
+//! Trades AMM spot against the controller's redemption price (not the
+//! external ZEC price), so the controller's transmission channel is
+//! exercised by an agent that expects convergence.
+
+use zai_sim::agents::{AgentAction, BasisTrader, BasisTraderConfig};
+use zai_sim::amm::Amm;
+use zai_sim::scenario::{Scenario, ScenarioConfig};
+use zai_sim::scenarios::{add_agents, generate_prices, ScenarioId};
+
+fn amm() -> Amm {
+    // Spot 50 ZAI/ZEC
+    Amm::new(10_000.0, 500_000.0, 0.003)
+}
+
+fn mean_abs_basis(s: &Scenario) -> f64 {
+    s.metrics
+        .iter()
+        .map(|m| ((m.amm_spot_price - m.redemption_price) / m.redemption_price).abs())
+        .sum::<f64>()
+        / s.metrics.len() as f64
+}
+
+fn run(id: ScenarioId, traders: usize) -> Scenario {
+    let mut scenario = Scenario::new_with_seed(&ScenarioConfig::default(), 42);
+    add_agents(id, &mut scenario);
+    for _ in 0..traders {
+        scenario
+            .basis_traders
+            .push(BasisTrader::new(BasisTraderConfig::default()));
+    }
+    scenario.run(&generate_prices(id, 2000, 42));
+    scenario
+}
+
+#[test]
+fn test_buys_zai_below_redemption() {
+    let mut amm = amm();
+    let mut trader = BasisTrader::new(BasisTraderConfig::default());
+
+    // Redemption 45 vs spot 50: ZAI is cheap, rate already negative
+    let action = trader.act(&mut amm, 45.0, -1e-6, 1);
+    assert!(matches!(action, AgentAction::SellZec { .. }));
+    assert!(trader.zai_position() > 0.0);
+    assert!(amm.spot_price() < 50.0, "buying ZAI narrows the basis");
+}
+
+#[test]
+fn test_sells_zai_above_redemption() {
+    let mut amm = amm();
+    let mut trader = BasisTrader::new(BasisTraderConfig::default());
+
+    let action = trader.act(&mut amm, 55.0, 1e-6, 1);
+    assert!(matches!(action, AgentAction::BuyZec { .. }));
+    assert!(trader.zai_position() < 0.0);
+    assert!(amm.spot_price() > 50.0);
+}
+
+#[test]
+fn test_rate_confirmation_gates_entry() {
+    let mut amm = amm();
+    let mut trader = BasisTrader::new(BasisTraderConfig::default());
+    // Controller still pushing the wrong way: wait
+    assert!(matches!(
+        trader.act(&mut amm, 45.0, 1e-6, 1),
+        AgentAction::None
+    ));
+
+    let mut eager = BasisTrader::new(BasisTraderConfig {
+        require_rate_confirmation: false,
+        ..BasisTraderConfig::default()
+    });
+    assert!(matches!(
+        eager.act(&mut amm, 45.0, 1e-6, 1),
+        AgentAction::SellZec { .. }
+    ));
+}
+
+#[test]
+fn test_unwinds_when_basis_closes() {
+    let mut amm = amm();
+    let mut trader = BasisTrader::new(BasisTraderConfig::default());
+    trader.act(&mut amm, 45.0, -1e-6, 1);
+    let opened = trader.zai_position();
+    assert!(opened > 0.0);
+
+    // Basis closed at the new spot: position shrinks back toward flat
+    let spot = amm.spot_price();
+    let mut block = 2;
+    while trader.zai_position() > 0.01 && block < 100 {
+        let redemption_price = amm.spot_price();
+        trader.act(&mut amm, redemption_price, 0.0, block);
+        block += 1;
+    }
+    assert!(trader.zai_position().abs() <= 0.01);
+    assert!(amm.spot_price() > spot);
+}
+
+#[test]
+fn test_traders_tighten_basis_in_crash() {
+    let without = run(ScenarioId::BlackThursday, 0);
+    let with = run(ScenarioId::BlackThursday, 1);
+
+    let (b0, b1) = (mean_abs_basis(&without), mean_abs_basis(&with));
+    println!("Black Thursday mean |basis|: {:.4} -> {:.4}", b0, b1);
+    assert!(b1 < b0 * 0.5, "basis trading should narrow the gap");
+
+    let trader = &with.basis_traders[0];
+    assert!(trader.trade_count > 0);
+    assert!(trader.pnl_zec(with.controller.redemption_price) > 0.0);
+}
@@ -0,0 +1,206 @@
+//! Block-time jitter and per-block transaction capacity.
+//!
+//! Blocks get a drawn interval and a limited number of transactions.
+//! Agents that don't fit queue for the next block and are served first
+//! there; liquidations share the same room.
+
+use zai_sim::agents::{AgentAction, CdpHolder, CdpHolderConfig, NoiseTraderPopulation};
+use zai_sim::amm::Amm;
+use zai_sim::block_space::{BlockSpace, BlockSpaceConfig};
+use zai_sim::cdp::{CdpConfig, VaultRegistry};
+use zai_sim::liquidation::{LiquidationConfig, LiquidationEngine};
+use zai_sim::scenario::{Scenario, ScenarioConfig};
+use zai_sim::scenarios::{add_agents, generate_prices, ScenarioId};
+
+fn trade() -> AgentAction {
+    AgentAction::SellZec {
+        zec_spent: 1.0,
+        zai_received: 50.0,
+    }
+}
+
+#[test]
+fn test_uniform_unbounded_by_default() {
+    let mut space = BlockSpace::new(BlockSpaceConfig::default(), 1);
+    for _ in 0..10 {
+        assert_eq!(space.begin_block(), 75.0);
+        for i in 0..100 {
+            assert!(space.admit(&format!("agent_{}", i)));
+            space.fill(&[trade()]);
+        }
+    }
+    assert_eq!(space.elapsed_secs, 750.0);
+    assert_eq!(space.liquidation_room(), None);
+    assert_eq!(space.total_deferred, 0);
+}
+
+#[test]
+fn test_jitter_keeps_mean_block_time() {
+    let mut space = BlockSpace::new(
+        BlockSpaceConfig {
+            block_time_jitter: 0.5,
+            ..BlockSpaceConfig::default()
+        },
+        7,
+    );
+    let times: Vec<f64> = (0..10_000).map(|_| space.begin_block()).collect();
+    let mean = times.iter().sum::<f64>() / times.len() as f64;
+    assert!((mean - 75.0).abs() < 1.5, "{}", mean);
+    assert!(times.iter().all(|&t| t > 0.0));
+    assert!(times.iter().any(|&t| t > 120.0));
+    assert!(times.iter().any(|&t| t < 40.0));
+}
+
+#[test]
+fn test_overflow_queues_to_next_block() {
+    let mut space = BlockSpace::new(
+        BlockSpaceConfig {
+            max_txs_per_block: Some(3),
+            ..BlockSpaceConfig::default()
+        },
+        1,
+    );
+    space.begin_block();
+    for agent in ["a", "b", "c"] {
+        assert!(space.admit(agent));
+        space.fill(&[trade(), AgentAction::None]);
+    }
+    assert!(!space.admit("d"));
+    assert_eq!(space.liquidation_room(), Some(0));
+
+    // `d` is served first: newcomers only get the room it doesn't need
+    space.begin_block();
+    assert!(space.queued.contains("d"));
+    assert!(space.admit("x"));
+    space.fill(&[trade()]);
+    assert!(space.admit("y"));
+    space.fill(&[trade()]);
+    assert!(!space.admit("z"));
+    assert!(space.admit("d"));
+    assert_eq!(space.total_deferred, 2);
+}
+
+#[test]
+fn test_long_blocks_have_less_room() {
+    let mut space = BlockSpace::new(
+        BlockSpaceConfig {
+            block_time_jitter: 0.5,
+            max_txs_per_block: Some(100),
+            ..BlockSpaceConfig::default()
+        },
+        3,
+    );
+    for _ in 0..100 {
+        let t = space.begin_block();
+        let room = space.liquidation_room().unwrap();
+        assert_eq!(room, (100.0 * (75.0 / t)).round() as u32);
+    }
+}
+
+#[test]
+fn test_liquidations_stop_when_block_is_full() {
+    let mut amm = Amm::new(10000.0, 500000.0, 0.003);
+    for b in 1..=50 {
+        amm.record_price(b);
+    }
+    let mut registry = VaultRegistry::new(CdpConfig {
+        stability_fee_rate: 0.0,
+        ..CdpConfig::default()
+    });
+    for _ in 0..3 {
+        let id = registry
+            .open_vault("owner", 40.0, 1000.0, 50, &amm)
+            .unwrap();
+        registry.vaults.get_mut(&id).unwrap().collateral_zec = 28.0;
+        registry.reindex(id);
+    }
+    let mut engine = LiquidationEngine::new(LiquidationConfig::default());
+    engine.block_capacity = Some(1);
+    let results = engine.transparent_liquidate(&mut registry, &mut amm, 51);
+    assert_eq!(results.len(), 1);
+    assert_eq!(engine.block_capacity, Some(0));
+    assert_eq!(registry.vaults.len(), 2);
+
+    // The rest go once the next block has room
+    engine.block_capacity = None;
+    let results = engine.transparent_liquidate(&mut registry, &mut amm, 52);
+    assert_eq!(results.len(), 2);
+}
+
+fn run(block_space: Option<BlockSpaceConfig>) -> Scenario {
+    let config = ScenarioConfig {
+        block_space,
+        noise_traders: Some(NoiseTraderPopulation::default()),
+        ..ScenarioConfig::default()
+    };
+    let mut scenario = Scenario::new_with_seed(&config, 42);
+    add_agents(ScenarioId::BlackThursday, &mut scenario);
+    for i in 0..10 {
+        let size = (i % 3 + 1) as f64;
+        scenario.cdp_holders.push(CdpHolder::new(CdpHolderConfig {
+            target_ratio: 1.6,
+            action_threshold_ratio: 1.2,
+            reserve_zec: 0.0,
+            initial_collateral: 40.0 * size,
+            initial_debt: 1250.0 * size,
+        }));
+    }
+    scenario.run(&generate_prices(ScenarioId::BlackThursday, 1000, 42));
+    scenario
+}
+
+#[test]
+fn test_scenario_under_capacity_limit() {
+    let unbounded = run(None);
+    assert!(unbounded
+        .metrics
+        .iter()
+        .all(|m| m.block_time_secs == 0.0 && m.deferred_agents == 0));
+
+    let limited = run(Some(BlockSpaceConfig {
+        max_txs_per_block: Some(3),
+        ..BlockSpaceConfig::default()
+    }));
+    assert!(limited.metrics.iter().all(|m| m.block_time_secs == 75.0));
+    assert!(limited.metrics.iter().any(|m| m.deferred_agents > 0));
+    assert!(limited.metrics.iter().all(|m| m.liquidation_count <= 3));
+    assert!(limited.block_space.as_ref().unwrap().total_deferred > 0);
+
+    let jittered = run(Some(BlockSpaceConfig {
+        block_time_jitter: 0.3,
+        max_txs_per_block: Some(3),
+        ..BlockSpaceConfig::default()
+    }));
+    let times: Vec<f64> = jittered.metrics.iter().map(|m| m.block_time_secs).collect();
+    let mean = times.iter().sum::<f64>() / times.len() as f64;
+    assert!((mean - 75.0).abs() < 7.5, "{}", mean);
+    assert!(times.windows(2).any(|w| w[0] != w[1]));
+
+    let summary = |s: &Scenario| {
+        let max_liq = s
+            .metrics
+            .iter()
+            .map(|m| m.liquidation_count)
+            .max()
+            .unwrap_or(0);
+        let deferred: u32 = s.metrics.iter().map(|m| m.deferred_agents).sum();
+        (
+            s.liquidation_engine.history.len(),
+            max_liq,
+            deferred,
+            s.liquidation_engine.total_bad_debt,
+        )
+    };
+    println!("\n{:<10} liquidations  max/block  deferred  bad debt", "");
+    for (name, s) in [
+        ("unbounded", &unbounded),
+        ("3 tx", &limited),
+        ("3 tx ±30%", &jittered),
+    ] {
+        let (n, max_liq, deferred, bad_debt) = summary(s);
+        println!(
+            "{:<10} {:>12}  {:>9}  {:>8}  {:>8.2}",
+            name, n, max_liq, deferred, bad_debt
+        );
+    }
+}
@@ -130,7 +130,7 @@ fn compute_true_twap(prices: &[f64], block_times: &[f64], window_blocks: usize)
 
 /// Compute mean and max TWAP error across all blocks (after warmup).
 fn compute_twap_errors(
-    metrics: &[zai_sim::scenario::BlockMetrics],
+    metrics: &zai_sim::scenario::MetricsStore,
     prices: &[f64],
     block_times: &[f64],
     window_blocks: usize,
@@ -140,7 +140,7 @@ fn compute_twap_errors(
     let mut max_error: f64 = 0.0;
     let mut count = 0;
 
-    for (i, m) in metrics.iter().enumerate() {
+    for (i, twap) in metrics.twap_price.iter().enumerate() {
         if i < warmup {
             continue;
         }
@@ -150,7 +150,7 @@ fn compute_twap_errors(
             window_blocks,
         );
         if true_twap > 0.0 {
-            let error = (m.twap_price - true_twap).abs() / true_twap;
+            let error = (twap - true_twap).abs() / true_twap;
             sum_error += error;
             max_error = max_error.max(error);
             count += 1;
@@ -170,9 +170,11 @@ fn compute_twap_errors(
 // ═══════════════════════════════════════════════════════════════════════
 
 fn default_config() -> ScenarioConfig {
-    let mut c = ScenarioConfig::default();
-    c.amm_initial_zec = 100_000.0;
-    c.amm_initial_zai = 5_000_000.0;
+    let mut c = ScenarioConfig {
+        amm_initial_zec: 100_000.0,
+        amm_initial_zai: 5_000_000.0,
+        ..ScenarioConfig::default()
+    };
     c.cdp_config.min_ratio = 2.0;
     c.cdp_config.twap_window = TWAP_WINDOW;
     c.controller_config = ControllerConfig::default_tick();
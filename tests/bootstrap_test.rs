@@ -88,9 +88,11 @@ fn bootstrap_configs() -> Vec<BootConfig> {
 }
 
 fn make_config(bc: &BootConfig) -> ScenarioConfig {
-    let mut config = ScenarioConfig::default();
-    config.amm_initial_zec = bc.amm_zec;
-    config.amm_initial_zai = bc.amm_zai;
+    let mut config = ScenarioConfig {
+        amm_initial_zec: bc.amm_zec,
+        amm_initial_zai: bc.amm_zai,
+        ..ScenarioConfig::default()
+    };
     config.cdp_config.min_ratio = bc.min_ratio;
     config.cdp_config.twap_window = bc.twap_window;
     config.controller_config = ControllerConfig::default_tick();
@@ -204,9 +206,11 @@ fn run_static(bc: &BootConfig, scenario_id: ScenarioId, scenario_label: &str) ->
 
 fn run_growing(crash_start: usize, run_name: &str) -> (BootstrapRow, Scenario) {
     // Start at $500K config
-    let mut config = ScenarioConfig::default();
-    config.amm_initial_zec = 10_000.0;
-    config.amm_initial_zai = 500_000.0;
+    let mut config = ScenarioConfig {
+        amm_initial_zec: 10_000.0,
+        amm_initial_zai: 500_000.0,
+        ..ScenarioConfig::default()
+    };
     config.cdp_config.min_ratio = 2.5;
     config.cdp_config.twap_window = 120;
     config.controller_config = ControllerConfig::default_tick();
@@ -244,7 +248,7 @@ fn run_growing(crash_start: usize, run_name: &str) -> (BootstrapRow, Scenario) {
         let block = i as u64 + 1;
 
         // Inject liquidity every 50 blocks during growth phase (blocks 50-500)
-        if block <= 500 && block % 50 == 0 {
+        if block <= 500 && block.is_multiple_of(50) {
             let ratio = scenario.amm.reserve_zai / scenario.amm.reserve_zec;
             let growth_zai = growth_zec_per_injection * ratio;
             scenario
@@ -364,9 +368,11 @@ fn bootstrap_liquidity_path() {
 
     for &(crash_start, name) in &growth_runs {
         let config_for_report = {
-            let mut c = ScenarioConfig::default();
-            c.amm_initial_zec = 10_000.0;
-            c.amm_initial_zai = 500_000.0;
+            let mut c = ScenarioConfig {
+                amm_initial_zec: 10_000.0,
+                amm_initial_zai: 500_000.0,
+                ..ScenarioConfig::default()
+            };
             c.cdp_config.min_ratio = 2.5;
             c.cdp_config.twap_window = 120;
             c.controller_config = ControllerConfig::default_tick();
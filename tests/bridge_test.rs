@@ -0,0 +1,184 @@
+//! Cross-chain bridge latency and capacity for external capital.
+//!
+//! With a bridge, arbers' replenishment and ZEC top-ups wait for capacity
+//! and take a drawn latency to arrive instead of landing instantly.
+
+use approx::assert_relative_eq;
+use zai_sim::agents::{Arbitrageur, ArbitrageurConfig};
+use zai_sim::bridge::{Bridge, BridgeConfig, BridgeDirection};
+use zai_sim::pool::Asset;
+use zai_sim::report::compute_recovery_blocks;
+use zai_sim::scenario::{Scenario, ScenarioConfig};
+use zai_sim::scenarios::{add_agents, generate_prices, ScenarioId};
+
+fn fixed(deposit_capacity_zai: Option<f64>) -> BridgeConfig {
+    BridgeConfig {
+        deposit_latency_blocks: 5.0,
+        withdraw_latency_blocks: 10.0,
+        latency_jitter: 0.0,
+        deposit_capacity_zai,
+        withdraw_capacity_zai: None,
+    }
+}
+
+#[test]
+fn test_transfers_arrive_after_latency() {
+    let mut bridge = Bridge::new(fixed(None), 1);
+    bridge.request("arber_0", Asset::Zai, 100.0, BridgeDirection::Deposit);
+    bridge.request("arber_0", Asset::Zai, 40.0, BridgeDirection::Withdraw);
+    assert!(bridge.advance(1, 50.0).is_empty());
+    assert_eq!(
+        bridge.pending("arber_0", Asset::Zai, BridgeDirection::Deposit),
+        100.0
+    );
+    for block in 2..6 {
+        assert!(bridge.advance(block, 50.0).is_empty());
+    }
+    let arrived = bridge.advance(6, 50.0);
+    assert_eq!(arrived.len(), 1);
+    assert_eq!(arrived[0].direction, BridgeDirection::Deposit);
+    assert_eq!(bridge.total_deposited_zai, 100.0);
+    assert_eq!(bridge.outstanding_zai(50.0), 40.0);
+    let arrived = bridge.advance(11, 50.0);
+    assert_eq!(arrived[0].amount, 40.0);
+    assert_eq!(bridge.outstanding_zai(50.0), 0.0);
+}
+
+#[test]
+fn test_capacity_splits_and_queues() {
+    let mut bridge = Bridge::new(fixed(Some(1000.0)), 1);
+    // 2,500 ZAI of ZEC at 50 ahead of 500 ZAI, and a withdrawal that has
+    // its own capacity
+    bridge.request("arber_0", Asset::Zec, 50.0, BridgeDirection::Deposit);
+    bridge.request("arber_1", Asset::Zai, 500.0, BridgeDirection::Deposit);
+    bridge.request("arber_1", Asset::Zai, 9000.0, BridgeDirection::Withdraw);
+
+    bridge.advance(1, 50.0);
+    assert_eq!(bridge.queued.len(), 2);
+    assert_relative_eq!(bridge.queued[0].amount, 30.0);
+    assert_eq!(bridge.in_transit.len(), 2);
+
+    bridge.advance(2, 50.0);
+    assert_relative_eq!(bridge.queued[0].amount, 10.0);
+    bridge.advance(3, 50.0);
+    assert!(bridge.queued.is_empty());
+
+    let landed: Vec<f64> = (6..=8)
+        .flat_map(|b| bridge.advance(b, 50.0))
+        .map(|t| t.amount)
+        .collect();
+    assert_eq!(landed.len(), 4);
+    assert_relative_eq!(landed.iter().take(3).sum::<f64>(), 50.0);
+    assert_relative_eq!(bridge.total_deposited_zai, 3000.0);
+}
+
+#[test]
+fn test_latency_jitter_keeps_mean() {
+    let mut bridge = Bridge::new(
+        BridgeConfig {
+            deposit_capacity_zai: None,
+            ..BridgeConfig::default()
+        },
+        7,
+    );
+    for _ in 0..10_000 {
+        bridge.request("a", Asset::Zai, 1.0, BridgeDirection::Deposit);
+    }
+    bridge.advance(1, 50.0);
+    let latencies: Vec<f64> = bridge
+        .in_transit
+        .iter()
+        .map(|t| (t.arrives_at_block - 1) as f64)
+        .collect();
+    let mean = latencies.iter().sum::<f64>() / latencies.len() as f64;
+    assert!((mean - 12.0).abs() < 0.5, "{}", mean);
+    assert!(latencies.iter().any(|&l| l > 20.0));
+}
+
+fn run(bridge: Option<BridgeConfig>) -> Scenario {
+    let config = ScenarioConfig {
+        bridge,
+        ..ScenarioConfig::default()
+    };
+    let mut scenario = Scenario::new_with_seed(&config, 42);
+    add_agents(ScenarioId::FlashCrash, &mut scenario);
+    // An arber living on outside capital: little inventory, steady top-ups
+    scenario.arbers.push(Arbitrageur::new(ArbitrageurConfig {
+        initial_zai_balance: 5000.0,
+        initial_zec_balance: 0.0,
+        arb_latency_sell_blocks: 0,
+        capital_replenish_rate: 2000.0,
+        max_trade_pct: 0.5,
+        ..ArbitrageurConfig::default()
+    }));
+    scenario.run(&generate_prices(ScenarioId::FlashCrash, 1000, 42));
+    scenario
+}
+
+#[test]
+fn test_scenario_capital_crosses_bridge() {
+    let instant = run(None);
+    assert!(instant.arbers.iter().all(|a| !a.bridged));
+    assert!(instant
+        .metrics
+        .iter()
+        .all(|m| m.bridge_outstanding_zai == 0.0));
+
+    let bridged = run(Some(BridgeConfig::default()));
+    assert!(bridged.arbers.iter().all(|a| a.bridged));
+    assert!(bridged
+        .metrics
+        .iter()
+        .any(|m| m.bridge_outstanding_zai > 0.0));
+    let bridge = bridged.bridge.as_ref().unwrap();
+    assert!(bridge.total_deposited_zai > 0.0);
+    assert!(bridge.total_withdrawn_zai > 0.0);
+
+    let throttled = run(Some(BridgeConfig {
+        deposit_latency_blocks: 120.0,
+        withdraw_latency_blocks: 240.0,
+        deposit_capacity_zai: Some(500.0),
+        withdraw_capacity_zai: Some(500.0),
+        ..BridgeConfig::default()
+    }));
+
+    println!("\n{:<10} recovery  deposited ZAI", "");
+    for (name, s) in [
+        ("instant", &instant),
+        ("bridged", &bridged),
+        ("throttled", &throttled),
+    ] {
+        let recovery = compute_recovery_blocks(&s.metrics, 1.0, 0.10);
+        let deposited = s.bridge.as_ref().map_or(0.0, |b| b.total_deposited_zai);
+        println!("{:<10} {:>8}  {:>13.0}", name, recovery, deposited);
+    }
+}
+
+#[test]
+fn test_removing_bridge_lands_transfers() {
+    let mut scenario = run(Some(BridgeConfig {
+        deposit_latency_blocks: 500.0,
+        ..BridgeConfig::default()
+    }));
+    let i = scenario.arbers.len() - 1;
+    let id = &format!("arber_{}", i);
+    let bridge = scenario.bridge.as_ref().unwrap();
+    let zai_due = bridge.pending(id, Asset::Zai, BridgeDirection::Deposit)
+        + bridge.pending(id, Asset::Zai, BridgeDirection::Withdraw);
+    let zec_due = bridge.pending(id, Asset::Zec, BridgeDirection::Deposit);
+    assert!(zai_due > 0.0);
+    let (zec, zai) = (
+        scenario.arbers[i].zec_balance,
+        scenario.arbers[i].zai_balance,
+    );
+
+    let config = ScenarioConfig {
+        bridge: None,
+        ..scenario.config.clone()
+    };
+    scenario.reconfigure(config);
+    assert!(scenario.bridge.is_none());
+    assert!(!scenario.arbers[i].bridged);
+    assert_relative_eq!(scenario.arbers[i].zai_balance, zai + zai_due);
+    assert_relative_eq!(scenario.arbers[i].zec_balance, zec + zec_due);
+}
@@ -0,0 +1,132 @@
+//! Historical calibration of agent parameters.
+//!
+//! Back-solves arber thresholds, demand elasticity and miner AMM fraction
+//! from hourly ZECUSDT candles and an observed DEX volume.
+
+use zai_sim::calibration::{calibrate, CalibrationInputs};
+use zai_sim::historical::{load_hourly_candles, HourlyCandle};
+use zai_sim::report::generate_calibration_report;
+
+const DATA: &str = "data/black_thursday_2020_hourly.csv";
+
+/// Candles whose volume rises with the size of the hourly move.
+fn synthetic_candles(volume_per_pct: f64) -> Vec<HourlyCandle> {
+    let mut price = 50.0;
+    let mut candles = Vec::new();
+    for i in 0..200u64 {
+        let move_pct = [0.2, 1.0, -0.5, 2.0, -1.5][(i % 5) as usize];
+        let prev = price;
+        price *= 1.0 + move_pct / 100.0;
+        candles.push(HourlyCandle {
+            timestamp: 1_600_000_000 + i * 3600,
+            open: prev,
+            high: prev.max(price) * 1.002,
+            low: prev.min(price) * 0.998,
+            close: price,
+            volume_from: 1000.0 + volume_per_pct * move_pct.abs(),
+            volume_to: 0.0,
+        });
+    }
+    candles
+}
+
+#[test]
+fn test_load_hourly_candles() {
+    let candles = load_hourly_candles(DATA).expect("load candles");
+    assert!(candles.len() > 100);
+    for c in &candles {
+        assert!(c.low <= c.close && c.close <= c.high, "bad candle {:?}", c);
+        assert!(c.volume_from >= 0.0);
+    }
+    assert!(load_hourly_candles("data/does_not_exist.csv").is_err());
+}
+
+#[test]
+fn test_ranges_are_ordered() {
+    let candles = load_hourly_candles(DATA).unwrap();
+    let report = calibrate(DATA, &candles, &CalibrationInputs::default()).unwrap();
+    assert_eq!(report.params.len(), 3);
+    for p in &report.params {
+        assert!(
+            p.low <= p.estimate && p.estimate <= p.high,
+            "{}: {} <= {} <= {}",
+            p.name,
+            p.low,
+            p.estimate,
+            p.high
+        );
+        assert!(p.low >= 0.0);
+    }
+    assert!(report.dex_share > 0.0 && report.dex_share < 1.0);
+}
+
+#[test]
+fn test_arb_threshold_clears_round_trip_fee() {
+    let candles = synthetic_candles(0.0);
+    let inputs = CalibrationInputs {
+        swap_fee: 0.01,
+        ..CalibrationInputs::default()
+    };
+    let report = calibrate("synthetic", &candles, &inputs).unwrap();
+    let arb = report.get("arb_threshold_pct").unwrap();
+    assert!((arb.low - 2.0).abs() < 1e-9);
+    assert!(arb.estimate >= 2.0);
+}
+
+#[test]
+fn test_elasticity_tracks_volume_response() {
+    let flat = calibrate(
+        "flat",
+        &synthetic_candles(0.0),
+        &CalibrationInputs::default(),
+    )
+    .unwrap();
+    let responsive = calibrate(
+        "responsive",
+        &synthetic_candles(500.0),
+        &CalibrationInputs::default(),
+    )
+    .unwrap();
+    let flat_e = flat.get("demand_elasticity").unwrap().estimate;
+    let resp_e = responsive.get("demand_elasticity").unwrap().estimate;
+    assert!(flat_e.abs() < 1e-9, "flat volume elasticity {}", flat_e);
+    assert!(resp_e > 0.0);
+}
+
+#[test]
+fn test_miner_fraction_bounded_by_dex_volume() {
+    let candles = synthetic_candles(0.0);
+    let small = calibrate(
+        "small",
+        &candles,
+        &CalibrationInputs {
+            dex_volume_zec_per_day: 100.0,
+            ..CalibrationInputs::default()
+        },
+    )
+    .unwrap();
+    let miner = small.get("miner_amm_fraction").unwrap();
+    let m = CalibrationInputs::default().miner;
+    let sold_per_day = m.block_reward * m.miner_sell_fraction * 1152.0;
+    assert!((miner.high - (100.0 / sold_per_day).min(1.0)).abs() < 1e-9);
+
+    let (_, _, calibrated) = small.calibrated_configs();
+    assert!((calibrated.miner_amm_fraction - miner.estimate).abs() < 1e-12);
+}
+
+#[test]
+fn test_rejects_too_few_candles() {
+    let candles = synthetic_candles(0.0);
+    assert!(calibrate("short", &candles[..2], &CalibrationInputs::default()).is_err());
+}
+
+#[test]
+fn test_calibration_html_report() {
+    let candles = load_hourly_candles(DATA).unwrap();
+    let report = calibrate(DATA, &candles, &CalibrationInputs::default()).unwrap();
+    let html = generate_calibration_report(&report);
+    assert!(html.contains("Calibration Report"));
+    for p in &report.params {
+        assert!(html.contains(p.name));
+    }
+}
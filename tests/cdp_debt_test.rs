@@ -0,0 +1,155 @@
+//! CDP holders managing debt through the AMM.
+//!
+//! Under a debt policy a holder repays debt with ZAI (bought on the AMM
+//! with reserve ZEC) when its ratio is low and re-borrows and buys ZEC when
+//! it is comfortably high, so vault owners trade both ways on the AMM.
+
+use zai_sim::agents::{AgentAction, CdpHolder, CdpHolderConfig, DebtPolicy};
+use zai_sim::amm::Amm;
+use zai_sim::cdp::{CdpConfig, VaultRegistry};
+use zai_sim::scenario::{Scenario, ScenarioConfig};
+use zai_sim::scenarios::{add_agents, generate_prices, ScenarioId};
+
+/// Vault at CR 2.5 (50 ZEC collateral, 1000 ZAI debt, price 50).
+fn setup(target: f64, threshold: f64, policy: DebtPolicy) -> (CdpHolder, VaultRegistry, Amm) {
+    let amm = Amm::new(10_000.0, 500_000.0, 0.003);
+    // No fees, so the actions below are exact
+    let mut registry = VaultRegistry::new(CdpConfig {
+        stability_fee_rate: 0.0,
+        ..CdpConfig::default()
+    });
+    let mut holder = CdpHolder::new(CdpHolderConfig {
+        target_ratio: target,
+        action_threshold_ratio: threshold,
+        reserve_zec: 100.0,
+        initial_collateral: 50.0,
+        initial_debt: 1000.0,
+    })
+    .with_debt_policy(policy);
+    holder.open_vault(&mut registry, &amm, 0).unwrap();
+    (holder, registry, amm)
+}
+
+fn debt(holder: &CdpHolder, registry: &VaultRegistry) -> f64 {
+    registry
+        .get_vault(holder.vault_id.unwrap())
+        .unwrap()
+        .debt_zai
+}
+
+#[test]
+fn test_repays_from_zai_balance_before_topping_up() {
+    let (mut holder, mut registry, mut amm) = setup(3.0, 2.8, DebtPolicy::default());
+    holder.zai_balance = 500.0;
+    let action = holder.act_with_amm(&mut registry, &mut amm, |_| true, 1);
+
+    assert!(matches!(action, AgentAction::CdpAction { .. }));
+    // Back to CR 3.0: debt 2500 / 3
+    assert!((debt(&holder, &registry) - 2500.0 / 3.0).abs() < 0.01);
+    assert!((holder.zai_balance - (500.0 - 500.0 / 3.0)).abs() < 0.01);
+    assert_eq!(holder.repayments, 1);
+    assert_eq!(holder.topups, 0);
+    assert_eq!(holder.reserve_zec, 100.0);
+    assert_eq!(amm.reserve_zec, 10_000.0);
+}
+
+#[test]
+fn test_buys_zai_on_amm_to_repay() {
+    let policy = DebtPolicy {
+        buy_to_repay: true,
+        ..DebtPolicy::default()
+    };
+    let (mut holder, mut registry, mut amm) = setup(3.0, 2.8, policy);
+    let price = amm.spot_price();
+    holder.act_with_amm(&mut registry, &mut amm, |_| true, 1);
+
+    // About 3.33 ZEC sold for the 166.67 ZAI of excess debt, less fees
+    assert!((holder.reserve_zec - (100.0 - 10.0 / 3.0)).abs() < 1e-9);
+    assert!(amm.spot_price() < price);
+    let repaid = 1000.0 - debt(&holder, &registry);
+    assert!(repaid > 160.0 && repaid < 2500.0 / 15.0);
+    assert!(holder.zai_balance < 1e-9);
+    assert_eq!(holder.repayments, 1);
+    assert_eq!(holder.topups, 0);
+}
+
+#[test]
+fn test_repay_respects_debt_floor() {
+    // Target far above reach: repaying down to it would leave 25 ZAI of
+    // debt, under the 100 floor, so a balance that covers it all repays all
+    let (mut holder, mut registry, mut amm) = setup(100.0, 2.8, DebtPolicy::default());
+    holder.zai_balance = 2000.0;
+    holder.act_with_amm(&mut registry, &mut amm, |_| true, 1);
+    assert_eq!(debt(&holder, &registry), 0.0);
+    assert!((holder.zai_balance - 1000.0).abs() < 1e-3);
+
+    // One that doesn't stops at the floor
+    let (mut holder, mut registry, mut amm) = setup(100.0, 2.8, DebtPolicy::default());
+    holder.zai_balance = 950.0;
+    holder.act_with_amm(&mut registry, &mut amm, |_| true, 1);
+    assert!((debt(&holder, &registry) - 100.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_reborrows_when_ratio_is_high() {
+    let policy = DebtPolicy {
+        reborrow_ratio: Some(2.3),
+        ..DebtPolicy::default()
+    };
+    let (mut holder, mut registry, mut amm) = setup(2.0, 1.8, policy.clone());
+    let price = amm.spot_price();
+    let action = holder.act_with_amm(&mut registry, &mut amm, |_| true, 1);
+
+    assert!(matches!(action, AgentAction::CdpAction { .. }));
+    // Borrowed back to CR 2.0 and bought ZEC with it
+    assert!((debt(&holder, &registry) - 1250.0).abs() < 1e-3);
+    assert!(holder.reserve_zec > 104.0 && holder.reserve_zec < 105.0);
+    assert!(amm.spot_price() > price);
+    assert_eq!(holder.reborrows, 1);
+    assert_eq!(holder.transactions(), 1);
+
+    // Not while minting is refused
+    let (mut holder, mut registry, mut amm) = setup(2.0, 1.8, policy);
+    let action = holder.act_with_amm(&mut registry, &mut amm, |_| false, 1);
+    assert!(matches!(action, AgentAction::None));
+    assert_eq!(debt(&holder, &registry), 1000.0);
+}
+
+fn run_holders(id: ScenarioId, policy: DebtPolicy) -> Scenario {
+    let mut scenario = Scenario::new_with_seed(&ScenarioConfig::default(), 42);
+    add_agents(id, &mut scenario);
+    for _ in 0..5 {
+        scenario.cdp_holders.push(
+            CdpHolder::new(CdpHolderConfig {
+                target_ratio: 2.0,
+                action_threshold_ratio: 1.9,
+                reserve_zec: 10.0,
+                initial_collateral: 5.0,
+                initial_debt: 120.0,
+            })
+            .with_debt_policy(policy.clone()),
+        );
+    }
+    scenario.run(&generate_prices(id, 1000, 42));
+    scenario
+}
+
+#[test]
+fn test_holders_trade_both_ways() {
+    let policy = DebtPolicy {
+        buy_to_repay: true,
+        reborrow_ratio: Some(2.2),
+    };
+    let repayments = |s: &Scenario| s.cdp_holders.iter().map(|h| h.repayments).sum::<u32>();
+    let reborrows = |s: &Scenario| s.cdp_holders.iter().map(|h| h.reborrows).sum::<u32>();
+
+    let bear = run_holders(ScenarioId::SustainedBear, policy.clone());
+    assert!(repayments(&bear) > 0);
+
+    let bull = run_holders(ScenarioId::BullMarket, policy);
+    assert!(reborrows(&bull) > 0);
+
+    // Without a policy holders only ever top up
+    let plain = run_holders(ScenarioId::SustainedBear, DebtPolicy::default());
+    assert_eq!(repayments(&plain) + reborrows(&plain), 0);
+}
@@ -1,6 +1,7 @@
 use approx::assert_relative_eq;
-use zai_sim::amm::Amm;
+use zai_sim::amm::{Amm, TwapKind};
 use zai_sim::cdp::{CdpConfig, VaultRegistry};
+use zai_sim::error::ZaiSimError;
 
 /// Helper: create an AMM at $50 ZEC/ZAI with TWAP recorded for sufficient blocks.
 fn setup_amm(block: u64) -> Amm {
@@ -19,6 +20,7 @@ fn default_config() -> CdpConfig {
         debt_floor: 100.0,
         stability_fee_rate: 0.02,
         twap_window: 48,
+        twap_kind: TwapKind::Arithmetic,
     }
 }
 
@@ -95,7 +97,10 @@ fn test_min_ratio_enforcement() {
     // Try to open undercollateralized vault: 1 ZEC ($50), 100 ZAI → ratio = 0.5
     let result = registry.open_vault("alice", 1.0, 100.0, 100, &amm);
     assert!(result.is_err(), "Should reject vault below min ratio");
-    assert!(result.unwrap_err().contains("below minimum"));
+    assert!(matches!(
+        result.unwrap_err(),
+        ZaiSimError::BelowMinRatio { .. }
+    ));
 
     // Open valid vault: 10 ZEC ($500), 300 ZAI → ratio ≈ 1.67
     let id = registry
@@ -174,7 +179,10 @@ fn test_debt_floor() {
     // Can't open vault with debt below floor (100 ZAI)
     let result = registry.open_vault("alice", 10.0, 50.0, 100, &amm);
     assert!(result.is_err(), "Should reject debt below floor");
-    assert!(result.unwrap_err().contains("below floor"));
+    assert!(matches!(
+        result.unwrap_err(),
+        ZaiSimError::BelowDebtFloor { .. }
+    ));
 
     // Zero debt is fine (collateral-only vault)
     let id_zero = registry.open_vault("alice", 10.0, 0.0, 100, &amm).unwrap();
@@ -188,7 +196,10 @@ fn test_debt_floor() {
     // Can't partially repay below floor: repay 50 → leaves 50 < 100
     let result = registry.repay_zai(id, 50.0, 100);
     assert!(result.is_err(), "Partial repay below floor should fail");
-    assert!(result.unwrap_err().contains("below floor"));
+    assert!(matches!(
+        result.unwrap_err(),
+        ZaiSimError::BelowDebtFloor { .. }
+    ));
 
     // Full repayment to zero is always allowed
     let result = registry.repay_zai(id, 100.0, 100);
@@ -257,7 +268,10 @@ fn test_deposit_withdraw_collateral() {
     // Can't withdraw more than available
     let result = registry.withdraw_collateral(id, 20.0, 100, &amm);
     assert!(result.is_err());
-    assert!(result.unwrap_err().contains("Insufficient collateral"));
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("Insufficient collateral"));
 
     // Can't deposit zero or negative
     assert!(registry.deposit_collateral(id, 0.0).is_err());
@@ -356,6 +370,7 @@ fn test_configurable_parameters() {
         debt_floor: 500.0,
         stability_fee_rate: 0.05,
         twap_window: 96,
+        twap_kind: TwapKind::Arithmetic,
     };
 
     let mut registry = VaultRegistry::new(config);
@@ -0,0 +1,218 @@
+//! Debt ceiling auto-growth policy.
+//!
+//! A ceiling policy grows the debt ceiling toward a multiple of AMM depth
+//! while the system is healthy, shrinks it on thin collateralization or
+//! bad debt, and changes course only after a signal persists.
+
+use approx::assert_relative_eq;
+use zai_sim::agents::{CdpHolder, CdpHolderConfig};
+use zai_sim::ceiling_policy::{CeilingInputs, CeilingMode, CeilingPolicy, CeilingPolicyConfig};
+use zai_sim::circuit_breaker::{DebtCeiling, DebtCeilingConfig};
+use zai_sim::scenario::{Scenario, ScenarioConfig};
+use zai_sim::scenarios::{add_agents, generate_prices, ScenarioId};
+
+fn ceiling(initial: f64, min: f64) -> DebtCeiling {
+    DebtCeiling::new(DebtCeilingConfig {
+        initial_ceiling: initial,
+        min_ceiling: min,
+        ..DebtCeilingConfig::default()
+    })
+}
+
+fn policy() -> CeilingPolicy {
+    CeilingPolicy::new(CeilingPolicyConfig {
+        growth_rate: 0.01,
+        shrink_rate: 0.01,
+        hysteresis_blocks: 10,
+        bad_debt_window_blocks: 20,
+        ..CeilingPolicyConfig::default()
+    })
+}
+
+/// Inputs with 50,000 ZAI of AMM depth and 50,000 ZAI of debt backed at
+/// `ratio`.
+fn inputs(ratio: f64, total_bad_debt: f64) -> CeilingInputs {
+    CeilingInputs {
+        amm_depth_zai: 50_000.0,
+        total_debt: 50_000.0,
+        collateral_value: 50_000.0 * ratio,
+        total_bad_debt,
+    }
+}
+
+#[test]
+fn test_grows_to_depth_target_after_hysteresis() {
+    let mut ceiling = ceiling(100_000.0, 50_000.0);
+    let mut policy = policy();
+    for _ in 0..9 {
+        policy.adjust(&mut ceiling, &inputs(4.0, 0.0));
+        assert_eq!(policy.mode, CeilingMode::Hold);
+        assert_eq!(ceiling.current_ceiling, 100_000.0);
+    }
+    policy.adjust(&mut ceiling, &inputs(4.0, 0.0));
+    assert_eq!(policy.mode, CeilingMode::Grow);
+    assert_relative_eq!(ceiling.current_ceiling, 101_000.0);
+
+    // 10x depth caps the growth
+    for _ in 0..500 {
+        policy.adjust(&mut ceiling, &inputs(4.0, 0.0));
+    }
+    assert_relative_eq!(ceiling.current_ceiling, 500_000.0);
+
+    // Shallower depth pulls it down while still growing
+    let shallow = CeilingInputs {
+        amm_depth_zai: 20_000.0,
+        ..inputs(4.0, 0.0)
+    };
+    for _ in 0..500 {
+        policy.adjust(&mut ceiling, &shallow);
+    }
+    assert_eq!(policy.mode, CeilingMode::Grow);
+    assert_relative_eq!(ceiling.current_ceiling, 200_000.0);
+}
+
+#[test]
+fn test_band_and_hysteresis_hold_course() {
+    let mut ceiling = ceiling(100_000.0, 50_000.0);
+    let mut policy = policy();
+    for _ in 0..10 {
+        policy.adjust(&mut ceiling, &inputs(4.0, 0.0));
+    }
+    assert_eq!(policy.mode, CeilingMode::Grow);
+
+    // Inside the band the course stays
+    for _ in 0..20 {
+        policy.adjust(&mut ceiling, &inputs(2.0, 0.0));
+    }
+    assert_eq!(policy.mode, CeilingMode::Grow);
+
+    // A dip shorter than the hysteresis, broken by the band, changes
+    // nothing
+    for _ in 0..9 {
+        policy.adjust(&mut ceiling, &inputs(1.5, 0.0));
+    }
+    policy.adjust(&mut ceiling, &inputs(2.0, 0.0));
+    for _ in 0..9 {
+        policy.adjust(&mut ceiling, &inputs(1.5, 0.0));
+    }
+    assert_eq!(policy.mode, CeilingMode::Grow);
+    let grown = ceiling.current_ceiling;
+
+    policy.adjust(&mut ceiling, &inputs(1.5, 0.0));
+    assert_eq!(policy.mode, CeilingMode::Shrink);
+    assert_relative_eq!(ceiling.current_ceiling, grown * 0.99);
+    assert_eq!(policy.mode_changes, 2);
+    for _ in 0..500 {
+        policy.adjust(&mut ceiling, &inputs(1.5, 0.0));
+    }
+    assert_eq!(ceiling.current_ceiling, 50_000.0);
+}
+
+#[test]
+fn test_recent_bad_debt_shrinks_until_it_ages_out() {
+    let mut ceiling = ceiling(100_000.0, 50_000.0);
+    let mut policy = policy();
+    for _ in 0..10 {
+        policy.adjust(&mut ceiling, &inputs(4.0, 0.0));
+    }
+    // 500 ZAI of bad debt against a 100 ZAI tolerance
+    for _ in 0..10 {
+        policy.adjust(&mut ceiling, &inputs(4.0, 500.0));
+    }
+    assert_eq!(policy.mode, CeilingMode::Shrink);
+    assert_relative_eq!(policy.recent_bad_debt(), 500.0);
+
+    // Once the loss is out of the 20-block window, growth resumes after
+    // the hysteresis
+    let mut blocks = 0;
+    while policy.mode == CeilingMode::Shrink {
+        policy.adjust(&mut ceiling, &inputs(4.0, 500.0));
+        blocks += 1;
+    }
+    assert_eq!(policy.recent_bad_debt(), 0.0);
+    assert_eq!(blocks, 20);
+}
+
+fn bootstrap(ceiling_policy: Option<CeilingPolicyConfig>) -> Scenario {
+    let config = ScenarioConfig {
+        debt_ceiling_config: DebtCeilingConfig {
+            initial_ceiling: 5_000.0,
+            min_ceiling: 5_000.0,
+            ..DebtCeilingConfig::default()
+        },
+        ceiling_policy,
+        ..ScenarioConfig::default()
+    };
+    let mut scenario = Scenario::new_with_seed(&config, 42);
+    add_agents(ScenarioId::SteadyState, &mut scenario);
+    // 20,000 ZAI of vault demand against a 5,000 ZAI launch ceiling
+    for _ in 0..20 {
+        scenario.cdp_holders.push(CdpHolder::new(CdpHolderConfig {
+            initial_collateral: 100.0,
+            initial_debt: 1000.0,
+            ..CdpHolderConfig::default()
+        }));
+    }
+    scenario.run(&generate_prices(ScenarioId::SteadyState, 2000, 42));
+    scenario
+}
+
+#[test]
+fn test_bootstrap_vaults_open_as_ceiling_grows() {
+    let scenario = bootstrap(Some(CeilingPolicyConfig::default()));
+    let metrics = &scenario.metrics;
+    assert_eq!(metrics.vaults_waiting[0], 15);
+    assert_eq!(metrics.vault_count[0], 5);
+    assert!(metrics.vaults_waiting.windows(2).all(|w| w[1] <= w[0]));
+    let last = metrics.last().unwrap();
+    assert_eq!(last.vaults_waiting, 0);
+    assert_eq!(last.vault_count, 20);
+    assert!(last.debt_ceiling > 20_000.0);
+    assert_eq!(
+        scenario.ceiling_policy.as_ref().unwrap().vaults_admitted,
+        15
+    );
+
+    let opened = metrics.iter().position(|m| m.vaults_waiting == 0).unwrap();
+    println!(
+        "\nBootstrap: all 20 vaults open by block {}; ceiling {:.0} at {:.1}% utilization",
+        metrics.block[opened],
+        last.debt_ceiling,
+        last.debt_ceiling_utilization * 100.0
+    );
+
+    // Without a policy the ceiling is advisory only
+    let unbounded = bootstrap(None);
+    assert_eq!(unbounded.metrics.vault_count[0], 20);
+    assert!(unbounded.metrics.debt_ceiling_utilization[0] > 1.0);
+    assert!(unbounded.metrics.iter().all(|m| m.vaults_waiting == 0));
+}
+
+#[test]
+fn test_removing_policy_opens_waiting_vaults() {
+    let config = ScenarioConfig {
+        debt_ceiling_config: DebtCeilingConfig {
+            initial_ceiling: 5_000.0,
+            min_ceiling: 5_000.0,
+            ..DebtCeilingConfig::default()
+        },
+        ceiling_policy: Some(CeilingPolicyConfig::default()),
+        ..ScenarioConfig::default()
+    };
+    let mut scenario = Scenario::new_with_seed(&config, 42);
+    for _ in 0..8 {
+        scenario
+            .cdp_holders
+            .push(CdpHolder::new(CdpHolderConfig::default()));
+    }
+    scenario.run(&generate_prices(ScenarioId::SteadyState, 10, 42));
+    assert_eq!(scenario.registry.vaults.len(), 5);
+
+    let config = ScenarioConfig {
+        ceiling_policy: None,
+        ..scenario.config.clone()
+    };
+    scenario.reconfigure(config);
+    assert_eq!(scenario.registry.vaults.len(), 8);
+    assert!(scenario.cdp_holders.iter().all(|h| h.vault_id.is_some()));
+}
@@ -0,0 +1,171 @@
+//! Checkpoint and resume.
+//!
+//! A scenario that writes a full-state checkpoint every N blocks can be
+//! restored after an interruption and continued; the resumed run must follow
+//! the same trajectory as one that was never interrupted.
+
+use std::path::PathBuf;
+
+use zai_sim::agents::{Attacker, AttackerConfig, LpAgent, LpAgentConfig};
+use zai_sim::error::ZaiSimError;
+use zai_sim::lending::LendingMarketConfig;
+use zai_sim::scenario::{Scenario, ScenarioConfig};
+use zai_sim::scenarios::{add_agents, generate_prices, ScenarioId};
+
+const BLOCKS: usize = 1000;
+const SEED: u64 = 42;
+
+fn checkpoint_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("zai_sim_checkpoint_{}.json", name))
+}
+
+fn build(id: ScenarioId, config: &ScenarioConfig) -> Scenario {
+    let mut scenario = Scenario::new_with_seed(config, SEED);
+    add_agents(id, &mut scenario);
+    scenario
+        .lp_agents
+        .push(LpAgent::new(LpAgentConfig::default()));
+    for i in 0..10 {
+        scenario
+            .registry
+            .open_vault(
+                &format!("vault_{}", i),
+                (1.6 + i as f64 * 0.2) * 20.0,
+                1000.0,
+                0,
+                &scenario.amm,
+            )
+            .unwrap();
+    }
+    scenario
+}
+
+/// Run `id` uninterrupted, and again interrupted at `stop` then resumed from
+/// the checkpoint written at that block.
+fn run_both(
+    id: ScenarioId,
+    config: ScenarioConfig,
+    stop: usize,
+    name: &str,
+) -> (Scenario, Scenario) {
+    let prices = generate_prices(id, BLOCKS, SEED);
+
+    let mut uninterrupted = build(id, &config);
+    uninterrupted.run(&prices);
+
+    let path = checkpoint_path(name);
+    let config = ScenarioConfig {
+        checkpoint_interval: 100,
+        checkpoint_path: Some(path.clone()),
+        ..config
+    };
+    let mut interrupted = build(id, &config);
+    interrupted.run(&prices[..stop]);
+    drop(interrupted);
+
+    let mut resumed = Scenario::restore(&path).unwrap();
+    assert_eq!(resumed.last_block(), stop as u64);
+    resumed.run(&prices);
+
+    (uninterrupted, resumed)
+}
+
+fn assert_same_trajectory(a: &Scenario, b: &Scenario) {
+    assert_eq!(a.metrics.len(), b.metrics.len());
+    for (x, y) in a.metrics.iter().zip(&b.metrics) {
+        assert_eq!(x.block, y.block);
+        assert_eq!(x.amm_spot_price, y.amm_spot_price, "block {}", x.block);
+        assert_eq!(x.twap_price, y.twap_price, "block {}", x.block);
+        assert_eq!(x.redemption_price, y.redemption_price, "block {}", x.block);
+        assert_eq!(x.total_debt, y.total_debt, "block {}", x.block);
+        assert_eq!(x.vault_count, y.vault_count, "block {}", x.block);
+        assert_eq!(
+            x.liquidation_count, y.liquidation_count,
+            "block {}",
+            x.block
+        );
+    }
+}
+
+#[test]
+fn test_resume_matches_uninterrupted_run() {
+    let (a, b) = run_both(
+        ScenarioId::BlackThursday,
+        ScenarioConfig::default(),
+        500,
+        "det",
+    );
+    assert_same_trajectory(&a, &b);
+}
+
+#[test]
+fn test_resume_preserves_rng_state() {
+    let config = ScenarioConfig {
+        stochastic: true,
+        ..ScenarioConfig::default()
+    };
+    let (a, b) = run_both(ScenarioId::SteadyState, config, 300, "stochastic");
+    assert_same_trajectory(&a, &b);
+}
+
+#[test]
+fn test_resume_with_lending_market_and_attacker() {
+    let config = ScenarioConfig {
+        lending_market: Some(LendingMarketConfig::default()),
+        ..ScenarioConfig::default()
+    };
+    let prices = generate_prices(ScenarioId::SteadyState, BLOCKS, SEED);
+    let path = checkpoint_path("lending");
+
+    let with_attacker = |config: &ScenarioConfig| {
+        let mut s = build(ScenarioId::SteadyState, config);
+        s.attackers.push(Attacker::new_borrowed(AttackerConfig {
+            attack_capital_zec: 2000.0,
+            hold_blocks: 3,
+            attack_at_block: 400,
+            ..AttackerConfig::default()
+        }));
+        s
+    };
+
+    let mut uninterrupted = with_attacker(&config);
+    uninterrupted.run(&prices);
+
+    let mut interrupted = with_attacker(&ScenarioConfig {
+        checkpoint_interval: 200,
+        checkpoint_path: Some(path.clone()),
+        ..config.clone()
+    });
+    interrupted.run(&prices[..400]);
+
+    let mut resumed = Scenario::restore(&path).unwrap();
+    resumed.run(&prices);
+    assert_same_trajectory(&uninterrupted, &resumed);
+}
+
+#[test]
+fn test_checkpoint_written_at_last_interval() {
+    let path = checkpoint_path("interval");
+    let config = ScenarioConfig {
+        checkpoint_interval: 150,
+        checkpoint_path: Some(path.clone()),
+        ..ScenarioConfig::default()
+    };
+    let prices = generate_prices(ScenarioId::SteadyState, 500, SEED);
+    let mut scenario = build(ScenarioId::SteadyState, &config);
+    scenario.run(&prices);
+
+    let restored = Scenario::restore(&path).unwrap();
+    assert_eq!(restored.last_block(), 450);
+    assert_eq!(restored.metrics.len(), 450);
+    assert_eq!(restored.lp_agents.len(), 1);
+    assert!(!path.with_extension("tmp").exists());
+}
+
+#[test]
+fn test_restore_missing_file_errors() {
+    assert!(matches!(
+        Scenario::restore(&checkpoint_path("does_not_exist")),
+        Err(ZaiSimError::Io(_))
+    ));
+}
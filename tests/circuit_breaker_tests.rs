@@ -378,11 +378,11 @@ fn test_scenario_runner_basic() {
 
     assert_eq!(scenario.metrics.len(), 100);
 
-    let first = &scenario.metrics[0];
+    let first = scenario.metrics.row(0);
     assert_relative_eq!(first.external_price, 50.0);
     assert!(first.amm_spot_price > 0.0);
 
-    let last = &scenario.metrics[99];
+    let last = scenario.metrics.row(99);
     // With constant external price and arbers, AMM should stay near $50
     assert!(
         (last.amm_spot_price - 50.0).abs() < 10.0,
@@ -414,7 +414,7 @@ fn test_scenario_with_price_crash() {
     assert_eq!(scenario.metrics.len(), 100);
 
     // After crash, AMM price should have moved toward 30
-    let end_price = scenario.metrics[99].amm_spot_price;
+    let end_price = scenario.metrics.amm_spot_price[99];
     assert!(
         end_price < 50.0,
         "AMM price should drop after external crash: {}",
@@ -0,0 +1,123 @@
+//! Machine-readable stress output and CI exit codes.
+//!
+//! `stress --format json` prints verdicts and summaries as JSON on stdout, and
+//! the process exits non-zero when any scenario reaches the `--fail-on` level.
+#![cfg(all(feature = "net", feature = "sqlite"))]
+
+use std::process::Command;
+
+use zai_sim::output::{compute_summary, stress_results_json};
+use zai_sim::report::{evaluate_pass_fail, FailOn, Verdict};
+use zai_sim::scenario::ScenarioConfig;
+use zai_sim::scenarios::{run_stress, ScenarioId};
+
+fn stress(args: &[&str], dir: &str) -> std::process::Output {
+    let out_dir = std::env::temp_dir().join(format!("zai_sim_cli_{}", dir));
+    Command::new(env!("CARGO_BIN_EXE_zai-sim"))
+        .arg("stress")
+        .args(args)
+        .arg("--output-dir")
+        .arg(&out_dir)
+        .output()
+        .expect("failed to run zai-sim")
+}
+
+#[test]
+fn test_fail_on_levels() {
+    assert!(FailOn::HardFail.is_failure(&Verdict::HardFail));
+    assert!(!FailOn::HardFail.is_failure(&Verdict::SoftFail));
+    assert!(FailOn::SoftFail.is_failure(&Verdict::SoftFail));
+    assert!(!FailOn::SoftFail.is_failure(&Verdict::Pass));
+    assert!(!FailOn::Never.is_failure(&Verdict::HardFail));
+
+    assert_eq!("hard".parse::<FailOn>().unwrap(), FailOn::HardFail);
+    assert_eq!("soft".parse::<FailOn>().unwrap(), FailOn::SoftFail);
+    assert_eq!("never".parse::<FailOn>().unwrap(), FailOn::Never);
+    assert!("sometimes".parse::<FailOn>().is_err());
+}
+
+#[test]
+fn test_stress_results_json_shape() {
+    let config = ScenarioConfig::default();
+    let target = config.initial_redemption_price;
+    let entries: Vec<_> = [ScenarioId::SteadyState, ScenarioId::BlackThursday]
+        .into_iter()
+        .map(|sid| {
+            let s = run_stress(sid, &config, 300, 42);
+            (
+                sid,
+                evaluate_pass_fail(&s.metrics, target),
+                compute_summary(&s.metrics, target),
+            )
+        })
+        .collect();
+
+    let json: serde_json::Value =
+        serde_json::from_str(&stress_results_json(&entries).unwrap()).unwrap();
+    let worst = entries
+        .iter()
+        .fold(Verdict::Pass, |acc, (_, v, _)| acc.worst(v.overall.clone()));
+
+    assert_eq!(json["overall"], serde_json::to_value(&worst).unwrap());
+    let scenarios = json["scenarios"].as_array().unwrap();
+    assert_eq!(scenarios.len(), 2);
+    assert_eq!(scenarios[0]["scenario"], "steady_state");
+    assert_eq!(scenarios[0]["summary"]["total_blocks"], 300);
+    assert!(!scenarios[0]["verdict"]["criteria"].as_array().unwrap().is_empty());
+    assert!(!scenarios[1]["expectations"].as_array().unwrap().is_empty());
+}
+
+#[test]
+fn test_cli_json_on_stdout_and_zero_exit() {
+    let out = stress(&["--id", "1", "--blocks", "300", "--format", "json"], "json");
+    assert!(out.status.success(), "steady_state should not HARD FAIL");
+
+    let json: serde_json::Value = serde_json::from_slice(&out.stdout)
+        .expect("stdout must be pure JSON in json mode");
+    assert_eq!(json["scenarios"][0]["scenario"], "steady_state");
+    assert!(!out.stderr.is_empty(), "progress goes to stderr");
+}
+
+#[test]
+fn test_cli_exit_codes() {
+    // Black Thursday is a SOFT FAIL at the reference config
+    let soft = stress(&["--id", "2", "--fail-on", "soft"], "soft");
+    assert_eq!(soft.status.code(), Some(1));
+
+    let hard = stress(&["--id", "2", "--fail-on", "hard"], "hard");
+    assert_eq!(hard.status.code(), Some(0));
+
+    let invalid = stress(&["--id", "99"], "invalid");
+    assert_eq!(invalid.status.code(), Some(2));
+}
+
+#[test]
+fn test_cli_scenario_subset() {
+    let out = stress(
+        &[
+            "--id",
+            "1,3-4",
+            "--blocks",
+            "200",
+            "--format",
+            "json",
+            "--fail-on",
+            "never",
+        ],
+        "subset",
+    );
+    assert!(out.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    let names: Vec<_> = json["scenarios"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| s["scenario"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["steady_state", "flash_crash", "sustained_bear"]);
+    let index = std::env::temp_dir().join("zai_sim_cli_subset/index.html");
+    assert!(index.exists(), "subsets share one master summary");
+
+    let backwards = stress(&["--id", "5-2"], "backwards");
+    assert_eq!(backwards.status.code(), Some(2));
+}
@@ -0,0 +1,151 @@
+//! Close-factor partial liquidation.
+//!
+//! With a close factor, a liquidation repays at most that fraction of a
+//! vault's debt, sells the collateral covering it plus the penalty, and
+//! leaves the vault open. Unlike graduated liquidation, the amount is set
+//! by the debt rather than a share of collateral per block.
+
+use approx::assert_relative_eq;
+use zai_sim::agents::{CdpHolder, CdpHolderConfig};
+use zai_sim::amm::Amm;
+use zai_sim::cdp::{CdpConfig, VaultRegistry};
+use zai_sim::liquidation::{LiquidationConfig, LiquidationEngine, LiquidationMode};
+use zai_sim::scenario::{Scenario, ScenarioConfig};
+use zai_sim::scenarios::{add_agents, generate_prices, ScenarioId};
+
+fn setup(
+    close_factor: Option<f64>,
+    debt: f64,
+    collateral: f64,
+) -> (Amm, VaultRegistry, LiquidationEngine, u64) {
+    let mut amm = Amm::new(10000.0, 500000.0, 0.003);
+    for b in 1..=50 {
+        amm.record_price(b);
+    }
+    let mut registry = VaultRegistry::new(CdpConfig {
+        stability_fee_rate: 0.0,
+        ..CdpConfig::default()
+    });
+    let engine = LiquidationEngine::new(LiquidationConfig {
+        close_factor,
+        ..LiquidationConfig::default()
+    });
+    // Opened at CR 2.0, then knocked down to `collateral`
+    let id = registry
+        .open_vault("owner", debt * 2.0 / 50.0, debt, 50, &amm)
+        .unwrap();
+    registry.vaults.get_mut(&id).unwrap().collateral_zec = collateral;
+    registry.reindex(id);
+    (amm, registry, engine, id)
+}
+
+#[test]
+fn test_repays_close_factor_and_leaves_vault_open() {
+    // CR 1.4 against a 1.5 minimum
+    let (mut amm, mut registry, mut engine, id) = setup(Some(0.5), 1000.0, 28.0);
+    let results = engine.transparent_liquidate(&mut registry, &mut amm, 51);
+    assert_eq!(results.len(), 1);
+    let r = &results[0];
+    assert_eq!(r.mode, LiquidationMode::CloseFactor);
+    assert_relative_eq!(r.debt_to_cover, 500.0, epsilon = 1e-6);
+    assert_relative_eq!(r.penalty_amount, 500.0 * 0.13, epsilon = 1e-6);
+    assert_eq!(r.bad_debt, 0.0);
+
+    // Collateral worth the repayment plus penalty left; the rest stays
+    let vault = &registry.vaults[&id];
+    assert_relative_eq!(vault.debt_zai, 500.0, epsilon = 1e-6);
+    assert_relative_eq!(vault.collateral_zec, 28.0 - r.collateral_seized);
+    assert_relative_eq!(registry.total_debt, 500.0, epsilon = 1e-6);
+    assert!(r.collateral_seized > 565.0 / 50.0 && r.collateral_seized < 12.0);
+
+    // Back above the minimum, so the next scan leaves it alone
+    assert!(!registry.is_liquidatable(id, &amm));
+
+    // Without a close factor the whole vault goes
+    let (mut amm, mut registry, mut engine, id) = setup(None, 1000.0, 28.0);
+    let results = engine.transparent_liquidate(&mut registry, &mut amm, 51);
+    assert_eq!(results[0].mode, LiquidationMode::Transparent);
+    assert_eq!(results[0].collateral_seized, 28.0);
+    assert!(!registry.vaults.contains_key(&id));
+}
+
+#[test]
+fn test_dust_below_floor_is_repaid() {
+    // Half of 150 would leave 75 ZAI, under the 100 ZAI floor
+    let (mut amm, mut registry, mut engine, id) = setup(Some(0.5), 150.0, 4.2);
+    let results = engine.transparent_liquidate(&mut registry, &mut amm, 51);
+    assert_relative_eq!(results[0].debt_to_cover, 150.0, epsilon = 1e-6);
+    assert!(results[0].collateral_seized < 4.2);
+    assert!(!registry.vaults.contains_key(&id));
+    assert_relative_eq!(registry.total_debt, 0.0, epsilon = 1e-9);
+}
+
+#[test]
+fn test_insolvent_vault_is_closed() {
+    // CR 0.5: not even half the debt can be covered
+    let (mut amm, mut registry, mut engine, id) = setup(Some(0.5), 1000.0, 10.0);
+    let results = engine.transparent_liquidate(&mut registry, &mut amm, 51);
+    let r = &results[0];
+    assert_eq!(r.collateral_seized, 10.0);
+    assert!(r.bad_debt > 490.0, "{}", r.bad_debt);
+    assert_relative_eq!(r.debt_to_cover + r.bad_debt, 1000.0, epsilon = 1e-9);
+    assert!(!registry.vaults.contains_key(&id));
+    assert_relative_eq!(engine.total_bad_debt, r.bad_debt);
+}
+
+fn run(close_factor: Option<f64>) -> Scenario {
+    let config = ScenarioConfig {
+        liquidation_config: LiquidationConfig {
+            close_factor,
+            ..LiquidationConfig::default()
+        },
+        ..ScenarioConfig::default()
+    };
+    let mut scenario = Scenario::new_with_seed(&config, 42);
+    add_agents(ScenarioId::BlackThursday, &mut scenario);
+    for _ in 0..5 {
+        scenario.cdp_holders.push(CdpHolder::new(CdpHolderConfig {
+            target_ratio: 1.6,
+            action_threshold_ratio: 1.2,
+            reserve_zec: 0.0,
+            initial_collateral: 40.0,
+            initial_debt: 1250.0,
+        }));
+    }
+    scenario.run(&generate_prices(ScenarioId::BlackThursday, 1000, 42));
+    scenario
+}
+
+#[test]
+fn test_black_thursday_with_close_factor() {
+    let partial = run(Some(0.5));
+    let full = run(None);
+    let history = &partial.liquidation_engine.history;
+    assert!(
+        !history.is_empty(),
+        "Black Thursday should liquidate someone"
+    );
+    assert!(history
+        .iter()
+        .all(|r| r.mode == LiquidationMode::CloseFactor));
+    assert!(full
+        .liquidation_engine
+        .history
+        .iter()
+        .all(|r| r.mode != LiquidationMode::CloseFactor));
+
+    let seized = |s: &Scenario| -> f64 {
+        s.liquidation_engine
+            .history
+            .iter()
+            .map(|r| r.collateral_seized)
+            .sum()
+    };
+    println!(
+        "\nCollateral seized: full {:.2} ZEC in {} liquidations, close factor {:.2} ZEC in {}",
+        seized(&full),
+        full.liquidation_engine.history.len(),
+        seized(&partial),
+        history.len()
+    );
+}
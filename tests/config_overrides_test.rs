@@ -0,0 +1,50 @@
+//! Config overrides for the Python and WASM bindings.
+//!
+//! Bindings pass configs as objects holding only the fields they change;
+//! these are merged over the defaults, nested configs field by field.
+
+use serde_json::json;
+use zai_sim::error::ZaiSimError;
+use zai_sim::scenario::ScenarioConfig;
+use zai_sim::scenarios::ScenarioId;
+
+#[test]
+fn test_overrides_merge_over_defaults() {
+    let defaults = ScenarioConfig::default();
+    let config = ScenarioConfig::with_overrides(json!({
+        "amm_swap_fee": 0.01,
+        "cdp_config": { "min_ratio": 2.0 },
+    }))
+    .unwrap();
+    assert_eq!(config.amm_swap_fee, 0.01);
+    assert_eq!(config.cdp_config.min_ratio, 2.0);
+    // Siblings of an overridden nested field keep their defaults
+    assert_eq!(
+        config.cdp_config.liquidation_penalty,
+        defaults.cdp_config.liquidation_penalty
+    );
+    assert_eq!(
+        config.initial_redemption_price,
+        defaults.initial_redemption_price
+    );
+
+    let config = ScenarioConfig::with_overrides(serde_json::Value::Null).unwrap();
+    assert_eq!(config.amm_swap_fee, defaults.amm_swap_fee);
+}
+
+#[test]
+fn test_bad_overrides_rejected() {
+    let err = ScenarioConfig::with_overrides(json!([1, 2])).unwrap_err();
+    assert!(matches!(err, ZaiSimError::Config(_)), "{}", err);
+
+    let err = ScenarioConfig::with_overrides(json!({ "amm_swap_fee": "high" })).unwrap_err();
+    assert!(matches!(err, ZaiSimError::Json(_)), "{}", err);
+}
+
+#[test]
+fn test_scenarios_by_name() {
+    for id in ScenarioId::all() {
+        assert_eq!(ScenarioId::from_name(id.name()), Some(id));
+    }
+    assert_eq!(ScenarioId::from_name("black_monday"), None);
+}
@@ -18,9 +18,11 @@ const BLOCKS: usize = 1000;
 const SEED: u64 = 42;
 
 fn config_with_cr(min_ratio: f64) -> ScenarioConfig {
-    let mut c = ScenarioConfig::default();
-    c.amm_initial_zec = 100_000.0;
-    c.amm_initial_zai = 5_000_000.0;
+    let mut c = ScenarioConfig {
+        amm_initial_zec: 100_000.0,
+        amm_initial_zai: 5_000_000.0,
+        ..ScenarioConfig::default()
+    };
     c.cdp_config.min_ratio = min_ratio;
     c.cdp_config.twap_window = 240;
     c.controller_config = ControllerConfig::default_tick();
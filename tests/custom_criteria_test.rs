@@ -0,0 +1,124 @@
+//! User-defined pass/fail criteria.
+//!
+//! Embedders register their own checks, as `Criterion` impls or closures,
+//! and `evaluate_with_criteria` reports them alongside the built-in ones,
+//! failing the run at each criterion's severity.
+
+use zai_sim::report::*;
+use zai_sim::scenario::{MetricsStore, ScenarioConfig};
+use zai_sim::scenarios::{run_stress, ScenarioId};
+
+/// Fails if the system stays halted for more than `max_blocks` in a row.
+struct MaxHaltDuration {
+    max_blocks: u64,
+}
+
+impl Criterion for MaxHaltDuration {
+    fn name(&self) -> &str {
+        "No halt longer than 2h"
+    }
+
+    fn severity(&self) -> Verdict {
+        Verdict::HardFail
+    }
+
+    fn evaluate(&self, metrics: &MetricsStore) -> CriterionResult {
+        let mut run = 0;
+        let mut longest = 0;
+        for &halted in &metrics.halted {
+            run = if halted { run + 1 } else { 0 };
+            longest = longest.max(run);
+        }
+        CriterionResult {
+            name: self.name().to_string(),
+            passed: longest <= self.max_blocks,
+            severity: self.severity(),
+            details: format!("Longest halt: {} blocks", longest),
+        }
+    }
+}
+
+fn lp_net_pnl() -> Box<dyn Criterion> {
+    Box::new(FnCriterion::new(
+        "LP net PnL >= 0",
+        Verdict::SoftFail,
+        |metrics: &MetricsStore| {
+            let net = metrics.lp_net_return_zai.last().copied().unwrap_or(0.0);
+            (net >= 0.0, format!("LP net return: {:.2} ZAI", net))
+        },
+    ))
+}
+
+fn fixed(name: &str, severity: Verdict, passed: bool) -> Box<dyn Criterion> {
+    Box::new(FnCriterion::new(name, severity, move |_: &MetricsStore| {
+        (passed, String::new())
+    }))
+}
+
+fn steady_state() -> MetricsStore {
+    run_stress(ScenarioId::SteadyState, &ScenarioConfig::default(), 200, 42).metrics
+}
+
+#[test]
+fn test_custom_criteria_appended() {
+    let metrics = steady_state();
+    let base = evaluate_pass_fail(&metrics, 50.0);
+    assert_eq!(base.overall, Verdict::Pass);
+
+    let custom: Vec<Box<dyn Criterion>> =
+        vec![lp_net_pnl(), Box::new(MaxHaltDuration { max_blocks: 96 })];
+    let result = evaluate_with_criteria(&metrics, 50.0, &custom);
+    assert_eq!(result.criteria.len(), base.criteria.len() + 2);
+    let halt = result.criteria.last().unwrap();
+    assert_eq!(halt.name, "No halt longer than 2h");
+    assert!(halt.passed);
+    assert!(halt.details.starts_with("Longest halt: "));
+    assert_eq!(result.criteria[base.criteria.len()].name, "LP net PnL >= 0");
+    assert_eq!(
+        evaluate_with_criteria(&metrics, 50.0, &[]).criteria.len(),
+        base.criteria.len()
+    );
+}
+
+#[test]
+fn test_failing_criterion_sets_verdict() {
+    let metrics = steady_state();
+    let verdict =
+        |custom: Vec<Box<dyn Criterion>>| evaluate_with_criteria(&metrics, 50.0, &custom).overall;
+
+    assert_eq!(
+        verdict(vec![fixed("ok", Verdict::HardFail, true)]),
+        Verdict::Pass
+    );
+    assert_eq!(
+        verdict(vec![fixed("soft", Verdict::SoftFail, false)]),
+        Verdict::SoftFail
+    );
+    // The worst failure wins, whatever the order
+    assert_eq!(
+        verdict(vec![
+            fixed("hard", Verdict::HardFail, false),
+            fixed("soft", Verdict::SoftFail, false),
+        ]),
+        Verdict::HardFail
+    );
+}
+
+#[test]
+fn test_halt_duration_criterion() {
+    let mut metrics = steady_state();
+    for (i, halted) in metrics.halted.iter_mut().enumerate() {
+        *halted = (50..150).contains(&i);
+    }
+    let custom: Vec<Box<dyn Criterion>> = vec![Box::new(MaxHaltDuration { max_blocks: 96 })];
+    let result = evaluate_with_criteria(&metrics, 50.0, &custom);
+    let halt = result.criteria.last().unwrap();
+    assert!(!halt.passed);
+    assert_eq!(halt.details, "Longest halt: 100 blocks");
+    assert_eq!(result.overall, Verdict::HardFail);
+
+    // Two shorter halts stay within the limit
+    metrics.halted[100] = false;
+    let result = evaluate_with_criteria(&metrics, 50.0, &custom);
+    assert!(result.criteria.last().unwrap().passed);
+}
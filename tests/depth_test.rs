@@ -0,0 +1,126 @@
+//! Order-book depth curves and the impact fit.
+//!
+//! Snapshots reduce to the notional resting within each distance of the mid;
+//! curves round-trip through the depth CSV, and `calibrate_depth` recovers
+//! the AMM reserve and external market depth that produced a curve.
+
+use zai_sim::calibration::calibrate_depth;
+use zai_sim::depth::{append_curves, load_curves, DepthCurve, DepthPoint, DepthSnapshot};
+use zai_sim::scenario::ScenarioConfig;
+
+fn snapshot() -> DepthSnapshot {
+    DepthSnapshot {
+        timestamp_ms: 1_000,
+        bids: vec![(99.95, 10.0), (99.5, 20.0), (98.0, 30.0)],
+        asks: vec![(100.05, 10.0), (100.6, 20.0), (103.0, 30.0)],
+    }
+}
+
+/// Depth curve of a constant-product pool with ZAI reserve `y`.
+fn amm_curve(y: f64, mid: f64, timestamp_ms: u64) -> DepthCurve {
+    DepthCurve {
+        timestamp_ms,
+        mid,
+        points: [10.0, 50.0, 100.0, 500.0, 1000.0]
+            .iter()
+            .map(|&bps: &f64| {
+                let m = bps / 10_000.0;
+                DepthPoint {
+                    bps,
+                    bid_notional: y * (1.0 - (1.0 - m).sqrt()),
+                    ask_notional: y * ((1.0 + m).sqrt() - 1.0),
+                }
+            })
+            .collect(),
+    }
+}
+
+#[test]
+fn test_curve_sums_notional_within_levels() {
+    let curve = snapshot().curve(&[10.0, 100.0, 500.0]).unwrap();
+    assert!((curve.mid - 100.0).abs() < 1e-9);
+    assert_eq!(curve.points.len(), 3);
+
+    let near = curve.points[0];
+    assert!((near.bid_notional - 999.5).abs() < 1e-9);
+    assert!((near.ask_notional - 1000.5).abs() < 1e-9);
+
+    let one_pct = curve.points[1];
+    assert!((one_pct.bid_notional - (999.5 + 1990.0)).abs() < 1e-9);
+    assert!((one_pct.ask_notional - (1000.5 + 2012.0)).abs() < 1e-9);
+
+    let five_pct = curve.points[2];
+    assert!((five_pct.bid_notional - (999.5 + 1990.0 + 2940.0)).abs() < 1e-9);
+    assert!((five_pct.ask_notional - (1000.5 + 2012.0 + 3090.0)).abs() < 1e-9);
+}
+
+#[test]
+fn test_one_sided_book_has_no_curve() {
+    let mut book = snapshot();
+    book.asks.clear();
+    assert!(book.mid().is_none());
+    assert!(book.curve(&[100.0]).is_none());
+}
+
+#[test]
+fn test_curves_round_trip_through_csv() {
+    let path = std::env::temp_dir().join("zai_sim_depth_curves.csv");
+    let _ = std::fs::remove_file(&path);
+
+    let first = amm_curve(1_000_000.0, 50.0, 1_000);
+    let second = amm_curve(2_000_000.0, 51.0, 61_000);
+    append_curves(std::slice::from_ref(&first), &path).unwrap();
+    append_curves(std::slice::from_ref(&second), &path).unwrap();
+
+    let loaded = load_curves(&path).unwrap();
+    assert_eq!(loaded, vec![first, second]);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_calibrate_depth_recovers_amm_reserve() {
+    let curves = vec![
+        amm_curve(1_000_000.0, 49.0, 0),
+        amm_curve(1_000_000.0, 50.0, 60_000),
+        amm_curve(1_000_000.0, 51.0, 120_000),
+    ];
+    let fit = calibrate_depth(&curves).unwrap();
+    assert_eq!(fit.snapshots, 3);
+    assert_eq!(fit.mid, 50.0);
+    assert!((fit.amm_zai_reserve - 1_000_000.0).abs() < 1e-6);
+    assert!((fit.amm_zec_reserve - 20_000.0).abs() < 1e-6);
+    // Near the mid a pool with reserve Y has about Y/200 per 1%
+    assert!(fit.depth_zai > 4_000.0 && fit.depth_zai < 6_000.0);
+}
+
+#[test]
+fn test_calibrate_depth_recovers_linear_depth() {
+    let curve = DepthCurve {
+        timestamp_ms: 0,
+        mid: 50.0,
+        points: [25.0, 100.0, 300.0]
+            .iter()
+            .map(|&bps| DepthPoint {
+                bps,
+                bid_notional: 80_000.0 * bps / 100.0,
+                ask_notional: 80_000.0 * bps / 100.0,
+            })
+            .collect(),
+    };
+    let fit = calibrate_depth(&[curve]).unwrap();
+    assert!((fit.depth_zai - 80_000.0).abs() < 1e-6);
+
+    let mut config = ScenarioConfig::default();
+    let price = config.initial_amm_price();
+    fit.apply(&mut config);
+    assert_eq!(config.amm_initial_depth, Some(fit.amm_zai_reserve));
+    assert_eq!(config.initial_amm_price(), price);
+    let feedback = config.price_feedback.unwrap();
+    assert!((feedback.market.depth_zai - 80_000.0).abs() < 1e-6);
+    assert!(feedback.market.recovery_half_life_blocks.is_some());
+}
+
+#[test]
+fn test_calibrate_depth_needs_snapshots() {
+    assert!(calibrate_depth(&[]).is_err());
+}
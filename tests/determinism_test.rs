@@ -0,0 +1,92 @@
+//! Run-to-run determinism verification.
+//!
+//! The same config and seed must produce bit-identical per-block metrics,
+//! including when runs execute concurrently in rayon pools.
+
+use zai_sim::determinism::{first_metrics_divergence, verify_determinism};
+use zai_sim::scenario::ScenarioConfig;
+use zai_sim::scenarios::{run_stress, ScenarioId};
+
+const BLOCKS: usize = 300;
+
+#[test]
+fn test_repeat_run_is_deterministic() {
+    let report = verify_determinism(
+        ScenarioId::BlackThursday,
+        &ScenarioConfig::default(),
+        BLOCKS,
+        42,
+        &[],
+    )
+    .unwrap();
+    assert!(report.is_deterministic(), "{:?}", report.divergence);
+    assert_eq!(report.runs_compared, 1);
+}
+
+#[test]
+fn test_deterministic_across_thread_counts() {
+    let report = verify_determinism(
+        ScenarioId::FlashCrash,
+        &ScenarioConfig::default(),
+        BLOCKS,
+        7,
+        &[1, 2, 4],
+    )
+    .unwrap();
+    assert!(report.is_deterministic(), "{:?}", report.divergence);
+    assert_eq!(report.runs_compared, 7);
+}
+
+#[test]
+fn test_reports_first_divergent_block_and_field() {
+    let config = ScenarioConfig::default();
+    let reference = run_stress(ScenarioId::SustainedBear, &config, BLOCKS, 42).metrics;
+    let mut altered = reference.clone();
+    altered.total_debt[120] = f64::from_bits(altered.total_debt[120].to_bits() ^ 1);
+    altered.amm_spot_price[200] += 1.0;
+
+    let diffs = first_metrics_divergence(&reference, &altered).expect("divergence");
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].block, reference.block[120]);
+    assert_eq!(diffs[0].field, "total_debt");
+    assert_ne!(diffs[0].expected, diffs[0].actual);
+}
+
+#[test]
+fn test_different_seeds_diverge() {
+    let config = ScenarioConfig {
+        stochastic: true,
+        ..ScenarioConfig::default()
+    };
+    let a = run_stress(ScenarioId::BlackThursday, &config, BLOCKS, 1).metrics;
+    let b = run_stress(ScenarioId::BlackThursday, &config, BLOCKS, 2).metrics;
+    assert!(first_metrics_divergence(&a, &b).is_some());
+}
+
+#[test]
+fn test_truncated_run_reports_missing_block() {
+    let reference = run_stress(
+        ScenarioId::FlashCrash,
+        &ScenarioConfig::default(),
+        BLOCKS,
+        42,
+    )
+    .metrics;
+    let mut truncated = reference.clone();
+    truncated.truncate(reference.len() - 10);
+    let diffs = first_metrics_divergence(&reference, &truncated).unwrap();
+    assert_eq!(diffs[0].field, "block_missing");
+    assert_eq!(diffs[0].block, reference.block[reference.len() - 10]);
+}
+
+#[test]
+fn test_zero_threads_rejected() {
+    assert!(verify_determinism(
+        ScenarioId::FlashCrash,
+        &ScenarioConfig::default(),
+        50,
+        42,
+        &[0]
+    )
+    .is_err());
+}
@@ -18,9 +18,11 @@ use std::time::Instant;
 const SEED: u64 = 42;
 
 fn config_5m() -> ScenarioConfig {
-    let mut config = ScenarioConfig::default();
-    config.amm_initial_zec = 100_000.0;
-    config.amm_initial_zai = 5_000_000.0;
+    let mut config = ScenarioConfig {
+        amm_initial_zec: 100_000.0,
+        amm_initial_zai: 5_000_000.0,
+        ..ScenarioConfig::default()
+    };
     config.cdp_config.min_ratio = 2.0;
     config.cdp_config.twap_window = 240;
     config.controller_config = ControllerConfig::default_tick();
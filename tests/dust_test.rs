@@ -0,0 +1,213 @@
+//! Dust and debt-floor enforcement.
+//!
+//! No flow may leave a vault owing less than the debt floor: opens, borrows
+//! and repayments below it are rejected, redemptions and holder repayments
+//! stop at it, and a partial liquidation that would leave dust either takes
+//! the whole debt or clears the leftover out of the penalty, writing off
+//! what the penalty doesn't cover.
+
+use approx::assert_relative_eq;
+use zai_sim::agents::{CdpHolder, CdpHolderConfig};
+use zai_sim::amm::Amm;
+use zai_sim::cdp::{CdpConfig, DustPolicy, VaultRegistry};
+use zai_sim::error::ZaiSimError;
+use zai_sim::liquidation::{LiquidationConfig, LiquidationEngine};
+use zai_sim::scenario::{Scenario, ScenarioConfig};
+
+fn amm() -> Amm {
+    let mut amm = Amm::new(10_000.0, 500_000.0, 0.003);
+    for b in 1..=50 {
+        amm.record_price(b);
+    }
+    amm
+}
+
+fn registry(min_ratio: f64) -> VaultRegistry {
+    VaultRegistry::new(CdpConfig {
+        min_ratio,
+        stability_fee_rate: 0.0,
+        ..CdpConfig::default()
+    })
+}
+
+/// Vault owing `debt`, knocked down to collateral ratio `ratio` at $50.
+fn vault_at_ratio(registry: &mut VaultRegistry, amm: &Amm, debt: f64, ratio: f64) -> u64 {
+    let id = registry
+        .open_vault("owner", debt * 3.0 / 50.0, debt, 50, amm)
+        .unwrap();
+    registry.vaults.get_mut(&id).unwrap().collateral_zec = debt * ratio / 50.0;
+    registry.reindex(id);
+    id
+}
+
+#[test]
+fn test_floor_checks() {
+    let config = CdpConfig::default();
+    assert!(config.is_dust(50.0));
+    assert!(!config.is_dust(0.0));
+    assert!(!config.is_dust(100.0));
+    assert!(config.check_debt_floor(0.0).is_ok());
+    assert!(matches!(
+        config.check_debt_floor(99.0),
+        Err(ZaiSimError::BelowDebtFloor { .. })
+    ));
+
+    // Repayments stop at the floor rather than leave dust
+    assert_eq!(config.max_repayment(150.0, 30.0), 30.0);
+    assert_eq!(config.max_repayment(150.0, 80.0), 50.0);
+    assert_eq!(config.max_repayment(150.0, 500.0), 150.0);
+    assert_eq!(config.max_repayment(80.0, 40.0), 0.0);
+
+    // Liquidations take the whole debt or leave the dust to be cleared
+    assert_eq!(
+        config.liquidation_repayment(150.0, 75.0, DustPolicy::Wipe),
+        150.0
+    );
+    assert_eq!(
+        config.liquidation_repayment(150.0, 75.0, DustPolicy::RollIntoPenalty),
+        75.0
+    );
+    assert_eq!(
+        config.liquidation_repayment(500.0, 250.0, DustPolicy::Wipe),
+        250.0
+    );
+}
+
+#[test]
+fn test_borrow_below_floor_is_rejected() {
+    let amm = amm();
+    let mut registry = registry(1.5);
+    assert!(matches!(
+        registry.open_vault("a", 10.0, 50.0, 50, &amm),
+        Err(ZaiSimError::BelowDebtFloor { .. })
+    ));
+
+    let id = registry.open_vault("a", 10.0, 0.0, 50, &amm).unwrap();
+    assert!(matches!(
+        registry.borrow_zai(id, 60.0, 51, &amm),
+        Err(ZaiSimError::BelowDebtFloor { .. })
+    ));
+    registry.borrow_zai(id, 150.0, 51, &amm).unwrap();
+
+    assert!(matches!(
+        registry.repay_zai(id, 80.0, 52),
+        Err(ZaiSimError::BelowDebtFloor { .. })
+    ));
+    registry.repay_zai(id, 50.0, 52).unwrap();
+    registry.repay_zai(id, 100.0, 53).unwrap();
+    assert_eq!(registry.get_vault(id).unwrap().debt_zai, 0.0);
+}
+
+fn close_factor(dust_policy: DustPolicy) -> (LiquidationEngine, VaultRegistry, Amm, u64) {
+    let amm = amm();
+    let mut registry = registry(1.5);
+    // Half of 150 would leave 75 ZAI, under the 100 ZAI floor
+    let id = vault_at_ratio(&mut registry, &amm, 150.0, 1.4);
+    let engine = LiquidationEngine::new(LiquidationConfig {
+        close_factor: Some(0.5),
+        dust_policy,
+        ..LiquidationConfig::default()
+    });
+    (engine, registry, amm, id)
+}
+
+#[test]
+fn test_close_factor_dust_rolls_into_penalty() {
+    let (mut engine, mut registry, mut amm, id) = close_factor(DustPolicy::RollIntoPenalty);
+    let r = &engine.transparent_liquidate(&mut registry, &mut amm, 51)[0];
+
+    // The 9.75 ZAI penalty on the 75 repaid goes to the dust; the rest of
+    // it is written off
+    let penalty = 75.0 * 0.13;
+    assert_relative_eq!(r.debt_to_cover, 75.0 + penalty, epsilon = 1e-6);
+    assert_relative_eq!(r.penalty_amount, 0.0, epsilon = 1e-6);
+    assert_relative_eq!(r.bad_debt, 75.0 - penalty, epsilon = 1e-6);
+    assert_relative_eq!(engine.total_bad_debt, r.bad_debt);
+    // The closed vault's unsold collateral is the owner's
+    assert_relative_eq!(
+        r.collateral_returned,
+        150.0 * 1.4 / 50.0 - r.collateral_seized,
+        epsilon = 1e-12
+    );
+    assert!(!registry.vaults.contains_key(&id));
+    assert_relative_eq!(registry.total_debt, 0.0, epsilon = 1e-9);
+
+    // Wiping instead repays all of it from the collateral
+    let (mut engine, mut registry, mut amm, id) = close_factor(DustPolicy::Wipe);
+    let r = &engine.transparent_liquidate(&mut registry, &mut amm, 51)[0];
+    assert_relative_eq!(r.debt_to_cover, 150.0, epsilon = 1e-6);
+    assert_eq!(r.bad_debt, 0.0);
+    assert!(!registry.vaults.contains_key(&id));
+}
+
+fn graduated(dust_policy: DustPolicy) -> (LiquidationEngine, VaultRegistry, Amm, u64) {
+    let amm = amm();
+    let mut registry = registry(2.0);
+    // A 10% slice repays about 15.6 of 110 ZAI, leaving dust
+    let id = vault_at_ratio(&mut registry, &amm, 110.0, 1.6);
+    let engine = LiquidationEngine::new(LiquidationConfig {
+        graduated_liquidation: true,
+        graduated_pct_per_block: 0.10,
+        graduated_cr_floor: 1.5,
+        dust_policy,
+        ..LiquidationConfig::default()
+    });
+    (engine, registry, amm, id)
+}
+
+#[test]
+fn test_graduated_slice_never_leaks_debt() {
+    // Wiping sells the whole vault and settles it, returning the excess
+    let (mut engine, mut registry, mut amm, id) = graduated(DustPolicy::Wipe);
+    let r = &engine.graduated_liquidate(&mut registry, &mut amm, 51)[0];
+    assert_relative_eq!(r.collateral_seized, 110.0 * 1.6 / 50.0);
+    assert_relative_eq!(r.debt_to_cover, 110.0, epsilon = 1e-9);
+    assert_relative_eq!(r.penalty_amount, 110.0 * 0.13, epsilon = 1e-9);
+    assert!(r.surplus_to_owner > 0.0);
+    assert_eq!(r.collateral_returned, 0.0);
+    assert_eq!(r.bad_debt, 0.0);
+    assert!(!registry.vaults.contains_key(&id));
+    assert_relative_eq!(registry.total_debt, 0.0, epsilon = 1e-9);
+
+    // Rolling takes the slice and clears the dust from its penalty
+    let (mut engine, mut registry, mut amm, id) = graduated(DustPolicy::RollIntoPenalty);
+    let r = &engine.graduated_liquidate(&mut registry, &mut amm, 51)[0];
+    assert_relative_eq!(
+        r.collateral_seized,
+        0.1 * 110.0 * 1.6 / 50.0,
+        epsilon = 1e-12
+    );
+    assert_eq!(r.penalty_amount, 0.0);
+    assert!(r.bad_debt > 80.0, "{}", r.bad_debt);
+    assert_relative_eq!(r.debt_to_cover + r.bad_debt, 110.0, epsilon = 1e-9);
+    assert_relative_eq!(
+        r.collateral_returned,
+        0.9 * 110.0 * 1.6 / 50.0,
+        epsilon = 1e-12
+    );
+    assert!(!registry.vaults.contains_key(&id));
+    assert_relative_eq!(registry.total_debt, 0.0, epsilon = 1e-9);
+}
+
+#[test]
+fn test_dust_vaults_are_counted_each_block() {
+    let mut scenario = Scenario::new_with_seed(&ScenarioConfig::default(), 42);
+    for _ in 0..5 {
+        scenario
+            .cdp_holders
+            .push(CdpHolder::new(CdpHolderConfig::default()));
+    }
+    scenario.initialize_agents();
+    for block in 1..=20 {
+        scenario.step(block, 50.0);
+    }
+    assert!(scenario.metrics.iter().all(|m| m.dust_vaults == 0));
+
+    // Raising the floor over every open vault's debt turns them all to dust
+    let open = scenario.registry.vaults.len();
+    assert!(open > 0);
+    scenario.registry.config.debt_floor = 1e9;
+    scenario.step(21, 50.0);
+    assert_eq!(scenario.registry.dust_vault_count(), open);
+    assert_eq!(scenario.metrics.last().unwrap().dust_vaults, open);
+}
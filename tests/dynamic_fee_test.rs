@@ -0,0 +1,122 @@
+//! Volatility-responsive AMM swap fee.
+//!
+//! The fee jumps to `max_fee` while realized volatility or net flow over a
+//! trailing window is above threshold and decays back to the base fee
+//! afterwards. The TwapManipulation comparison shows what the higher fee
+//! does to LP fee income and to the attacker.
+
+use approx::assert_relative_eq;
+use zai_sim::amm::{Amm, DynamicFee, DynamicFeeConfig};
+use zai_sim::scenario::ScenarioConfig;
+use zai_sim::scenarios::{run_stress, ScenarioId};
+
+const BASE_FEE: f64 = 0.003;
+
+fn flat_amm(blocks: u64) -> Amm {
+    let mut amm = Amm::new(10000.0, 500000.0, BASE_FEE);
+    for b in 1..=blocks {
+        amm.record_price(b);
+    }
+    amm
+}
+
+#[test]
+fn test_volatility_raises_fee_then_decays() {
+    let mut amm = flat_amm(20);
+    let mut fee = DynamicFee::new(DynamicFeeConfig::default());
+    assert_eq!(fee.update(&amm, BASE_FEE), BASE_FEE);
+
+    // A 30% dump is far above 2% per-block volatility
+    amm.swap_zec_for_zai(2000.0, 21).unwrap();
+    amm.record_price(22);
+    assert_relative_eq!(fee.update(&amm, BASE_FEE), 0.01);
+
+    // Stays up while the dump is in the 12-block window, then halves over
+    // the next 12 calm blocks
+    let mut current = 0.0;
+    for b in 23..=45 {
+        amm.record_price(b);
+        current = fee.update(&amm, BASE_FEE);
+        if b <= 33 {
+            assert_relative_eq!(current, 0.01);
+        }
+    }
+    assert_relative_eq!(current, BASE_FEE + 0.007 / 2.0, epsilon = 1e-12);
+}
+
+#[test]
+fn test_net_flow_raises_fee() {
+    let flow_only = DynamicFeeConfig {
+        volatility_threshold: 1.0,
+        ..DynamicFeeConfig::default()
+    };
+    let mut amm = flat_amm(20);
+    for b in 21..=26 {
+        amm.swap_zec_for_zai(100.0, b).unwrap();
+    }
+    amm.record_price(27);
+    // ~600 ZEC sold into a ~10,600 ZEC reserve: 5.7% net flow
+    assert!(amm.net_flow_fraction(12) > 0.05);
+
+    let mut fee = DynamicFee::new(flow_only.clone());
+    assert_relative_eq!(fee.update(&amm, BASE_FEE), 0.01);
+
+    let mut tolerant = DynamicFee::new(DynamicFeeConfig {
+        net_flow_threshold: 0.1,
+        ..flow_only
+    });
+    assert_eq!(tolerant.update(&amm, BASE_FEE), BASE_FEE);
+}
+
+#[test]
+fn test_twap_manipulation_with_dynamic_fee() {
+    let fixed = run_stress(
+        ScenarioId::TwapManipulation,
+        &ScenarioConfig::default(),
+        1000,
+        42,
+    );
+    let config = ScenarioConfig {
+        dynamic_fee: Some(DynamicFeeConfig::default()),
+        ..ScenarioConfig::default()
+    };
+    let dynamic = run_stress(ScenarioId::TwapManipulation, &config, 1000, 42);
+
+    assert!(fixed.metrics.iter().all(|m| m.swap_fee == BASE_FEE));
+    let max_fee = dynamic
+        .metrics
+        .iter()
+        .map(|m| m.swap_fee)
+        .fold(0.0, f64::max);
+    assert_relative_eq!(max_fee, 0.01);
+    // Calm stretches run at the base fee
+    assert_eq!(dynamic.metrics.swap_fee[100], BASE_FEE);
+
+    let attacker_cost = |s: &zai_sim::scenario::Scenario| {
+        let a = &s.attackers[0];
+        a.config.attack_capital_zec - a.zec_balance - a.zai_balance / s.amm.spot_price()
+    };
+    let (fixed_fees, dynamic_fees) = (
+        fixed.amm.cumulative_fees_zai,
+        dynamic.amm.cumulative_fees_zai,
+    );
+    println!(
+        "\n{:<10} {:>14} {:>18}",
+        "Fee", "LP fees (ZAI)", "Attack cost (ZEC)"
+    );
+    println!(
+        "{:<10} {:>14.2} {:>18.2}",
+        "fixed",
+        fixed_fees,
+        attacker_cost(&fixed)
+    );
+    println!(
+        "{:<10} {:>14.2} {:>18.2}",
+        "dynamic",
+        dynamic_fees,
+        attacker_cost(&dynamic)
+    );
+
+    // Spikes trade at the elevated fee, so LPs earn more
+    assert!(dynamic_fees > fixed_fees);
+}
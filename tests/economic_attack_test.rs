@@ -11,7 +11,8 @@ use zai_sim::controller::ControllerConfig;
 use zai_sim::output;
 use zai_sim::report;
 use zai_sim::scenario::{Scenario, ScenarioConfig};
-use zai_sim::scenarios::{add_agents, generate_prices, ScenarioId};
+use zai_sim::scenario_builder::{ScenarioBuilder, VaultPopulation};
+use zai_sim::scenarios::{generate_prices, ScenarioId};
 
 const BLOCKS: usize = 1000;
 const SEED: u64 = 42;
@@ -23,9 +24,11 @@ const WARMUP: u64 = 240;
 // ═══════════════════════════════════════════════════════════════════════
 
 fn attack_config() -> ScenarioConfig {
-    let mut c = ScenarioConfig::default();
-    c.amm_initial_zec = 100_000.0;
-    c.amm_initial_zai = 5_000_000.0;
+    let mut c = ScenarioConfig {
+        amm_initial_zec: 100_000.0,
+        amm_initial_zai: 5_000_000.0,
+        ..ScenarioConfig::default()
+    };
     c.cdp_config.min_ratio = 2.0;
     c.cdp_config.twap_window = 240;
     c.controller_config = ControllerConfig::default_tick();
@@ -34,32 +37,15 @@ fn attack_config() -> ScenarioConfig {
 }
 
 fn setup_scenario(config: &ScenarioConfig) -> Scenario {
-    let mut scenario = Scenario::new_with_seed(config, SEED);
-
-    // Add standard agents (arber + miner)
-    add_agents(ScenarioId::SteadyState, &mut scenario);
-
-    // Initialize agents
-    for lp in &mut scenario.lp_agents {
-        lp.provide_liquidity(&mut scenario.amm);
-    }
-    for lp in &mut scenario.il_aware_lps {
-        lp.provide_liquidity(&mut scenario.amm);
-    }
-
-    // Open 25 vaults at 210-280% CR
-    for i in 0..25 {
-        let cr = 2.10 + (i as f64) * 0.70 / 24.0;
-        let debt = 1000.0;
-        let collateral = cr * debt / 50.0;
-        let owner = format!("victim_{}", i);
-        scenario
-            .registry
-            .open_vault(&owner, collateral, debt, 0, &scenario.amm)
-            .unwrap();
-    }
-
-    scenario
+    // Standard agents (arber + miner) and 25 vaults at 210-280% CR
+    ScenarioBuilder::new(config.clone())
+        .with_seed(SEED)
+        .with_stress_agents(ScenarioId::SteadyState)
+        .with_vault_population(
+            VaultPopulation::new(25, 1000.0, 2.10, 2.80).with_owner_prefix("victim"),
+        )
+        .build()
+        .unwrap()
 }
 
 // ═══════════════════════════════════════════════════════════════════════
@@ -142,7 +128,7 @@ fn action_baseline(_block: u64, _whale: &Whale) -> AttackAction {
 }
 
 fn action_dump_and_hunt(block: u64, _whale: &Whale) -> AttackAction {
-    if block >= WARMUP + 1 && block <= WARMUP + 10 {
+    if (WARMUP + 1..=WARMUP + 10).contains(&block) {
         AttackAction::SellZec(5_000.0)
     } else if block == WARMUP + 500 {
         AttackAction::BuyBackAll
@@ -163,9 +149,9 @@ fn action_crash_amplify(block: u64, _whale: &Whale) -> AttackAction {
 }
 
 fn action_sustained(block: u64, whale: &Whale) -> AttackAction {
-    if block >= WARMUP + 1 && block <= WARMUP + 100 {
+    if (WARMUP + 1..=WARMUP + 100).contains(&block) {
         AttackAction::SellZec(1_000.0)
-    } else if block >= WARMUP + 101 && block <= WARMUP + 110 {
+    } else if (WARMUP + 101..=WARMUP + 110).contains(&block) {
         // Spread buyback over 10 blocks
         AttackAction::BuyBackSpread(whale.zai / (WARMUP + 111 - block) as f64)
     } else {
@@ -288,7 +274,7 @@ fn run_attack(config: &ScenarioConfig, attack: &AttackDef) -> (AttackRow, Scenar
 
         // Track min prices after warmup
         if block > WARMUP {
-            let twap = scenario.amm.get_twap(config.cdp_config.twap_window as u64);
+            let twap = scenario.amm.get_twap(config.cdp_config.twap_window);
             min_twap = min_twap.min(twap);
             min_spot = min_spot.min(scenario.amm.spot_price());
         }
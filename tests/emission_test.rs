@@ -0,0 +1,96 @@
+//! ZEC emission schedule and halving-aware miners.
+//!
+//! `EmissionSchedule` models the post-Blossom subsidy, its halvings and the
+//! dev fund split; `ChainCalendar` maps simulation blocks to mainnet heights
+//! and dates so long runs cross halvings at the right point.
+
+use approx::assert_relative_eq;
+use chrono::NaiveDate;
+use zai_sim::agents::{MinerAgent, MinerAgentConfig};
+use zai_sim::amm::Amm;
+use zai_sim::emission::{ChainCalendar, EmissionConfig, EmissionSchedule};
+
+fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+#[test]
+fn test_subsidy_halves_at_mainnet_heights() {
+    let s = EmissionSchedule::default();
+    assert_eq!(s.subsidy(1_000_000), 6.25);
+    assert_eq!(s.subsidy(1_046_400), 3.125);
+    assert_eq!(s.subsidy(2_726_399), 3.125);
+    assert_eq!(s.subsidy(2_726_400), 1.5625);
+    assert_eq!(s.subsidy(4_406_400), 0.78125);
+    assert_eq!(s.next_halving_height(2_800_000), 4_406_400);
+}
+
+#[test]
+fn test_dev_fund_split() {
+    let s = EmissionSchedule::default();
+    // 80% of the current 1.5625 subsidy matches the static default reward
+    assert_relative_eq!(s.miner_reward(2_800_000), 1.25, epsilon = 1e-12);
+    assert_relative_eq!(s.dev_fund(2_800_000), 0.3125, epsilon = 1e-12);
+
+    let ended = EmissionSchedule {
+        dev_fund_end_height: Some(3_146_400),
+        ..EmissionSchedule::default()
+    };
+    assert_eq!(ended.miner_reward(3_146_400), 1.5625);
+}
+
+#[test]
+fn test_calendar_maps_blocks_to_dates() {
+    let cal = ChainCalendar::default();
+    assert_eq!(cal.height(1), 2_726_400);
+    assert_eq!(cal.date(1), date(2024, 11, 23));
+    // 1152 blocks of 75s per day
+    assert_eq!(cal.date(1 + 1152), date(2024, 11, 24));
+    assert_eq!(cal.block_at_height(2_726_400), Some(1));
+    assert_eq!(cal.block_at_height(2_726_399), None);
+}
+
+#[test]
+fn test_2028_halving_from_start_date() {
+    let emission = EmissionConfig {
+        calendar: ChainCalendar::starting_at(date(2028, 10, 1)),
+        ..EmissionConfig::default()
+    };
+    assert_eq!(emission.calendar.date(1), date(2028, 10, 1));
+
+    let halving = emission.next_halving_block(1).unwrap();
+    assert_eq!(emission.calendar.height(halving), 4_406_400);
+    assert_eq!(emission.calendar.date(halving), date(2028, 11, 21));
+    assert_relative_eq!(emission.miner_reward(halving - 1), 1.25, epsilon = 1e-12);
+    assert_relative_eq!(emission.miner_reward(halving), 0.625, epsilon = 1e-12);
+}
+
+#[test]
+fn test_static_reward_without_schedule() {
+    let config = MinerAgentConfig::default();
+    assert!(config.emission.is_none());
+    assert_eq!(config.reward_at(1), config.block_reward);
+    assert_eq!(config.reward_at(10_000_000), config.block_reward);
+}
+
+#[test]
+fn test_miner_reward_drops_at_halving() {
+    let mut amm = Amm::new(100_000.0, 5_000_000.0, 0.003);
+    let mut miner = MinerAgent::new(MinerAgentConfig {
+        miner_sell_fraction: 0.0,
+        emission: Some(EmissionConfig {
+            calendar: ChainCalendar {
+                start_height: 4_406_400 - 10,
+                ..ChainCalendar::default()
+            },
+            ..EmissionConfig::default()
+        }),
+        ..MinerAgentConfig::default()
+    });
+
+    for block in 1..=20 {
+        miner.act(&mut amm, block);
+    }
+    // Ten blocks at 1.25 ZEC, then ten at 0.625 after the halving
+    assert_relative_eq!(miner.zec_balance, 18.75, epsilon = 1e-9);
+}
@@ -0,0 +1,67 @@
+//! Acceptance suite: every stress scenario must meet its registered
+//! expectations at the reference config, and a degraded config must not.
+
+use zai_sim::expectations::{self, Expectation};
+use zai_sim::output::compute_summary;
+use zai_sim::report::{evaluate_pass_fail, Verdict};
+use zai_sim::scenario::ScenarioConfig;
+use zai_sim::scenarios::{run_stress, ScenarioId};
+
+const BLOCKS: usize = 1000;
+const SEED: u64 = 42;
+
+fn failures(sid: ScenarioId, config: &ScenarioConfig) -> Vec<String> {
+    let target = config.initial_redemption_price;
+    let scenario = run_stress(sid, config, BLOCKS, SEED);
+    let verdict = evaluate_pass_fail(&scenario.metrics, target);
+    let summary = compute_summary(&scenario.metrics, target);
+
+    expectations::evaluate(sid, &verdict, &summary)
+        .into_iter()
+        .filter(|r| !r.passed)
+        .map(|r| format!("{}: {} (actual {})", sid.name(), r.expectation.describe(), r.actual))
+        .collect()
+}
+
+#[test]
+fn test_every_scenario_has_expectations() {
+    for sid in ScenarioId::all() {
+        let exp = expectations::expectations_for(sid);
+        assert!(
+            exp.iter().any(|e| matches!(e, Expectation::VerdictAtLeast(_))),
+            "{} must declare a verdict expectation",
+            sid.name()
+        );
+    }
+    assert!(expectations::expectations_for(ScenarioId::SteadyState)
+        .contains(&Expectation::VerdictAtLeast(Verdict::Pass)));
+    assert!(expectations::expectations_for(ScenarioId::SteadyState)
+        .contains(&Expectation::MaxLiquidations(0)));
+}
+
+#[test]
+fn test_reference_config_meets_all_expectations() {
+    let config = ScenarioConfig::default();
+    let all: Vec<String> = ScenarioId::all()
+        .into_iter()
+        .flat_map(|sid| failures(sid, &config))
+        .collect();
+    assert!(all.is_empty(), "Unmet expectations:\n{}", all.join("\n"));
+}
+
+#[test]
+fn test_thin_pool_proposal_is_rejected() {
+    // A proposal to shrink the AMM to 1/10th depth should fail acceptance
+    let config = ScenarioConfig {
+        amm_initial_zec: 1_000.0,
+        amm_initial_zai: 50_000.0,
+        ..ScenarioConfig::default()
+    };
+
+    let all: Vec<String> = ScenarioId::all()
+        .into_iter()
+        .flat_map(|sid| failures(sid, &config))
+        .collect();
+    println!("Thin-pool failures:\n  {}", all.join("\n  "));
+    assert!(!all.is_empty(), "Thin pool should violate at least one expectation");
+}
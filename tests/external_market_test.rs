@@ -0,0 +1,118 @@
+//! Finite-depth external market.
+//!
+//! Arbers used to convert ZAI↔ZEC off-chain at the oracle price with zero
+//! slippage. With an `ExternalMarket` their hedging trades walk the venue
+//! price, so arb capacity against a mispriced AMM is bounded by depth.
+
+use approx::assert_relative_eq;
+use zai_sim::agents::{Arbitrageur, ArbitrageurConfig};
+use zai_sim::amm::Amm;
+use zai_sim::external_market::{ExternalMarket, ExternalMarketConfig};
+
+const ORACLE: f64 = 50.0;
+
+fn market(depth_zai: f64, half_life: Option<f64>) -> ExternalMarket {
+    ExternalMarket::new(ExternalMarketConfig {
+        depth_zai,
+        recovery_half_life_blocks: half_life,
+    })
+}
+
+#[test]
+fn test_small_trades_fill_near_oracle() {
+    let m = market(1_000_000.0, None);
+    assert_relative_eq!(m.quote_buy_zec(1.0, ORACLE), ORACLE, max_relative = 1e-6);
+    assert_relative_eq!(m.quote_sell_zec(1.0, ORACLE), ORACLE, max_relative = 1e-6);
+}
+
+#[test]
+fn test_slippage_grows_with_size() {
+    let m = market(10_000.0, None);
+    let avg_small = m.quote_buy_zec(10.0, ORACLE) / 10.0;
+    let avg_large = m.quote_buy_zec(1000.0, ORACLE) / 1000.0;
+    assert!(avg_small > ORACLE);
+    assert!(avg_large > avg_small);
+    assert!(m.quote_sell_zec(1000.0, ORACLE) / 1000.0 < ORACLE);
+}
+
+#[test]
+fn test_buy_moves_price_by_depth() {
+    let mut m = market(10_000.0, None);
+    let cost = m.buy_zec(100.0, ORACLE, 1);
+    // Impact is 1% per depth_zai of notional
+    assert_relative_eq!(m.impact, cost / 10_000.0 * 0.01, epsilon = 1e-12);
+    assert!(m.price(ORACLE) > ORACLE);
+}
+
+#[test]
+fn test_impact_recovers_with_half_life() {
+    let mut m = market(10_000.0, Some(10.0));
+    m.buy_zec(100.0, ORACLE, 1);
+    let impact = m.impact;
+    m.recover(11);
+    assert_relative_eq!(m.impact, impact / 2.0, epsilon = 1e-12);
+
+    let mut sticky = market(10_000.0, None);
+    sticky.buy_zec(100.0, ORACLE, 1);
+    let impact = sticky.impact;
+    sticky.recover(1000);
+    assert_eq!(sticky.impact, impact);
+}
+
+/// Run one zero-latency arber for `blocks` against an AMM priced at 60 while
+/// the oracle sits at 50; returns the final AMM price and the arber.
+fn arb_mispriced_amm(
+    external_market: Option<ExternalMarketConfig>,
+    blocks: u64,
+) -> (f64, Arbitrageur) {
+    let mut amm = Amm::new(10_000.0, 600_000.0, 0.003);
+    let mut arber = Arbitrageur::new(ArbitrageurConfig {
+        arb_latency_sell_blocks: 0,
+        external_market,
+        ..ArbitrageurConfig::default()
+    });
+    for block in 1..=blocks {
+        arber.act(&mut amm, ORACLE, block);
+    }
+    (amm.spot_price(), arber)
+}
+
+#[test]
+fn test_frictionless_arber_closes_gap() {
+    let (price, _) = arb_mispriced_amm(None, 40);
+    assert!(price < ORACLE * 1.01, "AMM price {}", price);
+}
+
+#[test]
+fn test_thin_market_bounds_arb_capacity() {
+    let (frictionless, _) = arb_mispriced_amm(None, 40);
+    let thin = ExternalMarketConfig {
+        depth_zai: 5_000.0,
+        recovery_half_life_blocks: None,
+    };
+    let (price, arber) = arb_mispriced_amm(Some(thin), 40);
+
+    let market = arber.external_market.as_ref().unwrap();
+    assert!(market.impact > 0.0);
+    // The arber stops once the AMM meets the venue price its own buying lifted
+    assert!(
+        price > frictionless + 2.0,
+        "thin {} vs frictionless {}",
+        price,
+        frictionless
+    );
+    assert_relative_eq!(price, market.price(ORACLE), max_relative = 0.02);
+    // Hedged: ZEC inventory is bought back rather than run down
+    assert!(arber.zec_balance > 1900.0);
+}
+
+#[test]
+fn test_recovery_restores_arb_capacity() {
+    let thin = |half_life| ExternalMarketConfig {
+        depth_zai: 10_000.0,
+        recovery_half_life_blocks: half_life,
+    };
+    let (sticky, _) = arb_mispriced_amm(Some(thin(None)), 200);
+    let (recovering, _) = arb_mispriced_amm(Some(thin(Some(5.0))), 200);
+    assert!(recovering < sticky);
+}
@@ -0,0 +1,196 @@
+//! Scheduled halts, oracle outages and AMM pauses.
+//!
+//! A fault schedule attaches to any scenario: during a halt nothing lands
+//! on chain, during an oracle outage external-price liquidations wait, and
+//! a paused AMM takes no swaps.
+
+use zai_sim::faults::{Fault, FaultSchedule};
+use zai_sim::oracle::{OracleConfig, OracleFailure, OracleFeed};
+use zai_sim::scenario::{Scenario, ScenarioConfig};
+use zai_sim::scenario_file::ScenarioFile;
+use zai_sim::scenarios::{add_agents, generate_prices, ScenarioId};
+
+#[test]
+fn test_parse_faults() {
+    assert_eq!(
+        "halt:400-600".parse::<Fault>().unwrap(),
+        Fault::Halt {
+            from_block: 400,
+            to_block: 600
+        }
+    );
+    assert_eq!(
+        "oracle:10-10".parse::<Fault>().unwrap(),
+        Fault::OracleOutage {
+            from_block: 10,
+            to_block: 10
+        }
+    );
+    assert!(matches!(
+        "amm_pause:1-5".parse::<Fault>().unwrap(),
+        Fault::AmmPause { .. }
+    ));
+    for bad in ["halt:600-400", "crash:1-2", "halt:400", "halt"] {
+        assert!(bad.parse::<Fault>().is_err(), "{}", bad);
+    }
+}
+
+#[test]
+fn test_schedule_queries() {
+    let schedule = FaultSchedule::sequencer_downtime(1000)
+        .with(Fault::OracleOutage {
+            from_block: 700,
+            to_block: 750,
+        })
+        .with(Fault::AmmPause {
+            from_block: 800,
+            to_block: 810,
+        });
+    assert!(!schedule.is_halted(400));
+    assert!(schedule.is_halted(401) && schedule.is_halted(600));
+    assert!(!schedule.is_halted(601));
+    // A halt closes the AMM too; an oracle outage doesn't
+    assert!(schedule.is_amm_closed(500) && schedule.is_amm_closed(805));
+    assert!(!schedule.is_amm_closed(720));
+    assert!(schedule.is_oracle_down(720) && !schedule.is_oracle_down(500));
+
+    let failures: Vec<OracleFailure> = schedule.oracle_failures().collect();
+    assert_eq!(
+        failures,
+        vec![
+            OracleFailure::Stale {
+                from_block: 401,
+                to_block: 600
+            },
+            OracleFailure::Outage {
+                from_block: 700,
+                to_block: 750
+            },
+        ]
+    );
+}
+
+fn run(config: ScenarioConfig, id: ScenarioId) -> Scenario {
+    let mut scenario = Scenario::new_with_seed(&config, 42);
+    add_agents(id, &mut scenario);
+    scenario.run(&generate_prices(id, 1000, 42));
+    scenario
+}
+
+#[test]
+fn test_halt_freezes_the_chain() {
+    // Downtime overlaid on a crash: the market falls while nothing lands
+    let config = ScenarioConfig {
+        faults: FaultSchedule::new().with(Fault::Halt {
+            from_block: 250,
+            to_block: 400,
+        }),
+        ..ScenarioConfig::default()
+    };
+    let halted = run(config, ScenarioId::BlackThursday);
+    let before = halted.metrics.row(248);
+    for m in halted.metrics.rows(249..400) {
+        assert!(m.network_halted && m.amm_paused && !m.halted);
+        assert_eq!(m.liquidation_count, 0);
+        assert_eq!(m.amm_reserve_zec, before.amm_reserve_zec);
+        assert_eq!(m.amm_reserve_zai, before.amm_reserve_zai);
+    }
+    assert!(!halted.metrics.network_halted[400]);
+
+    let live = run(ScenarioConfig::default(), ScenarioId::BlackThursday);
+    println!(
+        "\nBlack Thursday with blocks 250-400 halted: {} liquidations (live {}), bad debt {:.2} (live {:.2})",
+        halted.liquidation_engine.history.len(),
+        live.liquidation_engine.history.len(),
+        halted.liquidation_engine.total_bad_debt,
+        live.liquidation_engine.total_bad_debt
+    );
+}
+
+#[test]
+fn test_amm_pause_rejects_swaps() {
+    let config = ScenarioConfig {
+        faults: FaultSchedule::new().with(Fault::AmmPause {
+            from_block: 100,
+            to_block: 150,
+        }),
+        ..ScenarioConfig::default()
+    };
+    let scenario = run(config, ScenarioId::FlashCrash);
+    let before = scenario.metrics.row(98);
+    for m in scenario.metrics.rows(99..150) {
+        assert!(m.amm_paused && !m.network_halted);
+        assert_eq!(m.amm_reserve_zec, before.amm_reserve_zec);
+    }
+    assert!(!scenario.metrics.amm_paused[150]);
+    assert_eq!(scenario.amm.max_swap_fraction, None);
+}
+
+#[test]
+fn test_oracle_outage_holds_liquidations() {
+    let faults = FaultSchedule::new().with(Fault::OracleOutage {
+        from_block: 300,
+        to_block: 500,
+    });
+
+    // External-price liquidation has nothing to judge eligibility by
+    let external = run(
+        ScenarioConfig {
+            use_external_oracle_for_liquidation: true,
+            faults: faults.clone(),
+            ..ScenarioConfig::default()
+        },
+        ScenarioId::BlackThursday,
+    );
+    for m in external.metrics.rows(299..500) {
+        assert!(m.oracle_outage);
+        assert_eq!(m.liquidation_count, 0);
+    }
+
+    // A configured oracle reports nothing
+    let oracle = run(
+        ScenarioConfig {
+            oracle: Some(OracleConfig::new(OracleFeed::ExternalSpot)),
+            faults,
+            ..ScenarioConfig::default()
+        },
+        ScenarioId::BlackThursday,
+    );
+    assert!(oracle
+        .metrics
+        .rows(299..500)
+        .all(|m| m.oracle_price.is_none()));
+    assert!(oracle.metrics.oracle_price[500].is_some());
+    // The schedule's failures ride alongside the oracle's own config
+    assert!(oracle.config.oracle.as_ref().unwrap().failures.is_empty());
+    assert_eq!(oracle.oracle.as_ref().unwrap().config.failures.len(), 1);
+}
+
+#[test]
+fn test_faults_from_scenario_file() {
+    let file = ScenarioFile::from_yaml_str(
+        r#"
+name: downtime_crash
+config:
+  faults:
+    - { type: halt, from_block: 20, to_block: 40 }
+    - { type: oracle_outage, from_block: 60, to_block: 70 }
+prices:
+  - { type: hold, price: 50, blocks: 50 }
+  - { type: ramp, to: 30, blocks: 50 }
+"#,
+    )
+    .unwrap();
+    let config = file.scenario_config(&ScenarioConfig::default()).unwrap();
+    assert_eq!(config.faults.faults.len(), 2);
+    assert!(config.faults.is_halted(30));
+
+    let scenario = file.run(&ScenarioConfig::default(), 42).unwrap();
+    let halted: Vec<u64> = scenario
+        .metrics
+        .iter()
+        .filter(|m| m.network_halted)
+        .map(|m| m.block)
+        .collect();
+    assert_eq!(halted, (20..=40).collect::<Vec<u64>>());
+}
@@ -0,0 +1,180 @@
+//! Stability fee accrual through the registry's rate accumulator.
+//!
+//! Fees compound per block into one accumulator; a vault's debt grows by
+//! the accumulator's change since its last accrual, so accruing often or
+//! rarely, in any order, gives the same debt, and reads see fees due
+//! without accruing them.
+
+use approx::assert_relative_eq;
+use zai_sim::amm::Amm;
+use zai_sim::cdp::{apr_to_per_block, per_block_to_apr, CdpConfig, VaultRegistry};
+use zai_sim::governance::ParameterSchedule;
+use zai_sim::scenario::{Scenario, ScenarioConfig};
+
+const BLOCKS_PER_YEAR: f64 = 365.25 * 24.0 * 3600.0 / 75.0;
+
+fn amm() -> Amm {
+    Amm::new(10_000.0, 500_000.0, 0.003)
+}
+
+fn registry(rate: f64) -> VaultRegistry {
+    VaultRegistry::new(CdpConfig {
+        stability_fee_rate: rate,
+        ..CdpConfig::default()
+    })
+}
+
+#[test]
+fn test_apr_per_block_conversion() {
+    assert_relative_eq!(apr_to_per_block(0.02), 0.02 / BLOCKS_PER_YEAR);
+    assert_relative_eq!(per_block_to_apr(apr_to_per_block(0.07)), 0.07);
+    assert_eq!(apr_to_per_block(0.0), 0.0);
+    // A year of per-block compounding at 2% APR: about e^0.02
+    let year = (1.0 + apr_to_per_block(0.02)).powi(BLOCKS_PER_YEAR as i32);
+    assert_relative_eq!(year, 0.02f64.exp(), epsilon = 1e-6);
+}
+
+#[test]
+fn test_accrual_is_invariant_to_call_ordering() {
+    let amm = amm();
+    let open = |registry: &mut VaultRegistry| {
+        let a = registry.open_vault("a", 100.0, 1000.0, 10, &amm).unwrap();
+        let b = registry.open_vault("b", 200.0, 3000.0, 10, &amm).unwrap();
+        (a, b)
+    };
+
+    // Accrued every 1000 blocks, interleaved
+    let mut often = registry(0.05);
+    let (a, b) = open(&mut often);
+    for block in (1000..=50_000).step_by(1000) {
+        often.accrue_fees(a, block).unwrap();
+        often.accrue_fees(b, block + 3).unwrap();
+    }
+    often.accrue_all_fees(50_010);
+
+    // Accrued once each at the end, in the other order, twice
+    let mut once = registry(0.05);
+    let (a2, b2) = open(&mut once);
+    once.accrue_fees(b2, 50_010).unwrap();
+    once.accrue_fees(a2, 50_010).unwrap();
+    once.accrue_fees(a2, 50_010).unwrap();
+    once.accrue_all_fees(50_010);
+
+    for (x, y) in [(a, a2), (b, b2)] {
+        assert_relative_eq!(
+            often.get_vault(x).unwrap().debt_zai,
+            once.get_vault(y).unwrap().debt_zai,
+            max_relative = 1e-12
+        );
+    }
+    assert_relative_eq!(often.total_debt, once.total_debt, max_relative = 1e-12);
+    assert_relative_eq!(
+        often.total_fees_accrued,
+        once.total_fees_accrued,
+        max_relative = 1e-9
+    );
+
+    // 50,000 blocks at 5%
+    let expected = 1000.0 * (1.0 + apr_to_per_block(0.05)).powi(50_000);
+    assert_relative_eq!(
+        once.get_vault(a2).unwrap().debt_zai,
+        expected,
+        max_relative = 1e-9
+    );
+    // An earlier block is not accrued again
+    let debt = once.get_vault(a2).unwrap().debt_zai;
+    once.accrue_fees(a2, 20_000).unwrap();
+    assert_eq!(once.get_vault(a2).unwrap().debt_zai, debt);
+}
+
+#[test]
+fn test_rate_change_applies_from_the_block_it_is_made() {
+    let amm = amm();
+    let mut registry = registry(0.02);
+    let id = registry.open_vault("a", 100.0, 1000.0, 0, &amm).unwrap();
+
+    // The vault isn't touched, but the accumulator is advanced before the
+    // fee goes up, so the first 10,000 blocks stay at 2%
+    registry.drip(10_000);
+    registry.config.stability_fee_rate = 0.20;
+    registry.accrue_fees(id, 20_000).unwrap();
+
+    let expected = 1000.0
+        * (1.0 + apr_to_per_block(0.02)).powi(10_000)
+        * (1.0 + apr_to_per_block(0.20)).powi(10_000);
+    assert_relative_eq!(
+        registry.get_vault(id).unwrap().debt_zai,
+        expected,
+        max_relative = 1e-9
+    );
+}
+
+#[test]
+fn test_reads_see_fees_due() {
+    let amm = amm();
+    let mut registry = registry(0.10);
+    // At $50: ratio 1.52, just above the 1.5 minimum
+    let id = registry.open_vault("a", 30.4, 1000.0, 0, &amm).unwrap();
+    assert!(!registry.is_liquidatable(id, &amm));
+
+    // Half a year of fees without an accrual
+    let block = (BLOCKS_PER_YEAR / 2.0) as u64;
+    let due = registry.debt_at(id, block).unwrap();
+    assert_relative_eq!(
+        due,
+        1000.0 * (1.0 + apr_to_per_block(0.10)).powi(block as i32),
+        max_relative = 1e-9
+    );
+    let vault = registry.vault_at(id, block).unwrap();
+    assert_eq!(vault.debt_zai, due);
+    assert!(vault.collateral_ratio(50.0) < 1.5);
+    // Stored state is untouched by reads
+    assert_eq!(registry.get_vault(id).unwrap().debt_zai, 1000.0);
+    assert_eq!(registry.total_debt, 1000.0);
+
+    // Once the accumulator reaches the block, registry queries and the
+    // total include the fees due
+    registry.drip(block);
+    assert_relative_eq!(registry.total_debt, due, max_relative = 1e-12);
+    assert!(registry.is_liquidatable(id, &amm));
+    assert_eq!(registry.liquidatable_at_price(50.0), vec![id]);
+    assert_relative_eq!(
+        registry.liquidation_penalty_amount(id).unwrap(),
+        due * 0.13,
+        max_relative = 1e-12
+    );
+    assert_relative_eq!(registry.owner_totals("a").1, due, max_relative = 1e-12);
+
+    // Accruing makes it stored, once, without counting the fees again
+    registry.accrue_fees(id, block).unwrap();
+    assert_relative_eq!(registry.total_debt, due, max_relative = 1e-12);
+    assert_relative_eq!(
+        registry.get_vault(id).unwrap().debt_zai,
+        due,
+        max_relative = 1e-12
+    );
+    assert_relative_eq!(
+        registry.total_fees_accrued,
+        due - 1000.0,
+        max_relative = 1e-9
+    );
+    assert_eq!(
+        registry.debt_at(id, block).unwrap(),
+        registry.get_vault(id).unwrap().debt_zai
+    );
+}
+
+#[test]
+fn test_scheduled_fee_change_compounds_from_the_next_block() {
+    let mut scenario = Scenario::new(&ScenarioConfig {
+        parameter_schedule: ParameterSchedule::new().at(100, "stability_fee_rate", 0.20),
+        ..ScenarioConfig::default()
+    });
+    scenario.run(&[50.0; 200]);
+
+    // Up to block 100 at the default 2%, after it at 20%
+    let registry = &scenario.registry;
+    let expected = (1.0 + apr_to_per_block(0.02)).powi(100)
+        * (1.0 + apr_to_per_block(0.20)).powi(registry.accumulator_block as i32 - 100);
+    assert_relative_eq!(registry.rate_accumulator, expected, max_relative = 1e-9);
+}
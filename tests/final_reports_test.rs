@@ -20,9 +20,11 @@ const LONG_BLOCKS: usize = 50_000;
 const SEED: u64 = 42;
 
 fn config_5m_stochastic() -> ScenarioConfig {
-    let mut config = ScenarioConfig::default();
-    config.amm_initial_zec = 100_000.0;
-    config.amm_initial_zai = 5_000_000.0;
+    let mut config = ScenarioConfig {
+        amm_initial_zec: 100_000.0,
+        amm_initial_zai: 5_000_000.0,
+        ..ScenarioConfig::default()
+    };
     config.cdp_config.min_ratio = 2.0;
     config.cdp_config.twap_window = 240;
     config.controller_config = ControllerConfig::default_tick();
@@ -64,7 +66,13 @@ fn final_reports() {
     for sid in ScenarioId::all() {
         let scenario = run_stress(sid, &config, BLOCKS, SEED);
 
-        let html = report::generate_report(&scenario.metrics, &config, sid.name(), target);
+        let html = report::generate_report_with_liquidations(
+            &scenario.metrics,
+            &scenario.liquidation_engine.history,
+            &config,
+            sid.name(),
+            target,
+        );
         report::save_report(&html, &report_dir.join(format!("{}.html", sid.name()))).unwrap();
 
         let verdict = report::evaluate_pass_fail(&scenario.metrics, target);
@@ -88,8 +96,9 @@ fn final_reports() {
     println!("\n  ── Long Duration ──");
     let sb_scenario = run_stress(ScenarioId::SustainedBear, &config, LONG_BLOCKS, SEED);
 
-    let sb_html = report::generate_report(
+    let sb_html = report::generate_report_with_liquidations(
         &sb_scenario.metrics,
+        &sb_scenario.liquidation_engine.history,
         &config,
         "sustained_bear_50k",
         target,
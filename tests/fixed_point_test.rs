@@ -0,0 +1,148 @@
+//! Fixed-point arithmetic backend.
+//!
+//! `Fixed` is pure integer arithmetic, so its results are the same on every
+//! platform. These tests check it against `f64`, and check that the
+//! simulation math in `zai_sim::fixed` is plain `f64` without the
+//! `fixed-point` feature and deterministic with it.
+
+use zai_sim::fixed::{self, Fixed};
+use zai_sim::scenario::ScenarioConfig;
+use zai_sim::scenarios::{run_stress, ScenarioId};
+
+/// Within `rel` relative error, or the 1e-18 resolution of `Fixed`.
+fn close(a: f64, b: f64, rel: f64) -> bool {
+    (a - b).abs() <= (rel * b.abs()).max(2e-18)
+}
+
+#[test]
+fn test_basic_arithmetic() {
+    let a = Fixed::from_f64(1.5);
+    let b = Fixed::from_f64(-0.25);
+    assert_eq!((a + b).to_f64(), 1.25);
+    assert_eq!((a - b).to_f64(), 1.75);
+    assert_eq!((a * b).to_f64(), -0.375);
+    assert_eq!(a.checked_div(b).unwrap().to_f64(), -6.0);
+    assert_eq!(a.checked_div(Fixed::ZERO), None);
+    assert_eq!((-a).to_f64(), -1.5);
+    assert_eq!(b.abs().to_f64(), 0.25);
+    assert_eq!(
+        Fixed::from_int(3),
+        Fixed::from_raw(3_000_000_000_000_000_000)
+    );
+    assert_eq!(a.to_string(), "1.500000000000000000");
+    assert_eq!(b.to_string(), "-0.250000000000000000");
+
+    // Products of pool-sized reserves keep full precision
+    let x = Fixed::from_int(100_000);
+    let y = Fixed::from_int(5_000_000);
+    assert_eq!(x * y, Fixed::from_int(500_000_000_000));
+    assert_eq!((x * y).checked_div(x).unwrap(), y);
+}
+
+#[test]
+fn test_saturates_at_range_limits() {
+    let big = Fixed::from_int(1_000_000_000_000);
+    assert_eq!(big * big, Fixed::MAX);
+    assert_eq!(big * -big, -Fixed::MAX);
+    assert_eq!(Fixed::MAX + Fixed::ONE, Fixed::MAX);
+    assert_eq!(Fixed::MIN - Fixed::ONE, Fixed::MIN);
+    assert_eq!(Fixed::from_f64(f64::NAN), Fixed::ZERO);
+    assert_eq!(Fixed::from_int(200).exp(), Fixed::MAX);
+    assert_eq!(Fixed::from_int(-200).exp(), Fixed::ZERO);
+}
+
+#[test]
+fn test_transcendentals_match_f64() {
+    for x in [1e-6, 0.01, 0.5, 0.999, 1.0, 1.001, 2.0, 50.0, 12_345.678] {
+        let ln = Fixed::from_f64(x).ln().unwrap().to_f64();
+        assert!(
+            (ln - x.ln()).abs() < 1e-15,
+            "ln {}: {} vs {}",
+            x,
+            ln,
+            x.ln()
+        );
+    }
+    assert_eq!(Fixed::ZERO.ln(), None);
+    assert_eq!(Fixed::from_f64(-1.0).ln(), None);
+    assert_eq!(Fixed::ONE.ln(), Some(Fixed::ZERO));
+
+    for x in [-20.0, -1.0, -1e-9, 0.0, 0.3, 1.0, 3.9, 10.0, 40.0] {
+        let exp = Fixed::from_f64(x).exp().to_f64();
+        assert!(
+            close(exp, x.exp(), 1e-14),
+            "exp {}: {} vs {}",
+            x,
+            exp,
+            x.exp()
+        );
+    }
+    assert_eq!(Fixed::ZERO.exp(), Fixed::ONE);
+
+    // Per-block fee compounding over long horizons
+    for (base, n) in [
+        (1.0 + 1e-9, 400_000),
+        (1.0 - 1e-7, 50_000),
+        (1.01, 500),
+        (2.0, -3),
+    ] {
+        let pow = Fixed::from_f64(base).powi(n).to_f64();
+        let expected = base.powi(n as i32);
+        assert!(
+            close(pow, expected, 1e-10),
+            "{}^{}: {} vs {}",
+            base,
+            n,
+            pow,
+            expected
+        );
+    }
+    assert_eq!(Fixed::from_f64(7.5).powi(0), Fixed::ONE);
+}
+
+#[cfg(not(feature = "fixed-point"))]
+#[test]
+fn test_default_math_is_plain_f64() {
+    for (a, b) in [(5e11, 100_010.0), (1.3, 0.7), (-2.0, 3.0)] {
+        assert_eq!(fixed::mul(a, b), a * b);
+        assert_eq!(fixed::div(a, b), a / b);
+    }
+    assert_eq!(fixed::powi(1.0 + 1e-9, 1000), (1.0 + 1e-9f64).powi(1000));
+    assert_eq!(fixed::ln(49.7), 49.7f64.ln());
+    assert_eq!(fixed::exp(3.9), 3.9f64.exp());
+    assert!(fixed::ln(-1.0).is_nan());
+    assert_eq!(fixed::div(1.0, 0.0), f64::INFINITY);
+}
+
+#[cfg(feature = "fixed-point")]
+#[test]
+fn test_fixed_math_tracks_f64() {
+    for (a, b) in [(5e11, 100_010.0), (1.3, 0.7), (-2.0, 3.0)] {
+        assert!(close(fixed::mul(a, b), a * b, 1e-15));
+        assert!(close(fixed::div(a, b), a / b, 1e-15));
+    }
+    // 1 + 1e-9 rounds differently as f64 and Fixed, compounded 1000 times
+    assert!(close(
+        fixed::powi(1.0 + 1e-9, 1000),
+        (1.0 + 1e-9f64).powi(1000),
+        1e-12
+    ));
+    assert!(close(fixed::ln(49.7), 49.7f64.ln(), 1e-15));
+    assert!(close(fixed::exp(3.9), 3.9f64.exp(), 1e-14));
+    assert!(fixed::ln(-1.0).is_nan());
+    assert_eq!(fixed::ln(0.0), f64::NEG_INFINITY);
+    assert_eq!(fixed::div(1.0, 0.0), f64::INFINITY);
+}
+
+#[test]
+fn test_runs_are_deterministic() {
+    let config = ScenarioConfig::default();
+    let a = run_stress(ScenarioId::BlackThursday, &config, 300, 42);
+    let b = run_stress(ScenarioId::BlackThursday, &config, 300, 42);
+    for (x, y) in a.metrics.iter().zip(&b.metrics) {
+        assert_eq!(x.amm_spot_price.to_bits(), y.amm_spot_price.to_bits());
+        assert_eq!(x.redemption_price.to_bits(), y.redemption_price.to_bits());
+        assert_eq!(x.total_debt.to_bits(), y.total_debt.to_bits());
+    }
+    assert_eq!(a.metrics.len(), b.metrics.len());
+}
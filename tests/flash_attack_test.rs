@@ -0,0 +1,114 @@
+//! Flash-loan style atomic attacks.
+//!
+//! A flash attack borrows ZEC, dumps it, runs the liquidation pass against
+//! the manipulated pool and buys the ZEC back inside one block. These tests
+//! check that TWAP-priced liquidation gives it nothing to trigger, that an
+//! unprofitable attack reverts, and which configs are exploitable.
+
+use zai_sim::flash_attack::{self, FlashAttackConfig, FlashScan};
+use zai_sim::scenario::{Scenario, ScenarioConfig};
+use zai_sim::scenarios::{add_agents, generate_prices, ScenarioId};
+
+/// Twenty 1,000 ZAI vaults at CR 1.6, just above the 1.5 minimum.
+fn open_vaults(scenario: &mut Scenario) {
+    for i in 0..20 {
+        scenario
+            .registry
+            .open_vault(&format!("vault_{}", i), 32.0, 1000.0, 0, &scenario.amm)
+            .unwrap();
+    }
+}
+
+fn scenario(config: &ScenarioConfig) -> Scenario {
+    let mut scenario = Scenario::new_with_seed(config, 42);
+    add_agents(ScenarioId::SteadyState, &mut scenario);
+    open_vaults(&mut scenario);
+    scenario.run(&generate_prices(ScenarioId::SteadyState, 100, 42));
+    scenario
+}
+
+fn spot_config() -> ScenarioConfig {
+    ScenarioConfig {
+        use_amm_liquidation: true,
+        ..ScenarioConfig::default()
+    }
+}
+
+#[test]
+fn test_twap_liquidation_ignores_atomic_dump() {
+    let mut s = scenario(&ScenarioConfig::default());
+    let reserves = (s.amm.reserve_zec, s.amm.reserve_zai);
+    let vaults = s.registry.vaults.len();
+    let liquidated = s.liquidation_engine.history.len();
+
+    let result = flash_attack::simulate(&s, &FlashAttackConfig::default()).unwrap();
+    assert_eq!(result.block, s.last_block() + 1);
+    assert_eq!(result.liquidations, 0);
+    assert_eq!(result.bad_debt_created, 0.0);
+    // Two swap fees plus the loan fee
+    assert!(result.profit_zec < -result.loan_fee_zec, "{:?}", result);
+
+    // The attack would revert, so nothing lands
+    let result = flash_attack::execute(&mut s, &FlashAttackConfig::default()).unwrap();
+    assert!(!result.executed);
+    assert_eq!((s.amm.reserve_zec, s.amm.reserve_zai), reserves);
+    assert_eq!(s.registry.vaults.len(), vaults);
+    assert_eq!(s.liquidation_engine.history.len(), liquidated);
+}
+
+#[test]
+fn test_spot_liquidation_is_triggered_atomically() {
+    let mut s = scenario(&spot_config());
+    let vaults = s.registry.vaults.len();
+
+    // 5,000 ZEC into a 10,000 ZEC pool takes spot far below the 46.9
+    // liquidation price of a CR 1.6 vault
+    let result = flash_attack::simulate(&s, &FlashAttackConfig::default()).unwrap();
+    assert!(result.liquidations > 0, "{:?}", result);
+    assert!(result.collateral_liquidated > 0.0);
+    assert_eq!(s.registry.vaults.len(), vaults, "simulate must not land");
+
+    let executed = flash_attack::execute(&mut s, &FlashAttackConfig::default()).unwrap();
+    assert_eq!(executed.executed, result.profit_zec > 0.0);
+    if executed.executed {
+        assert_eq!(executed.profit_zec, result.profit_zec);
+        assert!(s.registry.vaults.len() < vaults);
+    } else {
+        assert_eq!(s.registry.vaults.len(), vaults);
+    }
+    println!(
+        "\nSpot liquidation: {} liquidations, profit {:.2} ZEC, executed {}",
+        result.liquidations, result.profit_zec, executed.executed
+    );
+}
+
+#[test]
+fn test_scan_reports_exploitability() {
+    let configs = [
+        ("TWAP (default)", ScenarioConfig::default()),
+        ("AMM spot", spot_config()),
+        (
+            "external oracle",
+            ScenarioConfig {
+                use_external_oracle_for_liquidation: true,
+                ..ScenarioConfig::default()
+            },
+        ),
+    ];
+    println!("\nAtomically exploitable:");
+    for (name, config) in configs {
+        let mut scan = FlashScan::new(config, ScenarioId::SteadyState, 300, 42);
+        scan.setup = open_vaults;
+        let report = scan.run().unwrap();
+        assert!(report.attempts > 0);
+        if name != "AMM spot" {
+            assert!(!report.is_exploitable(), "{}: {:?}", name, report.best);
+        }
+        println!(
+            "  {:<16} {:<3}  best profit {:>10.2} ZEC",
+            name,
+            if report.is_exploitable() { "yes" } else { "no" },
+            report.best.as_ref().map_or(0.0, |b| b.profit_zec)
+        );
+    }
+}
@@ -0,0 +1,124 @@
+//! Funding-rate style negative rates on ZAI balances.
+//!
+//! While ZAI trades above par the controller's positive redemption rate is
+//! charged on ZAI held, paid to the treasury, and demand agents sell into
+//! the carry instead of buying.
+
+use approx::assert_relative_eq;
+use zai_sim::agents::{AgentAction, DemandAgent, DemandAgentConfig};
+use zai_sim::amm::Amm;
+use zai_sim::funding::{FundingRate, FundingRateConfig};
+use zai_sim::savings::{SavingsConfig, SavingsModule};
+use zai_sim::scenario::{Scenario, ScenarioConfig};
+use zai_sim::scenarios::{add_agents, generate_prices, ScenarioId};
+
+const BLOCKS_PER_YEAR: f64 = 365.25 * 24.0 * 3600.0 / 75.0;
+
+#[test]
+fn test_rate_follows_controller() {
+    let mut funding = FundingRate::new(FundingRateConfig::default());
+    assert_eq!(funding.update(5e-5), -5e-5);
+    assert_relative_eq!(funding.holding_cost(), 5e-5 * BLOCKS_PER_YEAR);
+
+    // Below par the redemption price does the work alone
+    assert_eq!(funding.update(-5e-5), 0.0);
+    assert_eq!(funding.holding_cost(), 0.0);
+
+    // Capped per block
+    assert_eq!(funding.update(1.0), -1e-4);
+}
+
+#[test]
+fn test_charges_balances() {
+    let mut funding = FundingRate::new(FundingRateConfig::default());
+    let mut balance = 10_000.0;
+    assert_eq!(funding.charge(&mut balance), 0.0);
+
+    funding.update(1e-4);
+    let charged = funding.charge(&mut balance);
+    assert_relative_eq!(charged, 1.0, epsilon = 1e-12);
+    assert_relative_eq!(balance, 9_999.0, epsilon = 1e-9);
+    assert_eq!(funding.total_charged_zai, charged);
+
+    let mut savings = SavingsModule::new(SavingsConfig::default());
+    savings.deposit(10_000.0).unwrap();
+    assert_relative_eq!(funding.charge_savings(&mut savings), 1.0, epsilon = 1e-9);
+    assert_relative_eq!(savings.total_deposits_zai(), 9_999.0, epsilon = 1e-9);
+
+    // Savings can be exempt
+    let mut exempt = FundingRate::new(FundingRateConfig {
+        charge_savings: false,
+        ..FundingRateConfig::default()
+    });
+    exempt.update(1e-4);
+    assert_eq!(exempt.charge_savings(&mut savings), 0.0);
+}
+
+#[test]
+fn test_demand_sells_into_carry() {
+    let mut amm = Amm::new(10_000.0, 500_000.0, 0.003);
+    let mut agent = DemandAgent::new(DemandAgentConfig::default());
+    agent.zai_balance = 10_000.0;
+
+    // 10%/yr carry at 0.001 per 1%/yr: sell 1% of the balance
+    let action = agent.act_with_carry(&mut amm, 50.0, 0.1, 1);
+    match action {
+        AgentAction::BuyZec { zai_spent, .. } => {
+            assert_relative_eq!(zai_spent, 100.0, epsilon = 1e-9)
+        }
+        other => panic!("expected a ZAI sale, got {:?}", other),
+    }
+    assert!(amm.spot_price() > 50.0);
+
+    // Without carry it keeps buying
+    let action = agent.act_with_carry(&mut amm, 50.0, 0.0, 2);
+    assert!(matches!(action, AgentAction::BuyZai { .. }));
+}
+
+fn run(funding_rate: Option<FundingRateConfig>) -> Scenario {
+    let config = ScenarioConfig {
+        funding_rate,
+        ..ScenarioConfig::default()
+    };
+    let mut scenario = Scenario::new_with_seed(&config, 42);
+    add_agents(ScenarioId::BlackThursday, &mut scenario);
+    scenario
+        .demand_agents
+        .push(DemandAgent::new(DemandAgentConfig::default()));
+    scenario.run(&generate_prices(ScenarioId::BlackThursday, 1000, 42));
+    scenario
+}
+
+#[test]
+fn test_scenario_charges_above_par() {
+    let scenario = run(Some(FundingRateConfig::default()));
+    // The crash leaves ZAI above par and the charge kicks in
+    assert!(scenario.metrics.iter().any(|m| m.funding_rate < 0.0));
+    assert!(scenario.metrics.iter().all(|m| m.funding_rate <= 0.0));
+    assert!(scenario
+        .metrics
+        .funding_charged_zai
+        .windows(2)
+        .all(|w| w[1] >= w[0]));
+    let funding = scenario.funding_rate.as_ref().unwrap();
+    assert!(funding.total_charged_zai > 0.0);
+
+    let baseline = run(None);
+    assert!(baseline
+        .metrics
+        .iter()
+        .all(|m| m.funding_charged_zai == 0.0));
+
+    let premium = |s: &Scenario| {
+        s.metrics
+            .iter()
+            .map(|m| ((m.redemption_price - m.amm_spot_price) / m.redemption_price).max(0.0))
+            .sum::<f64>()
+            / s.metrics.len() as f64
+    };
+    println!(
+        "\nMean ZAI premium over par: baseline {:.4}, with funding charge {:.4}",
+        premium(&baseline),
+        premium(&scenario)
+    );
+}
@@ -0,0 +1,90 @@
+//! Property-based fuzzing of whole runs (`cargo test --features fuzz`).
+//!
+//! Random configs, price paths and agent schedules must leave the
+//! accounting invariants intact, cumulative counters monotone and every
+//! metric free of NaN, including with an extra agent added from outside
+//! the crate.
+#![cfg(feature = "fuzz")]
+
+use proptest::prelude::*;
+use zai_sim::fuzz::{
+    agent_schedule, check_counters, check_no_nan, check_run, fuzz_case, price_path,
+    scenario_config, ScheduledAgent,
+};
+use zai_sim::scenario::Scenario;
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(24))]
+
+    #[test]
+    fn generated_runs_hold_properties(case in fuzz_case(300)) {
+        let scenario = case.run(|_| {});
+        prop_assert_eq!(scenario.metrics.len(), 300);
+        check_run(&scenario)?;
+    }
+
+    #[test]
+    fn custom_agent_holds_properties(case in fuzz_case(200), every in 5u64..50) {
+        // A downstream agent: dumps a tenth of the first arber's ZEC on
+        // the AMM every `every` blocks
+        let scenario = case.run(|s| {
+            s.after_step(move |s: &mut Scenario, block| {
+                if block % every == 0 {
+                    let zec = s.arbers[0].zec_balance * 0.1;
+                    if let Ok(zai) = s.amm.swap_zec_for_zai(zec, block) {
+                        s.arbers[0].zec_balance -= zec;
+                        s.arbers[0].zai_balance += zai;
+                    }
+                }
+            })
+        });
+        check_run(&scenario)?;
+    }
+
+    #[test]
+    fn price_paths_stay_positive(path in price_path(50.0, 400)) {
+        prop_assert_eq!(path.len(), 400);
+        prop_assert!(path.iter().all(|p| p.is_finite() && *p > 0.0));
+    }
+
+    #[test]
+    fn configs_enable_recording_checker(config in scenario_config()) {
+        let invariants = config.invariants.as_ref().unwrap();
+        prop_assert!(!invariants.panic_on_violation);
+        prop_assert_eq!(config.initial_redemption_price, config.initial_amm_price());
+    }
+
+    #[test]
+    fn schedules_start_with_an_arber(schedule in agent_schedule(50.0, 100)) {
+        prop_assert!(matches!(schedule.joins[0], (0, ScheduledAgent::Arber(_))));
+        for (block, agent) in &schedule.joins {
+            prop_assert!(*block < 100);
+            if matches!(agent, ScheduledAgent::Miner(_)) {
+                prop_assert_eq!(*block, 0);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_checks_catch_broken_metrics() {
+    let mut scenario = Scenario::new(&Default::default());
+    scenario.run(&[50.0; 20]);
+    let mut metrics = scenario.metrics.clone();
+    assert!(check_counters(&metrics).is_ok());
+    assert!(check_no_nan(&metrics).is_ok());
+
+    metrics.cumulative_fees_zai[10] = metrics.cumulative_fees_zai[9] - 1.0;
+    let err = check_counters(&metrics).unwrap_err().to_string();
+    assert!(err.contains("cumulative_fees_zai fell"), "{}", err);
+
+    let mut metrics = scenario.metrics.clone();
+    metrics.twap_price[3] = f64::NAN;
+    let err = check_no_nan(&metrics).unwrap_err().to_string();
+    assert!(err.contains("twap_price is NaN at block 4"), "{}", err);
+
+    let mut metrics = scenario.metrics.clone();
+    metrics.side_pool_prices[5] = vec![1.0, f64::NAN];
+    let err = check_no_nan(&metrics).unwrap_err().to_string();
+    assert!(err.contains("side_pool_prices is NaN"), "{}", err);
+}
@@ -0,0 +1,168 @@
+//! Per-action transaction fees.
+//!
+//! With a gas model every agent trade, vault operation and liquidation
+//! pays a fee, flat or repriced each block from congestion. Arbers add it
+//! to their profit floor and keepers pass on lots that don't cover it.
+
+use approx::assert_relative_eq;
+use zai_sim::agents::{AgentAction, Arbitrageur, ArbitrageurConfig};
+use zai_sim::amm::Amm;
+use zai_sim::cdp::{CdpConfig, VaultRegistry};
+use zai_sim::gas::{CongestionConfig, GasConfig, GasMarket};
+use zai_sim::liquidation::{
+    KeeperLiquidityConfig, LiquidationConfig, LiquidationEngine, LiquidationMode,
+};
+use zai_sim::scenario::{Scenario, ScenarioConfig};
+use zai_sim::scenarios::{add_agents, generate_prices, ScenarioId};
+
+#[test]
+fn test_fee_paid_from_zec_then_zai() {
+    let mut gas = GasMarket::new(GasConfig::default());
+    let (mut zec, mut zai) = (0.01, 100.0);
+    // Half from ZEC, the other 0.01 ZEC as 0.5 ZAI at 50
+    assert_relative_eq!(gas.pay(&mut zec, &mut zai, 50.0), 0.02, epsilon = 1e-12);
+    assert_eq!(zec, 0.0);
+    assert_relative_eq!(zai, 99.5, epsilon = 1e-12);
+
+    // Only transactions pay
+    let actions = [
+        AgentAction::None,
+        AgentAction::Queued {
+            description: "later".to_string(),
+        },
+        AgentAction::SellZec {
+            zec_spent: 1.0,
+            zai_received: 50.0,
+        },
+    ];
+    let (mut zec, mut zai) = (1.0, 0.0);
+    assert_relative_eq!(gas.charge(&actions, &mut zec, &mut zai, 50.0), 0.02);
+    assert_relative_eq!(zec, 0.98, epsilon = 1e-12);
+    assert_eq!(gas.total_actions, 2);
+    assert_relative_eq!(gas.total_fees_zec, 0.04, epsilon = 1e-12);
+
+    // A flat fee never moves
+    gas.charge_liquidations(100);
+    assert_eq!(gas.begin_block(), 0.02);
+    assert_relative_eq!(gas.keeper_fees_zec, 2.0, epsilon = 1e-12);
+}
+
+#[test]
+fn test_congestion_reprices_fee() {
+    let mut gas = GasMarket::new(GasConfig {
+        congestion: Some(CongestionConfig {
+            target_actions_per_block: 10,
+            ..CongestionConfig::default()
+        }),
+        ..GasConfig::default()
+    });
+    // Over target: up by at most 12.5%
+    gas.charge_liquidations(30);
+    assert_relative_eq!(gas.begin_block(), 0.0225, epsilon = 1e-12);
+    assert_eq!(gas.block_actions, 0);
+    gas.charge_liquidations(15);
+    assert_relative_eq!(gas.begin_block(), 0.0225 * 1.0625, epsilon = 1e-12);
+    // Empty blocks bring it back down to the base fee, not below
+    for _ in 0..10 {
+        gas.begin_block();
+    }
+    assert_eq!(gas.fee_zec, 0.02);
+}
+
+#[test]
+fn test_arber_prices_in_gas() {
+    // AMM at 50 against 55 outside: 10,000 ZAI buys ~195.5 ZEC worth
+    // ~10,753 ZAI, about 753 ZAI of profit
+    let trade = |gas_fee_zai: f64| {
+        let mut amm = Amm::new(10000.0, 500000.0, 0.003);
+        let mut arber = Arbitrageur::new(ArbitrageurConfig::default());
+        arber.gas_fee_zai = gas_fee_zai;
+        arber.act(&mut amm, 55.0, 1)
+    };
+    assert!(matches!(trade(0.0)[..], [AgentAction::BuyZec { .. }]));
+    assert!(matches!(trade(700.0)[..], [AgentAction::BuyZec { .. }]));
+    assert!(trade(800.0).is_empty());
+}
+
+#[test]
+fn test_keepers_skip_lots_under_gas() {
+    let run = |gas_fee_zai: f64, amm_fallback: bool| {
+        let mut amm = Amm::new(10000.0, 500000.0, 0.003);
+        for b in 1..=50 {
+            amm.record_price(b);
+        }
+        let mut registry = VaultRegistry::new(CdpConfig {
+            stability_fee_rate: 0.0,
+            ..CdpConfig::default()
+        });
+        let mut engine = LiquidationEngine::new(LiquidationConfig {
+            keeper_liquidity: Some(KeeperLiquidityConfig {
+                amm_fallback,
+                ..KeeperLiquidityConfig::default()
+            }),
+            ..LiquidationConfig::default()
+        });
+        engine.gas_fee_zai = gas_fee_zai;
+        let id = registry
+            .open_vault("owner", 40.0, 1000.0, 50, &amm)
+            .unwrap();
+        registry.vaults.get_mut(&id).unwrap().collateral_zec = 28.0;
+        registry.reindex(id);
+        engine.keeper_liquidate(&mut registry, &mut amm, 51, 50.0)
+    };
+    // 1,130 ZAI buys ~23.79 ZEC worth ~1,189 ZAI: ~59 ZAI for the keeper
+    assert_eq!(run(50.0, false)[0].mode, LiquidationMode::KeeperPurchase);
+    assert!(run(60.0, false).is_empty());
+    assert_eq!(run(60.0, true)[0].mode, LiquidationMode::OracleLiquidation);
+}
+
+fn scenario(gas: Option<GasConfig>) -> Scenario {
+    let config = ScenarioConfig {
+        gas,
+        ..ScenarioConfig::default()
+    };
+    let mut scenario = Scenario::new_with_seed(&config, 42);
+    add_agents(ScenarioId::BlackThursday, &mut scenario);
+    scenario.run(&generate_prices(ScenarioId::BlackThursday, 500, 42));
+    scenario
+}
+
+#[test]
+fn test_scenario_charges_gas() {
+    let free = scenario(None);
+    assert!(free
+        .metrics
+        .iter()
+        .all(|m| m.gas_fee_zec == 0.0 && m.gas_paid_zec == 0.0));
+
+    let flat = scenario(Some(GasConfig::default()));
+    assert!(flat.metrics.iter().all(|m| m.gas_fee_zec == 0.02));
+    assert!(flat.metrics.gas_paid_zec.windows(2).all(|w| w[1] >= w[0]));
+    let gas = flat.gas.as_ref().unwrap();
+    assert!(gas.total_actions > 0);
+    assert!(flat.metrics.last().unwrap().gas_paid_zec > 0.0);
+
+    let congested = scenario(Some(GasConfig {
+        congestion: Some(CongestionConfig {
+            target_actions_per_block: 1,
+            ..CongestionConfig::default()
+        }),
+        ..GasConfig::default()
+    }));
+    let peak = congested
+        .metrics
+        .iter()
+        .map(|m| m.gas_fee_zec)
+        .fold(0.0, f64::max);
+    assert!(peak > 0.02, "{}", peak);
+
+    let arber_zai = |s: &Scenario| s.metrics.last().unwrap().arber_zai_total;
+    println!(
+        "\nGas paid: flat {:.2} ZEC, congested {:.2} ZEC (peak fee {:.4}); arber ZAI free {:.0}, flat {:.0}",
+        flat.metrics.last().unwrap().gas_paid_zec,
+        congested.metrics.last().unwrap().gas_paid_zec,
+        peak,
+        arber_zai(&free),
+        arber_zai(&flat)
+    );
+}
@@ -0,0 +1,103 @@
+//! Governance agent with reaction latency.
+//!
+//! A `GovernanceAgent` watches block metrics and, once a rule's threshold
+//! has been breached for long enough, enacts parameter changes after a
+//! voting delay.
+
+use std::sync::{Arc, Mutex};
+
+use zai_sim::agents::{Arbitrageur, ArbitrageurConfig};
+use zai_sim::governance::{
+    GovernanceAgent, GovernanceAgentConfig, GovernanceMetric, GovernanceRule, ParameterSet,
+};
+use zai_sim::scenario::{Scenario, ScenarioConfig};
+use zai_sim::scenarios::{run_stress_with, ScenarioId};
+
+fn fee_rule(metric: GovernanceMetric, threshold: f64, breach_blocks: u64) -> GovernanceRule {
+    GovernanceRule {
+        metric,
+        threshold,
+        breach_blocks,
+        changes: vec![ParameterSet {
+            param: "stability_fee_rate".to_string(),
+            value: 0.10,
+        }],
+    }
+}
+
+fn agent(rules: Vec<GovernanceRule>, voting_delay_blocks: u64) -> GovernanceAgent {
+    GovernanceAgent::new(GovernanceAgentConfig {
+        rules,
+        voting_delay_blocks,
+    })
+}
+
+#[test]
+fn test_change_enacted_after_voting_delay() {
+    let mut scenario = Scenario::new(&ScenarioConfig::default());
+    scenario
+        .arbers
+        .push(Arbitrageur::new(ArbitrageurConfig::default()));
+    // Always breached: proposes once three blocks have been observed
+    scenario.governance_agents.push(agent(
+        vec![fee_rule(GovernanceMetric::PegDeviation, -1.0, 3)],
+        10,
+    ));
+
+    let fees = Arc::new(Mutex::new(Vec::new()));
+    let sink = fees.clone();
+    scenario.after_step(move |s, _| {
+        sink.lock()
+            .unwrap()
+            .push(s.registry.config.stability_fee_rate)
+    });
+    scenario.run(&[50.0; 30]);
+
+    let gov = &scenario.governance_agents[0];
+    assert_eq!(gov.proposals.len(), 1);
+    assert_eq!(gov.proposals[0].proposed_at, 3);
+    assert_eq!(gov.proposals[0].enacted_at, 13);
+
+    let fees = fees.lock().unwrap();
+    assert_eq!(fees[11], 0.02);
+    assert_eq!(fees[12], 0.10);
+    assert_eq!(scenario.config.cdp_config.stability_fee_rate, 0.10);
+}
+
+#[test]
+fn test_no_breach_no_proposal() {
+    let mut scenario = Scenario::new(&ScenarioConfig::default());
+    scenario.governance_agents.push(agent(
+        vec![fee_rule(GovernanceMetric::Liquidations, 0.0, 1)],
+        10,
+    ));
+    scenario.run(&[50.0; 50]);
+    assert!(scenario.governance_agents[0].proposals.is_empty());
+    assert_eq!(scenario.registry.config.stability_fee_rate, 0.02);
+}
+
+#[test]
+fn test_latency_delays_black_thursday_response() {
+    let rules = || vec![fee_rule(GovernanceMetric::PegDeviation, 0.05, 12)];
+    let run = |delay| {
+        run_stress_with(
+            ScenarioId::BlackThursday,
+            &ScenarioConfig::default(),
+            1000,
+            42,
+            |s| s.governance_agents.push(agent(rules(), delay)),
+        )
+    };
+    let fast = run(12);
+    let slow = run(288);
+
+    let (f, s) = (
+        &fast.governance_agents[0].proposals,
+        &slow.governance_agents[0].proposals,
+    );
+    assert_eq!(f.len(), 1);
+    assert_eq!(s.len(), 1);
+    // Same breach, different reaction time
+    assert_eq!(f[0].proposed_at, s[0].proposed_at);
+    assert_eq!(s[0].enacted_at - f[0].enacted_at, 276);
+}
@@ -0,0 +1,97 @@
+//! Mid-run governance parameter changes.
+//!
+//! A `ParameterSchedule` in `ScenarioConfig` sets config values at given
+//! blocks, applied at the start of the block inside `Scenario::step`.
+
+use zai_sim::agents::{Arbitrageur, ArbitrageurConfig};
+use zai_sim::governance::{ParameterChange, ParameterSchedule};
+use zai_sim::scenario::{Scenario, ScenarioConfig};
+
+fn scheduled(schedule: ParameterSchedule) -> Scenario {
+    let config = ScenarioConfig {
+        parameter_schedule: schedule,
+        ..ScenarioConfig::default()
+    };
+    let mut scenario = Scenario::new(&config);
+    scenario
+        .arbers
+        .push(Arbitrageur::new(ArbitrageurConfig::default()));
+    scenario
+}
+
+#[test]
+fn test_parse_change() {
+    let change: ParameterChange = "400:min_ratio=2.5".parse().unwrap();
+    assert_eq!(
+        change,
+        ParameterChange {
+            block: 400,
+            param: "min_ratio".to_string(),
+            value: 2.5,
+        }
+    );
+    assert!("min_ratio=2.5".parse::<ParameterChange>().is_err());
+    assert!("400:min_ratio".parse::<ParameterChange>().is_err());
+    assert!("x:min_ratio=2.5".parse::<ParameterChange>().is_err());
+}
+
+#[test]
+fn test_changes_apply_at_their_block() {
+    let schedule = ParameterSchedule::new()
+        .at(40, "min_ratio", 2.5)
+        .at(80, "swap_fee", 0.001);
+    let mut scenario = scheduled(schedule);
+    let prices = vec![50.0; 100];
+
+    scenario.run(&prices[..39]);
+    assert_eq!(scenario.registry.config.min_ratio, 1.5);
+    scenario.run(&prices[..40]);
+    assert_eq!(scenario.registry.config.min_ratio, 2.5);
+    assert_eq!(scenario.config.cdp_config.min_ratio, 2.5);
+    assert_eq!(scenario.amm.swap_fee, 0.003);
+
+    scenario.run(&prices);
+    assert_eq!(scenario.amm.swap_fee, 0.001);
+    assert_eq!(scenario.config.amm_swap_fee, 0.001);
+}
+
+#[test]
+fn test_dotted_path_changes() {
+    let schedule = ParameterSchedule::new().at(10, "twap_breaker_config.max_twap_change_pct", 0.5);
+    let mut scenario = scheduled(schedule);
+    scenario.run(&[50.0; 20]);
+    assert_eq!(
+        scenario.breakers.twap_breaker.config.max_twap_change_pct,
+        0.5
+    );
+}
+
+#[test]
+fn test_min_ratio_change_blocks_new_minting() {
+    let mut scenario = scheduled(ParameterSchedule::new().at(5, "min_ratio", 2.5));
+    scenario.run(&[50.0; 4]);
+    // CR 2.0 is fine under the initial 1.5 minimum
+    assert!(scenario
+        .registry
+        .open_vault("before", 40.0, 1000.0, 4, &scenario.amm)
+        .is_ok());
+    scenario.run(&[50.0; 5]);
+    assert!(scenario
+        .registry
+        .open_vault("after", 40.0, 1000.0, 5, &scenario.amm)
+        .is_err());
+}
+
+#[test]
+fn test_validate_rejects_unknown_params() {
+    let config = ScenarioConfig::default();
+    assert!(ParameterSchedule::new()
+        .at(10, "min_ratio", 2.0)
+        .validate(&config)
+        .is_ok());
+    let err = ParameterSchedule::new()
+        .at(10, "not_a_param", 2.0)
+        .validate(&config)
+        .unwrap_err();
+    assert!(err.to_string().contains("block 10"), "{}", err);
+}
@@ -0,0 +1,172 @@
+//! Liquidation grace period with owner top-up priority.
+//!
+//! After a vault first drops below min_ratio, keepers and the system must
+//! wait `grace_period_blocks` before liquidating it, giving the owner a window
+//! to top up. This trades unnecessary liquidations (vaults that would have
+//! recovered) against the bad-debt risk of liquidating later.
+
+use zai_sim::agents::{CdpHolder, CdpHolderConfig};
+use zai_sim::amm::Amm;
+use zai_sim::cdp::{CdpConfig, VaultRegistry};
+use zai_sim::error::ZaiSimError;
+use zai_sim::liquidation::{LiquidationConfig, LiquidationEngine};
+use zai_sim::scenario::{Scenario, ScenarioConfig};
+use zai_sim::scenarios::{add_agents, generate_prices, ScenarioId};
+
+const BLOCKS: usize = 1000;
+const SEED: u64 = 42;
+const TARGET_PRICE: f64 = 50.0;
+
+fn setup(grace: u64) -> (Amm, VaultRegistry, LiquidationEngine, u64) {
+    let mut amm = Amm::new(10000.0, 500000.0, 0.003);
+    for b in 1..=50 {
+        amm.record_price(b);
+    }
+    let mut registry = VaultRegistry::new(CdpConfig {
+        stability_fee_rate: 0.0,
+        ..CdpConfig::default()
+    });
+    let engine = LiquidationEngine::new(LiquidationConfig {
+        grace_period_blocks: grace,
+        ..LiquidationConfig::default()
+    });
+    let id = registry.open_vault("owner", 40.0, 1000.0, 50, &amm).unwrap();
+    // Knock the vault to CR 1.4 at the TWAP price
+    registry.vaults.get_mut(&id).unwrap().collateral_zec = 28.0;
+    registry.reindex(id);
+    (amm, registry, engine, id)
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+// Engine mechanics
+// ═══════════════════════════════════════════════════════════════════════
+
+#[test]
+fn test_no_grace_liquidates_immediately() {
+    let (mut amm, mut registry, mut engine, id) = setup(0);
+    let results = engine.transparent_liquidate(&mut registry, &mut amm, 51);
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].vault_id, id);
+}
+
+#[test]
+fn test_grace_delays_system_liquidation() {
+    let (mut amm, mut registry, mut engine, id) = setup(10);
+
+    for block in 51..61 {
+        let results = engine.transparent_liquidate(&mut registry, &mut amm, block);
+        assert!(results.is_empty(), "Block {} is inside the grace window", block);
+        assert_eq!(engine.vaults_in_grace(block), 1);
+    }
+
+    let results = engine.transparent_liquidate(&mut registry, &mut amm, 61);
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].vault_id, id);
+}
+
+#[test]
+fn test_keeper_blocked_but_owner_may_self_liquidate() {
+    let (mut amm, mut registry, mut engine, id) = setup(10);
+
+    assert!(matches!(
+        engine.challenge_liquidate(id, "keeper", &mut registry, &mut amm, 51),
+        Err(ZaiSimError::InGracePeriod(_))
+    ));
+    assert!(registry.get_vault(id).is_some());
+
+    assert!(engine.self_liquidate(id, &mut registry, &mut amm, 52).is_ok());
+    assert!(registry.get_vault(id).is_none());
+}
+
+#[test]
+fn test_owner_top_up_during_grace_counts_as_recovery() {
+    let (mut amm, mut registry, mut engine, id) = setup(10);
+    let twap = amm.get_twap(registry.config.twap_window);
+
+    assert!(engine.transparent_liquidate(&mut registry, &mut amm, 51).is_empty());
+
+    registry.deposit_collateral(id, 20.0).unwrap();
+    engine.update_grace(&registry, twap);
+    assert_eq!(engine.grace_recoveries, 1);
+    assert_eq!(engine.vaults_in_grace(52), 0);
+
+    // A later dip starts a fresh window
+    registry.vaults.get_mut(&id).unwrap().collateral_zec = 28.0;
+    registry.reindex(id);
+    assert!(engine.transparent_liquidate(&mut registry, &mut amm, 100).is_empty());
+    assert_eq!(engine.vaults_in_grace(100), 1);
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+// Unnecessary liquidations vs bad-debt risk
+// ═══════════════════════════════════════════════════════════════════════
+
+struct GraceOutcome {
+    liquidations: u32,
+    recoveries: u32,
+    bad_debt: f64,
+}
+
+fn run(sid: ScenarioId, grace: u64) -> GraceOutcome {
+    let mut config = ScenarioConfig::default();
+    config.liquidation_config.grace_period_blocks = grace;
+
+    let mut scenario = Scenario::new_with_seed(&config, SEED);
+    add_agents(sid, &mut scenario);
+
+    // Owners who top up from a modest reserve, packed just above min_ratio
+    for i in 0..20 {
+        let cr = config.cdp_config.min_ratio + 0.05 + i as f64 * 0.03;
+        scenario.cdp_holders.push(CdpHolder::new(CdpHolderConfig {
+            target_ratio: 2.0,
+            action_threshold_ratio: 1.6,
+            reserve_zec: 5.0 + i as f64,
+            initial_collateral: cr * 1000.0 / TARGET_PRICE,
+            initial_debt: 1000.0,
+        }));
+    }
+
+    let prices = generate_prices(sid, BLOCKS, SEED);
+    scenario.run(&prices);
+
+    GraceOutcome {
+        liquidations: scenario.metrics.iter().map(|m| m.liquidation_count).sum(),
+        recoveries: scenario.liquidation_engine.grace_recoveries,
+        bad_debt: scenario.liquidation_engine.total_bad_debt,
+    }
+}
+
+#[test]
+fn test_grace_period_tradeoff() {
+    let graces = [0u64, 5, 20, 60];
+    println!();
+    println!(
+        "  {:<18} {:>6} {:>8} {:>10} {:>10}",
+        "Scenario", "Grace", "Liqs", "Recovered", "Bad Debt"
+    );
+
+    for sid in [
+        ScenarioId::FlashCrash,
+        ScenarioId::BlackThursday,
+        ScenarioId::SustainedBear,
+    ] {
+        let outcomes: Vec<GraceOutcome> = graces.iter().map(|&g| run(sid, g)).collect();
+        for (g, o) in graces.iter().zip(&outcomes) {
+            println!(
+                "  {:<18} {:>6} {:>8} {:>10} {:>10.2}",
+                sid.name(),
+                g,
+                o.liquidations,
+                o.recoveries,
+                o.bad_debt
+            );
+        }
+
+        assert_eq!(outcomes[0].recoveries, 0, "No grace means no grace recoveries");
+        assert!(
+            outcomes.last().unwrap().liquidations <= outcomes[0].liquidations,
+            "{}: a grace window should never add liquidations",
+            sid.name()
+        );
+    }
+}
@@ -34,9 +34,11 @@ fn recovery_prices(blocks: usize) -> Vec<f64> {
 }
 
 fn config_with_depth(graduated: bool, amm_zec: f64, amm_zai: f64) -> ScenarioConfig {
-    let mut config = ScenarioConfig::default();
-    config.amm_initial_zec = amm_zec;
-    config.amm_initial_zai = amm_zai;
+    let mut config = ScenarioConfig {
+        amm_initial_zec: amm_zec,
+        amm_initial_zai: amm_zai,
+        ..ScenarioConfig::default()
+    };
     config.cdp_config.min_ratio = 2.0;
     config.cdp_config.twap_window = 240;
     config.controller_config = ControllerConfig::default_tick();
@@ -143,11 +145,11 @@ fn analyze(scenario: &Scenario, label: &str, mode: &str, target: f64) -> Graduat
 
     // Death spiral detection: AMM price dropped >90% with no recovery in last 100 blocks
     let death_spiral = if scenario.metrics.len() > 200 {
-        let initial = scenario.metrics[0].amm_spot_price;
+        let initial = scenario.metrics.amm_spot_price[0];
         let final_price = scenario.metrics.last().unwrap().amm_spot_price;
         let dropped = final_price < initial * 0.1;
-        let last_100 = &scenario.metrics[scenario.metrics.len() - 100..];
-        let no_recovery = last_100.iter().all(|m| m.amm_spot_price < initial * 0.15);
+        let last_100 = &scenario.metrics.amm_spot_price[scenario.metrics.len() - 100..];
+        let no_recovery = last_100.iter().all(|p| *p < initial * 0.15);
         dropped && no_recovery
     } else {
         false
@@ -12,7 +12,8 @@ use zai_sim::controller::ControllerConfig;
 use zai_sim::output;
 use zai_sim::report;
 use zai_sim::scenario::{Scenario, ScenarioConfig};
-use zai_sim::scenarios::{add_agents, ScenarioId};
+use zai_sim::scenario_builder::{ScenarioBuilder, VaultPopulation};
+use zai_sim::scenarios::ScenarioId;
 
 const BLOCKS: usize = 1000;
 const SEED: u64 = 42;
@@ -104,9 +105,11 @@ fn configs() -> Vec<GriefConfig> {
 }
 
 fn make_config(gc: &GriefConfig) -> ScenarioConfig {
-    let mut config = ScenarioConfig::default();
-    config.amm_initial_zec = gc.amm_zec;
-    config.amm_initial_zai = gc.amm_zai;
+    let mut config = ScenarioConfig {
+        amm_initial_zec: gc.amm_zec,
+        amm_initial_zai: gc.amm_zai,
+        ..ScenarioConfig::default()
+    };
     config.cdp_config.min_ratio = gc.min_ratio;
     config.cdp_config.twap_window = gc.twap_window;
     config.controller_config = ControllerConfig::default_tick();
@@ -193,30 +196,19 @@ struct GriefRow {
 // ═══════════════════════════════════════════════════════════════════════
 
 fn setup_scenario(config: &ScenarioConfig) -> Scenario {
-    let mut scenario = Scenario::new_with_seed(config, SEED);
-    add_agents(ScenarioId::SteadyState, &mut scenario);
-
-    // Initialize LP agents
-    for lp in &mut scenario.lp_agents {
-        lp.provide_liquidity(&mut scenario.amm);
-    }
-    for lp in &mut scenario.il_aware_lps {
-        lp.provide_liquidity(&mut scenario.amm);
-    }
-
-    // Open 25 vaults at CR spread from min_ratio+0.10 to min_ratio+0.80
+    // 25 vaults at CR spread from min_ratio+0.10 to min_ratio+0.80
     let base_cr = config.cdp_config.min_ratio + 0.10;
-    for i in 0..NUM_VAULTS {
-        let cr = base_cr + (i as f64) * 0.70 / (NUM_VAULTS - 1) as f64;
-        let collateral = cr * VAULT_DEBT / TARGET_PRICE;
-        let owner = format!("vault_{}", i);
-        scenario
-            .registry
-            .open_vault(&owner, collateral, VAULT_DEBT, 0, &scenario.amm)
-            .unwrap();
-    }
-
-    scenario
+    ScenarioBuilder::new(config.clone())
+        .with_seed(SEED)
+        .with_stress_agents(ScenarioId::SteadyState)
+        .with_vault_population(VaultPopulation::new(
+            NUM_VAULTS,
+            VAULT_DEBT,
+            base_cr,
+            base_cr + 0.70,
+        ))
+        .build()
+        .unwrap()
 }
 
 fn run_griefing(gc: &GriefConfig) -> (GriefRow, Scenario) {
@@ -233,11 +225,11 @@ fn run_griefing(gc: &GriefConfig) -> (GriefRow, Scenario) {
         let block = i as u64 + 1;
 
         // Sustained attack: dump 1K ZEC/block for blocks 241-340
-        if block >= WARMUP + 1 && block <= WARMUP + 100 {
+        if (WARMUP + 1..=WARMUP + 100).contains(&block) {
             whale.sell_zec(1_000.0, &mut scenario.amm, block);
         }
         // Buyback: spread over blocks 341-350
-        else if block >= WARMUP + 101 && block <= WARMUP + 110 {
+        else if (WARMUP + 101..=WARMUP + 110).contains(&block) {
             let remaining = (WARMUP + 111 - block) as f64;
             whale.buy_zec(whale.zai / remaining, &mut scenario.amm, block);
         }
@@ -246,7 +238,7 @@ fn run_griefing(gc: &GriefConfig) -> (GriefRow, Scenario) {
 
         // Track min TWAP after warmup
         if block > WARMUP {
-            let twap = scenario.amm.get_twap(config.cdp_config.twap_window as u64);
+            let twap = scenario.amm.get_twap(config.cdp_config.twap_window);
             min_twap = min_twap.min(twap);
         }
     }
@@ -471,8 +463,7 @@ fn griefing_mitigation() {
     let best = all_rows
         .iter()
         .enumerate()
-        .filter(|(_, r)| r.bad_debt == 0.0)
-        .next()
+        .find(|(_, r)| r.bad_debt == 0.0)
         .or_else(|| {
             all_rows
                 .iter()
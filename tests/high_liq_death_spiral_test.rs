@@ -29,9 +29,11 @@ struct HighLiqConfig {
 }
 
 fn base_config(amm_zec: f64, amm_zai: f64) -> ScenarioConfig {
-    let mut config = ScenarioConfig::default();
-    config.amm_initial_zec = amm_zec;
-    config.amm_initial_zai = amm_zai;
+    let mut config = ScenarioConfig {
+        amm_initial_zec: amm_zec,
+        amm_initial_zai: amm_zai,
+        ..ScenarioConfig::default()
+    };
     config.cdp_config.min_ratio = 2.0;
     config.cdp_config.twap_window = 240;
     config.controller_config = ControllerConfig::default_tick();
@@ -153,9 +155,11 @@ fn high_liq_death_spiral() {
             gap_pct < 0.10 && m.external_price < 30.0 // AMM within 10% of crashed external
         });
 
-        let had_cascading_liqs = scenario.metrics.windows(10).any(|w| {
-            w.iter().map(|m| m.liquidation_count).sum::<u32>() > 3
-        });
+        let had_cascading_liqs = scenario
+            .metrics
+            .liquidation_count
+            .windows(10)
+            .any(|w| w.iter().sum::<u32>() > 3);
 
         if amm_tracked_external {
             println!("    → AMM TRACKED external during crash (defense bypassed)");
@@ -0,0 +1,91 @@
+//! Per-block hooks.
+//!
+//! `before_step` / `after_step` get mutable access around each block for
+//! interventions; `on_block` observes each finished block for custom metrics.
+
+use std::sync::{Arc, Mutex};
+
+use zai_sim::agents::{Arbitrageur, ArbitrageurConfig};
+use zai_sim::scenario::{Scenario, ScenarioConfig};
+
+fn scenario() -> Scenario {
+    let mut scenario = Scenario::new(&ScenarioConfig::default());
+    scenario
+        .arbers
+        .push(Arbitrageur::new(ArbitrageurConfig::default()));
+    scenario
+}
+
+fn crash_prices() -> Vec<f64> {
+    (0..100).map(|i| 50.0 - 0.2 * i as f64).collect()
+}
+
+#[test]
+fn test_on_block_sees_each_block() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let mut s = scenario();
+    let sink = seen.clone();
+    s.on_block(move |snap| {
+        assert_eq!(snap.metrics.block, snap.block);
+        assert_eq!(snap.scenario.metrics.len() as u64, snap.block);
+        sink.lock()
+            .unwrap()
+            .push((snap.block, snap.scenario.amm.spot_price()));
+    });
+    s.run(&crash_prices());
+
+    let seen = seen.lock().unwrap();
+    assert_eq!(seen.len(), 100);
+    for ((block, price), m) in seen.iter().zip(&s.metrics) {
+        assert_eq!(*block, m.block);
+        assert_eq!(*price, m.amm_spot_price);
+    }
+}
+
+#[test]
+fn test_before_step_applies_governance_change() {
+    let mut s = scenario();
+    s.before_step(|scenario, block| {
+        if block == 50 {
+            scenario.amm.swap_fee = 0.01;
+            scenario.registry.config.min_ratio = 2.0;
+        }
+    });
+    let fees = Arc::new(Mutex::new(Vec::new()));
+    let sink = fees.clone();
+    s.after_step(move |scenario, _| sink.lock().unwrap().push(scenario.amm.swap_fee));
+    s.run(&crash_prices());
+
+    let fees = fees.lock().unwrap();
+    assert_eq!(fees[48], 0.003);
+    assert_eq!(fees[49], 0.01);
+    assert_eq!(s.registry.config.min_ratio, 2.0);
+}
+
+#[test]
+fn test_observing_hooks_do_not_change_results() {
+    let mut plain = scenario();
+    plain.run(&crash_prices());
+
+    let mut hooked = scenario();
+    hooked.before_step(|_, _| {});
+    hooked.after_step(|_, _| {});
+    hooked.on_block(|_| {});
+    hooked.run(&crash_prices());
+
+    for (a, b) in plain.metrics.iter().zip(&hooked.metrics) {
+        assert_eq!(a.amm_spot_price, b.amm_spot_price);
+        assert_eq!(a.redemption_price, b.redemption_price);
+    }
+}
+
+#[test]
+fn test_hooks_run_once_per_block_with_wicks() {
+    let calls = Arc::new(Mutex::new(0));
+    let mut s = scenario();
+    let counter = calls.clone();
+    s.before_step(move |_, _| *counter.lock().unwrap() += 1);
+    let paths: Vec<Vec<f64>> = (0..20).map(|_| vec![50.0, 55.0, 45.0, 50.0]).collect();
+    s.run_paths(&paths);
+    assert_eq!(*calls.lock().unwrap(), 20);
+}
@@ -0,0 +1,173 @@
+//! Accounting invariant checks.
+//!
+//! Clean runs pass every check; state corrupted from a hook is reported at
+//! the block it happened, with the block's events in the diagnostic.
+
+use zai_sim::agents::{CdpHolder, CdpHolderConfig};
+use zai_sim::invariants::{Invariant, InvariantConfig, InvariantViolation};
+use zai_sim::scenario::{Scenario, ScenarioConfig};
+use zai_sim::scenarios::{run_stress, run_stress_with, ScenarioId};
+
+fn config(panic_on_violation: bool) -> ScenarioConfig {
+    ScenarioConfig {
+        invariants: Some(InvariantConfig {
+            panic_on_violation,
+            ..InvariantConfig::default()
+        }),
+        ..ScenarioConfig::default()
+    }
+}
+
+fn violations(scenario: &Scenario) -> &[InvariantViolation] {
+    &scenario.invariants.as_ref().unwrap().violations
+}
+
+/// Stress scenarios come without vaults; give them ten thinly
+/// collateralized ones that Black Thursday liquidates.
+fn add_vaults(scenario: &mut Scenario) {
+    for i in 0..10 {
+        let size = (i % 3 + 1) as f64;
+        scenario.cdp_holders.push(CdpHolder::new(CdpHolderConfig {
+            target_ratio: 1.6,
+            action_threshold_ratio: 1.2,
+            reserve_zec: 0.0,
+            initial_collateral: 40.0 * size,
+            initial_debt: 1250.0 * size,
+        }));
+    }
+}
+
+/// Run Black Thursday with vaults and `corrupt` applied after `block`.
+fn corrupted(block: u64, corrupt: fn(&mut Scenario)) -> Scenario {
+    run_stress_with(ScenarioId::BlackThursday, &config(false), 200, 42, |s| {
+        add_vaults(s);
+        s.after_step(move |s, b| {
+            if b == block {
+                corrupt(s);
+            }
+        });
+    })
+}
+
+#[test]
+fn test_clean_runs_pass() {
+    for id in [
+        ScenarioId::SteadyState,
+        ScenarioId::BlackThursday,
+        ScenarioId::SustainedBear,
+    ] {
+        let scenario = run_stress_with(id, &config(false), 500, 42, add_vaults);
+        assert!(
+            violations(&scenario).is_empty(),
+            "{}: {}",
+            id.name(),
+            violations(&scenario)[0]
+        );
+    }
+    // Off by default
+    let scenario = run_stress(ScenarioId::SteadyState, &ScenarioConfig::default(), 50, 42);
+    assert!(scenario.invariants.is_none());
+}
+
+#[test]
+fn test_total_debt_drift_reported() {
+    let scenario = corrupted(60, |s| s.registry.total_debt += 1000.0);
+    let v = &violations(&scenario)[0];
+    assert_eq!(v.block, 60);
+    assert_eq!(v.invariant, Invariant::TotalDebt);
+    assert!(v.detail.contains("registry total_debt"), "{}", v.detail);
+    // The drift persists, so every later block reports it too
+    assert!(violations(&scenario)
+        .iter()
+        .all(|v| v.invariant == Invariant::TotalDebt));
+    assert_eq!(violations(&scenario).len(), 200 - 60 + 1);
+}
+
+#[test]
+fn test_amm_k_drift_reported() {
+    let scenario = corrupted(30, |s| s.amm.k *= 1.01);
+    let first = &violations(&scenario)[0];
+    assert_eq!(first.block, 30);
+    assert_eq!(first.invariant, Invariant::AmmK);
+
+    // Minting LP shares out of nothing dilutes sqrt(k) per share
+    let scenario = corrupted(30, |s| s.amm.total_lp_shares *= 1.5);
+    let kinds: Vec<Invariant> = violations(&scenario)
+        .iter()
+        .filter(|v| v.block == 30)
+        .map(|v| v.invariant)
+        .collect();
+    assert!(kinds.contains(&Invariant::AmmKPerShare), "{:?}", kinds);
+    assert!(kinds.contains(&Invariant::LpShares), "{:?}", kinds);
+}
+
+#[test]
+fn test_negative_balance_reported() {
+    let scenario = corrupted(10, |s| {
+        let id = *s.registry.vaults.keys().min().unwrap();
+        s.registry.vaults.get_mut(&id).unwrap().collateral_zec = -1.0;
+        s.registry.reindex(id);
+    });
+    let v = &violations(&scenario)[0];
+    assert_eq!(v.block, 10);
+    assert_eq!(v.invariant, Invariant::NonNegative);
+    assert!(v.detail.contains("collateral is -1"), "{}", v.detail);
+}
+
+#[test]
+fn test_diagnostic_lists_block_events() {
+    // Corrupt the first block with a liquidation
+    let clean = run_stress_with(
+        ScenarioId::BlackThursday,
+        &config(false),
+        200,
+        42,
+        add_vaults,
+    );
+    let block = clean
+        .liquidation_engine
+        .history
+        .first()
+        .expect("Black Thursday liquidates")
+        .block;
+    let liquidated = clean
+        .liquidation_engine
+        .history
+        .iter()
+        .filter(|r| r.block == block)
+        .count();
+
+    let scenario = run_stress_with(ScenarioId::BlackThursday, &config(false), 200, 42, |s| {
+        add_vaults(s);
+        s.after_step(move |s, b| {
+            if b == block {
+                s.treasury.balance_zai = -50.0;
+            }
+        });
+    });
+    let v = &violations(&scenario)[0];
+    assert_eq!(v.block, block);
+    let liquidations = v
+        .events
+        .iter()
+        .filter(|e| e.starts_with("liquidation of vault"))
+        .count();
+    assert_eq!(liquidations, liquidated);
+    let text = v.to_string();
+    assert!(text.starts_with(&format!(
+        "invariant non_negative violated at block {}: treasury balance is -50",
+        block
+    )));
+}
+
+#[test]
+#[should_panic(expected = "invariant total_debt violated at block 5")]
+fn test_panics_when_configured() {
+    run_stress_with(ScenarioId::SteadyState, &config(true), 20, 42, |s| {
+        s.after_step(|s, b| {
+            if b == 5 {
+                s.registry.total_debt -= 1.0;
+            }
+        })
+    });
+}
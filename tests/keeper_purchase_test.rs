@@ -0,0 +1,169 @@
+//! Keeper purchase of liquidated collateral.
+//!
+//! Keepers pay a vault's debt plus penalty in ZAI and take its collateral
+//! at a discount to the eligibility price, so liquidations never sell on
+//! the AMM. Comparing against AMM routing isolates how much of a death
+//! spiral comes from pushing seized collateral through thin liquidity.
+
+use approx::assert_relative_eq;
+use zai_sim::agents::{CdpHolder, CdpHolderConfig};
+use zai_sim::amm::Amm;
+use zai_sim::cdp::{CdpConfig, VaultRegistry};
+use zai_sim::liquidation::{
+    KeeperLiquidityConfig, LiquidationConfig, LiquidationEngine, LiquidationMode,
+};
+use zai_sim::scenario::{Scenario, ScenarioConfig};
+use zai_sim::scenarios::{add_agents, generate_prices, ScenarioId};
+
+fn setup(
+    keepers: KeeperLiquidityConfig,
+    collateral: f64,
+) -> (Amm, VaultRegistry, LiquidationEngine, u64) {
+    let mut amm = Amm::new(10000.0, 500000.0, 0.003);
+    for b in 1..=50 {
+        amm.record_price(b);
+    }
+    let mut registry = VaultRegistry::new(CdpConfig {
+        stability_fee_rate: 0.0,
+        ..CdpConfig::default()
+    });
+    let engine = LiquidationEngine::new(LiquidationConfig {
+        keeper_liquidity: Some(keepers),
+        ..LiquidationConfig::default()
+    });
+    // 1,000 ZAI opened at CR 2.0, then knocked down to `collateral`
+    let id = registry
+        .open_vault("owner", 40.0, 1000.0, 50, &amm)
+        .unwrap();
+    registry.vaults.get_mut(&id).unwrap().collateral_zec = collateral;
+    registry.reindex(id);
+    (amm, registry, engine, id)
+}
+
+#[test]
+fn test_keepers_buy_at_discount_off_amm() {
+    // CR 1.4 against a 1.5 minimum
+    let (mut amm, mut registry, mut engine, id) = setup(KeeperLiquidityConfig::default(), 28.0);
+    let results = engine.keeper_liquidate(&mut registry, &mut amm, 51, 50.0);
+    assert_eq!(results.len(), 1);
+    let r = &results[0];
+    assert_eq!(r.mode, LiquidationMode::KeeperPurchase);
+
+    // Debt plus 13% penalty, paid for ZEC at 47.5
+    assert_relative_eq!(r.zai_from_keepers, 1130.0, epsilon = 1e-9);
+    assert_relative_eq!(r.collateral_seized, 1130.0 / 47.5, epsilon = 1e-9);
+    assert_relative_eq!(r.penalty_amount, 130.0, epsilon = 1e-9);
+    assert_eq!(r.zai_from_amm, 0.0);
+    assert_eq!(r.bad_debt, 0.0);
+    assert!(!registry.vaults.contains_key(&id));
+    assert_relative_eq!(registry.total_debt, 0.0, epsilon = 1e-9);
+
+    // The AMM is untouched; keepers hold the collateral
+    assert_eq!(amm.reserve_zec, 10000.0);
+    assert_eq!(amm.reserve_zai, 500000.0);
+    assert_relative_eq!(engine.keeper_zai, 1_000_000.0 - 1130.0, epsilon = 1e-6);
+    assert_relative_eq!(engine.keeper_zec, r.collateral_seized);
+}
+
+#[test]
+fn test_underwater_vault_leaves_bad_debt() {
+    // Collateral worth 950 ZAI to keepers against 1,000 debt
+    let (mut amm, mut registry, mut engine, _) = setup(KeeperLiquidityConfig::default(), 20.0);
+    let results = engine.keeper_liquidate(&mut registry, &mut amm, 51, 50.0);
+    let r = &results[0];
+    assert_eq!(r.collateral_seized, 20.0);
+    assert_relative_eq!(r.zai_from_keepers, 950.0, epsilon = 1e-9);
+    assert_relative_eq!(r.bad_debt, 50.0, epsilon = 1e-9);
+    assert_eq!(r.penalty_amount, 0.0);
+    assert_relative_eq!(engine.total_bad_debt, 50.0, epsilon = 1e-9);
+}
+
+#[test]
+fn test_keeper_funds_limit_liquidations() {
+    let keepers = KeeperLiquidityConfig {
+        initial_zai: 500.0,
+        replenish_zai_per_block: 10.0,
+        ..KeeperLiquidityConfig::default()
+    };
+
+    // 500 + 510 ZAI by block 51 cannot pay 1,130: the vault waits
+    let (mut amm, mut registry, mut engine, id) = setup(keepers.clone(), 28.0);
+    assert!(engine
+        .keeper_liquidate(&mut registry, &mut amm, 51, 50.0)
+        .is_empty());
+    assert!(registry.vaults.contains_key(&id));
+
+    // By block 70 keepers have 1,200
+    let results = engine.keeper_liquidate(&mut registry, &mut amm, 70, 50.0);
+    assert_eq!(results.len(), 1);
+    assert_relative_eq!(engine.keeper_zai, 70.0, epsilon = 1e-6);
+
+    // With the fallback, an unaffordable lot is sold on the AMM instead
+    let (mut amm, mut registry, mut engine, id) = setup(
+        KeeperLiquidityConfig {
+            amm_fallback: true,
+            ..keepers
+        },
+        28.0,
+    );
+    let results = engine.keeper_liquidate(&mut registry, &mut amm, 51, 50.0);
+    assert_eq!(results[0].mode, LiquidationMode::OracleLiquidation);
+    assert!(results[0].zai_from_amm > 0.0);
+    assert!(amm.reserve_zec > 10000.0);
+    assert!(!registry.vaults.contains_key(&id));
+}
+
+fn run(keeper_liquidity: Option<KeeperLiquidityConfig>) -> Scenario {
+    let config = ScenarioConfig {
+        liquidation_config: LiquidationConfig {
+            keeper_liquidity,
+            ..LiquidationConfig::default()
+        },
+        ..ScenarioConfig::default()
+    };
+    let mut scenario = Scenario::new_with_seed(&config, 42);
+    add_agents(ScenarioId::BlackThursday, &mut scenario);
+    for _ in 0..5 {
+        scenario.cdp_holders.push(CdpHolder::new(CdpHolderConfig {
+            target_ratio: 1.6,
+            action_threshold_ratio: 1.2,
+            reserve_zec: 0.0,
+            initial_collateral: 40.0,
+            initial_debt: 1250.0,
+        }));
+    }
+    scenario.run(&generate_prices(ScenarioId::BlackThursday, 1000, 42));
+    scenario
+}
+
+#[test]
+fn test_black_thursday_keepers_vs_amm() {
+    let keepers = run(Some(KeeperLiquidityConfig::default()));
+    let amm = run(None);
+    let history = &keepers.liquidation_engine.history;
+    assert!(
+        !history.is_empty(),
+        "Black Thursday should liquidate someone"
+    );
+    assert!(history
+        .iter()
+        .all(|r| r.mode == LiquidationMode::KeeperPurchase && r.zai_from_amm == 0.0));
+    assert!(keepers.liquidation_engine.keeper_zec > 0.0);
+
+    let min_spot = |s: &Scenario| {
+        s.metrics
+            .iter()
+            .map(|m| m.amm_spot_price)
+            .fold(f64::INFINITY, f64::min)
+    };
+    println!("\nBlack Thursday liquidation routing:");
+    for (name, s) in [("AMM", &amm), ("keepers", &keepers)] {
+        println!(
+            "  {:<8} liquidations {:>3}  bad debt {:>10.2}  min spot {:>7.2}",
+            name,
+            s.liquidation_engine.history.len(),
+            s.liquidation_engine.total_bad_debt,
+            min_spot(s)
+        );
+    }
+}
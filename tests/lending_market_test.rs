@@ -0,0 +1,199 @@
+//! External ZEC/ZAI lending market as a capital source.
+//!
+//! Instead of fixed starting balances, arbitrageurs and attackers can borrow
+//! inventory from a utilization-priced money market. A thin market caps how
+//! much an attacker can source, and a draining pool makes borrowing expensive.
+
+use zai_sim::agents::{Arbitrageur, ArbitrageurConfig, Attacker, AttackerConfig};
+use zai_sim::lending::{LendingAsset, LendingMarket, LendingMarketConfig, LendingPool};
+use zai_sim::scenario::{Scenario, ScenarioConfig};
+use zai_sim::scenarios::{add_agents, generate_prices, ScenarioId};
+
+const BLOCKS: usize = 1000;
+const SEED: u64 = 42;
+const TARGET_PRICE: f64 = 50.0;
+
+// ═══════════════════════════════════════════════════════════════════════
+// Rate model and loan accounting
+// ═══════════════════════════════════════════════════════════════════════
+
+#[test]
+fn test_kinked_borrow_rate() {
+    let mut pool = LendingPool::new(LendingMarketConfig::default().zec);
+    let c = pool.config.clone();
+    assert_eq!(pool.borrow_rate(), c.base_rate);
+
+    pool.total_borrowed = pool.total_supply * c.optimal_utilization;
+    assert!((pool.borrow_rate() - (c.base_rate + c.slope_low)).abs() < 1e-12);
+
+    // Halfway between the kink and 100%
+    pool.total_borrowed = pool.total_supply * (1.0 + c.optimal_utilization) / 2.0;
+    let expected = c.base_rate + c.slope_low + c.slope_high / 2.0;
+    assert!((pool.borrow_rate() - expected).abs() < 1e-12);
+}
+
+#[test]
+fn test_borrow_capped_by_max_utilization() {
+    let mut market = LendingMarket::new(LendingMarketConfig::default());
+    let supply = market.zec.total_supply;
+    let cap = supply * market.zec.config.max_utilization;
+
+    let got = market.borrow("a", LendingAsset::Zec, supply, 1).unwrap();
+    assert!((got - cap).abs() < 1e-9);
+    assert!(market.borrow("b", LendingAsset::Zec, 1.0, 1).is_err());
+    // ZAI pool is independent
+    assert!(market.borrow("b", LendingAsset::Zai, 1000.0, 1).is_ok());
+}
+
+#[test]
+fn test_interest_accrues_and_repay_clears_position() {
+    let mut market = LendingMarket::new(LendingMarketConfig::default());
+    market.borrow("a", LendingAsset::Zec, 10_000.0, 1).unwrap();
+    let supply_before = market.zec.total_supply;
+
+    market.accrue(100_001);
+    let debt = market.debt_of("a", LendingAsset::Zec);
+    assert!(debt > 10_000.0, "Debt should grow with interest");
+    assert!((market.zec.total_supply - supply_before - (debt - 10_000.0)).abs() < 1e-6);
+
+    let repaid = market
+        .repay("a", LendingAsset::Zec, 1e9, 100_001)
+        .unwrap();
+    assert!((repaid - debt).abs() < 1e-6);
+    assert_eq!(market.debt_of("a", LendingAsset::Zec), 0.0);
+    assert!(market.zec.total_borrowed.abs() < 1e-6);
+    assert!(market.repay("a", LendingAsset::Zec, 1.0, 100_002).is_err());
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+// Agent inventory sourcing
+// ═══════════════════════════════════════════════════════════════════════
+
+#[test]
+fn test_arber_refills_and_repays_inventory() {
+    let mut market = LendingMarket::new(LendingMarketConfig::default());
+    let mut arber = Arbitrageur::new(ArbitrageurConfig {
+        borrow_from_lending: true,
+        ..ArbitrageurConfig::default()
+    });
+    let target = arber.config.initial_zec_balance;
+
+    // Healthy inventory: nothing to do
+    assert!(arber.manage_inventory("arb", &mut market, 1).is_empty());
+
+    arber.zec_balance = 100.0;
+    arber.manage_inventory("arb", &mut market, 2);
+    assert!((arber.zec_balance - target).abs() < 1e-9);
+    assert!((market.debt_of("arb", LendingAsset::Zec) - (target - 100.0)).abs() < 1e-6);
+
+    arber.zec_balance = target + 5000.0;
+    arber.manage_inventory("arb", &mut market, 3);
+    assert!(market.debt_of("arb", LendingAsset::Zec) < 1e-6);
+}
+
+#[test]
+fn test_arber_without_flag_never_borrows() {
+    let mut market = LendingMarket::new(LendingMarketConfig::default());
+    let mut arber = Arbitrageur::new(ArbitrageurConfig::default());
+    arber.zec_balance = 0.0;
+    assert!(arber.manage_inventory("arb", &mut market, 1).is_empty());
+    assert_eq!(market.zec.total_borrowed, 0.0);
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+// Scenario: can an attacker source the capital?
+// ═══════════════════════════════════════════════════════════════════════
+
+fn run_manipulation(zec_supply: Option<f64>) -> Scenario {
+    let mut config = ScenarioConfig::default();
+    if let Some(supply) = zec_supply {
+        let mut lending = LendingMarketConfig::default();
+        lending.zec.initial_supply = supply;
+        config.lending_market = Some(lending);
+    }
+
+    let mut scenario = Scenario::new_with_seed(&config, SEED);
+    add_agents(ScenarioId::SteadyState, &mut scenario);
+    let attack = AttackerConfig {
+        attack_capital_zec: 5000.0,
+        hold_blocks: 3,
+        attack_at_block: 500,
+        ..AttackerConfig::default()
+    };
+    scenario.attackers.push(if zec_supply.is_some() {
+        Attacker::new_borrowed(attack)
+    } else {
+        Attacker::new(attack)
+    });
+
+    let prices = generate_prices(ScenarioId::SteadyState, BLOCKS, SEED);
+    scenario.run(&prices);
+    scenario
+}
+
+fn max_peg_dev(scenario: &Scenario) -> f64 {
+    scenario
+        .metrics
+        .iter()
+        .map(|b| ((b.amm_spot_price - TARGET_PRICE) / TARGET_PRICE).abs())
+        .fold(0.0, f64::max)
+}
+
+#[test]
+fn test_thin_lending_market_limits_attack() {
+    let owned = run_manipulation(None);
+    let deep = run_manipulation(Some(50_000.0));
+    let thin = run_manipulation(Some(1_000.0));
+
+    println!();
+    println!("  {:<12} {:>12} {:>12}", "Funding", "Max Dev", "Peak Util");
+    for (name, s) in [("owned", &owned), ("deep", &deep), ("thin", &thin)] {
+        let peak_util = s
+            .metrics
+            .iter()
+            .map(|b| b.lending_zec_utilization)
+            .fold(0.0, f64::max);
+        println!(
+            "  {:<12} {:>11.2}% {:>11.2}%",
+            name,
+            max_peg_dev(s) * 100.0,
+            peak_util * 100.0
+        );
+    }
+
+    // A deep market funds the full attack
+    assert!((max_peg_dev(&deep) - max_peg_dev(&owned)).abs() < 0.01);
+    // A thin market caps the position at 95% of 1000 ZEC
+    assert!(max_peg_dev(&thin) < max_peg_dev(&deep));
+    assert!(thin.attackers[0].zec_balance < 1_000.0);
+}
+
+#[test]
+fn test_arbers_borrow_through_sustained_bear() {
+    let config = ScenarioConfig {
+        lending_market: Some(LendingMarketConfig::default()),
+        ..ScenarioConfig::default()
+    };
+
+    let mut scenario = Scenario::new_with_seed(&config, SEED);
+    add_agents(ScenarioId::SustainedBear, &mut scenario);
+    for arber in &mut scenario.arbers {
+        arber.config.borrow_from_lending = true;
+    }
+    let prices = generate_prices(ScenarioId::SustainedBear, BLOCKS, SEED);
+    scenario.run(&prices);
+
+    let market = scenario.lending_market.as_ref().unwrap();
+    let last = scenario.metrics.last().unwrap();
+    println!(
+        "  ZEC util={:.2}% rate={:.2}% interest={:.2} ZEC | ZAI util={:.2}%",
+        last.lending_zec_utilization * 100.0,
+        last.lending_zec_borrow_rate * 100.0,
+        market.zec.total_interest_accrued,
+        market.zai.utilization() * 100.0,
+    );
+    assert!(
+        market.zec.total_borrowed + market.zai.total_borrowed > 0.0,
+        "Arbers should draw on the lending market during a bear"
+    );
+}
@@ -1,6 +1,7 @@
 use approx::assert_relative_eq;
-use zai_sim::amm::Amm;
+use zai_sim::amm::{Amm, TwapKind};
 use zai_sim::cdp::{CdpConfig, VaultRegistry};
+use zai_sim::error::ZaiSimError;
 use zai_sim::liquidation::{LiquidationConfig, LiquidationEngine, LiquidationMode};
 
 /// Helper: create AMM at $50 ZEC/ZAI with TWAP established.
@@ -19,6 +20,7 @@ fn default_cdp_config() -> CdpConfig {
         debt_floor: 100.0,
         stability_fee_rate: 0.02,
         twap_window: 48,
+        twap_kind: TwapKind::Arithmetic,
     }
 }
 
@@ -224,7 +226,7 @@ fn test_challenge_response() {
         .challenge_liquidate(id3, "evil_keeper", &mut reg3, &mut amm3, 200)
         .unwrap_err();
     assert!(
-        err.contains("not liquidatable"),
+        matches!(err, ZaiSimError::NotLiquidatable(id) if id == id3),
         "Should reject challenge on healthy vault"
     );
 }
@@ -291,6 +293,7 @@ fn test_bad_debt_tracking() {
         debt_floor: 100.0,
         stability_fee_rate: 0.0, // no fees for clarity
         twap_window: 48,
+        twap_kind: TwapKind::Arithmetic,
     });
 
     let mut engine = LiquidationEngine::new(LiquidationConfig::default());
@@ -334,6 +337,7 @@ fn test_bad_debt_tracking() {
         debt_floor: 100.0,
         stability_fee_rate: 0.0,
         twap_window: 48,
+        twap_kind: TwapKind::Arithmetic,
     });
     let mut eng2 = LiquidationEngine::new(LiquidationConfig::default());
 
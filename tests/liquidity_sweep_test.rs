@@ -10,9 +10,11 @@ const BLOCKS: usize = 1000;
 const SEED: u64 = 42;
 
 fn config_with_liquidity(amm_zec: f64, amm_zai: f64) -> ScenarioConfig {
-    let mut config = ScenarioConfig::default();
-    config.amm_initial_zec = amm_zec;
-    config.amm_initial_zai = amm_zai;
+    let mut config = ScenarioConfig {
+        amm_initial_zec: amm_zec,
+        amm_initial_zai: amm_zai,
+        ..ScenarioConfig::default()
+    };
     config.cdp_config.min_ratio = 2.0;
     config.cdp_config.twap_window = 240;
     config.controller_config = ControllerConfig::default_tick();
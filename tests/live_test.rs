@@ -0,0 +1,69 @@
+//! Live shadow deployment.
+//!
+//! The websocket itself needs network access, so these tests drive the
+//! trade-to-block mapping and the shadow session with synthetic trades.
+#![cfg(feature = "net")]
+
+use zai_sim::live::{parse_trade, BlockClock, LiveConfig, LiveSession};
+use zai_sim::scenario::{Scenario, ScenarioConfig};
+
+const BLOCK_MS: u64 = 75_000;
+const T0: u64 = 1_700_000_000_000;
+
+#[test]
+fn test_parse_binance_trade() {
+    let msg = r#"{"e":"trade","E":1700000000123,"s":"ZECUSDT","t":1,"p":"31.42","q":"2.5","T":1700000000120,"m":false,"M":true}"#;
+    assert_eq!(parse_trade(msg), Some((1_700_000_000_120, 31.42)));
+
+    assert_eq!(parse_trade(r#"{"result":null,"id":1}"#), None);
+    assert_eq!(parse_trade("not json"), None);
+}
+
+#[test]
+fn test_block_closes_at_last_trade() {
+    let mut clock = BlockClock::new(75);
+    assert!(clock.push(T0, 30.0).is_empty());
+    assert!(clock.push(T0 + 10_000, 31.0).is_empty());
+    assert!(clock.push(T0 + BLOCK_MS - 1, 32.0).is_empty());
+    // First trade of block 2 closes block 1 at its last price
+    assert_eq!(clock.push(T0 + BLOCK_MS, 33.0), vec![(1, 32.0)]);
+}
+
+#[test]
+fn test_quiet_blocks_repeat_close() {
+    let mut clock = BlockClock::new(75);
+    clock.push(T0, 30.0);
+    // No trades for blocks 2-3; block 4's first trade closes 1-3
+    let closed = clock.push(T0 + 3 * BLOCK_MS + 5, 29.0);
+    assert_eq!(closed, vec![(1, 30.0), (2, 30.0), (3, 30.0)]);
+}
+
+#[test]
+fn test_session_steps_and_reports() {
+    let scenario_config = ScenarioConfig {
+        amm_initial_price: Some(30.0),
+        ..ScenarioConfig::default()
+    };
+    let config = LiveConfig {
+        block_time_secs: 75,
+        report_every: 4,
+        window: 3,
+    };
+    let mut session = LiveSession::new(Scenario::new(&scenario_config), config);
+
+    let mut reports = Vec::new();
+    for i in 0..10 {
+        // Two trades per block
+        let ts = T0 + i * BLOCK_MS;
+        reports.extend(session.on_trade(ts, 30.0));
+        reports.extend(session.on_trade(ts + 30_000, 30.0));
+    }
+
+    // Trades in blocks 1-10 close blocks 1-9
+    assert_eq!(session.scenario.metrics.len(), 9);
+    assert_eq!(session.scenario.metrics.block[8], 9);
+    // Reports at blocks 4 and 8, each over the last 3 blocks
+    assert_eq!(reports.len(), 2);
+    assert!(reports.iter().all(|r| r.total_blocks == 3));
+    assert!(reports[1].mean_peg_deviation < 0.01);
+}
@@ -0,0 +1,66 @@
+//! Structured logs from the simulation.
+//!
+//! Liquidations are logged at info level inside a span carrying their
+//! block, so a single vault's fate can be traced without recompiling.
+
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+use zai_sim::scenario::ScenarioConfig;
+use zai_sim::scenarios::{run_stress, ScenarioId};
+
+/// A log writer tests can read back.
+#[derive(Clone, Default)]
+struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Buffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_liquidations_logged_with_block() {
+    let buffer = Buffer::default();
+    let writer = buffer.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::DEBUG)
+        .with_writer(move || writer.clone())
+        .json()
+        .finish();
+
+    let scenario = tracing::subscriber::with_default(subscriber, || {
+        let config = ScenarioConfig {
+            use_amm_liquidation: true,
+            ..ScenarioConfig::default()
+        };
+        run_stress(ScenarioId::BlackThursday, &config, 500, 42)
+    });
+    let liquidations = scenario.liquidation_engine.history.len();
+
+    let logs = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    let events: Vec<serde_json::Value> = logs
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let liquidated: Vec<&serde_json::Value> = events
+        .iter()
+        .filter(|e| e["fields"]["message"] == "vault liquidated")
+        .collect();
+    assert_eq!(liquidated.len(), liquidations);
+    for event in liquidated {
+        assert_eq!(event["level"], "INFO");
+        assert!(event["fields"]["vault"].is_u64());
+        assert!(event["span"]["block"].is_u64(), "{}", event);
+    }
+    let blocks = events
+        .iter()
+        .filter(|e| e["fields"]["message"] == "block done")
+        .count();
+    assert_eq!(blocks, scenario.metrics.len());
+}
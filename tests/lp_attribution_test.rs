@@ -0,0 +1,136 @@
+//! LP fee APR and fee-vs-IL attribution.
+//!
+//! Income is split between cohorts by the shares they held, impermanent
+//! loss is measured against holding each cohort's deposit, and the net
+//! return is fees plus penalties plus (negative) IL.
+
+use approx::assert_relative_eq;
+use zai_sim::amm::Amm;
+use zai_sim::lp_attribution::{cohort_of, pool_totals, LpAttribution};
+use zai_sim::scenario::ScenarioConfig;
+use zai_sim::scenarios::{run_stress, ScenarioId};
+
+const BLOCKS_PER_YEAR: f64 = 365.25 * 24.0 * 3600.0 / 75.0;
+
+#[test]
+fn test_cohort_names() {
+    assert_eq!(cohort_of("private_lp_3"), "private_lp");
+    assert_eq!(cohort_of("lp_agent"), "lp_agent");
+    assert_eq!(cohort_of("genesis"), "genesis");
+    assert_eq!(cohort_of("protocol"), "protocol");
+}
+
+#[test]
+fn test_fees_and_il_of_single_lp() {
+    let mut amm = Amm::new(10000.0, 500000.0, 0.003);
+    let mut attribution = LpAttribution::new();
+    assert_eq!(attribution.observe(&amm, 0.0, 0).len(), 1);
+
+    amm.swap_zai_for_zec(100000.0, 1).unwrap();
+    let cohorts = attribution.observe(&amm, 0.0, 1);
+    let genesis = &cohorts[0];
+    assert_eq!(genesis.cohort, "genesis");
+
+    // All fees go to the only LP
+    assert_relative_eq!(genesis.fees_zai, amm.cumulative_fees_zai);
+    assert_eq!(genesis.penalties_zai, 0.0);
+
+    // IL against holding the genesis deposit at the new price
+    let p = amm.spot_price();
+    let held = 10000.0 * p + 500000.0;
+    let expected_il = 2.0 * (10000.0 * 500000.0 * p).sqrt() - held;
+    assert!(expected_il < 0.0);
+    assert_relative_eq!(genesis.il_zai, expected_il, epsilon = 1e-6);
+    assert_relative_eq!(
+        genesis.net_return_zai,
+        genesis.fees_zai + genesis.il_zai,
+        epsilon = 1e-9
+    );
+
+    // One block of fees, annualized
+    assert_relative_eq!(
+        genesis.fee_apr,
+        genesis.fees_zai / genesis.value_zai * BLOCKS_PER_YEAR,
+        epsilon = 1e-9
+    );
+}
+
+#[test]
+fn test_income_split_by_shares() {
+    let mut amm = Amm::new(10000.0, 500000.0, 0.003);
+    amm.add_liquidity(10000.0, 500000.0, "private_lp_0")
+        .unwrap();
+    amm.add_liquidity(20000.0, 1000000.0, "private_lp_1")
+        .unwrap();
+    let mut attribution = LpAttribution::new();
+    attribution.observe(&amm, 0.0, 0);
+
+    amm.swap_zec_for_zai(500.0, 1).unwrap();
+    // 40 ZAI of the fee growth was a liquidation penalty paid to LPs
+    amm.cumulative_fees_zai += 40.0;
+    let cohorts = attribution.observe(&amm, 40.0, 1);
+    let names: Vec<&str> = cohorts.iter().map(|c| c.cohort.as_str()).collect();
+    assert_eq!(names, vec!["genesis", "private_lp"]);
+
+    // The private cohort holds 3/4 of the pool
+    assert_relative_eq!(
+        cohorts[1].fees_zai,
+        3.0 * cohorts[0].fees_zai,
+        epsilon = 1e-9
+    );
+    assert_relative_eq!(cohorts[1].penalties_zai, 30.0, epsilon = 1e-9);
+
+    let pool = pool_totals(&cohorts);
+    assert_relative_eq!(
+        pool.fees_zai,
+        amm.cumulative_fees_zai - 40.0,
+        epsilon = 1e-9
+    );
+    assert_relative_eq!(pool.penalties_zai, 40.0, epsilon = 1e-9);
+}
+
+#[test]
+fn test_withdrawal_realizes_il() {
+    let mut amm = Amm::new(10000.0, 500000.0, 0.003);
+    let mut attribution = LpAttribution::new();
+    attribution.observe(&amm, 0.0, 0);
+
+    amm.swap_zai_for_zec(100000.0, 1).unwrap();
+    let before = attribution.observe(&amm, 0.0, 1)[0].il_zai;
+
+    // Withdrawing at the same price only moves loss from open to realized
+    let half = amm.lp_shares["genesis"] / 2.0;
+    amm.remove_liquidity(half, "genesis").unwrap();
+    let after = attribution.observe(&amm, 0.0, 2)[0].il_zai;
+    assert_relative_eq!(after, before, epsilon = 1e-6);
+
+    // Moving back toward $50 recovers only the open half
+    amm.swap_zec_for_zai(1000.0, 3).unwrap();
+    let back = attribution.observe(&amm, 0.0, 3)[0].il_zai;
+    assert!(back > before && back < 0.0);
+}
+
+#[test]
+fn test_scenario_metrics_and_csv() {
+    let scenario = run_stress(
+        ScenarioId::BlackThursday,
+        &ScenarioConfig::default(),
+        500,
+        42,
+    );
+    let last = scenario.metrics.last().unwrap();
+    assert!(!last.lp_cohorts.is_empty());
+    let pool = pool_totals(&last.lp_cohorts);
+    assert_eq!(last.lp_net_return_zai, pool.net_return_zai);
+    assert!(last.lp_il_zai < 0.0);
+    assert!(scenario.metrics.iter().all(|m| m.lp_fee_apr >= 0.0));
+
+    let path = std::env::temp_dir().join("zai_sim_lp_attribution.csv");
+    scenario.save_metrics_csv(&path).unwrap();
+    let csv = std::fs::read_to_string(&path).unwrap();
+    let header = csv.lines().next().unwrap();
+    for column in ["lp_fee_apr", "lp_net_return_zai", "lp_genesis_il_zai"] {
+        assert!(header.split(',').any(|h| h == column), "{}", column);
+    }
+    let _ = std::fs::remove_file(&path);
+}
@@ -8,6 +8,7 @@
 use zai_sim::agents::*;
 use zai_sim::controller::ControllerConfig;
 use zai_sim::output;
+use zai_sim::protocol_liquidity::ProtocolLiquidityConfig;
 use zai_sim::report;
 use zai_sim::scenario::{Scenario, ScenarioConfig};
 use zai_sim::scenarios::{generate_prices, ScenarioId};
@@ -15,9 +16,11 @@ use zai_sim::scenarios::{generate_prices, ScenarioId};
 const SEED: u64 = 42;
 
 fn config_5m() -> ScenarioConfig {
-    let mut config = ScenarioConfig::default();
-    config.amm_initial_zec = 100_000.0;
-    config.amm_initial_zai = 5_000_000.0;
+    let mut config = ScenarioConfig {
+        amm_initial_zec: 100_000.0,
+        amm_initial_zai: 5_000_000.0,
+        ..ScenarioConfig::default()
+    };
     config.cdp_config.min_ratio = 2.0;
     config.cdp_config.twap_window = 240;
     config.controller_config = ControllerConfig::default_tick();
@@ -131,29 +134,32 @@ struct ProtocolLpResult {
 
 fn run_protocol_lp_test(protocol_fraction: f64, label: &str) -> ProtocolLpResult {
     // Tiny genesis pool — LPs provide the real liquidity
-    let mut config = ScenarioConfig::default();
-    config.amm_initial_zec = 100.0;
-    config.amm_initial_zai = 5000.0;
-    config.amm_swap_fee = 0.003;
+    let mut config = ScenarioConfig {
+        amm_initial_zec: 100.0,
+        amm_initial_zai: 5000.0,
+        amm_swap_fee: 0.003,
+        ..ScenarioConfig::default()
+    };
     config.cdp_config.min_ratio = 2.0;
     config.cdp_config.twap_window = 240;
     config.controller_config = ControllerConfig::default_tick();
     let target = config.initial_redemption_price;
 
-    let blocks = 1000;
-    let prices = generate_prices(ScenarioId::LiquidityCrisis, blocks, SEED);
-    let mut scenario = Scenario::new(&config);
-
     // Total target: 100K ZEC + 5M ZAI ($5M pool)
-    // Protocol LP: permanent, never withdraws
-    let protocol_zec = 100_000.0 * protocol_fraction;
-    let protocol_zai = 5_000_000.0 * protocol_fraction;
+    // Protocol LP: permanent, never withdraws, no further funding
     if protocol_fraction > 0.0 {
-        let _ = scenario
-            .amm
-            .add_liquidity(protocol_zec, protocol_zai, "protocol_lp");
+        config.protocol_liquidity = Some(ProtocolLiquidityConfig {
+            initial_zec: 100_000.0 * protocol_fraction,
+            fee_share: 0.0,
+            penalty_share: 0.0,
+            ..ProtocolLiquidityConfig::default()
+        });
     }
 
+    let blocks = 1000;
+    let prices = generate_prices(ScenarioId::LiquidityCrisis, blocks, SEED);
+    let mut scenario = Scenario::new(&config);
+
     // Private IL-aware LPs: withdraw when real P&L < -2%
     let private_total_fraction = 1.0 - protocol_fraction;
     let private_count = 5;
@@ -232,11 +238,13 @@ struct IlAwareLpResult {
 
 fn run_il_aware_lp_test(blocks: usize) -> IlAwareLpResult {
     // Create scenario with minimal genesis, LPs provide the real liquidity
-    let mut config = ScenarioConfig::default();
-    // Small genesis so LPs are the real liquidity
-    config.amm_initial_zec = 100.0;
-    config.amm_initial_zai = 5000.0;
-    config.amm_swap_fee = 0.003;
+    let mut config = ScenarioConfig {
+        // Small genesis so LPs are the real liquidity
+        amm_initial_zec: 100.0,
+        amm_initial_zai: 5000.0,
+        amm_swap_fee: 0.003,
+        ..ScenarioConfig::default()
+    };
     config.cdp_config.min_ratio = 2.0;
     config.cdp_config.twap_window = 240;
     config.controller_config = ControllerConfig::default_tick();
@@ -84,9 +84,11 @@ fn patterns() -> Vec<PatternDef> {
 // ═══════════════════════════════════════════════════════════════════════
 
 fn default_config() -> ScenarioConfig {
-    let mut c = ScenarioConfig::default();
-    c.amm_initial_zec = 100_000.0;
-    c.amm_initial_zai = 5_000_000.0;
+    let mut c = ScenarioConfig {
+        amm_initial_zec: 100_000.0,
+        amm_initial_zai: 5_000_000.0,
+        ..ScenarioConfig::default()
+    };
     c.cdp_config.min_ratio = 2.0;
     c.cdp_config.twap_window = 240;
     c.controller_config = ControllerConfig::default_tick();
@@ -0,0 +1,286 @@
+//! Streaming metrics sinks.
+//!
+//! `Scenario::stream_metrics` writes each block to a `MetricsSink` as it
+//! finishes, flushes in chunks so a crash keeps everything up to the last
+//! flush, and can bound the metrics kept in memory.
+
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use zai_sim::error::ZaiSimError;
+use zai_sim::lp_attribution::LpCohortMetrics;
+use zai_sim::metrics_sink::*;
+#[cfg(feature = "sqlite")]
+use zai_sim::output::{SqlValue, SqliteStore};
+use zai_sim::scenario::{BlockMetrics, ScenarioConfig};
+use zai_sim::scenarios::{run_stress, run_stress_with, ScenarioId};
+
+const BLOCKS: usize = 300;
+
+fn temp_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("zai_sim_stream_{}", name));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+/// Records which blocks were written and when the sink was flushed.
+#[derive(Clone, Default)]
+struct RecordingSink {
+    written: Arc<Mutex<Vec<u64>>>,
+    flushed_at: Arc<Mutex<Vec<usize>>>,
+}
+
+impl MetricsSink for RecordingSink {
+    fn write(&mut self, metrics: &BlockMetrics) -> Result<(), ZaiSimError> {
+        self.written.lock().unwrap().push(metrics.block);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), ZaiSimError> {
+        let written = self.written.lock().unwrap().len();
+        self.flushed_at.lock().unwrap().push(written);
+        Ok(())
+    }
+}
+
+#[test]
+fn test_csv_stream_matches_saved_csv() {
+    let config = ScenarioConfig::default();
+    let streamed = temp_path("timeseries.csv");
+    let sink = CsvSink::create(&streamed).unwrap();
+    let scenario = run_stress_with(ScenarioId::BlackThursday, &config, BLOCKS, 42, |s| {
+        s.stream_metrics(sink, StreamConfig::default())
+    });
+    let saved = temp_path("saved.csv");
+    scenario.save_metrics_csv(&saved).unwrap();
+
+    let streamed = std::fs::read_to_string(&streamed).unwrap();
+    let saved = std::fs::read_to_string(&saved).unwrap();
+    assert_eq!(streamed.lines().count(), BLOCKS + 1);
+    assert_eq!(saved.lines().count(), BLOCKS + 1);
+    // Same columns, except LP cohorts that first appear after block 1
+    for (s, f) in streamed.lines().zip(saved.lines()) {
+        let s: Vec<&str> = s.split(',').collect();
+        let f: Vec<&str> = f.split(',').collect();
+        assert!(s.len() <= f.len());
+        assert_eq!(s, f[..s.len()]);
+    }
+    assert!(streamed.starts_with("block,external_price,"));
+}
+
+#[test]
+fn test_retain_bounds_memory() {
+    let config = ScenarioConfig::default();
+    let full = run_stress(ScenarioId::SteadyState, &config, BLOCKS, 42);
+    let sink = RecordingSink::default();
+    let written = sink.written.clone();
+    let streamed = run_stress_with(ScenarioId::SteadyState, &config, BLOCKS, 42, |s| {
+        s.stream_metrics(
+            sink,
+            StreamConfig {
+                flush_every: 1,
+                retain: Some(10),
+            },
+        )
+    });
+
+    assert_eq!(streamed.metrics.len(), 10);
+    assert_eq!(streamed.metrics.block[0], BLOCKS as u64 - 9);
+    assert_eq!(
+        *written.lock().unwrap(),
+        (1..=BLOCKS as u64).collect::<Vec<_>>()
+    );
+    // Trimming history does not change the simulation
+    let last = streamed.metrics.last().unwrap();
+    let expected = full.metrics.last().unwrap();
+    assert_eq!(last.amm_spot_price, expected.amm_spot_price);
+    assert_eq!(last.total_debt, expected.total_debt);
+}
+
+#[test]
+fn test_flushes_in_chunks_and_at_end() {
+    let config = ScenarioConfig::default();
+    let sink = RecordingSink::default();
+    let flushed_at = sink.flushed_at.clone();
+    run_stress_with(ScenarioId::SteadyState, &config, 120, 42, |s| {
+        s.stream_metrics(
+            sink,
+            StreamConfig {
+                flush_every: 50,
+                retain: None,
+            },
+        )
+    });
+    assert_eq!(*flushed_at.lock().unwrap(), [50, 100, 120]);
+}
+
+#[test]
+fn test_partial_csv_survives_mid_run() {
+    let config = ScenarioConfig::default();
+    let path = temp_path("partial.csv");
+    let sink = CsvSink::create(&path).unwrap();
+    // Lines on disk when block 101 starts, as a crash there would leave them
+    let on_disk = Arc::new(Mutex::new(0));
+    let seen = on_disk.clone();
+    let reader = path.clone();
+    run_stress_with(ScenarioId::SteadyState, &config, 120, 42, |s| {
+        s.stream_metrics(
+            sink,
+            StreamConfig {
+                flush_every: 50,
+                retain: None,
+            },
+        );
+        s.before_step(move |_, block| {
+            if block == 101 {
+                let csv = std::fs::read_to_string(&reader).unwrap();
+                *seen.lock().unwrap() = csv.lines().count();
+            }
+        });
+    });
+    assert_eq!(*on_disk.lock().unwrap(), 101);
+    let csv = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(csv.lines().count(), 121);
+}
+
+fn cohort(name: &str, fees_zai: f64) -> LpCohortMetrics {
+    LpCohortMetrics {
+        cohort: name.to_string(),
+        shares: 1.0,
+        value_zai: 100.0,
+        fee_apr: 0.05,
+        fees_zai,
+        penalties_zai: 0.0,
+        il_zai: 0.0,
+        net_return_zai: fees_zai,
+    }
+}
+
+#[test]
+fn test_csv_stream_widens_header_for_late_cohorts() {
+    let path = temp_path("late_cohorts.csv");
+    let scenario = run_stress(ScenarioId::SteadyState, &ScenarioConfig::default(), 3, 42);
+    let mut blocks: Vec<BlockMetrics> = scenario.metrics.iter().collect();
+    blocks[0].lp_cohorts = vec![cohort("protocol", 1.0)];
+    blocks[1].lp_cohorts = vec![cohort("protocol", 2.0)];
+    blocks[2].lp_cohorts = vec![cohort("protocol", 3.0), cohort("private", 7.0)];
+
+    let mut sink = CsvSink::create(&path).unwrap();
+    for m in &blocks {
+        sink.write(m).unwrap();
+        sink.flush().unwrap();
+    }
+    drop(sink);
+
+    let mut reader = csv::Reader::from_path(&path).unwrap();
+    let header = reader.headers().unwrap().clone();
+    let column = |name: &str| header.iter().position(|h| h == name).unwrap();
+    let (protocol, private) = (
+        column("lp_protocol_fees_zai"),
+        column("lp_private_fees_zai"),
+    );
+    let rows: Vec<csv::StringRecord> = reader.records().map(|r| r.unwrap()).collect();
+    assert_eq!(rows.len(), 3);
+    assert_eq!(&rows[0][protocol], "1.00");
+    assert_eq!(&rows[0][private], "");
+    assert_eq!(&rows[2][protocol], "3.00");
+    assert_eq!(&rows[2][private], "7.00");
+}
+
+#[cfg(feature = "parquet")]
+#[test]
+fn test_parquet_stream_writes_complete_parts() {
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    let dir = temp_path("parquet");
+    let _ = std::fs::remove_dir_all(&dir);
+    let sink = ParquetSink::create(&dir).unwrap();
+    let scenario = run_stress_with(
+        ScenarioId::SteadyState,
+        &ScenarioConfig::default(),
+        BLOCKS,
+        42,
+        |s| {
+            s.stream_metrics(
+                sink,
+                StreamConfig {
+                    flush_every: 64,
+                    retain: Some(1),
+                },
+            )
+        },
+    );
+    let last = scenario.metrics.row(0);
+    drop(scenario);
+
+    let mut parts: Vec<PathBuf> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .collect();
+    parts.sort();
+    assert_eq!(parts.len(), BLOCKS.div_ceil(64));
+    assert!(parts.iter().all(|p| p.extension().unwrap() == "parquet"));
+
+    let mut blocks = Vec::new();
+    for part in &parts {
+        let reader = ParquetRecordBatchReaderBuilder::try_new(std::fs::File::open(part).unwrap())
+            .unwrap()
+            .build()
+            .unwrap();
+        for batch in reader {
+            let batch = batch.unwrap();
+            assert!(batch.schema().field_with_name("lp_cohorts").is_ok());
+            let column = batch
+                .column_by_name("block")
+                .unwrap()
+                .as_any()
+                .downcast_ref::<arrow_array::UInt64Array>()
+                .unwrap();
+            blocks.extend(column.values().iter().copied());
+        }
+    }
+    assert_eq!(blocks.len(), BLOCKS);
+    assert!(blocks.windows(2).all(|w| w[0] < w[1]));
+    assert_eq!(*blocks.last().unwrap(), last.block);
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn test_sqlite_stream_appends_block_metrics() {
+    let config = ScenarioConfig::default();
+    let path = temp_path("store.sqlite");
+    let store = SqliteStore::open(&path).unwrap();
+    let run_id = store
+        .insert_run(
+            "stream",
+            "steady_state",
+            42,
+            BLOCKS,
+            config.initial_redemption_price,
+            &config,
+        )
+        .unwrap();
+    let sink = SqliteSink::new(store, run_id);
+    let scenario = run_stress_with(ScenarioId::SteadyState, &config, BLOCKS, 42, |s| {
+        s.stream_metrics(
+            sink,
+            StreamConfig {
+                flush_every: 64,
+                retain: Some(1),
+            },
+        )
+    });
+    assert_eq!(scenario.metrics.len(), 1);
+    let last = scenario.metrics.row(0);
+    drop(scenario);
+
+    let store = SqliteStore::open(&path).unwrap();
+    let rows = store
+        .query(&format!(
+            "SELECT COUNT(*), MAX(block) FROM block_metrics WHERE run_id = {}",
+            run_id
+        ))
+        .unwrap();
+    assert_eq!(rows.rows[0][0], SqlValue::Integer(BLOCKS as i64));
+    assert_eq!(rows.rows[0][1], SqlValue::Integer(last.block as i64));
+}
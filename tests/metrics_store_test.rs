@@ -0,0 +1,99 @@
+//! Column-wise metrics storage.
+//!
+//! `MetricsStore` holds a run's metrics as one series per field, with
+//! breaker actions in a separate event log, and hands back whole blocks as
+//! `BlockMetrics` on demand.
+
+use zai_sim::output::{compute_summary, extract_events};
+use zai_sim::scenario::{MetricsStore, ScenarioConfig};
+use zai_sim::scenarios::{run_stress, ScenarioId};
+
+fn black_thursday() -> MetricsStore {
+    run_stress(
+        ScenarioId::BlackThursday,
+        &ScenarioConfig::default(),
+        300,
+        42,
+    )
+    .metrics
+}
+
+#[test]
+fn test_rows_round_trip() {
+    let metrics = black_thursday();
+    assert_eq!(metrics.len(), 300);
+    assert!(!metrics.breaker_events.is_empty());
+
+    let copy: MetricsStore = metrics.iter().collect();
+    assert_eq!(
+        serde_json::to_value(&copy).unwrap(),
+        serde_json::to_value(&metrics).unwrap()
+    );
+
+    let mut fired = 0;
+    for (i, m) in metrics.iter().enumerate() {
+        assert_eq!(m.block, metrics.block[i]);
+        assert_eq!(m.amm_spot_price, metrics.amm_spot_price[i]);
+        assert_eq!(m.halted, metrics.halted[i]);
+        assert_eq!(
+            m.breaker_actions.len(),
+            metrics.breaker_events_at(m.block).len()
+        );
+        fired += m.breaker_actions.len();
+    }
+    assert_eq!(fired, metrics.breaker_events.len());
+    assert_eq!(metrics.rows(290..).count(), 10);
+    assert_eq!(metrics.last().unwrap().block, 300);
+    assert!(metrics.get(300).is_none());
+}
+
+#[test]
+fn test_truncation_keeps_events_in_step() {
+    let metrics = black_thursday();
+    let first_event = metrics.breaker_events[0].0;
+    let cut = first_event as usize;
+
+    // Blocks start at 1, so `cut - 1` rows end just before the event
+    let mut head = metrics.clone();
+    head.truncate(cut - 1);
+    assert_eq!(head.len(), cut - 1);
+    assert!(head.breaker_events.is_empty());
+    assert!(extract_events(&head)
+        .iter()
+        .all(|e| e.event_type == "liquidation"));
+
+    let mut tail = metrics.clone();
+    tail.remove_first(cut);
+    assert_eq!(tail.block[0], first_event + 1);
+    assert!(tail.breaker_events.iter().all(|(b, _)| *b > first_event));
+    assert_eq!(
+        tail.breaker_events.len() + metrics.breaker_events_at(first_event).len(),
+        metrics.breaker_events.len()
+    );
+}
+
+#[test]
+fn test_summary_from_columns() {
+    let config = ScenarioConfig::default();
+    let metrics = black_thursday();
+    let summary = compute_summary(&metrics, config.initial_redemption_price);
+
+    let liquidations: u32 = metrics.iter().map(|m| m.liquidation_count).sum();
+    let triggers: usize = metrics.iter().map(|m| m.breaker_actions.len()).sum();
+    assert_eq!(summary.total_liquidations, liquidations);
+    assert_eq!(summary.breaker_triggers as usize, triggers);
+    assert_eq!(
+        summary.halt_blocks,
+        metrics.iter().filter(|m| m.halted).count() as u64
+    );
+    assert_eq!(
+        summary.pause_blocks,
+        metrics.iter().filter(|m| m.minting_paused).count() as u64
+    );
+    let mean = metrics.iter().map(|m| m.amm_spot_price).sum::<f64>() / metrics.len() as f64;
+    assert_eq!(summary.mean_amm_price, mean);
+    assert_eq!(
+        compute_summary(&MetricsStore::default(), 50.0).total_blocks,
+        0
+    );
+}
@@ -65,9 +65,11 @@ fn stddev(values: &[f64]) -> f64 {
 // ═══════════════════════════════════════════════════════════════════════
 
 fn config_5m_stochastic() -> ScenarioConfig {
-    let mut c = ScenarioConfig::default();
-    c.amm_initial_zec = 100_000.0;
-    c.amm_initial_zai = 5_000_000.0;
+    let mut c = ScenarioConfig {
+        amm_initial_zec: 100_000.0,
+        amm_initial_zai: 5_000_000.0,
+        ..ScenarioConfig::default()
+    };
     c.cdp_config.min_ratio = 2.0;
     c.cdp_config.twap_window = 240;
     c.controller_config = ControllerConfig::default_tick();
@@ -194,7 +196,7 @@ fn compute_stats(scenario_name: &str, results: &[RunResult]) -> ScenarioStats {
 // Run Single
 // ═══════════════════════════════════════════════════════════════════════
 
-fn run_single(sid: ScenarioId, seed: u64) -> RunResult {
+fn run_single(sid: ScenarioId, seed: u64) -> (RunResult, report::MonteCarloRun) {
     let config = config_5m_stochastic();
     let target = 50.0;
 
@@ -215,14 +217,18 @@ fn run_single(sid: ScenarioId, seed: u64) -> RunResult {
         .max()
         .unwrap_or(0);
 
-    RunResult {
+    let result = RunResult {
         bad_debt: summary.total_bad_debt,
         mean_peg: summary.mean_peg_deviation,
         max_peg: summary.max_peg_deviation,
         liqs: summary.total_liquidations,
         max_zombie_count,
         verdict: verdict.overall.label().to_string(),
-    }
+    };
+    (
+        result,
+        report::MonteCarloRun::from_metrics(seed, &scenario.metrics, target),
+    )
 }
 
 // ═══════════════════════════════════════════════════════════════════════
@@ -242,7 +248,7 @@ fn generate_monte_carlo_html(all_stats: &[ScenarioStats]) -> String {
         };
         rows.push_str(&format!(
             "<tr>\
-             <td>{name}</td>\
+             <td><a href=\"{name}.html\">{name}</a></td>\
              <td>{seeds}</td>\
              <td><span class=\"badge {cls}\">{pass:.0}%</span></td>\
              <td>{bd_mean:.2}</td><td>{bd_p95:.2}</td><td>{bd_p99:.2}</td><td>{bd_max:.2}</td>\
@@ -391,11 +397,22 @@ fn monte_carlo_sweep() {
     for &(scenario_name, sid) in &scenarios {
         print!("  Running {} (seeds 1-{})...", scenario_name, NUM_SEEDS);
         let mut results: Vec<RunResult> = Vec::with_capacity(NUM_SEEDS as usize);
+        let mut runs = Vec::with_capacity(NUM_SEEDS as usize);
 
         for seed in 1..=NUM_SEEDS {
-            results.push(run_single(sid, seed));
+            let (result, run) = run_single(sid, seed);
+            results.push(result);
+            runs.push(run);
         }
 
+        // Fan charts and tail distributions across seeds
+        let fan_html = report::generate_monte_carlo_report(scenario_name, &runs);
+        report::save_report(
+            &fan_html,
+            &report_dir.join(format!("{}.html", scenario_name)),
+        )
+        .expect("save monte carlo scenario report");
+
         let stats = compute_stats(scenario_name, &results);
         let pass_pct = stats.pass_count as f64 / stats.num_seeds as f64 * 100.0;
         println!(
@@ -506,7 +523,8 @@ fn monte_carlo_sweep() {
 
     println!("\n═══════════════════════════════════════════════════════════════════════════════════════════════════════════════════════════");
     println!("  Reports saved to: reports/monte_carlo/");
-    println!("  Summary:          reports/monte_carlo/index.html\n");
+    println!("  Summary:          reports/monte_carlo/index.html");
+    println!("  Fan charts:       reports/monte_carlo/<scenario>.html\n");
 
     // ═══════════════════════════════════════════════════════════════════
     // HTML Report
@@ -521,6 +539,13 @@ fn monte_carlo_sweep() {
     // ═══════════════════════════════════════════════════════════════════
 
     assert!(html_path.exists(), "Monte Carlo report should exist");
+    for &(scenario_name, _) in &scenarios {
+        assert!(
+            report_dir.join(format!("{}.html", scenario_name)).exists(),
+            "{}: fan chart report should exist",
+            scenario_name
+        );
+    }
 
     for s in &all_stats {
         assert!(
@@ -83,9 +83,11 @@ fn arber_configs(name: &str) -> Vec<ArbitrageurConfig> {
 // ── Scenario setup ─────────────────────────────────────────────────────
 
 fn base_config() -> ScenarioConfig {
-    let mut config = ScenarioConfig::default();
-    config.amm_initial_zec = 100_000.0;
-    config.amm_initial_zai = 5_000_000.0;
+    let mut config = ScenarioConfig {
+        amm_initial_zec: 100_000.0,
+        amm_initial_zai: 5_000_000.0,
+        ..ScenarioConfig::default()
+    };
     config.cdp_config.min_ratio = 2.0;
     config.cdp_config.twap_window = 240;
     config.controller_config = ControllerConfig::default_tick();
@@ -219,11 +221,11 @@ fn analyze(scenario: &Scenario, arber_label: &str, target: f64) -> ArberResult {
 
     // Death spiral detection
     let death_spiral = if scenario.metrics.len() > 200 {
-        let initial = scenario.metrics[0].amm_spot_price;
+        let initial = scenario.metrics.amm_spot_price[0];
         let final_price = scenario.metrics.last().unwrap().amm_spot_price;
         let dropped = final_price < initial * 0.1;
-        let last_100 = &scenario.metrics[scenario.metrics.len() - 100..];
-        let no_recovery = last_100.iter().all(|m| m.amm_spot_price < initial * 0.15);
+        let last_100 = &scenario.metrics.amm_spot_price[scenario.metrics.len() - 100..];
+        let no_recovery = last_100.iter().all(|p| *p < initial * 0.15);
         dropped && no_recovery
     } else {
         false
@@ -0,0 +1,127 @@
+//! Intrablock OHLC wick simulation.
+//!
+//! Close-only price data hides flash wicks inside a candle. With wick paths,
+//! each block steps through the candle's open, high, low and close, so a wick
+//! below a vault's liquidation price liquidates it even when every close is
+//! safe.
+#![cfg(feature = "net")]
+
+use zai_sim::data_fetcher::{ohlc_paths, Kline, WickOrder};
+use zai_sim::scenario::{Scenario, ScenarioConfig};
+
+const BLOCKS: usize = 20;
+
+fn kline(open: f64, high: f64, low: f64, close: f64) -> Kline {
+    Kline {
+        timestamp_ms: 0,
+        open,
+        high,
+        low,
+        close,
+        volume: 0.0,
+    }
+}
+
+/// Flat $50 candles, with one flash wick down to $30 at block 10.
+fn wick_klines() -> Vec<Kline> {
+    (0..BLOCKS)
+        .map(|i| {
+            if i == 9 {
+                kline(50.0, 51.0, 30.0, 50.0)
+            } else {
+                kline(50.0, 50.0, 50.0, 50.0)
+            }
+        })
+        .collect()
+}
+
+/// Oracle-liquidation scenario with one vault at CR 2.0 ($50, 40 ZEC / 1000 ZAI),
+/// liquidatable below $37.50.
+fn vault_scenario() -> Scenario {
+    let config = ScenarioConfig {
+        use_external_oracle_for_liquidation: true,
+        ..ScenarioConfig::default()
+    };
+    let mut scenario = Scenario::new(&config);
+    scenario
+        .registry
+        .open_vault("owner", 40.0, 1000.0, 0, &scenario.amm)
+        .unwrap();
+    scenario
+}
+
+#[test]
+fn test_high_first_path_order() {
+    let paths = ohlc_paths(&[kline(50.0, 55.0, 45.0, 48.0)], WickOrder::HighFirst, 42);
+    assert_eq!(paths, vec![vec![50.0, 55.0, 45.0, 48.0]]);
+}
+
+#[test]
+fn test_path_drops_repeated_prices() {
+    let paths = ohlc_paths(
+        &[kline(50.0, 50.0, 50.0, 50.0), kline(50.0, 55.0, 50.0, 55.0)],
+        WickOrder::HighFirst,
+        42,
+    );
+    assert_eq!(paths, vec![vec![50.0], vec![50.0, 55.0, 50.0, 55.0]]);
+}
+
+#[test]
+fn test_random_order_is_seeded() {
+    let klines: Vec<Kline> = (0..100).map(|_| kline(50.0, 55.0, 45.0, 48.0)).collect();
+    let a = ohlc_paths(&klines, WickOrder::Random, 7);
+    let b = ohlc_paths(&klines, WickOrder::Random, 7);
+    assert_eq!(a, b);
+
+    let high_first = a.iter().filter(|p| p[1] == 55.0).count();
+    let low_first = a.iter().filter(|p| p[1] == 45.0).count();
+    assert_eq!(high_first + low_first, 100);
+    assert!(high_first > 0 && low_first > 0);
+}
+
+#[test]
+fn test_wick_order_parses() {
+    assert_eq!("ohlc".parse::<WickOrder>().unwrap(), WickOrder::HighFirst);
+    assert_eq!("random".parse::<WickOrder>().unwrap(), WickOrder::Random);
+    assert!("sideways".parse::<WickOrder>().is_err());
+}
+
+#[test]
+fn test_close_only_misses_wick() {
+    let closes: Vec<f64> = wick_klines().iter().map(|k| k.close).collect();
+    let mut scenario = vault_scenario();
+    scenario.run(&closes);
+
+    let liquidations: u32 = scenario.metrics.iter().map(|m| m.liquidation_count).sum();
+    assert_eq!(liquidations, 0);
+    assert_eq!(scenario.registry.vaults.len(), 1);
+}
+
+#[test]
+fn test_wick_triggers_liquidation() {
+    let paths = ohlc_paths(&wick_klines(), WickOrder::HighFirst, 42);
+    let mut scenario = vault_scenario();
+    scenario.run_paths(&paths);
+
+    assert_eq!(scenario.metrics.len(), BLOCKS);
+    // Sub-step liquidations are counted in the block they happen in
+    assert_eq!(scenario.metrics.liquidation_count[9], 1);
+    let liquidations: u32 = scenario.metrics.iter().map(|m| m.liquidation_count).sum();
+    assert_eq!(liquidations, 1);
+}
+
+#[test]
+fn test_single_price_paths_match_run() {
+    let closes: Vec<f64> = wick_klines().iter().map(|k| k.close).collect();
+    let paths: Vec<Vec<f64>> = closes.iter().map(|&p| vec![p]).collect();
+
+    let mut a = vault_scenario();
+    a.run(&closes);
+    let mut b = vault_scenario();
+    b.run_paths(&paths);
+
+    for (ma, mb) in a.metrics.iter().zip(&b.metrics) {
+        assert_eq!(ma.amm_spot_price, mb.amm_spot_price);
+        assert_eq!(ma.liquidation_count, mb.liquidation_count);
+    }
+}
@@ -0,0 +1,254 @@
+//! Configurable price oracles.
+//!
+//! Feeds (external spot, AMM TWAP, median, delayed, bounded-step) compose,
+//! and failures (stale reports, outages, manipulation spikes) are injected
+//! over block ranges. Liquidation and the TWAP breaker read the oracle
+//! through the `Oracle` trait.
+
+use approx::assert_relative_eq;
+use zai_sim::amm::{Amm, TwapKind};
+use zai_sim::cdp::{CdpConfig, VaultRegistry};
+use zai_sim::circuit_breaker::*;
+use zai_sim::liquidation::{LiquidationConfig, LiquidationEngine};
+use zai_sim::oracle::*;
+use zai_sim::scenario::{Scenario, ScenarioConfig};
+
+/// AMM at $50 with a TWAP established through `block`.
+fn setup_amm(block: u64) -> Amm {
+    let mut amm = Amm::new(10000.0, 500000.0, 0.003);
+    for b in 1..=block {
+        amm.record_price(b);
+    }
+    amm
+}
+
+/// Feed `prices` to `oracle` as the external price from block 1, returning
+/// each block's report.
+fn run_oracle(oracle: &mut PriceOracle, amm: &Amm, prices: &[f64]) -> Vec<Option<f64>> {
+    prices
+        .iter()
+        .enumerate()
+        .map(|(i, &external_price)| {
+            oracle.update(&OracleInputs {
+                block: i as u64 + 1,
+                external_price,
+                amm,
+            })
+        })
+        .collect()
+}
+
+#[test]
+fn test_feeds() {
+    let amm = setup_amm(10);
+    let prices = [50.0, 40.0, 30.0, 30.0];
+
+    let mut spot = PriceOracle::new(OracleConfig::new(OracleFeed::ExternalSpot));
+    assert_eq!(run_oracle(&mut spot, &amm, &prices), prices.map(Some));
+
+    let mut delayed = PriceOracle::new(OracleConfig::new(OracleFeed::ExternalSpot.delayed(2)));
+    assert_eq!(
+        run_oracle(&mut delayed, &amm, &prices),
+        [Some(50.0), Some(50.0), Some(50.0), Some(40.0)]
+    );
+
+    let mut bounded = PriceOracle::new(OracleConfig::new(
+        OracleFeed::ExternalSpot.bounded_step(0.1),
+    ));
+    let reports = run_oracle(&mut bounded, &amm, &prices);
+    assert_relative_eq!(reports[1].unwrap(), 45.0);
+    assert_relative_eq!(reports[2].unwrap(), 40.5);
+    assert_relative_eq!(reports[3].unwrap(), 36.45);
+
+    // Median of external spot, AMM spot ($50) and AMM TWAP ($50)
+    let mut median = PriceOracle::new(OracleConfig::new(OracleFeed::median(vec![
+        OracleFeed::ExternalSpot,
+        OracleFeed::AmmSpot,
+        OracleFeed::AmmTwap { window: 5 },
+    ])));
+    let reports = run_oracle(&mut median, &amm, &prices);
+    assert!(reports.iter().all(|&p| p == Some(50.0)));
+}
+
+#[test]
+fn test_failures() {
+    let amm = setup_amm(10);
+    let prices = [50.0, 48.0, 46.0, 44.0, 42.0, 40.0, 38.0, 36.0];
+
+    let mut oracle = PriceOracle::new(
+        OracleConfig::new(OracleFeed::ExternalSpot)
+            .with_failure(OracleFailure::Outage {
+                from_block: 2,
+                to_block: 3,
+            })
+            .with_failure(OracleFailure::Spike {
+                from_block: 4,
+                to_block: 4,
+                factor: 0.5,
+            }),
+    );
+    let reports = run_oracle(&mut oracle, &amm, &prices);
+    assert_eq!(&reports[..3], &[Some(50.0), None, None]);
+    assert_eq!(reports[3], Some(22.0));
+    assert_eq!(reports[4], Some(42.0));
+
+    // A stale oracle keeps its last report until it is too old to use
+    let mut config =
+        OracleConfig::new(OracleFeed::ExternalSpot).with_failure(OracleFailure::Stale {
+            from_block: 2,
+            to_block: 6,
+        });
+    config.max_age_blocks = Some(3);
+    let mut stale = PriceOracle::new(config);
+    let reports = run_oracle(&mut stale, &amm, &prices);
+    assert_eq!(
+        reports,
+        [
+            Some(50.0),
+            Some(50.0),
+            Some(50.0),
+            Some(50.0),
+            None,
+            None,
+            Some(38.0),
+            Some(36.0)
+        ]
+    );
+    assert_eq!(stale.last_report_block(), Some(8));
+}
+
+#[test]
+fn test_liquidation_reads_oracle() {
+    let amm = setup_amm(100);
+    let config = CdpConfig {
+        min_ratio: 1.5,
+        liquidation_penalty: 0.13,
+        debt_floor: 100.0,
+        stability_fee_rate: 0.0,
+        twap_window: 48,
+        twap_kind: TwapKind::Arithmetic,
+    };
+    let mut registry = VaultRegistry::new(config);
+    // CR 1.67 at $50, 1.33 at $40
+    registry
+        .open_vault("alice", 10.0, 300.0, 100, &amm)
+        .unwrap();
+
+    let mut engine = LiquidationEngine::new(LiquidationConfig::default());
+    let mut amm = amm;
+    let oracle_config =
+        OracleConfig::new(OracleFeed::ExternalSpot).with_failure(OracleFailure::Outage {
+            from_block: 101,
+            to_block: 101,
+        });
+    let mut oracle = PriceOracle::new(oracle_config);
+
+    // Down: nothing is liquidated even though the external price crashed
+    oracle.update(&OracleInputs {
+        block: 101,
+        external_price: 40.0,
+        amm: &amm,
+    });
+    let results = engine.liquidate_with_oracle(&mut registry, &mut amm, 101, &oracle);
+    assert!(results.is_empty());
+
+    oracle.update(&OracleInputs {
+        block: 102,
+        external_price: 40.0,
+        amm: &amm,
+    });
+    let results = engine.liquidate_with_oracle(&mut registry, &mut amm, 102, &oracle);
+    assert_eq!(results.len(), 1);
+}
+
+#[test]
+fn test_twap_breaker_reads_oracle() {
+    let amm = setup_amm(100);
+    let mut oracle = PriceOracle::new(OracleConfig::new(OracleFeed::ExternalSpot));
+
+    let mut breaker = TwapBreaker::new(TwapBreakerConfig::default());
+    oracle.update(&OracleInputs {
+        block: 100,
+        external_price: 48.0,
+        amm: &amm,
+    });
+    assert_eq!(
+        breaker.check_oracle(&amm, &oracle, 100),
+        BreakerAction::None
+    );
+
+    // AMM TWAP ($50) is 25% above a manipulated $40 report
+    oracle.update(&OracleInputs {
+        block: 101,
+        external_price: 40.0,
+        amm: &amm,
+    });
+    let action = breaker.check_oracle(&amm, &oracle, 101);
+    assert!(matches!(action, BreakerAction::PauseMinting { .. }));
+
+    // No usable price pauses minting too
+    let mut breaker = TwapBreaker::new(TwapBreakerConfig::default());
+    let down = PriceOracle::new(OracleConfig::new(OracleFeed::ExternalSpot));
+    let action = breaker.check_oracle(&amm, &down, 100);
+    assert!(matches!(action, BreakerAction::PauseMinting { .. }));
+}
+
+#[test]
+fn test_scenario_oracle_outage() {
+    let config = ScenarioConfig {
+        oracle: Some(OracleConfig::new(OracleFeed::ExternalSpot).with_failure(
+            OracleFailure::Outage {
+                from_block: 10,
+                to_block: 20,
+            },
+        )),
+        ..ScenarioConfig::default()
+    };
+    let mut scenario = Scenario::new(&config);
+    scenario.run(&[50.0; 30]);
+
+    for m in &scenario.metrics {
+        let down = (10..=20).contains(&m.block);
+        assert_eq!(m.oracle_price.is_none(), down, "block {}", m.block);
+    }
+    let paused = scenario.metrics.row(9);
+    assert!(paused
+        .breaker_actions
+        .iter()
+        .any(|a| matches!(a, BreakerAction::PauseMinting { .. })));
+
+    // Without an oracle the metric stays empty
+    let mut plain = Scenario::new(&ScenarioConfig::default());
+    plain.run(&[50.0; 5]);
+    assert!(plain.metrics.iter().all(|m| m.oracle_price.is_none()));
+}
+
+#[test]
+fn test_config_round_trip() {
+    let config: OracleConfig = serde_json::from_str(
+        r#"{
+            "feed": {
+                "type": "delayed",
+                "blocks": 4,
+                "feed": {
+                    "type": "median",
+                    "feeds": [{ "type": "external_spot" }, { "type": "amm_twap", "window": 12 }]
+                }
+            },
+            "failures": [{ "type": "spike", "from_block": 100, "to_block": 110, "factor": 0.7 }]
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(
+        config.feed,
+        OracleFeed::median(vec![
+            OracleFeed::ExternalSpot,
+            OracleFeed::AmmTwap { window: 12 }
+        ])
+        .delayed(4)
+    );
+    assert!(config.failures[0].covers(110));
+    assert!(!config.failures[0].covers(111));
+    // Omitted max age means reports never expire
+    assert_eq!(config.max_age_blocks, None);
+}
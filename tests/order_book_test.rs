@@ -0,0 +1,148 @@
+//! Limit-order-book venue.
+//!
+//! A book fills level by level at fixed prices and cannot fill past its
+//! depth; the AMM always fills but slides along its curve. These tests walk
+//! the book, compare price impact against a constant-product pool of
+//! similar size, and check that routing and cross-venue arbitrage use it.
+
+use approx::assert_relative_eq;
+use zai_sim::amm::Amm;
+use zai_sim::order_book::{OrderBook, OrderBookConfig};
+use zai_sim::scenario::ScenarioConfig;
+use zai_sim::scenarios::{run_stress, ScenarioId};
+
+fn book(mid: f64) -> OrderBook {
+    OrderBook::new(OrderBookConfig::default(), mid)
+}
+
+fn amm_with_book(mid: f64) -> Amm {
+    let mut amm = Amm::new(10000.0, 500000.0, 0.003);
+    amm.order_book = Some(book(mid));
+    amm
+}
+
+#[test]
+fn test_fills_walk_levels() {
+    let mut book = book(50.0);
+    // 25 ZEC at the 0.1% level, the other 5 at 0.25%
+    let zai = book.swap_zec_for_zai(30.0).unwrap();
+    assert_relative_eq!(zai, (25.0 * 49.95 + 5.0 * 49.875) * 0.999, epsilon = 1e-9);
+    assert_eq!(book.best_bid(), Some(1));
+    assert_relative_eq!(book.bids[1], 45.0);
+    // The asks are untouched
+    assert_relative_eq!(book.ask_depth_zec(), 1575.0);
+
+    // 20% of the consumed 30 ZEC comes back each block
+    book.replenish();
+    assert_relative_eq!(book.bid_depth_zec(), 1545.0 + 30.0 * 0.2, epsilon = 1e-9);
+}
+
+#[test]
+fn test_finite_depth() {
+    let mut book = book(50.0);
+    assert_eq!(book.quote_zec_for_zai(2000.0), 0.0);
+    assert!(book.swap_zec_for_zai(2000.0).is_err());
+    assert_relative_eq!(book.bid_depth_zec(), 1575.0);
+
+    // What 1,000 ZEC would cost at the inside ask buys less: the order
+    // walks out to the 5% level
+    let zai = 1000.0 * 50.05 / 0.999;
+    let zec = book.swap_zai_for_zec(zai).unwrap();
+    assert!(zec < 1000.0 && zec > 950.0, "{}", zec);
+}
+
+#[test]
+fn test_price_impact_versus_amm() {
+    let book = book(50.0);
+    let amm = Amm::new(10000.0, 500000.0, 0.003);
+
+    // 1,000 ZEC: the book's levels stay within 5% of mid, while the AMM
+    // slides ~9%
+    let book_avg = book.quote_zec_for_zai(1000.0) / 1000.0;
+    let amm_avg = amm.quote_zec_for_zai(1000.0) / 1000.0;
+    assert!(book_avg > 48.5, "{}", book_avg);
+    assert!(amm_avg < 45.5, "{}", amm_avg);
+
+    // A small order pays the book's tighter spread and lower fee
+    assert!(book.quote_zec_for_zai(1.0) > amm.quote_zec_for_zai(1.0));
+}
+
+#[test]
+fn test_routing_uses_book() {
+    let mut amm = amm_with_book(50.0);
+    let out = amm.sell_zec(100.0, 1).unwrap();
+    assert!(out > 100.0 * 49.5, "{}", out);
+    assert_eq!(amm.reserve_zec, 10000.0);
+    assert_relative_eq!(
+        amm.order_book.as_ref().unwrap().bid_depth_zec(),
+        1475.0,
+        epsilon = 1e-9
+    );
+
+    // Too large for the book: the AMM takes it
+    let before = amm.reserve_zec;
+    amm.sell_zec(3000.0, 2).unwrap();
+    assert_eq!(amm.reserve_zec, before + 3000.0);
+}
+
+#[test]
+fn test_arbitrage_closes_gap() {
+    // Book quoted 10% above the AMM
+    let mut amm = amm_with_book(55.0);
+    let moved = amm.arbitrage_order_book(1);
+    assert!(moved > 0.0);
+    assert!(amm.spot_price() > 53.5, "{}", amm.spot_price());
+
+    // No profitable trade is left
+    assert_eq!(amm.arbitrage_order_book(1), 0.0);
+
+    // Quoted below the AMM, arbitrage runs the other way
+    let mut amm = amm_with_book(45.0);
+    assert!(amm.arbitrage_order_book(1) > 0.0);
+    assert!(amm.spot_price() < 46.5, "{}", amm.spot_price());
+}
+
+#[test]
+fn test_scenario_with_order_book() {
+    let config = ScenarioConfig {
+        order_book: Some(OrderBookConfig::default()),
+        ..ScenarioConfig::default()
+    };
+    let with_book = run_stress(ScenarioId::BlackThursday, &config, 500, 42);
+    let amm_only = run_stress(
+        ScenarioId::BlackThursday,
+        &ScenarioConfig::default(),
+        500,
+        42,
+    );
+
+    assert!(with_book
+        .metrics
+        .iter()
+        .all(|m| m.order_book_bid_depth.is_some_and(|d| d <= 1575.0 + 1e-9)));
+    assert!(amm_only
+        .metrics
+        .iter()
+        .all(|m| m.order_book_bid_depth.is_none()));
+    assert!(
+        with_book
+            .amm
+            .order_book
+            .as_ref()
+            .unwrap()
+            .cumulative_volume_zec
+            > 0.0
+    );
+
+    let min_spot = |s: &zai_sim::scenario::Scenario| {
+        s.metrics
+            .iter()
+            .map(|m| m.amm_spot_price)
+            .fold(f64::INFINITY, f64::min)
+    };
+    println!(
+        "\nMin AMM price: AMM only {:.2}, with order book {:.2}",
+        min_spot(&amm_only),
+        min_spot(&with_book)
+    );
+}
@@ -13,10 +13,12 @@ const SEED: u64 = 42;
 /// - 240-block TWAP window
 /// - 200% min collateral ratio
 fn base_config() -> ScenarioConfig {
-    let mut config = ScenarioConfig::default();
-    config.amm_initial_zec = 100_000.0;
-    config.amm_initial_zai = 5_000_000.0;
-    config.controller_config = ControllerConfig::default_tick();
+    let mut config = ScenarioConfig {
+        amm_initial_zec: 100_000.0,
+        amm_initial_zai: 5_000_000.0,
+        controller_config: ControllerConfig::default_tick(),
+        ..ScenarioConfig::default()
+    };
     config.cdp_config.twap_window = 240;
     config.cdp_config.min_ratio = 2.0;
     config
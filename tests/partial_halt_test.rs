@@ -0,0 +1,224 @@
+//! Graded (partial) halt vs full halt.
+//!
+//! The cascade breaker originally halted all agent activity. Graded mode keeps
+//! agents active with capped swaps, frozen minting and rate-limited LP
+//! withdrawals. This test compares both responses on the same crash and
+//! records recovery time, halt blocks and bad debt.
+
+use zai_sim::amm::Amm;
+use zai_sim::circuit_breaker::*;
+use zai_sim::error::ZaiSimError;
+use zai_sim::report;
+use zai_sim::scenario::{Scenario, ScenarioConfig};
+use zai_sim::scenarios::{add_agents, generate_prices, ScenarioId};
+
+const BLOCKS: usize = 1000;
+const SEED: u64 = 42;
+const TARGET_PRICE: f64 = 50.0;
+const NUM_VAULTS: usize = 40;
+
+// ═══════════════════════════════════════════════════════════════════════
+// AMM restriction primitives
+// ═══════════════════════════════════════════════════════════════════════
+
+#[test]
+fn test_swap_cap_rejects_oversized_swaps() {
+    let mut amm = Amm::new(10000.0, 500000.0, 0.003);
+    amm.max_swap_fraction = Some(0.01);
+
+    // 1% of 10000 ZEC reserve = 100 ZEC cap
+    assert!(matches!(
+        amm.swap_zec_for_zai(150.0, 1),
+        Err(ZaiSimError::VelocityLimit(_))
+    ));
+    assert!(amm.swap_zec_for_zai(50.0, 1).is_ok());
+
+    // ZAI side: 1% of ~500K reserve
+    assert!(amm.swap_zai_for_zec(10_000.0, 2).is_err());
+    assert!(amm.swap_zai_for_zec(1_000.0, 2).is_ok());
+
+    amm.max_swap_fraction = None;
+    assert!(amm.swap_zec_for_zai(150.0, 3).is_ok());
+}
+
+#[test]
+fn test_lp_withdrawal_budget_is_consumed() {
+    let mut amm = Amm::new(10000.0, 500000.0, 0.003);
+    let shares = amm.add_liquidity(100.0, 5000.0, "lp").unwrap();
+
+    amm.lp_withdrawal_budget = Some(shares / 2.0);
+    assert!(amm.remove_liquidity(shares, "lp").is_err());
+    assert!(amm.remove_liquidity(shares / 4.0, "lp").is_ok());
+    assert!(amm.remove_liquidity(shares / 4.0, "lp").is_ok());
+    // Budget exhausted
+    assert!(matches!(
+        amm.remove_liquidity(shares / 4.0, "lp"),
+        Err(ZaiSimError::VelocityLimit(_))
+    ));
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+// Engine behavior
+// ═══════════════════════════════════════════════════════════════════════
+
+fn engine(mode: HaltMode) -> CircuitBreakerEngine {
+    let mut engine = CircuitBreakerEngine::new(
+        TwapBreakerConfig::default(),
+        CascadeBreakerConfig {
+            max_liquidations_in_window: 3,
+            window_blocks: 10,
+            pause_blocks: 20,
+        },
+        DebtCeilingConfig::default(),
+    );
+    engine.halt_mode = mode;
+    engine
+}
+
+#[test]
+fn test_full_mode_halts() {
+    let amm = Amm::new(10000.0, 500000.0, 0.003);
+    let registry = zai_sim::cdp::VaultRegistry::new(Default::default());
+    let mut engine = engine(HaltMode::Full);
+
+    engine.record_liquidations(100, 5);
+    let actions = engine.check_all(&amm, &registry, 50.0, 100);
+
+    assert!(actions
+        .iter()
+        .any(|a| matches!(a, BreakerAction::EmergencyHalt { .. })));
+    assert!(engine.is_halted(101));
+    assert!(!engine.is_partially_halted(101));
+    assert!(engine.graded_restrictions(101).is_none());
+}
+
+#[test]
+fn test_graded_mode_partially_halts() {
+    let amm = Amm::new(10000.0, 500000.0, 0.003);
+    let registry = zai_sim::cdp::VaultRegistry::new(Default::default());
+    let mut engine = engine(HaltMode::Graded(GradedHaltConfig::default()));
+
+    engine.record_liquidations(100, 5);
+    let actions = engine.check_all(&amm, &registry, 50.0, 100);
+
+    assert!(actions
+        .iter()
+        .any(|a| matches!(a, BreakerAction::PartialHalt { .. })));
+    assert!(!engine.is_halted(101), "Graded mode must not fully halt");
+    assert!(engine.is_partially_halted(101));
+    assert!(engine.is_minting_paused(101), "Graded default freezes minting");
+    assert!(engine.graded_restrictions(101).is_some());
+
+    // Expires after pause_blocks
+    assert!(!engine.is_partially_halted(120));
+    assert!(engine.graded_restrictions(120).is_none());
+}
+
+#[test]
+fn test_graded_mode_without_mint_freeze() {
+    let amm = Amm::new(10000.0, 500000.0, 0.003);
+    let registry = zai_sim::cdp::VaultRegistry::new(Default::default());
+    let mut engine = engine(HaltMode::Graded(GradedHaltConfig {
+        freeze_minting: false,
+        ..GradedHaltConfig::default()
+    }));
+
+    engine.record_liquidations(100, 5);
+    engine.check_all(&amm, &registry, 50.0, 100);
+
+    assert!(engine.is_partially_halted(101));
+    assert!(!engine.is_minting_paused(101));
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+// Graded vs full halt on a liquidation cascade
+// ═══════════════════════════════════════════════════════════════════════
+
+fn make_config(mode: HaltMode) -> ScenarioConfig {
+    let mut config = ScenarioConfig::default();
+    config.cascade_breaker_config.max_liquidations_in_window = 5;
+    config.halt_mode = mode;
+    config
+}
+
+fn run_cascade(mode: HaltMode) -> Scenario {
+    let config = make_config(mode);
+    let mut scenario = Scenario::new_with_seed(&config, SEED);
+    add_agents(ScenarioId::BlackThursday, &mut scenario);
+
+    // Vaults packed just above min_ratio so the crash liquidates many at once
+    let base_cr = config.cdp_config.min_ratio + 0.05;
+    for i in 0..NUM_VAULTS {
+        let cr = base_cr + (i as f64) * 0.50 / (NUM_VAULTS - 1) as f64;
+        let collateral = cr * 1000.0 / TARGET_PRICE;
+        scenario
+            .registry
+            .open_vault(&format!("vault_{}", i), collateral, 1000.0, 0, &scenario.amm)
+            .unwrap();
+    }
+
+    let prices = generate_prices(ScenarioId::BlackThursday, BLOCKS, SEED);
+    scenario.run(&prices);
+    scenario
+}
+
+#[test]
+fn test_graded_vs_full_halt_recovery() {
+    let modes = vec![
+        ("full", HaltMode::Full),
+        ("graded", HaltMode::Graded(GradedHaltConfig::default())),
+        (
+            "graded_loose",
+            HaltMode::Graded(GradedHaltConfig {
+                max_swap_pct_of_reserve: 0.05,
+                freeze_minting: true,
+                max_lp_withdrawal_pct_per_block: 0.05,
+            }),
+        ),
+    ];
+
+    println!();
+    println!(
+        "  {:<14} {:>10} {:>10} {:>10} {:>8} {:>10} {:>10}",
+        "Mode", "Recovery", "Halt", "Partial", "Liqs", "Bad Debt", "Mean Dev"
+    );
+
+    let mut results = Vec::new();
+    for (name, mode) in modes {
+        let scenario = run_cascade(mode);
+        let m = &scenario.metrics;
+        let recovery = report::compute_recovery_blocks(m, TARGET_PRICE, 0.10);
+        let halt_blocks = m.iter().filter(|b| b.halted).count();
+        let partial_blocks = m.iter().filter(|b| b.partial_halted).count();
+        let liqs: u32 = m.iter().map(|b| b.liquidation_count).sum();
+        let bad_debt = m.last().map(|b| b.bad_debt).unwrap_or(0.0);
+        let mean_dev = m
+            .iter()
+            .map(|b| ((b.amm_spot_price - TARGET_PRICE) / TARGET_PRICE).abs())
+            .sum::<f64>()
+            / m.len() as f64;
+
+        println!(
+            "  {:<14} {:>10} {:>10} {:>10} {:>8} {:>10.2} {:>9.2}%",
+            name,
+            recovery,
+            halt_blocks,
+            partial_blocks,
+            liqs,
+            bad_debt,
+            mean_dev * 100.0
+        );
+        results.push((name, halt_blocks, partial_blocks));
+    }
+
+    let (_, full_halt, full_partial) = results[0];
+    let (_, graded_halt, graded_partial) = results[1];
+    assert_eq!(full_partial, 0, "Full mode never partially halts");
+    assert_eq!(graded_halt, 0, "Graded mode never fully halts");
+    assert!(
+        full_halt > 0 && graded_partial > 0,
+        "Cascade should trip the breaker in both modes (full={}, graded={})",
+        full_halt,
+        graded_partial
+    );
+}
@@ -0,0 +1,199 @@
+//! Liquidation penalty routing policy.
+//!
+//! The penalty is split between keeper, LPs, insurance fund, treasury and
+//! burn by shares that sum to one, and each sink's receipts are tracked.
+
+use zai_sim::agents::{CdpHolder, CdpHolderConfig};
+use zai_sim::amm::Amm;
+use zai_sim::cdp::{CdpConfig, VaultRegistry};
+use zai_sim::liquidation::{LiquidationConfig, LiquidationEngine, PenaltyRouting, PenaltySink};
+use zai_sim::scenario::{Scenario, ScenarioConfig};
+use zai_sim::scenarios::{add_agents, generate_prices, ScenarioId};
+use zai_sim::sweep::SweepEngine;
+use zai_sim::treasury::{Treasury, TreasuryConfig};
+
+fn routing() -> PenaltyRouting {
+    PenaltyRouting::new(0.2, 0.3, 0.25, 0.15, 0.1).unwrap()
+}
+
+/// One vault at CR 1.4 against a 50 ZAI/ZEC TWAP.
+fn setup(routing: Option<PenaltyRouting>) -> (Amm, VaultRegistry, LiquidationEngine, u64) {
+    let mut amm = Amm::new(10000.0, 500000.0, 0.003);
+    for b in 1..=50 {
+        amm.record_price(b);
+    }
+    let mut registry = VaultRegistry::new(CdpConfig {
+        stability_fee_rate: 0.0,
+        ..CdpConfig::default()
+    });
+    let engine = LiquidationEngine::new(LiquidationConfig {
+        penalty_routing: routing,
+        ..LiquidationConfig::default()
+    });
+    let id = registry
+        .open_vault("owner", 40.0, 1000.0, 50, &amm)
+        .unwrap();
+    registry.vaults.get_mut(&id).unwrap().collateral_zec = 28.0;
+    registry.reindex(id);
+    (amm, registry, engine, id)
+}
+
+fn receipts(engine: &LiquidationEngine) -> [f64; 5] {
+    [
+        engine.total_keeper_rewards,
+        engine.total_penalties_to_lps,
+        engine.total_penalties_to_insurance,
+        engine.total_penalties_collected,
+        engine.total_penalties_burned,
+    ]
+}
+
+#[test]
+fn test_validation() {
+    assert!(PenaltyRouting::new(0.5, 0.5, 0.0, 0.0, 0.0).is_ok());
+    assert!(PenaltyRouting::new(0.5, 0.4, 0.0, 0.0, 0.0).is_err());
+    assert!(PenaltyRouting::new(1.2, -0.2, 0.0, 0.0, 0.0).is_err());
+}
+
+#[test]
+fn test_legacy_knobs_map_to_routing() {
+    let config = LiquidationConfig {
+        keeper_reward_pct: 0.5,
+        liquidation_penalty_to_lps_pct: 0.4,
+        ..LiquidationConfig::default()
+    };
+    let r = config.routing();
+    assert_eq!((r.keeper, r.lps, r.treasury), (0.5, 0.2, 0.3));
+    assert_eq!((r.insurance_fund, r.burn), (0.0, 0.0));
+    r.validate().unwrap();
+}
+
+#[test]
+fn test_with_share_rescales_others() {
+    let r = routing().with_share(PenaltySink::Lps, 0.65).unwrap();
+    assert!((r.lps - 0.65).abs() < 1e-12);
+    // Others keep their proportions: keeper:insurance = 0.2:0.25
+    assert!((r.keeper / r.insurance_fund - 0.8).abs() < 1e-12);
+    r.validate().unwrap();
+
+    assert!(routing().with_share(PenaltySink::Burn, 1.5).is_err());
+}
+
+#[test]
+fn test_keeper_liquidation_splits_penalty() {
+    let (mut amm, mut registry, mut engine, id) = setup(Some(routing()));
+    let reserve_before = amm.reserve_zai;
+    let result = engine
+        .challenge_liquidate(id, "keeper", &mut registry, &mut amm, 51)
+        .unwrap();
+    let penalty = result.penalty_amount;
+    assert!(penalty > 0.0);
+
+    let got = receipts(&engine);
+    let want = [0.2, 0.3, 0.25, 0.15, 0.1].map(|share| penalty * share);
+    for (g, w) in got.iter().zip(want) {
+        assert!((g - w).abs() < 1e-9, "{:?} vs {:?}", got, want);
+    }
+    assert_eq!(result.keeper_reward, got[0]);
+    // LP share lands in AMM reserves on top of the swap
+    let expected = reserve_before - result.zai_from_amm + got[1];
+    assert!((amm.reserve_zai - expected).abs() < 1e-6);
+}
+
+#[test]
+fn test_keeper_share_redistributed_without_keeper() {
+    let (mut amm, mut registry, mut engine, _) = setup(Some(routing()));
+    let results = engine.transparent_liquidate(&mut registry, &mut amm, 51);
+    let penalty = results[0].penalty_amount;
+
+    let got = receipts(&engine);
+    assert_eq!(got[0], 0.0);
+    // 0.3 / 0.8 of the penalty to LPs, and so on
+    for (g, share) in got[1..].iter().zip([0.3, 0.25, 0.15, 0.1]) {
+        assert!((g - penalty * share / 0.8).abs() < 1e-9);
+    }
+    assert!((got.iter().sum::<f64>() - penalty).abs() < 1e-9);
+}
+
+#[test]
+fn test_insurance_fund_absorbs_bad_debt_first() {
+    let (mut amm, mut registry, mut engine, _) = setup(Some(routing()));
+    engine.transparent_liquidate(&mut registry, &mut amm, 51);
+
+    let mut treasury = Treasury::new(TreasuryConfig::default());
+    treasury.sync(&registry, &engine, 0.0);
+    let insurance = treasury.insurance_fund_zai;
+    let surplus = treasury.balance_zai;
+    assert!((insurance - engine.total_penalties_to_insurance).abs() < 1e-12);
+    assert!((surplus - engine.total_penalties_collected).abs() < 1e-12);
+
+    let shortfall = treasury.absorb_bad_debt(insurance + 1.0);
+    assert_eq!(shortfall, 0.0);
+    assert_eq!(treasury.insurance_fund_zai, 0.0);
+    assert!((treasury.balance_zai - (surplus - 1.0)).abs() < 1e-9);
+}
+
+#[test]
+fn test_sweep_alias_keeps_shares_summing_to_one() {
+    let mut config = ScenarioConfig::default();
+    SweepEngine::set_param(&mut config, "penalty_to_insurance", 0.4).unwrap();
+    let r = config.liquidation_config.penalty_routing.clone().unwrap();
+    assert_eq!(r.insurance_fund, 0.4);
+    // Legacy split was 50% keeper / 50% treasury
+    assert!((r.keeper - 0.3).abs() < 1e-12);
+    assert!((r.treasury - 0.3).abs() < 1e-12);
+    assert!(SweepEngine::set_param(&mut config, "penalty_to_nowhere", 0.1).is_err());
+}
+
+#[test]
+fn test_metrics_track_cumulative_receipts() {
+    let config = ScenarioConfig {
+        liquidation_config: LiquidationConfig {
+            penalty_routing: Some(routing()),
+            ..LiquidationConfig::default()
+        },
+        ..ScenarioConfig::default()
+    };
+    let mut scenario = Scenario::new_with_seed(&config, 42);
+    add_agents(ScenarioId::BlackThursday, &mut scenario);
+    // Thinly collateralized holders with no reserve get liquidated in the crash
+    for _ in 0..5 {
+        scenario.cdp_holders.push(CdpHolder::new(CdpHolderConfig {
+            target_ratio: 1.6,
+            action_threshold_ratio: 1.2,
+            reserve_zec: 0.0,
+            initial_collateral: 40.0,
+            initial_debt: 1250.0,
+        }));
+    }
+    scenario.run(&generate_prices(ScenarioId::BlackThursday, 1000, 42));
+
+    let last = scenario.metrics.last().unwrap();
+    let total = last.penalty_to_keepers
+        + last.penalty_to_lps
+        + last.penalty_to_insurance
+        + last.penalty_to_treasury
+        + last.penalty_burned;
+    let penalties: f64 = scenario
+        .liquidation_engine
+        .history
+        .iter()
+        .map(|r| r.penalty_amount)
+        .sum();
+    println!(
+        "penalties {:.2}: lps {:.2}, insurance {:.2}, treasury {:.2}, burn {:.2}",
+        penalties,
+        last.penalty_to_lps,
+        last.penalty_to_insurance,
+        last.penalty_to_treasury,
+        last.penalty_burned
+    );
+    assert!(penalties > 0.0, "Black Thursday should liquidate someone");
+    assert!((total - penalties).abs() < 1e-6);
+    assert!(scenario
+        .metrics
+        .penalty_to_insurance
+        .windows(2)
+        .all(|w| w[1] >= w[0]));
+    assert!(last.insurance_fund_balance <= last.penalty_to_insurance + 1e-9);
+}
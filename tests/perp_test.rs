@@ -0,0 +1,169 @@
+//! ZEC perp funding and basis arbitrage.
+//!
+//! The perp's funding rate follows a flat rate or a replayed schedule and is
+//! pushed back by the arbitrageurs' own position; basis arbitrageurs buy AMM
+//! spot against a perp short while funding is rich and collect it.
+
+use zai_sim::agents::*;
+use zai_sim::amm::Amm;
+use zai_sim::perp::{
+    load_funding, load_open_interest, save_funding, save_open_interest, FundingSample,
+    OpenInterestSample, PerpConfig, PerpMarket,
+};
+use zai_sim::scenario::{Scenario, ScenarioConfig};
+
+#[test]
+fn test_funding_schedule_and_arb_pressure() {
+    let mut perp = PerpMarket::new(PerpConfig {
+        funding_schedule: vec![0.0002, 0.0004],
+        funding_interval_blocks: 10,
+        ..PerpConfig::default()
+    });
+    assert_eq!(perp.base_rate(1), 0.0002);
+    assert_eq!(perp.base_rate(10), 0.0002);
+    assert_eq!(perp.base_rate(11), 0.0004);
+    // The last scheduled rate holds once the schedule runs out
+    assert_eq!(perp.base_rate(500), 0.0004);
+    assert!(perp.is_funding_block(10));
+    assert!(!perp.is_funding_block(11));
+    assert!(!perp.is_funding_block(0));
+
+    // Short 10% of open interest: 0.01 * 0.1 = 0.001 off the rate
+    perp.arb_short_zec = 20_000.0;
+    assert!((perp.funding_rate(1) - (0.0002 - 0.001)).abs() < 1e-12);
+
+    // Clamped to the cap
+    perp.arb_short_zec = -2_000_000.0;
+    assert_eq!(perp.funding_rate(1), 0.0075);
+
+    let flat = PerpMarket::new(PerpConfig::default());
+    assert_eq!(flat.funding_rate(1), 0.0001);
+}
+
+#[test]
+fn test_config_from_history_round_trips_through_csv() {
+    let dir = std::env::temp_dir().join("zai_sim_perp_history");
+    let funding_path = dir.join("funding.csv");
+    let oi_path = dir.join("open_interest.csv");
+
+    let funding = vec![
+        FundingSample {
+            timestamp_ms: 0,
+            rate: 0.0001,
+            mark_price: 30.0,
+        },
+        FundingSample {
+            timestamp_ms: 28_800_000,
+            rate: -0.0002,
+            mark_price: 29.5,
+        },
+    ];
+    let oi = vec![
+        OpenInterestSample {
+            timestamp_ms: 0,
+            open_interest_zec: 100_000.0,
+            notional: 3_000_000.0,
+        },
+        OpenInterestSample {
+            timestamp_ms: 3_600_000,
+            open_interest_zec: 300_000.0,
+            notional: 9_000_000.0,
+        },
+    ];
+    save_funding(&funding, &funding_path).unwrap();
+    save_open_interest(&oi, &oi_path).unwrap();
+    assert_eq!(load_funding(&funding_path).unwrap(), funding);
+    assert_eq!(load_open_interest(&oi_path).unwrap(), oi);
+
+    let config = PerpConfig::from_history(&funding, &oi);
+    assert_eq!(config.funding_schedule, vec![0.0001, -0.0002]);
+    assert_eq!(config.open_interest_zec, 200_000.0);
+    // Without open interest the default stands
+    let config = PerpConfig::from_history(&funding, &[]);
+    assert_eq!(
+        config.open_interest_zec,
+        PerpConfig::default().open_interest_zec
+    );
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_basis_arb_hedges_and_collects_funding() {
+    let mut amm = Amm::new(10_000.0, 500_000.0, 0.003);
+    let mut arb = BasisArbAgent::new(BasisArbConfig::default());
+
+    // Rich funding: buy spot, short the same ZEC on the perp
+    match arb.act(&mut amm, 0.001, 50.0, 1) {
+        AgentAction::BuyZec { zec_received, .. } => {
+            assert!((arb.perp_zec + zec_received).abs() < 1e-9);
+            assert!((arb.zec_balance - 1000.0 - zec_received).abs() < 1e-9);
+        }
+        other => panic!("expected a spot buy, got {:?}", other),
+    }
+    assert_eq!(arb.perp_entry_price, 50.0);
+
+    // Shorts receive positive funding
+    let short = -arb.perp_zec;
+    let paid = arb.receive_funding(0.001, 50.0);
+    assert!((paid - short * 50.0 * 0.001).abs() < 1e-9);
+    assert_eq!(arb.margin_zai, paid);
+
+    // Funding in the dead zone between exit and entry: hold
+    assert!(matches!(
+        arb.act(&mut amm, 0.0001, 50.0, 2),
+        AgentAction::None
+    ));
+
+    // Funding gone: unwind, realizing the short's PnL at the lower index
+    match arb.act(&mut amm, 0.0, 48.0, 3) {
+        AgentAction::SellZec { .. } => {}
+        other => panic!("expected an unwind, got {:?}", other),
+    }
+    assert!(arb.perp_zec.abs() < 1e-9);
+    assert!((arb.margin_zai - paid - short * 2.0).abs() < 1e-6);
+    assert_eq!(arb.trade_count, 2);
+}
+
+#[test]
+fn test_negative_funding_goes_long() {
+    let mut amm = Amm::new(10_000.0, 500_000.0, 0.003);
+    let mut arb = BasisArbAgent::new(BasisArbConfig::default());
+    let price = amm.spot_price();
+    assert!(matches!(
+        arb.act(&mut amm, -0.001, price, 1),
+        AgentAction::SellZec { .. }
+    ));
+    assert_eq!(arb.perp_zec, 25.0);
+    assert!(amm.spot_price() < price);
+    // Negative funding: shorts pay the long
+    assert!(arb.receive_funding(-0.001, price) > 0.0);
+}
+
+#[test]
+fn test_scenario_arbs_short_rich_funding() {
+    let config = ScenarioConfig {
+        perp: Some(PerpConfig {
+            funding_rate: 0.001,
+            ..PerpConfig::default()
+        }),
+        ..ScenarioConfig::default()
+    };
+    let mut scenario = Scenario::new_with_seed(&config, 42);
+    scenario
+        .basis_arbs
+        .push(BasisArbAgent::new(BasisArbConfig::default()));
+    let price = config.initial_amm_price();
+    scenario.run(&[price; 400]);
+
+    let arb = &scenario.basis_arbs[0];
+    assert!(arb.perp_zec < -100.0);
+    // One funding payment, at block 384
+    let perp = scenario.perp.as_ref().unwrap();
+    assert!(arb.funding_zai > 0.0);
+    assert_eq!(perp.total_funding_zai, arb.funding_zai);
+    assert_eq!(perp.arb_short_zec, -arb.perp_zec);
+
+    let last = scenario.metrics.last().unwrap();
+    assert_eq!(last.basis_arb_perp_zec, arb.perp_zec);
+    assert!(last.perp_funding_rate < 0.001);
+}
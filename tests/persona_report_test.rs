@@ -0,0 +1,104 @@
+//! Cross-scenario persona report.
+//!
+//! Follows one LP or vault owner through every stress scenario and
+//! summarizes their P&L, liquidation probability and worst drawdown.
+
+use zai_sim::persona::{run_persona, run_persona_scenario, Persona};
+use zai_sim::report::generate_persona_report;
+use zai_sim::scenario::ScenarioConfig;
+use zai_sim::scenarios::ScenarioId;
+
+const BLOCKS: usize = 500;
+
+fn vault(collateral_ratio: f64) -> Persona {
+    Persona::VaultOwner {
+        collateral_ratio,
+        debt_zai: 10_000.0,
+        reserve_zec: 0.0,
+    }
+}
+
+#[test]
+fn test_lp_report_covers_every_scenario() {
+    let report = run_persona(
+        &Persona::Lp {
+            capital_zai: 100_000.0,
+        },
+        &ScenarioConfig::default(),
+        BLOCKS,
+        &[42],
+    );
+
+    assert_eq!(report.scenarios.len(), ScenarioId::all().len());
+    assert_eq!(report.outcomes.len(), ScenarioId::all().len());
+    for o in &report.outcomes {
+        assert!((o.initial_value_zai - 100_000.0).abs() < 1.0);
+        assert!(!o.liquidated);
+        assert!(o.worst_drawdown_pct >= 0.0);
+    }
+    assert_eq!(report.liquidation_probability, 0.0);
+    assert!(
+        report.worst_pnl_pct < 0.0,
+        "some scenario should lose money"
+    );
+}
+
+#[test]
+fn test_steady_state_lp_roughly_flat() {
+    let outcome = run_persona_scenario(
+        &Persona::Lp {
+            capital_zai: 100_000.0,
+        },
+        ScenarioId::SteadyState,
+        &ScenarioConfig::default(),
+        BLOCKS,
+        42,
+    );
+    assert!(outcome.final_pnl_pct.abs() < 0.05, "{:?}", outcome);
+}
+
+#[test]
+fn test_vault_owner_initial_value_is_equity() {
+    let outcome = run_persona_scenario(
+        &vault(2.0),
+        ScenarioId::SteadyState,
+        &ScenarioConfig::default(),
+        BLOCKS,
+        42,
+    );
+    // 200% CR on 10K debt leaves 10K ZAI of equity
+    assert!((outcome.initial_value_zai - 10_000.0).abs() < 1.0);
+    assert!(!outcome.liquidated);
+}
+
+#[test]
+fn test_lower_cr_is_liquidated_more_often() {
+    let config = ScenarioConfig::default();
+    let risky = run_persona(&vault(1.6), &config, BLOCKS, &[42]);
+    let safe = run_persona(&vault(3.0), &config, BLOCKS, &[42]);
+    println!(
+        "liquidation probability: 160% CR {:.2}, 300% CR {:.2}",
+        risky.liquidation_probability, safe.liquidation_probability
+    );
+
+    assert!(risky.liquidation_probability > 0.0);
+    assert!(risky.liquidation_probability >= safe.liquidation_probability);
+    for o in risky.outcomes.iter().filter(|o| o.liquidated) {
+        assert!(o.liquidation_block.is_some());
+    }
+    for s in &risky.scenarios {
+        assert!((0.0..=1.0).contains(&s.liquidation_probability));
+        assert!(s.worst_pnl_pct <= s.mean_pnl_pct + 1e-12);
+    }
+}
+
+#[test]
+fn test_html_report() {
+    let report = run_persona(&vault(2.3), &ScenarioConfig::default(), 200, &[42]);
+    let html = generate_persona_report(&report);
+    assert!(html.contains("Persona Report"));
+    assert!(html.contains("Vault owner at 230% CR"));
+    for id in ScenarioId::all() {
+        assert!(html.contains(id.name()), "missing {}", id.name());
+    }
+}
@@ -0,0 +1,91 @@
+//! StableSwap and constant-product pools.
+//!
+//! A ZAI/USD StableSwap pool should trade almost 1:1 near balance and
+//! steepen sharply once one side is drained, unlike a constant-product
+//! pool of the same depth.
+
+use approx::assert_relative_eq;
+use zai_sim::pool::{Asset, Curve, Pool, PoolConfig};
+use zai_sim::scenario::{Scenario, ScenarioConfig};
+
+const DEPTH: f64 = 1_000_000.0;
+
+fn constant_product() -> PoolConfig {
+    PoolConfig {
+        curve: Curve::ConstantProduct,
+        ..PoolConfig::zai_usd(DEPTH, 100.0)
+    }
+}
+
+#[test]
+fn test_balanced_pool_is_at_peg() {
+    for config in [PoolConfig::zai_usd(DEPTH, 100.0), constant_product()] {
+        assert_relative_eq!(Pool::new(&config).spot_price(), 1.0, epsilon = 1e-9);
+    }
+}
+
+#[test]
+fn test_stableswap_is_flat_near_peg() {
+    let stable = Pool::new(&PoolConfig::zai_usd(DEPTH, 100.0));
+    let product = Pool::new(&constant_product());
+
+    // 1% of depth: StableSwap loses only the fee and a sliver of slippage
+    let stable_out = stable.quote_out(10_000.0);
+    let product_out = product.quote_out(10_000.0);
+    assert!(stable_out > 9_990.0, "{}", stable_out);
+    assert!(product_out < 9_900.0, "{}", product_out);
+
+    // Higher amplification is flatter still
+    let flatter = Pool::new(&PoolConfig::zai_usd(DEPTH, 1000.0));
+    assert!(flatter.quote_out(100_000.0) > stable.quote_out(100_000.0));
+}
+
+#[test]
+fn test_stableswap_steepens_off_peg() {
+    let mut pool = Pool::new(&PoolConfig::zai_usd(DEPTH, 100.0));
+    pool.swap_base_for_quote(500_000.0).unwrap();
+    // Half the depth sold in and the price has barely moved...
+    assert!(pool.spot_price() > 0.99, "{}", pool.spot_price());
+
+    pool.swap_base_for_quote(400_000.0).unwrap();
+    // ...but draining most of the USD side breaks the peg
+    assert!(pool.spot_price() < 0.9, "{}", pool.spot_price());
+}
+
+#[test]
+fn test_round_trip_loses_only_fees() {
+    let mut pool = Pool::new(&PoolConfig::zai_usd(DEPTH, 100.0));
+    let usd = pool.swap_base_for_quote(50_000.0).unwrap();
+    let zai = pool.swap_quote_for_base(usd).unwrap();
+    assert!(zai < 50_000.0);
+    assert!(zai > 50_000.0 * (1.0 - 2.0 * pool.swap_fee) - 1.0);
+    assert!(pool.cumulative_fees_quote > 0.0);
+    assert!(pool.swap_base_for_quote(0.0).is_err());
+}
+
+#[test]
+fn test_scenario_pools() {
+    let config = ScenarioConfig {
+        pools: vec![PoolConfig::zai_usd(DEPTH, 100.0)],
+        ..ScenarioConfig::default()
+    };
+    let mut scenario = Scenario::new(&config);
+    assert_eq!(scenario.amm.pools.len(), 1);
+    assert_eq!(scenario.amm.pools[0].base, Asset::Zai);
+
+    scenario.amm.pools[0].swap_base_for_quote(1_000.0).unwrap();
+    let reserve = scenario.amm.pools[0].reserve_base;
+    let mut changed = config.clone();
+    changed.pools[0].curve = Curve::StableSwap {
+        amplification: 500.0,
+    };
+    scenario.reconfigure(changed);
+    // The curve changes in place; reserves are kept
+    assert_eq!(scenario.amm.pools[0].reserve_base, reserve);
+    assert_eq!(
+        scenario.amm.pools[0].curve,
+        Curve::StableSwap {
+            amplification: 500.0
+        }
+    );
+}
@@ -0,0 +1,71 @@
+//! Historical event presets.
+//!
+//! Presets resolve by id to hourly windows; the bundled ones load from
+//! `data/` and replay at their real price level with the standard roster.
+
+use std::path::{Path, PathBuf};
+
+use zai_sim::error::ZaiSimError;
+use zai_sim::presets::{add_standard_agents, preset, preset_config, BLOCKS_PER_HOUR, PRESETS};
+use zai_sim::scenario::Scenario;
+
+fn data_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("data")
+}
+
+#[test]
+fn test_preset_lookup() {
+    let may = preset("may2021").unwrap();
+    assert_eq!(may.start, "2021-05-10");
+    assert_eq!(
+        may.cache_path(Path::new("data")),
+        Path::new("data/may_2021_crash_hourly.csv")
+    );
+    assert!(preset("ftx2022").is_ok());
+    assert!(preset("bear2022").is_ok());
+
+    match preset("mtgox2014") {
+        Err(ZaiSimError::InvalidInput(msg)) => assert!(msg.contains("may2021")),
+        other => panic!("expected an unknown preset error, got {:?}", other),
+    }
+
+    // Ids are unique
+    for (i, p) in PRESETS.iter().enumerate() {
+        assert!(PRESETS[i + 1..].iter().all(|q| q.id != p.id));
+    }
+}
+
+#[test]
+fn test_bundled_presets_load_from_data() {
+    for id in [
+        "covid2020",
+        "black-thursday",
+        "may2021",
+        "luna2022",
+        "ftx2022",
+        "rally2024",
+    ] {
+        let p = preset(id).unwrap();
+        let hourly = p.hourly_prices(&data_dir()).unwrap();
+        let blocks = p.block_prices(&data_dir()).unwrap();
+        assert_eq!(blocks.len(), (hourly.len() - 1) * BLOCKS_PER_HOUR, "{}", id);
+        assert_eq!(blocks[0], hourly[0]);
+    }
+}
+
+#[test]
+fn test_ftx_replay_with_standard_roster() {
+    let prices = preset("ftx2022")
+        .unwrap()
+        .block_prices(&data_dir())
+        .unwrap();
+    let config = preset_config(&prices);
+    assert_eq!(config.initial_redemption_price, prices[0]);
+
+    let mut scenario = Scenario::new_with_seed(&config, 42);
+    add_standard_agents(&mut scenario);
+    assert_eq!(scenario.arbers.len(), 1);
+    assert_eq!(scenario.cdp_holders.len(), 1);
+    scenario.run(&prices);
+    assert_eq!(scenario.metrics.len(), prices.len());
+}
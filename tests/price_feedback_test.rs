@@ -0,0 +1,96 @@
+//! Endogenous external price feedback.
+//!
+//! With `price_feedback` set, ZEC the simulation sells onto external markets
+//! (miners' off-AMM share, attacker exits, hedged liquidation collateral)
+//! moves the external price, so a crash can deepen beyond the scripted path.
+
+use zai_sim::agents::{MinerAgent, MinerAgentConfig};
+use zai_sim::external_market::{ExternalMarketConfig, PriceFeedbackConfig};
+use zai_sim::scenario::{Scenario, ScenarioConfig};
+
+fn thin_feedback(half_life: Option<f64>) -> PriceFeedbackConfig {
+    PriceFeedbackConfig {
+        market: ExternalMarketConfig {
+            depth_zai: 1_000.0,
+            recovery_half_life_blocks: half_life,
+        },
+        liquidation_external_share: 1.0,
+    }
+}
+
+fn miner_scenario(price_feedback: Option<PriceFeedbackConfig>) -> Scenario {
+    let config = ScenarioConfig {
+        price_feedback,
+        ..ScenarioConfig::default()
+    };
+    let mut scenario = Scenario::new(&config);
+    scenario
+        .miners
+        .push(MinerAgent::new(MinerAgentConfig::default()));
+    scenario
+}
+
+#[test]
+fn test_exogenous_by_default() {
+    let mut scenario = miner_scenario(None);
+    scenario.run(&[50.0; 100]);
+    assert!(scenario.external_market.is_none());
+    assert!(scenario
+        .metrics
+        .iter()
+        .all(|m| m.external_price == 50.0 && m.external_price_impact == 0.0));
+}
+
+#[test]
+fn test_miner_external_sales_push_price_down() {
+    let mut scenario = miner_scenario(Some(thin_feedback(None)));
+    scenario.run(&[50.0; 100]);
+
+    // Block 1 trades at the scripted price; impact accumulates afterwards
+    assert_eq!(scenario.metrics.external_price[0], 50.0);
+    let last = scenario.metrics.last().unwrap();
+    assert!(last.external_price_impact < 0.0);
+    assert!(last.external_price < 50.0);
+    assert!(scenario
+        .metrics
+        .external_price
+        .windows(2)
+        .all(|w| w[1] <= w[0]));
+}
+
+#[test]
+fn test_impact_recovers_toward_script() {
+    let mut sticky = miner_scenario(Some(thin_feedback(None)));
+    sticky.run(&[50.0; 200]);
+    let mut recovering = miner_scenario(Some(thin_feedback(Some(5.0))));
+    recovering.run(&[50.0; 200]);
+
+    let sticky_price = sticky.metrics.last().unwrap().external_price;
+    let recovering_price = recovering.metrics.last().unwrap().external_price;
+    assert!(recovering_price > sticky_price);
+}
+
+#[test]
+fn test_liquidation_collateral_deepens_crash() {
+    let config = ScenarioConfig {
+        use_external_oracle_for_liquidation: true,
+        price_feedback: Some(thin_feedback(None)),
+        ..ScenarioConfig::default()
+    };
+    let mut scenario = Scenario::new(&config);
+    // CR 2.0 at $50, liquidatable below $37.50
+    scenario
+        .registry
+        .open_vault("owner", 40.0, 1000.0, 0, &scenario.amm)
+        .unwrap();
+
+    let prices = [50.0, 50.0, 50.0, 50.0, 30.0, 30.0, 30.0];
+    scenario.run(&prices);
+
+    let crash = scenario.metrics.row(4);
+    assert_eq!(crash.liquidation_count, 1);
+    assert_eq!(crash.external_price, 30.0);
+    // The seized collateral's external sale lands on the next block's price
+    assert!(crash.external_price_impact < 0.0);
+    assert!(scenario.metrics.external_price[5] < 30.0);
+}
@@ -0,0 +1,90 @@
+//! Multi-pair price matrices.
+//!
+//! Several pairs' closes are aligned on shared timestamps, saved as one
+//! CSV, and their return correlations drive simulated co-moving paths.
+
+use zai_sim::price_matrix::PriceMatrix;
+
+fn series(prices: &[(u64, f64)]) -> Vec<(u64, f64)> {
+    prices.to_vec()
+}
+
+#[test]
+fn test_align_keeps_shared_timestamps() {
+    let matrix = PriceMatrix::align(vec![
+        (
+            "ZECUSDT".into(),
+            series(&[(0, 50.0), (60, 51.0), (120, 52.0), (180, 53.0)]),
+        ),
+        (
+            "BTCUSDT".into(),
+            series(&[(0, 30_000.0), (120, 30_100.0), (180, 30_200.0)]),
+        ),
+    ])
+    .unwrap();
+    assert_eq!(matrix.timestamps_ms, vec![0, 120, 180]);
+    assert_eq!(matrix.column("ZECUSDT").unwrap(), &[50.0, 52.0, 53.0]);
+    assert_eq!(
+        matrix.column("BTCUSDT").unwrap(),
+        &[30_000.0, 30_100.0, 30_200.0]
+    );
+    assert!(matrix.column("ZECBTC").is_none());
+
+    assert!(PriceMatrix::align(Vec::new()).is_err());
+    assert!(PriceMatrix::align(vec![
+        ("ZECUSDT".into(), series(&[(0, 50.0)])),
+        ("ZECUSDT".into(), series(&[(0, 50.0)])),
+    ])
+    .is_err());
+}
+
+#[test]
+fn test_save_and_load_round_trip() {
+    let matrix = PriceMatrix::align(vec![
+        ("ZECUSDT".into(), series(&[(0, 50.0), (60, 49.5)])),
+        ("ZECBTC".into(), series(&[(0, 0.0017), (60, 0.00168)])),
+    ])
+    .unwrap();
+    let path = std::env::temp_dir().join("zai_sim_price_matrix.csv");
+    matrix.save(&path).unwrap();
+    let text = std::fs::read_to_string(&path).unwrap();
+    assert!(text.starts_with("timestamp_ms,ZECUSDT,ZECBTC\n"));
+    assert_eq!(PriceMatrix::load(&path).unwrap(), matrix);
+}
+
+#[test]
+fn test_correlation_and_simulation() {
+    // BTC moves with half of ZEC's log returns; a third pair never moves
+    let zec: Vec<f64> = (0..200)
+        .map(|i| 50.0 * (0.01 * (i as f64).sin()).exp())
+        .collect();
+    let btc: Vec<f64> = zec.iter().map(|p| 30_000.0 * (p / 50.0).sqrt()).collect();
+    let rows = |prices: &[f64]| -> Vec<(u64, f64)> {
+        prices
+            .iter()
+            .enumerate()
+            .map(|(i, &p)| (i as u64 * 60_000, p))
+            .collect()
+    };
+    let matrix = PriceMatrix::align(vec![
+        ("ZECUSDT".into(), rows(&zec)),
+        ("BTCUSDT".into(), rows(&btc)),
+        ("USDCUSDT".into(), rows(&[1.0; 200])),
+    ])
+    .unwrap();
+    let corr = matrix.correlation();
+    assert!((corr[0][1] - 1.0).abs() < 1e-9);
+    assert_eq!(corr[0][2], 0.0);
+    let cov = matrix.covariance();
+    assert!((cov[1][1] - cov[0][0] / 4.0).abs() < 1e-12);
+
+    let sim = matrix.simulate(500, 7);
+    assert_eq!(sim.len(), 500);
+    assert_eq!(sim.timestamps_ms[1], 60_000);
+    assert_eq!(sim.prices[0][0], zec[0]);
+    assert!(sim.column("USDCUSDT").unwrap().iter().all(|&p| p == 1.0));
+    // Perfectly correlated history stays perfectly correlated
+    assert!((sim.correlation()[0][1] - 1.0).abs() < 1e-6);
+    assert_eq!(sim, matrix.simulate(500, 7));
+    assert_ne!(sim, matrix.simulate(500, 8));
+}
@@ -0,0 +1,140 @@
+//! Exchange price sources.
+//!
+//! Binance, Coinbase and Kraken return candles in different layouts. These
+//! tests check each adapter's parsing against recorded response shapes, so
+//! they run without network access.
+#![cfg(feature = "net")]
+
+use serde_json::json;
+use zai_sim::data_fetcher::{
+    interval_secs, parse_depth, parse_funding, parse_open_interest, Binance, Coinbase, Exchange,
+    Kraken, PriceSource,
+};
+use zai_sim::error::ZaiSimError;
+
+#[test]
+fn test_exchange_parses() {
+    assert_eq!("binance".parse::<Exchange>().unwrap(), Exchange::Binance);
+    assert_eq!("coinbase".parse::<Exchange>().unwrap(), Exchange::Coinbase);
+    assert_eq!("kraken".parse::<Exchange>().unwrap(), Exchange::Kraken);
+    assert!("mtgox".parse::<Exchange>().is_err());
+
+    assert_eq!(Exchange::Coinbase.source().default_pair(), "ZEC-USD");
+}
+
+#[test]
+fn test_interval_secs() {
+    assert_eq!(interval_secs("1m").unwrap(), 60);
+    assert_eq!(interval_secs("15m").unwrap(), 900);
+    assert_eq!(interval_secs("4h").unwrap(), 14_400);
+    assert_eq!(interval_secs("1d").unwrap(), 86_400);
+    assert!(interval_secs("").is_err());
+    assert!(interval_secs("m").is_err());
+    assert!(interval_secs("1y").is_err());
+}
+
+#[test]
+fn test_binance_parse() {
+    let body = json!([
+        [1700000000000u64, "30.1", "30.5", "29.9", "30.2", "1200.5"],
+        [1700000060000u64, "30.2", "30.3", "30.0", "30.0", "800.0"]
+    ]);
+    let klines = Binance.parse_page(&body).unwrap();
+    assert_eq!(klines.len(), 2);
+    assert_eq!(klines[0].timestamp_ms, 1_700_000_000_000);
+    assert_eq!(klines[0].high, 30.5);
+    assert_eq!(klines[1].close, 30.0);
+    assert_eq!(klines[1].volume, 800.0);
+}
+
+#[test]
+fn test_binance_parse_depth() {
+    let body = json!({
+        "lastUpdateId": 1027024,
+        "bids": [["30.10", "12.5"], ["30.05", "40.0"]],
+        "asks": [["30.20", "8.0"]]
+    });
+    let book = parse_depth(&body, 1_700_000_000_000).unwrap();
+    assert_eq!(book.timestamp_ms, 1_700_000_000_000);
+    assert_eq!(book.bids, vec![(30.10, 12.5), (30.05, 40.0)]);
+    assert_eq!(book.asks, vec![(30.20, 8.0)]);
+    assert!((book.mid().unwrap() - 30.15).abs() < 1e-9);
+
+    assert!(matches!(
+        parse_depth(&json!({"code": -1121}), 0),
+        Err(ZaiSimError::Exchange(_))
+    ));
+}
+
+#[test]
+fn test_binance_parse_funding_and_open_interest() {
+    let body = json!([
+        {"symbol": "ZECUSDT", "fundingTime": 1700006400000u64, "fundingRate": "0.00010000", "markPrice": "30.12000000"},
+        {"symbol": "ZECUSDT", "fundingTime": 1700035200000u64, "fundingRate": "-0.00025000", "markPrice": ""}
+    ]);
+    let funding = parse_funding(&body).unwrap();
+    assert_eq!(funding.len(), 2);
+    assert_eq!(funding[0].timestamp_ms, 1700006400000);
+    assert_eq!(funding[0].rate, 0.0001);
+    assert_eq!(funding[0].mark_price, 30.12);
+    assert_eq!(funding[1].rate, -0.00025);
+    assert_eq!(funding[1].mark_price, 0.0);
+
+    let body = json!([
+        {"symbol": "ZECUSDT", "sumOpenInterest": "152340.5", "sumOpenInterestValue": "4588497.86", "timestamp": 1700006400000u64}
+    ]);
+    let oi = parse_open_interest(&body).unwrap();
+    assert_eq!(oi[0].timestamp_ms, 1700006400000);
+    assert_eq!(oi[0].open_interest_zec, 152340.5);
+    assert_eq!(oi[0].notional, 4588497.86);
+
+    assert!(matches!(
+        parse_funding(&json!({"code": -1121, "msg": "Invalid symbol."})),
+        Err(ZaiSimError::Exchange(_))
+    ));
+}
+
+#[test]
+fn test_coinbase_parse_reorders_columns_and_time() {
+    // [time_s, low, high, open, close, volume], newest first
+    let body = json!([
+        [1700000060u64, 30.0, 30.3, 30.2, 30.0, 800.0],
+        [1700000000u64, 29.9, 30.5, 30.1, 30.2, 1200.5]
+    ]);
+    let klines = Coinbase.parse_page(&body).unwrap();
+    assert_eq!(klines[0].timestamp_ms, 1_700_000_000_000);
+    assert_eq!(klines[0].open, 30.1);
+    assert_eq!(klines[0].high, 30.5);
+    assert_eq!(klines[0].low, 29.9);
+    assert_eq!(klines[0].close, 30.2);
+    assert_eq!(klines[1].timestamp_ms, 1_700_000_060_000);
+}
+
+#[test]
+fn test_kraken_parse() {
+    // [time_s, open, high, low, close, vwap, volume, count]
+    let body = json!({
+        "error": [],
+        "result": {
+            "XZECZUSD": [
+                [1700000000u64, "30.1", "30.5", "29.9", "30.2", "30.15", "1200.5", 42],
+                [1700000060u64, "30.2", "30.3", "30.0", "30.0", "30.1", "800.0", 17]
+            ],
+            "last": 1700000000u64
+        }
+    });
+    let klines = Kraken.parse_page(&body).unwrap();
+    assert_eq!(klines.len(), 2);
+    assert_eq!(klines[0].timestamp_ms, 1_700_000_000_000);
+    assert_eq!(klines[0].volume, 1200.5);
+    assert_eq!(klines[1].low, 30.0);
+}
+
+#[test]
+fn test_kraken_in_band_error() {
+    let body = json!({ "error": ["EQuery:Unknown asset pair"] });
+    assert!(matches!(
+        Kraken.parse_page(&body),
+        Err(ZaiSimError::Exchange(msg)) if msg.contains("Unknown asset pair")
+    ));
+}
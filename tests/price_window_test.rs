@@ -0,0 +1,111 @@
+//! Slicing and resampling price CSVs.
+//!
+//! `--from`/`--to` pick a block or date range and `--resample` interpolates
+//! rows onto the simulation's block cadence instead of one row per block.
+
+use std::path::Path;
+
+use chrono::NaiveDate;
+use zai_sim::historical::{
+    load_timed_prices, parse_interval, resample, row_interval_secs, PriceBound, PriceWindow,
+};
+
+fn minute_rows(prices: &[f64]) -> Vec<(i64, f64)> {
+    prices
+        .iter()
+        .enumerate()
+        .map(|(i, &p)| (1_600_000_000_000 + i as i64 * 60_000, p))
+        .collect()
+}
+
+#[test]
+fn test_parse_interval_and_bounds() {
+    assert_eq!(parse_interval("75s").unwrap(), 75.0);
+    assert_eq!(parse_interval("1m").unwrap(), 60.0);
+    assert_eq!(parse_interval("2h").unwrap(), 7200.0);
+    assert_eq!(parse_interval("90").unwrap(), 90.0);
+    for bad in ["0s", "1w", "m", "-5s"] {
+        assert!(parse_interval(bad).is_err(), "{}", bad);
+    }
+
+    assert_eq!("120".parse::<PriceBound>().unwrap(), PriceBound::Block(120));
+    let day = NaiveDate::from_ymd_opt(2021, 5, 19).unwrap();
+    assert_eq!(
+        "2021-05-19".parse::<PriceBound>().unwrap(),
+        PriceBound::Time(day.and_hms_opt(0, 0, 0).unwrap())
+    );
+    assert_eq!(
+        "2021-05-19T12:30".parse::<PriceBound>().unwrap(),
+        PriceBound::Time(day.and_hms_opt(12, 30, 0).unwrap())
+    );
+    assert!("yesterday".parse::<PriceBound>().is_err());
+}
+
+#[test]
+fn test_resample_minutes_to_blocks() {
+    // 1m klines over 5 minutes become 75 s blocks: t = 0, 75, 150, 225, 300
+    let rows = minute_rows(&[50.0, 54.0, 58.0, 62.0, 66.0, 70.0]);
+    assert_eq!(row_interval_secs(&rows), Some(60.0));
+    let blocks = resample(&rows, 75.0);
+    let prices: Vec<f64> = blocks.iter().map(|r| r.1).collect();
+    assert_eq!(prices, vec![50.0, 55.0, 60.0, 65.0, 70.0]);
+    assert_eq!(blocks[1].0 - blocks[0].0, 75_000);
+
+    // Hourly rows upsample to 48 blocks per hour
+    let hourly = vec![(0, 40.0), (3_600_000, 88.0)];
+    let upsampled = resample(&hourly, 75.0);
+    assert_eq!(upsampled.len(), 49);
+    assert_eq!(upsampled[1].1, 41.0);
+}
+
+#[test]
+fn test_window_by_block_and_date() {
+    let rows = minute_rows(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+    let blocks = PriceWindow {
+        from: Some(PriceBound::Block(2)),
+        to: Some(PriceBound::Block(3)),
+        resample_secs: None,
+    };
+    let sliced: Vec<f64> = blocks.apply(&rows).unwrap().iter().map(|r| r.1).collect();
+    assert_eq!(sliced, vec![3.0, 4.0]);
+
+    // Dates compare against row timestamps, inclusive at both ends
+    let at = |minute: i64| {
+        chrono::DateTime::from_timestamp_millis(1_600_000_000_000 + minute * 60_000)
+            .unwrap()
+            .naive_utc()
+    };
+    let dates = PriceWindow {
+        from: Some(PriceBound::Time(at(1))),
+        to: Some(PriceBound::Time(at(4))),
+        resample_secs: None,
+    };
+    assert_eq!(dates.apply(&rows).unwrap().len(), 4);
+
+    let empty = PriceWindow {
+        from: Some(PriceBound::Block(10)),
+        ..PriceWindow::default()
+    };
+    assert!(empty.apply(&rows).is_err());
+}
+
+#[test]
+fn test_load_timed_prices_formats() {
+    // CryptoCompare files keep timestamps in seconds
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("data/may_2021_crash_hourly.csv");
+    let rows = load_timed_prices(&path).unwrap();
+    assert_eq!(rows[0], (1_620_604_800_000, 307.52));
+    assert_eq!(row_interval_secs(&rows), Some(3600.0));
+
+    // `fetch` output keeps them in milliseconds
+    let fetched = std::env::temp_dir().join("zai_sim_price_window.csv");
+    std::fs::write(
+        &fetched,
+        "timestamp_ms,open,high,low,close,volume\n1000,1,2,0.5,1.5,10\n61000,1.5,2,1,1.8,12\n",
+    )
+    .unwrap();
+    assert_eq!(
+        load_timed_prices(&fetched).unwrap(),
+        vec![(1000, 1.5), (61_000, 1.8)]
+    );
+}
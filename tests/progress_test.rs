@@ -0,0 +1,48 @@
+//! Live sweep progress.
+//!
+//! A monitor attached to a sweep counts every run with its verdict, across
+//! stages, and renders the latest peg deviation as a sparkline.
+
+use std::sync::Arc;
+
+use zai_sim::progress::{sparkline, ProgressMode, ProgressMonitor, ProgressTally};
+use zai_sim::scenarios::ScenarioId;
+use zai_sim::sweep::{SweepEngine, SweepParam};
+
+#[test]
+fn test_sparkline() {
+    assert_eq!(sparkline(&[0.0, 1.0, 2.0, 4.0, 8.0, 3.0], 40), "▁▂▃▅█▄");
+    assert_eq!(sparkline(&[0.0; 10], 5), "▁▁▁▁▁");
+    assert_eq!(sparkline(&[], 5), "");
+    // Each character shows the worst block in its span
+    let mut spike = vec![0.01; 1000];
+    spike[500] = 0.3;
+    let line = sparkline(&spike, 10);
+    assert_eq!(line.chars().count(), 10);
+    assert_eq!(line.chars().nth(5), Some('█'));
+    assert_eq!(line.chars().filter(|c| *c == '▁').count(), 9);
+}
+
+#[test]
+fn test_monitor_counts_every_run() {
+    let monitor = Arc::new(ProgressMonitor::new(ProgressMode::Log, 50.0));
+    let engine = SweepEngine::new(100, 42, 50.0).with_progress(monitor.clone());
+    let params = [SweepParam {
+        name: "min_ratio".into(),
+        values: vec![1.5, 2.0],
+    }];
+    let scenarios = [ScenarioId::SteadyState, ScenarioId::FlashCrash];
+
+    engine.run_grid(&params, &scenarios);
+    let tally = monitor.tally();
+    assert_eq!(tally.done, 4);
+    assert_eq!(tally.total, 4);
+    assert_eq!(tally.pass + tally.soft_fail + tally.hard_fail, 4);
+
+    // A later stage adds to the totals
+    let configs = vec![vec![("min_ratio".to_string(), 1.5)]];
+    engine.run_monte_carlo(&configs, &scenarios, 3);
+    let ProgressTally { done, total, .. } = monitor.tally();
+    assert_eq!((done, total), (10, 10));
+    monitor.finish();
+}
@@ -0,0 +1,140 @@
+//! Protocol-owned liquidity.
+//!
+//! POL is seeded at genesis, grows from a share of treasury income, only
+//! deploys near the external price and up to its pool-share cap, and with
+//! `never_withdraw` keeps its position when private LPs leave.
+
+use approx::assert_relative_eq;
+use zai_sim::amm::Amm;
+use zai_sim::protocol_liquidity::{ProtocolLiquidity, ProtocolLiquidityConfig, POL_OWNER};
+use zai_sim::scenario::ScenarioConfig;
+use zai_sim::scenarios::{run_stress, ScenarioId};
+use zai_sim::treasury::{Treasury, TreasuryConfig};
+
+fn seeded(config: ProtocolLiquidityConfig) -> (Amm, ProtocolLiquidity) {
+    let mut amm = Amm::new(10000.0, 500000.0, 0.003);
+    let mut pol = ProtocolLiquidity::new(config);
+    pol.seed(&mut amm).unwrap();
+    (amm, pol)
+}
+
+#[test]
+fn test_seed_position() {
+    let (amm, pol) = seeded(ProtocolLiquidityConfig::default());
+    // 1,000 ZEC beside the 10,000 ZEC genesis position
+    assert_relative_eq!(pol.pool_share(&amm), 1.0 / 11.0, epsilon = 1e-12);
+    assert_eq!(pol.shares(&amm), amm.lp_shares[POL_OWNER]);
+    assert_relative_eq!(pol.value_zai(&amm), 100_000.0, epsilon = 1e-6);
+    assert_relative_eq!(amm.spot_price(), 50.0, epsilon = 1e-12);
+}
+
+#[test]
+fn test_funded_from_treasury_income() {
+    let mut pol = ProtocolLiquidity::new(ProtocolLiquidityConfig {
+        penalty_share: 0.25,
+        ..ProtocolLiquidityConfig::default()
+    });
+    let mut treasury = Treasury::new(TreasuryConfig::default());
+    treasury.total_fees_collected = 1000.0;
+    treasury.total_penalties_collected = 400.0;
+    treasury.balance_zai = 1400.0;
+
+    pol.fund(&mut treasury);
+    assert_relative_eq!(pol.buffer_zai, 500.0 + 100.0);
+    assert_relative_eq!(treasury.balance_zai, 800.0);
+
+    // Only new income counts, and never more than the surplus holds
+    treasury.total_fees_collected = 3000.0;
+    treasury.balance_zai = 200.0;
+    pol.fund(&mut treasury);
+    assert_relative_eq!(pol.buffer_zai, 800.0);
+    assert_eq!(treasury.balance_zai, 0.0);
+    assert_relative_eq!(pol.total_funded_zai, 800.0);
+}
+
+#[test]
+fn test_deploys_near_peg() {
+    let (mut amm, mut pol) = seeded(ProtocolLiquidityConfig::default());
+    pol.buffer_zai = 5000.0;
+    let before = pol.shares(&amm);
+
+    // AMM 10% away from the external price: hold
+    assert_eq!(pol.rebalance(&mut amm, 55.0, 1), 0.0);
+    assert_eq!(pol.buffer_zai, 5000.0);
+
+    let added = pol.rebalance(&mut amm, 50.0, 1);
+    assert!(added > 0.0);
+    assert_relative_eq!(pol.shares(&amm), before + added);
+    // Half swapped for ZEC and the pair deployed; the swap moves the price
+    // up by more than its fee, so a little ZEC is left behind
+    assert_eq!(pol.buffer_zai, 0.0);
+    assert!(pol.buffer_zec < 1.0, "{}", pol.buffer_zec);
+}
+
+#[test]
+fn test_growth_stops_at_cap() {
+    let (mut amm, mut pol) = seeded(ProtocolLiquidityConfig {
+        max_pool_share: 0.1,
+        ..ProtocolLiquidityConfig::default()
+    });
+    pol.buffer_zai = 1_000_000.0;
+    pol.rebalance(&mut amm, 50.0, 1);
+    assert_relative_eq!(pol.pool_share(&amm), 0.1, epsilon = 1e-9);
+    assert!(pol.buffer_zai > 0.0);
+}
+
+#[test]
+fn test_never_withdraw() {
+    let config = ProtocolLiquidityConfig {
+        max_pool_share: 0.2,
+        ..ProtocolLiquidityConfig::default()
+    };
+    let (mut amm, mut pol) = seeded(config.clone());
+    assert!(pol.withdraw(&mut amm, 1.0).is_err());
+
+    // Private LPs pull 70% of theirs; POL's share rises past its cap
+    let genesis = amm.lp_shares["genesis"];
+    amm.remove_liquidity(genesis * 0.7, "genesis").unwrap();
+    assert!(pol.pool_share(&amm) > 0.2);
+    let held = pol.shares(&amm);
+    assert_eq!(pol.rebalance(&mut amm, 50.0, 1), 0.0);
+    assert_eq!(pol.shares(&amm), held);
+
+    // Without the guarantee it withdraws back to the cap
+    let (mut amm, mut pol) = seeded(ProtocolLiquidityConfig {
+        never_withdraw: false,
+        ..config
+    });
+    amm.remove_liquidity(genesis * 0.7, "genesis").unwrap();
+    assert!(pol.rebalance(&mut amm, 50.0, 1) < 0.0);
+    assert_relative_eq!(pol.pool_share(&amm), 0.2, epsilon = 1e-9);
+    assert!(pol.buffer_zec > 0.0);
+}
+
+#[test]
+fn test_scenario_metrics() {
+    let config = ScenarioConfig {
+        protocol_liquidity: Some(ProtocolLiquidityConfig::default()),
+        ..ScenarioConfig::default()
+    };
+    let scenario = run_stress(ScenarioId::BlackThursday, &config, 1000, 42);
+    let pol = scenario.protocol_liquidity.as_ref().unwrap();
+
+    assert!(scenario.metrics.iter().all(|m| m.pol_pool_share > 0.0));
+    assert!(scenario
+        .metrics
+        .pol_fees_earned_zai
+        .windows(2)
+        .all(|w| w[1] >= w[0]));
+    let last = scenario.metrics.last().unwrap();
+    assert!(last.pol_fees_earned_zai > 0.0);
+    assert_eq!(last.pol_fees_earned_zai, pol.fees_earned_zai);
+
+    let baseline = run_stress(
+        ScenarioId::BlackThursday,
+        &ScenarioConfig::default(),
+        1000,
+        42,
+    );
+    assert!(baseline.metrics.iter().all(|m| m.pol_pool_share == 0.0));
+}
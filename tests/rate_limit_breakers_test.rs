@@ -0,0 +1,206 @@
+//! Redemption drift and minting rate limiter breakers.
+//!
+//! One breaker holds the controller's redemption price within a band per
+//! window; the other pauses minting once net new debt in a window passes
+//! its limit. Both show up as breaker actions in metrics and events.
+
+use approx::assert_relative_eq;
+use zai_sim::agents::{CdpHolder, CdpHolderConfig};
+use zai_sim::amm::Amm;
+use zai_sim::cdp::{CdpConfig, VaultRegistry};
+use zai_sim::ceiling_policy::CeilingPolicyConfig;
+use zai_sim::circuit_breaker::*;
+use zai_sim::output::extract_events;
+use zai_sim::scenario::{Scenario, ScenarioConfig};
+use zai_sim::scenarios::{add_agents, generate_prices, ScenarioId};
+
+#[test]
+fn test_redemption_drift_capped_within_window() {
+    let mut limiter = RedemptionDriftLimiter::new(RedemptionDriftConfig {
+        max_drift_pct: 0.02,
+        window_blocks: 10,
+    });
+    assert_eq!(limiter.limit(1.0, 1), (1.0, BreakerAction::None));
+    assert_eq!(limiter.limit(1.01, 2), (1.01, BreakerAction::None));
+
+    let (price, action) = limiter.limit(1.05, 3);
+    assert_relative_eq!(price, 1.02);
+    assert!(matches!(action, BreakerAction::CapRedemptionDrift { .. }));
+    assert!(limiter.is_capping());
+    // Still capped: no new action for the same episode
+    let (price, action) = limiter.limit(1.06, 4);
+    assert_relative_eq!(price, 1.02);
+    assert_eq!(action, BreakerAction::None);
+    assert_eq!(limiter.limit(1.0, 5), (1.0, BreakerAction::None));
+    assert!(!limiter.is_capping());
+    assert_eq!((limiter.trigger_count, limiter.capped_blocks), (1, 2));
+
+    // Block 1 has left the window; the band now starts from block 2's 1.01
+    let (price, action) = limiter.limit(1.05, 12);
+    assert_relative_eq!(price, 1.0302);
+    assert!(matches!(action, BreakerAction::CapRedemptionDrift { .. }));
+    assert_eq!(limiter.trigger_count, 2);
+}
+
+#[test]
+fn test_mint_rate_limit_pauses_and_ages_out() {
+    let mut limiter = MintRateLimiter::new(MintRateLimiterConfig {
+        max_net_mint: 1000.0,
+        window_blocks: 10,
+        pause_blocks: 5,
+    });
+    assert_eq!(limiter.check(0.0, 1), BreakerAction::None);
+    assert!(limiter.allows(0.0, 1000.0));
+    assert!(!limiter.allows(0.0, 1001.0));
+    assert_eq!(limiter.check(800.0, 2), BreakerAction::None);
+    assert!(!limiter.allows(800.0, 300.0));
+
+    assert!(matches!(
+        limiter.check(1200.0, 3),
+        BreakerAction::RateLimitMinting { blocks: 5, .. }
+    ));
+    for block in 4..=8 {
+        assert_eq!(limiter.check(1200.0, block), BreakerAction::None);
+    }
+    assert!(!limiter.triggered);
+    // Block 1's zero debt is still the window's baseline
+    assert!(matches!(
+        limiter.check(1200.0, 9),
+        BreakerAction::RateLimitMinting { .. }
+    ));
+    // Once it ages out, block 2's 800 is
+    limiter.check(1200.0, 12);
+    assert_relative_eq!(limiter.net_minted(1200.0), 400.0);
+    assert_eq!(limiter.trigger_count, 2);
+}
+
+#[test]
+fn test_engine_pauses_minting_on_rate_limit() {
+    let amm = Amm::new(10000.0, 500000.0, 0.003);
+    let mut registry = VaultRegistry::new(CdpConfig::default());
+    let mut breakers = CircuitBreakerEngine::new(
+        TwapBreakerConfig::default(),
+        CascadeBreakerConfig::default(),
+        DebtCeilingConfig::default(),
+    );
+    breakers.mint_limiter = Some(MintRateLimiter::new(MintRateLimiterConfig {
+        max_net_mint: 5000.0,
+        window_blocks: 100,
+        pause_blocks: 20,
+    }));
+    assert!(breakers.check_all(&amm, &registry, 50.0, 1).is_empty());
+    assert!(breakers.mint_allowed(registry.total_debt, 5000.0));
+
+    for _ in 0..3 {
+        registry.open_vault("a", 100.0, 2000.0, 2, &amm).unwrap();
+    }
+    assert!(!breakers.mint_allowed(registry.total_debt, 1.0));
+    let actions = breakers.check_all(&amm, &registry, 50.0, 2);
+    assert!(matches!(
+        actions.as_slice(),
+        [BreakerAction::RateLimitMinting { blocks: 20, .. }]
+    ));
+    assert!(breakers.is_minting_paused(21));
+    assert!(!breakers.is_minting_paused(22));
+}
+
+/// Largest redemption price move over `window` blocks.
+fn max_drift(s: &Scenario, window: usize) -> f64 {
+    s.metrics
+        .redemption_price
+        .windows(window + 1)
+        .map(|w| (w[window] / w[0] - 1.0).abs())
+        .fold(0.0, f64::max)
+}
+
+fn run(config: ScenarioConfig) -> Scenario {
+    let mut scenario = Scenario::new_with_seed(&config, 42);
+    add_agents(ScenarioId::BlackThursday, &mut scenario);
+    scenario.run(&generate_prices(ScenarioId::BlackThursday, 1000, 42));
+    scenario
+}
+
+#[test]
+fn test_scenario_redemption_drift_limited() {
+    let free = run(ScenarioConfig::default());
+    let unlimited = max_drift(&free, 100);
+    assert!(free.metrics.iter().all(|m| !m.redemption_capped));
+    assert!(unlimited > 0.0);
+
+    let limit = unlimited / 2.0;
+    let limited = run(ScenarioConfig {
+        redemption_drift_limiter: Some(RedemptionDriftConfig {
+            max_drift_pct: limit,
+            window_blocks: 100,
+        }),
+        ..ScenarioConfig::default()
+    });
+    assert!(max_drift(&limited, 100) <= limit + 1e-12);
+    assert!(limited.metrics.iter().any(|m| m.redemption_capped));
+
+    let events = extract_events(&limited.metrics);
+    let caps = events
+        .iter()
+        .filter(|e| e.event_type == "cap_redemption_drift")
+        .count();
+    assert!(caps > 0);
+    assert_eq!(
+        caps as u64,
+        limited
+            .breakers
+            .redemption_limiter
+            .as_ref()
+            .unwrap()
+            .trigger_count
+    );
+    println!(
+        "\nBlack Thursday: max 100-block redemption drift {:.3}% unlimited, {:.3}% limited ({} capping episodes)",
+        unlimited * 100.0,
+        max_drift(&limited, 100) * 100.0,
+        caps
+    );
+}
+
+#[test]
+fn test_scenario_mint_rate_spreads_bootstrap() {
+    let bootstrap = |mint_rate_limiter| {
+        let config = ScenarioConfig {
+            debt_ceiling_config: DebtCeilingConfig {
+                initial_ceiling: 5_000.0,
+                min_ceiling: 5_000.0,
+                ..DebtCeilingConfig::default()
+            },
+            ceiling_policy: Some(CeilingPolicyConfig::default()),
+            mint_rate_limiter,
+            ..ScenarioConfig::default()
+        };
+        let mut scenario = Scenario::new_with_seed(&config, 42);
+        add_agents(ScenarioId::SteadyState, &mut scenario);
+        for _ in 0..20 {
+            scenario.cdp_holders.push(CdpHolder::new(CdpHolderConfig {
+                initial_collateral: 100.0,
+                initial_debt: 1000.0,
+                ..CdpHolderConfig::default()
+            }));
+        }
+        scenario.run(&generate_prices(ScenarioId::SteadyState, 2000, 42));
+        scenario
+    };
+
+    let unlimited = bootstrap(None);
+    assert_eq!(unlimited.metrics.last().unwrap().vault_count, 20);
+
+    let limited = bootstrap(Some(MintRateLimiterConfig {
+        max_net_mint: 3000.0,
+        window_blocks: 500,
+        pause_blocks: 48,
+    }));
+    // No more than three 1,000 ZAI vaults open within any window
+    assert!(limited
+        .metrics
+        .vault_count
+        .windows(500)
+        .all(|w| w[499].saturating_sub(w[0]) <= 3));
+    let admitted = limited.ceiling_policy.as_ref().unwrap().vaults_admitted;
+    assert!(admitted > 0 && admitted < 15, "{}", admitted);
+}
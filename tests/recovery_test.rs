@@ -34,9 +34,11 @@ fn recovery_prices(blocks: usize) -> Vec<f64> {
 }
 
 fn config_5m() -> ScenarioConfig {
-    let mut config = ScenarioConfig::default();
-    config.amm_initial_zec = 100_000.0;
-    config.amm_initial_zai = 5_000_000.0;
+    let mut config = ScenarioConfig {
+        amm_initial_zec: 100_000.0,
+        amm_initial_zai: 5_000_000.0,
+        ..ScenarioConfig::default()
+    };
     config.cdp_config.min_ratio = 2.0;
     config.cdp_config.twap_window = 240;
     config.controller_config = ControllerConfig::default_tick();
@@ -89,13 +91,13 @@ fn recovery_scenario() {
     let phase2_end = 5500;
 
     // End of phase 1 (crash bottom)
-    let p1_metrics = &scenario.metrics[phase1_end - 1];
+    let p1_metrics = scenario.metrics.row(phase1_end - 1);
     let p1_spot = p1_metrics.amm_spot_price;
     let p1_ext = p1_metrics.external_price;
     let p1_gap_pct = ((p1_spot - p1_ext) / p1_ext).abs() * 100.0;
 
     // End of phase 2 (held at bottom)
-    let p2_metrics = &scenario.metrics[phase2_end - 1];
+    let p2_metrics = scenario.metrics.row(phase2_end - 1);
     let p2_spot = p2_metrics.amm_spot_price;
     let p2_ext = p2_metrics.external_price;
     let p2_gap_pct = ((p2_spot - p2_ext) / p2_ext).abs() * 100.0;
@@ -110,15 +112,15 @@ fn recovery_scenario() {
     let p3_cr_gap = p3_metrics.mean_collateral_ratio_twap - p3_metrics.mean_collateral_ratio_ext;
 
     // Max zombies during phases
-    let max_zombies_p2 = scenario.metrics[phase1_end..phase2_end]
+    let max_zombies_p2 = scenario.metrics.zombie_vault_count[phase1_end..phase2_end]
         .iter()
-        .map(|m| m.zombie_vault_count)
+        .copied()
         .max()
         .unwrap_or(0);
 
-    let max_zombies_p3 = scenario.metrics[phase2_end..]
+    let max_zombies_p3 = scenario.metrics.zombie_vault_count[phase2_end..]
         .iter()
-        .map(|m| m.zombie_vault_count)
+        .copied()
         .max()
         .unwrap_or(0);
 
@@ -0,0 +1,252 @@
+//! Liquity-style redemption vs the redemption-rate controller.
+//!
+//! Redemption lets ZAI holders swap ZAI for collateral at face value, drawn
+//! from the lowest-CR vaults first. The RedeemerAgent buys discounted ZAI on
+//! the AMM and redeems it, which pushes the AMM back toward par directly
+//! instead of through the controller's slow rate adjustment.
+
+use zai_sim::agents::{CdpHolder, CdpHolderConfig, RedeemerAgent, RedeemerConfig};
+use zai_sim::amm::Amm;
+use zai_sim::cdp::{CdpConfig, VaultRegistry};
+use zai_sim::controller::{ControllerConfig, ControllerMode};
+use zai_sim::liquidation::{LiquidationConfig, LiquidationEngine};
+use zai_sim::scenario::{Scenario, ScenarioConfig};
+use zai_sim::scenarios::{add_agents, generate_prices, ScenarioId};
+
+const BLOCKS: usize = 1000;
+const SEED: u64 = 42;
+const TARGET_PRICE: f64 = 50.0;
+
+fn setup() -> (Amm, VaultRegistry, LiquidationEngine) {
+    let mut amm = Amm::new(10000.0, 500000.0, 0.003);
+    for b in 1..=50 {
+        amm.record_price(b);
+    }
+    let registry = VaultRegistry::new(CdpConfig {
+        stability_fee_rate: 0.0,
+        ..CdpConfig::default()
+    });
+    let engine = LiquidationEngine::new(LiquidationConfig::default());
+    (amm, registry, engine)
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+// Redemption mechanics
+// ═══════════════════════════════════════════════════════════════════════
+
+#[test]
+fn test_redeem_lowest_cr_first_at_face_value() {
+    let (amm, mut registry, mut engine) = setup();
+    // CR 3.0, 1.6, 2.0 at price 50
+    let high = registry.open_vault("high", 60.0, 1000.0, 50, &amm).unwrap();
+    let low = registry.open_vault("low", 32.0, 1000.0, 50, &amm).unwrap();
+    let mid = registry.open_vault("mid", 40.0, 1000.0, 50, &amm).unwrap();
+
+    let result = engine
+        .redeem("r", 500.0, TARGET_PRICE, &mut registry, &amm, 51)
+        .unwrap();
+
+    assert_eq!(result.vaults_touched.len(), 1);
+    assert_eq!(result.vaults_touched[0].0, low);
+    assert!((result.zai_redeemed - 500.0).abs() < 1e-9);
+
+    // 500 ZAI at 50 ZAI/ZEC = 10 ZEC drawn, minus 0.5% fee
+    let fee = LiquidationConfig::default().redemption_fee_pct;
+    assert!((result.fee_zec - 10.0 * fee).abs() < 1e-9);
+    assert!((result.zec_received - 10.0 * (1.0 - fee)).abs() < 1e-9);
+
+    let v = registry.get_vault(low).unwrap();
+    assert!((v.debt_zai - 500.0).abs() < 1e-9);
+    assert!((v.collateral_zec - 22.0).abs() < 1e-9);
+    assert!((registry.total_debt - 2500.0).abs() < 1e-6);
+
+    assert_eq!(registry.get_vault(high).unwrap().debt_zai, 1000.0);
+    assert_eq!(registry.get_vault(mid).unwrap().debt_zai, 1000.0);
+}
+
+#[test]
+fn test_full_redemption_closes_vault_and_returns_surplus() {
+    let (amm, mut registry, mut engine) = setup();
+    let low = registry.open_vault("low", 32.0, 1000.0, 50, &amm).unwrap();
+    let next = registry.open_vault("next", 40.0, 1000.0, 50, &amm).unwrap();
+
+    let result = engine
+        .redeem("r", 1500.0, TARGET_PRICE, &mut registry, &amm, 51)
+        .unwrap();
+
+    assert!(registry.get_vault(low).is_none());
+    // 32 ZEC - 20 ZEC drawn = 12 ZEC back to the owner
+    let (id, owner, returned) = &result.vaults_closed[0];
+    assert_eq!((*id, owner.as_str()), (low, "low"));
+    assert!((returned - 12.0).abs() < 1e-9);
+    assert_eq!(result.surplus_collateral_returned, *returned);
+    assert!((registry.get_vault(next).unwrap().debt_zai - 500.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_redemption_respects_debt_floor() {
+    let (amm, mut registry, mut engine) = setup();
+    let id = registry.open_vault("v", 40.0, 1000.0, 50, &amm).unwrap();
+
+    // Redeeming 950 would leave 50 < floor of 100; capped at 900
+    let result = engine
+        .redeem("r", 950.0, TARGET_PRICE, &mut registry, &amm, 51)
+        .unwrap();
+
+    assert!((result.zai_redeemed - 900.0).abs() < 1e-9);
+    assert!((registry.get_vault(id).unwrap().debt_zai - 100.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_redemption_skips_underwater_vaults() {
+    let (amm, mut registry, mut engine) = setup();
+    let id = registry.open_vault("v", 40.0, 1000.0, 50, &amm).unwrap();
+
+    // At a redemption price of 20, the vault is worth 800 < 1000 debt
+    let result = engine.redeem("r", 100.0, 20.0, &mut registry, &amm, 51);
+    assert!(result.is_err());
+    assert_eq!(registry.get_vault(id).unwrap().debt_zai, 1000.0);
+}
+
+#[test]
+fn test_redeemer_acts_only_below_par() {
+    let (mut amm, mut registry, mut engine) = setup();
+    registry.open_vault("v", 100.0, 2000.0, 50, &amm).unwrap();
+    let mut redeemer = RedeemerAgent::new(RedeemerConfig::default());
+
+    // At par: no action
+    redeemer.act(&mut amm, &mut registry, &mut engine, TARGET_PRICE, 51);
+    assert_eq!(redeemer.total_redeemed_zai, 0.0);
+
+    // Push ZAI below par (more ZAI per ZEC on the AMM)
+    amm.swap_zai_for_zec(50_000.0, 52).unwrap();
+    redeemer.act(&mut amm, &mut registry, &mut engine, TARGET_PRICE, 53);
+    assert!(redeemer.total_redeemed_zai > 0.0);
+    assert!(redeemer.total_profit_zec > 0.0);
+    assert!(registry.total_debt < 2000.0);
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+// Scenario-by-scenario comparison: controller vs redemption
+// ═══════════════════════════════════════════════════════════════════════
+
+#[derive(Clone, Copy, PartialEq)]
+enum PegMechanism {
+    Controller,
+    Redemption,
+    Both,
+}
+
+fn frozen_controller() -> ControllerConfig {
+    ControllerConfig {
+        mode: ControllerMode::PI { kp: 0.0, ki: 0.0 },
+        ..ControllerConfig::default_pi()
+    }
+}
+
+fn run(sid: ScenarioId, mechanism: PegMechanism) -> Scenario {
+    let mut config = ScenarioConfig::default();
+    if mechanism == PegMechanism::Redemption {
+        config.controller_config = frozen_controller();
+    }
+
+    let mut scenario = Scenario::new_with_seed(&config, SEED);
+    add_agents(sid, &mut scenario);
+    if mechanism != PegMechanism::Controller {
+        scenario
+            .redeemers
+            .push(RedeemerAgent::new(RedeemerConfig::default()));
+    }
+
+    // 20 vaults between 200% and 400% CR to redeem against
+    for i in 0..20 {
+        let cr = 2.0 + i as f64 * 0.1;
+        scenario
+            .registry
+            .open_vault(
+                &format!("vault_{}", i),
+                cr * 2000.0 / TARGET_PRICE,
+                2000.0,
+                0,
+                &scenario.amm,
+            )
+            .unwrap();
+    }
+
+    let prices = generate_prices(sid, BLOCKS, SEED);
+    scenario.run(&prices);
+    scenario
+}
+
+fn mean_peg_dev(scenario: &Scenario) -> f64 {
+    let m = &scenario.metrics;
+    m.iter()
+        .map(|b| ((b.amm_spot_price - TARGET_PRICE) / TARGET_PRICE).abs())
+        .sum::<f64>()
+        / m.len() as f64
+}
+
+#[test]
+fn test_controller_vs_redemption_by_scenario() {
+    println!();
+    println!(
+        "  {:<20} {:>12} {:>12} {:>12} {:>12}",
+        "Scenario", "Controller", "Redemption", "Both", "Redeemed"
+    );
+
+    let mut any_redeemed = false;
+    for sid in ScenarioId::all() {
+        let ctrl = run(sid, PegMechanism::Controller);
+        let redeem = run(sid, PegMechanism::Redemption);
+        let both = run(sid, PegMechanism::Both);
+
+        let redeemed = redeem.liquidation_engine.total_redeemed_zai;
+        any_redeemed |= redeemed > 0.0;
+
+        assert_eq!(ctrl.liquidation_engine.total_redeemed_zai, 0.0);
+        assert!(
+            redeem.registry.total_debt <= ctrl.registry.total_debt + 1e-6 || redeemed == 0.0,
+            "{}: redemption should retire debt",
+            sid.name()
+        );
+
+        println!(
+            "  {:<20} {:>11.2}% {:>11.2}% {:>11.2}% {:>12.0}",
+            sid.name(),
+            mean_peg_dev(&ctrl) * 100.0,
+            mean_peg_dev(&redeem) * 100.0,
+            mean_peg_dev(&both) * 100.0,
+            redeemed,
+        );
+    }
+
+    assert!(any_redeemed, "ZAI should trade below par in at least one scenario");
+}
+
+#[test]
+fn test_redeemed_vault_collateral_returns_to_holder() {
+    let mut scenario = Scenario::new_with_seed(&ScenarioConfig::default(), SEED);
+    scenario.cdp_holders.push(CdpHolder::new(CdpHolderConfig {
+        reserve_zec: 0.0,
+        initial_collateral: 40.0,
+        initial_debt: 1000.0,
+        ..CdpHolderConfig::default()
+    }));
+    scenario
+        .redeemers
+        .push(RedeemerAgent::new(RedeemerConfig::default()));
+    scenario.initialize_agents();
+    let id = scenario.cdp_holders[0].vault_id.unwrap();
+
+    // ZAI well below par: the redeemer buys enough to close the vault
+    scenario.amm.swap_zai_for_zec(50_000.0, 1).unwrap();
+    scenario.step(1, TARGET_PRICE);
+
+    let redemption = &scenario.liquidation_engine.redemption_history[0];
+    let (closed, _, returned) = redemption.vaults_closed[0].clone();
+    assert_eq!(closed, id);
+    assert!(returned > 0.0);
+    let holder = &scenario.cdp_holders[0];
+    assert_eq!(holder.vault_id, None);
+    assert_eq!(holder.reserve_zec, returned);
+}
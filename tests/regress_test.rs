@@ -0,0 +1,56 @@
+//! Golden-run regression checks.
+//!
+//! A recorded baseline must match a rerun exactly, and changed outcomes
+//! must be reported with their deltas against the tolerance.
+
+use zai_sim::regress::{self, Baseline, Tolerance};
+use zai_sim::scenario::ScenarioConfig;
+use zai_sim::scenarios::ScenarioId;
+
+#[test]
+fn test_rerun_matches_baseline() {
+    let config = ScenarioConfig::default();
+    let baseline = Baseline::record(&config, 100, 7);
+    assert_eq!(baseline.scenarios.len(), ScenarioId::all().len());
+
+    let dir = std::env::temp_dir().join("zai_sim_regress_baseline");
+    let _ = std::fs::remove_dir_all(&dir);
+    baseline.save(&dir).unwrap();
+    let stored = Baseline::load(&dir).unwrap();
+    assert_eq!(stored, baseline);
+
+    let report = regress::regress(&stored, &config, Tolerance::default());
+    assert!(report.passed());
+    assert!(report.deltas.is_empty(), "{:?}", report.deltas);
+}
+
+#[test]
+fn test_changes_reported_against_tolerance() {
+    let baseline = Baseline::record(&ScenarioConfig::default(), 100, 7);
+    let mut current = baseline.clone();
+    let steady = current.scenarios.get_mut("steady_state").unwrap();
+    let bad_debt = steady.get_mut("total_bad_debt").unwrap();
+    *bad_debt = Some(bad_debt.unwrap() + 1e-3);
+    current.scenarios.remove("flash_crash");
+
+    let loose = Tolerance {
+        relative: 0.0,
+        absolute: 1e-2,
+    };
+    let report = regress::compare(&baseline, &current, loose);
+    assert_eq!(report.deltas.len(), 1);
+    let delta = &report.deltas[0];
+    assert_eq!(delta.metric, "total_bad_debt");
+    assert!(delta.within_tolerance);
+    assert!((delta.delta().unwrap() - 1e-3).abs() < 1e-9);
+    assert_eq!(report.unmatched, vec!["flash_crash".to_string()]);
+    assert!(!report.passed());
+
+    current.scenarios.insert(
+        "flash_crash".to_string(),
+        baseline.scenarios["flash_crash"].clone(),
+    );
+    let report = regress::compare(&baseline, &current, Tolerance::default());
+    assert!(!report.passed());
+    assert_eq!(report.regressions().count(), 1);
+}
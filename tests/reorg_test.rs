@@ -0,0 +1,173 @@
+//! Chain reorg injection.
+//!
+//! After a block the latest blocks may be orphaned: the scenario rolls back
+//! to the state before them and re-mines them at the same prices with
+//! arbitrageurs last and agents in reverse order, unwinding trades and
+//! liquidations.
+
+use std::sync::{Arc, Mutex};
+
+use zai_sim::agents::{CdpHolder, CdpHolderConfig};
+use zai_sim::error::ZaiSimError;
+use zai_sim::metrics_sink::{MetricsSink, StreamConfig};
+use zai_sim::reorg::{ReorgConfig, ReorgInjector};
+use zai_sim::scenario::{BlockMetrics, Scenario, ScenarioConfig};
+use zai_sim::scenarios::{add_agents, generate_prices, ScenarioId};
+
+#[test]
+fn test_injector_keeps_latest_points() {
+    let mut injector = ReorgInjector::new(
+        ReorgConfig {
+            probability: 1.0,
+            max_depth: 3,
+        },
+        1,
+    );
+    assert!(injector.draw().is_none());
+    for block in 1..=5 {
+        injector.push_state(block, vec![block as u8]);
+    }
+    // Only blocks 3..=5 can be orphaned; the point returned is the state
+    // before the first orphaned block
+    let (from, state) = injector.draw().unwrap();
+    assert!((3..=5).contains(&from), "{}", from);
+    assert_eq!(state, vec![from as u8]);
+    // Points after it went with it
+    let mut seen = Vec::new();
+    while let Some((b, _)) = injector.draw() {
+        seen.push(b);
+    }
+    assert!(seen.iter().all(|&b| b >= 3 && b < from));
+}
+
+#[test]
+fn test_zero_probability_never_reorgs() {
+    let mut injector = ReorgInjector::new(
+        ReorgConfig {
+            probability: 0.0,
+            ..ReorgConfig::default()
+        },
+        1,
+    );
+    for block in 1..=1000 {
+        injector.push_state(block, Vec::new());
+        assert!(injector.draw().is_none());
+    }
+}
+
+fn run(reorg: Option<ReorgConfig>) -> Scenario {
+    run_with(reorg, |_| {})
+}
+
+fn run_with(reorg: Option<ReorgConfig>, setup: impl FnOnce(&mut Scenario)) -> Scenario {
+    let config = ScenarioConfig {
+        reorg,
+        ..ScenarioConfig::default()
+    };
+    let mut scenario = Scenario::new_with_seed(&config, 42);
+    add_agents(ScenarioId::BlackThursday, &mut scenario);
+    for i in 0..10 {
+        let size = (i % 3 + 1) as f64;
+        scenario.cdp_holders.push(CdpHolder::new(CdpHolderConfig {
+            target_ratio: 1.6,
+            action_threshold_ratio: 1.2,
+            reserve_zec: 0.0,
+            initial_collateral: 40.0 * size,
+            initial_debt: 1250.0 * size,
+        }));
+    }
+    setup(&mut scenario);
+    scenario.run(&generate_prices(ScenarioId::BlackThursday, 300, 42));
+    scenario
+}
+
+#[test]
+fn test_no_reorgs_matches_final_chain() {
+    let final_chain = run(None);
+    let never = run(Some(ReorgConfig {
+        probability: 0.0,
+        ..ReorgConfig::default()
+    }));
+    assert_eq!(final_chain.metrics.len(), never.metrics.len());
+    for (a, b) in final_chain.metrics.iter().zip(&never.metrics) {
+        assert_eq!(a.amm_spot_price, b.amm_spot_price);
+        assert_eq!(a.liquidation_count, b.liquidation_count);
+        assert_eq!(b.reorgs, 0);
+    }
+}
+
+#[test]
+fn test_scenario_under_reorgs() {
+    let final_chain = run(None);
+    let reorged = run(Some(ReorgConfig {
+        probability: 0.2,
+        max_depth: 3,
+    }));
+
+    // Orphaned blocks leave no trace in the output
+    let blocks: Vec<u64> = reorged.metrics.iter().map(|m| m.block).collect();
+    assert_eq!(blocks, (1..=300).collect::<Vec<u64>>());
+    assert!(reorged.metrics.reorgs.windows(2).all(|w| w[1] >= w[0]));
+
+    let events = &reorged.reorg.as_ref().unwrap().events;
+    assert!(events.len() > 20, "{}", events.len());
+    assert!(events.iter().all(|e| (1..=3).contains(&e.depth)));
+    assert_eq!(reorged.metrics.last().unwrap().reorgs, events.len() as u32);
+    assert!(events
+        .iter()
+        .any(|e| e.orphaned_spot_price != e.replayed_spot_price));
+
+    let liquidations = |s: &Scenario| s.liquidation_engine.history.len();
+    let orphaned: u32 = events.iter().map(|e| e.orphaned_liquidations).sum();
+    let replayed: u32 = events.iter().map(|e| e.replayed_liquidations).sum();
+    println!(
+        "\nReorgs: {} (deepest {}); liquidations final chain {}, reorged {}; orphaned {} replaced by {}",
+        events.len(),
+        events.iter().map(|e| e.depth).max().unwrap_or(0),
+        liquidations(&final_chain),
+        liquidations(&reorged),
+        orphaned,
+        replayed
+    );
+}
+
+/// Records each block written to it with its AMM spot price.
+#[derive(Clone, Default)]
+struct BlockLog(Arc<Mutex<Vec<(u64, f64)>>>);
+
+impl MetricsSink for BlockLog {
+    fn write(&mut self, metrics: &BlockMetrics) -> Result<(), ZaiSimError> {
+        self.0
+            .lock()
+            .unwrap()
+            .push((metrics.block, metrics.amm_spot_price));
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), ZaiSimError> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_streams_only_final_blocks() {
+    let log = BlockLog::default();
+    let sink = log.clone();
+    let reorged = run_with(
+        Some(ReorgConfig {
+            probability: 0.2,
+            max_depth: 3,
+        }),
+        |s| s.stream_metrics(sink, StreamConfig::default()),
+    );
+    assert!(!reorged.reorg.as_ref().unwrap().events.is_empty());
+
+    // Each block reaches the sink once, as it ended up on the chain
+    let streamed = log.0.lock().unwrap();
+    let canonical: Vec<(u64, f64)> = reorged
+        .metrics
+        .iter()
+        .map(|m| (m.block, m.amm_spot_price))
+        .collect();
+    assert_eq!(*streamed, canonical);
+}
@@ -1,3 +1,5 @@
+use zai_sim::circuit_breaker::TwapBreakerConfig;
+use zai_sim::liquidation::{LiquidationMode, LiquidationResult};
 use zai_sim::output;
 use zai_sim::report::*;
 use zai_sim::scenario::ScenarioConfig;
@@ -203,6 +205,351 @@ fn test_master_summary_links() {
     );
 }
 
+// ═══════════════════════════════════════════════════════════════════════
+// Breaker Timeline Tests
+// ═══════════════════════════════════════════════════════════════════════
+
+#[test]
+fn test_breaker_timeline_tracks_pauses() {
+    // A hair-trigger TWAP breaker pauses minting through the crash
+    let config = ScenarioConfig {
+        twap_breaker_config: TwapBreakerConfig {
+            max_twap_change_pct: 0.01,
+            ..TwapBreakerConfig::default()
+        },
+        ..ScenarioConfig::default()
+    };
+    let scenario = run_stress(ScenarioId::BlackThursday, &config, 500, TEST_SEED);
+    let metrics = &scenario.metrics;
+    let timeline = breaker_timeline(metrics);
+    assert!(timeline.windows(2).all(|w| w[0].block <= w[1].block));
+    let breaker_events = output::extract_events(metrics)
+        .iter()
+        .filter(|e| e.event_type != "liquidation")
+        .count();
+    assert!(timeline.len() <= breaker_events);
+
+    let pauses: Vec<_> = timeline
+        .iter()
+        .filter(|e| e.kind == "pause_minting")
+        .collect();
+    assert!(!pauses.is_empty());
+    let paused = |b: u64| {
+        metrics
+            .iter()
+            .find(|m| m.block == b)
+            .unwrap()
+            .minting_paused
+    };
+    for e in &pauses {
+        // Paused from the block after the trigger until the release
+        let end = e.released_at.unwrap_or(metrics.last().unwrap().block + 1);
+        assert!((e.block + 1..end).all(&paused));
+        if let Some(released) = e.released_at {
+            assert!(!paused(released));
+        }
+    }
+
+    let html = generate_report(metrics, &config, "black_thursday", 50.0);
+    assert!(html.contains("Breaker Timeline"));
+    assert!(html.contains("Breaker Events"));
+    assert!(html.contains("Minting Paused"));
+    assert!(html.contains(&format!(
+        "<tr><td>{}</td><td>pause_minting</td>",
+        pauses[0].block
+    )));
+}
+
+#[test]
+fn test_report_without_breaker_events() {
+    let config = ScenarioConfig::default();
+    let scenario = run_stress(ScenarioId::SteadyState, &config, 100, TEST_SEED);
+    assert!(breaker_timeline(&scenario.metrics).is_empty());
+    let html = generate_report(&scenario.metrics, &config, "steady_state", 50.0);
+    assert!(html.contains("No breaker triggered"));
+    assert!(html.contains("const EP=[];"));
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+// Liquidation Table Tests
+// ═══════════════════════════════════════════════════════════════════════
+
+fn liquidation(
+    block: u64,
+    vault_id: u64,
+    mode: LiquidationMode,
+    bad_debt: f64,
+) -> LiquidationResult {
+    LiquidationResult {
+        vault_id,
+        owner: format!("holder_{}", vault_id),
+        mode,
+        collateral_seized: 20.0,
+        debt_to_cover: 900.0,
+        zai_from_amm: 950.0,
+        zai_from_keepers: 0.0,
+        penalty_amount: 50.0,
+        keeper_reward: 10.0,
+        surplus_to_owner: 0.0,
+        collateral_returned: 0.0,
+        bad_debt,
+        block,
+    }
+}
+
+#[test]
+fn test_report_lists_liquidations() {
+    let config = ScenarioConfig::default();
+    let scenario = run_stress(ScenarioId::SteadyState, &config, 50, TEST_SEED);
+    let liquidations = [
+        liquidation(12, 7, LiquidationMode::Transparent, 0.0),
+        liquidation(
+            30,
+            3,
+            LiquidationMode::ChallengeResponse {
+                keeper: "keeper_1".to_string(),
+            },
+            25.5,
+        ),
+    ];
+    let html =
+        generate_report_with_liquidations(&scenario.metrics, &liquidations, &config, "test", 50.0);
+    assert!(html.contains("<h3>Liquidations</h3>"));
+    assert!(html.contains("Liquidation Sizes"));
+    assert!(html.contains(r#"const LQ=[{"block":12,"mode":"transparent","vault":7,"#));
+    assert!(html.contains(r#""mode":"challenge_response""#));
+    assert!(html.contains(r#""keeper":"keeper_1""#));
+    assert!(html.contains(r#""bad_debt":25.5"#));
+
+    // Without results the table is empty
+    let html = generate_report(&scenario.metrics, &config, "test", 50.0);
+    assert!(html.contains("const LQ=[];"));
+}
+
+#[test]
+fn test_scenario_liquidations_in_report() {
+    let config = ScenarioConfig::default();
+    let scenario = run_stress(ScenarioId::BlackThursday, &config, 500, TEST_SEED);
+    let history = &scenario.liquidation_engine.history;
+    let html = generate_report_with_liquidations(&scenario.metrics, history, &config, "test", 50.0);
+    let rows = html.matches(r#""owner":"#).count();
+    assert_eq!(rows, history.len());
+    for r in history {
+        assert!(html.contains(&format!(r#""mode":"{}""#, r.mode.name())));
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+// Monte Carlo Report Tests
+// ═══════════════════════════════════════════════════════════════════════
+
+#[test]
+fn test_percentile_and_fan_bands() {
+    let sorted = [1.0, 2.0, 3.0, 4.0, 5.0];
+    assert_eq!(percentile(&sorted, 0.5), 3.0);
+    assert_eq!(percentile(&sorted, 0.25), 2.0);
+    assert!((percentile(&sorted, 0.95) - 4.8).abs() < 1e-9);
+    assert_eq!(percentile(&[], 0.5), 0.0);
+
+    // Bands run as long as the shortest series
+    let series: [&[f64]; 3] = [&[1.0, 10.0], &[3.0, 30.0, 99.0], &[2.0, 20.0]];
+    assert_eq!(
+        fan_bands(&series, &[0.0, 0.5, 1.0]),
+        vec![vec![1.0, 10.0], vec![2.0, 20.0], vec![3.0, 30.0]]
+    );
+    assert_eq!(fan_bands(&series, &FAN_PERCENTILES).len(), 5);
+}
+
+#[test]
+fn test_histogram_and_cdf() {
+    assert_eq!(
+        histogram(&[0.0, 1.0, 2.0, 3.0, 4.0], 2),
+        vec![(0.0, 2.0, 2), (2.0, 4.0, 3)]
+    );
+    assert_eq!(
+        histogram(&[5.0, 5.0], 2),
+        vec![(5.0, 6.0, 2), (6.0, 7.0, 0)]
+    );
+    assert!(histogram(&[], 10).is_empty());
+
+    assert_eq!(
+        empirical_cdf(&[3.0, 1.0, 1.0, 2.0]),
+        vec![(1.0, 0.5), (2.0, 0.75), (3.0, 1.0)]
+    );
+}
+
+#[test]
+fn test_monte_carlo_report() {
+    let config = ScenarioConfig::default();
+    let runs: Vec<MonteCarloRun> = (1..=4)
+        .map(|seed| {
+            let scenario = run_stress(ScenarioId::BlackThursday, &config, 200, seed);
+            MonteCarloRun::from_metrics(seed, &scenario.metrics, 50.0)
+        })
+        .collect();
+    assert!(runs.iter().all(|r| r.amm_price.len() == 200));
+
+    let html = generate_monte_carlo_report("black_thursday", &runs);
+    assert!(html.contains("<!DOCTYPE html>"));
+    assert!(html.contains("ZAI Monte Carlo — black_thursday"));
+    assert!(html.contains("<h2>4 seeds,"));
+    for id in 1..=6 {
+        assert!(html.contains(&format!("<canvas id=\"c{}\">", id)));
+    }
+    assert!(html.contains("<tr><td>Bad Debt</td>"));
+    assert!(html.contains("<tr><td>Max Peg Deviation</td>"));
+
+    // Five percentile bands of one value per block
+    let price = html
+        .lines()
+        .find_map(|l| l.strip_prefix("const PRICE="))
+        .unwrap();
+    assert_eq!(price.matches('[').count(), 6);
+    assert_eq!(price.matches(',').count(), 5 * 199 + 4);
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+// Markdown / PDF Summary Tests
+// ═══════════════════════════════════════════════════════════════════════
+
+#[test]
+fn test_report_format_parse() {
+    assert_eq!("html".parse::<ReportFormat>().unwrap(), ReportFormat::Html);
+    assert_eq!(
+        "md".parse::<ReportFormat>().unwrap(),
+        ReportFormat::Markdown
+    );
+    assert_eq!(
+        "markdown".parse::<ReportFormat>().unwrap(),
+        ReportFormat::Markdown
+    );
+    assert_eq!("pdf".parse::<ReportFormat>().unwrap(), ReportFormat::Pdf);
+    assert!("docx".parse::<ReportFormat>().is_err());
+    assert_eq!(ReportFormat::Markdown.extension(), "md");
+}
+
+#[test]
+fn test_markdown_summary() {
+    let config = ScenarioConfig::default();
+    let scenario = run_stress(ScenarioId::BlackThursday, &config, 200, TEST_SEED);
+    let verdict = evaluate_pass_fail(&scenario.metrics, 50.0);
+    let md = generate_markdown_summary(&scenario.metrics, &config, "black_thursday", 50.0);
+
+    assert!(md.starts_with("## ZAI Report — black_thursday\n"));
+    assert!(md.contains(&format!("**Verdict: {}**", verdict.overall.label())));
+    // One row per criterion, each with four cells
+    for c in &verdict.criteria {
+        let row = md
+            .lines()
+            .find(|l| l.starts_with(&format!("| {} |", c.name)))
+            .unwrap();
+        assert_eq!(row.matches(" | ").count(), 3);
+    }
+    assert!(md.contains("| Liquidations |"));
+    assert!(md.contains("min ratio 150%"));
+    assert!(
+        !md.contains("<td>"),
+        "Markdown summary should carry no HTML"
+    );
+}
+
+#[test]
+fn test_master_summary_markdown() {
+    let config = ScenarioConfig::default();
+    let scenario = run_stress(ScenarioId::SteadyState, &config, 200, TEST_SEED);
+    let verdict = evaluate_pass_fail(&scenario.metrics, 50.0);
+    let summary = output::compute_summary(&scenario.metrics, 50.0);
+    let entries = vec![("steady_state".to_string(), verdict, summary)];
+
+    let md = generate_master_summary_markdown(&entries);
+    assert!(md.contains("**1 / 1 scenarios pass**"));
+    assert!(md.contains("| [steady_state](steady_state.md) | PASS |"));
+}
+
+#[test]
+fn test_pdf_summary() {
+    let config = ScenarioConfig::default();
+    let scenario = run_stress(ScenarioId::SteadyState, &config, 200, TEST_SEED);
+    let md = generate_markdown_summary(&scenario.metrics, &config, "steady_state", 50.0);
+    let pdf = String::from_utf8(markdown_to_pdf(&md)).unwrap();
+
+    assert!(pdf.starts_with("%PDF-1.4\n"));
+    assert!(pdf.ends_with("%%EOF\n"));
+    assert!(pdf.contains("(ZAI Report - steady_state) Tj"));
+    assert!(pdf.contains("(Verdict: PASS) Tj"));
+    // Table pipes and emphasis are laid out as plain columns
+    assert!(!pdf.contains("(|"));
+    assert!(!pdf.contains("**"));
+
+    // The cross-reference table points at each object
+    let xref: usize = pdf
+        .split("startxref\n")
+        .nth(1)
+        .and_then(|s| s.lines().next())
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert!(pdf[xref..].starts_with("xref\n"));
+    for (i, entry) in pdf[xref..]
+        .lines()
+        .skip(3)
+        .take_while(|l| l.ends_with(" n "))
+        .enumerate()
+    {
+        let offset: usize = entry[..10].parse().unwrap();
+        assert!(pdf[offset..].starts_with(&format!("{} 0 obj\n", i + 1)));
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+// Offline Report Tests
+// ═══════════════════════════════════════════════════════════════════════
+
+const FAKE_CHART_JS: &str = "window.Chart=function Chart(){};/*</script>*/";
+
+#[test]
+fn test_offline_report_inlines_chart_js() {
+    let config = ScenarioConfig::default();
+    let scenario = run_stress(ScenarioId::SteadyState, &config, 100, TEST_SEED);
+    let html = generate_report(&scenario.metrics, &config, "test", 50.0);
+    assert!(html.contains(CHART_JS_CDN));
+
+    let offline = inline_chart_js(&html, FAKE_CHART_JS);
+    assert!(!offline.contains(CHART_JS_CDN));
+    assert!(!offline.contains("<script src="));
+    assert!(
+        offline.contains("<script>\nwindow.Chart=function Chart(){};/*<\\/script>*/\n</script>")
+    );
+    assert_eq!(
+        offline.matches("<script").count(),
+        html.matches("<script").count()
+    );
+}
+
+#[test]
+fn test_offline_comparison_and_chartless_reports() {
+    let dir = std::env::temp_dir().join(format!("zai_sim_offline_{}", std::process::id()));
+    let config = ScenarioConfig::default();
+    let scenario = run_stress(ScenarioId::SteadyState, &config, 100, TEST_SEED);
+    output::save_all(&scenario, &config, 50.0, &dir).unwrap();
+    let run = output::load_run(&dir).unwrap();
+    let offline = inline_chart_js(&generate_comparison(&run, &run), FAKE_CHART_JS);
+    assert!(!offline.contains(CHART_JS_CDN));
+    assert!(offline.contains("window.Chart="));
+
+    // Nothing to inline into a report without charts
+    let plain = "<html><body>no charts</body></html>";
+    assert_eq!(inline_chart_js(plain, FAKE_CHART_JS), plain);
+
+    let js_path = dir.join("chart.umd.min.js");
+    std::fs::write(&js_path, FAKE_CHART_JS).unwrap();
+    assert_eq!(load_chart_js(&js_path).unwrap(), FAKE_CHART_JS);
+    std::fs::write(&js_path, "console.log(1)").unwrap();
+    assert!(load_chart_js(&js_path).is_err());
+    assert!(load_chart_js(&dir.join("missing.js")).is_err());
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
 // ═══════════════════════════════════════════════════════════════════════
 // All Scenarios Report Generation
 // ═══════════════════════════════════════════════════════════════════════
@@ -0,0 +1,225 @@
+//! Resumable, rate-limited fetch.
+//!
+//! Long fetches write each page to disk as it arrives, so a run that dies
+//! part-way can be rerun and continue after the last saved candle. Transient
+//! failures back off exponentially with jitter, and Binance requests pause
+//! when the reported minute weight nears the limit.
+#![cfg(feature = "net")]
+
+use std::cell::{Cell, RefCell};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use zai_sim::data_fetcher::{
+    fetch_matrix, fetch_to_csv, load_csv, Binance, Kline, Page, PriceSource, RetryPolicy,
+};
+use zai_sim::error::ZaiSimError;
+
+const MINUTE_MS: u64 = 60_000;
+
+/// Serves 1m candles from block 0 in pages of 10, failing with a
+/// non-transient error once `fail_after` requests have been served.
+struct MockSource {
+    candles: u64,
+    fail_after: Option<usize>,
+    requests: Cell<usize>,
+    starts: RefCell<Vec<u64>>,
+}
+
+impl MockSource {
+    fn new(candles: u64, fail_after: Option<usize>) -> Self {
+        MockSource {
+            candles,
+            fail_after,
+            requests: Cell::new(0),
+            starts: RefCell::new(Vec::new()),
+        }
+    }
+}
+
+impl PriceSource for MockSource {
+    fn name(&self) -> &'static str {
+        "Mock"
+    }
+
+    fn default_pair(&self) -> &'static str {
+        "ZECUSD"
+    }
+
+    fn request_delay(&self) -> Duration {
+        Duration::ZERO
+    }
+
+    fn fetch_page(
+        &self,
+        _pair: &str,
+        _interval: &str,
+        start_ms: u64,
+        _end_ms: u64,
+    ) -> Result<Page, ZaiSimError> {
+        if Some(self.requests.get()) == self.fail_after {
+            return Err(ZaiSimError::Exchange("connection reset".to_string()));
+        }
+        self.requests.set(self.requests.get() + 1);
+        self.starts.borrow_mut().push(start_ms);
+
+        let first = start_ms.div_ceil(MINUTE_MS);
+        let klines: Vec<Kline> = (first..(first + 10).min(self.candles))
+            .map(|i| Kline {
+                timestamp_ms: i * MINUTE_MS,
+                open: 50.0,
+                high: 51.0,
+                low: 49.0,
+                close: 50.0 + i as f64,
+                volume: 1.0,
+            })
+            .collect();
+        let next_start_ms = klines.last().map(|k| k.timestamp_ms + 1);
+        Ok(Page {
+            klines,
+            next_start_ms,
+        })
+    }
+
+    fn parse_page(&self, _body: &serde_json::Value) -> Result<Vec<Kline>, ZaiSimError> {
+        Ok(Vec::new())
+    }
+}
+
+fn temp_csv(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("zai_sim_{}_{}.csv", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+#[test]
+fn test_backoff_doubles_and_caps() {
+    let retry = RetryPolicy {
+        max_retries: 10,
+        base_delay: Duration::from_secs(1),
+        max_delay: Duration::from_secs(10),
+    };
+    // No jitter → lower half-step bound; full jitter → the whole step
+    assert_eq!(retry.delay(0, 0.0), Duration::from_millis(500));
+    assert_eq!(retry.delay(1, 0.0), Duration::from_secs(1));
+    assert_eq!(retry.delay(2, 0.0), Duration::from_secs(2));
+    assert_eq!(retry.delay(8, 1.0), Duration::from_secs(10));
+    assert_eq!(retry.delay(40, 1.0), Duration::from_secs(10));
+}
+
+#[test]
+fn test_non_http_errors_are_not_retried() {
+    assert!(!RetryPolicy::is_transient(&ZaiSimError::Exchange(
+        "EQuery:Unknown asset pair".to_string()
+    )));
+}
+
+#[test]
+fn test_binance_weight_pause() {
+    assert_eq!(Binance::weight_pause(100, 0), None);
+    // 15s into the minute at the budget → wait for the window to reset
+    assert_eq!(
+        Binance::weight_pause(Binance::WEIGHT_BUDGET, 15_000),
+        Some(Duration::from_secs(45))
+    );
+}
+
+#[test]
+fn test_fetch_writes_all_pages() {
+    let path = temp_csv("fetch_all");
+    let source = MockSource::new(35, None);
+    let summary = fetch_to_csv(
+        &source,
+        "ZECUSD",
+        "1m",
+        0,
+        100 * MINUTE_MS,
+        &RetryPolicy::default(),
+        &path,
+    )
+    .unwrap();
+
+    assert_eq!(summary.existing, 0);
+    assert_eq!(summary.fetched, 35);
+    let klines = load_csv(&path).unwrap();
+    assert_eq!(klines.len(), 35);
+    assert_eq!(klines[34].close, 84.0);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_rerun_resumes_after_last_saved_candle() {
+    let path = temp_csv("fetch_resume");
+    let end_ms = 100 * MINUTE_MS;
+
+    // First run dies after two pages; those pages are already on disk
+    let interrupted = MockSource::new(35, Some(2));
+    assert!(fetch_to_csv(
+        &interrupted,
+        "ZECUSD",
+        "1m",
+        0,
+        end_ms,
+        &RetryPolicy::default(),
+        &path
+    )
+    .is_err());
+    assert_eq!(load_csv(&path).unwrap().len(), 20);
+
+    // Rerun starts right after candle 19 instead of from the beginning
+    let rerun = MockSource::new(35, None);
+    let summary = fetch_to_csv(
+        &rerun,
+        "ZECUSD",
+        "1m",
+        0,
+        end_ms,
+        &RetryPolicy::default(),
+        &path,
+    )
+    .unwrap();
+    assert_eq!(rerun.starts.borrow()[0], 19 * MINUTE_MS + 1);
+    assert_eq!(summary.existing, 20);
+    assert_eq!(summary.fetched, 15);
+
+    let klines = load_csv(&path).unwrap();
+    assert_eq!(klines.len(), 35);
+    assert!(klines
+        .windows(2)
+        .all(|w| w[1].timestamp_ms == w[0].timestamp_ms + MINUTE_MS));
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_fetch_matrix_aligns_pairs() {
+    let end_ms = 35 * MINUTE_MS;
+    let (zec, btc) = (temp_csv("matrix_zec"), temp_csv("matrix_btc"));
+    // ZEC was half fetched by an earlier run and resumes on its own
+    fetch_to_csv(
+        &MockSource::new(20, None),
+        "ZECUSD",
+        "1m",
+        0,
+        end_ms,
+        &RetryPolicy::default(),
+        &zec,
+    )
+    .unwrap();
+
+    let (matrix, summaries) = fetch_matrix(
+        &MockSource::new(35, None),
+        &[("ZECUSD", zec.as_path()), ("BTCUSD", btc.as_path())],
+        "1m",
+        0,
+        end_ms,
+        &RetryPolicy::default(),
+    )
+    .unwrap();
+    assert_eq!(summaries[0].existing, 20);
+    assert_eq!(summaries[1].fetched, 35);
+    assert_eq!(matrix.pairs, vec!["ZECUSD", "BTCUSD"]);
+    assert_eq!(matrix.len(), 35);
+    assert_eq!(matrix.column("BTCUSD").unwrap()[34], 84.0);
+    let _ = std::fs::remove_file(&zec);
+    let _ = std::fs::remove_file(&btc);
+}
@@ -0,0 +1,126 @@
+//! Routing across the ZEC/ZAI AMM and side pools.
+//!
+//! With deep ZEC/USD and ZAI/USD pools beside a shallow AMM, a large ZEC
+//! sale should go through USD, while small trades stay on the AMM where the
+//! fee is lower. Without side pools routing is the plain AMM swap.
+
+use approx::assert_relative_eq;
+use zai_sim::amm::Amm;
+use zai_sim::pool::{Asset, Curve, Pool, PoolConfig};
+use zai_sim::routing::Venue;
+use zai_sim::scenario::ScenarioConfig;
+use zai_sim::scenarios::{run_stress, ScenarioId};
+
+fn zec_usd(depth_zec: f64) -> PoolConfig {
+    PoolConfig {
+        base: Asset::Zec,
+        quote: Asset::Usd,
+        reserve_base: depth_zec,
+        reserve_quote: depth_zec * 50.0,
+        swap_fee: 0.003,
+        curve: Curve::ConstantProduct,
+    }
+}
+
+fn pools() -> Vec<PoolConfig> {
+    vec![zec_usd(100_000.0), PoolConfig::zai_usd(5_000_000.0, 100.0)]
+}
+
+/// A 1,000 ZEC AMM beside the deep pools.
+fn shallow_amm() -> Amm {
+    let mut amm = Amm::new(1000.0, 50000.0, 0.003);
+    amm.pools = pools().iter().map(Pool::new).collect();
+    amm
+}
+
+#[test]
+fn test_large_sale_routes_through_usd() {
+    let amm = shallow_amm();
+    let route = amm.best_route(Asset::Zec, Asset::Zai, 100.0).unwrap();
+    let venues: Vec<Venue> = route.hops.iter().map(|h| h.venue).collect();
+    assert_eq!(venues, vec![Venue::Pool(0), Venue::Pool(1)]);
+    assert_eq!(route.hops[0].buy, Asset::Usd);
+    // 10% of the AMM's depth would lose ~9% to slippage
+    assert!(route.amount_out > amm.quote_zec_for_zai(100.0) * 1.05);
+}
+
+#[test]
+fn test_small_trade_stays_on_amm() {
+    let amm = shallow_amm();
+    let route = amm.best_route(Asset::Zec, Asset::Zai, 0.01).unwrap();
+    assert_eq!(route.hops.len(), 1);
+    assert_eq!(route.hops[0].venue, Venue::Amm);
+}
+
+#[test]
+fn test_swap_routed_executes_quote() {
+    let mut amm = shallow_amm();
+    let quoted = amm.best_route(Asset::Zec, Asset::Zai, 100.0).unwrap();
+    let out = amm.sell_zec(100.0, 1).unwrap();
+    assert_relative_eq!(out, quoted.amount_out, epsilon = 1e-9);
+    // The AMM itself was not touched
+    assert_eq!(amm.reserve_zec, 1000.0);
+    assert_eq!(amm.pools[0].reserve_base, 100_100.0);
+
+    // Nothing trades ZEC for USD without the pools
+    let mut bare = Amm::new(1000.0, 50000.0, 0.003);
+    assert!(bare.best_route(Asset::Zec, Asset::Usd, 1.0).is_none());
+    assert!(bare.swap_routed(Asset::Zec, Asset::Usd, 1.0, 1).is_err());
+}
+
+#[test]
+fn test_no_pools_is_direct_swap() {
+    let mut routed = Amm::new(1000.0, 50000.0, 0.003);
+    let mut direct = Amm::new(1000.0, 50000.0, 0.003);
+    assert_eq!(
+        routed.sell_zec(100.0, 1).unwrap(),
+        direct.swap_zec_for_zai(100.0, 1).unwrap()
+    );
+    assert_eq!(
+        routed.buy_zec(2000.0, 2).unwrap(),
+        direct.swap_zai_for_zec(2000.0, 2).unwrap()
+    );
+    assert_eq!(routed.reserve_zec, direct.reserve_zec);
+}
+
+#[test]
+fn test_scenario_side_pools_track_external_price() {
+    let config = ScenarioConfig {
+        pools: pools(),
+        ..ScenarioConfig::default()
+    };
+    let scenario = run_stress(ScenarioId::BlackThursday, &config, 500, 42);
+    assert!(scenario
+        .metrics
+        .iter()
+        .all(|m| m.side_pool_prices.len() == 2));
+    // Re-priced from outside each block; only that block's routed trades
+    // (large liquidations) move them away
+    let tracking = scenario
+        .metrics
+        .iter()
+        .filter(|m| (m.side_pool_prices[0] - m.external_price).abs() / m.external_price < 0.02)
+        .count();
+    assert!(
+        tracking * 100 >= scenario.metrics.len() * 95,
+        "{}",
+        tracking
+    );
+
+    let baseline = run_stress(
+        ScenarioId::BlackThursday,
+        &ScenarioConfig::default(),
+        500,
+        42,
+    );
+    assert!(baseline
+        .metrics
+        .iter()
+        .all(|m| m.side_pool_prices.is_empty()));
+    let bad_debt = |s: &zai_sim::scenario::Scenario| s.metrics.last().unwrap().bad_debt;
+    println!(
+        "\nBad debt: AMM only {:.2}, with side pools {:.2}",
+        bad_debt(&baseline),
+        bad_debt(&scenario)
+    );
+}
@@ -0,0 +1,133 @@
+//! Run comparison (A/B) report.
+//!
+//! Two runs saved by `output::save_all` are read back and compared: their
+//! price, debt and liquidation series overlaid, their configs diffed and
+//! their summary metrics diffed with percentage deltas.
+
+use std::path::PathBuf;
+
+use zai_sim::liquidation::LiquidationConfig;
+use zai_sim::output::{compute_summary, load_run, save_all, SavedRun};
+use zai_sim::report::*;
+use zai_sim::scenario::{Scenario, ScenarioConfig};
+use zai_sim::scenarios::{run_stress, ScenarioId};
+
+fn save(name: &str, config: &ScenarioConfig) -> (Scenario, PathBuf) {
+    let scenario = run_stress(ScenarioId::BlackThursday, config, 300, 42);
+    let dir = std::env::temp_dir()
+        .join(format!("zai_sim_compare_{}", std::process::id()))
+        .join(name);
+    let _ = std::fs::remove_dir_all(&dir);
+    save_all(&scenario, config, 50.0, &dir).unwrap();
+    (scenario, dir)
+}
+
+fn summary_value(run: &SavedRun, key: &str) -> f64 {
+    run.summary.iter().find(|(k, _)| k == key).unwrap().1
+}
+
+fn graduated() -> ScenarioConfig {
+    ScenarioConfig {
+        use_graduated_liquidation: true,
+        liquidation_config: LiquidationConfig {
+            graduated_liquidation: true,
+            ..LiquidationConfig::default()
+        },
+        ..ScenarioConfig::default()
+    }
+}
+
+#[test]
+fn test_load_run_round_trip() {
+    let (scenario, dir) = save("round_trip", &ScenarioConfig::default());
+    let run = load_run(&dir).unwrap();
+    assert_eq!(run.name, "round_trip");
+
+    let blocks = run.series("block").unwrap();
+    assert_eq!(blocks.len(), scenario.metrics.len());
+    assert_eq!(blocks[0], scenario.metrics.block[0] as f64);
+    assert!(run
+        .series("halted")
+        .unwrap()
+        .iter()
+        .all(|h| *h == 0.0 || *h == 1.0));
+    assert!(run.series("no_such_column").is_none());
+
+    let summary = compute_summary(&scenario.metrics, 50.0);
+    assert_eq!(
+        summary_value(&run, "total_blocks"),
+        summary.total_blocks as f64
+    );
+    assert_eq!(
+        summary_value(&run, "liquidations.total"),
+        summary.total_liquidations as f64
+    );
+    let setting = |key: &str| run.config.iter().find(|(k, _)| k == key).unwrap().1.clone();
+    assert_eq!(setting("cdp.min_ratio"), "1.5");
+    assert_eq!(setting("liquidation.graduated"), "false");
+
+    assert!(load_run(&dir.join("missing")).is_err());
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_metric_delta_percentages() {
+    let delta = |a, b| MetricDelta {
+        name: "m".to_string(),
+        a,
+        b,
+    };
+    assert_eq!(delta(Some(200.0), Some(150.0)).pct_change(), Some(-25.0));
+    assert_eq!(delta(Some(-4.0), Some(-2.0)).pct_change(), Some(50.0));
+    assert_eq!(delta(Some(0.0), Some(1.0)).pct_change(), None);
+    assert_eq!(delta(None, Some(1.0)).pct_change(), None);
+}
+
+#[test]
+fn test_graduated_on_vs_off() {
+    let (_, dir_a) = save("graduated_off", &ScenarioConfig::default());
+    let (_, dir_b) = save("graduated_on", &graduated());
+    let a = load_run(&dir_a).unwrap();
+    let b = load_run(&dir_b).unwrap();
+
+    assert_eq!(
+        config_diff(&a, &b),
+        vec![ConfigChange {
+            key: "liquidation.graduated".to_string(),
+            a: Some("false".to_string()),
+            b: Some("true".to_string()),
+        }]
+    );
+    assert!(config_diff(&a, &a).is_empty());
+
+    let deltas = summary_deltas(&a, &b);
+    assert_eq!(deltas.len(), a.summary.len());
+    let blocks = deltas.iter().find(|d| d.name == "total_blocks").unwrap();
+    assert_eq!(blocks.pct_change(), Some(0.0));
+
+    let html = generate_comparison(&a, &b);
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("A: graduated_off"));
+    assert!(html.contains("B: graduated_on"));
+    assert!(html.contains("<td>liquidation.graduated</td><td>false</td><td>true</td>"));
+    assert!(html.contains("Cumulative Liquidations"));
+    assert!(html.contains("Summary Metrics"));
+    assert!(generate_comparison(&a, &a).contains("Configs are identical"));
+
+    println!(
+        "\n{:<28} {:>12} {:>12} {:>9}",
+        "metric", "off", "on", "delta"
+    );
+    for d in &deltas {
+        println!(
+            "{:<28} {:>12.4} {:>12.4} {:>9}",
+            d.name,
+            d.a.unwrap_or(f64::NAN),
+            d.b.unwrap_or(f64::NAN),
+            d.pct_change()
+                .map_or_else(|| "-".to_string(), |p| format!("{:+.2}%", p))
+        );
+    }
+    let _ = std::fs::remove_dir_all(&dir_a);
+    let _ = std::fs::remove_dir_all(&dir_b);
+}
@@ -0,0 +1,120 @@
+//! Multi-dimensional sweeps with grid, random and Latin hypercube sampling.
+
+use zai_sim::scenario::ScenarioConfig;
+use zai_sim::scenarios::ScenarioId;
+use zai_sim::sweep::{SamplingStrategy, SweepEngine, SweepRange};
+
+fn ranges() -> Vec<SweepRange> {
+    vec![
+        "min_ratio=1.5:2.5".parse().unwrap(),
+        "cdp_config.debt_floor=100:500".parse().unwrap(),
+    ]
+}
+
+fn column(points: &[Vec<(String, f64)>], i: usize) -> Vec<f64> {
+    points.iter().map(|p| p[i].1).collect()
+}
+
+#[test]
+fn test_parse_range_and_strategy() {
+    let r: SweepRange = "swap_fee=0.001:0.01".parse().unwrap();
+    assert_eq!(r.name, "swap_fee");
+    assert_eq!((r.min, r.max), (0.001, 0.01));
+
+    assert!("swap_fee".parse::<SweepRange>().is_err());
+    assert!("swap_fee=0.01:0.001".parse::<SweepRange>().is_err());
+    assert_eq!(
+        "lhs".parse::<SamplingStrategy>().unwrap(),
+        SamplingStrategy::LatinHypercube
+    );
+    assert!("sobol".parse::<SamplingStrategy>().is_err());
+}
+
+#[test]
+fn test_latin_hypercube_covers_every_stratum() {
+    let n = 20;
+    let points = SweepEngine::sample(&ranges(), SamplingStrategy::LatinHypercube, n, 7);
+    assert_eq!(points.len(), n);
+
+    for (i, r) in ranges().iter().enumerate() {
+        let mut strata: Vec<usize> = column(&points, i)
+            .iter()
+            .map(|v| (((v - r.min) / (r.max - r.min)) * n as f64) as usize)
+            .collect();
+        strata.sort();
+        assert_eq!(strata, (0..n).collect::<Vec<_>>(), "axis {}", r.name);
+    }
+}
+
+#[test]
+fn test_random_is_bounded_and_seeded() {
+    let a = SweepEngine::sample(&ranges(), SamplingStrategy::Random, 50, 1);
+    let b = SweepEngine::sample(&ranges(), SamplingStrategy::Random, 50, 1);
+    let c = SweepEngine::sample(&ranges(), SamplingStrategy::Random, 50, 2);
+    assert_eq!(a, b);
+    assert_ne!(a, c);
+    for (i, r) in ranges().iter().enumerate() {
+        assert!(column(&a, i).iter().all(|v| (r.min..=r.max).contains(v)));
+    }
+}
+
+#[test]
+fn test_grid_fits_budget() {
+    // 3 x 3 = 9 fits a budget of 10; 4 x 4 would not
+    let points = SweepEngine::sample(&ranges(), SamplingStrategy::Grid, 10, 0);
+    assert_eq!(points.len(), 9);
+    let mut ratios = column(&points, 0);
+    ratios.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    ratios.dedup();
+    assert_eq!(ratios, vec![1.5, 2.0, 2.5]);
+}
+
+#[test]
+fn test_set_param_by_config_path() {
+    let mut config = ScenarioConfig::default();
+    SweepEngine::set_param(&mut config, "cdp_config.debt_floor", 250.0).unwrap();
+    SweepEngine::set_param(&mut config, "cdp_config.twap_window", 239.6).unwrap();
+    SweepEngine::set_param(&mut config, "amm_initial_depth", 2_000_000.0).unwrap();
+    SweepEngine::set_param(&mut config, "min_ratio", 1.8).unwrap();
+
+    assert_eq!(config.cdp_config.debt_floor, 250.0);
+    assert_eq!(config.cdp_config.twap_window, 240);
+    assert_eq!(config.amm_initial_depth, Some(2_000_000.0));
+    assert_eq!(config.cdp_config.min_ratio, 1.8);
+
+    assert!(SweepEngine::set_param(&mut config, "cdp_config.nope", 1.0).is_err());
+    assert!(SweepEngine::set_param(&mut config, "stochastic", 1.0).is_err());
+}
+
+#[test]
+fn test_run_sampled_ranks_results() {
+    let engine = SweepEngine::new(200, 42, 50.0);
+    let ranges: Vec<SweepRange> = vec![
+        "min_ratio=1.5:2.5".parse().unwrap(),
+        "amm_initial_depth=250000:5000000".parse().unwrap(),
+    ];
+    let results = engine
+        .run_sampled(
+            &ranges,
+            SamplingStrategy::LatinHypercube,
+            6,
+            &[ScenarioId::BlackThursday],
+        )
+        .unwrap();
+
+    assert_eq!(results.len(), 6);
+    assert!(results
+        .windows(2)
+        .all(|w| w[0].overall_score >= w[1].overall_score));
+    assert_eq!(results[0].params.len(), 2);
+
+    let bad = vec!["no_such_param=0:1".parse().unwrap()];
+    assert!(engine
+        .run_sampled(
+            &bad,
+            SamplingStrategy::Random,
+            2,
+            &[ScenarioId::SteadyState]
+        )
+        .is_err());
+}
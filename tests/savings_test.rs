@@ -0,0 +1,154 @@
+//! ZAI savings rate and saver agent.
+//!
+//! Deposits earn the savings rate through a growing index, interest is
+//! paid only as far as the treasury surplus reaches, the rate follows the
+//! controller, and savers move ZAI between the AMM and savings.
+
+use approx::assert_relative_eq;
+use zai_sim::agents::{AgentAction, SaverAgent, SaverAgentConfig};
+use zai_sim::amm::Amm;
+use zai_sim::savings::{SavingsConfig, SavingsModule};
+use zai_sim::scenario::{Scenario, ScenarioConfig};
+use zai_sim::scenarios::{add_agents, generate_prices, ScenarioId};
+use zai_sim::treasury::{Treasury, TreasuryConfig};
+
+const BLOCKS_PER_YEAR: f64 = 365.25 * 24.0 * 3600.0 / 75.0;
+
+fn funded_treasury(balance: f64) -> Treasury {
+    let mut treasury = Treasury::new(TreasuryConfig::default());
+    treasury.balance_zai = balance;
+    treasury
+}
+
+#[test]
+fn test_deposit_earns_rate() {
+    let mut savings = SavingsModule::new(SavingsConfig::default());
+    let mut treasury = funded_treasury(1_000_000.0);
+    let shares = savings.deposit(10_000.0).unwrap();
+    assert_eq!(shares, 10_000.0);
+
+    let paid = savings.accrue(&mut treasury, 1000);
+    let growth = (1.0 + 0.02 / BLOCKS_PER_YEAR).powi(1000);
+    assert_relative_eq!(savings.chi, growth, epsilon = 1e-12);
+    assert_relative_eq!(paid, 10_000.0 * (growth - 1.0), epsilon = 1e-9);
+    assert_relative_eq!(treasury.balance_zai, 1_000_000.0 - paid, epsilon = 1e-9);
+
+    // A later deposit mints fewer shares; withdrawing returns principal plus interest
+    assert!(savings.deposit(10_000.0).unwrap() < 10_000.0);
+    let zai = savings.withdraw(shares).unwrap();
+    assert_relative_eq!(zai, 10_000.0 + paid, epsilon = 1e-9);
+    assert!(savings.withdraw(savings.total_shares * 2.0).is_err());
+    assert!(savings.deposit(0.0).is_err());
+}
+
+#[test]
+fn test_interest_limited_to_surplus() {
+    let mut savings = SavingsModule::new(SavingsConfig::default());
+    let mut treasury = funded_treasury(1.0);
+    savings.deposit(1_000_000.0).unwrap();
+
+    let paid = savings.accrue(&mut treasury, 1000);
+    assert_eq!(paid, 1.0);
+    assert_eq!(treasury.balance_zai, 0.0);
+    assert_relative_eq!(savings.total_deposits_zai(), 1_000_001.0, epsilon = 1e-6);
+    assert!(savings.unfunded_interest_zai > 40.0);
+
+    // Nothing left to pay from
+    assert_eq!(savings.accrue(&mut treasury, 2000), 0.0);
+}
+
+#[test]
+fn test_rate_follows_controller() {
+    let mut savings = SavingsModule::new(SavingsConfig::default());
+    assert_eq!(savings.rate, 0.02);
+
+    // ZAI below redemption: the controller's negative rate raises the savings rate
+    let raised = savings.set_rate(-2e-8);
+    assert_relative_eq!(raised, 0.02 + 2e-8 * BLOCKS_PER_YEAR, epsilon = 1e-12);
+    assert_eq!(savings.set_rate(-1e-4), 0.2);
+
+    // ZAI above redemption: the rate falls, but not below zero
+    assert_eq!(savings.set_rate(1e-4), 0.0);
+    assert_eq!(savings.set_rate(0.0), 0.02);
+}
+
+#[test]
+fn test_saver_moves_between_amm_and_savings() {
+    let mut amm = Amm::new(10_000.0, 500_000.0, 0.003);
+    let mut savings = SavingsModule::new(SavingsConfig::default());
+    let mut saver = SaverAgent::new(SaverAgentConfig::default());
+
+    // At the peg with a 2% rate: lock the wallet's ZAI
+    let action = saver.act(&mut amm, &mut savings, 50.0, 1);
+    assert!(matches!(action, AgentAction::SavingsDeposit { .. }));
+    assert_eq!(saver.zai_balance, 0.0);
+    assert_relative_eq!(saver.total_zai(&savings), 50_000.0);
+
+    // Then buy more ZAI with ZEC while it trades at or below redemption
+    let action = saver.act(&mut amm, &mut savings, 50.0, 2);
+    assert!(matches!(action, AgentAction::BuyZai { .. }));
+    assert!(amm.spot_price() < 50.0);
+
+    // The rate drops below the saver's floor: withdraw in steps
+    savings.set_rate(1e-4);
+    let action = saver.act(&mut amm, &mut savings, 50.0, 3);
+    assert!(matches!(action, AgentAction::SavingsWithdraw { zai, .. } if zai == 5000.0));
+
+    // ZAI at a premium: withdraw the rest, then sell on the AMM
+    savings.set_rate(0.0);
+    let mut block = 4;
+    while saver.savings_shares > 0.0 {
+        saver.act(&mut amm, &mut savings, 55.0, block);
+        block += 1;
+    }
+    let zai = saver.zai_balance;
+    let action = saver.act(&mut amm, &mut savings, 55.0, block);
+    assert!(matches!(action, AgentAction::BuyZec { .. }));
+    assert!(saver.zai_balance < zai);
+}
+
+fn run(savings: Option<SavingsConfig>, savers: usize) -> Scenario {
+    let config = ScenarioConfig {
+        savings,
+        ..ScenarioConfig::default()
+    };
+    let mut scenario = Scenario::new_with_seed(&config, 42);
+    add_agents(ScenarioId::BlackThursday, &mut scenario);
+    for _ in 0..savers {
+        scenario
+            .savers
+            .push(SaverAgent::new(SaverAgentConfig::default()));
+    }
+    scenario.run(&generate_prices(ScenarioId::BlackThursday, 1000, 42));
+    scenario
+}
+
+#[test]
+fn test_scenario_metrics() {
+    let scenario = run(Some(SavingsConfig::default()), 2);
+    assert!(scenario
+        .metrics
+        .iter()
+        .any(|m| m.savings_deposits_zai > 0.0));
+    assert!(scenario
+        .metrics
+        .savings_interest_paid_zai
+        .windows(2)
+        .all(|w| w[1] >= w[0]));
+
+    // Interest only ever comes out of fee and penalty income
+    let savings = scenario.savings.as_ref().unwrap();
+    let income =
+        scenario.treasury.total_fees_collected + scenario.treasury.total_penalties_collected;
+    assert!(savings.total_interest_paid_zai <= income + 1e-9);
+    let last = scenario.metrics.last().unwrap();
+    assert_eq!(
+        last.savings_interest_paid_zai,
+        savings.total_interest_paid_zai
+    );
+
+    // Without the module savers sit out and nothing is reported
+    let baseline = run(None, 2);
+    assert!(baseline.metrics.iter().all(|m| m.savings_rate == 0.0));
+    assert_eq!(baseline.savers[0].zai_balance, 50_000.0);
+}
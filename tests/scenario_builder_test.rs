@@ -0,0 +1,62 @@
+//! Fluent scenario construction.
+//!
+//! The builder adds agents, puts LP liquidity in once (a later `run` must
+//! not add it again) and opens vault populations at the requested ratios.
+
+use zai_sim::agents::{ArbitrageurConfig, AttackerConfig, LpAgentConfig};
+use zai_sim::error::ZaiSimError;
+use zai_sim::scenario_builder::{ScenarioBuilder, VaultPopulation};
+use zai_sim::scenarios::ScenarioId;
+
+#[test]
+fn test_builds_agents_lps_and_vaults() {
+    let lp = LpAgentConfig {
+        initial_zec: 1_000.0,
+        initial_zai: 50_000.0,
+        ..LpAgentConfig::default()
+    };
+    let mut scenario = ScenarioBuilder::default()
+        .with_amm(100_000.0, 5_000_000.0)
+        .with_stress_agents(ScenarioId::SteadyState)
+        .with_arbers(2, ArbitrageurConfig::default())
+        .with_lps(1, lp)
+        .with_attacker(AttackerConfig::default())
+        .with_vault_population(VaultPopulation::new(5, 1000.0, 1.6, 2.0))
+        .with_seed(7)
+        .build()
+        .unwrap();
+
+    assert_eq!(scenario.seed, 7);
+    assert_eq!(scenario.arbers.len(), 3);
+    assert_eq!(scenario.miners.len(), 1);
+    assert_eq!(scenario.attackers.len(), 1);
+    assert!(scenario.lp_agents[0].is_providing);
+    assert_eq!(scenario.amm.reserve_zec, 101_000.0);
+
+    assert_eq!(scenario.registry.vaults.len(), 5);
+    let price = scenario.amm.spot_price();
+    let mut ratios: Vec<f64> = scenario
+        .registry
+        .vaults
+        .values()
+        .map(|v| v.collateral_ratio(price))
+        .collect();
+    ratios.sort_by(f64::total_cmp);
+    for (ratio, expected) in ratios.iter().zip([1.6, 1.7, 1.8, 1.9, 2.0]) {
+        assert!((ratio - expected).abs() < 1e-9, "{} vs {}", ratio, expected);
+    }
+
+    // Running does not deposit the LP's liquidity a second time
+    let shares = scenario.amm.total_lp_shares;
+    scenario.run(&[50.0]);
+    assert_eq!(scenario.amm.total_lp_shares, shares);
+}
+
+#[test]
+fn test_unopenable_vaults_fail_build() {
+    let result = ScenarioBuilder::default()
+        .with_config(|c| c.cdp_config.min_ratio = 2.0)
+        .with_vault_population(VaultPopulation::new(3, 1000.0, 1.5, 2.5))
+        .build();
+    assert!(matches!(result, Err(ZaiSimError::BelowMinRatio { .. })));
+}
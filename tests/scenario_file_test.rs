@@ -0,0 +1,250 @@
+//! Declarative stress scenarios.
+//!
+//! A YAML or TOML file describes price segments, the agent roster and
+//! mid-run config changes, so new stress cases need no recompile.
+
+use std::path::Path;
+
+use approx::assert_relative_eq;
+use serde_json::json;
+use zai_sim::agents::AttackStrategy;
+use zai_sim::amm::TwapKind;
+use zai_sim::scenario::{Scenario, ScenarioConfig};
+use zai_sim::scenario_file::{self, AgentRoster, ConfigSetting, ScenarioFile};
+use zai_sim::scenarios::{self, ScenarioId};
+
+const CRASH_YAML: &str = r#"
+name: step_crash
+description: Hold, crash, hold at the bottom
+prices:
+  - { type: hold, price: 50, blocks: 10 }
+  - { type: ramp, to: 30, blocks: 4 }
+  - { type: hold, blocks: 6 }
+"#;
+
+#[test]
+fn test_yaml_price_segments() {
+    let file = ScenarioFile::from_yaml_str(CRASH_YAML).unwrap();
+    assert_eq!(file.name, "step_crash");
+    assert_eq!(file.blocks(), 20);
+
+    let prices = file.price_path(42).unwrap();
+    assert_eq!(prices.len(), 20);
+    assert_eq!(prices[9], 50.0);
+    assert_eq!(&prices[10..14], &[45.0, 40.0, 35.0, 30.0]);
+    // A hold without a price continues from the ramp's end
+    assert!(prices[14..].iter().all(|&p| p == 30.0));
+}
+
+#[test]
+fn test_toml_matches_yaml() {
+    let toml = r#"
+        name = "step_crash"
+        description = "Hold, crash, hold at the bottom"
+
+        [[prices]]
+        type = "hold"
+        price = 50
+        blocks = 10
+
+        [[prices]]
+        type = "ramp"
+        to = 30
+        blocks = 4
+
+        [[prices]]
+        type = "hold"
+        blocks = 6
+    "#;
+    let from_toml = ScenarioFile::from_toml_str(toml).unwrap();
+    let from_yaml = ScenarioFile::from_yaml_str(CRASH_YAML).unwrap();
+    assert_eq!(
+        from_toml.price_path(42).unwrap(),
+        from_yaml.price_path(42).unwrap()
+    );
+}
+
+#[test]
+fn test_walk_is_seeded_and_clamped() {
+    let file = ScenarioFile::from_yaml_str(
+        "name: walk\nprices:\n  - { type: walk, from: 20, sigma: 5, min: 15, max: 25, blocks: 200 }\n",
+    )
+    .unwrap();
+    let a = file.price_path(7).unwrap();
+    assert_eq!(a, file.price_path(7).unwrap());
+    assert_ne!(a, file.price_path(8).unwrap());
+    assert!(a.iter().all(|&p| (15.0..=25.0).contains(&p)));
+}
+
+#[test]
+fn test_builtin_segment_reproduces_stress_scenario() {
+    let file = ScenarioFile::from_yaml_str(
+        "name: flash\nprices:\n  - { type: builtin, scenario: flash_crash, blocks: 300 }\n",
+    )
+    .unwrap();
+    let config = ScenarioConfig::default();
+    let from_file = file.run(&config, 42).unwrap();
+    // Default roster is the built-ins' baseline: one arber, one miner
+    let builtin = scenarios::run_stress(ScenarioId::FlashCrash, &config, 300, 42);
+
+    assert_eq!(from_file.metrics.len(), builtin.metrics.len());
+    for (a, b) in from_file.metrics.iter().zip(&builtin.metrics) {
+        assert_eq!(a.external_price, b.external_price);
+        assert_eq!(a.amm_spot_price, b.amm_spot_price);
+    }
+}
+
+#[test]
+fn test_config_and_agent_overrides() {
+    let file = ScenarioFile::from_yaml_str(
+        r#"
+name: roster
+config:
+  amm_swap_fee: 0.01
+  cdp_config: { min_ratio: 1.8 }
+prices:
+  - { type: hold, price: 50, blocks: 5 }
+agents:
+  arbers: [{}, { arb_latency_sell_blocks: 0 }]
+  attackers: [{ attack_capital_zec: 8000, attack_at_block: 3 }]
+"#,
+    )
+    .unwrap();
+    let config = file.scenario_config(&ScenarioConfig::default()).unwrap();
+    assert_eq!(config.amm_swap_fee, 0.01);
+    assert_eq!(config.cdp_config.min_ratio, 1.8);
+    // Untouched fields keep their defaults
+    assert_eq!(
+        config.cdp_config.liquidation_penalty,
+        ScenarioConfig::default().cdp_config.liquidation_penalty
+    );
+
+    let scenario = file.build(&config, 42).unwrap();
+    assert_eq!(scenario.arbers.len(), 2);
+    assert_eq!(scenario.arbers[1].config.arb_latency_sell_blocks, 0);
+    // Miners were not listed, so the default miner stays
+    assert_eq!(scenario.miners.len(), 1);
+    assert_eq!(scenario.attackers.len(), 1);
+    assert_eq!(scenario.attackers[0].config.attack_capital_zec, 8000.0);
+    assert_eq!(scenario.attackers[0].config.hold_blocks, 3);
+}
+
+#[test]
+fn test_unknown_fields_are_rejected() {
+    let typo = ScenarioFile::from_yaml_str(
+        "name: typo\nconfig: { cdp_config: { min_ration: 1.8 } }\nprices:\n  - { type: hold, price: 50, blocks: 5 }\n",
+    )
+    .unwrap();
+    let err = typo
+        .scenario_config(&ScenarioConfig::default())
+        .unwrap_err();
+    assert!(err.to_string().contains("cdp_config.min_ration"), "{}", err);
+
+    let builtin = ScenarioFile::from_yaml_str(
+        "name: b\nprices:\n  - { type: builtin, scenario: nope, blocks: 5 }\n",
+    )
+    .unwrap();
+    assert!(builtin.price_path(42).is_err());
+
+    let no_start =
+        ScenarioFile::from_yaml_str("name: s\nprices:\n  - { type: hold, blocks: 5 }\n").unwrap();
+    assert!(no_start.price_path(42).is_err());
+}
+
+#[test]
+fn test_mid_run_config_change() {
+    let file = ScenarioFile::from_yaml_str(
+        r#"
+name: fee_hike
+prices:
+  - { type: hold, price: 50, blocks: 20 }
+events:
+  - { at_block: 11, set: { amm_swap_fee: 0.01, cdp_config: { min_ratio: 2.0 } } }
+"#,
+    )
+    .unwrap();
+    let scenario = file.run(&ScenarioConfig::default(), 42).unwrap();
+
+    assert_eq!(scenario.metrics.len(), 20);
+    assert_relative_eq!(scenario.amm.swap_fee, 0.01);
+    assert_relative_eq!(scenario.registry.config.min_ratio, 2.0);
+    assert_relative_eq!(scenario.config.amm_swap_fee, 0.01);
+
+    let late = ScenarioFile::from_yaml_str(
+        "name: late\nprices:\n  - { type: hold, price: 50, blocks: 5 }\nevents:\n  - { at_block: 9, set: { amm_swap_fee: 0.01 } }\n",
+    )
+    .unwrap();
+    assert!(late.run(&ScenarioConfig::default(), 42).is_err());
+}
+
+#[test]
+fn test_roster_classes_and_attack_strategy() {
+    let mut roster = AgentRoster::empty();
+    *roster.class_mut("lp_agents").unwrap() = vec![json!({ "il_threshold": 0.03 }); 2];
+    *roster.class_mut("attackers").unwrap() = vec![json!({
+        "attack_at_block": 5,
+        "strategy": { "type": "drip", "zec_per_block": 100.0, "blocks": 30, "unwind_blocks": 3 },
+    })];
+    assert!(roster.class_mut("keepers").is_err());
+
+    let mut scenario = Scenario::new(&ScenarioConfig::default());
+    roster.add_to(&mut scenario, 42).unwrap();
+    assert!(scenario.arbers.is_empty());
+    assert_eq!(scenario.lp_agents.len(), 2);
+    assert_eq!(scenario.lp_agents[1].config.il_threshold, 0.03);
+    assert_eq!(scenario.attackers[0].config.attack_at_block, 5);
+    assert_eq!(
+        scenario.attackers[0].config.strategy,
+        AttackStrategy::Drip {
+            zec_per_block: 100.0,
+            blocks: 30,
+            unwind_blocks: 3,
+        }
+    );
+
+    *roster.class_mut("attackers").unwrap() = vec![json!({ "strategy": { "type": "nope" } })];
+    let err = roster
+        .add_to(&mut Scenario::new(&ScenarioConfig::default()), 42)
+        .unwrap_err();
+    assert!(err.to_string().contains("strategy"), "{}", err);
+}
+
+#[test]
+fn test_config_settings() {
+    let mut config = ScenarioConfig::default();
+    for setting in [
+        "min_ratio=2.0",
+        "cdp_config.twap_window=240",
+        "cdp_config.twap_kind=median",
+        "use_amm_liquidation=true",
+    ] {
+        let setting: ConfigSetting = setting.parse().unwrap();
+        setting.apply(&mut config).unwrap();
+    }
+    assert_eq!(config.cdp_config.min_ratio, 2.0);
+    assert_eq!(config.cdp_config.twap_window, 240);
+    assert_eq!(config.cdp_config.twap_kind, TwapKind::Median);
+    assert!(config.use_amm_liquidation);
+
+    assert!("min_ratio".parse::<ConfigSetting>().is_err());
+    for bad in ["cdp_config.min_ration=2.0", "use_amm_liquidation=maybe"] {
+        let setting: ConfigSetting = bad.parse().unwrap();
+        assert!(setting.apply(&mut config).is_err(), "{}", bad);
+    }
+}
+
+#[test]
+fn test_load_production_config() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("configs/production.toml");
+    let config = scenario_file::load_config(&path, &ScenarioConfig::default()).unwrap();
+    assert_eq!(config.amm_initial_zai, 5_000_000.0);
+    assert_eq!(config.cdp_config.min_ratio, 2.0);
+    assert_eq!(config.cdp_config.twap_window, 240);
+    // Unset fields keep the base config's values
+    assert_eq!(config.amm_swap_fee, ScenarioConfig::default().amm_swap_fee);
+
+    let typo = std::env::temp_dir().join("zai_sim_config_typo.json");
+    std::fs::write(&typo, r#"{"cdp_config":{"min_ration":2.0}}"#).unwrap();
+    let err = scenario_file::load_config(&typo, &ScenarioConfig::default()).unwrap_err();
+    assert!(err.to_string().contains("min_ration"), "{}", err);
+}
@@ -0,0 +1,114 @@
+//! Scenario composition.
+//!
+//! `ScenarioMix` chains built-in stress scenarios one after another or
+//! overlays them on the same blocks, from the library or `stress --id 2+7`.
+
+use approx::assert_relative_eq;
+use zai_sim::scenario::ScenarioConfig;
+use zai_sim::scenarios::{generate_prices, run_mix, run_stress, ScenarioId, ScenarioMix};
+
+use ScenarioId::*;
+
+#[test]
+fn test_parse_compositions() {
+    assert_eq!(
+        "2+7".parse::<ScenarioMix>().unwrap(),
+        BlackThursday.chain(BankRun)
+    );
+    assert_eq!(
+        "3~13".parse::<ScenarioMix>().unwrap(),
+        FlashCrash.overlay(SequencerDowntime)
+    );
+    assert_eq!(
+        "flash_crash~sequencer_downtime"
+            .parse::<ScenarioMix>()
+            .unwrap(),
+        FlashCrash.overlay(SequencerDowntime)
+    );
+    // Overlay binds tighter than chain
+    assert_eq!(
+        "2+3~13".parse::<ScenarioMix>().unwrap(),
+        BlackThursday.chain(FlashCrash.overlay(SequencerDowntime))
+    );
+    assert_eq!(
+        "4".parse::<ScenarioMix>().unwrap(),
+        ScenarioMix::Single(SustainedBear)
+    );
+    assert!("14".parse::<ScenarioMix>().is_err());
+    assert!("2+".parse::<ScenarioMix>().is_err());
+}
+
+#[test]
+fn test_builders_flatten_and_name() {
+    let mix = BlackThursday.chain(BankRun).chain(FlashCrash);
+    assert_eq!(
+        mix,
+        ScenarioMix::Chain(vec![
+            ScenarioMix::Single(BlackThursday),
+            ScenarioMix::Single(BankRun),
+            ScenarioMix::Single(FlashCrash),
+        ])
+    );
+    assert_eq!(mix.name(), "black_thursday+bank_run+flash_crash");
+    assert_eq!(
+        BlackThursday
+            .chain(BankRun)
+            .overlay(OracleComparison)
+            .name(),
+        "(black_thursday+bank_run)~oracle_comparison"
+    );
+}
+
+#[test]
+fn test_chain_continues_from_previous_price() {
+    let prices = BlackThursday.chain(BankRun).generate_prices(1000, 42);
+    assert_eq!(prices.len(), 1000);
+    // Black Thursday ends at 35; Bank Run is rescaled to start there
+    assert_relative_eq!(prices[499], 35.0);
+    assert_relative_eq!(prices[500], 35.0);
+    let bank_run = generate_prices(BankRun, 500, 42);
+    assert_relative_eq!(prices[999], bank_run[499] * 35.0 / 50.0, epsilon = 1e-9);
+}
+
+#[test]
+fn test_overlay_multiplies_relative_moves() {
+    let prices = FlashCrash
+        .overlay(SequencerDowntime)
+        .generate_prices(1000, 42);
+    let flash = generate_prices(FlashCrash, 1000, 42);
+    // Before the downtime gap the overlay is the flash crash itself
+    assert_relative_eq!(prices[520], flash[520]);
+    // After it, the 50 -> 35 gap scales the recovered price by 0.7
+    assert_relative_eq!(prices[900], flash[900] * 0.7, epsilon = 1e-9);
+}
+
+#[test]
+fn test_single_mix_matches_run_stress() {
+    let config = ScenarioConfig::default();
+    let mix = run_mix(&ScenarioMix::Single(BankRun), &config, 300, 42);
+    let direct = run_stress(BankRun, &config, 300, 42);
+    assert_eq!(mix.demand_agents.len(), direct.demand_agents.len());
+    for (a, b) in mix.metrics.iter().zip(&direct.metrics) {
+        assert_eq!(a.amm_spot_price, b.amm_spot_price);
+    }
+}
+
+#[test]
+fn test_composed_agents() {
+    let config = ScenarioConfig::default();
+    let overlay = run_mix(
+        &LiquidityCrisis.overlay(MinerCapitulation),
+        &config,
+        200,
+        42,
+    );
+    // One shared baseline arber and miner, plus each part's extras
+    assert_eq!(overlay.arbers.len(), 1);
+    assert_eq!(overlay.miners.len(), 4);
+    assert_eq!(overlay.lp_agents.len(), 1);
+
+    // The TWAP attack in the second half of a chain is shifted to that half
+    let chain = run_mix(&SteadyState.chain(TwapManipulation), &config, 1200, 42);
+    assert_eq!(chain.attackers.len(), 1);
+    assert_eq!(chain.attackers[0].config.attack_at_block, 600 + 500);
+}
@@ -305,13 +305,85 @@ fn test_all_scenarios_complete() {
             sid.name()
         );
         assert!(
-            scenario.metrics[0].amm_spot_price > 0.0,
+            scenario.metrics.amm_spot_price[0] > 0.0,
             "Scenario {} should have positive AMM price",
             sid.name()
         );
     }
 }
 
+#[test]
+fn test_stress_on_historical_prices() {
+    let path =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("data/may_2021_crash_hourly.csv");
+    let mut prices = zai_sim::historical::load_close_prices(&path).unwrap();
+    let (first, last) = (prices[0], *prices.last().unwrap());
+    rescale_prices(&mut prices, 50.0);
+    assert!((prices[0] - 50.0).abs() < 1e-9);
+    assert!((prices.last().unwrap() - 50.0 * last / first).abs() < 1e-9);
+
+    let config = ScenarioConfig::default();
+    let scenario = run_stress_on(
+        ScenarioId::BlackThursday,
+        &config,
+        &prices,
+        TEST_SEED,
+        |_| {},
+    );
+    assert_eq!(scenario.metrics.len(), prices.len());
+    assert_eq!(scenario.metrics.external_price[0], prices[0]);
+    // BlackThursday's roster, not its generated path
+    assert_eq!(scenario.arbers.len(), 1);
+    assert_eq!(scenario.miners.len(), 1);
+}
+
+#[test]
+fn test_parse_scenario_ids() {
+    use ScenarioId::*;
+    assert_eq!(
+        parse_scenario_ids("2,3,7-10").unwrap(),
+        vec![
+            BlackThursday,
+            FlashCrash,
+            BankRun,
+            BullMarket,
+            OracleComparison,
+            CombinedStress
+        ]
+    );
+    assert_eq!(
+        parse_scenario_ids("flash_crash, 2-3").unwrap(),
+        vec![FlashCrash, BlackThursday]
+    );
+    assert_eq!(parse_scenario_ids("0").unwrap(), ScenarioId::all());
+    assert_eq!(parse_scenario_ids("all").unwrap(), ScenarioId::all());
+    for bad in ["4-2", "1-14", "2,,3", "crash"] {
+        assert!(parse_scenario_ids(bad).is_err(), "{}", bad);
+    }
+}
+
+#[test]
+fn test_scenario_info() {
+    let crash = ScenarioId::BlackThursday.info(DEFAULT_BLOCKS, TEST_SEED);
+    assert_eq!(crash.id, 2);
+    assert_eq!(crash.name, "black_thursday");
+    assert_eq!(crash.start_price, 50.0);
+    assert_eq!(crash.min_price, 20.0);
+    assert_eq!(crash.end_price, 35.0);
+    assert_eq!(crash.hours, 1000.0 * 75.0 / 3600.0);
+    assert_eq!(
+        crash.agents,
+        vec![("arbers".to_string(), 1), ("miners".to_string(), 1)]
+    );
+
+    // Scenario-specific agents come on top of the baseline arber and miner
+    let capitulation = ScenarioId::MinerCapitulation.info(TEST_BLOCKS, TEST_SEED);
+    assert!(capitulation.agents.contains(&("miners".to_string(), 4)));
+    let manipulation = ScenarioId::TwapManipulation.info(DEFAULT_BLOCKS, TEST_SEED);
+    assert!(manipulation.agents.contains(&("attackers".to_string(), 1)));
+    assert_eq!(manipulation.max_price, 100.0);
+}
+
 // ═══════════════════════════════════════════════════════════════════════
 // Scoring Tests
 // ═══════════════════════════════════════════════════════════════════════
@@ -459,6 +531,53 @@ fn test_output_files() {
     let _ = std::fs::remove_dir_all(&dir);
 }
 
+#[test]
+fn test_output_manifest() {
+    let config = ScenarioConfig {
+        amm_swap_fee: 0.007,
+        ..ScenarioConfig::default()
+    };
+    let scenario = run_stress(ScenarioId::SteadyState, &config, 50, 7);
+    let dir = std::env::temp_dir().join("zai_sim_test_manifest");
+    let _ = std::fs::remove_dir_all(&dir);
+
+    output::save_all(&scenario, &config, 50.0, &dir).unwrap();
+    let manifest = output::Manifest::load(&dir).unwrap();
+    assert_eq!(manifest.crate_version, env!("CARGO_PKG_VERSION"));
+    assert_eq!(manifest.seeds, vec![7]);
+    assert_eq!(manifest.config.amm_swap_fee, 0.007);
+    assert!(manifest.price_sources.is_empty());
+    assert!(chrono::DateTime::parse_from_rfc3339(&manifest.created_at).is_ok());
+
+    // The manifest's config reproduces the run
+    let rerun = run_stress(
+        ScenarioId::SteadyState,
+        &manifest.config,
+        50,
+        manifest.seeds[0],
+    );
+    assert_eq!(rerun.metrics.len(), scenario.metrics.len());
+    assert_eq!(
+        rerun.metrics.last().unwrap().amm_spot_price,
+        scenario.metrics.last().unwrap().amm_spot_price
+    );
+
+    let prices = dir.join("prices.csv");
+    std::fs::write(&prices, "abc").unwrap();
+    let manifest = output::Manifest::new(&config, &[7])
+        .with_price_source(&prices)
+        .unwrap();
+    output::save_all_with_manifest(&scenario, &config, 50.0, &dir, &manifest).unwrap();
+    let source = &output::Manifest::load(&dir).unwrap().price_sources[0];
+    assert_eq!(
+        source.sha256,
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    );
+    assert_eq!(source.bytes, 3);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
 #[test]
 fn test_summary_metrics() {
     let config = ScenarioConfig::default();
@@ -0,0 +1,131 @@
+//! Vault owners choosing self-liquidation.
+//!
+//! A holder with nothing left to defend its vault weighs the chance of a
+//! keeper liquidation at the full penalty against self-liquidating now at
+//! the reduced one, scaled by its risk aversion.
+
+use zai_sim::agents::{AgentAction, CdpHolder, CdpHolderConfig, SelfLiquidationPolicy};
+use zai_sim::amm::Amm;
+use zai_sim::cdp::{CdpConfig, VaultRegistry};
+use zai_sim::liquidation::{LiquidationConfig, LiquidationEngine, LiquidationMode};
+use zai_sim::scenario::{Scenario, ScenarioConfig};
+use zai_sim::scenarios::{add_agents, generate_prices, ScenarioId};
+
+#[test]
+fn test_exit_weighs_expected_penalty() {
+    let neutral = SelfLiquidationPolicy::default();
+    assert_eq!(neutral.liquidation_probability(1.5, 1.5), 1.0);
+    assert_eq!(neutral.liquidation_probability(1.4, 1.5), 1.0);
+    // A 5% drop away from liquidation: 1/e
+    let p = neutral.liquidation_probability(1.5 / 0.95, 1.5);
+    assert!((p - (-1.0f64).exp()).abs() < 1e-12);
+
+    // Full penalty 13%, self-liquidation at half of it
+    assert!(neutral.prefers_exit(1.52, 1.5, 0.13, 0.065));
+    assert!(!neutral.prefers_exit(1.6, 1.5, 0.13, 0.065));
+    let averse = SelfLiquidationPolicy {
+        risk_aversion: 3.0,
+        ..SelfLiquidationPolicy::default()
+    };
+    assert!(averse.prefers_exit(1.6, 1.5, 0.13, 0.065));
+    // A free exit is always worth it
+    assert!(neutral.prefers_exit(2.5, 1.5, 0.13, 0.0));
+}
+
+/// Undefended vault at CR 2.5 (50 ZEC collateral, 1000 ZAI debt, price 50)
+/// that acts below 2.8.
+fn setup(reserve_zec: f64) -> (CdpHolder, VaultRegistry, Amm) {
+    let amm = Amm::new(10_000.0, 500_000.0, 0.003);
+    let mut registry = VaultRegistry::new(CdpConfig::default());
+    let mut holder = CdpHolder::new(CdpHolderConfig {
+        target_ratio: 3.0,
+        action_threshold_ratio: 2.8,
+        reserve_zec,
+        initial_collateral: 50.0,
+        initial_debt: 1000.0,
+    })
+    .with_self_liquidation(SelfLiquidationPolicy::default());
+    holder.open_vault(&mut registry, &amm, 0).unwrap();
+    (holder, registry, amm)
+}
+
+fn engine_with(self_liquidation_penalty_pct: f64) -> LiquidationEngine {
+    LiquidationEngine::new(LiquidationConfig {
+        self_liquidation_penalty_pct,
+        ..LiquidationConfig::default()
+    })
+}
+
+#[test]
+fn test_undefended_holder_self_liquidates() {
+    let (mut holder, mut registry, mut amm) = setup(0.0);
+    let mut engine = engine_with(0.0);
+    let id = holder.vault_id.unwrap();
+    let action = holder.self_liquidate_if_rational(&mut engine, &mut registry, &mut amm, 1);
+
+    assert!(matches!(action, AgentAction::CdpAction { .. }));
+    assert!(registry.get_vault(id).is_none());
+    assert_eq!(holder.vault_id, None);
+    assert_eq!(holder.self_liquidations, 1);
+    let result = engine.history.last().unwrap();
+    assert_eq!(result.mode, LiquidationMode::SelfLiquidation);
+    // 50 ZEC sell for about 2475 ZAI; the owner keeps what's over the debt
+    assert_eq!(holder.zai_balance, result.surplus_to_owner);
+    assert!(holder.zai_balance > 1400.0);
+}
+
+#[test]
+fn test_holder_waits_when_exit_costs_more() {
+    // At CR 2.5 liquidation is a 40% drop away: not worth half the penalty
+    let (mut holder, mut registry, mut amm) = setup(0.0);
+    let mut engine = engine_with(0.5);
+    let action = holder.self_liquidate_if_rational(&mut engine, &mut registry, &mut amm, 1);
+    assert!(matches!(action, AgentAction::None));
+    assert!(holder.vault_id.is_some());
+
+    // A holder with reserves defends instead
+    let (mut holder, mut registry, mut amm) = setup(100.0);
+    let mut engine = engine_with(0.0);
+    holder.self_liquidate_if_rational(&mut engine, &mut registry, &mut amm, 1);
+    assert!(holder.vault_id.is_some());
+    assert!(engine.history.is_empty());
+
+    // As does one without a policy
+    let (mut holder, mut registry, mut amm) = setup(0.0);
+    holder.self_liquidation = None;
+    holder.self_liquidate_if_rational(&mut engine, &mut registry, &mut amm, 1);
+    assert!(holder.vault_id.is_some());
+}
+
+#[test]
+fn test_scenario_holders_exit_before_keepers() {
+    let mut scenario = Scenario::new_with_seed(&ScenarioConfig::default(), 42);
+    add_agents(ScenarioId::SustainedBear, &mut scenario);
+    for _ in 0..5 {
+        scenario.cdp_holders.push(
+            CdpHolder::new(CdpHolderConfig {
+                target_ratio: 2.0,
+                action_threshold_ratio: 1.9,
+                reserve_zec: 0.0,
+                initial_collateral: 5.0,
+                initial_debt: 120.0,
+            })
+            .with_self_liquidation(SelfLiquidationPolicy::default()),
+        );
+    }
+    scenario.run(&generate_prices(ScenarioId::SustainedBear, 1000, 42));
+
+    let exits: u32 = scenario
+        .cdp_holders
+        .iter()
+        .map(|h| h.self_liquidations)
+        .sum();
+    assert!(exits > 0);
+    let recorded: usize = scenario.metrics.iter().map(|m| m.self_liquidations).sum();
+    assert_eq!(recorded, exits as usize);
+    assert!(scenario
+        .cdp_holders
+        .iter()
+        .filter(|h| h.self_liquidations > 0)
+        .all(|h| h.vault_id.is_none() && h.zai_balance > 0.0));
+}
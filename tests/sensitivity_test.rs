@@ -0,0 +1,120 @@
+//! Sobol sensitivity analysis.
+//!
+//! Checks the Saltelli estimators against models with known indices, then
+//! runs a small analysis over the simulator itself.
+
+use zai_sim::scenarios::ScenarioId;
+use zai_sim::sensitivity::{run_sensitivity, saltelli, sobol_indices};
+use zai_sim::sweep::SweepRange;
+
+fn unit(name: &str) -> SweepRange {
+    SweepRange {
+        name: name.to_string(),
+        min: 0.0,
+        max: 1.0,
+    }
+}
+
+#[test]
+fn test_additive_model_indices() {
+    // y = x1 + 2 x2 with uniform inputs: S1 = 1/5, S2 = 4/5, x3 inert.
+    let ranges = vec![unit("x1"), unit("x2"), unit("x3")];
+    let report = saltelli(&ranges, &["y".to_string()], 4000, 7, |p| {
+        vec![p[0] + 2.0 * p[1]]
+    })
+    .unwrap();
+    assert_eq!(report.evaluations, 4000 * 5);
+
+    let idx = &report.outcomes[0].indices;
+    assert!((idx[0].first_order - 0.2).abs() < 0.05, "{:?}", idx[0]);
+    assert!((idx[1].first_order - 0.8).abs() < 0.05, "{:?}", idx[1]);
+    assert!((idx[0].total - 0.2).abs() < 0.05, "{:?}", idx[0]);
+    assert!((idx[1].total - 0.8).abs() < 0.05, "{:?}", idx[1]);
+    assert!(idx[2].first_order.abs() < 1e-12 && idx[2].total.abs() < 1e-12);
+}
+
+#[test]
+fn test_interaction_shows_in_total_index_only() {
+    // y = x1 * x2 centered at zero: no first-order effect, all interaction.
+    let ranges = vec![
+        SweepRange {
+            name: "x1".to_string(),
+            min: -1.0,
+            max: 1.0,
+        },
+        SweepRange {
+            name: "x2".to_string(),
+            min: -1.0,
+            max: 1.0,
+        },
+    ];
+    let report = saltelli(&ranges, &["y".to_string()], 4000, 3, |p| vec![p[0] * p[1]]).unwrap();
+    for idx in &report.outcomes[0].indices {
+        assert!(idx.first_order.abs() < 0.05, "{:?}", idx);
+        assert!((idx.total - 1.0).abs() < 0.1, "{:?}", idx);
+    }
+}
+
+#[test]
+fn test_large_mean_does_not_inflate_indices() {
+    let ranges = vec![unit("x1"), unit("x2")];
+    let report = saltelli(&ranges, &["y".to_string()], 1000, 11, |p| {
+        vec![1e6 + 1e-3 * p[0]]
+    })
+    .unwrap();
+    let idx = &report.outcomes[0].indices;
+    assert!((idx[0].first_order - 1.0).abs() < 0.1, "{:?}", idx[0]);
+}
+
+#[test]
+fn test_constant_outcome_has_zero_indices() {
+    let indices = sobol_indices(&[1.0; 4], &[1.0; 4], &[vec![1.0; 4]]);
+    assert_eq!(indices, vec![(0.0, 0.0)]);
+}
+
+#[test]
+fn test_rejects_bad_inputs() {
+    let outcomes = vec!["total_bad_debt".to_string()];
+    let ranges = vec!["swap_fee=0.001:0.01".parse::<SweepRange>().unwrap()];
+    let scenarios = [ScenarioId::FlashCrash];
+    assert!(run_sensitivity(&[], &outcomes, 8, &scenarios, 50, 42).is_err());
+    assert!(run_sensitivity(&ranges, &outcomes, 8, &[], 50, 42).is_err());
+    assert!(run_sensitivity(
+        &ranges,
+        &["no_such_metric".to_string()],
+        8,
+        &scenarios,
+        50,
+        42
+    )
+    .is_err());
+    let unknown = vec!["no_such_param=0:1".parse::<SweepRange>().unwrap()];
+    assert!(run_sensitivity(&unknown, &outcomes, 8, &scenarios, 50, 42).is_err());
+}
+
+#[test]
+fn test_simulation_sensitivity_runs() {
+    let ranges = vec![
+        "swap_fee=0.001:0.01".parse::<SweepRange>().unwrap(),
+        "liquidation_penalty=0.05:0.2"
+            .parse::<SweepRange>()
+            .unwrap(),
+    ];
+    let report = run_sensitivity(
+        &ranges,
+        &["max_peg_deviation".to_string()],
+        16,
+        &[ScenarioId::FlashCrash],
+        200,
+        42,
+    )
+    .unwrap();
+    assert_eq!(report.evaluations, 16 * 4);
+    let idx = &report.outcomes[0].indices;
+    assert_eq!(idx[0].param, "swap_fee");
+    assert_eq!(idx[1].param, "liquidation_penalty");
+    for i in idx {
+        assert!(i.first_order.is_finite() && i.total.is_finite(), "{:?}", i);
+        assert!(i.total >= 0.0, "{:?}", i);
+    }
+}
@@ -0,0 +1,173 @@
+//! Output directory dashboard server.
+//!
+//! The dashboard lists saved runs, serves their reports and files, and
+//! answers JSON summary queries, without reaching outside the directory.
+
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use zai_sim::output;
+use zai_sim::scenario::ScenarioConfig;
+use zai_sim::scenarios::{run_stress, ScenarioId};
+use zai_sim::serve::{Dashboard, RunEntry};
+
+/// An output directory with two stress runs (one nested, one with an HTML
+/// report) and a sweep results file.
+fn output_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("zai_sim_serve_{}", name));
+    let _ = std::fs::remove_dir_all(&dir);
+    let config = ScenarioConfig::default();
+    for (id, sub) in [
+        (ScenarioId::SteadyState, "steady_state"),
+        (ScenarioId::FlashCrash, "sweep_a/flash_crash"),
+    ] {
+        let scenario = run_stress(id, &config, 50, 42);
+        output::save_all(
+            &scenario,
+            &config,
+            config.initial_redemption_price,
+            &dir.join(sub),
+        )
+        .unwrap();
+    }
+    std::fs::write(dir.join("steady_state.html"), "<html>report</html>").unwrap();
+    std::fs::write(
+        dir.join("sweep_results.csv"),
+        "min_ratio,overall_score\n1.5,0.82\n2.0,0.91\n",
+    )
+    .unwrap();
+    dir
+}
+
+fn body(dashboard: &Dashboard, path: &str) -> serde_json::Value {
+    let response = dashboard.handle("GET", path);
+    assert_eq!(response.status, 200, "{}", path);
+    assert_eq!(response.content_type, "application/json");
+    serde_json::from_slice(&response.body).unwrap()
+}
+
+#[test]
+fn test_lists_runs_and_reports() {
+    let dir = output_dir("list");
+    let dashboard = Dashboard::new(&dir);
+    assert_eq!(
+        dashboard.runs().unwrap(),
+        vec![
+            RunEntry {
+                name: "steady_state".into(),
+                report: Some("steady_state.html".into()),
+                has_timeseries: true,
+            },
+            RunEntry {
+                name: "sweep_a/flash_crash".into(),
+                report: None,
+                has_timeseries: true,
+            },
+        ]
+    );
+    let runs = body(&dashboard, "/api/runs");
+    assert_eq!(runs[1]["name"], "sweep_a/flash_crash");
+
+    let index = dashboard.handle("GET", "/");
+    assert_eq!(index.status, 200);
+    let html = String::from_utf8(index.body).unwrap();
+    assert!(html.contains("<a href=\"/steady_state.html\">report</a>"));
+    assert!(html.contains("/api/runs/sweep_a/flash_crash"));
+    assert!(html.contains("Sweep results"));
+}
+
+#[test]
+fn test_json_summaries() {
+    let dir = output_dir("json");
+    let dashboard = Dashboard::new(&dir);
+    let summary = body(&dashboard, "/api/runs/steady_state");
+    assert_eq!(summary["total_blocks"], 50);
+
+    let all = body(&dashboard, "/api/summaries");
+    assert_eq!(all["sweep_a/flash_crash"]["total_blocks"], 50);
+    assert_eq!(all.as_object().unwrap().len(), 2);
+
+    let rows = body(&dashboard, "/api/sweep_results?format=json");
+    assert_eq!(rows[1]["min_ratio"], 2.0);
+    assert_eq!(rows[1]["overall_score"], 0.91);
+
+    assert_eq!(dashboard.handle("GET", "/api/runs/missing").status, 404);
+}
+
+#[test]
+fn test_serves_files_inside_root_only() {
+    let dir = output_dir("files");
+    let dashboard = Dashboard::new(&dir);
+    let report = dashboard.handle("GET", "/steady_state.html");
+    assert_eq!(report.status, 200);
+    assert_eq!(report.content_type, "text/html; charset=utf-8");
+    assert_eq!(report.body, b"<html>report</html>");
+
+    let csv = dashboard.handle("GET", "/sweep_a%2Fflash_crash/timeseries.csv");
+    assert_eq!(csv.status, 200);
+    assert!(csv.content_type.starts_with("text/csv"));
+
+    let secret = dir.with_extension("secret");
+    std::fs::write(&secret, "outside").unwrap();
+    let name = secret.file_name().unwrap().to_string_lossy();
+    assert_eq!(
+        dashboard.handle("GET", &format!("/../{}", name)).status,
+        404
+    );
+    assert_eq!(dashboard.handle("GET", "/%2e%2e/etc/passwd").status, 404);
+    assert_eq!(dashboard.handle("GET", "/api/runs/../x").status, 400);
+    assert_eq!(dashboard.handle("GET", "/bad%zz").status, 400);
+    assert_eq!(dashboard.handle("POST", "/api/runs").status, 405);
+}
+
+#[test]
+fn test_http_round_trip() {
+    let dir = output_dir("http");
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    std::thread::spawn(move || Dashboard::new(dir).serve(listener));
+
+    let mut stream = TcpStream::connect(addr).unwrap();
+    stream
+        .write_all(b"GET /api/runs/steady_state HTTP/1.1\r\nHost: localhost\r\n\r\n")
+        .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    assert!(head.starts_with("HTTP/1.1 200 OK"), "{}", head);
+    assert!(head.contains("Content-Type: application/json"));
+    assert!(head.contains(&format!("Content-Length: {}", body.len())));
+    let summary: serde_json::Value = serde_json::from_str(body).unwrap();
+    assert_eq!(summary["total_blocks"], 50);
+}
+
+#[test]
+fn test_stalled_client_is_dropped() {
+    let dir = output_dir("stalled");
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    std::thread::spawn(move || {
+        Dashboard::new(dir)
+            .with_io_timeout(Duration::from_millis(100))
+            .serve(listener)
+    });
+
+    // A client that never finishes its request is cut off...
+    let mut stalled = TcpStream::connect(addr).unwrap();
+    stalled.write_all(b"GET /api/runs HTTP/1.1\r\n").unwrap();
+    stalled
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    let mut response = Vec::new();
+    stalled.read_to_end(&mut response).unwrap();
+    assert!(response.is_empty());
+
+    // ...and the server keeps answering others
+    let mut stream = TcpStream::connect(addr).unwrap();
+    stream.write_all(b"GET /api/runs HTTP/1.1\r\n\r\n").unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    assert!(response.starts_with("HTTP/1.1 200 OK"), "{}", response);
+}
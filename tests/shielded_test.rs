@@ -0,0 +1,168 @@
+//! Shielded-pool holdings and unshielding delay.
+//!
+//! Agents keep part of their funds shielded; a wallet shortfall is
+//! unshielded in capped batches that arrive after a delay.
+
+use approx::assert_relative_eq;
+use zai_sim::scenario::{Scenario, ScenarioConfig};
+use zai_sim::scenarios::{add_agents, generate_prices, ScenarioId};
+use zai_sim::shielded::{ShieldedPool, ShieldedPoolConfig};
+
+#[test]
+fn test_unshield_batches_arrive_after_delay() {
+    let mut pool = ShieldedPool::new(ShieldedPoolConfig::default());
+    let (mut zec, mut zai) = (1000.0, 0.0);
+
+    // Half is shielded straight away
+    pool.settle("a", &mut zec, &mut zai, 1);
+    assert_eq!(zec, 500.0);
+    assert_eq!(pool.accounts["a"].zec, 500.0);
+
+    // Spending down to 100 opens a 200 ZEC shortfall, met in 100 ZEC
+    // batches that count as soon as they are requested
+    zec = 100.0;
+    pool.settle("a", &mut zec, &mut zai, 2);
+    pool.settle("a", &mut zec, &mut zai, 3);
+    pool.settle("a", &mut zec, &mut zai, 4);
+    assert_eq!(zec, 100.0);
+    assert_eq!(pool.accounts["a"].zec, 300.0);
+    assert_eq!(pool.accounts["a"].in_flight(), (200.0, 0.0));
+    assert_eq!(pool.totals(), (500.0, 0.0));
+
+    for block in 5..=11 {
+        pool.settle("a", &mut zec, &mut zai, block);
+        assert_eq!(zec, 100.0);
+    }
+    pool.settle("a", &mut zec, &mut zai, 12);
+    assert_eq!(zec, 200.0);
+    pool.settle("a", &mut zec, &mut zai, 13);
+    assert_eq!(zec, 300.0);
+    assert_eq!(pool.accounts["a"].in_flight(), (0.0, 0.0));
+    assert_eq!(pool.total_unshielded_zec, 200.0);
+
+    // Dropping the model returns everything
+    pool.release("a", &mut zec, &mut zai);
+    assert_eq!(zec, 600.0);
+    assert!(pool.accounts.is_empty());
+}
+
+/// ZEC and ZAI in every agent wallet.
+fn wallets(s: &Scenario) -> (f64, f64) {
+    let mut balances: Vec<(f64, f64)> = Vec::new();
+    balances.extend(s.arbers.iter().map(|a| (a.zec_balance, a.zai_balance)));
+    balances.extend(
+        s.demand_agents
+            .iter()
+            .map(|a| (a.zec_balance, a.zai_balance)),
+    );
+    balances.extend(s.miners.iter().map(|a| (a.zec_balance, a.zai_balance)));
+    balances.extend(s.redeemers.iter().map(|a| (a.zec_balance, a.zai_balance)));
+    balances.extend(
+        s.basis_traders
+            .iter()
+            .map(|a| (a.zec_balance, a.zai_balance)),
+    );
+    balances.extend(s.savers.iter().map(|a| (a.zec_balance, a.zai_balance)));
+    balances.extend(
+        s.noise_traders
+            .iter()
+            .map(|a| (a.zec_balance, a.zai_balance)),
+    );
+    balances
+        .iter()
+        .fold((0.0, 0.0), |(zec, zai), (z, d)| (zec + z, zai + d))
+}
+
+fn run(shielded_pool: Option<ShieldedPoolConfig>, blocks: usize) -> Scenario {
+    let config = ScenarioConfig {
+        shielded_pool,
+        ..ScenarioConfig::default()
+    };
+    let mut scenario = Scenario::new_with_seed(&config, 42);
+    add_agents(ScenarioId::BankRun, &mut scenario);
+    scenario.run(&generate_prices(ScenarioId::BankRun, blocks, 42));
+    scenario
+}
+
+#[test]
+fn test_no_unshielding_only_accumulates() {
+    let locked = run(
+        Some(ShieldedPoolConfig {
+            max_batch_zec: 0.0,
+            max_batch_zai: 0.0,
+            ..ShieldedPoolConfig::default()
+        }),
+        300,
+    );
+    assert!(locked.metrics.iter().all(|m| m.shielded_zec > 0.0));
+    assert!(locked
+        .metrics
+        .shielded_zec
+        .windows(2)
+        .all(|w| w[1] >= w[0] - 1e-9));
+    assert!(locked
+        .metrics
+        .shielded_zai
+        .windows(2)
+        .all(|w| w[1] >= w[0] - 1e-9));
+    assert_eq!(
+        locked.shielded_pool.as_ref().unwrap().total_unshielded_zec,
+        0.0
+    );
+}
+
+#[test]
+fn test_removing_model_returns_funds() {
+    let mut scenario = run(Some(ShieldedPoolConfig::default()), 300);
+    let (wallet_zec, wallet_zai) = wallets(&scenario);
+    let (shielded_zec, shielded_zai) = scenario.shielded_pool.as_ref().unwrap().totals();
+    assert!(shielded_zec > 0.0 && shielded_zai > 0.0);
+
+    let config = ScenarioConfig {
+        shielded_pool: None,
+        ..scenario.config.clone()
+    };
+    scenario.reconfigure(config);
+    assert!(scenario.shielded_pool.is_none());
+    let (zec, zai) = wallets(&scenario);
+    assert_relative_eq!(zec, wallet_zec + shielded_zec, max_relative = 1e-12);
+    assert_relative_eq!(zai, wallet_zai + shielded_zai, max_relative = 1e-12);
+}
+
+#[test]
+fn test_bank_run_under_shielding() {
+    let peg = |s: &Scenario| {
+        s.metrics
+            .iter()
+            .map(|m| (m.amm_spot_price / m.redemption_price - 1.0).abs())
+            .fold(0.0, f64::max)
+    };
+    let transparent = run(None, 1000);
+    assert!(transparent.metrics.iter().all(|m| m.shielded_zec == 0.0));
+    println!("\n{:<22} max dev  bad debt  shielded ZAI", "");
+    println!(
+        "{:<22} {:>7.2}%  {:>8.2}  {:>12}",
+        "transparent",
+        peg(&transparent) * 100.0,
+        transparent.liquidation_engine.total_bad_debt,
+        "-"
+    );
+    for (name, delay) in [("shielded, 10 blocks", 10), ("shielded, 100 blocks", 100)] {
+        let shielded = run(
+            Some(ShieldedPoolConfig {
+                unshield_delay_blocks: delay,
+                ..ShieldedPoolConfig::default()
+            }),
+            1000,
+        );
+        let last = shielded.metrics.last().unwrap();
+        assert!(last.shielded_zai > 0.0);
+        println!(
+            "{:<22} {:>7.2}%  {:>8.2}  {:>12.0}",
+            name,
+            peg(&shielded) * 100.0,
+            shielded.liquidation_engine.total_bad_debt,
+            last.shielded_zai
+        );
+    }
+}
@@ -25,9 +25,11 @@ fn slow_bleed_prices(blocks: usize) -> Vec<f64> {
 }
 
 fn config_5m() -> ScenarioConfig {
-    let mut config = ScenarioConfig::default();
-    config.amm_initial_zec = 100_000.0;
-    config.amm_initial_zai = 5_000_000.0;
+    let mut config = ScenarioConfig {
+        amm_initial_zec: 100_000.0,
+        amm_initial_zai: 5_000_000.0,
+        ..ScenarioConfig::default()
+    };
     config.cdp_config.min_ratio = 2.0;
     config.cdp_config.twap_window = 240;
     config.controller_config = ControllerConfig::default_tick();
@@ -114,11 +116,12 @@ fn slow_bleed_scenario() {
 
     for &cp in &checkpoints {
         if cp < scenario.metrics.len() {
-            let m = &scenario.metrics[cp];
+            let m = scenario.metrics.row(cp);
             let gap = gaps[cp];
             // Count breaker triggers up to this point
-            let breakers_so_far: u32 = scenario.metrics[..=cp]
-                .iter()
+            let breakers_so_far: u32 = scenario
+                .metrics
+                .rows(..=cp)
                 .map(|m| {
                     m.breaker_actions
                         .iter()
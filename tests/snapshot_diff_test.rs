@@ -0,0 +1,105 @@
+//! Snapshot-based regression diffing.
+//!
+//! Two runs that record a `StateSnapshot` every K blocks can be diffed to
+//! find the earliest block where a code or config change altered the
+//! trajectory.
+
+use zai_sim::agents::{Attacker, AttackerConfig};
+use zai_sim::scenario::{Scenario, ScenarioConfig};
+use zai_sim::scenarios::{add_agents, generate_prices, ScenarioId};
+use zai_sim::snapshot::{
+    diff_snapshots, first_divergence, load_snapshots_csv, save_snapshots_csv, StateSnapshot,
+};
+
+const BLOCKS: usize = 1000;
+const SEED: u64 = 42;
+const INTERVAL: u64 = 50;
+
+fn run(attack_at: Option<u64>) -> Scenario {
+    let config = ScenarioConfig {
+        snapshot_interval: INTERVAL,
+        ..ScenarioConfig::default()
+    };
+
+    let mut scenario = Scenario::new_with_seed(&config, SEED);
+    add_agents(ScenarioId::SteadyState, &mut scenario);
+    for i in 0..10 {
+        scenario
+            .registry
+            .open_vault(
+                &format!("vault_{}", i),
+                (1.6 + i as f64 * 0.2) * 20.0,
+                1000.0,
+                0,
+                &scenario.amm,
+            )
+            .unwrap();
+    }
+    if let Some(block) = attack_at {
+        scenario.attackers.push(Attacker::new(AttackerConfig {
+            attack_capital_zec: 2000.0,
+            hold_blocks: 3,
+            attack_at_block: block,
+            ..AttackerConfig::default()
+        }));
+    }
+
+    let prices = generate_prices(ScenarioId::SteadyState, BLOCKS, SEED);
+    scenario.run(&prices);
+    scenario
+}
+
+#[test]
+fn test_snapshots_recorded_every_interval() {
+    let scenario = run(None);
+    assert_eq!(scenario.snapshots.len(), BLOCKS / INTERVAL as usize);
+    assert!(scenario
+        .snapshots
+        .iter()
+        .all(|s| s.block.is_multiple_of(INTERVAL)));
+
+    let first = &scenario.snapshots[0];
+    assert_eq!(first.cr_histogram.iter().sum::<u32>() as u64, first.vault_count);
+    assert_eq!(first.vault_count, 10);
+}
+
+#[test]
+fn test_identical_runs_do_not_diverge() {
+    let a = run(None);
+    let b = run(None);
+    assert!(diff_snapshots(&a.snapshots, &b.snapshots, 0.0).is_empty());
+}
+
+#[test]
+fn test_pinpoints_earliest_divergence() {
+    let baseline = run(None);
+    let changed = run(Some(520));
+
+    let first = first_divergence(&baseline.snapshots, &changed.snapshots, 1e-9)
+        .expect("an attack must change the trajectory");
+    // Attack lands at block 520; the first snapshot at or after it is 550
+    assert_eq!(first.block, 550);
+    assert!(diff_snapshots(&baseline.snapshots, &changed.snapshots, 1e-9)
+        .iter()
+        .all(|d| d.block >= 550));
+}
+
+#[test]
+fn test_missing_snapshots_are_reported() {
+    let a = run(None);
+    let truncated: Vec<StateSnapshot> = a.snapshots[..5].to_vec();
+
+    let diffs = diff_snapshots(&a.snapshots, &truncated, 0.0);
+    assert_eq!(diffs.len(), a.snapshots.len() - 5);
+    assert!(diffs.iter().all(|d| d.field == "snapshot_missing" && d.left == 1.0));
+}
+
+#[test]
+fn test_csv_round_trip_is_exact() {
+    let scenario = run(None);
+    let path = std::env::temp_dir().join("zai_sim_snapshot_round_trip.csv");
+    save_snapshots_csv(&scenario.snapshots, &path).unwrap();
+    let loaded = load_snapshots_csv(&path).unwrap();
+
+    assert_eq!(loaded, scenario.snapshots);
+}
@@ -0,0 +1,184 @@
+//! SQLite results store.
+//!
+//! Runs, per-block metrics, summaries, verdicts and sweep results go into
+//! one SQLite file that can be queried with SQL.
+#![cfg(feature = "sqlite")]
+
+use std::path::PathBuf;
+
+use zai_sim::output::{compute_summary, sql_value_text, SqlValue, SqliteStore};
+use zai_sim::scenario::ScenarioConfig;
+use zai_sim::scenarios::{run_stress, ScenarioId};
+use zai_sim::sweep::SweepResult;
+
+const BLOCKS: usize = 300;
+
+fn db_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("zai_sim_store_{}.sqlite", name));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+fn store_runs(store: &SqliteStore, ids: &[ScenarioId], seeds: &[u64]) -> Vec<i64> {
+    let config = ScenarioConfig::default();
+    let target = config.initial_redemption_price;
+    let mut run_ids = Vec::new();
+    for &id in ids {
+        for &seed in seeds {
+            let scenario = run_stress(id, &config, BLOCKS, seed);
+            run_ids.push(
+                store
+                    .save_run("test", id.name(), seed, &scenario, target)
+                    .unwrap(),
+            );
+        }
+    }
+    run_ids
+}
+
+fn scalar(store: &SqliteStore, sql: &str) -> SqlValue {
+    store.query(sql).unwrap().rows[0][0].clone()
+}
+
+fn number(value: &SqlValue) -> f64 {
+    match value {
+        SqlValue::Integer(i) => *i as f64,
+        SqlValue::Real(r) => *r,
+        other => panic!("expected a number, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_run_is_stored_with_metrics_summary_and_verdict() {
+    let store = SqliteStore::open(&db_path("single")).unwrap();
+    let run_id = store_runs(&store, &[ScenarioId::BlackThursday], &[42])[0];
+
+    let blocks = scalar(
+        &store,
+        &format!(
+            "SELECT COUNT(*) FROM block_metrics WHERE run_id = {}",
+            run_id
+        ),
+    );
+    assert_eq!(blocks, SqlValue::Integer(BLOCKS as i64));
+
+    let config = ScenarioConfig::default();
+    let scenario = run_stress(ScenarioId::BlackThursday, &config, BLOCKS, 42);
+    let summary = compute_summary(&scenario.metrics, config.initial_redemption_price);
+    let stored = scalar(
+        &store,
+        "SELECT mean_peg_deviation FROM summaries WHERE run_id = 1",
+    );
+    assert_eq!(stored, SqlValue::Real(summary.mean_peg_deviation));
+
+    let verdict = store
+        .query("SELECT scenario, verdict, seed FROM runs")
+        .unwrap();
+    assert_eq!(verdict.columns, ["scenario", "verdict", "seed"]);
+    assert_eq!(verdict.rows[0][0], SqlValue::Text("black_thursday".into()));
+    assert!(matches!(verdict.rows[0][1], SqlValue::Text(_)));
+    assert_eq!(verdict.rows[0][2], SqlValue::Integer(42));
+
+    let criteria = scalar(&store, "SELECT COUNT(*) FROM verdicts");
+    assert!(number(&criteria) > 0.0);
+}
+
+#[test]
+fn test_config_round_trips_as_json() {
+    let store = SqliteStore::open(&db_path("config")).unwrap();
+    store_runs(&store, &[ScenarioId::SteadyState], &[1]);
+
+    let json = match scalar(&store, "SELECT config FROM runs") {
+        SqlValue::Text(s) => s,
+        other => panic!("config stored as {:?}", other),
+    };
+    let config: ScenarioConfig = serde_json::from_str(&json).unwrap();
+    assert_eq!(
+        config.amm_initial_zai,
+        ScenarioConfig::default().amm_initial_zai
+    );
+}
+
+#[test]
+fn test_aggregate_by_scenario() {
+    let store = SqliteStore::open(&db_path("aggregate")).unwrap();
+    store_runs(
+        &store,
+        &[ScenarioId::SteadyState, ScenarioId::BlackThursday],
+        &[1, 2, 3],
+    );
+
+    let result = store.aggregate("max_peg_deviation", "scenario").unwrap();
+    assert_eq!(result.columns, ["scenario", "runs", "mean", "min", "max"]);
+    assert_eq!(result.rows.len(), 2);
+    for row in &result.rows {
+        assert_eq!(row[1], SqlValue::Integer(3));
+        let (mean, min, max) = (number(&row[2]), number(&row[3]), number(&row[4]));
+        assert!(min <= mean && mean <= max);
+    }
+    // Crash deviates further from the peg than steady state
+    assert!(number(&result.rows[0][2]) > number(&result.rows[1][2]));
+}
+
+#[test]
+fn test_query_returns_null_text_and_blob_cells() {
+    let store = SqliteStore::open(&db_path("cells")).unwrap();
+    let result = store
+        .query("SELECT NULL AS n, CAST(NULL AS TEXT) AS t, X'00FF' AS b")
+        .unwrap();
+    assert_eq!(
+        result.rows,
+        vec![vec![
+            SqlValue::Null,
+            SqlValue::Null,
+            SqlValue::Blob(vec![0, 255])
+        ]]
+    );
+    let cells: Vec<String> = result.rows[0].iter().map(sql_value_text).collect();
+    assert_eq!(cells, ["NULL", "NULL", "X'00FF'"]);
+}
+
+#[test]
+fn test_aggregate_rejects_unknown_columns() {
+    let store = SqliteStore::open(&db_path("reject")).unwrap();
+    assert!(store.aggregate("no_such_metric", "scenario").is_err());
+    assert!(store
+        .aggregate("mean_peg_deviation", "scenario; DROP TABLE runs")
+        .is_err());
+    assert!(store.query("SELECT * FROM no_such_table").is_err());
+}
+
+#[test]
+fn test_sweep_results_and_reopen() {
+    let path = db_path("sweep");
+    {
+        let store = SqliteStore::open(&path).unwrap();
+        let results: Vec<SweepResult> = [(1.5, 0.2), (2.0, 0.1)]
+            .iter()
+            .map(|&(ratio, score)| SweepResult {
+                params: vec![("min_ratio".to_string(), ratio)],
+                scores: vec![
+                    (ScenarioId::SteadyState, score),
+                    (ScenarioId::FlashCrash, score * 2.0),
+                ],
+                overall_score: score,
+            })
+            .collect();
+        store.save_sweep_results("mc", &results).unwrap();
+    }
+
+    // Schema creation is idempotent and data persists across opens
+    let store = SqliteStore::open(&path).unwrap();
+    let best = store
+        .query(
+            "SELECT p.value FROM sweep_results r \
+             JOIN sweep_params p ON p.result_id = r.id \
+             WHERE p.name = 'min_ratio' ORDER BY r.overall_score LIMIT 1",
+        )
+        .unwrap();
+    assert_eq!(best.rows, vec![vec![SqlValue::Real(2.0)]]);
+    assert_eq!(
+        scalar(&store, "SELECT COUNT(*) FROM sweep_scores"),
+        SqlValue::Integer(4)
+    );
+}
@@ -104,9 +104,11 @@ fn configs() -> Vec<ConfigDef> {
 }
 
 fn make_config(def: &ConfigDef) -> ScenarioConfig {
-    let mut config = ScenarioConfig::default();
-    config.amm_initial_zec = def.amm_zec;
-    config.amm_initial_zai = def.amm_zai;
+    let mut config = ScenarioConfig {
+        amm_initial_zec: def.amm_zec,
+        amm_initial_zai: def.amm_zai,
+        ..ScenarioConfig::default()
+    };
     config.cdp_config.min_ratio = def.min_ratio;
     config.cdp_config.twap_window = def.twap_window;
     config.controller_config = ControllerConfig::default_tick();
@@ -0,0 +1,91 @@
+//! Tail-risk summary metrics.
+//!
+//! Max drawdown of the AMM price, blocks under peg, CVaR of per-block peg
+//! deviation and the collateral ratio's peak-to-trough fall, computed into
+//! `SummaryMetrics` so reviewers don't have to rederive them from CSVs.
+
+use zai_sim::agents::{CdpHolder, CdpHolderConfig};
+use zai_sim::output::{compute_summary, cvar, max_drawdown, save_metrics_json};
+use zai_sim::report::generate_markdown_summary;
+use zai_sim::scenario::{MetricsStore, ScenarioConfig};
+use zai_sim::scenarios::{run_stress, run_stress_with, ScenarioId};
+
+#[test]
+fn test_max_drawdown() {
+    // Peak 12, later trough 3
+    assert_eq!(max_drawdown(&[10.0, 12.0, 6.0, 9.0, 3.0, 11.0]), 0.75);
+    // The deeper of two drawdowns, relative to its own peak
+    assert_eq!(max_drawdown(&[4.0, 3.0, 10.0, 6.0]), 0.4);
+    assert_eq!(max_drawdown(&[1.0, 2.0, 3.0]), 0.0);
+    assert_eq!(max_drawdown(&[]), 0.0);
+}
+
+#[test]
+fn test_cvar() {
+    let values: Vec<f64> = (1..=100).map(|i| i as f64 / 100.0).collect();
+    assert!((cvar(&values, 0.95) - 0.98).abs() < 1e-12);
+    assert_eq!(cvar(&values, 0.99), 1.0);
+    // Always at least the single worst value
+    assert_eq!(cvar(&[0.2, 0.5, 0.1], 0.99), 0.5);
+    assert_eq!(cvar(&[], 0.95), 0.0);
+}
+
+#[test]
+fn test_tail_risk_in_summary() {
+    let config = ScenarioConfig::default();
+    // Stress scenarios come without vaults; the crash needs some to draw
+    // the collateral ratio down
+    let scenario = run_stress_with(ScenarioId::BlackThursday, &config, 500, 42, |s| {
+        for _ in 0..5 {
+            s.cdp_holders.push(CdpHolder::new(CdpHolderConfig::default()));
+        }
+    });
+    let summary = compute_summary(&scenario.metrics, 50.0);
+
+    let prices: Vec<f64> = scenario.metrics.iter().map(|m| m.amm_spot_price).collect();
+    assert_eq!(summary.max_drawdown, max_drawdown(&prices));
+    assert!(summary.max_drawdown > 0.0 && summary.max_drawdown < 1.0);
+    assert_eq!(
+        summary.under_peg_blocks,
+        prices.iter().filter(|p| **p < 50.0).count() as u64
+    );
+    assert!(summary.mean_peg_deviation <= summary.cvar_95_deviation);
+    assert!(summary.cvar_95_deviation <= summary.cvar_99_deviation);
+    assert!(summary.cvar_99_deviation <= summary.max_peg_deviation);
+    assert!(summary.collateral_ratio_drawdown > 0.0);
+
+    let empty = compute_summary(&MetricsStore::default(), 50.0);
+    assert_eq!((empty.max_drawdown, empty.under_peg_blocks), (0.0, 0));
+
+    let md = generate_markdown_summary(&scenario.metrics, &config, "black_thursday", 50.0);
+    assert!(md.contains(&format!(
+        "| AMM price max drawdown | {:.2}% |",
+        summary.max_drawdown * 100.0
+    )));
+    assert!(md.contains(&format!(
+        "| Blocks under peg | {} |",
+        summary.under_peg_blocks
+    )));
+}
+
+#[test]
+fn test_tail_risk_in_metrics_json() {
+    let scenario = run_stress(ScenarioId::FlashCrash, &ScenarioConfig::default(), 300, 42);
+    let summary = compute_summary(&scenario.metrics, 50.0);
+    let path = std::env::temp_dir()
+        .join(format!("zai_sim_tail_risk_{}", std::process::id()))
+        .join("metrics.json");
+    save_metrics_json(&summary, &path).unwrap();
+
+    let json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    let tail = &json["tail_risk"];
+    assert_eq!(tail["under_peg_blocks"], summary.under_peg_blocks);
+    assert!((tail["cvar_99_deviation"].as_f64().unwrap() - summary.cvar_99_deviation).abs() < 1e-6);
+    assert!(
+        (tail["collateral_ratio_drawdown"].as_f64().unwrap() - summary.collateral_ratio_drawdown)
+            .abs()
+            < 1e-6
+    );
+    let _ = std::fs::remove_dir_all(path.parent().unwrap());
+}
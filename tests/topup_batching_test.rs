@@ -0,0 +1,130 @@
+//! Gas-aware vault top-ups.
+//!
+//! Holders pay a per-transaction fee and batch small top-ups until they are
+//! worth it, so dust-sized defensive actions no longer make small holders
+//! look better defended than they are.
+
+use zai_sim::agents::{AgentAction, CdpHolder, CdpHolderConfig, TopUpPolicy};
+use zai_sim::amm::Amm;
+use zai_sim::cdp::{CdpConfig, VaultRegistry};
+use zai_sim::scenario::{Scenario, ScenarioConfig};
+use zai_sim::scenarios::{add_agents, generate_prices, ScenarioId};
+
+/// Vault at CR 2.5 (50 ZEC collateral, 1000 ZAI debt, price 50) that wants
+/// `target` and acts below `threshold`.
+fn setup(target: f64, threshold: f64, policy: TopUpPolicy) -> (CdpHolder, VaultRegistry, Amm) {
+    let amm = Amm::new(10_000.0, 500_000.0, 0.003);
+    // No fees, so the actions below are exact
+    let mut registry = VaultRegistry::new(CdpConfig {
+        stability_fee_rate: 0.0,
+        ..CdpConfig::default()
+    });
+    let mut holder = CdpHolder::new(CdpHolderConfig {
+        target_ratio: target,
+        action_threshold_ratio: threshold,
+        reserve_zec: 100.0,
+        initial_collateral: 50.0,
+        initial_debt: 1000.0,
+    })
+    .with_topup_policy(policy);
+    holder.open_vault(&mut registry, &amm, 0).unwrap();
+    (holder, registry, amm)
+}
+
+fn gas_aware() -> TopUpPolicy {
+    TopUpPolicy {
+        tx_fee_zec: 0.05,
+        min_topup_zec: 1.0,
+        max_fee_fraction: 0.1,
+        emergency_ratio: 0.0,
+    }
+}
+
+#[test]
+fn test_default_policy_tops_up_exact_need() {
+    let (mut holder, mut registry, amm) = setup(2.52, 2.51, TopUpPolicy::default());
+    let action = holder.act(&mut registry, &amm, 1);
+
+    assert!(matches!(action, AgentAction::CdpAction { .. }));
+    assert_eq!(holder.topups, 1);
+    assert!((holder.reserve_zec - (100.0 - 0.4)).abs() < 1e-6);
+    assert_eq!(holder.fees_paid_zec, 0.0);
+}
+
+#[test]
+fn test_dust_need_is_deferred() {
+    // Needs 0.4 ZEC: below the 1 ZEC minimum and the fee is >10% of it
+    let (mut holder, mut registry, amm) = setup(2.52, 2.51, gas_aware());
+    holder.act(&mut registry, &amm, 1);
+
+    assert_eq!(holder.topups, 0);
+    assert_eq!(holder.deferred_topups, 1);
+    assert_eq!(holder.reserve_zec, 100.0);
+}
+
+#[test]
+fn test_economic_need_pays_fee() {
+    // Needs 14 ZEC: well above the minimum, fee is a small fraction
+    let (mut holder, mut registry, amm) = setup(3.2, 3.0, gas_aware());
+    holder.act(&mut registry, &amm, 1);
+
+    assert_eq!(holder.topups, 1);
+    assert!((holder.fees_paid_zec - 0.05).abs() < 1e-12);
+    assert!((holder.reserve_zec - (100.0 - 14.0 - 0.05)).abs() < 1e-6);
+}
+
+#[test]
+fn test_emergency_tops_up_at_minimum_size() {
+    let policy = TopUpPolicy {
+        emergency_ratio: 2.6,
+        ..gas_aware()
+    };
+    let (mut holder, mut registry, amm) = setup(2.52, 2.51, policy);
+    holder.act(&mut registry, &amm, 1);
+
+    // Rounded up from the 0.4 ZEC need to the 1 ZEC minimum
+    assert_eq!(holder.topups, 1);
+    let vault = registry.get_vault(holder.vault_id.unwrap()).unwrap();
+    assert!((vault.collateral_zec - 51.0).abs() < 1e-9);
+}
+
+fn run_small_holders(policy: TopUpPolicy) -> Scenario {
+    let mut scenario = Scenario::new_with_seed(&ScenarioConfig::default(), 42);
+    add_agents(ScenarioId::SustainedBear, &mut scenario);
+    for _ in 0..20 {
+        scenario.cdp_holders.push(
+            CdpHolder::new(CdpHolderConfig {
+                target_ratio: 2.0,
+                action_threshold_ratio: 1.9,
+                reserve_zec: 10.0,
+                initial_collateral: 5.0,
+                initial_debt: 120.0,
+            })
+            .with_topup_policy(policy.clone()),
+        );
+    }
+    scenario.run(&generate_prices(ScenarioId::SustainedBear, 1000, 42));
+    scenario
+}
+
+#[test]
+fn test_small_holders_defend_less_with_fees() {
+    let frictionless = run_small_holders(TopUpPolicy::default());
+    let gas = run_small_holders(gas_aware());
+
+    let topups = |s: &Scenario| s.cdp_holders.iter().map(|h| h.topups).sum::<u32>();
+    let fees = |s: &Scenario| s.cdp_holders.iter().map(|h| h.fees_paid_zec).sum::<f64>();
+    let deferred = |s: &Scenario| s.cdp_holders.iter().map(|h| h.deferred_topups).sum::<u32>();
+    println!(
+        "top-ups {} -> {}, deferred {}, fees {:.2} ZEC",
+        topups(&frictionless),
+        topups(&gas),
+        deferred(&gas),
+        fees(&gas)
+    );
+
+    assert!(topups(&frictionless) > 0);
+    assert!(topups(&gas) < topups(&frictionless));
+    assert!(deferred(&gas) > 0);
+    assert!(fees(&gas) > 0.0);
+}
@@ -0,0 +1,201 @@
+//! Treasury surplus buffer and debt auctions.
+//!
+//! Stability fees and liquidation penalties accumulate in the treasury,
+//! bad debt is absorbed from that buffer first, and any shortfall is
+//! recapitalized through governance-token debt auctions.
+
+use zai_sim::amm::Amm;
+use zai_sim::cdp::{CdpConfig, VaultRegistry};
+use zai_sim::liquidation::{LiquidationConfig, LiquidationEngine};
+use zai_sim::scenario::{Scenario, ScenarioConfig};
+use zai_sim::scenarios::{add_agents, generate_prices, ScenarioId};
+use zai_sim::treasury::{DebtAuctionMode, Treasury, TreasuryConfig};
+
+const BLOCKS: usize = 1000;
+const SEED: u64 = 42;
+const TARGET_PRICE: f64 = 50.0;
+
+fn setup_amm() -> Amm {
+    let mut amm = Amm::new(10000.0, 500000.0, 0.003);
+    for b in 1..=50 {
+        amm.record_price(b);
+    }
+    amm
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+// Surplus buffer
+// ═══════════════════════════════════════════════════════════════════════
+
+#[test]
+fn test_surplus_absorbs_bad_debt_first() {
+    let mut treasury = Treasury::new(TreasuryConfig::default());
+    treasury.deposit_surplus(1000.0);
+
+    assert_eq!(treasury.absorb_bad_debt(400.0), 0.0);
+    assert!((treasury.balance_zai - 600.0).abs() < 1e-9);
+
+    let shortfall = treasury.absorb_bad_debt(1000.0);
+    assert!((shortfall - 400.0).abs() < 1e-9);
+    assert_eq!(treasury.balance_zai, 0.0);
+    assert!((treasury.total_bad_debt_absorbed - 1000.0).abs() < 1e-9);
+    assert!((treasury.uncovered_bad_debt - 400.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_sync_collects_fees_and_penalties_once() {
+    let amm = setup_amm();
+    let mut registry = VaultRegistry::new(CdpConfig {
+        stability_fee_rate: 0.10,
+        ..CdpConfig::default()
+    });
+    let mut engine = LiquidationEngine::new(LiquidationConfig::default());
+    let id = registry.open_vault("v", 100.0, 2000.0, 50, &amm).unwrap();
+
+    registry.accrue_fees(id, 50_050).unwrap();
+    engine.total_penalties_collected = 25.0;
+
+    let mut treasury = Treasury::new(TreasuryConfig::default());
+    treasury.sync(&registry, &engine, 0.0);
+    let fees = registry.total_fees_accrued;
+    assert!(fees > 0.0);
+    assert!((treasury.balance_zai - (fees + 25.0)).abs() < 1e-9);
+
+    // A second sync with no new income changes nothing
+    treasury.sync(&registry, &engine, 0.0);
+    assert!((treasury.balance_zai - (fees + 25.0)).abs() < 1e-9);
+
+    // Fees routed to LPs bypass the treasury
+    registry.accrue_fees(id, 100_050).unwrap();
+    let new_fees = registry.total_fees_accrued - fees;
+    treasury.sync(&registry, &engine, new_fees);
+    assert!((treasury.total_fees_collected - fees).abs() < 1e-9);
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+// Debt auctions
+// ═══════════════════════════════════════════════════════════════════════
+
+#[test]
+fn test_debt_auction_covers_shortfall_in_lots() {
+    let mut amm = setup_amm();
+    let mut treasury = Treasury::new(TreasuryConfig {
+        auction_lot_zai: 1000.0,
+        auction_interval_blocks: 10,
+        ..TreasuryConfig::default()
+    });
+    treasury.absorb_bad_debt(2500.0);
+
+    let a = treasury.run_debt_auction(&mut amm, 100).unwrap();
+    assert!((a.zai_raised - 1000.0).abs() < 1e-9);
+    // 1000 ZAI at 1000 ZAI/token with a 5% discount
+    assert!((a.gov_tokens_minted - 1000.0 / 950.0).abs() < 1e-9);
+    assert_eq!(a.zec_sold_on_amm, 0.0);
+
+    // Interval not yet elapsed
+    assert!(treasury.run_debt_auction(&mut amm, 105).is_none());
+
+    treasury.run_debt_auction(&mut amm, 110).unwrap();
+    let last = treasury.run_debt_auction(&mut amm, 120).unwrap();
+    assert!((last.zai_raised - 500.0).abs() < 1e-9);
+    assert!(treasury.uncovered_bad_debt.abs() < 1e-9);
+    assert!(treasury.run_debt_auction(&mut amm, 130).is_none());
+    assert_eq!(treasury.auctions.len(), 3);
+}
+
+#[test]
+fn test_disabled_auctions_leave_debt_uncovered() {
+    let mut amm = setup_amm();
+    let mut treasury = Treasury::new(TreasuryConfig {
+        auction_mode: DebtAuctionMode::Disabled,
+        ..TreasuryConfig::default()
+    });
+    treasury.absorb_bad_debt(500.0);
+    assert!(treasury.run_debt_auction(&mut amm, 100).is_none());
+    assert!((treasury.uncovered_bad_debt - 500.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_bidders_buying_on_amm_support_zai() {
+    let mut amm = setup_amm();
+    let spot_before = amm.spot_price();
+    let mut treasury = Treasury::new(TreasuryConfig {
+        bidders_buy_zai_on_amm: true,
+        ..TreasuryConfig::default()
+    });
+    treasury.absorb_bad_debt(2000.0);
+
+    let a = treasury.run_debt_auction(&mut amm, 100).unwrap();
+    assert!((a.zai_raised - 2000.0).abs() < 1e-6);
+    assert!(a.zec_sold_on_amm > 0.0);
+    // Bidders sell ZEC for ZAI: fewer ZAI per ZEC afterwards
+    assert!(amm.spot_price() < spot_before);
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+// Scenario integration
+// ═══════════════════════════════════════════════════════════════════════
+
+fn run_crash(auction_mode: DebtAuctionMode) -> Scenario {
+    let mut config = ScenarioConfig {
+        use_amm_liquidation: true,
+        ..ScenarioConfig::default()
+    };
+    config.treasury_config.auction_mode = auction_mode;
+
+    let mut scenario = Scenario::new_with_seed(&config, SEED);
+    add_agents(ScenarioId::BlackThursday, &mut scenario);
+    for i in 0..30 {
+        let cr = config.cdp_config.min_ratio + 0.05 + i as f64 * 0.02;
+        scenario
+            .registry
+            .open_vault(
+                &format!("vault_{}", i),
+                cr * 5000.0 / TARGET_PRICE,
+                5000.0,
+                0,
+                &scenario.amm,
+            )
+            .unwrap();
+    }
+
+    let prices = generate_prices(ScenarioId::BlackThursday, BLOCKS, SEED);
+    scenario.run(&prices);
+    scenario
+}
+
+#[test]
+fn test_treasury_accounts_for_all_bad_debt() {
+    for mode in [
+        DebtAuctionMode::Disabled,
+        TreasuryConfig::default().auction_mode,
+    ] {
+        let scenario = run_crash(mode.clone());
+        let t = &scenario.treasury;
+        let total_bad_debt = scenario.liquidation_engine.total_bad_debt;
+
+        let accounted = t.total_bad_debt_absorbed + t.uncovered_bad_debt + t.total_auction_proceeds;
+        assert!(
+            (accounted - total_bad_debt).abs() < 1e-6,
+            "{:?}: absorbed + uncovered + auctioned must equal bad debt",
+            mode
+        );
+        let income = t.total_fees_collected + t.total_penalties_collected;
+        assert!((t.balance_zai - (income - t.total_bad_debt_absorbed)).abs() < 1e-6);
+
+        let last = scenario.metrics.last().unwrap();
+        assert!((last.treasury_balance - t.balance_zai).abs() < 1e-9);
+        assert!((last.uncovered_bad_debt - t.uncovered_bad_debt).abs() < 1e-9);
+
+        println!(
+            "  {:?}: bad_debt={:.0} absorbed={:.0} auctioned={:.0} uncovered={:.0} penalties={:.0} tokens={:.2}",
+            mode,
+            total_bad_debt,
+            t.total_bad_debt_absorbed,
+            t.total_auction_proceeds,
+            t.uncovered_bad_debt,
+            t.total_penalties_collected,
+            t.total_gov_tokens_minted,
+        );
+    }
+}
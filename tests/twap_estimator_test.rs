@@ -0,0 +1,125 @@
+//! Median, geometric and volume-weighted TWAP estimators.
+//!
+//! The arithmetic TWAP moves in proportion to how far and how long a
+//! manipulator pushes the spot price. These tests push the AMM with a short
+//! spike and compare how far each estimator follows it, and what moving it
+//! costs the attacker in the TwapManipulation scenario.
+
+use approx::assert_relative_eq;
+use zai_sim::amm::{Amm, TwapKind};
+use zai_sim::scenario::ScenarioConfig;
+use zai_sim::scenarios::{run_stress, ScenarioId};
+
+const KINDS: [TwapKind; 4] = [
+    TwapKind::Arithmetic,
+    TwapKind::Median,
+    TwapKind::Geometric,
+    TwapKind::VolumeWeighted,
+];
+
+/// AMM at $50 for 45 blocks, then a ZEC dump held for 3 blocks and bought
+/// back, through block 50.
+fn spiked_amm() -> Amm {
+    let mut amm = Amm::new(10000.0, 500000.0, 0.003);
+    for b in 1..=45 {
+        amm.record_price(b);
+    }
+    let zai = amm.swap_zec_for_zai(2000.0, 46).unwrap();
+    amm.swap_zai_for_zec(zai, 49).unwrap();
+    amm.record_price(50);
+    amm
+}
+
+#[test]
+fn test_flat_price_agrees() {
+    let mut amm = Amm::new(10000.0, 500000.0, 0.003);
+    for b in 1..=60 {
+        amm.record_price(b);
+    }
+    for kind in KINDS {
+        assert_relative_eq!(amm.twap(48, kind), 50.0, epsilon = 1e-9);
+    }
+}
+
+#[test]
+fn test_short_spike() {
+    let amm = spiked_amm();
+    let arithmetic = amm.twap(48, TwapKind::Arithmetic);
+    let median = amm.twap(48, TwapKind::Median);
+    let geometric = amm.twap(48, TwapKind::Geometric);
+    let vwap = amm.twap(48, TwapKind::VolumeWeighted);
+
+    // 3 of 48 blocks at ~$34.7 drag the mean down by about 1 dollar
+    assert!(arithmetic < 49.5 && arithmetic > 48.5, "{}", arithmetic);
+    // The median ignores a spike shorter than half the window
+    assert_relative_eq!(median, 50.0, epsilon = 1e-9);
+    // AM-GM: the geometric mean is never above the arithmetic one
+    assert!(geometric < arithmetic);
+    // Only the attack swaps traded, so they alone set the VWAP
+    assert!(vwap < arithmetic, "{}", vwap);
+}
+
+#[test]
+fn test_vwap_without_volume_falls_back() {
+    let mut amm = Amm::new(10000.0, 500000.0, 0.003);
+    for b in 1..=10 {
+        amm.record_price(b);
+    }
+    assert_eq!(
+        amm.twap(5, TwapKind::VolumeWeighted),
+        amm.twap(5, TwapKind::Arithmetic)
+    );
+}
+
+/// Largest deviation of the recorded TWAP from $50, as a fraction.
+fn max_twap_deviation(kind: TwapKind) -> (f64, f64) {
+    let mut config = ScenarioConfig::default();
+    config.cdp_config.twap_kind = kind;
+    let scenario = run_stress(ScenarioId::TwapManipulation, &config, 1000, 42);
+
+    let deviation = scenario
+        .metrics
+        .iter()
+        .map(|m| (m.twap_price - 50.0).abs() / 50.0)
+        .fold(0.0, f64::max);
+    let attacker = &scenario.attackers[0];
+    let cost_zec = attacker.config.attack_capital_zec
+        - attacker.zec_balance
+        - attacker.zai_balance / scenario.amm.spot_price();
+    (deviation, cost_zec)
+}
+
+#[test]
+fn test_manipulation_cost() {
+    println!(
+        "\n{:<16} {:>12} {:>12} {:>16}",
+        "Estimator", "Max dev %", "Cost ZEC", "ZEC per 1% move"
+    );
+    let results: Vec<(TwapKind, f64, f64)> = KINDS
+        .iter()
+        .map(|&kind| {
+            let (deviation, cost) = max_twap_deviation(kind);
+            println!(
+                "{:<16} {:>12.3} {:>12.2} {:>16.2}",
+                format!("{:?}", kind),
+                deviation * 100.0,
+                cost,
+                cost / (deviation * 100.0)
+            );
+            (kind, deviation, cost)
+        })
+        .collect();
+
+    let (_, arithmetic_dev, arithmetic_cost) = results[0];
+    let (_, median_dev, median_cost) = results[1];
+    // Valuation does not feed back into trading here, so the attack costs
+    // the same; only how far each estimator follows it differs
+    assert_relative_eq!(median_cost, arithmetic_cost, epsilon = 1e-9);
+    assert!(arithmetic_cost > 0.0);
+    assert!(
+        median_dev < arithmetic_dev,
+        "median {:.4} vs arithmetic {:.4}",
+        median_dev,
+        arithmetic_dev
+    );
+}
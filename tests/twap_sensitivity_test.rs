@@ -25,9 +25,11 @@ const BLOCKS: usize = 1000;
 const SEED: u64 = 42;
 
 fn base_config(twap_window: u64, amm_zec: f64, amm_zai: f64) -> ScenarioConfig {
-    let mut config = ScenarioConfig::default();
-    config.amm_initial_zec = amm_zec;
-    config.amm_initial_zai = amm_zai;
+    let mut config = ScenarioConfig {
+        amm_initial_zec: amm_zec,
+        amm_initial_zai: amm_zai,
+        ..ScenarioConfig::default()
+    };
     config.cdp_config.min_ratio = 2.0;
     config.cdp_config.twap_window = twap_window;
     config.controller_config = ControllerConfig::default_tick();
@@ -123,11 +125,11 @@ fn analyze(
 
     // Death spiral detection
     let death_spiral = if scenario.metrics.len() > 200 {
-        let initial = scenario.metrics[0].amm_spot_price;
+        let initial = scenario.metrics.amm_spot_price[0];
         let final_price = scenario.metrics.last().unwrap().amm_spot_price;
         let dropped = final_price < initial * 0.1;
-        let last_100 = &scenario.metrics[scenario.metrics.len() - 100..];
-        let no_recovery = last_100.iter().all(|m| m.amm_spot_price < initial * 0.15);
+        let last_100 = &scenario.metrics.amm_spot_price[scenario.metrics.len() - 100..];
+        let no_recovery = last_100.iter().all(|p| *p < initial * 0.15);
         dropped && no_recovery
     } else {
         false
@@ -367,7 +369,7 @@ fn twap_window_sensitivity_sweep() {
                 } else if r.total_bad_debt > 0.0 {
                     format!("${:.0}bd", r.total_bad_debt)
                 } else {
-                    format!("{}", r.verdict)
+                    r.verdict.to_string()
                 };
                 row.push_str(&format!(" {:>16}", cell));
             }
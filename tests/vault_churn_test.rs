@@ -0,0 +1,161 @@
+//! Stochastic vault arrivals and closures.
+//!
+//! With a churn process new holders open vaults and open ones close at
+//! rates driven by the price trend and the stability fee, so the vault book
+//! holds its size on a flat market, grows in a rally and winds down in a
+//! slide.
+
+use zai_sim::agents::{CdpHolder, CdpHolderConfig};
+use zai_sim::scenario::{Scenario, ScenarioConfig};
+use zai_sim::scenarios::{add_agents, generate_prices, ScenarioId};
+use zai_sim::vault_churn::{VaultChurn, VaultChurnConfig};
+
+#[test]
+fn test_rates_follow_trend_and_fee() {
+    let base = VaultChurnConfig {
+        trend_sensitivity: 10.0,
+        ..VaultChurnConfig::default()
+    };
+    let churn = VaultChurn::new(base.clone(), 42);
+    assert!((churn.arrival_rate(0.0, 0.02, 0) - base.arrival_rate).abs() < 1e-15);
+    assert!((churn.closure_rate(0.0, 0.02) - base.closure_rate).abs() < 1e-15);
+
+    // A 10% rally: arrivals up by e, closures down by e
+    let e = 1.0f64.exp();
+    assert!((churn.arrival_rate(0.1, 0.02, 0) - base.arrival_rate * e).abs() < 1e-12);
+    assert!((churn.closure_rate(0.1, 0.02) - base.closure_rate / e).abs() < 1e-12);
+
+    // A fee 2 points over the reference works the other way
+    assert!((churn.arrival_rate(0.0, 0.04, 0) - base.arrival_rate / e).abs() < 1e-12);
+    assert!((churn.closure_rate(0.0, 0.04) - base.closure_rate * e).abs() < 1e-12);
+
+    // Closure is a probability
+    assert_eq!(churn.closure_rate(-10.0, 0.02), 1.0);
+
+    // A large book draws arrivals to replace the vaults expected to close
+    let book = 1000;
+    let replacement = base.closure_rate * book as f64;
+    assert!(replacement > base.arrival_rate);
+    assert!((churn.arrival_rate(0.0, 0.02, book) - replacement).abs() < 1e-12);
+    assert!((churn.arrival_rate(0.1, 0.02, book) - replacement * e).abs() < 1e-12);
+}
+
+#[test]
+fn test_arrivals_are_poisson_with_sampled_vaults() {
+    let config = VaultChurnConfig {
+        arrival_rate: 0.5,
+        ..VaultChurnConfig::default()
+    };
+    let mut churn = VaultChurn::new(config.clone(), 7);
+    let mut arrivals = Vec::new();
+    for _ in 0..2000 {
+        arrivals.extend(churn.step(50.0, 0.02, 0));
+    }
+    // 1000 expected, standard deviation about 32
+    assert!(
+        arrivals.len() > 850 && arrivals.len() < 1150,
+        "{}",
+        arrivals.len()
+    );
+    for holder in &arrivals {
+        let ratio = holder.initial_collateral * 50.0 / holder.initial_debt;
+        assert!((2.0..4.0).contains(&ratio), "{}", ratio);
+        assert!(holder.initial_debt > 0.0);
+        assert_eq!(holder.target_ratio, config.new_holder.target_ratio);
+    }
+    let mut debts: Vec<f64> = arrivals.iter().map(|h| h.initial_debt).collect();
+    debts.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let median = debts[debts.len() / 2];
+    assert!(median > 900.0 && median < 1100.0, "{}", median);
+
+    // Flat price: no trend, closure chance at the base rate
+    assert_eq!(churn.average_price, Some(50.0));
+    assert!((churn.closure_probability - config.closure_rate).abs() < 1e-15);
+
+    // Same seed, same draws
+    let draws = |seed| {
+        let mut churn = VaultChurn::new(config.clone(), seed);
+        (0..100)
+            .flat_map(|_| churn.step(50.0, 0.02, 0))
+            .map(|h| h.initial_debt)
+            .collect::<Vec<f64>>()
+    };
+    assert_eq!(draws(7), draws(7));
+    assert_ne!(draws(7), draws(8));
+}
+
+/// CDP holders each run starts with.
+const HOLDERS: usize = 20;
+
+fn run_churn(id: ScenarioId) -> Scenario {
+    let config = ScenarioConfig {
+        vault_churn: Some(VaultChurnConfig {
+            arrival_rate: 0.02,
+            closure_rate: 0.002,
+            ..VaultChurnConfig::default()
+        }),
+        ..ScenarioConfig::default()
+    };
+    let mut scenario = Scenario::new_with_seed(&config, 42);
+    add_agents(id, &mut scenario);
+    for _ in 0..HOLDERS {
+        scenario
+            .cdp_holders
+            .push(CdpHolder::new(CdpHolderConfig::default()));
+    }
+    scenario.run(&generate_prices(id, 1000, 42));
+    scenario
+}
+
+#[test]
+fn test_book_grows_in_a_rally_and_shrinks_in_a_slide() {
+    let bull = run_churn(ScenarioId::BullMarket);
+    let bear = run_churn(ScenarioId::SustainedBear);
+    let churn = |s: &Scenario| {
+        let c = s.vault_churn.as_ref().unwrap();
+        (c.total_opened, c.total_closed)
+    };
+    let open_vaults = |s: &Scenario| {
+        s.cdp_holders
+            .iter()
+            .filter(|h| h.vault_id.is_some())
+            .count()
+    };
+    let (bull_opened, bull_closed) = churn(&bull);
+    let (bear_opened, bear_closed) = churn(&bear);
+    assert!(
+        bull_opened > bear_opened,
+        "{} vs {}",
+        bull_opened,
+        bear_opened
+    );
+    assert!(bear_closed > 0);
+
+    // The rally ends with more vaults than it started with, the slide
+    // with fewer
+    assert!(open_vaults(&bull) > HOLDERS);
+    assert!(open_vaults(&bear) < HOLDERS);
+
+    for (scenario, opened, closed) in [
+        (&bull, bull_opened, bull_closed),
+        (&bear, bear_opened, bear_closed),
+    ] {
+        let arrivals: usize = scenario.metrics.iter().map(|m| m.vault_arrivals).sum();
+        let closures: usize = scenario.metrics.iter().map(|m| m.vault_closures).sum();
+        assert_eq!(arrivals as u64, opened);
+        assert_eq!(closures as u64, closed);
+    }
+
+    // Without the process the book only changes through liquidation
+    let mut fixed = Scenario::new_with_seed(&ScenarioConfig::default(), 42);
+    add_agents(ScenarioId::BullMarket, &mut fixed);
+    for _ in 0..HOLDERS {
+        fixed
+            .cdp_holders
+            .push(CdpHolder::new(CdpHolderConfig::default()));
+    }
+    fixed.run(&generate_prices(ScenarioId::BullMarket, 1000, 42));
+    assert_eq!(fixed.cdp_holders.len(), HOLDERS);
+    assert_eq!(open_vaults(&fixed), HOLDERS);
+    assert!(fixed.metrics.iter().all(|m| m.vault_arrivals == 0));
+}
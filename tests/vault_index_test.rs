@@ -0,0 +1,152 @@
+//! Indexed liquidatable-vault lookup.
+//!
+//! The registry keeps vaults ordered by parity price (debt / collateral), so
+//! the vaults under the minimum ratio at any price are a range query. These
+//! tests check the indexed scans against a full pass over every vault as
+//! vaults are opened, changed, liquidated and reloaded.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use zai_sim::amm::Amm;
+use zai_sim::cdp::{CdpConfig, VaultRegistry};
+use zai_sim::liquidation::{LiquidationConfig, LiquidationEngine};
+
+const PRICES: [f64; 7] = [10.0, 25.0, 33.3, 40.0, 50.0, 75.0, 200.0];
+
+fn amm() -> Amm {
+    let mut amm = Amm::new(100_000.0, 5_000_000.0, 0.003);
+    for b in 1..=50 {
+        amm.record_price(b);
+    }
+    amm
+}
+
+/// `n` vaults at collateral ratios between 1.6 and 4 at the TWAP of 50.
+fn registry(n: usize, seed: u64, amm: &Amm) -> VaultRegistry {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut registry = VaultRegistry::new(CdpConfig {
+        stability_fee_rate: 0.0,
+        ..CdpConfig::default()
+    });
+    for i in 0..n {
+        let collateral = rng.gen_range(10.0..100.0);
+        let ratio = rng.gen_range(1.6..4.0);
+        let debt = collateral * 50.0 / ratio;
+        registry
+            .open_vault(&format!("v{}", i), collateral, debt, 50, amm)
+            .unwrap();
+    }
+    registry
+}
+
+fn brute_force(registry: &VaultRegistry, price: f64) -> Vec<u64> {
+    let mut ids: Vec<u64> = registry
+        .vaults
+        .values()
+        .filter(|v| v.debt_zai > 0.0 && v.collateral_ratio(price) < registry.config.min_ratio)
+        .map(|v| v.id)
+        .collect();
+    ids.sort();
+    ids
+}
+
+fn assert_matches_full_scan(registry: &VaultRegistry) {
+    for price in PRICES {
+        assert_eq!(
+            registry.liquidatable_at_price(price),
+            brute_force(registry, price),
+            "price {}",
+            price
+        );
+    }
+}
+
+#[test]
+fn test_index_matches_full_scan() {
+    let amm = amm();
+    let registry = registry(500, 7, &amm);
+    assert_matches_full_scan(&registry);
+    assert!(registry.liquidatable_at_price(50.0).is_empty());
+    assert_eq!(registry.liquidatable_at_price(10.0).len(), 500);
+
+    // A vault right at the boundary is not liquidatable
+    let mut registry = VaultRegistry::new(CdpConfig::default());
+    let id = registry.open_vault("edge", 30.0, 1000.0, 50, &amm).unwrap();
+    assert_eq!(registry.vaults[&id].collateral_ratio(50.0), 1.5);
+    assert!(registry.liquidatable_at_price(50.0).is_empty());
+    assert_eq!(registry.liquidatable_at_price(49.999), [id]);
+}
+
+#[test]
+fn test_index_follows_vault_changes() {
+    let amm = amm();
+    let mut registry = registry(200, 11, &amm);
+    let mut rng = StdRng::seed_from_u64(3);
+    for step in 0..400 {
+        let mut ids: Vec<u64> = registry.vaults.keys().copied().collect();
+        ids.sort();
+        let id = ids[rng.gen_range(0..ids.len())];
+        let _ = match step % 5 {
+            0 => registry.deposit_collateral(id, rng.gen_range(1.0..20.0)),
+            1 => registry.withdraw_collateral(id, rng.gen_range(0.1..5.0), 50, &amm),
+            2 => registry.borrow_zai(id, rng.gen_range(100.0..500.0), 50, &amm),
+            3 => registry.repay_zai(id, rng.gen_range(100.0..500.0), 50),
+            _ => registry.close_vault(id, 50).map(|_| ()),
+        };
+        if step % 50 == 0 {
+            assert_matches_full_scan(&registry);
+        }
+    }
+    assert_matches_full_scan(&registry);
+
+    // Stability fees raise every vault's parity price
+    registry.config.stability_fee_rate = 0.5;
+    registry.accrue_all_fees(50 + 200_000);
+    assert_matches_full_scan(&registry);
+}
+
+#[test]
+fn test_direct_changes_need_reindex() {
+    let amm = amm();
+    let mut registry = registry(50, 5, &amm);
+    let id = *registry.vaults.keys().min().unwrap();
+    registry.vaults.get_mut(&id).unwrap().collateral_zec = 1.0;
+    registry.reindex(id);
+    assert!(registry.liquidatable_at_price(50.0).contains(&id));
+    assert_matches_full_scan(&registry);
+
+    // Vaults removed behind the index's back fall back to a full pass
+    let gone = *registry.vaults.keys().max().unwrap();
+    registry.vaults.remove(&gone);
+    assert_matches_full_scan(&registry);
+    registry.reindex(gone);
+    assert_matches_full_scan(&registry);
+}
+
+#[test]
+fn test_reloaded_registry_rebuilds_index() {
+    let amm = amm();
+    let registry = registry(100, 9, &amm);
+    let json = serde_json::to_string(&registry).unwrap();
+    let mut reloaded: VaultRegistry = serde_json::from_str(&json).unwrap();
+    // Full pass until the first change rebuilds the index
+    assert_matches_full_scan(&reloaded);
+    let id = *reloaded.vaults.keys().min().unwrap();
+    reloaded.deposit_collateral(id, 1.0).unwrap();
+    assert_matches_full_scan(&reloaded);
+}
+
+#[test]
+fn test_engine_scans_use_index() {
+    let amm = amm();
+    let registry = registry(300, 13, &amm);
+    let engine = LiquidationEngine::new(LiquidationConfig::default());
+    for price in PRICES {
+        assert_eq!(
+            engine.scan_liquidatable_at_price(&registry, price),
+            brute_force(&registry, price)
+        );
+    }
+    // Nothing is under water at the TWAP the vaults were opened at
+    assert!(engine.scan_liquidatable(&registry, &amm).is_empty());
+}
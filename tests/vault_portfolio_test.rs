@@ -0,0 +1,184 @@
+//! Owners with several vaults.
+//!
+//! The registry answers per-owner queries (vaults, riskiest first, totals)
+//! and merges an owner's vaults; a portfolio owner spreads its debt across
+//! ratios, tops up the riskiest vaults first and merges once out of ZEC.
+
+use std::collections::BTreeMap;
+
+use zai_sim::agents::{AgentAction, VaultPortfolio, VaultPortfolioConfig};
+use zai_sim::amm::Amm;
+use zai_sim::cdp::{CdpConfig, VaultRegistry};
+use zai_sim::error::ZaiSimError;
+use zai_sim::scenario::{Scenario, ScenarioConfig};
+use zai_sim::scenarios::{add_agents, generate_prices, ScenarioId};
+
+fn amm() -> Amm {
+    Amm::new(10_000.0, 500_000.0, 0.003)
+}
+
+#[test]
+fn test_registry_queries_by_owner() {
+    let amm = amm();
+    let mut registry = VaultRegistry::new(CdpConfig::default());
+    // At $50: ratios 3.0, 2.0 and 2.5
+    let a = registry.open_vault("alice", 60.0, 1000.0, 0, &amm).unwrap();
+    let b = registry.open_vault("alice", 40.0, 1000.0, 0, &amm).unwrap();
+    let bob = registry.open_vault("bob", 50.0, 1000.0, 0, &amm).unwrap();
+    let c = registry.open_vault("alice", 50.0, 1000.0, 0, &amm).unwrap();
+
+    assert_eq!(registry.vaults_of("alice"), vec![a, b, c]);
+    assert_eq!(registry.vaults_of("bob"), vec![bob]);
+    assert!(registry.vaults_of("carol").is_empty());
+    assert_eq!(registry.vaults_of_by_ratio("alice", 50.0), vec![b, c, a]);
+    assert_eq!(registry.owner_totals("alice"), (150.0, 3000.0));
+
+    registry.merge_vaults(b, a, 0).unwrap();
+    assert!(registry.get_vault(b).is_none());
+    assert_eq!(registry.get_vault(a).unwrap().collateral_zec, 100.0);
+    assert_eq!(registry.get_vault(a).unwrap().debt_zai, 2000.0);
+    assert_eq!(registry.vaults_of("alice"), vec![a, c]);
+    assert_eq!(registry.owner_totals("alice"), (150.0, 3000.0));
+
+    assert!(matches!(
+        registry.merge_vaults(a, a, 0),
+        Err(ZaiSimError::InvalidInput(_))
+    ));
+    assert!(matches!(
+        registry.merge_vaults(bob, a, 0),
+        Err(ZaiSimError::InvalidInput(_))
+    ));
+    assert!(matches!(
+        registry.merge_vaults(b, a, 0),
+        Err(ZaiSimError::VaultNotFound(_))
+    ));
+}
+
+/// Vaults at 1.8, 1.9 and 3.5 with 1000 ZAI debt each, at $50; acts
+/// below 2.0 and tops up to 2.6.
+fn setup(reserve_zec: f64) -> (VaultPortfolio, VaultRegistry, Amm, Vec<u64>) {
+    let amm = amm();
+    // No fees, so the actions below are exact
+    let mut registry = VaultRegistry::new(CdpConfig {
+        stability_fee_rate: 0.0,
+        ..CdpConfig::default()
+    });
+    let mut portfolio = VaultPortfolio::new(
+        VaultPortfolioConfig {
+            vault_ratios: vec![1.8, 1.9, 3.5],
+            reserve_zec,
+            action_threshold_ratio: 2.0,
+            target_ratio: 2.6,
+            ..VaultPortfolioConfig::default()
+        },
+        "alice",
+    );
+    let ids = portfolio.open_vaults(&mut registry, &amm, 0).unwrap();
+    (portfolio, registry, amm, ids)
+}
+
+#[test]
+fn test_open_vaults_at_configured_ratios() {
+    let (portfolio, registry, _, ids) = setup(100.0);
+    assert!(portfolio.opened);
+    assert_eq!(registry.vaults_of("alice"), ids);
+    for (id, ratio) in ids.iter().zip([1.8, 1.9, 3.5]) {
+        let vault = registry.get_vault(*id).unwrap();
+        assert!((vault.collateral_ratio(50.0) - ratio).abs() < 1e-9);
+        assert_eq!(vault.debt_zai, 1000.0);
+    }
+}
+
+fn collateral(registry: &VaultRegistry, id: u64) -> f64 {
+    registry.get_vault(id).unwrap().collateral_zec
+}
+
+#[test]
+fn test_tops_up_riskiest_first() {
+    // Plenty of ZEC: both at-risk vaults go back to 2.6 (16 + 14 ZEC)
+    let (mut portfolio, mut registry, amm, ids) = setup(100.0);
+    let action = portfolio.act(&mut registry, &amm, 1);
+    assert!(matches!(action, AgentAction::CdpAction { vault_id, .. } if vault_id == ids[0]));
+    assert_eq!(portfolio.topups, 2);
+    assert!((portfolio.reserve_zec - 70.0).abs() < 1e-9);
+    assert!((collateral(&registry, ids[0]) - 52.0).abs() < 1e-9);
+    assert!((collateral(&registry, ids[1]) - 52.0).abs() < 1e-9);
+
+    // 10 ZEC only reaches the riskiest
+    let (mut portfolio, mut registry, amm, ids) = setup(10.0);
+    portfolio.act(&mut registry, &amm, 1);
+    assert_eq!(portfolio.topups, 1);
+    assert!(portfolio.reserve_zec.abs() < 1e-9);
+    assert!((collateral(&registry, ids[0]) - 46.0).abs() < 1e-9);
+    assert!((collateral(&registry, ids[1]) - 38.0).abs() < 1e-9);
+
+    // Out of ZEC, the vault still at risk folds into the healthiest
+    portfolio.act(&mut registry, &amm, 2);
+    assert_eq!(portfolio.merges, 1);
+    assert!(registry.get_vault(ids[1]).is_none());
+    let merged = registry.get_vault(ids[2]).unwrap();
+    assert!((merged.collateral_zec - 108.0).abs() < 1e-9);
+    assert!(merged.collateral_ratio(50.0) > 2.0);
+    // Nothing left at risk
+    assert!(matches!(
+        portfolio.act(&mut registry, &amm, 3),
+        AgentAction::None
+    ));
+}
+
+#[test]
+fn test_no_merge_without_consolidation() {
+    let (mut portfolio, mut registry, amm, ids) = setup(0.0);
+    portfolio.config.consolidate = false;
+    let action = portfolio.act(&mut registry, &amm, 1);
+    assert!(matches!(action, AgentAction::CdpAction { .. }));
+    assert_eq!(portfolio.merges, 0);
+    assert_eq!(registry.vaults_of("alice"), ids);
+}
+
+/// First liquidation block of each of `owner`'s vaults.
+fn liquidation_blocks(scenario: &Scenario, owner: &str) -> BTreeMap<u64, u64> {
+    let mut blocks = BTreeMap::new();
+    for result in scenario
+        .liquidation_engine
+        .history
+        .iter()
+        .filter(|r| r.owner == owner)
+    {
+        blocks.entry(result.vault_id).or_insert(result.block);
+    }
+    blocks
+}
+
+#[test]
+fn test_diversified_owner_is_liquidated_in_stages() {
+    let mut scenario = Scenario::new_with_seed(&ScenarioConfig::default(), 42);
+    add_agents(ScenarioId::SustainedBear, &mut scenario);
+    let undefended = |vault_ratios| VaultPortfolioConfig {
+        vault_ratios,
+        reserve_zec: 0.0,
+        consolidate: false,
+        ..VaultPortfolioConfig::default()
+    };
+    // Arbitrageurs run short of capital and the AMM bottoms out near $34,
+    // so vaults opened at $50 above about 2.25 are never reached
+    scenario
+        .vault_portfolios
+        .push(VaultPortfolio::new(undefended(vec![2.0]), "concentrated"));
+    scenario.vault_portfolios.push(VaultPortfolio::new(
+        undefended(vec![1.7, 2.0, 3.5]),
+        "diversified",
+    ));
+    scenario.run(&generate_prices(ScenarioId::SustainedBear, 1000, 42));
+
+    let concentrated = liquidation_blocks(&scenario, "concentrated");
+    let diversified = liquidation_blocks(&scenario, "diversified");
+    assert_eq!(concentrated.len(), 1);
+    // The spread position loses its riskiest vault first, ahead of the
+    // concentrated one, and the rest later or not at all
+    let mut stages: Vec<u64> = diversified.values().copied().collect();
+    stages.sort_unstable();
+    stages.dedup();
+    assert!(stages.len() >= 2);
+    assert!(stages[0] < concentrated.values().copied().min().unwrap());
+}
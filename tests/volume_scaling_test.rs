@@ -0,0 +1,105 @@
+//! Agent throughput scaled by historical candle volume.
+//!
+//! Each block's volume relative to the series median multiplies miner AMM
+//! sales, demand buying and arber capital replenishment, so thin periods
+//! in the data see less agent flow.
+
+use approx::assert_relative_eq;
+use zai_sim::agents::*;
+use zai_sim::amm::Amm;
+use zai_sim::scenario::{Scenario, ScenarioConfig};
+use zai_sim::volume::{VolumeProfile, VolumeScalingConfig};
+
+#[test]
+fn test_profile_relative_to_median() {
+    let profile = VolumeProfile::new(&[100.0, 200.0, 50.0, 0.0, 5000.0], &Default::default());
+    // Median of the positive volumes is 200 (upper median of four)
+    assert_eq!(profile.factor(1), 0.5);
+    assert_eq!(profile.factor(2), 1.0);
+    assert_eq!(profile.factor(3), 0.25);
+    // Empty candle floored, spike capped
+    assert_eq!(profile.factor(4), 0.1);
+    assert_eq!(profile.factor(5), 5.0);
+    // No volume data beyond the series or before block 1
+    assert_eq!(profile.factor(6), 1.0);
+    assert_eq!(profile.factor(0), 1.0);
+
+    let damped = VolumeProfile::new(
+        &[50.0, 200.0, 800.0],
+        &VolumeScalingConfig {
+            exponent: 0.5,
+            ..VolumeScalingConfig::default()
+        },
+    );
+    assert_eq!(damped.factors, vec![0.5, 1.0, 2.0]);
+
+    let flat = VolumeProfile::new(&[0.0, 0.0], &Default::default());
+    assert_eq!(flat.factors, vec![1.0, 1.0]);
+}
+
+#[test]
+fn test_agents_apply_volume_scale() {
+    let mut amm = Amm::new(10000.0, 500000.0, 0.003);
+    for b in 1..=50 {
+        amm.record_price(b);
+    }
+    let mut miner = MinerAgent::new(MinerAgentConfig {
+        block_reward: 1.25,
+        miner_sell_fraction: 0.5,
+        miner_amm_fraction: 0.3,
+        sell_immediately: true,
+        ..MinerAgentConfig::default()
+    });
+    miner.volume_scale = Some(0.5);
+    match miner.act(&mut amm, 51) {
+        AgentAction::MinerSell { zec_sold, .. } => assert_relative_eq!(zec_sold, 0.09375),
+        other => panic!("expected a miner sale, got {:?}", other),
+    }
+
+    let mut demand = DemandAgent::new(DemandAgentConfig::default());
+    demand.volume_scale = Some(2.0);
+    let par = amm.spot_price();
+    match demand.act(&mut amm, par, 52) {
+        AgentAction::BuyZai { zec_spent, .. } => {
+            assert_relative_eq!(
+                zec_spent,
+                2.0 * DemandAgentConfig::default().demand_base_rate
+            )
+        }
+        other => panic!("expected demand buying, got {:?}", other),
+    }
+
+    let mut arber = Arbitrageur::new(ArbitrageurConfig {
+        capital_replenish_rate: 100.0,
+        ..ArbitrageurConfig::default()
+    });
+    assert_eq!(arber.replenish_rate(), 100.0);
+    arber.volume_scale = Some(0.25);
+    assert_eq!(arber.replenish_rate(), 25.0);
+}
+
+fn run(volumes: Option<&[f64]>) -> Scenario {
+    let mut scenario = Scenario::new_with_seed(&ScenarioConfig::default(), 42);
+    scenario.miners.push(MinerAgent::new(MinerAgentConfig {
+        sell_immediately: true,
+        ..MinerAgentConfig::default()
+    }));
+    if let Some(volumes) = volumes {
+        scenario.scale_by_volume(volumes, &VolumeScalingConfig::default());
+    }
+    scenario.run(&[50.0; 200]);
+    scenario
+}
+
+#[test]
+fn test_thin_market_slows_miner_selling() {
+    let baseline = run(None);
+    assert_eq!(baseline.miners[0].volume_scale, None);
+
+    // Busy for the first half, empty for the second
+    let volumes: Vec<f64> = (0..200).map(|i| if i < 100 { 10.0 } else { 0.0 }).collect();
+    let thin = run(Some(&volumes));
+    assert_eq!(thin.miners[0].volume_scale, Some(0.1));
+    assert!(thin.miners[0].zai_balance < baseline.miners[0].zai_balance * 0.6);
+    assert!(thin.miners[0].zec_balance > baseline.miners[0].zec_balance);
+}